@@ -1,29 +1,25 @@
 extern crate rome;
 use rome::graph::GraphWriter;
 use rome::graphs::tel;
-use rome::io::TurtleParser;
+use rome::io::TurtleReader;
 use rome::io::write_pretty_turtle;
 use rome::namespaces::Namespaces;
 use std::env::args;
 use std::fs;
 use std::io;
-use std::io::Read;
 
-fn read_file(path: &str) -> io::Result<String> {
-    let mut f = fs::File::open(path)?;
-    let mut s = String::new();
-    f.read_to_string(&mut s)?;
-    Ok(s)
+fn open_file(path: &str) -> io::Result<io::BufReader<fs::File>> {
+    Ok(io::BufReader::new(fs::File::open(path)?))
 }
 
 fn load_file(input: &str) -> rome::Result<(Namespaces, tel::Graph128)> {
     let mut writer = tel::GraphCreator::with_capacity(65000);
     let mut prefixes = Namespaces::new();
-    let data = read_file(input)?;
+    let data = open_file(input)?;
     let mut base = String::from("file:");
     base.push_str(input);
     {
-        let mut triples = TurtleParser::new(data.as_str(), &base, &mut writer)?;
+        let mut triples = TurtleReader::new(data, &base, &mut writer)?;
         while let Some(step) = triples.next() {
             step?;
         }
@@ -5,7 +5,7 @@ extern crate rome;
 use rome::graph::{Graph, GraphWriter, Triple, ResourceTranslator, IRIPtr, LiteralPtr,
                   WriterResource};
 use rome::graphs::tel;
-use rome::io::TurtleParser;
+use rome::io::TurtleReader;
 use rome::iter::TransitiveIterator;
 use rome::namespaces::Namespaces;
 use rome::ontology;
@@ -19,7 +19,7 @@ use std::collections::HashSet;
 use std::env::args;
 use std::fs;
 use std::io;
-use std::io::{Read, Write};
+use std::io::Write;
 use std::path::PathBuf;
 
 type MyGraph = tel::Graph128;
@@ -45,17 +45,14 @@ struct OntoData<'g> {
     prefixes: Namespaces,
 }
 
-fn read_file(path: &str) -> io::Result<String> {
-    let mut f = match fs::File::open(path) {
+fn open_file(path: &str) -> io::Result<io::BufReader<fs::File>> {
+    match fs::File::open(path) {
         Err(e) => {
             println_stderr!("Cannot open file {}.", path);
-            return Err(e);
+            Err(e)
         }
-        Ok(f) => f,
-    };
-    let mut s = String::new();
-    f.read_to_string(&mut s)?;
-    Ok(s)
+        Ok(f) => Ok(io::BufReader::new(f)),
+    }
 }
 
 fn camel_case(str: &str) -> String {
@@ -259,10 +256,10 @@ fn load_files(inputs: &[String]) -> rome::Result<(Namespaces, MyGraph)> {
     let mut writer = tel::GraphCreator::with_capacity(65000);
     let mut prefixes = Namespaces::new();
     for input in inputs {
-        let data = read_file(input)?;
+        let data = open_file(input)?;
         let mut base = String::from("file:");
         base.push_str(input);
-        let mut triples = TurtleParser::new(data.as_str(), &base, &mut writer)?;
+        let mut triples = TurtleReader::new(data, &base, &mut writer)?;
         while let Some(step) = triples.next() {
             step?;
         }
@@ -3,7 +3,7 @@ extern crate time;
 use rome::graph;
 use rome::graph::*;
 use rome::graphs::tel;
-use rome::io::{TurtleParser, write_pretty_turtle, write_ntriples};
+use rome::io::{RDFXMLParser, TurtleParser, write_pretty_turtle, write_ntriples};
 use rome::namespaces::Namespaces;
 use std::env::args;
 use std::fs;
@@ -13,8 +13,15 @@ use std::path::Path;
 use std::rc::Rc;
 
 type MyGraph = tel::Graph64;
-type MyBlankNodeOrIRI<'g> =
-    graph::BlankNodeOrIRI<'g, <MyGraph as Graph<'g>>::BlankNodePtr, <MyGraph as Graph<'g>>::IRIPtr>;
+type MyBlankNodeOrIRI<'g> = graph::BlankNodeOrIRI<'g,
+                                                   <MyGraph as Graph<'g>>::BlankNodePtr,
+                                                   <MyGraph as Graph<'g>>::IRIPtr,
+                                                   <MyGraph as Graph<'g>>::QuotedTriplePtr>;
+type MyResource<'g> = graph::Resource<'g,
+                                      <MyGraph as Graph<'g>>::BlankNodePtr,
+                                      <MyGraph as Graph<'g>>::IRIPtr,
+                                      <MyGraph as Graph<'g>>::LiteralPtr,
+                                      <MyGraph as Graph<'g>>::QuotedTriplePtr>;
 
 macro_rules! println_stderr(
     ($($arg:tt)*) => { {
@@ -81,7 +88,14 @@ struct TestTurtleNegativeEval {
     action: String,
 }
 
-/// run all w3 RDF 1.1 Turtle tests from https://www.w3.org/TR/rdf11-testcases/
+/// The syntax of the files that are tested.
+#[derive (Debug,Clone,Copy)]
+enum Syntax {
+    Turtle,
+    RDFXML,
+}
+
+/// run all w3 RDF 1.1 Turtle or RDF/XML tests from https://www.w3.org/TR/rdf11-testcases/
 #[derive (Debug)]
 enum Approval {
     Approved,
@@ -286,28 +300,40 @@ fn load_graph(data: &str, base: &str) -> rome::Result<MyGraph> {
     Ok(writer.collect().sort_blank_nodes())
 }
 
-fn read<'g, T, B: 'g, I: 'g, L: 'g, F, R>(mut last: Option<T>,
-                                          i: &mut Iterator<Item = T>,
-                                          predicate: &str,
-                                          convert: F)
-                                          -> Result<(R, Option<T>), String>
-    where T: graph::Triple<'g, B, I, L>,
-          B: BlankNodePtr<'g>,
-          I: IRIPtr<'g>,
-          L: LiteralPtr<'g>,
-          F: Fn(Resource<'g, B, I, L>) -> Result<R, String>
-{
-    last = last.or_else(|| i.next());
-    while let Some(triple) = last {
-        if triple.predicate().as_str() == predicate {
-            return Ok((convert(triple.object())?, None));
+fn load_rdfxml_graph(data: &str, base: &str) -> rome::Result<MyGraph> {
+    let mut writer = tel::GraphCreator::with_capacity(65000);
+    {
+        let triples = RDFXMLParser::new(data, base, &mut writer)?;
+        for step in triples {
+            step?;
         }
-        last = i.next();
     }
-    Err(format!("Cannot find {}.", predicate))
+    Ok(writer.collect().sort_blank_nodes())
+}
+
+/// Load a test input in the syntax of the test suite.
+fn load_action(data: &str, base: &str, syntax: Syntax) -> rome::Result<MyGraph> {
+    match syntax {
+        Syntax::Turtle => load_graph(data, base),
+        Syntax::RDFXML => load_rdfxml_graph(data, base),
+    }
+}
+
+fn read<'g, F, R>(graph: &'g MyGraph,
+                  subject: &MyBlankNodeOrIRI<'g>,
+                  predicate: &str,
+                  convert: F)
+                  -> Result<R, String>
+    where F: Fn(MyResource<'g>) -> Result<R, String>
+{
+    let predicate = graph.find_iri(predicate).ok_or_else(|| format!("Cannot find {}.", predicate))?;
+    match graph.match_pattern(Some(subject), Some(&predicate), None).next() {
+        Some(triple) => convert(triple.object()),
+        None => Err(format!("Cannot find {}.", predicate.as_str())),
+    }
 }
 
-fn to_string<'g, B, I, L>(object: Resource<'g, B, I, L>) -> Result<String, String>
+fn to_string<'g, B, I, L, Q>(object: Resource<'g, B, I, L, Q>) -> Result<String, String>
     where B: BlankNodePtr<'g>,
           I: IRIPtr<'g>,
           L: LiteralPtr<'g>
@@ -319,7 +345,7 @@ fn to_string<'g, B, I, L>(object: Resource<'g, B, I, L>) -> Result<String, Strin
     }
 }
 
-fn to_approval<'g, B, I, L>(object: Resource<'g, B, I, L>) -> Result<Approval, String>
+fn to_approval<'g, B, I, L, Q>(object: Resource<'g, B, I, L, Q>) -> Result<Approval, String>
     where B: BlankNodePtr<'g>,
           I: IRIPtr<'g>,
           L: LiteralPtr<'g>
@@ -335,12 +361,11 @@ fn to_approval<'g, B, I, L>(object: Resource<'g, B, I, L>) -> Result<Approval, S
 fn load_test_turtle_eval(graph: &MyGraph,
                          subject: MyBlankNodeOrIRI)
                          -> Result<TestTurtleEval, String> {
-    let mut i = graph.iter_s(&subject);
-    let (comment, prev) = read(None, &mut i, RDFS_COMMENT, to_string)?;
-    let (action, prev) = read(prev, &mut i, MF_ACTION, to_string)?;
-    let (name, prev) = read(prev, &mut i, MF_NAME, to_string)?;
-    let (result, prev) = read(prev, &mut i, MF_RESULT, to_string)?;
-    let (approval, _) = read(prev, &mut i, RDFT_APPROVAL, to_approval)?;
+    let comment = read(graph, &subject, RDFS_COMMENT, to_string)?;
+    let action = read(graph, &subject, MF_ACTION, to_string)?;
+    let name = read(graph, &subject, MF_NAME, to_string)?;
+    let result = read(graph, &subject, MF_RESULT, to_string)?;
+    let approval = read(graph, &subject, RDFT_APPROVAL, to_approval)?;
     Ok(TestTurtleEval {
         id: subject_to_string(&subject),
         name: name,
@@ -353,11 +378,10 @@ fn load_test_turtle_eval(graph: &MyGraph,
 fn load_positive_syntax(graph: &MyGraph,
                         subject: MyBlankNodeOrIRI)
                         -> Result<TestTurtlePositiveSyntax, String> {
-    let mut i = graph.iter_s(&subject);
-    let (comment, prev) = read(None, &mut i, RDFS_COMMENT, to_string)?;
-    let (action, prev) = read(prev, &mut i, MF_ACTION, to_string)?;
-    let (name, prev) = read(prev, &mut i, MF_NAME, to_string)?;
-    let (approval, _) = read(prev, &mut i, RDFT_APPROVAL, to_approval)?;
+    let comment = read(graph, &subject, RDFS_COMMENT, to_string)?;
+    let action = read(graph, &subject, MF_ACTION, to_string)?;
+    let name = read(graph, &subject, MF_NAME, to_string)?;
+    let approval = read(graph, &subject, RDFT_APPROVAL, to_approval)?;
     Ok(TestTurtlePositiveSyntax {
         id: subject_to_string(&subject),
         name: name,
@@ -369,11 +393,10 @@ fn load_positive_syntax(graph: &MyGraph,
 fn load_negative_syntax(graph: &MyGraph,
                         subject: MyBlankNodeOrIRI)
                         -> Result<TestTurtleNegativeSyntax, String> {
-    let mut i = graph.iter_s(&subject);
-    let (comment, prev) = read(None, &mut i, RDFS_COMMENT, to_string)?;
-    let (action, prev) = read(prev, &mut i, MF_ACTION, to_string)?;
-    let (name, prev) = read(prev, &mut i, MF_NAME, to_string)?;
-    let (approval, _) = read(prev, &mut i, RDFT_APPROVAL, to_approval)?;
+    let comment = read(graph, &subject, RDFS_COMMENT, to_string)?;
+    let action = read(graph, &subject, MF_ACTION, to_string)?;
+    let name = read(graph, &subject, MF_NAME, to_string)?;
+    let approval = read(graph, &subject, RDFT_APPROVAL, to_approval)?;
     Ok(TestTurtleNegativeSyntax {
         id: subject_to_string(&subject),
         name: name,
@@ -385,11 +408,10 @@ fn load_negative_syntax(graph: &MyGraph,
 fn load_negative_eval(graph: &MyGraph,
                       subject: MyBlankNodeOrIRI)
                       -> Result<TestTurtleNegativeEval, String> {
-    let mut i = graph.iter_s(&subject);
-    let (comment, prev) = read(None, &mut i, RDFS_COMMENT, to_string)?;
-    let (action, prev) = read(prev, &mut i, MF_ACTION, to_string)?;
-    let (name, prev) = read(prev, &mut i, MF_NAME, to_string)?;
-    let (approval, _) = read(prev, &mut i, RDFT_APPROVAL, to_approval)?;
+    let comment = read(graph, &subject, RDFS_COMMENT, to_string)?;
+    let action = read(graph, &subject, MF_ACTION, to_string)?;
+    let name = read(graph, &subject, MF_NAME, to_string)?;
+    let approval = read(graph, &subject, RDFT_APPROVAL, to_approval)?;
     Ok(TestTurtleNegativeEval {
         id: subject_to_string(&subject),
         name: name,
@@ -422,7 +444,7 @@ fn run_tests(graph: &MyGraph, base: &str, base_dir: &str) -> rome::Result<Vec<As
     for t in
         graph.iter_object_iri_predicate("http://www.w3.org/ns/rdftest#TestTurtleEval", RDF_TYPE) {
         let test = load_test_turtle_eval(graph, t.subject())?;
-        let r = run_eval(&test, base, base_dir)?;
+        let r = run_eval(&test, base, base_dir, Syntax::Turtle)?;
         eval_result(&r);
         assertions.push(r);
     }
@@ -438,7 +460,7 @@ fn run_tests(graph: &MyGraph, base: &str, base_dir: &str) -> rome::Result<Vec<As
         graph.iter_object_iri_predicate("http://www.w3.org/ns/rdftest#TestTurtleNegativeSyntax",
                                         RDF_TYPE) {
         let test = load_negative_syntax(graph, t.subject())?;
-        let r = run_eval_negative_syntax(&test, base, base_dir)?;
+        let r = run_eval_negative_syntax(&test, base, base_dir, Syntax::Turtle)?;
         eval_result(&r);
         assertions.push(r);
     }
@@ -449,6 +471,21 @@ fn run_tests(graph: &MyGraph, base: &str, base_dir: &str) -> rome::Result<Vec<As
         eval_result(&r);
         assertions.push(r);
     }
+    for t in graph.iter_object_iri_predicate("http://www.w3.org/ns/rdftest#TestXMLEval",
+                                   RDF_TYPE) {
+        let test = load_test_turtle_eval(graph, t.subject())?;
+        let r = run_eval(&test, base, base_dir, Syntax::RDFXML)?;
+        eval_result(&r);
+        assertions.push(r);
+    }
+    for t in
+        graph.iter_object_iri_predicate("http://www.w3.org/ns/rdftest#TestXMLNegativeSyntax",
+                                        RDF_TYPE) {
+        let test = load_negative_syntax(graph, t.subject())?;
+        let r = run_eval_negative_syntax(&test, base, base_dir, Syntax::RDFXML)?;
+        eval_result(&r);
+        assertions.push(r);
+    }
     Ok(assertions)
 }
 
@@ -484,7 +521,11 @@ fn pass(test: &Rc<String>, input_file: &str) -> rome::Result<Assertion> {
     })
 }
 
-fn run_eval(test: &TestTurtleEval, base: &str, base_dir: &str) -> rome::Result<Assertion> {
+fn run_eval(test: &TestTurtleEval,
+            base: &str,
+            base_dir: &str,
+            syntax: Syntax)
+            -> rome::Result<Assertion> {
     let ttl_path = change_base(test.action.as_str(), base, base_dir);
     let nt_path = change_base(test.result.as_str(), base, base_dir);
     let ttl = read_file(&ttl_path)?;
@@ -504,7 +545,7 @@ fn run_eval(test: &TestTurtleEval, base: &str, base_dir: &str) -> rome::Result<A
             });
         }
     };
-    let ttl_graph = match load_graph(ttl.as_str(), test.action.as_str()) {
+    let ttl_graph = match load_action(ttl.as_str(), test.action.as_str(), syntax) {
         Ok(graph) => graph,
         Err(err) => {
             return fail(&test.id,
@@ -549,11 +590,12 @@ fn run_eval_positive_syntax(test: &TestTurtlePositiveSyntax,
 }
 fn run_eval_negative_syntax(test: &TestTurtleNegativeSyntax,
                             base: &str,
-                            base_dir: &str)
+                            base_dir: &str,
+                            syntax: Syntax)
                             -> rome::Result<Assertion> {
     let ttl_path = change_base(test.action.as_str(), base, base_dir);
     let ttl = read_file(&ttl_path)?;
-    if let Ok(graph) = load_graph(ttl.as_str(), test.action.as_str()) {
+    if let Ok(graph) = load_action(ttl.as_str(), test.action.as_str(), syntax) {
         return fail(&test.id,
                     &ttl_path,
                     format!("no error parsing the graph, {} triples.", graph.len()));
@@ -574,7 +616,7 @@ fn run_eval_negative_eval(test: &TestTurtleNegativeEval,
     pass(&test.id, &ttl_path)
 }
 
-fn output_as_turtle(assertions: &[Assertion]) -> rome::Result<()> {
+fn output_as_turtle(assertions: &[Assertion], base: &str) -> rome::Result<()> {
     let mut writer = tel::GraphCreator::with_capacity(100000);
     let mut cache = Cache::new();
     for a in assertions {
@@ -585,7 +627,7 @@ fn output_as_turtle(assertions: &[Assertion]) -> rome::Result<()> {
     ns.set(b"dc", "http://purl.org/dc/elements/1.1/");
     ns.set(b"earl", "http://www.w3.org/ns/earl#");
     ns.set(b"xsd", "http://www.w3.org/2001/XMLSchema#");
-    ns.set(b"test", "http://www.w3.org/2013/TurtleTests/manifest.ttl#");
+    ns.set(b"test", &format!("{}#", base));
     write_pretty_turtle(&ns, &graph, &mut ::std::io::stdout())?;
     Ok(())
 }
@@ -597,11 +639,15 @@ fn run(manifest_path: &str, output_turtle: bool) -> rome::Result<()> {
     dir.push('/');
 
     let manifest = read_file(path.to_str().unwrap())?;
-    let base = "http://www.w3.org/2013/TurtleTests/manifest.ttl";
+    let base = if manifest.contains("rdft:TestXML") {
+        "http://www.w3.org/2013/RDFXMLTests/manifest.ttl"
+    } else {
+        "http://www.w3.org/2013/TurtleTests/manifest.ttl"
+    };
     let graph = load_graph(manifest.as_str(), base)?;
     let assertions = run_tests(&graph, base, dir.as_str())?;
     if output_turtle {
-        output_as_turtle(&assertions)?;
+        output_as_turtle(&assertions, base)?;
     }
     Ok(())
 }
@@ -4,8 +4,33 @@ pub const XSD_DECIMAL: &'static str = "http://www.w3.org/2001/XMLSchema#decimal"
 pub const XSD_DOUBLE: &'static str = "http://www.w3.org/2001/XMLSchema#double";
 pub const XSD_INTEGER: &'static str = "http://www.w3.org/2001/XMLSchema#integer";
 pub const XSD_STRING: &'static str = "http://www.w3.org/2001/XMLSchema#string";
+pub const XSD_FLOAT: &'static str = "http://www.w3.org/2001/XMLSchema#float";
+pub const XSD_DATE_TIME: &'static str = "http://www.w3.org/2001/XMLSchema#dateTime";
+pub const XSD_DAY_TIME_DURATION: &'static str = "http://www.w3.org/2001/XMLSchema#dayTimeDuration";
 pub const RDF_LANG_STRING: &'static str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#langString";
 pub const RDF_FIRST: &'static str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#first";
 pub const RDF_NIL: &'static str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#nil";
 pub const RDF_REST: &'static str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#rest";
 pub const RDF_TYPE: &'static str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#type";
+pub const RDF_XML_LITERAL: &'static str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#XMLLiteral";
+pub const RDF_STATEMENT: &'static str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#Statement";
+pub const RDF_SUBJECT: &'static str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#subject";
+pub const RDF_PREDICATE: &'static str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#predicate";
+pub const RDF_OBJECT: &'static str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#object";
+pub const RDF_NS: &'static str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#";
+pub const RDFS_NS: &'static str = "http://www.w3.org/2000/01/rdf-schema#";
+pub const XSD_NS: &'static str = "http://www.w3.org/2001/XMLSchema#";
+pub const OWL_NS: &'static str = "http://www.w3.org/2002/07/owl#";
+pub const RDF_LIST: &'static str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#List";
+pub const RDF_PROPERTY: &'static str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#Property";
+pub const RDFS_CLASS: &'static str = "http://www.w3.org/2000/01/rdf-schema#Class";
+pub const RDFS_CONTAINER_MEMBERSHIP_PROPERTY: &'static str =
+    "http://www.w3.org/2000/01/rdf-schema#ContainerMembershipProperty";
+pub const RDFS_DATATYPE: &'static str = "http://www.w3.org/2000/01/rdf-schema#Datatype";
+pub const RDFS_DOMAIN: &'static str = "http://www.w3.org/2000/01/rdf-schema#domain";
+pub const RDFS_LITERAL: &'static str = "http://www.w3.org/2000/01/rdf-schema#Literal";
+pub const RDFS_MEMBER: &'static str = "http://www.w3.org/2000/01/rdf-schema#member";
+pub const RDFS_RANGE: &'static str = "http://www.w3.org/2000/01/rdf-schema#range";
+pub const RDFS_RESOURCE: &'static str = "http://www.w3.org/2000/01/rdf-schema#Resource";
+pub const RDFS_SUB_CLASS_OF: &'static str = "http://www.w3.org/2000/01/rdf-schema#subClassOf";
+pub const RDFS_SUB_PROPERTY_OF: &'static str = "http://www.w3.org/2000/01/rdf-schema#subPropertyOf";
@@ -0,0 +1,64 @@
+//! Table-based CRC-32 checksums.
+
+/// The lookup table for a CRC-32 with the reflected `polynomial`.
+const fn table(polynomial: u32) -> [u32; 256] {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut c = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            c = if c & 1 == 1 { polynomial ^ (c >> 1) } else { c >> 1 };
+            bit += 1;
+        }
+        table[i] = c;
+        i += 1;
+    }
+    table
+}
+
+static IEEE: [u32; 256] = table(0xedb8_8320);
+static CASTAGNOLI: [u32; 256] = table(0x82f6_3b78);
+
+/// A CRC-32 that is updated with the bytes as they are written or read.
+pub struct Crc32 {
+    table: &'static [u32; 256],
+    value: u32,
+}
+
+impl Crc32 {
+    /// CRC-32 as used in zlib and PNG.
+    pub fn ieee() -> Crc32 {
+        Crc32 {
+            table: &IEEE,
+            value: 0xffff_ffff,
+        }
+    }
+    /// CRC-32C (Castagnoli) as used in HDT.
+    pub fn castagnoli() -> Crc32 {
+        Crc32 {
+            table: &CASTAGNOLI,
+            value: 0xffff_ffff,
+        }
+    }
+    pub fn update(&mut self, bytes: &[u8]) {
+        for b in bytes {
+            self.value = self.table[((self.value ^ *b as u32) & 0xff) as usize] ^
+                         (self.value >> 8);
+        }
+    }
+    pub fn checksum(&self) -> u32 {
+        self.value ^ 0xffff_ffff
+    }
+}
+
+#[test]
+fn test_crc32() {
+    let mut crc = Crc32::ieee();
+    crc.update(b"1234");
+    crc.update(b"56789");
+    assert_eq!(crc.checksum(), 0xcbf4_3926);
+    let mut crc = Crc32::castagnoli();
+    crc.update(b"123456789");
+    assert_eq!(crc.checksum(), 0xe306_9283);
+}
@@ -20,6 +20,70 @@ pub enum Error {
     Custom(&'static str),
     /// A custom error from `String`.
     String(String),
+    /// The input is not valid for the syntax that was being parsed.
+    Syntax(SyntaxError),
+}
+
+/// The kind of statement that was being parsed when a syntax error occurred.
+#[derive (Debug,Clone,Copy,PartialEq,Eq)]
+pub enum StatementKind {
+    /// A prefix declaration, `@prefix` or `PREFIX`.
+    Prefix,
+    /// A base declaration, `@base` or `BASE`.
+    Base,
+    /// One or more triples.
+    Triples,
+    /// A TriG graph block, `GRAPH <g> { ... }` or `<g> { ... }`.
+    Graph,
+    /// An element in an RDF/XML document.
+    Element,
+    /// A value in a JSON-LD document.
+    Value,
+    /// A SPARQL query.
+    Query,
+}
+
+/// The position and context of a syntax error.
+#[derive (Debug,Clone)]
+pub struct SyntaxError {
+    /// The line of the error. The first line is 1.
+    pub line: usize,
+    /// The column of the error in characters. The first column is 1.
+    pub column: usize,
+    /// The offset of the error in bytes from the start of the input.
+    pub offset: usize,
+    /// The kind of statement that was being parsed.
+    pub statement: StatementKind,
+    /// A short piece of the input, starting at the error.
+    pub excerpt: String,
+    /// A description of the error.
+    pub message: String,
+}
+
+impl fmt::Display for StatementKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match *self {
+            StatementKind::Prefix => "prefix",
+            StatementKind::Base => "base",
+            StatementKind::Triples => "triples",
+            StatementKind::Graph => "graph",
+            StatementKind::Element => "element",
+            StatementKind::Value => "value",
+            StatementKind::Query => "query",
+        })
+    }
+}
+
+impl fmt::Display for SyntaxError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f,
+               "line {}, column {}: {} in {} statement near '{}'",
+               self.line,
+               self.column,
+               self.message,
+               self.statement,
+               self.excerpt)
+    }
 }
 
 impl fmt::Display for Error {
@@ -29,6 +93,7 @@ impl fmt::Display for Error {
             Error::NomError(ref e) => e.fmt(f),
             Error::Custom(s) => f.write_str(s),
             Error::String(ref s) => f.write_str(s.as_str()),
+            Error::Syntax(ref e) => e.fmt(f),
         }
     }
 }
@@ -48,6 +113,11 @@ impl From<String> for Error {
         Error::String(error)
     }
 }
+impl From<SyntaxError> for Error {
+    fn from(error: SyntaxError) -> Error {
+        Error::Syntax(error)
+    }
+}
 impl From<string::FromUtf8Error> for Error {
     fn from(_: string::FromUtf8Error) -> Error {
         Error::Custom("Error decoding invalid UTF-8")
@@ -57,6 +57,13 @@
 //! can take any kind of resource so the enum Resource encapsulates `BlankNodePtr`,
 //! `IRIPtr` and `LiteralPtr`.
 //!
+//! With RDF-star, a triple can itself be the subject or object of another
+//! triple. Such a quoted triple is written `<< s p o >>` in Turtle and
+//! N-Triples. Graphs that can contain quoted triples point to them with a
+//! `QuotedTriplePtr`. The enums `BlankNodeOrIRI` and `Resource` have a variant
+//! `QuotedTriple` for them. Graphs that cannot contain quoted triples use the
+//! type `NoQuotedTriple`.
+//!
 //! In this module, graphs are immutable, but an new graph can be created by
 //! extending another graph (TODO).
 //!
@@ -64,6 +71,8 @@
 
 use constants;
 use iter::SortedIterator;
+use namespaces::Namespaces;
+use query::PropertyPath;
 use std::cmp::Ordering;
 use std::marker::PhantomData;
 
@@ -94,7 +103,7 @@ pub trait BlankNodePtr<'g> {
     /// # creator.add_blank_blank(&blank_node, &ok, &blank_node);
     /// # let graph: tel::Graph64 = creator.collect();
     /// ```
-    fn to_blank_node_or_iri<I>(&self) -> BlankNodeOrIRI<'g, Self, I>
+    fn to_blank_node_or_iri<I, T>(&self) -> BlankNodeOrIRI<'g, Self, I, T>
         where Self: Clone,
               I: IRIPtr<'g>
     {
@@ -122,7 +131,7 @@ pub trait BlankNodePtr<'g> {
     /// # let resource_option = graph.iter().next().map(|t|t.object());
     /// # resource_option.or(Some(resource));
     /// ```
-    fn to_resource<I, L>(&self) -> Resource<'g, Self, I, L>
+    fn to_resource<I, L, T>(&self) -> Resource<'g, Self, I, L, T>
         where Self: Clone,
               I: IRIPtr<'g>,
               L: LiteralPtr<'g>
@@ -139,7 +148,7 @@ pub trait IRIPtr<'g> {
     fn as_str(&self) -> &str;
     /// Wrap the IRI in a BlankNodeOrIRI
     /// This is useful when using it as a subject in a triple.
-    fn to_blank_node_or_iri<B>(&self) -> BlankNodeOrIRI<'g, B, Self>
+    fn to_blank_node_or_iri<B, T>(&self) -> BlankNodeOrIRI<'g, B, Self, T>
         where Self: Clone,
               B: BlankNodePtr<'g>
     {
@@ -147,7 +156,7 @@ pub trait IRIPtr<'g> {
     }
     /// Wrap the IRI in a Resource
     /// This is useful when using it as an object in a triple.
-    fn to_resource<B, L>(&self) -> Resource<'g, B, Self, L>
+    fn to_resource<B, L, T>(&self) -> Resource<'g, B, Self, L, T>
         where Self: Clone,
               B: BlankNodePtr<'g>,
               L: LiteralPtr<'g>
@@ -197,7 +206,7 @@ pub trait LiteralPtr<'g> {
     fn language(&self) -> Option<&str>;
     /// Wrap the literal in a Resource.
     /// This is convenient when passing the literal as an object in a triple.
-    fn to_resource<B, I>(&self) -> Resource<'g, B, I, Self>
+    fn to_resource<B, I, T>(&self) -> Resource<'g, B, I, Self, T>
         where Self: Clone,
               B: BlankNodePtr<'g>,
               I: IRIPtr<'g>
@@ -206,9 +215,65 @@ pub trait LiteralPtr<'g> {
     }
 }
 
-/// An enum that contains a blank node or an IRI
+/// A trait for pointers to quoted triples in graphs.
+///
+/// A quoted triple is a triple that is the subject or object of another
+/// triple. It is not asserted by the graph, unless the graph also contains it
+/// as a normal triple. The quoted triples in a quoted triple have the same
+/// type as the quoted triple itself.
+pub trait QuotedTriplePtr<'g, B, I, L>: Triple<'g, B, I, L, Self> + Sized
+    where B: BlankNodePtr<'g>,
+          I: IRIPtr<'g>,
+          L: LiteralPtr<'g>
+{
+    /// Wrap the quoted triple in a BlankNodeOrIRI
+    /// This is useful when using it as a subject in a triple.
+    fn to_blank_node_or_iri(&self) -> BlankNodeOrIRI<'g, B, I, Self>
+        where Self: Clone
+    {
+        BlankNodeOrIRI::QuotedTriple(self.clone())
+    }
+    /// Wrap the quoted triple in a Resource
+    /// This is useful when using it as an object in a triple.
+    fn to_resource(&self) -> Resource<'g, B, I, L, Self>
+        where Self: Clone
+    {
+        Resource::QuotedTriple(self.clone())
+    }
+}
+
+/// The type of quoted triples in graphs that cannot contain quoted triples.
+///
+/// There are no values of this type.
+#[derive(PartialEq,Eq,PartialOrd,Ord,Clone,Copy,Debug,Hash)]
+pub enum NoQuotedTriple {}
+
+impl<'g, B, I, L> Triple<'g, B, I, L, NoQuotedTriple> for NoQuotedTriple
+    where B: BlankNodePtr<'g>,
+          I: IRIPtr<'g>,
+          L: LiteralPtr<'g>
+{
+    fn subject(&self) -> BlankNodeOrIRI<'g, B, I, NoQuotedTriple> {
+        match *self {}
+    }
+    fn predicate(&self) -> I {
+        match *self {}
+    }
+    fn object(&self) -> Resource<'g, B, I, L, NoQuotedTriple> {
+        match *self {}
+    }
+}
+
+impl<'g, B, I, L> QuotedTriplePtr<'g, B, I, L> for NoQuotedTriple
+    where B: BlankNodePtr<'g>,
+          I: IRIPtr<'g>,
+          L: LiteralPtr<'g>
+{
+}
+
+/// An enum that contains a blank node, an IRI or a quoted triple
 #[derive(PartialEq,Eq,PartialOrd,Ord,Clone,Debug)]
-pub enum BlankNodeOrIRI<'g, B: 'g, I: 'g>
+pub enum BlankNodeOrIRI<'g, B: 'g, I: 'g, T: 'g = NoQuotedTriple>
     where B: BlankNodePtr<'g>,
           I: IRIPtr<'g>
 {
@@ -216,10 +281,13 @@ pub enum BlankNodeOrIRI<'g, B: 'g, I: 'g>
     BlankNode(B, PhantomData<&'g u8>),
     /// This is an IRI.
     IRI(I),
+    /// This is a quoted triple.
+    QuotedTriple(T),
 }
-impl<'g, B, I> BlankNodeOrIRI<'g, B, I>
+impl<'g, B, I, T> BlankNodeOrIRI<'g, B, I, T>
     where B: BlankNodePtr<'g> + Clone,
-          I: IRIPtr<'g> + Clone
+          I: IRIPtr<'g> + Clone,
+          T: Clone
 {
     /// Is the BlankNodeOrIRI a blank node?
     pub fn is_blank_node(&self) -> bool {
@@ -242,6 +310,13 @@ impl<'g, B, I> BlankNodeOrIRI<'g, B, I>
             _ => None,
         }
     }
+    /// Is the BlankNodeOrIRI a quoted triple?
+    pub fn is_quoted_triple(&self) -> bool {
+        match *self {
+            BlankNodeOrIRI::QuotedTriple(_) => true,
+            _ => false,
+        }
+    }
     /// Cast BlankNodeOrIRI to an IRI, if applicable
     pub fn as_iri(&self) -> Option<&I> {
         match *self {
@@ -249,22 +324,30 @@ impl<'g, B, I> BlankNodeOrIRI<'g, B, I>
             _ => None,
         }
     }
+    /// Cast BlankNodeOrIRI to a quoted triple, if applicable
+    pub fn as_quoted_triple(&self) -> Option<&T> {
+        match *self {
+            BlankNodeOrIRI::QuotedTriple(ref t) => Some(t),
+            _ => None,
+        }
+    }
     /// Cast BlankNodeOrIRI to a Resource
-    pub fn to_resource<L>(&self) -> Resource<'g, B, I, L>
+    pub fn to_resource<L>(&self) -> Resource<'g, B, I, L, T>
         where Self: Clone,
               L: LiteralPtr<'g>
     {
         match *self {
             BlankNodeOrIRI::BlankNode(ref b, _) => Resource::BlankNode(b.clone(), PhantomData),
             BlankNodeOrIRI::IRI(ref i) => Resource::IRI(i.clone()),
+            BlankNodeOrIRI::QuotedTriple(ref t) => Resource::QuotedTriple(t.clone()),
         }
     }
 }
-/// A Resource is a blank node, an IRI or a literal.
+/// A Resource is a blank node, an IRI, a literal or a quoted triple.
 ///
 /// Resources are used in the object position of a triple.
 #[derive(PartialEq,Eq,PartialOrd,Ord,Clone,Debug)]
-pub enum Resource<'g, B: 'g, I: 'g, L: 'g>
+pub enum Resource<'g, B: 'g, I: 'g, L: 'g, T: 'g = NoQuotedTriple>
     where B: BlankNodePtr<'g>,
           I: IRIPtr<'g>,
           L: LiteralPtr<'g>
@@ -275,8 +358,10 @@ pub enum Resource<'g, B: 'g, I: 'g, L: 'g>
     IRI(I),
     /// This is a literal.
     Literal(L),
+    /// This is a quoted triple.
+    QuotedTriple(T),
 }
-impl<'g, B, I, L> Resource<'g, B, I, L>
+impl<'g, B, I, L, T> Resource<'g, B, I, L, T>
     where B: BlankNodePtr<'g>,
           I: IRIPtr<'g>,
           L: LiteralPtr<'g>
@@ -302,6 +387,13 @@ impl<'g, B, I, L> Resource<'g, B, I, L>
             _ => false,
         }
     }
+    /// Is this a quoted triple?
+    pub fn is_quoted_triple(&self) -> bool {
+        match *self {
+            Resource::QuotedTriple(_) => true,
+            _ => false,
+        }
+    }
     /// Cast Resource to a blank node, if applicable
     pub fn as_blank_node(&self) -> Option<&B> {
         match *self {
@@ -323,35 +415,45 @@ impl<'g, B, I, L> Resource<'g, B, I, L>
             _ => None,
         }
     }
+    /// Cast Resource to a quoted triple, if applicable
+    pub fn as_quoted_triple(&self) -> Option<&T> {
+        match *self {
+            Resource::QuotedTriple(ref t) => Some(t),
+            _ => None,
+        }
+    }
     /// Cast Resource to a BlankNodeOrIRI, if applicable
-    pub fn to_blank_node_or_iri(&self) -> Option<BlankNodeOrIRI<'g, B, I>>
+    pub fn to_blank_node_or_iri(&self) -> Option<BlankNodeOrIRI<'g, B, I, T>>
         where B: Clone,
-              I: Clone
+              I: Clone,
+              T: Clone
     {
         match *self {
             Resource::BlankNode(ref b, _) => {
                 Some(BlankNodeOrIRI::BlankNode(b.clone(), PhantomData))
             }
             Resource::IRI(ref i) => Some(BlankNodeOrIRI::IRI(i.clone())),
-            _ => None,
+            Resource::QuotedTriple(ref t) => Some(BlankNodeOrIRI::QuotedTriple(t.clone())),
+            Resource::Literal(_) => None,
         }
     }
 }
 
 /// Triples are fundamental to RDF.
 ///
-/// Each triple has a subject, a predicate and an object.
-pub trait Triple<'g, B, I, L>
+/// Each triple has a subject, a predicate and an object. The subject and
+/// object can be quoted triples of type `T`.
+pub trait Triple<'g, B, I, L, T = NoQuotedTriple>
     where B: BlankNodePtr<'g>,
           I: IRIPtr<'g>,
           L: LiteralPtr<'g>
 {
     /// Get the subject of this triple.
-    fn subject(&self) -> BlankNodeOrIRI<'g, B, I>;
+    fn subject(&self) -> BlankNodeOrIRI<'g, B, I, T>;
     /// Get the predicate of this triple.
     fn predicate(&self) -> I;
     /// Get the object of this triple.
-    fn object(&self) -> Resource<'g, B, I, L>;
+    fn object(&self) -> Resource<'g, B, I, L, T>;
 }
 
 /// `WriterBlankNodeOrIRI` is like `BlankNodeOrIRI` but for writing graphs.
@@ -362,6 +464,8 @@ pub enum WriterBlankNodeOrIRI<'g, W>
     BlankNode(W::BlankNode, PhantomData<&'g u8>),
     /// This is an IRI.
     IRI(W::IRI),
+    /// This is a quoted triple.
+    QuotedTriple(W::QuotedTriple),
 }
 /// `WriterResource` is like `Resource` but for writing graphs.
 pub enum WriterResource<'g, W>
@@ -373,6 +477,8 @@ pub enum WriterResource<'g, W>
     IRI(W::IRI),
     /// This is a literal.
     Literal(W::Literal),
+    /// This is a quoted triple.
+    QuotedTriple(W::QuotedTriple),
 }
 
 /// translate from one graph to another
@@ -394,7 +500,8 @@ pub trait ResourceTranslator<'g> {
                                    w: &mut Self::GraphWriter,
                                    blank_node_or_iri: &BlankNodeOrIRI<'g,
                                                                       <Self::Graph as Graph<'g>>::BlankNodePtr,
-                                                                      <Self::Graph as Graph<'g>>::IRIPtr>
+                                                                      <Self::Graph as Graph<'g>>::IRIPtr,
+                                                                      <Self::Graph as Graph<'g>>::QuotedTriplePtr>
                                   ) -> WriterBlankNodeOrIRI<'g, Self::GraphWriter>
         where Self: 'g
     {
@@ -403,6 +510,9 @@ pub trait ResourceTranslator<'g> {
                 WriterBlankNodeOrIRI::BlankNode(self.translate_blank_node(w, b), p)
             }
             BlankNodeOrIRI::IRI(ref i) => WriterBlankNodeOrIRI::IRI(w.create_iri(i)),
+            BlankNodeOrIRI::QuotedTriple(ref t) => {
+                WriterBlankNodeOrIRI::QuotedTriple(self.translate_quoted_triple(w, t))
+            }
         }
     }
     /// Translate a Resource from the source graph to the graph writer.
@@ -411,7 +521,8 @@ pub trait ResourceTranslator<'g> {
                           resource: &Resource<'g,
                                               <Self::Graph as Graph<'g>>::BlankNodePtr,
                                               <Self::Graph as Graph<'g>>::IRIPtr,
-                                              <Self::Graph as Graph<'g>>::LiteralPtr>
+                                              <Self::Graph as Graph<'g>>::LiteralPtr,
+                                              <Self::Graph as Graph<'g>>::QuotedTriplePtr>
                          ) -> WriterResource<'g, Self::GraphWriter>
         where Self: 'g
     {
@@ -421,8 +532,23 @@ pub trait ResourceTranslator<'g> {
             }
             Resource::IRI(ref i) => WriterResource::IRI(w.create_iri(i)),
             Resource::Literal(ref l) => WriterResource::Literal(w.create_literal(l)),
+            Resource::QuotedTriple(ref t) => {
+                WriterResource::QuotedTriple(self.translate_quoted_triple(w, t))
+            }
         }
     }
+    /// Translate a quoted triple from the source graph to the graph writer.
+    fn translate_quoted_triple(&mut self,
+                               w: &mut Self::GraphWriter,
+                               triple: &<Self::Graph as Graph<'g>>::QuotedTriplePtr
+                              ) -> <Self::GraphWriter as GraphWriter<'g>>::QuotedTriple
+        where Self: 'g
+    {
+        let subject = self.translate_blank_node_or_iri(w, &triple.subject());
+        let predicate = w.create_iri(&triple.predicate());
+        let object = self.translate_resource(w, &triple.object());
+        w.create_quoted_triple(&subject, &predicate, &object)
+    }
 }
 
 /// Trait for writing into a graph.
@@ -437,6 +563,8 @@ pub trait GraphWriter<'g> {
     type Datatype: Clone;
     /// The language type that is accepted by this writer.
     type Language;
+    /// The quoted triple type that is accepted by this writer.
+    type QuotedTriple: Clone;
     /// The type of the graph into which this writer writes.
     type Graph: Graph<'g>;
 
@@ -454,6 +582,15 @@ pub trait GraphWriter<'g> {
     fn create_literal_datatype(&mut self, value: &str, datatype: &Self::Datatype) -> Self::Literal;
     /// Create a new literal with the given language for the graph.
     fn create_literal_language(&mut self, value: &str, language: &Self::Language) -> Self::Literal;
+    /// Create a new quoted triple for the graph.
+    ///
+    /// Creating a quoted triple does not add the triple to the graph.
+    fn create_quoted_triple(&mut self,
+                            subject: &WriterBlankNodeOrIRI<'g, Self>,
+                            predicate: &Self::IRI,
+                            object: &WriterResource<'g, Self>)
+                            -> Self::QuotedTriple
+        where Self: Sized;
 
     /// Add a new triple to the graph.
     fn add(&mut self,
@@ -462,6 +599,14 @@ pub trait GraphWriter<'g> {
            object: &WriterResource<'g, Self>)
         where Self: Sized
     {
+        match (subject, object) {
+            (&WriterBlankNodeOrIRI::QuotedTriple(_), _) |
+            (_, &WriterResource::QuotedTriple(_)) => {
+                self.add_quoted(subject, predicate, object);
+                return;
+            }
+            _ => {}
+        }
         match *subject {
             WriterBlankNodeOrIRI::BlankNode(ref subject, _) => {
                 match *object {
@@ -474,6 +619,7 @@ pub trait GraphWriter<'g> {
                     WriterResource::Literal(ref object) => {
                         GraphWriter::add_blank_literal(self, subject, predicate, object);
                     }
+                    WriterResource::QuotedTriple(_) => {}
                 }
             }
             WriterBlankNodeOrIRI::IRI(ref subject) => {
@@ -487,8 +633,10 @@ pub trait GraphWriter<'g> {
                     WriterResource::Literal(ref object) => {
                         GraphWriter::add_iri_literal(self, subject, predicate, object);
                     }
+                    WriterResource::QuotedTriple(_) => {}
                 }
             }
+            WriterBlankNodeOrIRI::QuotedTriple(_) => {}
         }
     }
 
@@ -519,10 +667,51 @@ pub trait GraphWriter<'g> {
                        subject: &Self::IRI,
                        predicate: &Self::IRI,
                        object: &Self::Literal);
+    /// Add a new triple with a quoted triple as subject or object to the graph.
+    fn add_quoted(&mut self,
+                  subject: &WriterBlankNodeOrIRI<'g, Self>,
+                  predicate: &Self::IRI,
+                  object: &WriterResource<'g, Self>)
+        where Self: Sized;
     /// Close the GraphWriter and return the resulting graph.
     fn collect(self) -> Self::Graph;
 }
 
+/// Trait for writing into a dataset: a default graph and any number of
+/// named graphs.
+///
+/// The methods of `GraphWriter` add triples to the graph that was selected
+/// last with `set_graph`. Before `set_graph` is called, that is the default
+/// graph. Terms that are created by the writer can be used in every graph.
+pub trait DatasetWriter<'g>: GraphWriter<'g> {
+    /// The type of the dataset into which this writer writes.
+    type Dataset;
+
+    /// Select the graph to which new triples are added.
+    /// `None` selects the default graph.
+    /// An error is returned if the dataset cannot have a graph with this
+    /// name, e.g. a quoted triple.
+    fn set_graph(&mut self,
+                 graph_name: Option<&WriterBlankNodeOrIRI<'g, Self>>)
+                 -> ::error::Result<()>
+        where Self: Sized;
+    /// Add a new triple to the given graph.
+    fn add_quad(&mut self,
+                graph_name: Option<&WriterBlankNodeOrIRI<'g, Self>>,
+                subject: &WriterBlankNodeOrIRI<'g, Self>,
+                predicate: &Self::IRI,
+                object: &WriterResource<'g, Self>)
+                -> ::error::Result<()>
+        where Self: Sized
+    {
+        self.set_graph(graph_name)?;
+        self.add(subject, predicate, object);
+        Ok(())
+    }
+    /// Close the DatasetWriter and return the resulting dataset.
+    fn collect_dataset(self) -> Self::Dataset;
+}
+
 /// An RDF graph.
 ///
 /// An RDF graph contains triples (subject, predicate, object).
@@ -535,16 +724,33 @@ pub trait Graph<'g> {
     type IRIPtr: IRIPtr<'g> + Ord + Clone + 'g;
     /// The literal type of this graph.
     type LiteralPtr: LiteralPtr<'g> + Ord + Clone;
+    /// The quoted triple type of this graph.
+    type QuotedTriplePtr: QuotedTriplePtr<'g, Self::BlankNodePtr, Self::IRIPtr, Self::LiteralPtr>
+        + Ord
+        + Clone
+        + 'g;
     /// The implementation of triples that is ordered by subject, predicate, object.
-    type SPOTriple: Triple<'g, Self::BlankNodePtr, Self::IRIPtr, Self::LiteralPtr> + Ord + Clone;
+    type SPOTriple: Triple<'g,
+                           Self::BlankNodePtr,
+                           Self::IRIPtr,
+                           Self::LiteralPtr,
+                           Self::QuotedTriplePtr> + Ord + Clone;
     /// Iterator for iterating over all triples.
     type SPOIter: SortedIterator<Item = Self::SPOTriple>;
     /// Iterator for iterating over a range for triples sorted by subject, predicate, object.
     type SPORangeIter: SortedIterator<Item = Self::SPOTriple>;
     /// The implementation of triples that is ordered by object, predicate, subject.
-    type OPSTriple: Triple<'g, Self::BlankNodePtr, Self::IRIPtr, Self::LiteralPtr> + Ord + Clone;
+    type OPSTriple: Triple<'g,
+                           Self::BlankNodePtr,
+                           Self::IRIPtr,
+                           Self::LiteralPtr,
+                           Self::QuotedTriplePtr> + Ord + Clone;
     /// Iterator for iterating over a range for triples sorted by object, predicate, subject.
     type OPSRangeIter: SortedIterator<Item = Self::OPSTriple>;
+    /// Iterator for iterating over the triples with one predicate.
+    /// Because the predicate is the same, the triples are sorted by subject,
+    /// predicate, object.
+    type PSORangeIter: SortedIterator<Item = Self::SPOTriple>;
     /// Iterate over all triples sorted by subject, predicate, object.
     fn iter(&'g self) -> Self::SPOIter;
     /// Find the DatatypePtr for the given datatype.
@@ -559,27 +765,391 @@ pub trait Graph<'g> {
                         -> Option<Self::LiteralPtr>;
     /// Iterate over the triples that have the given subject.
     fn iter_s(&'g self,
-              subject: &BlankNodeOrIRI<'g, Self::BlankNodePtr, Self::IRIPtr>)
+              subject: &BlankNodeOrIRI<'g,
+                                       Self::BlankNodePtr,
+                                       Self::IRIPtr,
+                                       Self::QuotedTriplePtr>)
               -> Self::SPORangeIter;
     /// Iterate over the triples that have the given subject and predicate.
     fn iter_s_p(&'g self,
-                subject: &BlankNodeOrIRI<'g, Self::BlankNodePtr, Self::IRIPtr>,
+                subject: &BlankNodeOrIRI<'g,
+                                         Self::BlankNodePtr,
+                                         Self::IRIPtr,
+                                         Self::QuotedTriplePtr>,
                 predicate: &Self::IRIPtr)
                 -> Self::SPORangeIter;
     /// Iterate over the triples that have the given object.
     fn iter_o(&'g self,
-              object: &Resource<'g, Self::BlankNodePtr, Self::IRIPtr, Self::LiteralPtr>)
+              object: &Resource<'g,
+                                Self::BlankNodePtr,
+                                Self::IRIPtr,
+                                Self::LiteralPtr,
+                                Self::QuotedTriplePtr>)
               -> Self::OPSRangeIter;
     /// Iterate over the triples that have the given object and predicate.
     fn iter_o_p(&'g self,
-                object: &Resource<'g, Self::BlankNodePtr, Self::IRIPtr, Self::LiteralPtr>,
+                object: &Resource<'g,
+                                  Self::BlankNodePtr,
+                                  Self::IRIPtr,
+                                  Self::LiteralPtr,
+                                  Self::QuotedTriplePtr>,
                 predicate: &Self::IRIPtr)
                 -> Self::OPSRangeIter;
+    /// Iterate over the triples that have the given predicate.
+    fn iter_p(&'g self, predicate: &Self::IRIPtr) -> Self::PSORangeIter;
+    /// Iterate over the triples that have the given predicate and object.
+    ///
+    /// This gives the same triples as `iter_o_p`.
+    fn iter_p_o(&'g self,
+                predicate: &Self::IRIPtr,
+                object: &Resource<'g,
+                                  Self::BlankNodePtr,
+                                  Self::IRIPtr,
+                                  Self::LiteralPtr,
+                                  Self::QuotedTriplePtr>)
+                -> Self::OPSRangeIter {
+        self.iter_o_p(object, predicate)
+    }
 
     /// Iterator that returns no results.
     fn empty_spo_range(&'g self) -> Self::SPORangeIter;
     /// Iterator that returns no results.
     fn empty_ops_range(&'g self) -> Self::OPSRangeIter;
+    /// Iterator that returns no results.
+    fn empty_pso_range(&'g self) -> Self::PSORangeIter;
+
+    /// Iterate over the triples that match a pattern.
+    ///
+    /// Each part of the pattern that is `None` matches anything. The most
+    /// selective index is used for the parts that are given and the
+    /// remaining parts are checked for each triple. The triples come in the
+    /// order of that index, e.g. by object when only the object is given.
+    ///
+    /// ```
+    /// # use rome::graphs::tel;
+    /// # use rome::graph::*;
+    /// #
+    /// let mut creator = tel::GraphCreator::with_capacity(0);
+    /// let s = creator.create_iri(&"http://example.org/s");
+    /// let p = creator.create_iri(&"http://example.org/p");
+    /// let o = creator.create_iri(&"http://example.org/o");
+    /// creator.add_iri_iri(&s, &p, &o);
+    /// creator.add_iri_iri(&o, &p, &s);
+    /// let graph: tel::Graph64 = creator.collect();
+    /// let s = graph.find_iri("http://example.org/s").unwrap();
+    /// let p = graph.find_iri("http://example.org/p").unwrap();
+    /// assert_eq!(graph.match_pattern(None, Some(&p), None).count(), 2);
+    /// let subject = s.to_blank_node_or_iri();
+    /// assert_eq!(graph.match_pattern(Some(&subject), None, None).count(), 1);
+    /// let object = s.to_resource();
+    /// assert_eq!(graph.match_pattern(Some(&subject), None, Some(&object)).count(), 0);
+    /// ```
+    fn match_pattern(&'g self,
+                     subject: Option<&BlankNodeOrIRI<'g,
+                                                     Self::BlankNodePtr,
+                                                     Self::IRIPtr,
+                                                     Self::QuotedTriplePtr>>,
+                     predicate: Option<&Self::IRIPtr>,
+                     object: Option<&Resource<'g,
+                                              Self::BlankNodePtr,
+                                              Self::IRIPtr,
+                                              Self::LiteralPtr,
+                                              Self::QuotedTriplePtr>>)
+                     -> PatternIter<'g, Self>
+        where Self: Sized
+    {
+        let range = match (subject, predicate, object) {
+            (Some(s), Some(p), _) => PatternRange::SPO(self.iter_s_p(s, p)),
+            (None, Some(p), Some(o)) => PatternRange::OPS(self.iter_o_p(o, p)),
+            (Some(s), None, _) => PatternRange::SPO(self.iter_s(s)),
+            (None, None, Some(o)) => PatternRange::OPS(self.iter_o(o)),
+            (None, Some(p), None) => PatternRange::PSO(self.iter_p(p)),
+            (None, None, None) => PatternRange::All(self.iter()),
+        };
+        // only the object is not fixed by the range that is used
+        let object = match subject {
+            Some(_) => object.cloned(),
+            None => None,
+        };
+        PatternIter {
+            range: range,
+            object: object,
+        }
+    }
+
+    /// Find the terms that can be reached from `start` along a property path.
+    ///
+    /// The path is written in SPARQL syntax, e.g. `rdfs:subClassOf*`. It can
+    /// use full IRIs and the prefixes `rdf`, `rdfs`, `xsd` and `owl`. Use
+    /// `query::PropertyPath` for other prefixes or to follow a path from
+    /// more than one term.
+    ///
+    /// ```
+    /// # use rome::graphs::tel;
+    /// # use rome::graph::*;
+    /// #
+    /// let mut creator = tel::GraphCreator::with_capacity(0);
+    /// let sub_class_of = creator.create_iri(&"http://www.w3.org/2000/01/rdf-schema#subClassOf");
+    /// let cat = creator.create_iri(&"http://example.org/Cat");
+    /// let mammal = creator.create_iri(&"http://example.org/Mammal");
+    /// let animal = creator.create_iri(&"http://example.org/Animal");
+    /// creator.add_iri_iri(&cat, &sub_class_of, &mammal);
+    /// creator.add_iri_iri(&mammal, &sub_class_of, &animal);
+    /// let graph: tel::Graph64 = creator.collect();
+    /// let cat = graph.find_iri("http://example.org/Cat").unwrap().to_resource();
+    /// let classes = graph.path(&cat, "rdfs:subClassOf*").unwrap();
+    /// assert_eq!(classes.len(), 3);
+    /// ```
+    fn path(&'g self,
+            start: &Resource<'g,
+                             Self::BlankNodePtr,
+                             Self::IRIPtr,
+                             Self::LiteralPtr,
+                             Self::QuotedTriplePtr>,
+            path: &str)
+            -> ::error::Result<Vec<Resource<'g,
+                                            Self::BlankNodePtr,
+                                            Self::IRIPtr,
+                                            Self::LiteralPtr,
+                                            Self::QuotedTriplePtr>>>
+        where Self: Sized
+    {
+        let mut namespaces = Namespaces::new();
+        namespaces.set(b"rdf", constants::RDF_NS);
+        namespaces.set(b"rdfs", constants::RDFS_NS);
+        namespaces.set(b"xsd", constants::XSD_NS);
+        namespaces.set(b"owl", constants::OWL_NS);
+        Ok(PropertyPath::parse(path, &namespaces)?.objects(self, start))
+    }
+}
+
+/// A triple that is returned by `Graph::match_pattern`.
+///
+/// The triple comes from the index by subject or from the index by object.
+#[derive(PartialEq,Eq,PartialOrd,Ord,Clone,Debug)]
+pub enum PatternTriple<SPO, OPS> {
+    /// A triple from the index by subject.
+    SPO(SPO),
+    /// A triple from the index by object.
+    OPS(OPS),
+}
+
+impl<'g, B, I, L, T, SPO, OPS> Triple<'g, B, I, L, T> for PatternTriple<SPO, OPS>
+    where B: BlankNodePtr<'g>,
+          I: IRIPtr<'g>,
+          L: LiteralPtr<'g>,
+          SPO: Triple<'g, B, I, L, T>,
+          OPS: Triple<'g, B, I, L, T>
+{
+    fn subject(&self) -> BlankNodeOrIRI<'g, B, I, T> {
+        match *self {
+            PatternTriple::SPO(ref t) => t.subject(),
+            PatternTriple::OPS(ref t) => t.subject(),
+        }
+    }
+    fn predicate(&self) -> I {
+        match *self {
+            PatternTriple::SPO(ref t) => t.predicate(),
+            PatternTriple::OPS(ref t) => t.predicate(),
+        }
+    }
+    fn object(&self) -> Resource<'g, B, I, L, T> {
+        match *self {
+            PatternTriple::SPO(ref t) => t.object(),
+            PatternTriple::OPS(ref t) => t.object(),
+        }
+    }
+}
+
+enum PatternRange<'g, G>
+    where G: Graph<'g>
+{
+    All(G::SPOIter),
+    SPO(G::SPORangeIter),
+    OPS(G::OPSRangeIter),
+    PSO(G::PSORangeIter),
+}
+
+/// Iterator over the triples that match a pattern.
+///
+/// This iterator is returned by `Graph::match_pattern`.
+/// The `size_hint` is the size hint of the range in the index that is used,
+/// which makes it a cheap estimate of the number of matches.
+/// The order of the triples depends on that index, so it is not a
+/// `SortedIterator`.
+pub struct PatternIter<'g, G>
+    where G: Graph<'g>
+{
+    range: PatternRange<'g, G>,
+    /// The object of the pattern, if the range is not limited to it.
+    object: Option<Resource<'g, G::BlankNodePtr, G::IRIPtr, G::LiteralPtr, G::QuotedTriplePtr>>,
+}
+
+impl<'g, G> PatternIter<'g, G>
+    where G: Graph<'g>
+{
+    /// Does the triple have the object of the pattern? IRIs and literals are
+    /// compared by value, because a `GraphCollection` can have different
+    /// pointers for the same IRI or literal.
+    fn matches<T>(&self, triple: &T) -> bool
+        where T: Triple<'g, G::BlankNodePtr, G::IRIPtr, G::LiteralPtr, G::QuotedTriplePtr>
+    {
+        let object = match self.object {
+            Some(ref object) => object,
+            None => return true,
+        };
+        let other = triple.object();
+        match (object.as_iri(), other.as_iri(), object.as_literal(), other.as_literal()) {
+            (Some(a), Some(b), _, _) => a.as_str() == b.as_str(),
+            (_, _, Some(a), Some(b)) => {
+                a.as_str() == b.as_str() && a.datatype_str() == b.datatype_str() &&
+                a.language() == b.language()
+            }
+            _ => *object == other,
+        }
+    }
+}
+
+impl<'g, G> Iterator for PatternIter<'g, G>
+    where G: Graph<'g>
+{
+    type Item = PatternTriple<G::SPOTriple, G::OPSTriple>;
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let triple = match self.range {
+                PatternRange::All(ref mut i) => i.next().map(PatternTriple::SPO),
+                PatternRange::SPO(ref mut i) => i.next().map(PatternTriple::SPO),
+                PatternRange::OPS(ref mut i) => i.next().map(PatternTriple::OPS),
+                PatternRange::PSO(ref mut i) => i.next().map(PatternTriple::SPO),
+            };
+            match triple {
+                Some(ref t) if !self.matches(t) => {}
+                triple => return triple,
+            }
+        }
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (lower, upper) = match self.range {
+            PatternRange::All(ref i) => i.size_hint(),
+            PatternRange::SPO(ref i) => i.size_hint(),
+            PatternRange::OPS(ref i) => i.size_hint(),
+            PatternRange::PSO(ref i) => i.size_hint(),
+        };
+        // the object of the pattern can filter out any of the triples
+        match self.object {
+            Some(_) => (0, upper),
+            None => (lower, upper),
+        }
+    }
+}
+
+/// The name of a graph in a `Dataset`.
+///
+/// `B` is the blank node type of the graphs in the dataset. A blank node that
+/// names a graph is the same node as that blank node in the triples of the
+/// dataset.
+#[derive (Debug,Clone,Copy,PartialEq,Eq,PartialOrd,Ord,Hash)]
+pub enum GraphName<'g, B> {
+    /// A graph that is named by a blank node.
+    BlankNode(B),
+    /// A graph that is named by an IRI.
+    IRI(&'g str),
+}
+
+/// The name of a graph in the dataset `D`.
+pub type DatasetGraphName<'g, D> =
+    GraphName<'g, <<D as Dataset<'g>>::Graph as Graph<'g>>::BlankNodePtr>;
+
+/// A triple together with the name of the graph that contains it.
+#[derive (Debug,Clone,PartialEq,Eq)]
+pub struct Quad<'g, B, T> {
+    /// The name of the graph. The default graph has no name.
+    pub graph_name: Option<GraphName<'g, B>>,
+    /// The triple.
+    pub triple: T,
+}
+
+/// An RDF dataset.
+///
+/// A dataset has a default graph and any number of named graphs. The
+/// union graph contains the triples of all the graphs. Blank nodes are shared
+/// by all graphs, so a blank node in one graph is the same node as a blank
+/// node with the same label in another graph.
+///
+/// ```
+/// use rome::graph::{Dataset, DatasetWriter, Graph, GraphName, GraphWriter,
+///                   WriterBlankNodeOrIRI, WriterResource};
+/// use rome::graphs::tel;
+/// let mut creator = tel::DatasetCreator::with_capacity(0);
+/// let g = WriterBlankNodeOrIRI::IRI(creator.create_iri(&"http://example.org/g"));
+/// let s = WriterBlankNodeOrIRI::IRI(creator.create_iri(&"http://example.org/s"));
+/// let p = creator.create_iri(&"http://example.org/p");
+/// let o = WriterResource::Literal(creator.create_literal(&"o"));
+/// creator.add(&s, &p, &o);
+/// creator.add_quad(Some(&g), &s, &p, &o).unwrap();
+/// let dataset: tel::Dataset128 = creator.collect_dataset();
+/// let name = GraphName::IRI("http://example.org/g");
+/// assert_eq!(dataset.named_graph(&name).unwrap().iter().count(), 1);
+/// assert_eq!(dataset.quads().count(), 2);
+/// assert_eq!(dataset.union_graph().iter().count(), 1);
+/// ```
+pub trait Dataset<'g> {
+    /// The type of the default graph and the named graphs.
+    type Graph: Graph<'g> + 'g;
+    /// The type of the union of all graphs.
+    type UnionGraph: Graph<'g> + 'g;
+    /// Iterator over the graphs and their names.
+    type GraphIter: Iterator<Item = (Option<DatasetGraphName<'g, Self>>, &'g Self::Graph)>;
+    /// The graph that has no name.
+    fn default_graph(&'g self) -> &'g Self::Graph;
+    /// Find the graph with the given name.
+    fn named_graph(&'g self, name: &DatasetGraphName<'g, Self>) -> Option<&'g Self::Graph>;
+    /// Iterate over all graphs with their names, starting with the default
+    /// graph.
+    fn graphs(&'g self) -> Self::GraphIter;
+    /// The graph that contains the triples of all graphs.
+    fn union_graph(&'g self) -> &'g Self::UnionGraph;
+    /// Iterate over the triples of all graphs with the names of their graphs.
+    fn quads(&'g self) -> Quads<'g, Self>
+        where Self: Sized
+    {
+        Quads {
+            graphs: self.graphs(),
+            current: None,
+        }
+    }
+}
+
+/// Iterator over the quads in a `Dataset`.
+pub struct Quads<'g, D: 'g>
+    where D: Dataset<'g>
+{
+    graphs: D::GraphIter,
+    current: Option<(Option<DatasetGraphName<'g, D>>, <D::Graph as Graph<'g>>::SPOIter)>,
+}
+
+impl<'g, D: 'g> Iterator for Quads<'g, D>
+    where D: Dataset<'g>
+{
+    type Item = Quad<'g,
+                     <D::Graph as Graph<'g>>::BlankNodePtr,
+                     <D::Graph as Graph<'g>>::SPOTriple>;
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(ref mut current) = self.current {
+                if let Some(triple) = current.1.next() {
+                    return Some(Quad {
+                        graph_name: current.0.clone(),
+                        triple: triple,
+                    });
+                }
+            }
+            match self.graphs.next() {
+                Some((name, graph)) => self.current = Some((name, graph.iter())),
+                None => return None,
+            }
+        }
+    }
 }
 
 impl<'g> IRIPtr<'g> for &'g str {
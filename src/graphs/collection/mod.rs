@@ -12,6 +12,8 @@ pub enum Object {
     IRI,
     #[doc(hidden)]
     Literal,
+    #[doc(hidden)]
+    QuotedTriple,
 }
 
 /// `TripleCmpWrap` can wrap a Triple and has no associated types.
@@ -29,41 +31,81 @@ pub trait TripleCmpWrap<'g> {
     #[doc(hidden)]
     fn subject_is_blank_node(&self) -> bool;
     #[doc(hidden)]
+    fn subject_is_quoted_triple(&self) -> bool;
+    #[doc(hidden)]
     fn object_type(&self) -> Object;
 }
-fn compare_subject<'g, B: 'g, I: 'g>(a: &TripleCmpWrap,
-                                     b: BlankNodeOrIRI<'g, B, I>)
-                                     -> cmp::Ordering
+/// The rank of a kind of term in the order of the graphs: blank nodes come
+/// first, then quoted triples, IRIs and literals.
+fn object_rank(object: Object) -> u8 {
+    match object {
+        Object::BlankNode => 0,
+        Object::QuotedTriple => 1,
+        Object::IRI => 2,
+        Object::Literal => 3,
+    }
+}
+fn compare_subject<'g, B: 'g, I: 'g, Q: 'g>(a: &TripleCmpWrap,
+                                            b: BlankNodeOrIRI<'g, B, I, Q>)
+                                            -> cmp::Ordering
     where B: BlankNodePtr<'g>,
           I: IRIPtr<'g>
 {
+    let a_rank = if a.subject_is_blank_node() {
+        0
+    } else if a.subject_is_quoted_triple() {
+        1
+    } else {
+        2
+    };
+    let b_rank = match b {
+        BlankNodeOrIRI::BlankNode(_, _) => 0,
+        BlankNodeOrIRI::QuotedTriple(_) => 1,
+        BlankNodeOrIRI::IRI(_) => 2,
+    };
     match b {
+        _ if a_rank != b_rank => a_rank.cmp(&b_rank),
+        BlankNodeOrIRI::IRI(i) => a.cmp_subject_iri(i.as_str()),
         // blank nodes from different graphs are different
         // the left side is less
-        BlankNodeOrIRI::BlankNode(_, _) => cmp::Ordering::Less,
-        BlankNodeOrIRI::IRI(i) => a.cmp_subject_iri(i.as_str()),
+        _ => cmp::Ordering::Less,
     }
 }
-fn compare_object<'g, B: 'g, I: 'g, L: 'g>(a: &TripleCmpWrap,
-                                           b: Resource<'g, B, I, L>)
-                                           -> cmp::Ordering
+fn compare_object<'g, B: 'g, I: 'g, L: 'g, Q: 'g>(a: &TripleCmpWrap,
+                                                  b: Resource<'g, B, I, L, Q>)
+                                                  -> cmp::Ordering
     where B: BlankNodePtr<'g>,
           I: IRIPtr<'g>,
           L: LiteralPtr<'g>
 {
+    let b_rank = match b {
+        Resource::BlankNode(_, _) => 0,
+        Resource::QuotedTriple(_) => 1,
+        Resource::IRI(_) => 2,
+        Resource::Literal(_) => 3,
+    };
     match b {
-        Resource::BlankNode(_, _) => cmp::Ordering::Less,
+        _ if object_rank(a.object_type()) != b_rank => object_rank(a.object_type()).cmp(&b_rank),
         Resource::IRI(i) => a.cmp_object_iri(i.as_str()),
         Resource::Literal(l) => a.cmp_object_literal(l.as_str(), l.datatype_str(), l.language()),
+        _ => cmp::Ordering::Less,
     }
 }
 #[doc(hidden)]
+/// The size hint of an iterator that merges iterators with these size hints.
+/// A triple that is in more than one graph is returned once.
+pub fn merged_size_hint(hints: &[(usize, Option<usize>)]) -> (usize, Option<usize>) {
+    let lower = hints.iter().map(|h| h.0).max().unwrap_or(0);
+    let upper = hints.iter().try_fold(0usize, |sum, h| h.1.and_then(|u| sum.checked_add(u)));
+    (lower, upper)
+}
+#[doc(hidden)]
 /// sort by subject, predicate, object
-pub fn compare_spo<'g, B: 'g, I: 'g, L: 'g, T: 'g>(a: &TripleCmpWrap, b: &T) -> cmp::Ordering
+pub fn compare_spo<'g, B: 'g, I: 'g, L: 'g, Q: 'g, T: 'g>(a: &TripleCmpWrap, b: &T) -> cmp::Ordering
     where B: BlankNodePtr<'g>,
           I: IRIPtr<'g>,
           L: LiteralPtr<'g>,
-          T: Triple<'g, B, I, L>
+          T: Triple<'g, B, I, L, Q>
 {
     let mut cmp = compare_subject(a, b.subject());
     if cmp == cmp::Ordering::Equal {
@@ -76,17 +118,17 @@ pub fn compare_spo<'g, B: 'g, I: 'g, L: 'g, T: 'g>(a: &TripleCmpWrap, b: &T) ->
 }
 #[doc(hidden)]
 /// sort by object, predicate, subject
-pub fn compare_ops<'g, B: 'g, I: 'g, L: 'g, T: 'g>(a: &TripleCmpWrap, b: &T) -> cmp::Ordering
+pub fn compare_ops<'g, B: 'g, I: 'g, L: 'g, Q: 'g, T: 'g>(a: &TripleCmpWrap, b: &T) -> cmp::Ordering
     where B: BlankNodePtr<'g>,
           I: IRIPtr<'g>,
           L: LiteralPtr<'g>,
-          T: Triple<'g, B, I, L>
+          T: Triple<'g, B, I, L, Q>
 {
     let mut cmp = compare_object(a, b.object());
-    if cmp == cmp::Ordering::Less {
+    if cmp == cmp::Ordering::Equal {
         cmp = a.cmp_predicate(b.predicate().as_str());
     }
-    if cmp == cmp::Ordering::Less {
+    if cmp == cmp::Ordering::Equal {
         cmp = compare_subject(a, b.subject());
     }
     cmp
@@ -95,16 +137,16 @@ pub fn compare_ops<'g, B: 'g, I: 'g, L: 'g, T: 'g>(a: &TripleCmpWrap, b: &T) ->
 /// get the triple that is equal to the given triple and if needed,
 /// advance the iterator
 /// the iterator is forwarded one position at most
-pub fn get_equal_spo<'g, K: 'g, T: 'g, B: 'g, I: 'g, L: 'g>(iter: &mut Peekable<K>,
-                                                            t: &TripleCmpWrap<'g>,
-                                                            n: &mut u8,
-                                                            min_n: u8)
-                                                            -> Option<T>
+pub fn get_equal_spo<'g, K: 'g, T: 'g, B: 'g, I: 'g, L: 'g, Q: 'g>(iter: &mut Peekable<K>,
+                                                                   t: &TripleCmpWrap<'g>,
+                                                                   n: &mut u8,
+                                                                   min_n: u8)
+                                                                   -> Option<T>
     where K: Iterator<Item = T>,
           B: BlankNodePtr<'g>,
           I: IRIPtr<'g>,
           L: LiteralPtr<'g>,
-          T: Triple<'g, B, I, L>
+          T: Triple<'g, B, I, L, Q>
 {
     *n += 1;
     if *n == min_n {
@@ -120,16 +162,16 @@ pub fn get_equal_spo<'g, K: 'g, T: 'g, B: 'g, I: 'g, L: 'g>(iter: &mut Peekable<
 // get the triple that is equal to the given triple and if needed,
 // advance the iterator
 // the iterator is forwarded one position at most
-pub fn get_equal_ops<'g, K: 'g, T: 'g, B: 'g, I: 'g, L: 'g>(iter: &mut Peekable<K>,
-                                                            t: &TripleCmpWrap<'g>,
-                                                            n: &mut u8,
-                                                            min_n: u8)
-                                                            -> Option<T>
+pub fn get_equal_ops<'g, K: 'g, T: 'g, B: 'g, I: 'g, L: 'g, Q: 'g>(iter: &mut Peekable<K>,
+                                                                   t: &TripleCmpWrap<'g>,
+                                                                   n: &mut u8,
+                                                                   min_n: u8)
+                                                                   -> Option<T>
     where K: Iterator<Item = T>,
           B: BlankNodePtr<'g>,
           I: IRIPtr<'g>,
           L: LiteralPtr<'g>,
-          T: Triple<'g, B, I, L>
+          T: Triple<'g, B, I, L, Q>
 {
     *n += 1;
     if *n == min_n {
@@ -173,7 +215,8 @@ impl_triple_cmp_wrap_spo_ops {
             fn cmp_object_iri(&self, o: &str) -> cmp::Ordering {
                 use graph::{IRIPtr, Resource, Triple};
                 match self.object() {
-                    Resource::BlankNode(_,_) => cmp::Ordering::Less,
+                    Resource::BlankNode(_,_) |
+                    Resource::QuotedTriple(_) => cmp::Ordering::Less,
                     Resource::IRI(i) => i.as_str().cmp(o),
                     Resource::Literal(_) => cmp::Ordering::Greater
                 }
@@ -201,12 +244,20 @@ impl_triple_cmp_wrap_spo_ops {
                     _ => false
                 }
             }
+            fn subject_is_quoted_triple(&self) -> bool {
+                use $crate::graph::{BlankNodeOrIRI, Triple};
+                match self.subject() {
+                    BlankNodeOrIRI::QuotedTriple(_) => true,
+                    _ => false
+                }
+            }
             fn object_type(&self) -> Object {
                 use $crate::graph::{Resource, Triple};
                 match self.object() {
                     Resource::BlankNode(_,_) => Object::BlankNode,
                     Resource::IRI(_) => Object::IRI,
                     Resource::Literal(_) => Object::Literal,
+                    Resource::QuotedTriple(_) => Object::QuotedTriple,
                 }
             }
         }
@@ -223,9 +274,9 @@ spo_ops {
 #[doc(hidden)]
 #[derive(Clone,PartialEq,Eq,PartialOrd,Ord)]
 pub struct $name<'g> {
-    subject: $crate::graph::BlankNodeOrIRI<'g, BlankNode<'g>, IRI<'g>>,
+    subject: $crate::graph::BlankNodeOrIRI<'g, BlankNode<'g>, IRI<'g>, QuotedTriple<'g>>,
     predicate: IRI<'g>,
-    object: $crate::graph::Resource<'g,BlankNode<'g>,IRI<'g>,Literal<'g>>
+    object: $crate::graph::Resource<'g,BlankNode<'g>,IRI<'g>,Literal<'g>,QuotedTriple<'g>>
 }
 
 impl<'g> $name<'g> {
@@ -236,6 +287,11 @@ impl<'g> $name<'g> {
                 $crate::graph::BlankNodeOrIRI::BlankNode(BlankNode {
                     nodes: ($(triples.$n.as_ref().map(|t|t.subject().as_blank_node().unwrap().clone()),)+)
                 }, PhantomData)
+            } else if triple_ref.subject_is_quoted_triple() {
+                $crate::graph::BlankNodeOrIRI::QuotedTriple(QuotedTriple {
+                    triples: ($(triples.$n.as_ref()
+                        .map(|t|t.subject().as_quoted_triple().unwrap().clone()),)+)
+                })
             } else {
                 $crate::graph::BlankNodeOrIRI::IRI(IRI {
                     iris: ($(triples.$n.as_ref().map(|t|t.subject().as_iri().unwrap().clone()),)+)
@@ -246,26 +302,30 @@ impl<'g> $name<'g> {
             },
             object: match triple_ref.object_type() {
                 Object::BlankNode => Resource::BlankNode(BlankNode {
-                    nodes: ($(triples.$n.map(|t|t.subject().as_blank_node().unwrap().clone()),)+)
+                    nodes: ($(triples.$n.map(|t|t.object().as_blank_node().unwrap().clone()),)+)
                 }, PhantomData),
                 Object::IRI => Resource::IRI(IRI {
-                    iris: ($(triples.$n.map(|t|t.subject().as_iri().unwrap().clone()),)+)
+                    iris: ($(triples.$n.map(|t|t.object().as_iri().unwrap().clone()),)+)
                 }),
                 Object::Literal => Resource::Literal(Literal {
                     literals: ($(triples.$n.map(|t|t.object().as_literal().unwrap().clone()),)+)
+                }),
+                Object::QuotedTriple => Resource::QuotedTriple(QuotedTriple {
+                    triples: ($(triples.$n
+                        .map(|t|t.object().as_quoted_triple().unwrap().clone()),)+)
                 })
             }
         }
     }
 }
-impl<'g> Triple<'g, BlankNode<'g>, IRI<'g>, Literal<'g>> for $name<'g> {
-    fn subject(&self) -> BlankNodeOrIRI<'g, BlankNode<'g>, IRI<'g>> {
+impl<'g> Triple<'g, BlankNode<'g>, IRI<'g>, Literal<'g>, QuotedTriple<'g>> for $name<'g> {
+    fn subject(&self) -> BlankNodeOrIRI<'g, BlankNode<'g>, IRI<'g>, QuotedTriple<'g>> {
         self.subject.clone()
     }
     fn predicate(&self) -> IRI<'g> {
         self.predicate.clone()
     }
-    fn object(&self) -> Resource<'g, BlankNode<'g>, IRI<'g>, Literal<'g>> {
+    fn object(&self) -> Resource<'g, BlankNode<'g>, IRI<'g>, Literal<'g>, QuotedTriple<'g>> {
         self.object.clone()
     }
 }
@@ -275,6 +335,9 @@ impl<'g> Triple<'g, BlankNode<'g>, IRI<'g>, Literal<'g>> for $name<'g> {
 
 /// Create a module for a collection of graphs.
 ///
+/// Blank nodes and quoted triples from different graphs are different
+/// terms in the collection.
+///
 /// # Example
 ///
 /// ```
@@ -325,19 +388,41 @@ pub mod $name {
     type IRIs<'g> = ($(Option<<$graph_type as Graph<'g>>::IRIPtr>,)+);
     type Datatypes<'g> = ($(Option<<<$graph_type as Graph<'g>>::LiteralPtr as LiteralPtr<'g>>::DatatypePtr>,)+);
     type Literals<'g> = ($(Option<<$graph_type as Graph<'g>>::LiteralPtr>,)+);
+    type QuotedTriples<'g> = ($(Option<<$graph_type as Graph<'g>>::QuotedTriplePtr>,)+);
     type SPOTriples<'g> = ($(Option<<$graph_type as Graph<'g>>::SPOTriple>,)+);
     type SPOIters<'g> = ($(Peekable<<$graph_type as Graph<'g>>::SPOIter>,)+);
     type SPORangeIters<'g> = ($(Peekable<<$graph_type as Graph<'g>>::SPORangeIter>,)+);
     type OPSTriples<'g> = ($(Option<<$graph_type as Graph<'g>>::OPSTriple>,)+);
     type OPSRangeIters<'g> = ($(Peekable<<$graph_type as Graph<'g>>::OPSRangeIter>,)+);
+    type PSORangeIters<'g> = ($(Peekable<<$graph_type as Graph<'g>>::PSORangeIter>,)+);
 
     spo_ops!(SPOTriple SPOTriples($( $n:$graph_type),+));
     spo_ops!(OPSTriple OPSTriples($( $n:$graph_type),+));
 
-    #[derive(Clone,PartialEq,Eq,PartialOrd,Ord)]
+    #[derive(Clone,PartialEq,Eq)]
     pub struct BlankNode<'g> {
         nodes: BlankNodes<'g>
     }
+    /// The blank nodes of the first graph come first, as in the indexes of
+    /// the collection.
+    impl<'g> Ord for BlankNode<'g> {
+        fn cmp(&self, other: &Self) -> cmp::Ordering {
+            $(
+                match (self.nodes.$n.as_ref(), other.nodes.$n.as_ref()) {
+                    (Some(a), Some(b)) => return a.cmp(b),
+                    (Some(_), None) => return cmp::Ordering::Less,
+                    (None, Some(_)) => return cmp::Ordering::Greater,
+                    (None, None) => {}
+                }
+            )+
+            cmp::Ordering::Equal
+        }
+    }
+    impl<'g> PartialOrd for BlankNode<'g> {
+        fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
+            Some(self.cmp(other))
+        }
+    }
     impl<'g> fmt::Display for BlankNode<'g> {
         fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
             write!(f, "_")?;
@@ -419,6 +504,75 @@ pub mod $name {
             panic!("unreachable")
         }
     }
+    /// A quoted triple of one of the graphs.
+    #[derive(Clone,PartialEq,Eq)]
+    pub struct QuotedTriple<'g> {
+        triples: QuotedTriples<'g>,
+    }
+    /// The quoted triples of the first graph come first, as in the indexes of
+    /// the collection.
+    impl<'g> Ord for QuotedTriple<'g> {
+        fn cmp(&self, other: &Self) -> cmp::Ordering {
+            $(
+                match (self.triples.$n.as_ref(), other.triples.$n.as_ref()) {
+                    (Some(a), Some(b)) => return a.cmp(b),
+                    (Some(_), None) => return cmp::Ordering::Less,
+                    (None, Some(_)) => return cmp::Ordering::Greater,
+                    (None, None) => {}
+                }
+            )+
+            cmp::Ordering::Equal
+        }
+    }
+    impl<'g> PartialOrd for QuotedTriple<'g> {
+        fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
+            Some(self.cmp(other))
+        }
+    }
+    impl<'g> Triple<'g, BlankNode<'g>, IRI<'g>, Literal<'g>, QuotedTriple<'g>>
+        for QuotedTriple<'g> {
+        fn subject(&self) -> BlankNodeOrIRI<'g, BlankNode<'g>, IRI<'g>, QuotedTriple<'g>> {
+            let nodes = ($(self.triples.$n.as_ref()
+                .and_then(|t| t.subject().as_blank_node().cloned()),)+);
+            if $( nodes.$n.is_some() )||+ {
+                return BlankNodeOrIRI::BlankNode(BlankNode { nodes: nodes }, PhantomData);
+            }
+            let iris = ($(self.triples.$n.as_ref().and_then(|t| t.subject().as_iri().cloned()),)+);
+            if $( iris.$n.is_some() )||+ {
+                return BlankNodeOrIRI::IRI(IRI { iris: iris });
+            }
+            BlankNodeOrIRI::QuotedTriple(QuotedTriple {
+                triples: ($(self.triples.$n.as_ref()
+                    .and_then(|t| t.subject().as_quoted_triple().cloned()),)+)
+            })
+        }
+        fn predicate(&self) -> IRI<'g> {
+            IRI {
+                iris: ($(self.triples.$n.as_ref().map(|t| t.predicate()),)+)
+            }
+        }
+        fn object(&self) -> Resource<'g, BlankNode<'g>, IRI<'g>, Literal<'g>, QuotedTriple<'g>> {
+            let nodes = ($(self.triples.$n.as_ref()
+                .and_then(|t| t.object().as_blank_node().cloned()),)+);
+            if $( nodes.$n.is_some() )||+ {
+                return Resource::BlankNode(BlankNode { nodes: nodes }, PhantomData);
+            }
+            let iris = ($(self.triples.$n.as_ref().and_then(|t| t.object().as_iri().cloned()),)+);
+            if $( iris.$n.is_some() )||+ {
+                return Resource::IRI(IRI { iris: iris });
+            }
+            let literals = ($(self.triples.$n.as_ref()
+                .and_then(|t| t.object().as_literal().cloned()),)+);
+            if $( literals.$n.is_some() )||+ {
+                return Resource::Literal(Literal { literals: literals });
+            }
+            Resource::QuotedTriple(QuotedTriple {
+                triples: ($(self.triples.$n.as_ref()
+                    .and_then(|t| t.object().as_quoted_triple().cloned()),)+)
+            })
+        }
+    }
+    impl<'g> QuotedTriplePtr<'g, BlankNode<'g>, IRI<'g>, Literal<'g>> for QuotedTriple<'g> {}
     pub struct SPOIter<'g> {
         iters: SPOIters<'g>
     }
@@ -447,6 +601,9 @@ pub mod $name {
                 None
             }
         }
+        fn size_hint(&self) -> (usize, Option<usize>) {
+            merged_size_hint(&[$(self.iters.$n.size_hint()),+])
+        }
     }
     impl<'g> SortedIterator for SPOIter<'g> {}
     pub struct SPORangeIter<'g> {
@@ -477,8 +634,44 @@ pub mod $name {
                 None
             }
         }
+        fn size_hint(&self) -> (usize, Option<usize>) {
+            merged_size_hint(&[$(self.iters.$n.size_hint()),+])
+        }
     }
     impl<'g> SortedIterator for SPORangeIter<'g> {}
+    pub struct PSORangeIter<'g> {
+        iters: PSORangeIters<'g>
+    }
+    impl<'g> Iterator for PSORangeIter<'g> {
+        type Item = SPOTriple<'g>;
+        fn next(&mut self) -> Option<SPOTriple<'g>> {
+            let triples = ($(self.iters.$n.peek().map(|v|v.clone()),)+);
+            let mut n = 0;
+            let mut min_n = 0;
+            let mut min = None;
+            $(
+                n += 1;
+                if let Some(t) = triples.$n.as_ref() {
+                    if min.is_none() || compare_spo(min.unwrap(), t) == cmp::Ordering::Greater {
+                        min_n = n;
+                        min = Some(t as &TripleCmpWrap);
+                    }
+                }
+            )+
+            if let Some(t) = min {
+                n = 0;
+                Some(SPOTriple::new(t, ($(
+                    get_equal_spo(&mut self.iters.$n, t, &mut n, min_n),
+                )+)))
+            } else {
+                None
+            }
+        }
+        fn size_hint(&self) -> (usize, Option<usize>) {
+            merged_size_hint(&[$(self.iters.$n.size_hint()),+])
+        }
+    }
+    impl<'g> SortedIterator for PSORangeIter<'g> {}
     pub struct OPSRangeIter<'g> {
         iters: OPSRangeIters<'g>
     }
@@ -507,6 +700,9 @@ pub mod $name {
                 None
             }
         }
+        fn size_hint(&self) -> (usize, Option<usize>) {
+            merged_size_hint(&[$(self.iters.$n.size_hint()),+])
+        }
     }
     impl<'g> SortedIterator for OPSRangeIter<'g> {}
 
@@ -528,11 +724,13 @@ pub mod $name {
         type BlankNodePtr = BlankNode<'g>;
         type IRIPtr = IRI<'g>;
         type LiteralPtr = Literal<'g>;
+        type QuotedTriplePtr = QuotedTriple<'g>;
         type SPOTriple = SPOTriple<'g>;
         type SPOIter = SPOIter<'g>;
         type SPORangeIter = SPORangeIter<'g>;
         type OPSTriple = OPSTriple<'g>;
         type OPSRangeIter = OPSRangeIter<'g>;
+        type PSORangeIter = PSORangeIter<'g>;
         fn iter(&'g self) -> Self::SPOIter {
             SPOIter {
                 iters: ($(self.graphs.$n.iter().peekable(),)+)
@@ -578,7 +776,8 @@ pub mod $name {
             }
         }
         fn iter_s(&'g self,
-                    subject: &BlankNodeOrIRI<'g, Self::BlankNodePtr, Self::IRIPtr>)
+                    subject: &BlankNodeOrIRI<'g, Self::BlankNodePtr, Self::IRIPtr,
+                                             Self::QuotedTriplePtr>)
                     -> Self::SPORangeIter {
             let iters = match subject {
                 &BlankNodeOrIRI::BlankNode(ref b, _) => { ($(
@@ -600,12 +799,23 @@ pub mod $name {
                             self.graphs.$n.empty_spo_range()
                         }
                     }.peekable()
+                ,)+) },
+                &BlankNodeOrIRI::QuotedTriple(ref t) => { ($(
+                    match t.triples.$n {
+                        Some(ref t) => {
+                            self.graphs.$n.iter_s(&BlankNodeOrIRI::QuotedTriple(t.clone()))
+                        },
+                        _ => {
+                            self.graphs.$n.empty_spo_range()
+                        }
+                    }.peekable()
                 ,)+) }
             };
             SPORangeIter { iters: iters }
         }
         fn iter_s_p(&'g self,
-                    subject: &BlankNodeOrIRI<'g, Self::BlankNodePtr, Self::IRIPtr>,
+                    subject: &BlankNodeOrIRI<'g, Self::BlankNodePtr, Self::IRIPtr,
+                                             Self::QuotedTriplePtr>,
                     predicate: &Self::IRIPtr)
                     -> Self::SPORangeIter {
             let iters = match subject {
@@ -628,12 +838,23 @@ pub mod $name {
                             self.graphs.$n.empty_spo_range()
                         }
                     }.peekable()
+                ,)+) },
+                &BlankNodeOrIRI::QuotedTriple(ref t) => { ($(
+                    match (&t.triples.$n, &predicate.iris.$n) {
+                        (&Some(ref t), &Some(ref p)) => {
+                            self.graphs.$n.iter_s_p(&BlankNodeOrIRI::QuotedTriple(t.clone()), p)
+                        },
+                        _ => {
+                            self.graphs.$n.empty_spo_range()
+                        }
+                    }.peekable()
                 ,)+) }
             };
             SPORangeIter { iters: iters }
         }
         fn iter_o(&'g self,
-                    object: &Resource<'g, Self::BlankNodePtr, Self::IRIPtr, Self::LiteralPtr>)
+                    object: &Resource<'g, Self::BlankNodePtr, Self::IRIPtr, Self::LiteralPtr,
+                                      Self::QuotedTriplePtr>)
                     -> Self::OPSRangeIter {
             let iters = match object {
                 &Resource::BlankNode(ref b, _) => { ($(
@@ -665,12 +886,23 @@ pub mod $name {
                             self.graphs.$n.empty_ops_range()
                         }
                     }.peekable()
+                ,)+) },
+                &Resource::QuotedTriple(ref t) => { ($(
+                    match t.triples.$n {
+                        Some(ref t) => {
+                            self.graphs.$n.iter_o(&Resource::QuotedTriple(t.clone()))
+                        },
+                        _ => {
+                            self.graphs.$n.empty_ops_range()
+                        }
+                    }.peekable()
                 ,)+) }
             };
             OPSRangeIter { iters: iters }
         }
         fn iter_o_p(&'g self,
-                    object: &Resource<'g, Self::BlankNodePtr, Self::IRIPtr, Self::LiteralPtr>,
+                    object: &Resource<'g, Self::BlankNodePtr, Self::IRIPtr, Self::LiteralPtr,
+                                      Self::QuotedTriplePtr>,
                     predicate: &Self::IRIPtr)
                     -> Self::OPSRangeIter {
             let iters = match object {
@@ -703,10 +935,29 @@ pub mod $name {
                             self.graphs.$n.empty_ops_range()
                         }
                     }.peekable()
+                ,)+) },
+                &Resource::QuotedTriple(ref t) => { ($(
+                    match (&t.triples.$n, &predicate.iris.$n) {
+                        (&Some(ref t), &Some(ref p)) => {
+                            self.graphs.$n.iter_o_p(&Resource::QuotedTriple(t.clone()), p)
+                        },
+                        _ => {
+                            self.graphs.$n.empty_ops_range()
+                        }
+                    }.peekable()
                 ,)+) }
             };
             OPSRangeIter { iters: iters }
         }
+        fn iter_p(&'g self, predicate: &Self::IRIPtr) -> Self::PSORangeIter {
+            let iters = ($(
+                match predicate.iris.$n {
+                    Some(ref p) => self.graphs.$n.iter_p(p),
+                    None => self.graphs.$n.empty_pso_range(),
+                }.peekable()
+            ,)+);
+            PSORangeIter { iters: iters }
+        }
         /// iterator that returns no results
         fn empty_spo_range(&'g self) -> Self::SPORangeIter {
             SPORangeIter {
@@ -719,6 +970,12 @@ pub mod $name {
                 iters: ($( self.graphs.$n.empty_ops_range().peekable(), )+)
             }
         }
+        /// iterator that returns no results
+        fn empty_pso_range(&'g self) -> Self::PSORangeIter {
+            PSORangeIter {
+                iters: ($( self.graphs.$n.empty_pso_range().peekable(), )+)
+            }
+        }
     }
 }
     }
@@ -0,0 +1,703 @@
+use constants;
+use graph;
+use rand;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::collections::Bound::{Included, Unbounded};
+use std::marker::PhantomData;
+use std::sync::Arc;
+use super::triple::*;
+
+/// A mutable in-memory RDF graph.
+///
+/// Triples are added with `insert` or with the methods of
+/// `rome::graph::GraphWriter` and removed with `remove`. Both indexes and the
+/// shared strings are updated right away, so the graph can be queried at any
+/// time.
+pub struct MemGraph {
+    graph_id: u32,
+    highest_blank_node: u32,
+    /// The strings in the graph and the number of times each is used.
+    strings: HashMap<Arc<str>, usize>,
+    spo: BTreeSet<(Subject, IRIPtr, Object)>,
+    ops: BTreeSet<(Object, IRIPtr, Subject)>,
+    pso: BTreeSet<(IRIPtr, Subject, Object)>,
+}
+
+impl MemGraph {
+    /// Create a new empty graph.
+    pub fn new() -> MemGraph {
+        MemGraph {
+            graph_id: rand::random::<u32>(),
+            highest_blank_node: 0,
+            strings: HashMap::new(),
+            spo: BTreeSet::new(),
+            ops: BTreeSet::new(),
+            pso: BTreeSet::new(),
+        }
+    }
+    /// The number of triples in the graph.
+    pub fn len(&self) -> usize {
+        self.spo.len()
+    }
+    /// Is the graph empty?
+    pub fn is_empty(&self) -> bool {
+        self.spo.is_empty()
+    }
+    /// Does the graph contain the given triple?
+    pub fn contains(&self, subject: &Subject, predicate: &IRIPtr, object: &Object) -> bool {
+        self.spo.contains(&(subject.clone(), predicate.clone(), object.clone()))
+    }
+    /// Add a triple to the graph.
+    ///
+    /// Returns `false` if the graph already contained the triple.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a blank node in the triple was created by another graph.
+    pub fn insert(&mut self, subject: Subject, predicate: IRIPtr, object: Object) -> bool {
+        self.check_subject(&subject);
+        self.check_object(&object);
+        if self.contains(&subject, &predicate, &object) {
+            return false;
+        }
+        let subject = self.intern_subject(&subject);
+        let predicate = self.intern_iri(&predicate);
+        let object = self.intern_object(&object);
+        self.ops.insert((object.clone(), predicate.clone(), subject.clone()));
+        self.pso.insert((predicate.clone(), subject.clone(), object.clone()));
+        self.spo.insert((subject, predicate, object));
+        true
+    }
+    /// Remove a triple from the graph.
+    ///
+    /// Returns `false` if the graph did not contain the triple.
+    pub fn remove(&mut self, subject: &Subject, predicate: &IRIPtr, object: &Object) -> bool {
+        let spo = (subject.clone(), predicate.clone(), object.clone());
+        let (subject, predicate, object) = match self.spo.take(&spo) {
+            Some(spo) => spo,
+            None => return false,
+        };
+        self.ops.remove(&(object.clone(), predicate.clone(), subject.clone()));
+        self.pso.remove(&(predicate.clone(), subject.clone(), object.clone()));
+        self.release_subject(&subject);
+        self.release(&predicate.iri);
+        self.release_object(&object);
+        true
+    }
+    /// Add the triples of another graph.
+    ///
+    /// The blank nodes of the other graph become new blank nodes of this
+    /// graph.
+    pub fn add_graph<'g, G>(&mut self, graph: &'g G)
+        where G: graph::Graph<'g> + 'g
+    {
+        use graph::{GraphWriter, ResourceTranslator, Triple};
+        let mut translator: Translator<G> = Translator { blank_nodes: BTreeMap::new() };
+        for triple in graph.iter() {
+            let subject = translator.translate_blank_node_or_iri(self, &triple.subject());
+            let predicate = self.create_iri(&triple.predicate());
+            let object = translator.translate_resource(self, &triple.object());
+            self.add(&subject, &predicate, &object);
+        }
+    }
+    /// Write the triples of the graph to a writer.
+    ///
+    /// The blank nodes of this graph become new blank nodes of the writer.
+    pub fn write_to<'w, W>(&self, writer: &mut W)
+        where W: graph::GraphWriter<'w>
+    {
+        write_triples(&self.spo, writer);
+    }
+    /// Remove all triples from the graph.
+    pub fn clear(&mut self) {
+        self.strings.clear();
+        self.spo.clear();
+        self.ops.clear();
+        self.pso.clear();
+    }
+    fn check_blank_node(&self, blank_node: &BlankNodePtr) {
+        assert_eq!(self.graph_id,
+                   blank_node.graph_id,
+                   "Blank node is not associated with this graph.");
+    }
+    fn check_subject(&self, subject: &Subject) {
+        match *subject {
+            Subject::BlankNode(ref b) => self.check_blank_node(b),
+            Subject::QuotedTriple(ref t) => self.check_quoted_triple(t),
+            Subject::IRI(_) => {}
+        }
+    }
+    fn check_object(&self, object: &Object) {
+        match *object {
+            Object::BlankNode(ref b) => self.check_blank_node(b),
+            Object::QuotedTriple(ref t) => self.check_quoted_triple(t),
+            Object::IRI(_) |
+            Object::Literal(_) => {}
+        }
+    }
+    fn check_quoted_triple(&self, triple: &QuotedTriplePtr) {
+        self.check_subject(&triple.triple.0);
+        self.check_object(&triple.triple.2);
+    }
+    /// Count a use of the string and return the shared copy of it.
+    fn intern(&mut self, string: &Arc<str>) -> Arc<str> {
+        let shared = self.find_string(string).unwrap_or_else(|| string.clone());
+        *self.strings.entry(shared.clone()).or_insert(0) += 1;
+        shared
+    }
+    /// Remove a use of the string and drop it when it is no longer used.
+    fn release(&mut self, string: &str) {
+        let unused = match self.strings.get_mut(string) {
+            Some(count) => {
+                *count -= 1;
+                *count == 0
+            }
+            None => false,
+        };
+        if unused {
+            self.strings.remove(string);
+        }
+    }
+    fn intern_iri(&mut self, iri: &IRIPtr) -> IRIPtr {
+        IRIPtr { iri: self.intern(&iri.iri) }
+    }
+    fn intern_subject(&mut self, subject: &Subject) -> Subject {
+        match *subject {
+            Subject::BlankNode(b) => Subject::BlankNode(b),
+            Subject::QuotedTriple(ref t) => Subject::QuotedTriple(self.intern_quoted_triple(t)),
+            Subject::IRI(ref i) => Subject::IRI(self.intern_iri(i)),
+        }
+    }
+    fn intern_object(&mut self, object: &Object) -> Object {
+        match *object {
+            Object::BlankNode(b) => Object::BlankNode(b),
+            Object::QuotedTriple(ref t) => Object::QuotedTriple(self.intern_quoted_triple(t)),
+            Object::IRI(ref i) => Object::IRI(self.intern_iri(i)),
+            Object::Literal(ref l) => {
+                Object::Literal(LiteralPtr {
+                    lexical: self.intern(&l.lexical),
+                    datatype: self.intern(&l.datatype),
+                    language: l.language.as_ref().map(|l| self.intern(l)),
+                })
+            }
+        }
+    }
+    fn intern_quoted_triple(&mut self, triple: &QuotedTriplePtr) -> QuotedTriplePtr {
+        let subject = self.intern_subject(&triple.triple.0);
+        let predicate = self.intern_iri(&triple.triple.1);
+        let object = self.intern_object(&triple.triple.2);
+        QuotedTriplePtr { triple: Arc::new((subject, predicate, object)) }
+    }
+    fn release_subject(&mut self, subject: &Subject) {
+        match *subject {
+            Subject::BlankNode(_) => {}
+            Subject::QuotedTriple(ref t) => self.release_quoted_triple(t),
+            Subject::IRI(ref i) => self.release(&i.iri),
+        }
+    }
+    fn release_object(&mut self, object: &Object) {
+        match *object {
+            Object::BlankNode(_) => {}
+            Object::QuotedTriple(ref t) => self.release_quoted_triple(t),
+            Object::IRI(ref i) => self.release(&i.iri),
+            Object::Literal(ref l) => {
+                self.release(&l.lexical);
+                self.release(&l.datatype);
+                if let Some(ref language) = l.language {
+                    self.release(language);
+                }
+            }
+        }
+    }
+    fn release_quoted_triple(&mut self, triple: &QuotedTriplePtr) {
+        self.release_subject(&triple.triple.0);
+        self.release(&triple.triple.1.iri);
+        self.release_object(&triple.triple.2);
+    }
+    fn find_string(&self, string: &str) -> Option<Arc<str>> {
+        self.strings.get_key_value(string).map(|(s, _)| s.clone())
+    }
+    fn empty_iri() -> IRIPtr {
+        IRIPtr { iri: Arc::from("") }
+    }
+    fn range_spo<'g>(&'g self,
+                     subject: Subject,
+                     predicate: Option<IRIPtr>)
+                     -> RangeIter<'g, Subject, Object> {
+        let start = (subject.clone(),
+                     predicate.clone().unwrap_or_else(MemGraph::empty_iri),
+                     Object::min());
+        RangeIter {
+            iter: Some(self.spo.range((Included(start), Unbounded))),
+            first: Some(subject),
+            predicate: predicate,
+        }
+    }
+    fn range_ops<'g>(&'g self,
+                     object: Object,
+                     predicate: Option<IRIPtr>)
+                     -> RangeIter<'g, Object, Subject> {
+        let start = (object.clone(),
+                     predicate.clone().unwrap_or_else(MemGraph::empty_iri),
+                     Subject::min());
+        RangeIter {
+            iter: Some(self.ops.range((Included(start), Unbounded))),
+            first: Some(object),
+            predicate: predicate,
+        }
+    }
+}
+
+impl Default for MemGraph {
+    fn default() -> MemGraph {
+        MemGraph::new()
+    }
+}
+
+impl<'g> graph::Graph<'g> for MemGraph {
+    type BlankNodePtr = BlankNodePtr;
+    type IRIPtr = IRIPtr;
+    type LiteralPtr = LiteralPtr;
+    type QuotedTriplePtr = QuotedTriplePtr;
+    type SPOTriple = Triple<'g, Subject, Object>;
+    type SPOIter = RangeIter<'g, Subject, Object>;
+    type SPORangeIter = RangeIter<'g, Subject, Object>;
+    type OPSTriple = Triple<'g, Object, Subject>;
+    type OPSRangeIter = RangeIter<'g, Object, Subject>;
+    type PSORangeIter = PredicateIter<'g>;
+    fn iter(&'g self) -> Self::SPOIter {
+        RangeIter {
+            iter: Some(self.spo.range::<(Subject, IRIPtr, Object), _>(..)),
+            first: None,
+            predicate: None,
+        }
+    }
+    fn find_iri(&'g self, iri: &str) -> Option<IRIPtr> {
+        self.find_string(iri).map(|iri| IRIPtr { iri: iri })
+    }
+    fn find_literal<'a>(&'g self,
+                        literal: &'a str,
+                        datatype: &'a str,
+                        language: Option<&'a str>)
+                        -> Option<LiteralPtr> {
+        let lexical = self.find_string(literal)?;
+        match language.and_then(|l| self.find_string(l)) {
+            Some(language) => {
+                Some(LiteralPtr {
+                    lexical: lexical,
+                    datatype: Arc::from(constants::RDF_LANG_STRING),
+                    language: Some(language),
+                })
+            }
+            None => {
+                self.find_string(datatype).map(|datatype| {
+                    LiteralPtr {
+                        lexical: lexical,
+                        datatype: datatype,
+                        language: None,
+                    }
+                })
+            }
+        }
+    }
+    fn find_datatype<'a>(&'g self, datatype: &'a str) -> Option<DatatypePtr> {
+        self.find_string(datatype).map(|datatype| DatatypePtr { datatype: datatype })
+    }
+    fn iter_s(&'g self, subject: &BlankNodeOrIRI<'g>) -> Self::SPORangeIter {
+        self.range_spo(Subject::from(subject), None)
+    }
+    fn iter_s_p(&'g self,
+                subject: &BlankNodeOrIRI<'g>,
+                predicate: &IRIPtr)
+                -> Self::SPORangeIter {
+        self.range_spo(Subject::from(subject), Some(predicate.clone()))
+    }
+    fn iter_o(&'g self, object: &Resource<'g>) -> Self::OPSRangeIter {
+        self.range_ops(Object::from(object), None)
+    }
+    fn iter_o_p(&'g self, object: &Resource<'g>, predicate: &IRIPtr) -> Self::OPSRangeIter {
+        self.range_ops(Object::from(object), Some(predicate.clone()))
+    }
+    fn iter_p(&'g self, predicate: &IRIPtr) -> Self::PSORangeIter {
+        let start = (predicate.clone(), Subject::min(), Object::min());
+        PredicateIter {
+            iter: Some(self.pso.range((Included(start), Unbounded))),
+            predicate: predicate.clone(),
+        }
+    }
+    fn empty_spo_range(&'g self) -> Self::SPORangeIter {
+        RangeIter {
+            iter: None,
+            first: None,
+            predicate: None,
+        }
+    }
+    fn empty_ops_range(&'g self) -> Self::OPSRangeIter {
+        RangeIter {
+            iter: None,
+            first: None,
+            predicate: None,
+        }
+    }
+    fn empty_pso_range(&'g self) -> Self::PSORangeIter {
+        PredicateIter {
+            iter: None,
+            predicate: MemGraph::empty_iri(),
+        }
+    }
+}
+
+impl<'g> graph::GraphWriter<'g> for MemGraph {
+    type BlankNode = BlankNodePtr;
+    type IRI = IRIPtr;
+    type Literal = LiteralPtr;
+    type Datatype = DatatypePtr;
+    type Language = Arc<str>;
+    type QuotedTriple = QuotedTriplePtr;
+    type Graph = MemGraph;
+
+    fn create_blank_node(&mut self) -> BlankNodePtr {
+        self.highest_blank_node += 1;
+        BlankNodePtr {
+            graph_id: self.graph_id,
+            node_id: self.highest_blank_node,
+        }
+    }
+    fn create_iri<'a, I: 'a>(&mut self, iri: &I) -> IRIPtr
+        where I: graph::IRIPtr<'a>
+    {
+        IRIPtr { iri: self.find_string(iri.as_str()).unwrap_or_else(|| Arc::from(iri.as_str())) }
+    }
+    fn create_literal<'a, L: 'a>(&mut self, literal: &L) -> LiteralPtr
+        where L: graph::LiteralPtr<'a>
+    {
+        match literal.language() {
+            Some(language) => {
+                let language = self.create_language(language);
+                self.create_literal_language(literal.as_str(), &language)
+            }
+            None => {
+                let datatype = self.create_datatype(literal.datatype_str());
+                self.create_literal_datatype(literal.as_str(), &datatype)
+            }
+        }
+    }
+    fn create_datatype(&mut self, datatype: &str) -> DatatypePtr {
+        DatatypePtr {
+            datatype: self.find_string(datatype).unwrap_or_else(|| Arc::from(datatype)),
+        }
+    }
+    fn create_language(&mut self, language: &str) -> Arc<str> {
+        self.find_string(language).unwrap_or_else(|| Arc::from(language))
+    }
+    fn create_literal_datatype(&mut self, value: &str, datatype: &DatatypePtr) -> LiteralPtr {
+        LiteralPtr {
+            lexical: self.find_string(value).unwrap_or_else(|| Arc::from(value)),
+            datatype: datatype.datatype.clone(),
+            language: None,
+        }
+    }
+    fn create_literal_language(&mut self, value: &str, language: &Arc<str>) -> LiteralPtr {
+        LiteralPtr {
+            lexical: self.find_string(value).unwrap_or_else(|| Arc::from(value)),
+            datatype: self.create_datatype(constants::RDF_LANG_STRING).datatype,
+            language: Some(language.clone()),
+        }
+    }
+    fn create_quoted_triple(&mut self,
+                            subject: &graph::WriterBlankNodeOrIRI<'g, Self>,
+                            predicate: &IRIPtr,
+                            object: &graph::WriterResource<'g, Self>)
+                            -> QuotedTriplePtr {
+        QuotedTriplePtr {
+            triple: Arc::new((writer_subject(subject), predicate.clone(), writer_object(object))),
+        }
+    }
+    fn add_blank_blank(&mut self,
+                       subject: &BlankNodePtr,
+                       predicate: &IRIPtr,
+                       object: &BlankNodePtr) {
+        self.insert(Subject::BlankNode(*subject),
+                    predicate.clone(),
+                    Object::BlankNode(*object));
+    }
+    fn add_blank_iri(&mut self, subject: &BlankNodePtr, predicate: &IRIPtr, object: &IRIPtr) {
+        self.insert(Subject::BlankNode(*subject),
+                    predicate.clone(),
+                    Object::IRI(object.clone()));
+    }
+    fn add_blank_literal(&mut self,
+                         subject: &BlankNodePtr,
+                         predicate: &IRIPtr,
+                         object: &LiteralPtr) {
+        self.insert(Subject::BlankNode(*subject),
+                    predicate.clone(),
+                    Object::Literal(object.clone()));
+    }
+    fn add_iri_blank(&mut self, subject: &IRIPtr, predicate: &IRIPtr, object: &BlankNodePtr) {
+        self.insert(Subject::IRI(subject.clone()),
+                    predicate.clone(),
+                    Object::BlankNode(*object));
+    }
+    fn add_iri_iri(&mut self, subject: &IRIPtr, predicate: &IRIPtr, object: &IRIPtr) {
+        self.insert(Subject::IRI(subject.clone()),
+                    predicate.clone(),
+                    Object::IRI(object.clone()));
+    }
+    fn add_iri_literal(&mut self, subject: &IRIPtr, predicate: &IRIPtr, object: &LiteralPtr) {
+        self.insert(Subject::IRI(subject.clone()),
+                    predicate.clone(),
+                    Object::Literal(object.clone()));
+    }
+    fn add_quoted(&mut self,
+                  subject: &graph::WriterBlankNodeOrIRI<'g, Self>,
+                  predicate: &IRIPtr,
+                  object: &graph::WriterResource<'g, Self>) {
+        self.insert(writer_subject(subject), predicate.clone(), writer_object(object));
+    }
+    fn collect(self) -> MemGraph {
+        self
+    }
+}
+
+/// Write triples to a writer.
+///
+/// Each blank node in the triples becomes a new blank node of the writer.
+pub fn write_triples<'a, 'w, I, W>(triples: I, writer: &mut W)
+    where I: IntoIterator<Item = &'a (Subject, IRIPtr, Object)>,
+          W: graph::GraphWriter<'w>
+{
+    let mut blank_nodes = BTreeMap::new();
+    for (subject, predicate, object) in triples {
+        let subject = to_writer_subject(subject, writer, &mut blank_nodes);
+        let predicate = writer.create_iri(predicate);
+        let object = to_writer_object(object, writer, &mut blank_nodes);
+        writer.add(&subject, &predicate, &object);
+    }
+}
+
+/// Copies the blank nodes of a graph into a `MemGraph`.
+struct Translator<'g, G>
+    where G: graph::Graph<'g>
+{
+    blank_nodes: BTreeMap<G::BlankNodePtr, BlankNodePtr>,
+}
+
+impl<'g, G> graph::ResourceTranslator<'g> for Translator<'g, G>
+    where G: graph::Graph<'g>
+{
+    type Graph = G;
+    type GraphWriter = MemGraph;
+    fn translate_blank_node(&mut self,
+                            w: &mut MemGraph,
+                            blank_node: &G::BlankNodePtr)
+                            -> BlankNodePtr {
+        use graph::GraphWriter;
+        *self.blank_nodes.entry(blank_node.clone()).or_insert_with(|| w.create_blank_node())
+    }
+}
+
+fn to_writer_blank_node<'w, W>(blank_node: BlankNodePtr,
+                               writer: &mut W,
+                               blank_nodes: &mut BTreeMap<BlankNodePtr, W::BlankNode>)
+                               -> W::BlankNode
+    where W: graph::GraphWriter<'w>
+{
+    blank_nodes.entry(blank_node).or_insert_with(|| writer.create_blank_node()).clone()
+}
+
+fn to_writer_subject<'w, W>(subject: &Subject,
+                            writer: &mut W,
+                            blank_nodes: &mut BTreeMap<BlankNodePtr, W::BlankNode>)
+                            -> graph::WriterBlankNodeOrIRI<'w, W>
+    where W: graph::GraphWriter<'w>
+{
+    match *subject {
+        Subject::BlankNode(b) => {
+            graph::WriterBlankNodeOrIRI::BlankNode(to_writer_blank_node(b, writer, blank_nodes),
+                                                   PhantomData)
+        }
+        Subject::QuotedTriple(ref t) => {
+            graph::WriterBlankNodeOrIRI::QuotedTriple(to_writer_quoted_triple(t,
+                                                                              writer,
+                                                                              blank_nodes))
+        }
+        Subject::IRI(ref i) => graph::WriterBlankNodeOrIRI::IRI(writer.create_iri(i)),
+    }
+}
+
+fn to_writer_object<'w, W>(object: &Object,
+                           writer: &mut W,
+                           blank_nodes: &mut BTreeMap<BlankNodePtr, W::BlankNode>)
+                           -> graph::WriterResource<'w, W>
+    where W: graph::GraphWriter<'w>
+{
+    match *object {
+        Object::BlankNode(b) => {
+            graph::WriterResource::BlankNode(to_writer_blank_node(b, writer, blank_nodes),
+                                             PhantomData)
+        }
+        Object::QuotedTriple(ref t) => {
+            graph::WriterResource::QuotedTriple(to_writer_quoted_triple(t, writer, blank_nodes))
+        }
+        Object::IRI(ref i) => graph::WriterResource::IRI(writer.create_iri(i)),
+        Object::Literal(ref l) => graph::WriterResource::Literal(writer.create_literal(l)),
+    }
+}
+
+fn to_writer_quoted_triple<'w, W>(triple: &QuotedTriplePtr,
+                                  writer: &mut W,
+                                  blank_nodes: &mut BTreeMap<BlankNodePtr, W::BlankNode>)
+                                  -> W::QuotedTriple
+    where W: graph::GraphWriter<'w>
+{
+    let subject = to_writer_subject(&triple.triple.0, writer, blank_nodes);
+    let predicate = writer.create_iri(&triple.triple.1);
+    let object = to_writer_object(&triple.triple.2, writer, blank_nodes);
+    writer.create_quoted_triple(&subject, &predicate, &object)
+}
+
+fn writer_subject<'g>(subject: &graph::WriterBlankNodeOrIRI<'g, MemGraph>) -> Subject {
+    match *subject {
+        graph::WriterBlankNodeOrIRI::BlankNode(b, _) => Subject::BlankNode(b),
+        graph::WriterBlankNodeOrIRI::IRI(ref i) => Subject::IRI(i.clone()),
+        graph::WriterBlankNodeOrIRI::QuotedTriple(ref t) => Subject::QuotedTriple(t.clone()),
+    }
+}
+
+fn writer_object<'g>(object: &graph::WriterResource<'g, MemGraph>) -> Object {
+    match *object {
+        graph::WriterResource::BlankNode(b, _) => Object::BlankNode(b),
+        graph::WriterResource::IRI(ref i) => Object::IRI(i.clone()),
+        graph::WriterResource::Literal(ref l) => Object::Literal(l.clone()),
+        graph::WriterResource::QuotedTriple(ref t) => Object::QuotedTriple(t.clone()),
+    }
+}
+
+#[test]
+fn test_parse() {
+    use graphs::tel::Graph64;
+    use test_support::{ntriples, turtle_graph, TURTLE};
+    let graph: MemGraph = turtle_graph(TURTLE);
+    let tel: Graph64 = turtle_graph(TURTLE);
+    assert_eq!(graph.len(), tel.len());
+    // tel does not sort literals by value and numbers blank nodes differently
+    let normalize = |ntriples: String| {
+        let mut lines: Vec<String> = ntriples.lines()
+            .map(|l| {
+                let terms: Vec<_> = l.split(' ')
+                    .map(|t| if t.starts_with("_:") { "_:" } else { t })
+                    .collect();
+                terms.join(" ")
+            })
+            .collect();
+        lines.sort();
+        lines
+    };
+    assert_eq!(normalize(ntriples(&graph)), normalize(ntriples(&tel)));
+}
+
+#[test]
+fn test_insert_remove() {
+    use graph::{Graph, GraphWriter, IRIPtr, Triple};
+    use test_support::turtle_graph;
+    let mut graph: MemGraph = turtle_graph("@prefix : <http://a.example/> .
+:s :p :o, \"o\"@en ; :q _:b .
+_:b :p :o .
+");
+    assert_eq!(graph.len(), 4);
+    let s = Subject::IRI(graph.find_iri("http://a.example/s").unwrap());
+    let p = graph.find_iri("http://a.example/p").unwrap();
+    let o = Object::IRI(graph.find_iri("http://a.example/o").unwrap());
+    assert!(!graph.insert(s.clone(), p.clone(), o.clone()));
+    assert_eq!(graph.iter_o(&o.to_resource()).count(), 2);
+    assert!(graph.remove(&s, &p, &o));
+    assert!(!graph.remove(&s, &p, &o));
+    assert!(!graph.contains(&s, &p, &o));
+    assert_eq!(graph.len(), 3);
+    assert_eq!(graph.iter_s_p(&s.to_blank_node_or_iri(), &p).count(), 1);
+    let triples: Vec<_> = graph.iter_o(&o.to_resource()).collect();
+    assert_eq!(triples.len(), 1);
+    assert!(triples[0].subject().is_blank_node());
+    // remove all triples about the blank node
+    let b = graph.iter_s(&s.to_blank_node_or_iri())
+        .filter_map(|t| t.object().as_blank_node().cloned())
+        .next()
+        .unwrap();
+    let about_b: Vec<_> = graph.iter_s(&Subject::BlankNode(b).to_blank_node_or_iri())
+        .map(|t| t.to_owned())
+        .collect();
+    for (s, p, o) in &about_b {
+        assert!(graph.remove(s, p, o));
+    }
+    // the IRI is no longer used
+    assert!(graph.find_iri("http://a.example/o").is_none());
+    assert_eq!(graph.iter_o(&o.to_resource()).count(), 0);
+    let literal = graph.find_literal("o", "", Some("en")).unwrap();
+    assert_eq!(graph.iter_o_p(&Object::Literal(literal).to_resource(), &p).count(), 1);
+    // insert new terms
+    let r = graph.create_iri(&"http://a.example/r");
+    assert!(graph.insert(s.clone(), r.clone(), o.clone()));
+    assert_eq!(graph.find_iri("http://a.example/o").unwrap().as_str(), "http://a.example/o");
+    let ps: Vec<_> = graph.iter_s(&s.to_blank_node_or_iri())
+        .map(|t| t.predicate().as_str().to_string())
+        .collect();
+    assert_eq!(ps, vec!["http://a.example/p", "http://a.example/q", "http://a.example/r"]);
+    let all: Vec<_> = graph.iter().map(|t| t.to_owned()).collect();
+    for (s, p, o) in all {
+        assert!(graph.remove(&s, &p, &o));
+    }
+    assert!(graph.is_empty());
+    assert!(graph.strings.is_empty());
+    assert!(graph.ops.is_empty());
+    assert!(graph.pso.is_empty());
+}
+
+#[test]
+fn test_quoted_triples() {
+    use graph::{Graph, Triple};
+    use test_support::turtle_graph;
+    let mut graph: MemGraph = turtle_graph("@prefix : <http://a.example/> .
+:s :p << :a :b :c >> .
+");
+    let t = graph.iter().next().unwrap().object();
+    let quoted = t.as_quoted_triple().unwrap().clone();
+    assert_eq!(graph.iter_o(&t).count(), 1);
+    assert!(graph.find_iri("http://a.example/c").is_some());
+    let (s, p, o) = graph.iter().next().unwrap().to_owned();
+    assert_eq!(o, Object::QuotedTriple(quoted));
+    assert!(graph.remove(&s, &p, &o));
+    assert!(graph.find_iri("http://a.example/c").is_none());
+}
+
+#[test]
+#[should_panic]
+fn test_foreign_blank_node() {
+    use graph::GraphWriter;
+    let mut other = MemGraph::new();
+    let b = other.create_blank_node();
+    let mut graph = MemGraph::new();
+    let p = graph.create_iri(&"p");
+    graph.insert(Subject::BlankNode(b), p.clone(), Object::IRI(p));
+}
+
+#[test]
+fn test_iter_p() {
+    use graph::{Graph, IRIPtr, Triple};
+    use test_support::turtle_graph;
+    let mut graph: MemGraph = turtle_graph("@prefix : <http://a.example/> .
+:b :p :x ; :q :x .
+:a :p :x, \"a\" .
+");
+    let p = graph.find_iri("http://a.example/p").unwrap();
+    let subjects: Vec<_> = graph.iter_p(&p)
+        .map(|t| t.subject().as_iri().unwrap().as_str().to_string())
+        .collect();
+    assert_eq!(subjects, vec!["http://a.example/a", "http://a.example/a", "http://a.example/b"]);
+    let x = Object::IRI(graph.find_iri("http://a.example/x").unwrap());
+    assert_eq!(graph.iter_p_o(&p, &x.to_resource()).count(), 2);
+    let (s, p, o) = graph.iter_p(&p).next().unwrap().to_owned();
+    assert!(graph.remove(&s, &p, &o));
+    assert_eq!(graph.iter_p(&p).count(), 2);
+    assert_eq!(graph.iter_p(&graph.find_iri("http://a.example/x").unwrap()).count(), 0);
+}
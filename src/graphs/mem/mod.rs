@@ -0,0 +1,34 @@
+//! A mutable memory-based RDF graph.
+//!
+//! `MemGraph` implements `rome::graph::Graph` and `rome::graph::GraphWriter`.
+//! Unlike the graphs in `tel`, which are immutable once they are collected,
+//! triples can be inserted into and removed from a `MemGraph` at any time.
+//!
+//! The triples are kept in three sorted sets: by subject, predicate, object,
+//! by object, predicate, subject and by predicate, subject, object. Strings
+//! are shared between the triples that use them and are dropped when the
+//! last triple that uses them is removed.
+//!
+//! The pointers of a `MemGraph` own their values, so they can be kept while
+//! the graph is changed.
+//!
+//! ```
+//! use rome::graph::{Graph, GraphWriter};
+//! use rome::graphs::mem::{MemGraph, Object, Subject};
+//!
+//! let mut graph = MemGraph::new();
+//! let alice = graph.create_iri(&"http://example.org/alice");
+//! let knows = graph.create_iri(&"http://xmlns.com/foaf/0.1/knows");
+//! let bob = graph.create_iri(&"http://example.org/bob");
+//! graph.insert(Subject::IRI(alice.clone()), knows.clone(), Object::IRI(bob.clone()));
+//! assert_eq!(graph.iter().count(), 1);
+//! graph.remove(&Subject::IRI(alice), &knows, &Object::IRI(bob));
+//! assert!(graph.find_iri("http://example.org/bob").is_none());
+//! ```
+
+mod graph;
+mod triple;
+
+pub use self::graph::{write_triples, MemGraph};
+pub use self::triple::{BlankNodePtr, DatatypePtr, IRIPtr, LiteralPtr, Object, QuotedTriplePtr,
+                       Subject};
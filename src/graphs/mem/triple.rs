@@ -0,0 +1,323 @@
+use graph;
+use iter::SortedIterator;
+use std::collections::btree_set;
+use std::fmt;
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+/// A blank node in a `MemGraph`.
+#[derive (Clone,Copy,PartialEq,Eq,PartialOrd,Ord,Hash)]
+pub struct BlankNodePtr {
+    /// The id of the graph that created the blank node.
+    pub graph_id: u32,
+    /// The number of the blank node in the graph.
+    pub node_id: u32,
+}
+impl<'g> graph::BlankNodePtr<'g> for BlankNodePtr {}
+impl fmt::Display for BlankNodePtr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.node_id)
+    }
+}
+impl fmt::Debug for BlankNodePtr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "_:{}", self.node_id)
+    }
+}
+
+/// An IRI in a `MemGraph`.
+#[derive (Clone,PartialEq,Eq,PartialOrd,Ord,Hash)]
+pub struct IRIPtr {
+    /// The IRI.
+    pub iri: Arc<str>,
+}
+impl<'g> graph::IRIPtr<'g> for IRIPtr {
+    fn as_str(&self) -> &str {
+        &self.iri
+    }
+}
+impl fmt::Debug for IRIPtr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "<{}>", self.iri)
+    }
+}
+
+/// The datatype of a literal in a `MemGraph`.
+#[derive (Clone,PartialEq,Eq,PartialOrd,Ord,Hash)]
+pub struct DatatypePtr {
+    /// The datatype IRI.
+    pub datatype: Arc<str>,
+}
+impl<'g> graph::DatatypePtr<'g> for DatatypePtr {
+    fn as_str(&self) -> &str {
+        &self.datatype
+    }
+}
+
+/// A literal in a `MemGraph`.
+///
+/// Literals are ordered by lexical value, datatype and language.
+#[derive (Clone,PartialEq,Eq,PartialOrd,Ord,Hash)]
+pub struct LiteralPtr {
+    /// The lexical value.
+    pub lexical: Arc<str>,
+    /// The datatype IRI.
+    pub datatype: Arc<str>,
+    /// The language, if the datatype is `rdf:langString`.
+    pub language: Option<Arc<str>>,
+}
+impl<'g> graph::LiteralPtr<'g> for LiteralPtr {
+    type DatatypePtr = DatatypePtr;
+    fn as_str(&self) -> &str {
+        &self.lexical
+    }
+    fn datatype(&self) -> DatatypePtr {
+        DatatypePtr { datatype: self.datatype.clone() }
+    }
+    fn datatype_str(&self) -> &str {
+        &self.datatype
+    }
+    fn language(&self) -> Option<&str> {
+        self.language.as_deref()
+    }
+}
+impl fmt::Debug for LiteralPtr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.language {
+            Some(ref language) => write!(f, "{:?}@{}", self.lexical, language),
+            None => write!(f, "{:?}^^<{}>", self.lexical, self.datatype),
+        }
+    }
+}
+
+/// A quoted triple in a `MemGraph`.
+#[derive (Clone,PartialEq,Eq,PartialOrd,Ord,Hash,Debug)]
+pub struct QuotedTriplePtr {
+    /// The subject, predicate and object of the quoted triple.
+    pub triple: Arc<(Subject, IRIPtr, Object)>,
+}
+impl<'g> graph::Triple<'g, BlankNodePtr, IRIPtr, LiteralPtr, QuotedTriplePtr>
+    for QuotedTriplePtr {
+    fn subject(&self) -> BlankNodeOrIRI<'g> {
+        self.triple.0.to_blank_node_or_iri()
+    }
+    fn predicate(&self) -> IRIPtr {
+        self.triple.1.clone()
+    }
+    fn object(&self) -> Resource<'g> {
+        self.triple.2.to_resource()
+    }
+}
+impl<'g> graph::QuotedTriplePtr<'g, BlankNodePtr, IRIPtr, LiteralPtr> for QuotedTriplePtr {}
+
+pub type BlankNodeOrIRI<'g> = graph::BlankNodeOrIRI<'g, BlankNodePtr, IRIPtr, QuotedTriplePtr>;
+pub type Resource<'g> = graph::Resource<'g, BlankNodePtr, IRIPtr, LiteralPtr, QuotedTriplePtr>;
+
+/// The subject of a triple in a `MemGraph`.
+///
+/// Unlike `rome::graph::BlankNodeOrIRI`, a `Subject` is not tied to the
+/// lifetime of the graph, so it can be kept while the graph changes.
+/// Subjects are sorted in the order that `GraphCollection` expects.
+#[derive (Clone,PartialEq,Eq,PartialOrd,Ord,Hash,Debug)]
+pub enum Subject {
+    /// This is a blank node.
+    BlankNode(BlankNodePtr),
+    /// This is a quoted triple.
+    QuotedTriple(QuotedTriplePtr),
+    /// This is an IRI.
+    IRI(IRIPtr),
+}
+impl Subject {
+    /// The smallest possible subject.
+    pub fn min() -> Subject {
+        Subject::BlankNode(BlankNodePtr {
+            graph_id: 0,
+            node_id: 0,
+        })
+    }
+    /// Convert the subject to a `BlankNodeOrIRI`.
+    pub fn to_blank_node_or_iri<'g>(&self) -> BlankNodeOrIRI<'g> {
+        match *self {
+            Subject::BlankNode(b) => graph::BlankNodeOrIRI::BlankNode(b, PhantomData),
+            Subject::QuotedTriple(ref t) => graph::BlankNodeOrIRI::QuotedTriple(t.clone()),
+            Subject::IRI(ref i) => graph::BlankNodeOrIRI::IRI(i.clone()),
+        }
+    }
+    /// Convert the subject to an `Object`.
+    pub fn to_object(&self) -> Object {
+        match *self {
+            Subject::BlankNode(b) => Object::BlankNode(b),
+            Subject::QuotedTriple(ref t) => Object::QuotedTriple(t.clone()),
+            Subject::IRI(ref i) => Object::IRI(i.clone()),
+        }
+    }
+}
+impl<'a, 'g> From<&'a BlankNodeOrIRI<'g>> for Subject {
+    fn from(subject: &'a BlankNodeOrIRI<'g>) -> Subject {
+        match *subject {
+            graph::BlankNodeOrIRI::BlankNode(b, _) => Subject::BlankNode(b),
+            graph::BlankNodeOrIRI::IRI(ref i) => Subject::IRI(i.clone()),
+            graph::BlankNodeOrIRI::QuotedTriple(ref t) => Subject::QuotedTriple(t.clone()),
+        }
+    }
+}
+
+/// The object of a triple in a `MemGraph`.
+///
+/// Unlike `rome::graph::Resource`, an `Object` is not tied to the lifetime
+/// of the graph, so it can be kept while the graph changes.
+/// Objects are sorted in the order that `GraphCollection` expects.
+#[derive (Clone,PartialEq,Eq,PartialOrd,Ord,Hash,Debug)]
+pub enum Object {
+    /// This is a blank node.
+    BlankNode(BlankNodePtr),
+    /// This is a quoted triple.
+    QuotedTriple(QuotedTriplePtr),
+    /// This is an IRI.
+    IRI(IRIPtr),
+    /// This is a literal.
+    Literal(LiteralPtr),
+}
+impl Object {
+    /// The smallest possible object.
+    pub fn min() -> Object {
+        Object::BlankNode(BlankNodePtr {
+            graph_id: 0,
+            node_id: 0,
+        })
+    }
+    /// Convert the object to a `Resource`.
+    pub fn to_resource<'g>(&self) -> Resource<'g> {
+        match *self {
+            Object::BlankNode(b) => graph::Resource::BlankNode(b, PhantomData),
+            Object::QuotedTriple(ref t) => graph::Resource::QuotedTriple(t.clone()),
+            Object::IRI(ref i) => graph::Resource::IRI(i.clone()),
+            Object::Literal(ref l) => graph::Resource::Literal(l.clone()),
+        }
+    }
+    /// Convert the object to a `Subject`, unless it is a literal.
+    pub fn to_subject(&self) -> Option<Subject> {
+        match *self {
+            Object::BlankNode(b) => Some(Subject::BlankNode(b)),
+            Object::QuotedTriple(ref t) => Some(Subject::QuotedTriple(t.clone())),
+            Object::IRI(ref i) => Some(Subject::IRI(i.clone())),
+            Object::Literal(_) => None,
+        }
+    }
+}
+impl<'a, 'g> From<&'a Resource<'g>> for Object {
+    fn from(object: &'a Resource<'g>) -> Object {
+        match *object {
+            graph::Resource::BlankNode(b, _) => Object::BlankNode(b),
+            graph::Resource::IRI(ref i) => Object::IRI(i.clone()),
+            graph::Resource::Literal(ref l) => Object::Literal(l.clone()),
+            graph::Resource::QuotedTriple(ref t) => Object::QuotedTriple(t.clone()),
+        }
+    }
+}
+
+/// A triple in one of the indexes of a `MemGraph`.
+///
+/// Triples from the indexes by subject and by predicate have `Subject` as
+/// `A` and `Object` as `B` and are sorted by subject, predicate, object.
+/// Triples from the index by object have `Object` as `A` and `Subject` as
+/// `B` and are sorted by object, predicate, subject.
+#[derive (Clone,PartialEq,Eq,PartialOrd,Ord,Debug)]
+pub struct Triple<'g, A: 'g, B: 'g> {
+    pub first: &'g A,
+    pub predicate: &'g IRIPtr,
+    pub second: &'g B,
+}
+impl<'g> Triple<'g, Subject, Object> {
+    /// Copy the subject, predicate and object out of the graph.
+    pub fn to_owned(&self) -> (Subject, IRIPtr, Object) {
+        (self.first.clone(), self.predicate.clone(), self.second.clone())
+    }
+}
+impl<'g> Triple<'g, Object, Subject> {
+    /// Copy the subject, predicate and object out of the graph.
+    pub fn to_owned(&self) -> (Subject, IRIPtr, Object) {
+        (self.second.clone(), self.predicate.clone(), self.first.clone())
+    }
+}
+impl<'g> graph::Triple<'g, BlankNodePtr, IRIPtr, LiteralPtr, QuotedTriplePtr>
+    for Triple<'g, Subject, Object> {
+    fn subject(&self) -> BlankNodeOrIRI<'g> {
+        self.first.to_blank_node_or_iri()
+    }
+    fn predicate(&self) -> IRIPtr {
+        self.predicate.clone()
+    }
+    fn object(&self) -> Resource<'g> {
+        self.second.to_resource()
+    }
+}
+impl<'g> graph::Triple<'g, BlankNodePtr, IRIPtr, LiteralPtr, QuotedTriplePtr>
+    for Triple<'g, Object, Subject> {
+    fn subject(&self) -> BlankNodeOrIRI<'g> {
+        self.second.to_blank_node_or_iri()
+    }
+    fn predicate(&self) -> IRIPtr {
+        self.predicate.clone()
+    }
+    fn object(&self) -> Resource<'g> {
+        self.first.to_resource()
+    }
+}
+
+/// Iterator over a range of the index by subject or the index by object of
+/// a `MemGraph`.
+///
+/// The iterator stops at the first triple that does not have the given
+/// first term and predicate.
+pub struct RangeIter<'g, A: 'g, B: 'g> {
+    pub iter: Option<btree_set::Range<'g, (A, IRIPtr, B)>>,
+    pub first: Option<A>,
+    pub predicate: Option<IRIPtr>,
+}
+impl<'g, A, B> Iterator for RangeIter<'g, A, B>
+    where A: PartialEq
+{
+    type Item = Triple<'g, A, B>;
+    fn next(&mut self) -> Option<Self::Item> {
+        let triple = self.iter.as_mut().and_then(|i| i.next())?;
+        if self.first.as_ref().is_some_and(|f| *f != triple.0) ||
+           self.predicate.as_ref().is_some_and(|p| *p != triple.1) {
+            self.iter = None;
+            return None;
+        }
+        Some(Triple {
+            first: &triple.0,
+            predicate: &triple.1,
+            second: &triple.2,
+        })
+    }
+}
+impl<'g, A, B> SortedIterator for RangeIter<'g, A, B>
+    where A: Ord,
+          B: Ord
+{
+}
+
+/// Iterator over the triples with one predicate in the index by predicate
+/// of a `MemGraph`.
+pub struct PredicateIter<'g> {
+    pub iter: Option<btree_set::Range<'g, (IRIPtr, Subject, Object)>>,
+    pub predicate: IRIPtr,
+}
+impl<'g> Iterator for PredicateIter<'g> {
+    type Item = Triple<'g, Subject, Object>;
+    fn next(&mut self) -> Option<Self::Item> {
+        let triple = self.iter.as_mut().and_then(|i| i.next())?;
+        if triple.0 != self.predicate {
+            self.iter = None;
+            return None;
+        }
+        Some(Triple {
+            first: &triple.1,
+            predicate: &triple.0,
+            second: &triple.2,
+        })
+    }
+}
+impl<'g> SortedIterator for PredicateIter<'g> {}
@@ -1,12 +1,14 @@
 //! A number of Graph implementations.
 #[macro_use]
 pub mod collection;
+pub mod mem;
 pub mod tel;
 
 use self::collection::*;
 
 use std::cmp;
 
+impl_triple_cmp_wrap!(mem::MemGraph);
 impl_triple_cmp_wrap!(tel::Graph64);
 impl_triple_cmp_wrap!(tel::Graph128);
 
@@ -14,6 +16,10 @@ impl_triple_cmp_wrap!(tel::Graph128);
 mod test {
     graph_collection!(test_collection(0: super::super::tel::Graph64,
                                       1: super::super::tel::Graph128));
+    graph_collection!(mem_collection(0: super::super::mem::MemGraph,
+                                     1: super::super::tel::Graph64));
+
+    use graph::Graph;
 
     #[test]
     fn test() {
@@ -31,4 +37,205 @@ mod test {
         let g = test_collection::GraphCollection::new((&g1, &g2));
         assert_eq!(g.iter().count(), 2);
     }
+
+    #[test]
+    fn test_mem() {
+        use graph::{Graph, GraphWriter, IRIPtr, LiteralPtr, Triple};
+        let mut g1 = super::mem::MemGraph::new();
+        let s1 = g1.create_iri(&"s");
+        let p1 = g1.create_iri(&"p");
+        g1.add_iri_iri(&s1, &p1, &s1);
+        let xsd_string = g1.create_datatype("http://www.w3.org/2001/XMLSchema#string");
+        let l1 = g1.create_literal_datatype("o", &xsd_string);
+        g1.add_iri_literal(&s1, &p1, &l1);
+        let mut gw2 = super::tel::GraphCreator::with_capacity(0);
+        let s2 = gw2.create_iri(&"s");
+        let p2 = gw2.create_iri(&"p");
+        let o2 = gw2.create_iri(&"o");
+        gw2.add_iri_iri(&s2, &p2, &s2);
+        gw2.add_iri_iri(&s2, &p2, &o2);
+        let g2: super::tel::Graph64 = gw2.collect();
+        let g = mem_collection::GraphCollection::new((&g1, &g2));
+        assert_eq!(g.iter().count(), 3);
+        let p = g.find_iri("p").unwrap();
+        let objects: Vec<_> = g.iter_p(&p).map(|t| t.object()).collect();
+        assert_eq!(objects.len(), 3);
+        assert_eq!(objects[0].as_iri().map(|o| o.as_str()), Some("o"));
+        assert_eq!(objects[1].as_iri().map(|o| o.as_str()), Some("s"));
+        assert_eq!(objects[2].as_literal().map(|o| o.as_str()), Some("o"));
+        let s = g.find_iri("s").unwrap();
+        assert_eq!(g.iter_p_o(&p, &s.to_resource()).count(), 1);
+    }
+
+    #[test]
+    fn test_iter_o() {
+        use graph::{IRIPtr, Triple};
+        use test_support::turtle_graph;
+        let g1: super::tel::Graph64 = turtle_graph("<s> <q> <o> . <o> <p> <o> .");
+        let g2: super::tel::Graph128 = turtle_graph("<s> <p> <o> . <s> <q> <o> .");
+        let g = test_collection::GraphCollection::new((&g1, &g2));
+        // sorted by predicate and subject and the triple in both graphs once
+        let o = g.find_iri("http://a.example/o").unwrap();
+        let triples: Vec<_> = g.iter_o(&o.to_resource())
+            .map(|t| {
+                format!("{} {}", t.predicate().as_str(), t.subject().as_iri().unwrap().as_str())
+            })
+            .collect();
+        assert_eq!(triples,
+                   ["http://a.example/p http://a.example/o",
+                    "http://a.example/p http://a.example/s",
+                    "http://a.example/q http://a.example/s"]);
+    }
+
+    #[test]
+    fn test_blank_nodes_first() {
+        use graph::IRIPtr;
+        use test_support::turtle_graph;
+        // the graphs sort blank nodes before IRIs, so the triple that is in
+        // both graphs is still merged when one graph has a blank node before it
+        let g1: super::tel::Graph64 = turtle_graph("<s> <p> <o> .");
+        let g2: super::tel::Graph128 = turtle_graph("_:b <p> _:c . <s> <p> <o> . <s> <p> _:c .");
+        let g = test_collection::GraphCollection::new((&g1, &g2));
+        assert_eq!(g.iter().count(), 3);
+        let p = g.find_iri("http://a.example/p").unwrap();
+        assert_eq!(g.iter_p(&p).count(), 3);
+        let s = g.find_iri("http://a.example/s").unwrap();
+        assert_eq!(g.iter_s(&s.to_blank_node_or_iri()).count(), 2);
+    }
+
+    #[test]
+    fn test_quoted_triples() {
+        use graph::{GraphWriter, IRIPtr, LiteralPtr, QuotedTriplePtr, Triple};
+        use io::NTriplesParser;
+        let s1 = "<< <http://a.example/s> <http://a.example/p> _:o >> \
+                  <http://a.example/q> <http://a.example/o> .\n";
+        let mut gw1 = super::tel::GraphCreator::with_capacity(0);
+        for step in NTriplesParser::new(s1, &mut gw1).unwrap() {
+            step.unwrap();
+        }
+        let g1: super::tel::Graph64 = gw1.collect();
+        let s2 = "<http://a.example/o> <http://a.example/r> \
+                  << <http://a.example/s> <http://a.example/p> \"1\" >> .\n";
+        let mut gw2 = super::tel::GraphCreator::with_capacity(0);
+        for step in NTriplesParser::new(s2, &mut gw2).unwrap() {
+            step.unwrap();
+        }
+        let g2: super::tel::Graph128 = gw2.collect();
+        let g = test_collection::GraphCollection::new((&g1, &g2));
+        assert_eq!(g.iter().count(), 2);
+        let quoted: Vec<_> = g.iter().filter_map(|t| t.subject().as_quoted_triple().cloned())
+            .chain(g.iter().filter_map(|t| t.object().as_quoted_triple().cloned()))
+            .collect();
+        assert_eq!(quoted.len(), 2);
+        assert!(quoted[0].object().as_blank_node().is_some());
+        assert_eq!(quoted[1].object().as_literal().map(|l| l.as_str()), Some("1"));
+        for q in &quoted {
+            assert_eq!(q.subject().as_iri().map(|s| s.as_str()), Some("http://a.example/s"));
+            assert_eq!(q.predicate().as_str(), "http://a.example/p");
+        }
+        assert_eq!(g.iter_s(&quoted[0].to_blank_node_or_iri()).count(), 1);
+        assert_eq!(g.iter_o(&quoted[1].to_resource()).count(), 1);
+        assert_eq!(g.iter_o(&quoted[0].to_resource()).count(), 0);
+    }
+
+    const TURTLE: &'static str = "@prefix : <http://a.example/> .
+:s :p :o, \"o\", _:b ; :q :o .
+:o :p :s ; :q \"o\" .
+_:b :p :o .
+";
+
+    fn check_match_pattern<'g, G>(graph: &'g G)
+        where G: Graph<'g>
+    {
+        use graph::{BlankNodeOrIRI, IRIPtr, LiteralPtr, Resource, Triple};
+        // the IRIs and literals of a collection are compared by value
+        let same_subject = |a: &BlankNodeOrIRI<'g,
+                                               G::BlankNodePtr,
+                                               G::IRIPtr,
+                                               G::QuotedTriplePtr>,
+                            b: &BlankNodeOrIRI<'g,
+                                               G::BlankNodePtr,
+                                               G::IRIPtr,
+                                               G::QuotedTriplePtr>| {
+            match (a.as_iri(), b.as_iri()) {
+                (Some(a), Some(b)) => a.as_str() == b.as_str(),
+                _ => a == b,
+            }
+        };
+        let same_object = |a: &Resource<'g,
+                                        G::BlankNodePtr,
+                                        G::IRIPtr,
+                                        G::LiteralPtr,
+                                        G::QuotedTriplePtr>,
+                           b: &Resource<'g,
+                                        G::BlankNodePtr,
+                                        G::IRIPtr,
+                                        G::LiteralPtr,
+                                        G::QuotedTriplePtr>| {
+            match (a.as_iri(), b.as_iri(), a.as_literal(), b.as_literal()) {
+                (Some(a), Some(b), _, _) => a.as_str() == b.as_str(),
+                (_, _, Some(a), Some(b)) => {
+                    a.as_str() == b.as_str() && a.datatype_str() == b.datatype_str()
+                }
+                _ => a == b,
+            }
+        };
+        let iri = |i: &str| graph.find_iri(&format!("http://a.example/{}", i)).unwrap();
+        let subjects = vec![iri("s").to_blank_node_or_iri(), iri("o").to_blank_node_or_iri()];
+        let predicates = vec![iri("p"), iri("q")];
+        let literal = graph.find_literal("o", "http://www.w3.org/2001/XMLSchema#string", None);
+        let objects = vec![iri("o").to_resource(), Resource::Literal(literal.unwrap())];
+        let mut subject_options: Vec<Option<&_>> = subjects.iter().map(Some).collect();
+        subject_options.push(None);
+        let mut predicate_options: Vec<Option<&_>> = predicates.iter().map(Some).collect();
+        predicate_options.push(None);
+        let mut object_options: Vec<Option<&_>> = objects.iter().map(Some).collect();
+        object_options.push(None);
+        for s in &subject_options {
+            for p in &predicate_options {
+                for o in &object_options {
+                    let mut expected: Vec<_> = graph.iter()
+                        .filter(|t| s.is_none_or(|s| same_subject(s, &t.subject())) &&
+                                    p.is_none_or(|p| p.as_str() == t.predicate().as_str()) &&
+                                    o.is_none_or(|o| same_object(o, &t.object())))
+                        .map(|t| (t.subject(), t.predicate(), t.object()))
+                        .collect();
+                    let mut found: Vec<_> = graph.match_pattern(*s, *p, *o)
+                        .map(|t| (t.subject(), t.predicate(), t.object()))
+                        .collect();
+                    found.sort();
+                    expected.sort();
+                    assert!(found == expected);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_match_pattern() {
+        use graph::GraphWriter;
+        use io::TurtleParser;
+        let mut creator = super::tel::GraphCreator::with_capacity(0);
+        for step in TurtleParser::new(TURTLE, "http://a.example/", &mut creator).unwrap() {
+            step.unwrap();
+        }
+        let graph: super::tel::Graph64 = creator.collect();
+        check_match_pattern(&graph);
+        let mut mem_graph = super::mem::MemGraph::new();
+        for step in TurtleParser::new(TURTLE, "http://a.example/", &mut mem_graph).unwrap() {
+            step.unwrap();
+        }
+        check_match_pattern(&mem_graph);
+        // the IRIs and the literal of the pattern are in both graphs
+        let mut creator = super::tel::GraphCreator::with_capacity(0);
+        let turtle = "@prefix : <http://a.example/> .
+:s :p :o . :o :q \"o\" ; :r :s .
+";
+        for step in TurtleParser::new(turtle, "http://a.example/", &mut creator).unwrap() {
+            step.unwrap();
+        }
+        let other: super::tel::Graph128 = creator.collect();
+        let collection = test_collection::GraphCollection::new((&graph, &other));
+        check_match_pattern(&collection);
+    }
 }
@@ -0,0 +1,350 @@
+//! A binary file format for `tel` graphs.
+//!
+//! The file contains the sorted string collections and triple indexes as they
+//! are in memory, so a graph can be loaded without parsing or sorting.
+//! All numbers are little endian. The layout is:
+//!
+//! - the magic bytes `ROMETEL\0`
+//! - the format version, `u32`
+//! - the number of bytes per triple, `u32`: 8 for `Graph64`, 16 for `Graph128`
+//! - the highest blank node number, `u32`
+//! - the id of the `rdf:langString` datatype, `u32`
+//! - the strings and the datatypes and languages, each as the number of
+//!   bytes (`u64`), the UTF-8 bytes, the number of offsets (`u64`) and the
+//!   offsets (`u32`); the bytes and the offsets are padded with zeros to a
+//!   multiple of 8 bytes
+//! - the SPO and the OPS triples, each as a count (`u64`) and the triples
+//! - the index by predicate as the number of positions (`u64`), which is 0
+//!   for a graph without the index, and the positions in the SPO triples
+//!   (`u32`), padded with zeros to a multiple of 8 bytes
+//! - the quoted triples as a count (`u64`) and pairs of a number (`u32`) and
+//!   a triple
+//! - a CRC-32 of all preceding bytes, `u32`
+//!
+//! Version 1 of the format has no padding and versions 1 and 2 have no index
+//! by predicate. They can still be loaded, but not mapped.
+
+use crc::Crc32;
+use error::{Error, Result};
+use rand;
+use std::io::{Read, Write};
+use super::compact_triple::*;
+use super::graph::*;
+use super::mapped::Array;
+use super::string_collector::*;
+
+pub const MAGIC: &'static [u8; 8] = b"ROMETEL\0";
+pub const VERSION: u32 = 3;
+/// write triples in blocks of this many bytes
+const BLOCK_SIZE: usize = 1 << 16;
+
+struct ChecksumWriter<'a, W: 'a> {
+    writer: &'a mut W,
+    crc: Crc32,
+    pos: usize,
+}
+
+impl<'a, W> ChecksumWriter<'a, W>
+    where W: Write
+{
+    fn write(&mut self, bytes: &[u8]) -> Result<()> {
+        self.crc.update(bytes);
+        self.writer.write_all(bytes)?;
+        self.pos += bytes.len();
+        Ok(())
+    }
+    /// Write zeros up to a multiple of 8 bytes.
+    fn write_padding(&mut self) -> Result<()> {
+        let len = (8 - self.pos % 8) % 8;
+        self.write(&[0; 8][..len])
+    }
+    fn write_u32(&mut self, value: u32) -> Result<()> {
+        self.write(&value.to_le_bytes())
+    }
+    fn write_u64(&mut self, value: u64) -> Result<()> {
+        self.write(&value.to_le_bytes())
+    }
+    fn write_strings(&mut self, strings: &StringCollection) -> Result<()> {
+        self.write_u64(strings.buffer().len() as u64)?;
+        self.write(strings.buffer())?;
+        self.write_padding()?;
+        self.write_u32s(strings.starts())
+    }
+    /// Write the number of values, the values and the padding.
+    fn write_u32s(&mut self, values: &[u32]) -> Result<()> {
+        self.write_u64(values.len() as u64)?;
+        let mut buffer = Vec::with_capacity(BLOCK_SIZE);
+        for value in values {
+            buffer.extend_from_slice(&value.to_le_bytes());
+            if buffer.len() >= BLOCK_SIZE {
+                self.write(&buffer)?;
+                buffer.clear();
+            }
+        }
+        self.write(&buffer)?;
+        self.write_padding()
+    }
+    fn write_triples<T>(&mut self, triples: &[T]) -> Result<()>
+        where T: CompactTriple<u32>
+    {
+        self.write_u64(triples.len() as u64)?;
+        let mut buffer = Vec::with_capacity(BLOCK_SIZE);
+        for t in triples {
+            t.write_bytes(&mut buffer);
+            if buffer.len() >= BLOCK_SIZE {
+                self.write(&buffer)?;
+                buffer.clear();
+            }
+        }
+        self.write(&buffer)
+    }
+}
+
+struct ChecksumReader<'a, R: 'a> {
+    reader: &'a mut R,
+    crc: Crc32,
+    pos: u64,
+    /// version 1 has no padding
+    padded: bool,
+}
+
+impl<'a, R> ChecksumReader<'a, R>
+    where R: Read
+{
+    /// Read `len` bytes. The buffer grows while reading, so a corrupt length
+    /// gives an error instead of a large allocation.
+    fn read(&mut self, len: u64) -> Result<Vec<u8>> {
+        let mut bytes = Vec::new();
+        (&mut self.reader).take(len).read_to_end(&mut bytes)?;
+        if (bytes.len() as u64) < len {
+            return Err(Error::Custom("The binary graph is truncated."));
+        }
+        self.crc.update(&bytes);
+        self.pos += len;
+        Ok(bytes)
+    }
+    fn read_padding(&mut self) -> Result<()> {
+        if self.padded {
+            let len = (8 - self.pos % 8) % 8;
+            self.read(len)?;
+        }
+        Ok(())
+    }
+    fn read_u32(&mut self) -> Result<u32> {
+        let mut value = [0; 4];
+        value.copy_from_slice(&self.read(4)?);
+        Ok(u32::from_le_bytes(value))
+    }
+    fn read_u64(&mut self) -> Result<u64> {
+        let mut value = [0; 8];
+        value.copy_from_slice(&self.read(8)?);
+        Ok(u64::from_le_bytes(value))
+    }
+    fn read_strings(&mut self) -> Result<StringCollection> {
+        let len = self.read_u64()?;
+        let buffer = String::from_utf8(self.read(len)?)?;
+        self.read_padding()?;
+        let starts = self.read_u32s()?;
+        StringCollection::from_parts(buffer, starts)
+            .ok_or(Error::Custom("The binary graph has invalid strings."))
+    }
+    /// Read the number of values, the values and the padding.
+    fn read_u32s(&mut self) -> Result<Vec<u32>> {
+        let len = self.read_u64()?;
+        let values = self.read(len.saturating_mul(4))?
+            .chunks(4)
+            .map(|c| u32::from_le_bytes([c[0], c[1], c[2], c[3]]))
+            .collect();
+        self.read_padding()?;
+        Ok(values)
+    }
+    fn read_triples<T>(&mut self) -> Result<Vec<T>>
+        where T: CompactTriple<u32>
+    {
+        let len = self.read_u64()?;
+        let bytes = self.read(len.saturating_mul(T::byte_size() as u64))?;
+        Ok(bytes.chunks(T::byte_size()).map(T::read_bytes).collect())
+    }
+}
+
+impl<SPO, OPS> Graph<SPO, OPS>
+    where SPO: CompactTriple<u32>,
+          OPS: CompactTriple<u32>
+{
+    /// Write the graph in a versioned binary format with a checksum.
+    ///
+    /// The graph can be read again with `load`. The index by predicate is
+    /// written too, if the graph has one.
+    pub fn save<W>(&self, writer: &mut W) -> Result<()>
+        where W: Write
+    {
+        let checksum = {
+            let mut w = ChecksumWriter {
+                writer: writer,
+                crc: Crc32::ieee(),
+                pos: 0,
+            };
+            w.write(MAGIC)?;
+            w.write_u32(VERSION)?;
+            w.write_u32(SPO::byte_size() as u32)?;
+            w.write_u32(self.d.highest_blank_node)?;
+            w.write_u32(self.d.lang_string_datatype_id)?;
+            w.write_strings(&self.d.strings)?;
+            w.write_strings(&self.d.datatype_or_lang)?;
+            w.write_triples(&self.d.spo)?;
+            w.write_triples(&self.d.ops)?;
+            w.write_u32s(self.d.pso.as_ref().map_or(&[], |pso| &pso[..]))?;
+            w.write_u64(self.d.quoted_triples.len() as u64)?;
+            let mut buffer = Vec::new();
+            for &(node_id, ref t) in &self.d.quoted_triples {
+                buffer.clear();
+                buffer.extend_from_slice(&node_id.to_le_bytes());
+                t.write_bytes(&mut buffer);
+                w.write(&buffer)?;
+            }
+            w.crc.checksum()
+        };
+        writer.write_all(&checksum.to_le_bytes())?;
+        Ok(())
+    }
+    /// Read a graph that was written with `save`.
+    ///
+    /// An error is returned if the data is not a binary graph, has another
+    /// version or triple size, or does not match its checksum.
+    pub fn load<R>(reader: &mut R) -> Result<Graph<SPO, OPS>>
+        where R: Read
+    {
+        let (d, checksum) = {
+            let mut r = ChecksumReader {
+                reader: reader,
+                crc: Crc32::ieee(),
+                pos: 0,
+                padded: true,
+            };
+            if r.read(MAGIC.len() as u64)?[..] != MAGIC[..] {
+                return Err(Error::Custom("The data is not a binary graph."));
+            }
+            let version = r.read_u32()?;
+            r.padded = version != 1;
+            if version == 0 || version > VERSION {
+                return Err(Error::String(format!("Version {} of the binary graph format is \
+                                                  not supported.",
+                                                 version)));
+            }
+            if r.read_u32()? != SPO::byte_size() as u32 {
+                return Err(Error::Custom("The binary graph has triples of another size."));
+            }
+            let highest_blank_node = r.read_u32()?;
+            let lang_string_datatype_id = r.read_u32()?;
+            let strings = r.read_strings()?;
+            let datatype_or_lang = r.read_strings()?;
+            let spo = r.read_triples()?;
+            let ops = r.read_triples()?;
+            let pso = if version >= 3 { r.read_u32s()? } else { Vec::new() };
+            if !pso.is_empty() && (pso.len() != spo.len() ||
+                                   pso.iter().any(|&p| p as usize >= spo.len())) {
+                return Err(Error::Custom("The binary graph has an invalid predicate index."));
+            }
+            let len = r.read_u64()?;
+            let size = 4 + SPO::byte_size();
+            let quoted_triples = r.read(len.saturating_mul(size as u64))?
+                .chunks(size)
+                .map(|c| (u32::from_le_bytes([c[0], c[1], c[2], c[3]]), SPO::read_bytes(&c[4..])))
+                .collect();
+            let d = GraphData {
+                graph_id: rand::random::<u32>(),
+                strings: strings,
+                datatype_or_lang: datatype_or_lang,
+                spo: Array::from(spo),
+                ops: Array::from(ops),
+                pso: if pso.is_empty() { None } else { Some(Array::from(pso)) },
+                lang_string_datatype_id: lang_string_datatype_id,
+                highest_blank_node: highest_blank_node,
+                quoted_triples: quoted_triples,
+            };
+            (d, r.crc.checksum())
+        };
+        let mut expected = [0; 4];
+        reader.read_exact(&mut expected)?;
+        if u32::from_le_bytes(expected) != checksum {
+            return Err(Error::Custom("The checksum of the binary graph does not match."));
+        }
+        Ok(Graph { d: d })
+    }
+}
+
+#[test]
+fn test_save_load() {
+    use graph::Graph;
+    use graphs::tel::{Graph64, Graph128};
+    use test_support::{ntriples, turtle_graph, TURTLE};
+    let graph: Graph64 = turtle_graph(TURTLE);
+    let mut bytes = Vec::new();
+    graph.save(&mut bytes).unwrap();
+    let loaded = Graph64::load(&mut &bytes[..]).unwrap();
+    assert_eq!(ntriples(&loaded), ntriples(&graph));
+    // a Graph128 cannot read a Graph64
+    assert!(Graph128::load(&mut &bytes[..]).is_err());
+
+    let graph: Graph128 = turtle_graph(TURTLE);
+    let mut bytes = Vec::new();
+    graph.save(&mut bytes).unwrap();
+    let loaded = Graph128::load(&mut &bytes[..]).unwrap();
+    assert_eq!(ntriples(&loaded), ntriples(&graph));
+    assert!(!loaded.has_predicate_index());
+
+    // the index by predicate is saved too
+    let mut graph: Graph64 = turtle_graph(TURTLE);
+    graph.index_predicates();
+    let mut bytes = Vec::new();
+    graph.save(&mut bytes).unwrap();
+    let loaded = Graph64::load(&mut &bytes[..]).unwrap();
+    assert!(loaded.has_predicate_index());
+    let p = loaded.find_iri("http://a.example/p").unwrap();
+    assert_eq!(loaded.iter_p(&p).count(), 4);
+}
+
+#[test]
+fn test_load_errors() {
+    use graphs::tel::Graph64;
+    use test_support::{turtle_graph, TURTLE};
+    let graph: Graph64 = turtle_graph(TURTLE);
+    let mut bytes = Vec::new();
+    graph.save(&mut bytes).unwrap();
+    // a changed byte does not match the checksum
+    let mut corrupt = bytes.clone();
+    let last = corrupt.len() - 10;
+    corrupt[last] ^= 1;
+    assert!(Graph64::load(&mut &corrupt[..]).is_err());
+    // truncated data
+    assert!(Graph64::load(&mut &bytes[..bytes.len() - 1]).is_err());
+    // another version
+    let mut version = bytes.clone();
+    version[8] = VERSION as u8 + 1;
+    assert!(Graph64::load(&mut &version[..]).is_err());
+    assert!(Graph64::load(&mut &b"@prefix : <urn:> ."[..]).is_err());
+}
+
+#[test]
+fn test_load_version_2() {
+    use crc::Crc32;
+    use graphs::tel::Graph64;
+    use test_support::{ntriples, turtle_graph};
+    let graph: Graph64 = turtle_graph("@prefix : <http://a.example/> .
+:s :p :o , \"o\" .
+");
+    let mut bytes = Vec::new();
+    graph.save(&mut bytes).unwrap();
+    // version 2 has no index by predicate: drop its empty count, which comes
+    // before the count of quoted triples and the checksum
+    let len = bytes.len();
+    bytes.drain(len - 20..len - 12);
+    bytes[8] = 2;
+    let len = bytes.len();
+    let mut crc = Crc32::ieee();
+    crc.update(&bytes[..len - 4]);
+    bytes[len - 4..].copy_from_slice(&crc.checksum().to_le_bytes());
+    let loaded = Graph64::load(&mut &bytes[..]).unwrap();
+    assert_eq!(ntriples(&loaded), ntriples(&graph));
+    assert!(!loaded.has_predicate_index());
+}
@@ -1,5 +1,5 @@
 use std::fmt::Debug;
-#[derive (Debug,Clone)]
+#[derive (Debug,Clone,PartialEq)]
 pub enum TripleObjectType {
     BlankNode = 0,
     IRI = 1,
@@ -32,4 +32,10 @@ pub trait CompactTriple<T>: Ord + Copy + Eq + Debug
     fn set_predicate(&mut self, predicate: T);
     fn set_object(&mut self, object: T);
     fn set_datatype_or_lang(&mut self, datatype_or_lang: T);
+    /// The number of bytes in the binary form of the triple.
+    fn byte_size() -> usize;
+    /// Append the binary form of the triple to `bytes`.
+    fn write_bytes(&self, bytes: &mut Vec<u8>);
+    /// Read a triple from the first `byte_size()` bytes of `bytes`.
+    fn read_bytes(bytes: &[u8]) -> Self;
 }
@@ -0,0 +1,582 @@
+use constants;
+use error::{Error, Result};
+use graph;
+use graph::{GraphName, GraphWriter};
+use std::cmp;
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::rc::Rc;
+use super::compact_triple::*;
+use super::graph::*;
+use super::graph_creator::{CreateQuotedTriple, GraphCreator};
+use super::triple;
+use super::union_graph::UnionGraph;
+
+/// An RDF dataset with a default graph and named graphs.
+pub struct Dataset<SPO, OPS>
+    where SPO: CompactTriple<u32>,
+          OPS: CompactTriple<u32>
+{
+    /// the names of the named graphs, sorted
+    names: Vec<Name>,
+    /// the default graph followed by the named graphs in the order of `names`
+    union_graph: UnionGraph<SPO, OPS>,
+}
+
+/// The name of a graph in a `Dataset`. Blank nodes are stored by number.
+#[derive (PartialEq,Eq,PartialOrd,Ord)]
+enum Name {
+    BlankNode(u32),
+    IRI(String),
+}
+
+impl Name {
+    fn cmp_graph_name<'g, SPO, OPS>(&self,
+                                    name: &GraphName<'g, triple::BlankNodePtr<'g, SPO, OPS>>)
+                                    -> cmp::Ordering
+        where SPO: CompactTriple<u32>,
+              OPS: CompactTriple<u32>
+    {
+        match (self, *name) {
+            (&Name::BlankNode(n), GraphName::BlankNode(b)) => n.cmp(&b.node_id),
+            (&Name::BlankNode(_), GraphName::IRI(_)) => cmp::Ordering::Less,
+            (&Name::IRI(_), GraphName::BlankNode(_)) => cmp::Ordering::Greater,
+            (Name::IRI(iri), GraphName::IRI(name)) => iri.as_str().cmp(name),
+        }
+    }
+    /// The name as it is seen from `graph`.
+    fn graph_name<'g, SPO, OPS>(&'g self,
+                                graph: &'g Graph<SPO, OPS>)
+                                -> GraphName<'g, triple::BlankNodePtr<'g, SPO, OPS>>
+        where SPO: CompactTriple<u32>,
+              OPS: CompactTriple<u32>
+    {
+        match *self {
+            Name::BlankNode(n) => {
+                GraphName::BlankNode(triple::BlankNodePtr {
+                    graph_id: graph.d.graph_id,
+                    node_id: n,
+                    phantom: PhantomData,
+                })
+            }
+            Name::IRI(ref iri) => GraphName::IRI(iri),
+        }
+    }
+}
+
+impl<'g, SPO: 'g, OPS: 'g> graph::Dataset<'g> for Dataset<SPO, OPS>
+    where SPO: CompactTriple<u32>,
+          OPS: CompactTriple<u32>
+{
+    type Graph = Graph<SPO, OPS>;
+    type UnionGraph = UnionGraph<SPO, OPS>;
+    type GraphIter = Graphs<'g, SPO, OPS>;
+    fn default_graph(&'g self) -> &'g Graph<SPO, OPS> {
+        &self.union_graph.graphs[0]
+    }
+    fn named_graph(&'g self,
+                   name: &GraphName<'g, triple::BlankNodePtr<'g, SPO, OPS>>)
+                   -> Option<&'g Graph<SPO, OPS>> {
+        self.names
+            .binary_search_by(|n| n.cmp_graph_name(name))
+            .ok()
+            .map(|i| &self.union_graph.graphs[i + 1])
+    }
+    fn graphs(&'g self) -> Graphs<'g, SPO, OPS> {
+        Graphs {
+            dataset: self,
+            pos: 0,
+        }
+    }
+    fn union_graph(&'g self) -> &'g UnionGraph<SPO, OPS> {
+        &self.union_graph
+    }
+}
+
+/// Iterator over the graphs in a `Dataset`.
+pub struct Graphs<'a, SPO: 'a, OPS: 'a>
+    where SPO: CompactTriple<u32>,
+          OPS: CompactTriple<u32>
+{
+    dataset: &'a Dataset<SPO, OPS>,
+    pos: usize,
+}
+
+impl<'a, SPO, OPS> Iterator for Graphs<'a, SPO, OPS>
+    where SPO: CompactTriple<u32>,
+          OPS: CompactTriple<u32>
+{
+    type Item = (Option<GraphName<'a, triple::BlankNodePtr<'a, SPO, OPS>>>, &'a Graph<SPO, OPS>);
+    fn next(&mut self) -> Option<Self::Item> {
+        let pos = self.pos;
+        self.pos += 1;
+        let graph = match self.dataset.union_graph.graphs.get(pos) {
+            Some(graph) => graph,
+            None => return None,
+        };
+        if pos == 0 {
+            Some((None, graph))
+        } else {
+            Some((Some(self.dataset.names[pos - 1].graph_name(graph)), graph))
+        }
+    }
+}
+
+#[derive (Clone,Copy,PartialEq,Eq,Hash)]
+enum NameKey {
+    BlankNode(u32),
+    IRI(u32),
+}
+
+/// Creates a `Dataset` by writing a `GraphCreator` for every graph.
+///
+/// The terms are kept in the `DatasetCreator` so that they can be used with
+/// any of the graphs.
+pub struct DatasetCreator<SPO, OPS>
+    where SPO: CompactTriple<u32>,
+          OPS: CompactTriple<u32>
+{
+    strings: Vec<Rc<str>>,
+    string_ids: HashMap<Rc<str>, u32>,
+    highest_blank_node: u32,
+    default_graph: GraphCreator<SPO, OPS>,
+    named_graphs: Vec<(NameKey, GraphCreator<SPO, OPS>)>,
+    graph_ids: HashMap<NameKey, usize>,
+    current: Option<usize>,
+    quoted_triples: HashMap<u32, (Term, u32, Term)>,
+}
+
+/// A term in a quoted triple.
+#[derive (Clone,Copy)]
+enum Term {
+    BlankNode(u32),
+    IRI(u32),
+    Literal(DatasetLiteral),
+    QuotedTriple(u32),
+}
+
+#[derive (Clone,Copy)]
+pub struct DatasetBlankNode {
+    node_id: u32,
+}
+#[derive (Clone)]
+pub struct DatasetIRI {
+    iri: u32,
+}
+#[derive (Clone,Copy)]
+pub struct DatasetLiteral {
+    lexical: u32,
+    datatype: u32,
+    language: Option<u32>,
+}
+#[derive (Clone)]
+pub struct DatasetDatatype {
+    datatype: u32,
+}
+pub struct DatasetLanguage {
+    language: u32,
+}
+#[derive (Clone,Copy)]
+pub struct DatasetQuotedTriple {
+    node_id: u32,
+}
+
+impl<SPO, OPS> DatasetCreator<SPO, OPS>
+    where SPO: CompactTriple<u32>,
+          OPS: CompactTriple<u32>
+{
+    /// Create a new `DatasetCreator`. `capacity` is used for the default
+    /// graph.
+    pub fn with_capacity(capacity: usize) -> DatasetCreator<SPO, OPS> {
+        DatasetCreator {
+            strings: Vec::new(),
+            string_ids: HashMap::new(),
+            highest_blank_node: 0,
+            default_graph: GraphCreator::with_capacity(capacity),
+            named_graphs: Vec::new(),
+            graph_ids: HashMap::new(),
+            current: None,
+            quoted_triples: HashMap::new(),
+        }
+    }
+    fn add_string(&mut self, string: &str) -> u32 {
+        if let Some(id) = self.string_ids.get(string) {
+            return *id;
+        }
+        let id = self.strings.len() as u32;
+        let string: Rc<str> = Rc::from(string);
+        self.strings.push(string.clone());
+        self.string_ids.insert(string, id);
+        id
+    }
+    /// The strings and the creator of the current graph.
+    fn target(&mut self) -> (&[Rc<str>], &mut GraphCreator<SPO, OPS>) {
+        let (strings, _, creator) = self.quoted_target();
+        (strings, creator)
+    }
+    /// The strings, the quoted triples and the creator of the current graph.
+    fn quoted_target(&mut self) -> (&[Rc<str>], &Quoted, &mut GraphCreator<SPO, OPS>) {
+        let creator = match self.current {
+            None => &mut self.default_graph,
+            Some(i) => &mut self.named_graphs[i].1,
+        };
+        (&self.strings, &self.quoted_triples, creator)
+    }
+}
+
+type Quoted = HashMap<u32, (Term, u32, Term)>;
+
+fn subject_term<'g, SPO: 'g, OPS: 'g>(subject: &graph::WriterBlankNodeOrIRI<'g,
+                                                                          DatasetCreator<SPO, OPS>>)
+                                      -> Term
+    where SPO: CompactTriple<u32>,
+          OPS: CompactTriple<u32>
+{
+    match *subject {
+        graph::WriterBlankNodeOrIRI::BlankNode(ref b, _) => Term::BlankNode(b.node_id),
+        graph::WriterBlankNodeOrIRI::IRI(ref i) => Term::IRI(i.iri),
+        graph::WriterBlankNodeOrIRI::QuotedTriple(ref t) => Term::QuotedTriple(t.node_id),
+    }
+}
+
+fn object_term<'g, SPO: 'g, OPS: 'g>(object: &graph::WriterResource<'g, DatasetCreator<SPO, OPS>>)
+                                     -> Term
+    where SPO: CompactTriple<u32>,
+          OPS: CompactTriple<u32>
+{
+    match *object {
+        graph::WriterResource::BlankNode(ref b, _) => Term::BlankNode(b.node_id),
+        graph::WriterResource::IRI(ref i) => Term::IRI(i.iri),
+        graph::WriterResource::Literal(l) => Term::Literal(l),
+        graph::WriterResource::QuotedTriple(ref t) => Term::QuotedTriple(t.node_id),
+    }
+}
+
+fn create_subject<'g, SPO: 'g, OPS: 'g>(strings: &[Rc<str>],
+                                        quoted: &Quoted,
+                                        creator: &mut GraphCreator<SPO, OPS>,
+                                        term: &Term)
+                                        -> graph::WriterBlankNodeOrIRI<'g, GraphCreator<SPO, OPS>>
+    where SPO: CompactTriple<u32>,
+          OPS: CompactTriple<u32>
+{
+    match create_object(strings, quoted, creator, term) {
+        graph::WriterResource::BlankNode(b, p) => graph::WriterBlankNodeOrIRI::BlankNode(b, p),
+        graph::WriterResource::IRI(i) => graph::WriterBlankNodeOrIRI::IRI(i),
+        graph::WriterResource::QuotedTriple(t) => graph::WriterBlankNodeOrIRI::QuotedTriple(t),
+        graph::WriterResource::Literal(_) => panic!("A literal cannot be a subject."),
+    }
+}
+
+fn create_object<'g, SPO: 'g, OPS: 'g>(strings: &[Rc<str>],
+                                       quoted: &Quoted,
+                                       creator: &mut GraphCreator<SPO, OPS>,
+                                       term: &Term)
+                                       -> graph::WriterResource<'g, GraphCreator<SPO, OPS>>
+    where SPO: CompactTriple<u32>,
+          OPS: CompactTriple<u32>
+{
+    match *term {
+        Term::BlankNode(b) => graph::WriterResource::BlankNode(creator.blank_node(b), PhantomData),
+        Term::IRI(i) => {
+            graph::WriterResource::IRI(create_iri(strings, creator, &DatasetIRI { iri: i }))
+        }
+        Term::Literal(ref l) => graph::WriterResource::Literal(create_literal(strings, creator, l)),
+        Term::QuotedTriple(t) => {
+            graph::WriterResource::QuotedTriple(create_quoted(strings, quoted, creator, t))
+        }
+    }
+}
+
+/// Create the quoted triple and the quoted triples in it in `creator`.
+fn create_quoted<'g, SPO: 'g, OPS: 'g>(strings: &[Rc<str>],
+                                       quoted: &Quoted,
+                                       creator: &mut GraphCreator<SPO, OPS>,
+                                       node_id: u32)
+                                       -> CreateQuotedTriple
+    where SPO: CompactTriple<u32>,
+          OPS: CompactTriple<u32>
+{
+    let (ref subject, predicate, ref object) = quoted[&node_id];
+    let s = create_subject(strings, quoted, creator, subject);
+    let p = create_iri(strings, creator, &DatasetIRI { iri: predicate });
+    let o = create_object(strings, quoted, creator, object);
+    creator.quoted_triple(node_id, &s, &p, &o)
+}
+
+fn create_iri<'g, SPO: 'g, OPS: 'g>(strings: &[Rc<str>],
+                                    creator: &mut GraphCreator<SPO, OPS>,
+                                    iri: &DatasetIRI)
+                                    -> <GraphCreator<SPO, OPS> as GraphWriter<'g>>::IRI
+    where SPO: CompactTriple<u32>,
+          OPS: CompactTriple<u32>
+{
+    creator.create_iri(&&*strings[iri.iri as usize])
+}
+
+fn create_literal<'g, SPO: 'g, OPS: 'g>(strings: &[Rc<str>],
+                                        creator: &mut GraphCreator<SPO, OPS>,
+                                        literal: &DatasetLiteral)
+                                        -> <GraphCreator<SPO, OPS> as GraphWriter<'g>>::Literal
+    where SPO: CompactTriple<u32>,
+          OPS: CompactTriple<u32>
+{
+    let lexical = &strings[literal.lexical as usize];
+    match literal.language {
+        Some(language) => {
+            let language = creator.create_language(&strings[language as usize]);
+            creator.create_literal_language(lexical, &language)
+        }
+        None => {
+            let datatype = creator.create_datatype(&strings[literal.datatype as usize]);
+            creator.create_literal_datatype(lexical, &datatype)
+        }
+    }
+}
+
+impl<'g, SPO: 'g, OPS: 'g> graph::GraphWriter<'g> for DatasetCreator<SPO, OPS>
+    where SPO: CompactTriple<u32>,
+          OPS: CompactTriple<u32>
+{
+    type BlankNode = DatasetBlankNode;
+    type IRI = DatasetIRI;
+    type Literal = DatasetLiteral;
+    type Datatype = DatasetDatatype;
+    type Language = DatasetLanguage;
+    type QuotedTriple = DatasetQuotedTriple;
+    type Graph = Graph<SPO, OPS>;
+    fn create_blank_node(&mut self) -> DatasetBlankNode {
+        self.highest_blank_node += 1;
+        DatasetBlankNode { node_id: self.highest_blank_node }
+    }
+    fn create_iri<'a, I: 'a>(&mut self, i: &I) -> DatasetIRI
+        where I: graph::IRIPtr<'a>
+    {
+        DatasetIRI { iri: self.add_string(i.as_str()) }
+    }
+    fn create_literal<'a, L: 'a>(&mut self, l: &L) -> DatasetLiteral
+        where L: graph::LiteralPtr<'a>
+    {
+        DatasetLiteral {
+            lexical: self.add_string(l.as_str()),
+            datatype: self.add_string(l.datatype_str()),
+            language: l.language().map(|language| self.add_string(language)),
+        }
+    }
+    fn create_datatype(&mut self, datatype: &str) -> DatasetDatatype {
+        DatasetDatatype { datatype: self.add_string(datatype) }
+    }
+    fn create_language(&mut self, language: &str) -> DatasetLanguage {
+        DatasetLanguage { language: self.add_string(language) }
+    }
+    fn create_literal_datatype(&mut self,
+                               value: &str,
+                               datatype: &DatasetDatatype)
+                               -> DatasetLiteral {
+        DatasetLiteral {
+            lexical: self.add_string(value),
+            datatype: datatype.datatype,
+            language: None,
+        }
+    }
+    fn create_literal_language(&mut self,
+                               value: &str,
+                               language: &DatasetLanguage)
+                               -> DatasetLiteral {
+        DatasetLiteral {
+            lexical: self.add_string(value),
+            datatype: self.add_string(constants::RDF_LANG_STRING),
+            language: Some(language.language),
+        }
+    }
+    fn create_quoted_triple(&mut self,
+                            subject: &graph::WriterBlankNodeOrIRI<'g, Self>,
+                            predicate: &DatasetIRI,
+                            object: &graph::WriterResource<'g, Self>)
+                            -> DatasetQuotedTriple {
+        self.highest_blank_node += 1;
+        let node_id = self.highest_blank_node;
+        let triple = (subject_term(subject), predicate.iri, object_term(object));
+        self.quoted_triples.insert(node_id, triple);
+        DatasetQuotedTriple { node_id: node_id }
+    }
+    /// Close the writer and return only the default graph.
+    /// Use `collect_dataset` to get the named graphs too.
+    fn collect(self) -> Graph<SPO, OPS> {
+        self.default_graph.collect()
+    }
+    fn add_blank_blank(&mut self,
+                       subject: &DatasetBlankNode,
+                       predicate: &DatasetIRI,
+                       object: &DatasetBlankNode) {
+        let (strings, creator) = self.target();
+        let s = creator.blank_node(subject.node_id);
+        let p = create_iri(strings, creator, predicate);
+        let o = creator.blank_node(object.node_id);
+        creator.add_blank_blank(&s, &p, &o);
+    }
+    fn add_blank_iri(&mut self,
+                     subject: &DatasetBlankNode,
+                     predicate: &DatasetIRI,
+                     object: &DatasetIRI) {
+        let (strings, creator) = self.target();
+        let s = creator.blank_node(subject.node_id);
+        let p = create_iri(strings, creator, predicate);
+        let o = create_iri(strings, creator, object);
+        graph::GraphWriter::add_blank_iri(creator, &s, &p, &o);
+    }
+    fn add_blank_literal(&mut self,
+                         subject: &DatasetBlankNode,
+                         predicate: &DatasetIRI,
+                         object: &DatasetLiteral) {
+        let (strings, creator) = self.target();
+        let s = creator.blank_node(subject.node_id);
+        let p = create_iri(strings, creator, predicate);
+        let o = create_literal(strings, creator, object);
+        graph::GraphWriter::add_blank_literal(creator, &s, &p, &o);
+    }
+    fn add_iri_blank(&mut self,
+                     subject: &DatasetIRI,
+                     predicate: &DatasetIRI,
+                     object: &DatasetBlankNode) {
+        let (strings, creator) = self.target();
+        let s = create_iri(strings, creator, subject);
+        let p = create_iri(strings, creator, predicate);
+        let o = creator.blank_node(object.node_id);
+        graph::GraphWriter::add_iri_blank(creator, &s, &p, &o);
+    }
+    fn add_iri_iri(&mut self, subject: &DatasetIRI, predicate: &DatasetIRI, object: &DatasetIRI) {
+        let (strings, creator) = self.target();
+        let s = create_iri(strings, creator, subject);
+        let p = create_iri(strings, creator, predicate);
+        let o = create_iri(strings, creator, object);
+        graph::GraphWriter::add_iri_iri(creator, &s, &p, &o);
+    }
+    fn add_iri_literal(&mut self,
+                       subject: &DatasetIRI,
+                       predicate: &DatasetIRI,
+                       object: &DatasetLiteral) {
+        let (strings, creator) = self.target();
+        let s = create_iri(strings, creator, subject);
+        let p = create_iri(strings, creator, predicate);
+        let o = create_literal(strings, creator, object);
+        graph::GraphWriter::add_iri_literal(creator, &s, &p, &o);
+    }
+    fn add_quoted(&mut self,
+                  subject: &graph::WriterBlankNodeOrIRI<'g, Self>,
+                  predicate: &DatasetIRI,
+                  object: &graph::WriterResource<'g, Self>) {
+        let (subject, object) = (subject_term(subject), object_term(object));
+        let (strings, quoted, creator) = self.quoted_target();
+        let s = create_subject(strings, quoted, creator, &subject);
+        let p = create_iri(strings, creator, predicate);
+        let o = create_object(strings, quoted, creator, &object);
+        creator.add(&s, &p, &o);
+    }
+}
+
+impl<'g, SPO: 'g, OPS: 'g> graph::DatasetWriter<'g> for DatasetCreator<SPO, OPS>
+    where SPO: CompactTriple<u32>,
+          OPS: CompactTriple<u32>
+{
+    type Dataset = Dataset<SPO, OPS>;
+    fn set_graph(&mut self,
+                 graph_name: Option<&graph::WriterBlankNodeOrIRI<'g, Self>>)
+                 -> Result<()> {
+        let key = match graph_name {
+            None => {
+                self.current = None;
+                return Ok(());
+            }
+            Some(&graph::WriterBlankNodeOrIRI::BlankNode(ref b, _)) => {
+                NameKey::BlankNode(b.node_id)
+            }
+            Some(&graph::WriterBlankNodeOrIRI::IRI(ref i)) => NameKey::IRI(i.iri),
+            Some(&graph::WriterBlankNodeOrIRI::QuotedTriple(_)) => {
+                return Err(Error::Custom("A quoted triple cannot be a graph name."));
+            }
+        };
+        let named_graphs = &mut self.named_graphs;
+        let i = *self.graph_ids.entry(key).or_insert_with(|| {
+            named_graphs.push((key, GraphCreator::with_capacity(0)));
+            named_graphs.len() - 1
+        });
+        self.current = Some(i);
+        Ok(())
+    }
+    fn collect_dataset(self) -> Dataset<SPO, OPS> {
+        let strings = self.strings;
+        let mut named_graphs: Vec<_> = self.named_graphs
+            .into_iter()
+            .map(|(key, creator)| {
+                let name = match key {
+                    NameKey::BlankNode(n) => Name::BlankNode(n),
+                    NameKey::IRI(i) => Name::IRI(String::from(&*strings[i as usize])),
+                };
+                (name, creator.collect())
+            })
+            .collect();
+        named_graphs.sort_by(|a, b| a.0.cmp(&b.0));
+        let mut graphs = vec![self.default_graph.collect()];
+        let names = named_graphs.into_iter()
+            .map(|(name, graph)| {
+                graphs.push(graph);
+                name
+            })
+            .collect();
+        Dataset {
+            names: names,
+            union_graph: UnionGraph { graphs: graphs },
+        }
+    }
+}
+
+#[test]
+fn test_dataset_creator() {
+    use graph::{Dataset, DatasetWriter, Graph, IRIPtr, Triple, WriterBlankNodeOrIRI,
+                WriterResource};
+    use super::triple64::*;
+    let mut creator: DatasetCreator<Triple64SPO, Triple64OPS> = DatasetCreator::with_capacity(0);
+    let g = WriterBlankNodeOrIRI::IRI(creator.create_iri(&"g"));
+    let b = creator.create_blank_node();
+    let s = WriterBlankNodeOrIRI::BlankNode(b, PhantomData);
+    let p = creator.create_iri(&"p");
+    let o = WriterResource::BlankNode(b, PhantomData);
+    let l = WriterResource::Literal(creator.create_literal(&"l"));
+    creator.add(&s, &p, &l);
+    creator.add_quad(Some(&g), &s, &p, &o).unwrap();
+    creator.add_quad(Some(&s), &s, &p, &l).unwrap();
+    creator.add_quad(Some(&g), &s, &p, &l).unwrap();
+    // a quoted triple cannot name a graph
+    let t = WriterBlankNodeOrIRI::QuotedTriple(creator.create_quoted_triple(&s, &p, &l));
+    assert!(creator.add_quad(Some(&t), &s, &p, &o).is_err());
+    let dataset = creator.collect_dataset();
+    assert_eq!(dataset.default_graph().iter().count(), 1);
+    let names: Vec<_> = dataset.graphs().map(|g| g.0).collect();
+    assert_eq!(names.len(), 3);
+    let blank_node = match names[1] {
+        Some(GraphName::BlankNode(b)) => b,
+        _ => panic!("the first named graph is not named by a blank node"),
+    };
+    assert_eq!(blank_node.to_string(), "1");
+    assert_eq!(names[2], Some(GraphName::IRI("g")));
+    let named = dataset.named_graph(&GraphName::IRI("g")).unwrap();
+    assert_eq!(named.iter().count(), 2);
+    let triple = named.iter().next().unwrap();
+    assert_eq!(triple.predicate().as_str(), "p");
+    // blank nodes keep their number in every graph
+    assert_eq!(format!("{}", triple.subject().as_blank_node().unwrap()), "1");
+    assert!(dataset.named_graph(&GraphName::IRI("h")).is_none());
+    // the blank node that names a graph is the same node as in its triples
+    let by_blank_node = dataset.named_graph(&GraphName::BlankNode(blank_node)).unwrap();
+    let subject = by_blank_node.iter().next().unwrap().subject();
+    assert_eq!(subject.as_blank_node(), Some(&blank_node));
+    // the union graph has no duplicates
+    assert_eq!(dataset.union_graph().iter().count(), 2);
+    let quads: Vec<_> = dataset.quads().map(|q| q.graph_name).collect();
+    assert_eq!(quads,
+               vec![None,
+                    Some(GraphName::BlankNode(blank_node)),
+                    Some(GraphName::IRI("g")),
+                    Some(GraphName::IRI("g"))]);
+}
@@ -3,6 +3,7 @@ use std::cmp;
 use std::marker::PhantomData;
 use super::compact_triple::*;
 use super::iter::*;
+use super::mapped::Array;
 use super::string_collector::*;
 use super::triple::*;
 
@@ -13,10 +14,32 @@ pub struct GraphData<SPO, OPS>
     pub graph_id: u32,
     pub strings: StringCollection,
     pub datatype_or_lang: StringCollection,
-    pub spo: Vec<SPO>,
-    pub ops: Vec<OPS>,
+    pub spo: Array<SPO>,
+    pub ops: Array<OPS>,
+    /// positions in `spo` sorted by predicate, if the predicate index was
+    /// created
+    pub pso: Option<Array<u32>>,
     pub lang_string_datatype_id: u32,
     pub highest_blank_node: u32,
+    /// quoted triples sorted by their number, which they share with the
+    /// blank nodes
+    pub quoted_triples: Vec<(u32, SPO)>,
+}
+
+impl<SPO, OPS> GraphData<SPO, OPS>
+    where SPO: CompactTriple<u32>,
+          OPS: CompactTriple<u32>
+{
+    pub fn quoted_triple(&self, node_id: u32) -> Option<SPO> {
+        self.quoted_triples
+            .binary_search_by_key(&node_id, |q| q.0)
+            .ok()
+            .map(|i| self.quoted_triples[i].1)
+    }
+    pub fn is_quoted_triple(&self, node_id: u32) -> bool {
+        !self.quoted_triples.is_empty() &&
+        self.quoted_triples.binary_search_by_key(&node_id, |q| q.0).is_ok()
+    }
 }
 
 pub struct Graph<SPO, OPS>
@@ -31,7 +54,7 @@ pub trait Index<SPO, OPS, T>
           OPS: CompactTriple<u32>,
           T: CompactTriple<u32>
 {
-    fn index(graph: &GraphData<SPO, OPS>) -> &Vec<T>;
+    fn index(graph: &GraphData<SPO, OPS>) -> &[T];
 }
 
 pub struct SPOIndex<SPO, OPS>
@@ -45,7 +68,7 @@ impl<SPO, OPS> Index<SPO, OPS, SPO> for SPOIndex<SPO, OPS>
     where SPO: CompactTriple<u32>,
           OPS: CompactTriple<u32>
 {
-    fn index(graph: &GraphData<SPO, OPS>) -> &Vec<SPO> {
+    fn index(graph: &GraphData<SPO, OPS>) -> &[SPO] {
         &graph.spo
     }
 }
@@ -61,7 +84,7 @@ impl<SPO, OPS> Index<SPO, OPS, OPS> for OPSIndex<SPO, OPS>
     where SPO: CompactTriple<u32>,
           OPS: CompactTriple<u32>
 {
-    fn index(graph: &GraphData<SPO, OPS>) -> &Vec<OPS> {
+    fn index(graph: &GraphData<SPO, OPS>) -> &[OPS] {
         &graph.ops
     }
 }
@@ -336,32 +359,63 @@ impl<SPO, OPS> Graph<SPO, OPS>
         blank_info.shrink_to_fit();
 
         // translate the blank nodes in spo and ops
-        let mut spo = self.d.spo.clone();
+        let mut spo = self.d.spo.to_vec();
         for t in &mut spo {
             translate_object(t, &translation);
         }
         spo.sort();
-        let mut ops = self.d.ops.clone();
+        let mut ops = self.d.ops.to_vec();
         for t in &mut ops {
             translate_object(t, &translation);
         }
         ops.sort();
+        let mut quoted_triples: Vec<_> = self.d
+            .quoted_triples
+            .iter()
+            .map(|&(node_id, mut t)| {
+                translate_object(&mut t, &translation);
+                (translation[node_id as usize], t)
+            })
+            .collect();
+        quoted_triples.sort();
 
-        Graph {
+        let mut graph = Graph {
             d: GraphData {
                 graph_id: self.d.graph_id,
                 strings: self.d.strings.clone(),
                 datatype_or_lang: self.d.datatype_or_lang.clone(),
-                spo: spo,
-                ops: ops,
+                spo: Array::from(spo),
+                ops: Array::from(ops),
+                pso: None,
                 highest_blank_node: self.d.highest_blank_node,
                 lang_string_datatype_id: self.d.lang_string_datatype_id,
+                quoted_triples: quoted_triples,
             },
+        };
+        if self.has_predicate_index() {
+            graph.index_predicates();
         }
+        graph
     }
     pub fn len(&self) -> usize {
         self.d.spo.len()
     }
+    /// Create an index by predicate, so that `iter_p` does not have to
+    /// look at all triples.
+    ///
+    /// The index takes four bytes per triple. It is written by `save`, so
+    /// a graph that is loaded or mapped keeps it.
+    pub fn index_predicates(&mut self) {
+        let spo = &self.d.spo;
+        let mut pso: Vec<u32> = (0..spo.len() as u32).collect();
+        // the sort is stable, so the triples of a predicate stay sorted
+        pso.sort_by_key(|&pos| spo[pos as usize].predicate());
+        self.d.pso = Some(Array::from(pso));
+    }
+    /// Is there an index by predicate?
+    pub fn has_predicate_index(&self) -> bool {
+        self.d.pso.is_some()
+    }
 }
 
 fn translate_object<T>(t: &mut T, translation: &[u32])
@@ -411,11 +465,13 @@ impl<'g, SPO: 'g, OPS: 'g> graph::Graph<'g> for Graph<SPO, OPS>
     type BlankNodePtr = BlankNodePtr<'g, SPO, OPS>;
     type IRIPtr = IRIPtr<'g, SPO, OPS>;
     type LiteralPtr = LiteralPtr<'g, SPO, OPS>;
+    type QuotedTriplePtr = QuotedTriplePtr<'g, SPO, OPS>;
     type SPOTriple = Triple<'g, SPO, OPS, SPO>;
     type SPOIter = GraphIterator<'g, SPO, OPS, SPO, SPOIndex<SPO, OPS>>;
     type SPORangeIter = TripleRangeIterator<'g, SPO, OPS, SPO, SPOIndex<SPO, OPS>>;
     type OPSTriple = Triple<'g, SPO, OPS, OPS>;
     type OPSRangeIter = TripleRangeIterator<'g, SPO, OPS, OPS, OPSIndex<SPO, OPS>>;
+    type PSORangeIter = PredicateIterator<'g, SPO, OPS>;
     fn iter(&'g self) -> Self::SPOIter {
         GraphIterator {
             graph: &self.d,
@@ -473,6 +529,7 @@ impl<'g, SPO: 'g, OPS: 'g> graph::Graph<'g> for Graph<SPO, OPS>
         let spo = match *subject {
             graph::BlankNodeOrIRI::BlankNode(bn, _) => subject_blank_node(bn.node_id),
             graph::BlankNodeOrIRI::IRI(ref iri) => subject_iri(iri.iri),
+            graph::BlankNodeOrIRI::QuotedTriple(ref t) => subject_blank_node(t.node_id),
         };
         self.iter_subject(spo)
     }
@@ -485,6 +542,9 @@ impl<'g, SPO: 'g, OPS: 'g> graph::Graph<'g> for Graph<SPO, OPS>
                 subject_blank_node_predicate(bn.node_id, predicate.iri)
             }
             graph::BlankNodeOrIRI::IRI(ref iri) => subject_iri_predicate(iri.iri, predicate.iri),
+            graph::BlankNodeOrIRI::QuotedTriple(ref t) => {
+                subject_blank_node_predicate(t.node_id, predicate.iri)
+            }
         };
         self.iter_subject_predicate(spo)
     }
@@ -492,6 +552,7 @@ impl<'g, SPO: 'g, OPS: 'g> graph::Graph<'g> for Graph<SPO, OPS>
         let ops = match *object {
             graph::Resource::BlankNode(bn, _) => object_blank_node(bn.node_id),
             graph::Resource::IRI(ref iri) => object_iri(iri.iri),
+            graph::Resource::QuotedTriple(ref t) => object_blank_node(t.node_id),
             graph::Resource::Literal(ref l) => {
                 match l.language {
                     Some(lang) => object_literal_lang(l.lexical, lang),
@@ -510,6 +571,9 @@ impl<'g, SPO: 'g, OPS: 'g> graph::Graph<'g> for Graph<SPO, OPS>
                 object_blank_node_predicate(bn.node_id, predicate.iri)
             }
             graph::Resource::IRI(ref iri) => object_iri_predicate(iri.iri, predicate.iri),
+            graph::Resource::QuotedTriple(ref t) => {
+                object_blank_node_predicate(t.node_id, predicate.iri)
+            }
             graph::Resource::Literal(ref l) => {
                 match l.language {
                     Some(lang) => object_literal_lang_predicate(l.lexical, lang, predicate.iri),
@@ -519,10 +583,81 @@ impl<'g, SPO: 'g, OPS: 'g> graph::Graph<'g> for Graph<SPO, OPS>
         };
         self.iter_object_predicate(ops)
     }
+    fn iter_p(&'g self, predicate: &IRIPtr<'g, SPO, OPS>) -> Self::PSORangeIter {
+        let spo = &self.d.spo;
+        let predicate = predicate.iri;
+        match self.d.pso {
+            Some(ref pso) => {
+                PredicateIterator {
+                    graph: &self.d,
+                    positions: Some(&pso[..]),
+                    pos: pso.partition_point(|&p| spo[p as usize].predicate() < predicate),
+                    end: pso.partition_point(|&p| spo[p as usize].predicate() <= predicate),
+                    predicate: predicate,
+                }
+            }
+            None => {
+                PredicateIterator {
+                    graph: &self.d,
+                    positions: None,
+                    pos: 0,
+                    end: spo.len(),
+                    predicate: predicate,
+                }
+            }
+        }
+    }
     fn empty_spo_range(&'g self) -> Self::SPORangeIter {
         self.empty_range_iter()
     }
     fn empty_ops_range(&'g self) -> Self::OPSRangeIter {
         self.empty_range_iter()
     }
+    fn empty_pso_range(&'g self) -> Self::PSORangeIter {
+        PredicateIterator {
+            graph: &self.d,
+            positions: None,
+            pos: 0,
+            end: 0,
+            predicate: 0,
+        }
+    }
+}
+
+#[test]
+fn test_iter_p() {
+    use graph::{Graph, IRIPtr, LiteralPtr, Resource, Triple};
+    use graphs::tel::Graph64;
+    use test_support::turtle_graph;
+    let mut graph: Graph64 = turtle_graph("@prefix : <http://a.example/> .
+:b :p :x, \"a\" ; :q :x .
+:a :p \"a\", \"a\"@en ; :q :x .
+_:c :p :a .
+");
+    for index in &[false, true] {
+        if *index {
+            graph.index_predicates();
+        }
+        assert_eq!(graph.has_predicate_index(), *index);
+        let p = graph.find_iri("http://a.example/p").unwrap();
+        let triples: Vec<_> = graph.iter_p(&p).collect();
+        assert_eq!(triples.len(), 5);
+        assert!(triples.iter().all(|t| t.predicate().as_str() == p.as_str()));
+        assert!(triples.windows(2).all(|w| w[0] < w[1]));
+        let q = graph.find_iri("http://a.example/q").unwrap();
+        assert_eq!(graph.iter_p(&q).count(), 2);
+        let x = graph.find_iri("http://a.example/x").unwrap();
+        assert_eq!(graph.iter_p(&x).count(), 0);
+        // objects that are literals
+        let xsd_string = "http://www.w3.org/2001/XMLSchema#string";
+        let a = graph.find_literal("a", xsd_string, None).unwrap();
+        let subjects: Vec<_> = graph.iter_p_o(&p, &Resource::Literal(a.clone()))
+            .map(|t| t.subject().as_iri().unwrap().as_str().to_string())
+            .collect();
+        assert_eq!(subjects, vec!["http://a.example/a", "http://a.example/b"]);
+        let a_en = graph.find_literal("a", "", Some("en")).unwrap();
+        let triples: Vec<_> = graph.iter_o(&Resource::Literal(a_en)).collect();
+        assert_eq!(triples.len(), 1);
+        assert_eq!(triples[0].object().as_literal().unwrap().language(), Some("en"));
+    }
 }
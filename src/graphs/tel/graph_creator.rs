@@ -2,10 +2,12 @@ use constants;
 use graph;
 use rand;
 use std::cmp;
+use std::collections::{BTreeMap, HashMap};
 use std::marker::PhantomData;
 use std::mem;
 use super::compact_triple::*;
 use super::graph::*;
+use super::mapped::Array;
 use super::string_collector::*;
 use super::triple::*;
 #[cfg(test)]
@@ -21,6 +23,7 @@ pub struct GraphCreator<SPO, OPS>
     triples: Vec<SPO>,
     lang_string_datatype_id: StringId,
     highest_blank_node: u32,
+    quoted_triples: Vec<(u32, SPO)>,
     phantom: PhantomData<OPS>,
 }
 
@@ -56,6 +59,7 @@ impl<SPO, OPS> GraphCreator<SPO, OPS>
             triples: Vec::new(),
             lang_string_datatype_id: lang_string_datatype_id,
             highest_blank_node: 0,
+            quoted_triples: Vec::new(),
             phantom: PhantomData,
         }
     }
@@ -94,11 +98,112 @@ impl<SPO, OPS> GraphCreator<SPO, OPS>
     fn add_blank_lit_lang(&mut self, s: u32, p: StringId, o: StringId, lang: StringId) {
         self.add_s_blank(s, p, TripleObjectType::LiteralLang, o.id, lang.id);
     }
+    /// Get the blank node with the given id.
+    ///
+    /// This lets several graph creators share the numbering of blank nodes.
+    pub(crate) fn blank_node<'g>(&mut self, node_id: u32) -> BlankNodePtr<'g, SPO, OPS> {
+        self.highest_blank_node = cmp::max(self.highest_blank_node, node_id);
+        BlankNodePtr {
+            graph_id: self.graph_id,
+            node_id: node_id,
+            phantom: PhantomData,
+        }
+    }
+    /// Get the quoted triple with the given id.
+    ///
+    /// This lets several graph creators share the numbering of quoted triples.
+    pub(crate) fn quoted_triple<'g>(&mut self,
+                             node_id: u32,
+                             subject: &graph::WriterBlankNodeOrIRI<'g, Self>,
+                             predicate: &CreateIRI,
+                             object: &graph::WriterResource<'g, Self>)
+                             -> CreateQuotedTriple
+        where SPO: 'g,
+              OPS: 'g
+    {
+        self.highest_blank_node = cmp::max(self.highest_blank_node, node_id);
+        let triple = self.compact_triple(subject, predicate, object);
+        self.quoted_triples.push((node_id, triple));
+        CreateQuotedTriple { node_id: node_id }
+    }
     fn check_blank_node(&self, blank_node: &BlankNodePtr<SPO, OPS>) {
         assert_eq!(self.graph_id,
                    blank_node.graph_id,
                    "Blank node is not associated with this graph creator.");
     }
+    fn compact_triple<'g>(&mut self,
+                          subject: &graph::WriterBlankNodeOrIRI<'g, Self>,
+                          predicate: &CreateIRI,
+                          object: &graph::WriterResource<'g, Self>)
+                          -> SPO
+        where SPO: 'g,
+              OPS: 'g
+    {
+        let (subject_is_iri, s) = match *subject {
+            graph::WriterBlankNodeOrIRI::BlankNode(ref b, _) => {
+                self.check_blank_node(b);
+                (false, b.node_id)
+            }
+            graph::WriterBlankNodeOrIRI::IRI(ref i) => (true, i.iri.id),
+            graph::WriterBlankNodeOrIRI::QuotedTriple(ref t) => (false, t.node_id),
+        };
+        let (object_type, o, d) = match *object {
+            graph::WriterResource::BlankNode(ref b, _) => {
+                self.check_blank_node(b);
+                (TripleObjectType::BlankNode, b.node_id, 0)
+            }
+            graph::WriterResource::IRI(ref i) => (TripleObjectType::IRI, i.iri.id, 0),
+            graph::WriterResource::Literal(ref l) => {
+                match l.language {
+                    Some(lang) => (TripleObjectType::LiteralLang, l.lexical.id, lang.id),
+                    None => (TripleObjectType::Literal, l.lexical.id, l.datatype.id),
+                }
+            }
+            graph::WriterResource::QuotedTriple(ref t) => {
+                (TripleObjectType::BlankNode, t.node_id, 0)
+            }
+        };
+        SPO::triple(subject_is_iri, s, predicate.iri.id, object_type, o, d)
+    }
+}
+
+/// Give quoted triples with the same content the same number and change the
+/// numbers in `spo` accordingly.
+fn deduplicate_quoted_triples<SPO>(quoted_triples: &mut Vec<(u32, SPO)>, spo: &mut [SPO])
+    where SPO: CompactTriple<u32>
+{
+    if quoted_triples.is_empty() {
+        return;
+    }
+    // quoted triples are created after the quoted triples that they contain
+    quoted_triples.sort_by_key(|q| q.0);
+    let mut renumber = HashMap::new();
+    let mut unique = BTreeMap::new();
+    for &mut (node_id, ref mut t) in quoted_triples.iter_mut() {
+        renumber_quoted(t, &renumber);
+        let id = *unique.entry(*t).or_insert(node_id);
+        renumber.insert(node_id, id);
+    }
+    quoted_triples.retain(|&(node_id, _)| renumber[&node_id] == node_id);
+    quoted_triples.dedup_by_key(|q| q.0);
+    for t in spo {
+        renumber_quoted(t, &renumber);
+    }
+}
+
+fn renumber_quoted<T>(t: &mut T, renumber: &HashMap<u32, u32>)
+    where T: CompactTriple<u32>
+{
+    if !t.subject_is_iri() {
+        if let Some(&id) = renumber.get(&t.subject()) {
+            t.set_subject(id);
+        }
+    }
+    if t.object_is_blank_node() {
+        if let Some(&id) = renumber.get(&t.object()) {
+            t.set_object(id);
+        }
+    }
 }
 
 fn create_ops<SPO, OPS>(spo: &[SPO]) -> Vec<OPS>
@@ -134,6 +239,10 @@ pub struct CreateDatatype {
 pub struct CreateLanguage {
     language: StringId,
 }
+#[derive(Clone)]
+pub struct CreateQuotedTriple {
+    node_id: u32,
+}
 
 impl<'g, SPO: 'g, OPS: 'g> graph::GraphWriter<'g> for GraphCreator<SPO, OPS>
     where SPO: CompactTriple<u32>,
@@ -144,6 +253,7 @@ impl<'g, SPO: 'g, OPS: 'g> graph::GraphWriter<'g> for GraphCreator<SPO, OPS>
     type Literal = CreateLiteral;
     type Datatype = CreateDatatype;
     type Language = CreateLanguage;
+    type QuotedTriple = CreateQuotedTriple;
     type Graph = Graph<SPO, OPS>;
     fn create_blank_node(&mut self) -> BlankNodePtr<'g, SPO, OPS> {
         self.highest_blank_node += 1;
@@ -198,6 +308,14 @@ impl<'g, SPO: 'g, OPS: 'g> graph::GraphWriter<'g> for GraphCreator<SPO, OPS>
             language: Some(language.language),
         }
     }
+    fn create_quoted_triple(&mut self,
+                            subject: &graph::WriterBlankNodeOrIRI<'g, Self>,
+                            predicate: &CreateIRI,
+                            object: &graph::WriterResource<'g, Self>)
+                            -> CreateQuotedTriple {
+        let node_id = self.highest_blank_node + 1;
+        self.quoted_triple(node_id, subject, predicate, object)
+    }
 
     fn collect(mut self) -> Graph<SPO, OPS> {
         let (translation, string_collection) = self.string_collector.collect();
@@ -207,6 +325,13 @@ impl<'g, SPO: 'g, OPS: 'g> graph::GraphWriter<'g> for GraphCreator<SPO, OPS>
         for t in &mut spo {
             translate(t, &translation, &datatrans);
         }
+        let mut quoted_triples = Vec::new();
+        mem::swap(&mut quoted_triples, &mut self.quoted_triples);
+        for q in &mut quoted_triples {
+            translate(&mut q.1, &translation, &datatrans);
+        }
+        deduplicate_quoted_triples(&mut quoted_triples, &mut spo);
+        quoted_triples.shrink_to_fit();
         // sort according to StringId, which is sorted alphabetically
         spo.sort();
         spo.dedup();
@@ -217,10 +342,12 @@ impl<'g, SPO: 'g, OPS: 'g> graph::GraphWriter<'g> for GraphCreator<SPO, OPS>
                 graph_id: self.graph_id,
                 strings: string_collection,
                 datatype_or_lang: datatype_lang_collection,
-                spo: spo,
-                ops: ops,
+                spo: Array::from(spo),
+                ops: Array::from(ops),
+                pso: None,
                 lang_string_datatype_id: datatrans[self.lang_string_datatype_id].id,
                 highest_blank_node: self.highest_blank_node,
+                quoted_triples: quoted_triples,
             },
         }
     }
@@ -292,6 +419,13 @@ impl<'g, SPO: 'g, OPS: 'g> graph::GraphWriter<'g> for GraphCreator<SPO, OPS>
             }
         }
     }
+    fn add_quoted(&mut self,
+                  subject: &graph::WriterBlankNodeOrIRI<'g, Self>,
+                  predicate: &CreateIRI,
+                  object: &graph::WriterResource<'g, Self>) {
+        let triple = self.compact_triple(subject, predicate, object);
+        self.triples.push(triple);
+    }
 }
 
 #[test]
@@ -317,3 +451,26 @@ fn keep_blank_subject() {
     assert_eq!(triple.object(),
                graph::Resource::BlankNode(blank2, PhantomData));
 }
+
+#[test]
+fn quoted_triples() {
+    let mut creator: GraphCreator<Triple64SPO, Triple64OPS> = GraphCreator::with_capacity(0);
+    use graph::{GraphWriter, Graph, IRIPtr, QuotedTriplePtr, Triple, WriterBlankNodeOrIRI,
+                WriterResource};
+    let s = WriterBlankNodeOrIRI::IRI(creator.create_iri(&"s"));
+    let p = creator.create_iri(&"p");
+    let o = WriterResource::IRI(creator.create_iri(&"o"));
+    // the same quoted triple is stored once
+    let t1 = creator.create_quoted_triple(&s, &p, &o);
+    let t2 = creator.create_quoted_triple(&s, &p, &o);
+    creator.add(&WriterBlankNodeOrIRI::QuotedTriple(t1), &p, &o);
+    creator.add(&s, &p, &WriterResource::QuotedTriple(t2));
+    let graph = creator.collect();
+    assert_eq!(graph.iter().count(), 2);
+    let quoted = graph.iter().filter_map(|t| t.subject().as_quoted_triple().cloned()).next();
+    let quoted = quoted.unwrap();
+    assert_eq!(quoted.subject().as_iri().unwrap().as_str(), "s");
+    assert_eq!(quoted.object().as_iri().unwrap().as_str(), "o");
+    assert_eq!(graph.iter_o(&quoted.to_resource()).count(), 1);
+    assert_eq!(graph.iter_s(&quoted.to_blank_node_or_iri()).count(), 1);
+}
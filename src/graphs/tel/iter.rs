@@ -1,8 +1,8 @@
-use iter::SortedIterator;
-use std::marker::PhantomData;
 use super::compact_triple::*;
 use super::graph::*;
 use super::triple::*;
+use iter::SortedIterator;
+use std::marker::PhantomData;
 
 pub struct GraphIterator<'g, SPO: 'g, OPS: 'g, T, F>
     where SPO: CompactTriple<u32>,
@@ -33,6 +33,10 @@ impl<'g, SPO, OPS, T, F> Iterator for GraphIterator<'g, SPO, OPS, T, F>
             None
         }
     }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = F::index(self.graph).len() - self.pos;
+        (len, Some(len))
+    }
 }
 impl<'g, SPO, OPS, T, F> SortedIterator for GraphIterator<'g, SPO, OPS, T, F>
     where SPO: CompactTriple<u32>,
@@ -76,6 +80,13 @@ impl<'g, SPO, OPS, T, F> Iterator for TripleRangeIterator<'g, SPO, OPS, T, F>
             None
         }
     }
+    /// The index is sorted, so the number of remaining triples is found with
+    /// a binary search.
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let index = &F::index(self.graph)[self.pos..];
+        let len = index.partition_point(|t| *t < self.end);
+        (len, Some(len))
+    }
 }
 impl<'g, SPO, OPS, T, F> SortedIterator for TripleRangeIterator<'g, SPO, OPS, T, F>
     where SPO: CompactTriple<u32>,
@@ -84,3 +95,52 @@ impl<'g, SPO, OPS, T, F> SortedIterator for TripleRangeIterator<'g, SPO, OPS, T,
           F: Index<SPO, OPS, T>
 {
 }
+
+/// Iterator over the triples with one predicate.
+///
+/// Without a predicate index, all triples are scanned.
+pub struct PredicateIterator<'g, SPO: 'g, OPS: 'g>
+    where SPO: CompactTriple<u32>,
+          OPS: CompactTriple<u32>
+{
+    pub graph: &'g GraphData<SPO, OPS>,
+    pub positions: Option<&'g [u32]>,
+    pub pos: usize,
+    pub end: usize,
+    pub predicate: u32,
+}
+
+impl<'g, SPO, OPS> Iterator for PredicateIterator<'g, SPO, OPS>
+    where SPO: CompactTriple<u32>,
+          OPS: CompactTriple<u32>
+{
+    type Item = Triple<'g, SPO, OPS, SPO>;
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.pos < self.end {
+            let pos = match self.positions {
+                Some(positions) => positions[self.pos] as usize,
+                None => self.pos,
+            };
+            self.pos += 1;
+            let triple = self.graph.spo[pos];
+            if triple.predicate() == self.predicate {
+                return Some(Triple {
+                    graph: self.graph,
+                    triple: triple,
+                });
+            }
+        }
+        None
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self.positions {
+            Some(_) => (self.end - self.pos, Some(self.end - self.pos)),
+            None => (0, Some(self.end - self.pos)),
+        }
+    }
+}
+impl<'g, SPO, OPS> SortedIterator for PredicateIterator<'g, SPO, OPS>
+    where SPO: CompactTriple<u32>,
+          OPS: CompactTriple<u32>
+{
+}
@@ -0,0 +1,326 @@
+//! Read-only `tel` graphs that live in a memory-mapped file.
+//!
+//! The file is the binary format that is written by `Graph::save`. Its
+//! string collections and triple indexes are aligned to 8 bytes so that they
+//! can be used in place. The operating system shares the pages of the file
+//! between all processes that map it and only reads the pages that are used.
+
+use error::{Error, Result};
+use memmap2::Mmap;
+use rand;
+use std::fmt;
+use std::fs::File;
+use std::marker::PhantomData;
+use std::mem;
+use std::ops::Deref;
+use std::path::Path;
+use std::slice;
+use std::sync::Arc;
+use super::binary::{MAGIC, VERSION};
+use super::compact_triple::*;
+use super::graph::*;
+use super::string_collector::*;
+
+/// An array that is either in memory or in a memory-mapped file.
+///
+/// Only types that are valid for any bit pattern are placed in a mapped
+/// array: `u8`, `u32` and the compact triples.
+pub enum Array<T> {
+    Vec(Vec<T>),
+    Mapped {
+        map: Arc<Mmap>,
+        offset: usize,
+        len: usize,
+        phantom: PhantomData<T>,
+    },
+}
+
+impl<T> Array<T> {
+    /// Use `len` items of type `T` at `offset` in `map`.
+    /// `None` is returned if they do not fit or are not aligned.
+    fn mapped(map: &Arc<Mmap>, offset: usize, len: usize) -> Option<Array<T>> {
+        let end = len.checked_mul(mem::size_of::<T>()).and_then(|size| offset.checked_add(size));
+        let aligned = map.as_ptr().wrapping_add(offset).align_offset(mem::align_of::<T>()) == 0;
+        match end {
+            Some(end) if end <= map.len() && aligned => {
+                Some(Array::Mapped {
+                    map: map.clone(),
+                    offset: offset,
+                    len: len,
+                    phantom: PhantomData,
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+impl<T> Deref for Array<T> {
+    type Target = [T];
+    fn deref(&self) -> &[T] {
+        match *self {
+            Array::Vec(ref v) => v,
+            Array::Mapped { ref map, offset, len, .. } => {
+                // `mapped` checked the bounds and the alignment and only
+                // plain data is mapped
+                unsafe { slice::from_raw_parts(map.as_ptr().add(offset) as *const T, len) }
+            }
+        }
+    }
+}
+
+impl<T> From<Vec<T>> for Array<T> {
+    fn from(v: Vec<T>) -> Array<T> {
+        Array::Vec(v)
+    }
+}
+
+impl<T> Clone for Array<T>
+    where T: Clone
+{
+    fn clone(&self) -> Array<T> {
+        match *self {
+            Array::Vec(ref v) => Array::Vec(v.clone()),
+            Array::Mapped { ref map, offset, len, .. } => {
+                Array::Mapped {
+                    map: map.clone(),
+                    offset: offset,
+                    len: len,
+                    phantom: PhantomData,
+                }
+            }
+        }
+    }
+}
+
+impl<T> fmt::Debug for Array<T>
+    where T: fmt::Debug
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+/// Reads the parts of a mapped binary graph.
+struct MapReader {
+    map: Arc<Mmap>,
+    pos: usize,
+}
+
+impl MapReader {
+    fn truncated() -> Error {
+        Error::Custom("The binary graph is truncated.")
+    }
+    fn read(&mut self, len: usize) -> Result<&[u8]> {
+        let start = self.pos;
+        match start.checked_add(len) {
+            Some(end) if end <= self.map.len() => {
+                self.pos = end;
+                Ok(&self.map[start..end])
+            }
+            _ => Err(MapReader::truncated()),
+        }
+    }
+    fn read_u32(&mut self) -> Result<u32> {
+        let mut value = [0; 4];
+        value.copy_from_slice(self.read(4)?);
+        Ok(u32::from_le_bytes(value))
+    }
+    fn read_len(&mut self) -> Result<usize> {
+        let mut value = [0; 8];
+        value.copy_from_slice(self.read(8)?);
+        let len = u64::from_le_bytes(value);
+        if len > self.map.len() as u64 {
+            return Err(MapReader::truncated());
+        }
+        Ok(len as usize)
+    }
+    fn skip_padding(&mut self) {
+        self.pos += (8 - self.pos % 8) % 8;
+    }
+    fn read_array<T>(&mut self, len: usize) -> Result<Array<T>> {
+        let array = Array::mapped(&self.map, self.pos, len).ok_or_else(MapReader::truncated)?;
+        self.pos += len * mem::size_of::<T>();
+        Ok(array)
+    }
+    fn read_strings(&mut self) -> Result<StringCollection> {
+        let len = self.read_len()?;
+        let buffer = self.read_array(len)?;
+        self.skip_padding();
+        let starts = self.read_u32s()?;
+        StringCollection::from_mapped(buffer, starts)
+            .ok_or(Error::Custom("The binary graph has invalid strings."))
+    }
+    /// Read the number of values, the values and the padding.
+    fn read_u32s(&mut self) -> Result<Array<u32>> {
+        let len = self.read_len()?;
+        let values = self.read_array(len)?;
+        self.skip_padding();
+        Ok(values)
+    }
+    fn read_triples<T>(&mut self) -> Result<Array<T>>
+        where T: CompactTriple<u32>
+    {
+        let len = self.read_len()?;
+        self.read_array(len)
+    }
+}
+
+impl<SPO, OPS> Graph<SPO, OPS>
+    where SPO: CompactTriple<u32>,
+          OPS: CompactTriple<u32>
+{
+    /// Map a graph that was written with `save` into memory.
+    ///
+    /// The strings and triples are not copied, so the graph is available
+    /// right away and the memory is shared with other processes that map the
+    /// same file.
+    ///
+    /// Unlike `load`, this function does not verify the checksum, because
+    /// that would read the whole file. Strings and the index by predicate
+    /// are checked when they are used and invalid ones cause a panic.
+    ///
+    /// Mapping is only possible on little endian machines. On other machines
+    /// `load` can read the file.
+    ///
+    /// # Safety
+    ///
+    /// The graph reads the file in place, so the file must not be changed or
+    /// truncated, by this or any other process, while the graph or any of the
+    /// values that were obtained from it are alive. Otherwise the graph
+    /// reads memory that changes under it or that is no longer mapped.
+    pub unsafe fn map<P>(path: P) -> Result<Graph<SPO, OPS>>
+        where P: AsRef<Path>
+    {
+        if cfg!(target_endian = "big") ||
+           mem::size_of::<SPO>() != SPO::byte_size() ||
+           mem::size_of::<OPS>() != OPS::byte_size() {
+            return Err(Error::Custom("The binary graph cannot be mapped on this machine."));
+        }
+        let file = File::open(path)?;
+        // the file is only read and the caller keeps it unchanged
+        let map = Mmap::map(&file)?;
+        let mut r = MapReader {
+            map: Arc::new(map),
+            pos: 0,
+        };
+        if r.map.len() < MAGIC.len() || r.read(MAGIC.len())? != &MAGIC[..] {
+            return Err(Error::Custom("The data is not a binary graph."));
+        }
+        let version = r.read_u32()?;
+        if version != VERSION {
+            return Err(Error::String(format!("Version {} of the binary graph format cannot be \
+                                              mapped.",
+                                             version)));
+        }
+        if r.read_u32()? != SPO::byte_size() as u32 {
+            return Err(Error::Custom("The binary graph has triples of another size."));
+        }
+        let highest_blank_node = r.read_u32()?;
+        let lang_string_datatype_id = r.read_u32()?;
+        let strings = r.read_strings()?;
+        let datatype_or_lang = r.read_strings()?;
+        let spo = r.read_triples()?;
+        let ops = r.read_triples()?;
+        let pso = r.read_u32s()?;
+        if !pso.is_empty() && pso.len() != spo.len() {
+            return Err(Error::Custom("The binary graph has an invalid predicate index."));
+        }
+        // quoted triples are rare, so they are copied into memory
+        let len = r.read_len()?;
+        let size = 4 + SPO::byte_size();
+        let quoted_triples = r.read(len.saturating_mul(size))?
+            .chunks(size)
+            .map(|c| (u32::from_le_bytes([c[0], c[1], c[2], c[3]]), SPO::read_bytes(&c[4..])))
+            .collect();
+        r.read(4)?;
+        if r.pos != r.map.len() {
+            return Err(Error::Custom("The binary graph has trailing data."));
+        }
+        Ok(Graph {
+            d: GraphData {
+                graph_id: rand::random::<u32>(),
+                strings: strings,
+                datatype_or_lang: datatype_or_lang,
+                spo: spo,
+                ops: ops,
+                pso: if pso.is_empty() { None } else { Some(pso) },
+                lang_string_datatype_id: lang_string_datatype_id,
+                highest_blank_node: highest_blank_node,
+                quoted_triples: quoted_triples,
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+fn temp_file(name: &str) -> ::std::path::PathBuf {
+    ::std::env::temp_dir().join(format!("rome-{}-{}.bin", name, rand::random::<u32>()))
+}
+
+#[test]
+fn test_map() {
+    use graph::{BlankNodeOrIRI, Graph, IRIPtr, Triple};
+    use graphs::tel::{Graph64, Graph128};
+    use test_support::{ntriples, turtle_graph, TURTLE};
+    let path = temp_file("map");
+    let graph: Graph64 = turtle_graph(TURTLE);
+    graph.save(&mut File::create(&path).unwrap()).unwrap();
+    let mapped = unsafe { Graph64::map(&path) }.unwrap();
+    assert_eq!(ntriples(&mapped), ntriples(&graph));
+    assert_eq!(mapped.len(), graph.len());
+    let s = mapped.find_iri("http://a.example/s").unwrap();
+    let p = mapped.find_iri("http://a.example/p").unwrap();
+    assert_eq!(mapped.iter_s_p(&BlankNodeOrIRI::IRI(s.clone()), &p).count(), 3);
+    let r = mapped.find_iri("http://a.example/r").unwrap();
+    let o = mapped.iter_s_p(&BlankNodeOrIRI::IRI(s.clone()), &r).next().unwrap().object();
+    assert!(o.as_quoted_triple().is_some());
+    let triples: Vec<_> = mapped.iter_o(&o).collect();
+    assert_eq!(triples.len(), 1);
+    assert_eq!(triples[0].subject().as_iri().map(|i| i.as_str()), Some(s.as_str()));
+    // a Graph128 cannot map a Graph64
+    assert!(unsafe { Graph128::map(&path) }.is_err());
+    ::std::fs::remove_file(&path).unwrap();
+
+    let graph: Graph128 = turtle_graph(TURTLE);
+    graph.save(&mut File::create(&path).unwrap()).unwrap();
+    let mapped = unsafe { Graph128::map(&path) }.unwrap();
+    assert_eq!(ntriples(&mapped), ntriples(&graph));
+    assert!(!mapped.has_predicate_index());
+    ::std::fs::remove_file(&path).unwrap();
+
+    // the index by predicate is mapped too
+    let mut graph: Graph64 = turtle_graph(TURTLE);
+    graph.index_predicates();
+    graph.save(&mut File::create(&path).unwrap()).unwrap();
+    let mapped = unsafe { Graph64::map(&path) }.unwrap();
+    assert!(mapped.has_predicate_index());
+    let p = mapped.find_iri("http://a.example/p").unwrap();
+    assert_eq!(mapped.iter_p(&p).count(), 4);
+    ::std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_map_errors() {
+    use graphs::tel::Graph64;
+    use std::io::Write;
+    use test_support::{turtle_graph, TURTLE};
+    let path = temp_file("map-errors");
+    let graph: Graph64 = turtle_graph(TURTLE);
+    let mut bytes = Vec::new();
+    graph.save(&mut bytes).unwrap();
+    // truncated data
+    File::create(&path).unwrap().write_all(&bytes[..bytes.len() - 1]).unwrap();
+    assert!(unsafe { Graph64::map(&path) }.is_err());
+    // trailing data
+    let mut trailing = bytes.clone();
+    trailing.push(0);
+    File::create(&path).unwrap().write_all(&trailing).unwrap();
+    assert!(unsafe { Graph64::map(&path) }.is_err());
+    File::create(&path).unwrap().write_all(b"@prefix : <urn:> .").unwrap();
+    assert!(unsafe { Graph64::map(&path) }.is_err());
+    File::create(&path).unwrap();
+    assert!(unsafe { Graph64::map(&path) }.is_err());
+    ::std::fs::remove_file(&path).unwrap();
+    assert!(unsafe { Graph64::map(&path) }.is_err());
+}
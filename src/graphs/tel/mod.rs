@@ -4,15 +4,24 @@
 //! One is based on a 64 bit representation of triples and the other on a
 //! 128 bit representation of the triples. To make sure that all triples fit,
 //! it is recommended to use `Graph128`.
+//!
+//! A graph can be saved to a binary file with `save` and read again with
+//! `load`, which is much faster than parsing the graph again. A saved graph
+//! can also be mapped into memory with `map`. A mapped graph is read-only and
+//! its memory is shared between the processes that map the same file.
 
 mod compact_triple;
 mod triple128;
 mod triple64;
 mod graph_creator;
+mod dataset;
 mod string_collector;
 mod graph;
 mod iter;
 mod triple;
+mod binary;
+mod mapped;
+mod union_graph;
 
 pub use self::triple128::{Triple128SPO, Triple128OPS};
 pub use self::triple64::{Triple64SPO, Triple64OPS};
@@ -24,3 +33,12 @@ pub type Graph128 = graph::Graph<Triple128SPO, Triple128OPS>;
 /// Implementation of `rome::graph::GraphCreator` that can create Graph64
 /// and Graph128.
 pub type GraphCreator<A, B> = graph_creator::GraphCreator<A, B>;
+/// An RDF dataset with a default graph and named graphs that are stored
+/// like `Graph64`.
+pub type Dataset64 = dataset::Dataset<Triple64SPO, Triple64OPS>;
+/// An RDF dataset with a default graph and named graphs that are stored
+/// like `Graph128`.
+pub type Dataset128 = dataset::Dataset<Triple128SPO, Triple128OPS>;
+/// Implementation of `rome::graph::DatasetWriter` that can create Dataset64
+/// and Dataset128.
+pub type DatasetCreator<A, B> = dataset::DatasetCreator<A, B>;
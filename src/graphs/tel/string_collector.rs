@@ -1,5 +1,7 @@
 use std::cmp::Ordering;
 use std::ops::Index;
+use std::str;
+use super::mapped::Array;
 
 #[derive (Clone)]
 struct StringRef {
@@ -103,8 +105,8 @@ impl StringCollector {
         let mut starts: Vec<u32> = self.refs.iter().map(|r| r.start).collect();
         starts.push(new_buffer.len() as u32);
         let collection = StringCollection {
-            buffer: new_buffer,
-            starts: starts,
+            buffer: Buffer::String(new_buffer),
+            starts: Array::from(starts),
         };
         self.buffer.clear();
         self.refs.clear();
@@ -112,17 +114,67 @@ impl StringCollector {
     }
 }
 
+/// The strings of a collection. Strings in a mapped buffer are checked when
+/// they are used.
+#[derive (Clone,Debug)]
+enum Buffer {
+    String(String),
+    Mapped(Array<u8>),
+}
+
 #[derive (Clone,Debug)]
 pub struct StringCollection {
-    buffer: String,
-    starts: Vec<u32>,
+    buffer: Buffer,
+    starts: Array<u32>,
 }
 
 impl StringCollection {
+    /// Create a collection from the buffer and the start of each string.
+    /// The last start is the length of the buffer.
+    pub fn from_parts(buffer: String, starts: Vec<u32>) -> Option<StringCollection> {
+        let valid = starts.last() == Some(&(buffer.len() as u32)) &&
+                    starts.windows(2).all(|w| w[0] <= w[1]) &&
+                    starts.iter().all(|s| buffer.is_char_boundary(*s as usize));
+        if valid {
+            Some(StringCollection {
+                buffer: Buffer::String(buffer),
+                starts: Array::from(starts),
+            })
+        } else {
+            None
+        }
+    }
+    /// Create a collection from a mapped buffer and the start of each
+    /// string. Only the last start is checked, so that no more of the
+    /// mapping than needed is read.
+    pub fn from_mapped(buffer: Array<u8>, starts: Array<u32>) -> Option<StringCollection> {
+        if starts.last() == Some(&(buffer.len() as u32)) {
+            Some(StringCollection {
+                buffer: Buffer::Mapped(buffer),
+                starts: starts,
+            })
+        } else {
+            None
+        }
+    }
+    pub fn buffer(&self) -> &[u8] {
+        match self.buffer {
+            Buffer::String(ref s) => s.as_bytes(),
+            Buffer::Mapped(ref b) => b,
+        }
+    }
+    pub fn starts(&self) -> &[u32] {
+        &self.starts
+    }
     pub fn get(&self, i: StringId) -> &str {
         let start = self.starts[i.id as usize] as usize;
         let end = self.starts[(i.id + 1) as usize] as usize;
-        &self.buffer[start..end]
+        match self.buffer {
+            Buffer::String(ref s) => &s[start..end],
+            Buffer::Mapped(ref b) => {
+                str::from_utf8(&b[start..end]).expect("The mapped graph has an invalid string.")
+            }
+        }
     }
     pub fn find(&self, s: &str) -> Option<StringId> {
         match binary_search_by_index(self.starts.len() - 1,
@@ -151,6 +203,9 @@ fn binary_search_by_index<F>(len: usize, mut f: F) -> Result<usize, usize>
                 l = m + 1;
             }
             Ordering::Greater => {
+                if m == 0 {
+                    return Err(0);
+                }
                 r = m - 1;
             }
             Ordering::Equal => return Ok(m),
@@ -254,13 +254,111 @@ impl<'g, SPO: 'g, OPS: 'g> Into<graph::Resource<'g, BlankNodePtr<'g, SPO, OPS>,
     }
 }
 
+#[derive (Clone)]
+pub struct QuotedTriplePtr<'g, SPO: 'g, OPS: 'g>
+    where SPO: CompactTriple<u32>,
+          OPS: CompactTriple<u32>
+{
+    pub graph: &'g GraphData<SPO, OPS>,
+    /// quoted triples are numbered like blank nodes
+    pub node_id: u32,
+}
+impl<'g, SPO, OPS> QuotedTriplePtr<'g, SPO, OPS>
+    where SPO: CompactTriple<u32>,
+          OPS: CompactTriple<u32>
+{
+    fn triple(&self) -> Triple<'g, SPO, OPS, SPO> {
+        Triple {
+            graph: self.graph,
+            triple: self.graph.quoted_triple(self.node_id).expect("quoted triple is missing"),
+        }
+    }
+}
+impl<'g, SPO, OPS> PartialEq for QuotedTriplePtr<'g, SPO, OPS>
+    where SPO: CompactTriple<u32>,
+          OPS: CompactTriple<u32>
+{
+    fn eq(&self, other: &Self) -> bool {
+        if self.graph.graph_id == other.graph.graph_id {
+            self.node_id == other.node_id
+        } else {
+            self.triple() == other.triple()
+        }
+    }
+}
+impl<'g, SPO, OPS> Eq for QuotedTriplePtr<'g, SPO, OPS>
+    where SPO: CompactTriple<u32>,
+          OPS: CompactTriple<u32>
+{
+}
+impl<'g, SPO, OPS> PartialOrd for QuotedTriplePtr<'g, SPO, OPS>
+    where SPO: CompactTriple<u32>,
+          OPS: CompactTriple<u32>
+{
+    fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl<'g, SPO, OPS> Ord for QuotedTriplePtr<'g, SPO, OPS>
+    where SPO: CompactTriple<u32>,
+          OPS: CompactTriple<u32>
+{
+    fn cmp(&self, other: &Self) -> cmp::Ordering {
+        self.node_id.cmp(&other.node_id)
+    }
+}
+impl<'g, SPO, OPS> fmt::Debug for QuotedTriplePtr<'g, SPO, OPS>
+    where SPO: CompactTriple<u32>,
+          OPS: CompactTriple<u32>
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use graph::Triple;
+        let triple = self.triple();
+        write!(f,
+               "<< {:?} {:?} {:?} >>",
+               triple.subject(),
+               triple.predicate(),
+               triple.object())
+    }
+}
+impl<'g, SPO: 'g, OPS: 'g> graph::Triple<'g,
+                                         BlankNodePtr<'g, SPO, OPS>,
+                                         IRIPtr<'g, SPO, OPS>,
+                                         LiteralPtr<'g, SPO, OPS>,
+                                         QuotedTriplePtr<'g, SPO, OPS>>
+    for QuotedTriplePtr<'g, SPO, OPS>
+    where SPO: CompactTriple<u32>,
+          OPS: CompactTriple<u32>
+{
+    fn subject(&self) -> BlankNodeOrIRI<'g, SPO, OPS> {
+        self.triple().subject()
+    }
+    fn predicate(&self) -> IRIPtr<'g, SPO, OPS> {
+        self.triple().predicate()
+    }
+    fn object(&self) -> Resource<'g, SPO, OPS> {
+        self.triple().object()
+    }
+}
+impl<'g, SPO: 'g, OPS: 'g> graph::QuotedTriplePtr<'g,
+                                                  BlankNodePtr<'g, SPO, OPS>,
+                                                  IRIPtr<'g, SPO, OPS>,
+                                                  LiteralPtr<'g, SPO, OPS>>
+    for QuotedTriplePtr<'g, SPO, OPS>
+    where SPO: CompactTriple<u32>,
+          OPS: CompactTriple<u32>
+{
+}
+
 pub type BlankNodeOrIRI<'t, SPO, OPS> = graph::BlankNodeOrIRI<'t,
                                                               BlankNodePtr<'t, SPO, OPS>,
-                                                              IRIPtr<'t, SPO, OPS>>;
+                                                              IRIPtr<'t, SPO, OPS>,
+                                                              QuotedTriplePtr<'t, SPO, OPS>>;
 pub type Resource<'t, SPO, OPS> = graph::Resource<'t,
                                                   BlankNodePtr<'t, SPO, OPS>,
                                                   IRIPtr<'t, SPO, OPS>,
-                                                  LiteralPtr<'t, SPO, OPS>>;
+                                                  LiteralPtr<'t, SPO, OPS>,
+                                                  QuotedTriplePtr<'t, SPO, OPS>>;
 
 #[derive(Clone)]
 pub struct Triple<'g, SPO: 'g, OPS: 'g, T>
@@ -313,7 +411,9 @@ impl<'g, SPO, OPS, T> Ord for Triple<'g, SPO, OPS, T>
 impl<'g, SPO: 'g, OPS: 'g, T> graph::Triple<'g,
                                               BlankNodePtr<'g, SPO, OPS>,
                                               IRIPtr<'g, SPO, OPS>,
-                                              LiteralPtr<'g, SPO, OPS>> for Triple<'g, SPO, OPS, T>
+                                              LiteralPtr<'g, SPO, OPS>,
+                                              QuotedTriplePtr<'g, SPO, OPS>>
+    for Triple<'g, SPO, OPS, T>
     where SPO: CompactTriple<u32>,
           OPS: CompactTriple<u32>,
           T: CompactTriple<u32>
@@ -321,6 +421,9 @@ impl<'g, SPO: 'g, OPS: 'g, T> graph::Triple<'g,
     fn subject(&self) -> BlankNodeOrIRI<'g,SPO,OPS> {
         if self.triple.subject_is_iri() {
             graph::BlankNodeOrIRI::IRI(IRIPtr{graph:self.graph, iri: self.triple.subject() })
+        } else if self.graph.is_quoted_triple(self.triple.subject()) {
+            graph::BlankNodeOrIRI::QuotedTriple(QuotedTriplePtr{
+                    graph: self.graph, node_id: self.triple.subject() })
         } else {
             graph::BlankNodeOrIRI::BlankNode(BlankNodePtr{
                     graph_id:self.graph.graph_id,
@@ -334,6 +437,10 @@ impl<'g, SPO: 'g, OPS: 'g, T> graph::Triple<'g,
     fn object(&self) -> Resource<'g,SPO,OPS> {
         if self.triple.object_is_iri() {
             graph::Resource::IRI(IRIPtr{graph:self.graph, iri: self.triple.object() })
+        } else if self.triple.object_is_blank_node() &&
+                  self.graph.is_quoted_triple(self.triple.object()) {
+            graph::Resource::QuotedTriple(QuotedTriplePtr{
+                    graph: self.graph, node_id: self.triple.object() })
         } else if self.triple.object_is_blank_node() {
             graph::Resource::BlankNode(BlankNodePtr{
                     graph_id: self.graph.graph_id,
@@ -46,7 +46,9 @@ macro_rules! triple128_helper{(
     $object_type_offset:expr) => {
 
 /// Compact triple used in tel::Graph.
+/// The layout matches the binary format so that mapped triples can be used.
 #[derive(PartialEq,Eq,Hash,PartialOrd,Ord,Clone,Copy,Debug)]
+#[repr(C)]
 pub struct $name {
     v1: u64,
     v2: u64,
@@ -149,8 +151,8 @@ impl CompactTriple<u32> for $name {
         match (self.$object_var >> $object_type_offset) & 3 {
             0 => TripleObjectType::BlankNode,
             1 => TripleObjectType::IRI,
-            2 => TripleObjectType::Literal,
-            _ => TripleObjectType::LiteralLang,
+            2 => TripleObjectType::LiteralLang,
+            _ => TripleObjectType::Literal,
         }
     }
     fn has_language(&self) -> bool {
@@ -169,6 +171,23 @@ impl CompactTriple<u32> for $name {
     fn datatype_or_lang(&self) -> u32 {
         (self.v2 & DLMASK) as u32
     }
+    fn byte_size() -> usize {
+        16
+    }
+    fn write_bytes(&self, bytes: &mut Vec<u8>) {
+        bytes.extend_from_slice(&self.v1.to_le_bytes());
+        bytes.extend_from_slice(&self.v2.to_le_bytes());
+    }
+    fn read_bytes(bytes: &[u8]) -> $name {
+        let mut v1 = [0; 8];
+        let mut v2 = [0; 8];
+        v1.copy_from_slice(&bytes[..8]);
+        v2.copy_from_slice(&bytes[8..16]);
+        $name {
+            v1: u64::from_le_bytes(v1),
+            v2: u64::from_le_bytes(v2)
+        }
+    }
 }
 
     };
@@ -210,6 +229,7 @@ fn test_triple3() {
     assert_eq!(t.object_is_blank_node(), false);
     assert_eq!(t.object_is_iri(), false);
     assert_eq!(t.has_language(), false);
+    assert_eq!(t.object_type(), TripleObjectType::Literal);
     assert_eq!(t.object(), 3);
     assert_eq!(t.datatype_or_lang(), 4);
 }
@@ -222,6 +242,7 @@ fn test_triple4() {
     assert_eq!(t.object_is_blank_node(), false);
     assert_eq!(t.object_is_iri(), false);
     assert_eq!(t.has_language(), true);
+    assert_eq!(t.object_type(), TripleObjectType::LiteralLang);
     assert_eq!(t.object(), 3);
     assert_eq!(t.datatype_or_lang(), 4);
 }
@@ -277,6 +298,7 @@ fn test_triple3_ops() {
     assert_eq!(t.object_is_blank_node(), false);
     assert_eq!(t.object_is_iri(), false);
     assert_eq!(t.has_language(), false);
+    assert_eq!(t.object_type(), TripleObjectType::Literal);
     assert_eq!(t.object(), 3);
     assert_eq!(t.datatype_or_lang(), 4);
 }
@@ -289,6 +311,7 @@ fn test_triple4_ops() {
     assert_eq!(t.object_is_blank_node(), false);
     assert_eq!(t.object_is_iri(), false);
     assert_eq!(t.has_language(), true);
+    assert_eq!(t.object_type(), TripleObjectType::LiteralLang);
     assert_eq!(t.object(), 3);
     assert_eq!(t.datatype_or_lang(), 4);
 }
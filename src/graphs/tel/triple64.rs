@@ -40,7 +40,9 @@ macro_rules! triple64_helper{(
     $object_type_offset:expr) => {
 
 /// Compact triple used in tel::Graph.
+/// The layout matches the binary format so that mapped triples can be used.
 #[derive(PartialEq,Eq,Hash,PartialOrd,Ord,Clone,Copy,Debug)]
+#[repr(C)]
 pub struct $name {
     value: u64,
 }
@@ -128,8 +130,8 @@ impl CompactTriple<u32> for $name {
         match (self.value >> $object_type_offset) & 3 {
             0 => TripleObjectType::BlankNode,
             1 => TripleObjectType::IRI,
-            2 => TripleObjectType::Literal,
-            _ => TripleObjectType::LiteralLang,
+            2 => TripleObjectType::LiteralLang,
+            _ => TripleObjectType::Literal,
         }
     }
     fn has_language(&self) -> bool {
@@ -147,6 +149,17 @@ impl CompactTriple<u32> for $name {
     fn datatype_or_lang(&self) -> u32 {
         (self.value & DLMASK) as u32
     }
+    fn byte_size() -> usize {
+        8
+    }
+    fn write_bytes(&self, bytes: &mut Vec<u8>) {
+        bytes.extend_from_slice(&self.value.to_le_bytes());
+    }
+    fn read_bytes(bytes: &[u8]) -> $name {
+        let mut value = [0; 8];
+        value.copy_from_slice(&bytes[..8]);
+        $name { value: u64::from_le_bytes(value) }
+    }
 }
 
     };
@@ -188,6 +201,7 @@ fn test_triple3() {
     assert_eq!(t.object_is_blank_node(), false);
     assert_eq!(t.object_is_iri(), false);
     assert_eq!(t.has_language(), false);
+    assert_eq!(t.object_type(), TripleObjectType::Literal);
     assert_eq!(t.object(), 3);
     assert_eq!(t.datatype_or_lang(), 4);
 }
@@ -200,6 +214,7 @@ fn test_triple4() {
     assert_eq!(t.object_is_blank_node(), false);
     assert_eq!(t.object_is_iri(), false);
     assert_eq!(t.has_language(), true);
+    assert_eq!(t.object_type(), TripleObjectType::LiteralLang);
     assert_eq!(t.object(), 3);
     assert_eq!(t.datatype_or_lang(), 4);
 }
@@ -255,6 +270,7 @@ fn test_triple3_ops() {
     assert_eq!(t.object_is_blank_node(), false);
     assert_eq!(t.object_is_iri(), false);
     assert_eq!(t.has_language(), false);
+    assert_eq!(t.object_type(), TripleObjectType::Literal);
     assert_eq!(t.object(), 3);
     assert_eq!(t.datatype_or_lang(), 4);
 }
@@ -267,6 +283,7 @@ fn test_triple4_ops() {
     assert_eq!(t.object_is_blank_node(), false);
     assert_eq!(t.object_is_iri(), false);
     assert_eq!(t.has_language(), true);
+    assert_eq!(t.object_type(), TripleObjectType::LiteralLang);
     assert_eq!(t.object(), 3);
     assert_eq!(t.datatype_or_lang(), 4);
 }
@@ -0,0 +1,684 @@
+use constants;
+use graph;
+use iter::SortedIterator;
+use std::cmp;
+use std::fmt;
+use std::marker::PhantomData;
+use super::compact_triple::*;
+use super::graph::*;
+use super::iter::*;
+use super::string_collector::*;
+use super::triple;
+
+/// The union of the graphs of a `Dataset`.
+///
+/// The triples are not copied: every query is passed to each graph and the
+/// sorted results are merged. The graphs of a dataset share the numbers of
+/// their blank nodes, so a blank node is the same node in every graph.
+pub struct UnionGraph<SPO, OPS>
+    where SPO: CompactTriple<u32>,
+          OPS: CompactTriple<u32>
+{
+    pub graphs: Vec<Graph<SPO, OPS>>,
+}
+
+#[derive (Clone,Copy,PartialEq,Eq,PartialOrd,Ord)]
+pub struct BlankNodePtr {
+    node_id: u32,
+}
+impl fmt::Display for BlankNodePtr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.node_id)
+    }
+}
+impl fmt::Debug for BlankNodePtr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "_:{}", self.node_id)
+    }
+}
+impl<'g> graph::BlankNodePtr<'g> for BlankNodePtr {}
+
+#[derive (Clone,PartialEq,Eq,PartialOrd,Ord)]
+pub struct IRIPtr<'g> {
+    iri: &'g str,
+}
+impl<'g> graph::IRIPtr<'g> for IRIPtr<'g> {
+    fn as_str(&self) -> &str {
+        self.iri
+    }
+}
+impl<'g> fmt::Debug for IRIPtr<'g> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "<{}>", self.iri)
+    }
+}
+
+#[derive (Clone,PartialEq)]
+pub struct DatatypePtr<'g> {
+    datatype: &'g str,
+}
+impl<'g> graph::DatatypePtr<'g> for DatatypePtr<'g> {
+    fn as_str(&self) -> &str {
+        self.datatype
+    }
+}
+
+#[derive (Clone,PartialEq,Eq,PartialOrd,Ord)]
+pub struct LiteralPtr<'g> {
+    lexical: &'g str,
+    datatype: &'g str,
+    language: Option<&'g str>,
+}
+impl<'g> graph::LiteralPtr<'g> for LiteralPtr<'g> {
+    type DatatypePtr = DatatypePtr<'g>;
+    fn as_str(&self) -> &str {
+        self.lexical
+    }
+    fn datatype(&self) -> DatatypePtr<'g> {
+        DatatypePtr { datatype: self.datatype }
+    }
+    fn datatype_str(&self) -> &str {
+        self.datatype
+    }
+    fn language(&self) -> Option<&str> {
+        self.language
+    }
+}
+impl<'g> fmt::Debug for LiteralPtr<'g> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.language {
+            None => write!(f, "\"{}\"^^<{}>", self.lexical, self.datatype),
+            Some(lang) => write!(f, "\"{}\"@{}", self.lexical, lang),
+        }
+    }
+}
+
+/// A quoted triple is numbered like the blank nodes, so it is the same triple
+/// in every graph.
+#[derive (Clone)]
+pub struct QuotedTriplePtr<'g, SPO: 'g, OPS: 'g>
+    where SPO: CompactTriple<u32>,
+          OPS: CompactTriple<u32>
+{
+    graph: &'g GraphData<SPO, OPS>,
+    node_id: u32,
+}
+impl<'g, SPO, OPS> QuotedTriplePtr<'g, SPO, OPS>
+    where SPO: CompactTriple<u32>,
+          OPS: CompactTriple<u32>
+{
+    fn triple(&self) -> SPO {
+        self.graph.quoted_triple(self.node_id).expect("quoted triple is missing")
+    }
+}
+impl<'g, SPO, OPS> PartialEq for QuotedTriplePtr<'g, SPO, OPS>
+    where SPO: CompactTriple<u32>,
+          OPS: CompactTriple<u32>
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.node_id == other.node_id
+    }
+}
+impl<'g, SPO, OPS> Eq for QuotedTriplePtr<'g, SPO, OPS>
+    where SPO: CompactTriple<u32>,
+          OPS: CompactTriple<u32>
+{
+}
+impl<'g, SPO, OPS> PartialOrd for QuotedTriplePtr<'g, SPO, OPS>
+    where SPO: CompactTriple<u32>,
+          OPS: CompactTriple<u32>
+{
+    fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl<'g, SPO, OPS> Ord for QuotedTriplePtr<'g, SPO, OPS>
+    where SPO: CompactTriple<u32>,
+          OPS: CompactTriple<u32>
+{
+    fn cmp(&self, other: &Self) -> cmp::Ordering {
+        self.node_id.cmp(&other.node_id)
+    }
+}
+impl<'g, SPO: 'g, OPS: 'g> graph::Triple<'g,
+                                         BlankNodePtr,
+                                         IRIPtr<'g>,
+                                         LiteralPtr<'g>,
+                                         QuotedTriplePtr<'g, SPO, OPS>>
+    for QuotedTriplePtr<'g, SPO, OPS>
+    where SPO: CompactTriple<u32>,
+          OPS: CompactTriple<u32>
+{
+    fn subject(&self) -> BlankNodeOrIRI<'g, SPO, OPS> {
+        subject(self.graph, &self.triple())
+    }
+    fn predicate(&self) -> IRIPtr<'g> {
+        predicate(self.graph, &self.triple())
+    }
+    fn object(&self) -> Resource<'g, SPO, OPS> {
+        object(self.graph, &self.triple())
+    }
+}
+impl<'g, SPO: 'g, OPS: 'g> graph::QuotedTriplePtr<'g, BlankNodePtr, IRIPtr<'g>, LiteralPtr<'g>>
+    for QuotedTriplePtr<'g, SPO, OPS>
+    where SPO: CompactTriple<u32>,
+          OPS: CompactTriple<u32>
+{
+}
+
+pub type BlankNodeOrIRI<'g, SPO, OPS> = graph::BlankNodeOrIRI<'g,
+                                                              BlankNodePtr,
+                                                              IRIPtr<'g>,
+                                                              QuotedTriplePtr<'g, SPO, OPS>>;
+pub type Resource<'g, SPO, OPS> = graph::Resource<'g,
+                                                  BlankNodePtr,
+                                                  IRIPtr<'g>,
+                                                  LiteralPtr<'g>,
+                                                  QuotedTriplePtr<'g, SPO, OPS>>;
+
+fn string<SPO, OPS>(graph: &GraphData<SPO, OPS>, id: u32) -> &str
+    where SPO: CompactTriple<u32>,
+          OPS: CompactTriple<u32>
+{
+    graph.strings.get(StringId { id: id })
+}
+
+fn datatype_or_lang<SPO, OPS>(graph: &GraphData<SPO, OPS>, id: u32) -> &str
+    where SPO: CompactTriple<u32>,
+          OPS: CompactTriple<u32>
+{
+    graph.datatype_or_lang.get(StringId { id: id })
+}
+
+fn subject<'g, SPO, OPS, T>(graph: &'g GraphData<SPO, OPS>, t: &T) -> BlankNodeOrIRI<'g, SPO, OPS>
+    where SPO: CompactTriple<u32>,
+          OPS: CompactTriple<u32>,
+          T: CompactTriple<u32>
+{
+    if t.subject_is_iri() {
+        graph::BlankNodeOrIRI::IRI(IRIPtr { iri: string(graph, t.subject()) })
+    } else if graph.is_quoted_triple(t.subject()) {
+        graph::BlankNodeOrIRI::QuotedTriple(QuotedTriplePtr {
+            graph: graph,
+            node_id: t.subject(),
+        })
+    } else {
+        graph::BlankNodeOrIRI::BlankNode(BlankNodePtr { node_id: t.subject() }, PhantomData)
+    }
+}
+
+fn predicate<'g, SPO, OPS, T>(graph: &'g GraphData<SPO, OPS>, t: &T) -> IRIPtr<'g>
+    where SPO: CompactTriple<u32>,
+          OPS: CompactTriple<u32>,
+          T: CompactTriple<u32>
+{
+    IRIPtr { iri: string(graph, t.predicate()) }
+}
+
+fn object<'g, SPO, OPS, T>(graph: &'g GraphData<SPO, OPS>, t: &T) -> Resource<'g, SPO, OPS>
+    where SPO: CompactTriple<u32>,
+          OPS: CompactTriple<u32>,
+          T: CompactTriple<u32>
+{
+    match t.object_type() {
+        TripleObjectType::BlankNode if graph.is_quoted_triple(t.object()) => {
+            graph::Resource::QuotedTriple(QuotedTriplePtr {
+                graph: graph,
+                node_id: t.object(),
+            })
+        }
+        TripleObjectType::BlankNode => {
+            graph::Resource::BlankNode(BlankNodePtr { node_id: t.object() }, PhantomData)
+        }
+        TripleObjectType::IRI => graph::Resource::IRI(IRIPtr { iri: string(graph, t.object()) }),
+        TripleObjectType::Literal => {
+            graph::Resource::Literal(LiteralPtr {
+                lexical: string(graph, t.object()),
+                datatype: datatype_or_lang(graph, t.datatype_or_lang()),
+                language: None,
+            })
+        }
+        TripleObjectType::LiteralLang => {
+            graph::Resource::Literal(LiteralPtr {
+                lexical: string(graph, t.object()),
+                datatype: constants::RDF_LANG_STRING,
+                language: Some(datatype_or_lang(graph, t.datatype_or_lang())),
+            })
+        }
+    }
+}
+
+/// The parts of a compact triple with the strings instead of the string ids.
+///
+/// The strings of a graph are sorted, so comparing the parts in the order of
+/// an index gives the same order as comparing the compact triples. Unlike
+/// the ids, the strings can be compared between graphs.
+#[derive (PartialEq,Eq,PartialOrd,Ord)]
+pub struct Parts<'g> {
+    subject: (bool, u32, &'g str),
+    predicate: &'g str,
+    object: (u8, u32, &'g str),
+    datatype_or_lang: &'g str,
+}
+
+fn parts<'g, SPO, OPS, T>(graph: &'g GraphData<SPO, OPS>, t: &T) -> Parts<'g>
+    where SPO: CompactTriple<u32>,
+          OPS: CompactTriple<u32>,
+          T: CompactTriple<u32>
+{
+    let subject = if t.subject_is_iri() {
+        (true, 0, string(graph, t.subject()))
+    } else {
+        (false, t.subject(), "")
+    };
+    // the order of the object types is the order in the compact triples
+    let (object, datatype_or_lang) = match t.object_type() {
+        TripleObjectType::BlankNode => ((0, t.object(), ""), ""),
+        TripleObjectType::IRI => ((1, 0, string(graph, t.object())), ""),
+        TripleObjectType::LiteralLang => {
+            ((2, 0, string(graph, t.object())), self::datatype_or_lang(graph, t.datatype_or_lang()))
+        }
+        TripleObjectType::Literal => {
+            ((3, 0, string(graph, t.object())), self::datatype_or_lang(graph, t.datatype_or_lang()))
+        }
+    };
+    Parts {
+        subject: subject,
+        predicate: string(graph, t.predicate()),
+        object: object,
+        datatype_or_lang: datatype_or_lang,
+    }
+}
+
+/// The order of the triples in an index.
+pub trait Order {
+    fn cmp(a: &Parts, b: &Parts) -> cmp::Ordering;
+}
+impl<SPO, OPS> Order for SPOIndex<SPO, OPS>
+    where SPO: CompactTriple<u32>,
+          OPS: CompactTriple<u32>
+{
+    fn cmp(a: &Parts, b: &Parts) -> cmp::Ordering {
+        a.cmp(b)
+    }
+}
+impl<SPO, OPS> Order for OPSIndex<SPO, OPS>
+    where SPO: CompactTriple<u32>,
+          OPS: CompactTriple<u32>
+{
+    fn cmp(a: &Parts, b: &Parts) -> cmp::Ordering {
+        (a.object, a.predicate, a.subject, a.datatype_or_lang)
+            .cmp(&(b.object, b.predicate, b.subject, b.datatype_or_lang))
+    }
+}
+
+/// A triple from one of the graphs that compares with the triples from the
+/// other graphs in the order of index `F`.
+pub struct Triple<'g, SPO: 'g, OPS: 'g, T, F>
+    where SPO: CompactTriple<u32>,
+          OPS: CompactTriple<u32>,
+          T: CompactTriple<u32>
+{
+    graph: &'g GraphData<SPO, OPS>,
+    triple: T,
+    phantom: PhantomData<F>,
+}
+pub type SPOTriple<'g, SPO, OPS> = Triple<'g, SPO, OPS, SPO, SPOIndex<SPO, OPS>>;
+pub type OPSTriple<'g, SPO, OPS> = Triple<'g, SPO, OPS, OPS, OPSIndex<SPO, OPS>>;
+
+impl<'g, SPO, OPS, T, F> Clone for Triple<'g, SPO, OPS, T, F>
+    where SPO: CompactTriple<u32>,
+          OPS: CompactTriple<u32>,
+          T: CompactTriple<u32>
+{
+    fn clone(&self) -> Self {
+        Triple {
+            graph: self.graph,
+            triple: self.triple,
+            phantom: PhantomData,
+        }
+    }
+}
+impl<'g, SPO, OPS, T, F> From<triple::Triple<'g, SPO, OPS, T>> for Triple<'g, SPO, OPS, T, F>
+    where SPO: CompactTriple<u32>,
+          OPS: CompactTriple<u32>,
+          T: CompactTriple<u32>
+{
+    fn from(t: triple::Triple<'g, SPO, OPS, T>) -> Self {
+        Triple {
+            graph: t.graph,
+            triple: t.triple,
+            phantom: PhantomData,
+        }
+    }
+}
+impl<'g, SPO, OPS, T, F> PartialEq for Triple<'g, SPO, OPS, T, F>
+    where SPO: CompactTriple<u32>,
+          OPS: CompactTriple<u32>,
+          T: CompactTriple<u32>,
+          F: Order
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == cmp::Ordering::Equal
+    }
+}
+impl<'g, SPO, OPS, T, F> Eq for Triple<'g, SPO, OPS, T, F>
+    where SPO: CompactTriple<u32>,
+          OPS: CompactTriple<u32>,
+          T: CompactTriple<u32>,
+          F: Order
+{
+}
+impl<'g, SPO, OPS, T, F> PartialOrd for Triple<'g, SPO, OPS, T, F>
+    where SPO: CompactTriple<u32>,
+          OPS: CompactTriple<u32>,
+          T: CompactTriple<u32>,
+          F: Order
+{
+    fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl<'g, SPO, OPS, T, F> Ord for Triple<'g, SPO, OPS, T, F>
+    where SPO: CompactTriple<u32>,
+          OPS: CompactTriple<u32>,
+          T: CompactTriple<u32>,
+          F: Order
+{
+    fn cmp(&self, other: &Self) -> cmp::Ordering {
+        F::cmp(&parts(self.graph, &self.triple),
+               &parts(other.graph, &other.triple))
+    }
+}
+impl<'g, SPO: 'g, OPS: 'g, T, F> graph::Triple<'g,
+                                                 BlankNodePtr,
+                                                 IRIPtr<'g>,
+                                                 LiteralPtr<'g>,
+                                                 QuotedTriplePtr<'g, SPO, OPS>>
+    for Triple<'g, SPO, OPS, T, F>
+    where SPO: CompactTriple<u32>,
+          OPS: CompactTriple<u32>,
+          T: CompactTriple<u32>
+{
+    fn subject(&self) -> BlankNodeOrIRI<'g, SPO, OPS> {
+        subject(self.graph, &self.triple)
+    }
+    fn predicate(&self) -> IRIPtr<'g> {
+        predicate(self.graph, &self.triple)
+    }
+    fn object(&self) -> Resource<'g, SPO, OPS> {
+        object(self.graph, &self.triple)
+    }
+}
+
+/// Merges the sorted iterators of the graphs into one sorted iterator.
+/// A triple that is in more than one graph is returned once.
+pub struct MergeIterator<I, T> {
+    iters: Vec<I>,
+    /// the next triple of each iterator
+    heads: Vec<Option<T>>,
+}
+
+impl<I, T> MergeIterator<I, T>
+    where I: Iterator,
+          T: From<I::Item>
+{
+    fn new(mut iters: Vec<I>) -> MergeIterator<I, T> {
+        let heads = iters.iter_mut().map(|i| i.next().map(T::from)).collect();
+        MergeIterator {
+            iters: iters,
+            heads: heads,
+        }
+    }
+}
+
+impl<I, T> Iterator for MergeIterator<I, T>
+    where I: Iterator,
+          T: From<I::Item> + Ord + Clone
+{
+    type Item = T;
+    fn next(&mut self) -> Option<T> {
+        let min = match self.heads.iter().filter_map(|h| h.as_ref()).min() {
+            Some(min) => min.clone(),
+            None => return None,
+        };
+        for (head, iter) in self.heads.iter_mut().zip(self.iters.iter_mut()) {
+            if head.as_ref() == Some(&min) {
+                *head = iter.next().map(T::from);
+            }
+        }
+        Some(min)
+    }
+}
+impl<I, T> SortedIterator for MergeIterator<I, T>
+    where I: SortedIterator,
+          I::Item: Ord,
+          T: From<I::Item> + Ord + Clone
+{
+}
+
+/// Find the subject in one of the graphs.
+fn graph_subject<'g, SPO, OPS>(graph: &'g Graph<SPO, OPS>,
+                               subject: &BlankNodeOrIRI<'g, SPO, OPS>)
+                               -> Option<triple::BlankNodeOrIRI<'g, SPO, OPS>>
+    where SPO: CompactTriple<u32>,
+          OPS: CompactTriple<u32>
+{
+    match *subject {
+        graph::BlankNodeOrIRI::BlankNode(b, _) => {
+            Some(graph::BlankNodeOrIRI::BlankNode(graph_blank_node(graph, b.node_id), PhantomData))
+        }
+        graph::BlankNodeOrIRI::IRI(ref i) => {
+            graph::Graph::find_iri(graph, i.iri).map(graph::BlankNodeOrIRI::IRI)
+        }
+        graph::BlankNodeOrIRI::QuotedTriple(ref t) => {
+            Some(graph::BlankNodeOrIRI::QuotedTriple(graph_quoted_triple(graph, t.node_id)))
+        }
+    }
+}
+
+/// Find the object in one of the graphs.
+fn graph_object<'g, SPO, OPS>(graph: &'g Graph<SPO, OPS>,
+                              object: &Resource<'g, SPO, OPS>)
+                              -> Option<triple::Resource<'g, SPO, OPS>>
+    where SPO: CompactTriple<u32>,
+          OPS: CompactTriple<u32>
+{
+    match *object {
+        graph::Resource::BlankNode(b, _) => {
+            Some(graph::Resource::BlankNode(graph_blank_node(graph, b.node_id), PhantomData))
+        }
+        graph::Resource::IRI(ref i) => {
+            graph::Graph::find_iri(graph, i.iri).map(graph::Resource::IRI)
+        }
+        graph::Resource::Literal(ref l) => {
+            graph::Graph::find_literal(graph, l.lexical, l.datatype, l.language)
+                .map(graph::Resource::Literal)
+        }
+        graph::Resource::QuotedTriple(ref t) => {
+            Some(graph::Resource::QuotedTriple(graph_quoted_triple(graph, t.node_id)))
+        }
+    }
+}
+
+fn graph_blank_node<'g, SPO, OPS>(graph: &'g Graph<SPO, OPS>,
+                                  node_id: u32)
+                                  -> triple::BlankNodePtr<'g, SPO, OPS>
+    where SPO: CompactTriple<u32>,
+          OPS: CompactTriple<u32>
+{
+    triple::BlankNodePtr {
+        graph_id: graph.d.graph_id,
+        node_id: node_id,
+        phantom: PhantomData,
+    }
+}
+
+fn graph_quoted_triple<'g, SPO, OPS>(graph: &'g Graph<SPO, OPS>,
+                                     node_id: u32)
+                                     -> triple::QuotedTriplePtr<'g, SPO, OPS>
+    where SPO: CompactTriple<u32>,
+          OPS: CompactTriple<u32>
+{
+    triple::QuotedTriplePtr {
+        graph: &graph.d,
+        node_id: node_id,
+    }
+}
+
+impl<'g, SPO: 'g, OPS: 'g> graph::Graph<'g> for UnionGraph<SPO, OPS>
+    where SPO: CompactTriple<u32>,
+          OPS: CompactTriple<u32>
+{
+    type BlankNodePtr = BlankNodePtr;
+    type IRIPtr = IRIPtr<'g>;
+    type LiteralPtr = LiteralPtr<'g>;
+    type QuotedTriplePtr = QuotedTriplePtr<'g, SPO, OPS>;
+    type SPOTriple = SPOTriple<'g, SPO, OPS>;
+    type SPOIter = MergeIterator<GraphIterator<'g, SPO, OPS, SPO, SPOIndex<SPO, OPS>>,
+                                 SPOTriple<'g, SPO, OPS>>;
+    type SPORangeIter = MergeIterator<TripleRangeIterator<'g, SPO, OPS, SPO, SPOIndex<SPO, OPS>>,
+                                      SPOTriple<'g, SPO, OPS>>;
+    type OPSTriple = OPSTriple<'g, SPO, OPS>;
+    type OPSRangeIter = MergeIterator<TripleRangeIterator<'g, SPO, OPS, OPS, OPSIndex<SPO, OPS>>,
+                                      OPSTriple<'g, SPO, OPS>>;
+    type PSORangeIter = MergeIterator<PredicateIterator<'g, SPO, OPS>, SPOTriple<'g, SPO, OPS>>;
+    fn iter(&'g self) -> Self::SPOIter {
+        MergeIterator::new(self.graphs.iter().map(graph::Graph::iter).collect())
+    }
+    fn find_iri<'a>(&'g self, iri: &'a str) -> Option<IRIPtr<'g>> {
+        self.graphs
+            .iter()
+            .filter_map(|g| g.d.strings.find(iri).map(|id| g.d.strings.get(id)))
+            .next()
+            .map(|iri| IRIPtr { iri: iri })
+    }
+    fn find_literal<'a>(&'g self,
+                        literal: &'a str,
+                        datatype: &'a str,
+                        language: Option<&'a str>)
+                        -> Option<LiteralPtr<'g>> {
+        self.graphs
+            .iter()
+            .filter_map(|g| graph::Graph::find_literal(g, literal, datatype, language))
+            .next()
+            .map(|l| {
+                LiteralPtr {
+                    lexical: string(l.graph, l.lexical),
+                    datatype: datatype_or_lang(l.graph, l.datatype),
+                    language: l.language.map(|lang| datatype_or_lang(l.graph, lang)),
+                }
+            })
+    }
+    fn find_datatype<'a>(&'g self, datatype: &'a str) -> Option<DatatypePtr<'g>> {
+        self.graphs
+            .iter()
+            .filter_map(|g| {
+                let datatypes = &g.d.datatype_or_lang;
+                datatypes.find(datatype).map(|id| datatypes.get(id))
+            })
+            .next()
+            .map(|datatype| DatatypePtr { datatype: datatype })
+    }
+    fn iter_s(&'g self, subject: &BlankNodeOrIRI<'g, SPO, OPS>) -> Self::SPORangeIter {
+        MergeIterator::new(self.graphs
+            .iter()
+            .map(|g| match graph_subject(g, subject) {
+                Some(s) => graph::Graph::iter_s(g, &s),
+                None => graph::Graph::empty_spo_range(g),
+            })
+            .collect())
+    }
+    fn iter_s_p(&'g self,
+                subject: &BlankNodeOrIRI<'g, SPO, OPS>,
+                predicate: &IRIPtr<'g>)
+                -> Self::SPORangeIter {
+        MergeIterator::new(self.graphs
+            .iter()
+            .map(|g| match (graph_subject(g, subject), graph::Graph::find_iri(g, predicate.iri)) {
+                (Some(s), Some(p)) => graph::Graph::iter_s_p(g, &s, &p),
+                _ => graph::Graph::empty_spo_range(g),
+            })
+            .collect())
+    }
+    fn iter_o(&'g self, object: &Resource<'g, SPO, OPS>) -> Self::OPSRangeIter {
+        MergeIterator::new(self.graphs
+            .iter()
+            .map(|g| match graph_object(g, object) {
+                Some(o) => graph::Graph::iter_o(g, &o),
+                None => graph::Graph::empty_ops_range(g),
+            })
+            .collect())
+    }
+    fn iter_o_p(&'g self,
+                object: &Resource<'g, SPO, OPS>,
+                predicate: &IRIPtr<'g>)
+                -> Self::OPSRangeIter {
+        MergeIterator::new(self.graphs
+            .iter()
+            .map(|g| match (graph_object(g, object), graph::Graph::find_iri(g, predicate.iri)) {
+                (Some(o), Some(p)) => graph::Graph::iter_o_p(g, &o, &p),
+                _ => graph::Graph::empty_ops_range(g),
+            })
+            .collect())
+    }
+    fn iter_p(&'g self, predicate: &IRIPtr<'g>) -> Self::PSORangeIter {
+        MergeIterator::new(self.graphs
+            .iter()
+            .map(|g| match graph::Graph::find_iri(g, predicate.iri) {
+                Some(p) => graph::Graph::iter_p(g, &p),
+                None => graph::Graph::empty_pso_range(g),
+            })
+            .collect())
+    }
+    fn empty_spo_range(&'g self) -> Self::SPORangeIter {
+        MergeIterator::new(Vec::new())
+    }
+    fn empty_ops_range(&'g self) -> Self::OPSRangeIter {
+        MergeIterator::new(Vec::new())
+    }
+    fn empty_pso_range(&'g self) -> Self::PSORangeIter {
+        MergeIterator::new(Vec::new())
+    }
+}
+
+#[test]
+fn test_union_graph() {
+    use graph::{Dataset, DatasetWriter, Graph, GraphWriter, LiteralPtr, Triple,
+                WriterBlankNodeOrIRI, WriterResource};
+    use super::dataset::DatasetCreator;
+    use super::triple64::*;
+    let mut creator: DatasetCreator<Triple64SPO, Triple64OPS> = DatasetCreator::with_capacity(0);
+    let g = WriterBlankNodeOrIRI::IRI(creator.create_iri(&"g"));
+    let h = WriterBlankNodeOrIRI::IRI(creator.create_iri(&"h"));
+    let b = WriterBlankNodeOrIRI::BlankNode(creator.create_blank_node(), PhantomData);
+    let s = WriterBlankNodeOrIRI::IRI(creator.create_iri(&"s"));
+    let p = creator.create_iri(&"p");
+    let q = creator.create_iri(&"q");
+    let l = WriterResource::Literal(creator.create_literal(&"l"));
+    let o = WriterResource::IRI(creator.create_iri(&"o"));
+    let en = creator.create_language("en");
+    let x = WriterResource::Literal(creator.create_literal_language("x", &en));
+    creator.add(&b, &p, &l);
+    creator.add_quad(Some(&g), &b, &p, &o).unwrap();
+    creator.add_quad(Some(&g), &s, &q, &x).unwrap();
+    creator.add_quad(Some(&h), &b, &p, &l).unwrap();
+    let dataset = creator.collect_dataset();
+    let union = dataset.union_graph();
+    let triples: Vec<_> = union.iter().collect();
+    // the triple that is in two graphs is returned once
+    assert_eq!(triples.len(), 3);
+    assert!(triples.windows(2).all(|w| w[0] < w[1]));
+    let b = triples[0].subject();
+    assert_eq!(union.iter_s(&b).count(), 2);
+    let p = union.find_iri("p").unwrap();
+    assert_eq!(union.iter_s_p(&b, &p).count(), 2);
+    assert_eq!(union.iter_p(&p).count(), 2);
+    let l = union.find_literal("l", ::constants::XSD_STRING, None).unwrap();
+    assert_eq!(union.iter_o(&l.to_resource()).count(), 1);
+    let x = union.find_literal("x", ::constants::RDF_LANG_STRING, Some("en")).unwrap();
+    let q = union.find_iri("q").unwrap();
+    let triple = union.iter_o_p(&x.to_resource(), &q).next().unwrap();
+    assert_eq!(triple.subject().as_iri().map(|s| s.iri), Some("s"));
+    assert!(union.find_iri("r").is_none());
+}
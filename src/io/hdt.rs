@@ -0,0 +1,913 @@
+//! Reading and writing HDT (Header-Dictionary-Triples) files.
+//!
+//! An HDT file has four sections that each start with control information:
+//! a global section, a header with metadata in N-Triples, a dictionary with
+//! the sorted terms and the triples as numbers that refer to the dictionary.
+//! This module supports the dictionary with four sections (shared subjects
+//! and objects, subjects, predicates and objects), each compressed with
+//! plain front coding, and bitmap triples in SPO order. These are the
+//! defaults of the HDT tools.
+//!
+//! In the dictionary, IRIs are written without brackets, blank nodes start
+//! with `_:` and literals are written as `"lexical"`, `"lexical"@lang` or
+//! `"lexical"^^<datatype>`.
+
+use constants;
+use crc::Crc32;
+use error::{Error, Result};
+use graph;
+use graph::{BlankNodeOrIRI, DatatypePtr, Graph, IRIPtr, LiteralPtr, Resource, Triple,
+            WriterBlankNodeOrIRI, WriterResource};
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::io::Write;
+use std::marker::PhantomData;
+
+const COOKIE: &'static [u8; 4] = b"$HDT";
+const GLOBAL: u8 = 1;
+const HEADER: u8 = 2;
+const DICTIONARY: u8 = 3;
+const TRIPLES: u8 = 4;
+const HDT_CONTAINER: &'static str = "<http://purl.org/HDT/hdt#HDTv1>";
+const HEADER_NTRIPLES: &'static str = "ntriples";
+const DICTIONARY_FOUR: &'static str = "<http://purl.org/HDT/hdt#dictionaryFour>";
+const TRIPLES_BITMAP: &'static str = "<http://purl.org/HDT/hdt#triplesBitmap>";
+/// the value of the `order` property for SPO
+const ORDER_SPO: &'static str = "1";
+const TYPE_BITMAP: u8 = 1;
+const TYPE_SEQUENCE: u8 = 1;
+const TYPE_PFC: u8 = 2;
+/// the number of strings in a block of a front coded string section
+const BLOCK_SIZE: u64 = 16;
+
+fn crc8(bytes: &[u8]) -> u8 {
+    let mut crc = 0u8;
+    for b in bytes {
+        crc ^= *b;
+        for _ in 0..8 {
+            crc = if crc & 0x80 != 0 { (crc << 1) ^ 0x07 } else { crc << 1 };
+        }
+    }
+    crc
+}
+
+fn crc16(bytes: &[u8]) -> u16 {
+    let mut crc = 0u16;
+    for b in bytes {
+        crc ^= *b as u16;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xa001 } else { crc >> 1 };
+        }
+    }
+    crc
+}
+
+/// CRC-32C (Castagnoli)
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = Crc32::castagnoli();
+    crc.update(bytes);
+    crc.checksum()
+}
+
+/// Write a number in the variable length encoding of HDT: seven bits per
+/// byte, least significant first, with the high bit set on the last byte.
+fn write_vbyte(bytes: &mut Vec<u8>, mut value: u64) {
+    while value > 127 {
+        bytes.push((value & 127) as u8);
+        value >>= 7;
+    }
+    bytes.push(value as u8 | 0x80);
+}
+
+/// the number of bits that is needed for `value`
+fn bits(value: u64) -> u32 {
+    64 - value.leading_zeros()
+}
+
+/// Numbers with a fixed number of bits packed into 64 bit words, like the
+/// bitmaps and sequences in HDT.
+struct BitArray {
+    words: Vec<u64>,
+    width: u32,
+    len: u64,
+}
+
+impl BitArray {
+    fn new(width: u32) -> BitArray {
+        BitArray {
+            words: Vec::new(),
+            width: width,
+            len: 0,
+        }
+    }
+    fn from_bytes(bytes: &[u8], width: u32, len: u64) -> BitArray {
+        let words = bytes.chunks(8)
+            .map(|c| {
+                let mut word = [0; 8];
+                word[..c.len()].copy_from_slice(c);
+                u64::from_le_bytes(word)
+            })
+            .collect();
+        BitArray {
+            words: words,
+            width: width,
+            len: len,
+        }
+    }
+    fn push(&mut self, value: u64) {
+        let width = self.width as u64;
+        let pos = self.len * width;
+        self.len += 1;
+        if width == 0 {
+            return;
+        }
+        let end = (pos + width).div_ceil(64) as usize;
+        if self.words.len() < end {
+            self.words.resize(end, 0);
+        }
+        let word = (pos / 64) as usize;
+        let bit = pos % 64;
+        self.words[word] |= value << bit;
+        if bit + width > 64 {
+            self.words[word + 1] |= value >> (64 - bit);
+        }
+    }
+    fn get(&self, i: u64) -> u64 {
+        let width = self.width as u64;
+        if width == 0 {
+            return 0;
+        }
+        let pos = i * width;
+        let word = (pos / 64) as usize;
+        let bit = pos % 64;
+        let mut value = self.words[word] >> bit;
+        if bit + width > 64 {
+            value |= self.words[word + 1] << (64 - bit);
+        }
+        if width < 64 {
+            value &= (1 << width) - 1;
+        }
+        value
+    }
+    /// the packed numbers as bytes without the unused bytes at the end
+    fn bytes(&self) -> Vec<u8> {
+        let len = (self.len * self.width as u64).div_ceil(8) as usize;
+        let mut bytes = Vec::with_capacity(self.words.len() * 8);
+        for word in &self.words {
+            bytes.extend_from_slice(&word.to_le_bytes());
+        }
+        bytes.truncate(len);
+        bytes
+    }
+}
+
+fn write_control_information<W: Write>(writer: &mut W,
+                                       kind: u8,
+                                       format: &str,
+                                       properties: &[(&str, String)])
+                                       -> Result<()> {
+    let mut out = Vec::new();
+    out.extend_from_slice(COOKIE);
+    out.push(kind);
+    out.extend_from_slice(format.as_bytes());
+    out.push(0);
+    for &(key, ref value) in properties {
+        out.extend_from_slice(key.as_bytes());
+        out.push(b'=');
+        out.extend_from_slice(value.as_bytes());
+        out.push(b';');
+    }
+    out.push(0);
+    let crc = crc16(&out);
+    out.extend_from_slice(&crc.to_le_bytes());
+    writer.write_all(&out)?;
+    Ok(())
+}
+
+/// Write the preamble of a bitmap, a sequence or a string section, which
+/// ends with its CRC-8.
+fn write_preamble<W: Write>(writer: &mut W, mut preamble: Vec<u8>) -> Result<()> {
+    let crc = crc8(&preamble);
+    preamble.push(crc);
+    writer.write_all(&preamble)?;
+    Ok(())
+}
+
+/// Write data that ends with its CRC-32C.
+fn write_data<W: Write>(writer: &mut W, data: &[u8]) -> Result<()> {
+    writer.write_all(data)?;
+    writer.write_all(&crc32(data).to_le_bytes())?;
+    Ok(())
+}
+
+fn write_bitmap<W: Write>(writer: &mut W, bitmap: &BitArray) -> Result<()> {
+    let mut preamble = vec![TYPE_BITMAP];
+    write_vbyte(&mut preamble, bitmap.len);
+    write_preamble(writer, preamble)?;
+    write_data(writer, &bitmap.bytes())
+}
+
+fn write_sequence<W: Write>(writer: &mut W, values: &[u64]) -> Result<()> {
+    let mut sequence = BitArray::new(bits(values.iter().cloned().max().unwrap_or(0)));
+    for value in values {
+        sequence.push(*value);
+    }
+    let mut preamble = vec![TYPE_SEQUENCE, sequence.width as u8];
+    write_vbyte(&mut preamble, sequence.len);
+    write_preamble(writer, preamble)?;
+    write_data(writer, &sequence.bytes())
+}
+
+/// Write sorted strings with plain front coding. Each block starts with a
+/// complete string. The other strings are written as the length of the
+/// prefix that they share with the previous string and the rest of the
+/// string.
+fn write_strings<W: Write>(writer: &mut W, strings: &[String]) -> Result<()> {
+    let mut text = Vec::new();
+    let mut blocks = Vec::new();
+    for block in strings.chunks(BLOCK_SIZE as usize) {
+        blocks.push(text.len() as u64);
+        text.extend_from_slice(block[0].as_bytes());
+        text.push(0);
+        for pair in block.windows(2) {
+            let prefix = pair[0]
+                .bytes()
+                .zip(pair[1].bytes())
+                .take_while(|&(a, b)| a == b)
+                .count();
+            write_vbyte(&mut text, prefix as u64);
+            text.extend_from_slice(&pair[1].as_bytes()[prefix..]);
+            text.push(0);
+        }
+    }
+    blocks.push(text.len() as u64);
+    let mut preamble = vec![TYPE_PFC];
+    write_vbyte(&mut preamble, strings.len() as u64);
+    write_vbyte(&mut preamble, text.len() as u64);
+    write_vbyte(&mut preamble, BLOCK_SIZE);
+    write_preamble(writer, preamble)?;
+    write_sequence(writer, &blocks)?;
+    write_data(writer, &text)
+}
+
+fn quoted_triple_error() -> Error {
+    Error::Custom("Quoted triples cannot be written as HDT.")
+}
+
+fn subject_string<'g, B, I, T>(subject: &BlankNodeOrIRI<'g, B, I, T>) -> Result<String>
+    where B: graph::BlankNodePtr<'g> + Display,
+          I: IRIPtr<'g>
+{
+    match *subject {
+        BlankNodeOrIRI::BlankNode(ref b, _) => Ok(format!("_:{}", b)),
+        BlankNodeOrIRI::IRI(ref i) => Ok(String::from(i.as_str())),
+        BlankNodeOrIRI::QuotedTriple(_) => Err(quoted_triple_error()),
+    }
+}
+
+fn object_string<'g, B, I, L, T>(object: &Resource<'g, B, I, L, T>) -> Result<String>
+    where B: graph::BlankNodePtr<'g> + Display,
+          I: IRIPtr<'g>,
+          L: LiteralPtr<'g>
+{
+    match *object {
+        Resource::BlankNode(ref b, _) => Ok(format!("_:{}", b)),
+        Resource::IRI(ref i) => Ok(String::from(i.as_str())),
+        Resource::Literal(ref l) => {
+            if l.as_str().contains('\0') {
+                return Err(Error::Custom("HDT cannot contain literals with a null character."));
+            }
+            Ok(match l.language() {
+                Some(lang) => format!("\"{}\"@{}", l.as_str(), lang),
+                None if l.datatype().as_str() == constants::XSD_STRING => {
+                    format!("\"{}\"", l.as_str())
+                }
+                None => format!("\"{}\"^^<{}>", l.as_str(), l.datatype().as_str()),
+            })
+        }
+        Resource::QuotedTriple(_) => Err(quoted_triple_error()),
+    }
+}
+
+fn sorted(mut strings: Vec<String>) -> Vec<String> {
+    strings.sort();
+    strings.dedup();
+    strings
+}
+
+/// Return the id of `string` in the sorted `shared` strings or else in the
+/// sorted `strings`. Ids start at 1.
+fn find_id(shared: &[String], strings: &[String], string: &str) -> u64 {
+    match shared.binary_search_by(|s| s.as_str().cmp(string)) {
+        Ok(pos) => pos as u64 + 1,
+        Err(_) => {
+            let pos = strings.binary_search_by(|s| s.as_str().cmp(string))
+                .expect("string is missing from the dictionary");
+            (shared.len() + pos) as u64 + 1
+        }
+    }
+}
+
+/// Write out a graph as HDT.
+///
+/// `base` is the IRI of the dataset that is described in the header.
+/// HDT has no syntax for quoted triples, so a graph with quoted triples
+/// gives an error.
+pub fn write_hdt<'g, G: 'g, W>(base: &str, graph: &'g G, writer: &mut W) -> Result<()>
+    where G: Graph<'g>,
+          <G as Graph<'g>>::BlankNodePtr: Display,
+          W: Write
+{
+    // collect the terms
+    let mut subjects = Vec::new();
+    let mut predicates = Vec::new();
+    let mut objects = Vec::new();
+    for triple in graph.iter() {
+        subjects.push(subject_string(&triple.subject())?);
+        predicates.push(String::from(triple.predicate().as_str()));
+        objects.push(object_string(&triple.object())?);
+    }
+    let subjects = sorted(subjects);
+    let predicates = sorted(predicates);
+    let objects = sorted(objects);
+    let shared: Vec<String> = subjects.iter()
+        .filter(|s| objects.binary_search(s).is_ok())
+        .cloned()
+        .collect();
+    let subjects: Vec<String> = subjects.into_iter()
+        .filter(|s| shared.binary_search(s).is_err())
+        .collect();
+    let objects: Vec<String> = objects.into_iter()
+        .filter(|s| shared.binary_search(s).is_err())
+        .collect();
+
+    // translate the triples to ids
+    let mut triples = Vec::new();
+    for triple in graph.iter() {
+        let subject = subject_string(&triple.subject())?;
+        let object = object_string(&triple.object())?;
+        triples.push((find_id(&shared, &subjects, &subject),
+                      find_id(&[], &predicates, triple.predicate().as_str()),
+                      find_id(&shared, &objects, &object)));
+    }
+    triples.sort();
+    triples.dedup();
+    let mut seq_y = Vec::new();
+    let mut seq_z = Vec::with_capacity(triples.len());
+    let mut bitmap_y = BitArray::new(1);
+    let mut bitmap_z = BitArray::new(1);
+    for (i, &(s, p, o)) in triples.iter().enumerate() {
+        if i == 0 || triples[i - 1].0 != s || triples[i - 1].1 != p {
+            seq_y.push(p);
+        }
+        seq_z.push(o);
+        let next = triples.get(i + 1);
+        let last_object = next.is_none_or(|n| n.0 != s || n.1 != p);
+        bitmap_z.push(last_object as u64);
+        if last_object {
+            bitmap_y.push(next.is_none_or(|n| n.0 != s) as u64);
+        }
+    }
+
+    let size_strings: usize = shared.iter()
+        .chain(subjects.iter())
+        .chain(predicates.iter())
+        .chain(objects.iter())
+        .map(|s| s.len())
+        .sum();
+    let header = format!("<{base}> <{rdf}type> <{hdt}Dataset> .\n\
+                          <{base}> <{rdf}type> <{void}Dataset> .\n\
+                          <{base}> <{void}triples> \"{triples}\" .\n\
+                          <{base}> <{void}properties> \"{properties}\" .\n\
+                          <{base}> <{void}distinctSubjects> \"{subjects}\" .\n\
+                          <{base}> <{void}distinctObjects> \"{objects}\" .\n\
+                          <{base}> <{hdt}formatInformation> _:format .\n\
+                          _:format <{hdt}dictionary> _:dictionary .\n\
+                          _:format <{hdt}triples> _:triples .\n\
+                          _:dictionary <http://purl.org/dc/terms/format> {dictionary} .\n\
+                          _:dictionary <{hdt}dictionarynumSharedSubjectObject> \"{shared}\" .\n\
+                          _:dictionary <{hdt}dictionarysizeStrings> \"{size}\" .\n\
+                          _:triples <http://purl.org/dc/terms/format> {triples_format} .\n\
+                          _:triples <{hdt}triplesnumTriples> \"{triples}\" .\n\
+                          _:triples <{hdt}triplesOrder> \"SPO\" .\n",
+                         base = base,
+                         rdf = "http://www.w3.org/1999/02/22-rdf-syntax-ns#",
+                         hdt = "http://purl.org/HDT/hdt#",
+                         void = "http://rdfs.org/ns/void#",
+                         triples = triples.len(),
+                         properties = predicates.len(),
+                         subjects = shared.len() + subjects.len(),
+                         objects = shared.len() + objects.len(),
+                         dictionary = DICTIONARY_FOUR,
+                         shared = shared.len(),
+                         size = size_strings,
+                         triples_format = TRIPLES_BITMAP);
+
+    write_control_information(writer, GLOBAL, HDT_CONTAINER, &[("BaseUri", base.to_string())])?;
+    write_control_information(writer,
+                              HEADER,
+                              HEADER_NTRIPLES,
+                              &[("length", header.len().to_string())])?;
+    writer.write_all(header.as_bytes())?;
+    write_control_information(writer,
+                              DICTIONARY,
+                              DICTIONARY_FOUR,
+                              &[("mapping", String::from("1")),
+                                ("sizeStrings", size_strings.to_string())])?;
+    write_strings(writer, &shared)?;
+    write_strings(writer, &subjects)?;
+    write_strings(writer, &predicates)?;
+    write_strings(writer, &objects)?;
+    write_control_information(writer,
+                              TRIPLES,
+                              TRIPLES_BITMAP,
+                              &[("order", String::from(ORDER_SPO)),
+                                ("numTriples", triples.len().to_string())])?;
+    write_bitmap(writer, &bitmap_y)?;
+    write_bitmap(writer, &bitmap_z)?;
+    write_sequence(writer, &seq_y)?;
+    write_sequence(writer, &seq_z)
+}
+
+struct ControlInformation {
+    format: String,
+    properties: String,
+}
+
+impl ControlInformation {
+    fn property(&self, key: &str) -> Option<&str> {
+        self.properties
+            .split(';')
+            .filter_map(|p| {
+                let mut parts = p.splitn(2, '=');
+                match (parts.next(), parts.next()) {
+                    (Some(k), Some(v)) if k == key => Some(v),
+                    _ => None,
+                }
+            })
+            .next()
+    }
+}
+
+/// Reads the parts of an HDT file.
+struct Input<'a> {
+    src: &'a [u8],
+    pos: usize,
+}
+
+fn truncated() -> Error {
+    Error::Custom("The HDT data is truncated.")
+}
+
+fn checksum_error() -> Error {
+    Error::Custom("The checksum of the HDT data does not match.")
+}
+
+impl<'a> Input<'a> {
+    fn read(&mut self, len: u64) -> Result<&'a [u8]> {
+        if len > (self.src.len() - self.pos) as u64 {
+            return Err(truncated());
+        }
+        let start = self.pos;
+        self.pos += len as usize;
+        Ok(&self.src[start..self.pos])
+    }
+    fn read_u8(&mut self) -> Result<u8> {
+        Ok(self.read(1)?[0])
+    }
+    fn read_vbyte(&mut self) -> Result<u64> {
+        let mut value = 0;
+        let mut shift = 0;
+        loop {
+            let b = self.read_u8()?;
+            if shift > 63 {
+                return Err(Error::Custom("A number in the HDT data is too large."));
+            }
+            value |= ((b & 127) as u64) << shift;
+            if b & 0x80 != 0 {
+                return Ok(value);
+            }
+            shift += 7;
+        }
+    }
+    /// read up to the next null byte and skip the null byte
+    fn read_until_null(&mut self) -> Result<&'a [u8]> {
+        let len = self.src[self.pos..].iter().position(|b| *b == 0).ok_or_else(truncated)?;
+        let bytes = self.read(len as u64)?;
+        self.pos += 1;
+        Ok(bytes)
+    }
+    fn check_crc8(&mut self, start: usize) -> Result<()> {
+        let crc = crc8(&self.src[start..self.pos]);
+        if self.read_u8()? != crc {
+            return Err(checksum_error());
+        }
+        Ok(())
+    }
+    fn check_crc32(&mut self, data: &[u8]) -> Result<()> {
+        let mut crc = [0; 4];
+        crc.copy_from_slice(self.read(4)?);
+        if u32::from_le_bytes(crc) != crc32(data) {
+            return Err(checksum_error());
+        }
+        Ok(())
+    }
+    fn read_control_information(&mut self, kind: u8) -> Result<ControlInformation> {
+        let start = self.pos;
+        if self.read(COOKIE.len() as u64)? != &COOKIE[..] {
+            return Err(Error::Custom("The data is not HDT."));
+        }
+        if self.read_u8()? != kind {
+            return Err(Error::Custom("The sections of the HDT data are not in the expected \
+                                      order."));
+        }
+        let format = String::from_utf8(self.read_until_null()?.to_vec())?;
+        let properties = String::from_utf8(self.read_until_null()?.to_vec())?;
+        let expected = crc16(&self.src[start..self.pos]);
+        let mut crc = [0; 2];
+        crc.copy_from_slice(self.read(2)?);
+        if u16::from_le_bytes(crc) != expected {
+            return Err(checksum_error());
+        }
+        Ok(ControlInformation {
+            format: format,
+            properties: properties,
+        })
+    }
+    fn read_bit_array(&mut self, width: u32, len: u64) -> Result<BitArray> {
+        let size = len.checked_mul(width as u64).ok_or_else(truncated)?;
+        let data = self.read(size.div_ceil(8))?;
+        self.check_crc32(data)?;
+        Ok(BitArray::from_bytes(data, width, len))
+    }
+    fn read_bitmap(&mut self) -> Result<BitArray> {
+        let start = self.pos;
+        if self.read_u8()? != TYPE_BITMAP {
+            return Err(Error::Custom("The HDT data has an unsupported bitmap."));
+        }
+        let len = self.read_vbyte()?;
+        self.check_crc8(start)?;
+        self.read_bit_array(1, len)
+    }
+    fn read_sequence(&mut self) -> Result<BitArray> {
+        let start = self.pos;
+        if self.read_u8()? != TYPE_SEQUENCE {
+            return Err(Error::Custom("The HDT data has an unsupported sequence."));
+        }
+        let width = self.read_u8()? as u32;
+        if width > 64 {
+            return Err(Error::Custom("The HDT data has an invalid sequence."));
+        }
+        let len = self.read_vbyte()?;
+        self.check_crc8(start)?;
+        self.read_bit_array(width, len)
+    }
+    fn read_strings(&mut self) -> Result<Vec<String>> {
+        let start = self.pos;
+        if self.read_u8()? != TYPE_PFC {
+            return Err(Error::Custom("The HDT data has an unsupported string section."));
+        }
+        let len = self.read_vbyte()?;
+        let text_len = self.read_vbyte()?;
+        let block_size = self.read_vbyte()?;
+        self.check_crc8(start)?;
+        // the positions of the blocks are not needed to read all strings
+        self.read_sequence()?;
+        let text = self.read(text_len)?;
+        self.check_crc32(text)?;
+        if block_size == 0 || len > text_len {
+            return Err(Error::Custom("The HDT data has an invalid string section."));
+        }
+        let mut text = Input { src: text, pos: 0 };
+        let mut strings: Vec<String> = Vec::with_capacity(len as usize);
+        let mut string = Vec::new();
+        for i in 0..len {
+            if i % block_size == 0 {
+                string.clear();
+            } else {
+                let prefix = text.read_vbyte()?;
+                if prefix > string.len() as u64 {
+                    return Err(Error::Custom("The HDT data has an invalid string section."));
+                }
+                string.truncate(prefix as usize);
+            }
+            string.extend_from_slice(text.read_until_null()?);
+            strings.push(String::from_utf8(string.clone())?);
+        }
+        Ok(strings)
+    }
+}
+
+struct Dictionary {
+    shared: Vec<String>,
+    subjects: Vec<String>,
+    predicates: Vec<String>,
+    objects: Vec<String>,
+}
+
+fn invalid_id() -> Error {
+    Error::Custom("The HDT triples refer to a term that is not in the dictionary.")
+}
+
+impl Dictionary {
+    fn get<'a>(shared: &'a [String], strings: &'a [String], id: u64) -> Result<&'a str> {
+        if id == 0 {
+            Err(invalid_id())
+        } else if id <= shared.len() as u64 {
+            Ok(&shared[id as usize - 1])
+        } else {
+            strings.get((id - 1) as usize - shared.len()).map(|s| s.as_str()).ok_or_else(invalid_id)
+        }
+    }
+    fn subject(&self, id: u64) -> Result<&str> {
+        Dictionary::get(&self.shared, &self.subjects, id)
+    }
+    fn predicate(&self, id: u64) -> Result<&str> {
+        Dictionary::get(&[], &self.predicates, id)
+    }
+    fn object(&self, id: u64) -> Result<&str> {
+        Dictionary::get(&self.shared, &self.objects, id)
+    }
+}
+
+/// A parser for HDT files that writes the triples to a `GraphWriter`.
+///
+/// The dictionary and the triple index are read when the parser is created.
+/// Each step of the iterator adds the triples of one subject.
+pub struct HDTParser<'a, W: 'a>
+    where W: graph::GraphWriter<'a>
+{
+    writer: &'a mut W,
+    dictionary: Dictionary,
+    bitmap_y: BitArray,
+    bitmap_z: BitArray,
+    seq_y: BitArray,
+    seq_z: BitArray,
+    blank_nodes: HashMap<String, W::BlankNode>,
+    xsd_string: Option<W::Datatype>,
+    subject: u64,
+    y: u64,
+    z: u64,
+    done: bool,
+}
+
+impl<'a, W: 'a> HDTParser<'a, W>
+    where W: graph::GraphWriter<'a>
+{
+    pub fn new(src: &[u8], writer: &'a mut W) -> Result<HDTParser<'a, W>> {
+        let mut input = Input { src: src, pos: 0 };
+        let global = input.read_control_information(GLOBAL)?;
+        if global.format != HDT_CONTAINER {
+            return Err(Error::Custom("The data is not HDT."));
+        }
+        let header = input.read_control_information(HEADER)?;
+        let len = header.property("length")
+            .and_then(|l| l.parse().ok())
+            .ok_or(Error::Custom("The HDT header has no length."))?;
+        input.read(len)?;
+        let dictionary = input.read_control_information(DICTIONARY)?;
+        if dictionary.format != DICTIONARY_FOUR {
+            return Err(Error::String(format!("The HDT dictionary format {} is not supported.",
+                                             dictionary.format)));
+        }
+        let dictionary = Dictionary {
+            shared: input.read_strings()?,
+            subjects: input.read_strings()?,
+            predicates: input.read_strings()?,
+            objects: input.read_strings()?,
+        };
+        let triples = input.read_control_information(TRIPLES)?;
+        if triples.format != TRIPLES_BITMAP {
+            return Err(Error::String(format!("The HDT triples format {} is not supported.",
+                                             triples.format)));
+        }
+        if triples.property("order") != Some(ORDER_SPO) {
+            return Err(Error::Custom("Only HDT triples in SPO order are supported."));
+        }
+        let bitmap_y = input.read_bitmap()?;
+        let bitmap_z = input.read_bitmap()?;
+        let seq_y = input.read_sequence()?;
+        let seq_z = input.read_sequence()?;
+        if bitmap_y.len != seq_y.len || bitmap_z.len != seq_z.len {
+            return Err(Error::Custom("The HDT triples have bitmaps and sequences of different \
+                                      lengths."));
+        }
+        Ok(HDTParser {
+            writer: writer,
+            dictionary: dictionary,
+            bitmap_y: bitmap_y,
+            bitmap_z: bitmap_z,
+            seq_y: seq_y,
+            seq_z: seq_z,
+            blank_nodes: HashMap::new(),
+            xsd_string: None,
+            subject: 1,
+            y: 0,
+            z: 0,
+            done: false,
+        })
+    }
+    fn get_blank(&mut self, label: &str) -> W::BlankNode {
+        if let Some(n) = self.blank_nodes.get(label) {
+            return n.clone();
+        }
+        let n = self.writer.create_blank_node();
+        self.blank_nodes.insert(String::from(label), n.clone());
+        n
+    }
+    fn create_subject(&mut self, term: &str) -> Result<WriterBlankNodeOrIRI<'a, W>> {
+        if term.starts_with("_:") {
+            Ok(WriterBlankNodeOrIRI::BlankNode(self.get_blank(term), PhantomData))
+        } else if term.starts_with('"') {
+            Err(Error::Custom("The HDT data has a literal as subject."))
+        } else {
+            Ok(WriterBlankNodeOrIRI::IRI(self.writer.create_iri(&term)))
+        }
+    }
+    fn create_object(&mut self, term: &str) -> Result<WriterResource<'a, W>> {
+        if term.starts_with("_:") {
+            return Ok(WriterResource::BlankNode(self.get_blank(term), PhantomData));
+        }
+        if !term.starts_with('"') {
+            return Ok(WriterResource::IRI(self.writer.create_iri(&term)));
+        }
+        let end = term.rfind('"').unwrap_or(0);
+        if end == 0 {
+            return Err(Error::String(format!("The HDT data has an invalid literal {}.", term)));
+        }
+        let lexical = &term[1..end];
+        let suffix = &term[end + 1..];
+        let literal = if suffix.is_empty() {
+            if self.xsd_string.is_none() {
+                self.xsd_string = Some(self.writer.create_datatype(constants::XSD_STRING));
+            }
+            let datatype = self.xsd_string.as_ref().unwrap();
+            self.writer.create_literal_datatype(lexical, datatype)
+        } else if suffix.starts_with('@') && suffix.len() > 1 {
+            let language = self.writer.create_language(&suffix[1..]);
+            self.writer.create_literal_language(lexical, &language)
+        } else if suffix.starts_with("^^<") && suffix.ends_with('>') && suffix.len() > 4 {
+            let datatype = self.writer.create_datatype(&suffix[3..suffix.len() - 1]);
+            self.writer.create_literal_datatype(lexical, &datatype)
+        } else {
+            return Err(Error::String(format!("The HDT data has an invalid literal {}.", term)));
+        };
+        Ok(WriterResource::Literal(literal))
+    }
+    /// Add the triples of the next subject.
+    fn parse_subject(&mut self) -> Result<()> {
+        let term = self.dictionary.subject(self.subject)?.to_string();
+        let subject = self.create_subject(&term)?;
+        loop {
+            let term = self.dictionary.predicate(self.seq_y.get(self.y))?.to_string();
+            let predicate = self.writer.create_iri(&term.as_str());
+            loop {
+                if self.z >= self.seq_z.len {
+                    return Err(truncated());
+                }
+                let term = self.dictionary.object(self.seq_z.get(self.z))?.to_string();
+                let object = self.create_object(&term)?;
+                self.writer.add(&subject, &predicate, &object);
+                self.z += 1;
+                if self.bitmap_z.get(self.z - 1) == 1 {
+                    break;
+                }
+            }
+            self.y += 1;
+            if self.bitmap_y.get(self.y - 1) == 1 {
+                break;
+            }
+            if self.y >= self.seq_y.len {
+                return Err(truncated());
+            }
+        }
+        self.subject += 1;
+        Ok(())
+    }
+}
+
+impl<'a, W: 'a> Iterator for HDTParser<'a, W>
+    where W: graph::GraphWriter<'a>
+{
+    type Item = Result<()>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done || self.y >= self.seq_y.len {
+            return None;
+        }
+        let result = self.parse_subject();
+        if result.is_err() {
+            self.done = true;
+        }
+        Some(result)
+    }
+}
+
+#[cfg(test)]
+fn hdt_graph(hdt: &[u8]) -> ::graphs::tel::Graph128 {
+    use graph::GraphWriter;
+    use graphs::tel::GraphCreator;
+    let mut writer = GraphCreator::with_capacity(0);
+    for step in HDTParser::new(hdt, &mut writer).unwrap() {
+        step.unwrap();
+    }
+    writer.collect()
+}
+
+#[cfg(test)]
+fn to_hdt(graph: &::graphs::tel::Graph128) -> Vec<u8> {
+    let mut hdt = Vec::new();
+    write_hdt("http://a.example/", graph, &mut hdt).unwrap();
+    hdt
+}
+
+#[test]
+fn test_bit_array() {
+    for width in &[0, 1, 7, 33, 64] {
+        let mut array = BitArray::new(*width);
+        let values: Vec<u64> = (0..100u64)
+            .map(|i| if *width == 64 { !i } else { i.wrapping_mul(7919) % (1 << width) })
+            .collect();
+        for v in &values {
+            array.push(*v);
+        }
+        let array = BitArray::from_bytes(&array.bytes(), *width, array.len);
+        for (i, v) in values.iter().enumerate() {
+            assert_eq!(array.get(i as u64), *v);
+        }
+    }
+}
+
+#[test]
+fn test_hdt_round_trip() {
+    use graphs::tel::Graph128;
+    use test_support::{ntriples, turtle_graph};
+    let mut turtle = String::from("@prefix : <http://a.example/> .\n");
+    for i in 0..100 {
+        turtle.push_str(&format!(":s{} :p{} :s{}, \"value {}\", \"w{}\"@en, {} .\n",
+                                 i,
+                                 i % 3,
+                                 (i * 7) % 120,
+                                 i % 40,
+                                 i,
+                                 i % 17));
+    }
+    let graph: Graph128 = turtle_graph(&turtle);
+    let hdt = to_hdt(&graph);
+    let read = hdt_graph(&hdt);
+    assert_eq!(ntriples(&read), ntriples(&graph));
+    assert_eq!(to_hdt(&read), hdt);
+}
+
+#[test]
+fn test_hdt_blank_nodes() {
+    use graphs::tel::Graph128;
+    use test_support::{ntriples, turtle_graph};
+    let graph: Graph128 = turtle_graph("@prefix : <http://a.example/> .
+:s :p [ :q \"é\\\"ü\" ; :r ( 1 2.5 \"x\"^^:t ) ], [ :q :s ] .
+_:a :p _:a .
+");
+    let read = hdt_graph(&to_hdt(&graph));
+    assert_eq!(ntriples(&read.sort_blank_nodes()),
+               ntriples(&graph.sort_blank_nodes()));
+}
+
+#[test]
+fn test_hdt_empty() {
+    use graphs::tel::Graph128;
+    use test_support::turtle_graph;
+    let graph: Graph128 = turtle_graph("");
+    let hdt = to_hdt(&graph);
+    assert_eq!(hdt_graph(&hdt).len(), 0);
+}
+
+#[test]
+fn test_hdt_errors() {
+    use graph::GraphWriter;
+    use graphs::tel::{Graph128, GraphCreator};
+    use test_support::turtle_graph;
+    let graph: Graph128 = turtle_graph("<http://a.example/s> <http://a.example/p> \
+                                        << <http://a.example/s> <http://a.example/p> \
+                                        <http://a.example/o> >> .");
+    assert!(write_hdt("http://a.example/", &graph, &mut Vec::new()).is_err());
+
+    let graph: Graph128 = turtle_graph("<http://a.example/s> <http://a.example/p> \"o\" .");
+    let hdt = to_hdt(&graph);
+    let mut writer: GraphCreator<_, _> = GraphCreator::with_capacity(0);
+    // every changed byte is noticed, except in the header, which has no
+    // checksum
+    let header = hdt.windows(21).position(|w| w == b"<http://a.example/> <").unwrap();
+    let dictionary = hdt.windows(5).position(|w| w == b"$HDT\x03").unwrap();
+    for i in (0..header).chain(dictionary..hdt.len()) {
+        let mut corrupt = hdt.clone();
+        corrupt[i] ^= 0x10;
+        let ok = HDTParser::new(&corrupt, &mut writer)
+            .and_then(|parser| parser.collect::<Result<Vec<()>>>())
+            .is_ok();
+        assert!(!ok, "byte {} was changed", i);
+    }
+    assert!(HDTParser::new(&hdt[..hdt.len() - 1], &mut writer).is_err());
+    assert!(HDTParser::new(b"<http://a.example/s> <http://a.example/p> \"o\" .", &mut writer)
+        .is_err());
+    let _: Graph128 = writer.collect();
+}
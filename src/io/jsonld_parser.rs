@@ -0,0 +1,1204 @@
+use constants::*;
+use error::{Error, Result, StatementKind, SyntaxError};
+use graph;
+use graph::{WriterBlankNodeOrIRI, WriterResource};
+use iri;
+use serde_json;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::mem;
+use std::vec;
+
+const KEYWORDS: &'static [&'static str] = &["@base", "@container", "@context", "@direction",
+                                            "@graph", "@id", "@import", "@included", "@index",
+                                            "@json", "@language", "@list", "@nest", "@none",
+                                            "@prefix", "@propagate", "@protected", "@reverse",
+                                            "@set", "@type", "@value", "@version", "@vocab"];
+
+/// Remote contexts may load other remote contexts up to this depth.
+const MAX_REMOTE_CONTEXTS: usize = 16;
+
+/// A function that returns the JSON text of the document at an IRI.
+pub type DocumentLoader<'a> = Box<Fn(&str) -> Result<String> + 'a>;
+
+/// A term definition in a context.
+#[derive (Clone,Default)]
+struct Term {
+    /// the IRI or keyword of the term; `None` if the term is mapped to null
+    iri: Option<String>,
+    reverse: bool,
+    /// `@id`, `@vocab`, `@json`, `@none` or a datatype IRI
+    type_mapping: Option<String>,
+    /// `Some(None)` if the term has no language, also not the default one
+    language: Option<Option<String>>,
+    container: Vec<String>,
+    /// the term can be used as the prefix of a compact IRI
+    prefix: bool,
+    /// a context that applies to the values of the term
+    context: Option<Value>,
+}
+
+impl Term {
+    fn has_container(&self, container: &str) -> bool {
+        self.container.iter().any(|c| c == container)
+    }
+}
+
+#[derive (Clone,Default)]
+struct Context {
+    base: Option<String>,
+    vocab: Option<String>,
+    language: Option<String>,
+    terms: HashMap<String, Term>,
+}
+
+fn is_keyword(value: &str) -> bool {
+    KEYWORDS.contains(&value)
+}
+
+/// Values of the form `@` followed by letters are reserved for keywords and
+/// are ignored.
+fn looks_like_keyword(value: &str) -> bool {
+    value.len() > 1 && value.starts_with('@') &&
+    value[1..].chars().all(|c| c.is_ascii_alphabetic())
+}
+
+fn ends_with_gen_delim(iri: &str) -> bool {
+    iri.ends_with(|c| ":/?#[]@".contains(c))
+}
+
+fn invalid(message: &str) -> Error {
+    Error::String(format!("JSON-LD error: {}", message))
+}
+
+fn as_array(value: Value) -> Vec<Value> {
+    match value {
+        Value::Array(array) => array,
+        Value::Null => Vec::new(),
+        value => vec![value],
+    }
+}
+
+/// Append `value` to the array at `key` in `map`.
+fn append(map: &mut Map<String, Value>, key: &str, value: Value) {
+    let entry = map.entry(String::from(key)).or_insert_with(|| Value::Array(Vec::new()));
+    if let Value::Array(ref mut array) = *entry {
+        array.extend(as_array(value));
+    }
+}
+
+/// Expand `value` to an IRI with the terms, vocabulary and base of `active`.
+/// `None` means that the value does not map to an IRI and is ignored.
+fn expand_iri(active: &Context,
+              value: &str,
+              document_relative: bool,
+              vocab: bool)
+              -> Option<String> {
+    if is_keyword(value) {
+        return Some(String::from(value));
+    }
+    if looks_like_keyword(value) {
+        return None;
+    }
+    if vocab {
+        if let Some(term) = active.terms.get(value) {
+            return term.iri.clone();
+        }
+    }
+    if let Some(pos) = value.find(':') {
+        let (prefix, suffix) = (&value[..pos], &value[pos + 1..]);
+        if prefix == "_" || suffix.starts_with("//") {
+            return Some(String::from(value));
+        }
+        if let Some(&Term { iri: Some(ref iri), prefix: true, .. }) = active.terms.get(prefix) {
+            return Some(format!("{}{}", iri, suffix));
+        }
+        if iri::is_absolute(value) {
+            return Some(String::from(value));
+        }
+    }
+    if vocab {
+        if let Some(ref v) = active.vocab {
+            return Some(format!("{}{}", v, value));
+        }
+    }
+    if document_relative {
+        if let Some(ref base) = active.base {
+            return Some(iri::join(base, value));
+        }
+    }
+    Some(String::from(value))
+}
+
+/// Expands JSON-LD documents. Remote contexts are loaded with `loader`.
+struct Expander<'l> {
+    base: String,
+    loader: Option<&'l DocumentLoader<'l>>,
+}
+
+impl<'l> Expander<'l> {
+    fn process_context(&self, active: &Context, local: &Value, remote: usize) -> Result<Context> {
+        let mut result = active.clone();
+        for context in as_array(local.clone()) {
+            match context {
+                Value::Null => {
+                    result = Context {
+                        base: Some(self.base.clone()),
+                        ..Context::default()
+                    };
+                }
+                Value::String(ref reference) => {
+                    if remote >= MAX_REMOTE_CONTEXTS {
+                        return Err(invalid("too many remote contexts"));
+                    }
+                    let iri = match result.base {
+                        Some(ref base) => iri::join(base, reference),
+                        None => reference.clone(),
+                    };
+                    let loader = self.loader
+                        .ok_or_else(|| {
+                            Error::String(format!("JSON-LD error: cannot load remote context \
+                                                   <{}> without a document loader",
+                                                  iri))
+                        })?;
+                    let document: Value = serde_json::from_str(&loader(&iri)?)
+                        .map_err(|e| Error::String(format!("JSON-LD error: remote context <{}>: \
+                                                            {}",
+                                                           iri,
+                                                           e)))?;
+                    let context = match document {
+                        Value::Object(mut map) => map.remove("@context"),
+                        _ => None,
+                    };
+                    let context = context.ok_or_else(|| invalid("invalid remote context"))?;
+                    result = self.process_context(&result, &context, remote + 1)?;
+                }
+                Value::Object(ref map) => {
+                    if let Some(base) = map.get("@base") {
+                        result.base = match *base {
+                            Value::Null => None,
+                            Value::String(ref base) => {
+                                Some(match result.base {
+                                    Some(ref b) => iri::join(b, base),
+                                    None => base.clone(),
+                                })
+                            }
+                            _ => return Err(invalid("invalid base IRI")),
+                        };
+                    }
+                    if let Some(vocab) = map.get("@vocab") {
+                        result.vocab = match *vocab {
+                            Value::Null => None,
+                            Value::String(ref vocab) => expand_iri(&result, vocab, true, true),
+                            _ => return Err(invalid("invalid vocab mapping")),
+                        };
+                    }
+                    if let Some(language) = map.get("@language") {
+                        result.language = match *language {
+                            Value::Null => None,
+                            Value::String(ref language) => Some(language.clone()),
+                            _ => return Err(invalid("invalid default language")),
+                        };
+                    }
+                    let mut defined = HashMap::new();
+                    for term in map.keys() {
+                        match term.as_str() {
+                            "@base" | "@vocab" | "@language" | "@version" | "@propagate" |
+                            "@protected" | "@import" | "@direction" => {}
+                            _ => self.create_term_definition(&mut result, map, term, &mut defined)?,
+                        }
+                    }
+                }
+                _ => return Err(invalid("invalid local context")),
+            }
+        }
+        Ok(result)
+    }
+    /// Define the terms of `local` that `value` depends on.
+    fn define_dependencies(&self,
+                           active: &mut Context,
+                           local: &Map<String, Value>,
+                           value: &str,
+                           defined: &mut HashMap<String, bool>)
+                           -> Result<()> {
+        if local.contains_key(value) {
+            self.create_term_definition(active, local, value, defined)?;
+        }
+        if let Some(pos) = value.find(':') {
+            if local.contains_key(&value[..pos]) {
+                self.create_term_definition(active, local, &value[..pos], defined)?;
+            }
+        }
+        Ok(())
+    }
+    fn expand_dependent_iri(&self,
+                            active: &mut Context,
+                            local: &Map<String, Value>,
+                            value: &str,
+                            defined: &mut HashMap<String, bool>)
+                            -> Result<Option<String>> {
+        self.define_dependencies(active, local, value, defined)?;
+        Ok(expand_iri(active, value, false, true))
+    }
+    fn create_term_definition(&self,
+                              active: &mut Context,
+                              local: &Map<String, Value>,
+                              term: &str,
+                              defined: &mut HashMap<String, bool>)
+                              -> Result<()> {
+        match defined.get(term) {
+            Some(&true) => return Ok(()),
+            Some(&false) => {
+                return Err(Error::String(format!("JSON-LD error: cyclic IRI mapping for '{}'",
+                                                 term)))
+            }
+            None => {}
+        }
+        defined.insert(String::from(term), false);
+        if is_keyword(term) {
+            return Err(Error::String(format!("JSON-LD error: keyword redefinition of '{}'",
+                                             term)));
+        }
+        active.terms.remove(term);
+        let mut definition = Term::default();
+        let mut simple = false;
+        let value = match local[term] {
+            Value::Null => {
+                active.terms.insert(String::from(term), definition);
+                defined.insert(String::from(term), true);
+                return Ok(());
+            }
+            Value::String(ref id) => {
+                simple = true;
+                let mut map = Map::new();
+                map.insert(String::from("@id"), Value::String(id.clone()));
+                map
+            }
+            Value::Object(ref map) => map.clone(),
+            _ => return Err(invalid("invalid term definition")),
+        };
+        if let Some(t) = value.get("@type") {
+            let t = t.as_str().ok_or_else(|| invalid("invalid type mapping"))?;
+            let t = self.expand_dependent_iri(active, local, t, defined)?
+                .ok_or_else(|| invalid("invalid type mapping"))?;
+            if !["@id", "@vocab", "@json", "@none"].contains(&t.as_str()) &&
+               !iri::is_absolute(&t) {
+                return Err(invalid("invalid type mapping"));
+            }
+            definition.type_mapping = Some(t);
+        }
+        if let Some(reverse) = value.get("@reverse") {
+            let reverse = reverse.as_str().ok_or_else(|| invalid("invalid IRI mapping"))?;
+            definition.iri = self.expand_dependent_iri(active, local, reverse, defined)?;
+            definition.reverse = true;
+        } else if let Some(id) = value.get("@id") {
+            match *id {
+                Value::Null => {}
+                Value::String(ref id) => {
+                    definition.iri = self.expand_dependent_iri(active, local, id, defined)?;
+                    if definition.iri.is_none() {
+                        return Err(invalid("invalid IRI mapping"));
+                    }
+                    let iri = definition.iri.clone().unwrap();
+                    definition.prefix = !term.contains(':') && ends_with_gen_delim(&iri) &&
+                                        (simple || !value.contains_key("@prefix"));
+                }
+                _ => return Err(invalid("invalid IRI mapping")),
+            }
+        } else if let Some(pos) = term.find(':') {
+            self.define_dependencies(active, local, &term[..pos], defined)?;
+            definition.iri = expand_iri(active, term, false, true);
+        } else if let Some(ref vocab) = active.vocab {
+            definition.iri = Some(format!("{}{}", vocab, term));
+        } else {
+            return Err(Error::String(format!("JSON-LD error: invalid IRI mapping for '{}'",
+                                             term)));
+        }
+        if let Some(container) = value.get("@container") {
+            for c in as_array(container.clone()) {
+                match c {
+                    Value::String(c) => definition.container.push(c),
+                    _ => return Err(invalid("invalid container mapping")),
+                }
+            }
+        }
+        if let Some(language) = value.get("@language") {
+            definition.language = Some(match *language {
+                Value::Null => None,
+                Value::String(ref language) => Some(language.clone()),
+                _ => return Err(invalid("invalid language mapping")),
+            });
+        }
+        if let Some(&Value::Bool(prefix)) = value.get("@prefix") {
+            definition.prefix = prefix;
+        }
+        if let Some(context) = value.get("@context") {
+            definition.context = Some(context.clone());
+        }
+        active.terms.insert(String::from(term), definition);
+        defined.insert(String::from(term), true);
+        Ok(())
+    }
+    /// Expand `element`, which is the value of `property`.
+    fn expand(&self, active: &Context, property: Option<&str>, element: &Value) -> Result<Value> {
+        match *element {
+            Value::Null => Ok(Value::Null),
+            Value::Array(ref array) => {
+                let list = property.and_then(|p| active.terms.get(p))
+                    .is_some_and(|t| t.has_container("@list"));
+                let mut result = Vec::new();
+                for item in array {
+                    let expanded = self.expand(active, property, item)?;
+                    match expanded {
+                        Value::Null => {}
+                        Value::Array(items) => {
+                            if list {
+                                let mut map = Map::new();
+                                map.insert(String::from("@list"), Value::Array(items));
+                                result.push(Value::Object(map));
+                            } else {
+                                result.extend(items);
+                            }
+                        }
+                        expanded => result.push(expanded),
+                    }
+                }
+                Ok(Value::Array(result))
+            }
+            Value::Object(ref map) => self.expand_object(active, property, map),
+            ref scalar => {
+                match property {
+                    None | Some("@graph") => Ok(Value::Null),
+                    Some(property) => Ok(expand_value(active, property, scalar)),
+                }
+            }
+        }
+    }
+    fn expand_object(&self,
+                     active: &Context,
+                     property: Option<&str>,
+                     element: &Map<String, Value>)
+                     -> Result<Value> {
+        let mut scoped = None;
+        if let Some(&Term { context: Some(ref context), .. }) =
+            property.and_then(|p| active.terms.get(p)) {
+            scoped = Some(self.process_context(active, context, 0)?);
+        }
+        if let Some(context) = element.get("@context") {
+            let context = self.process_context(scoped.as_ref().unwrap_or(active), context, 0)?;
+            scoped = Some(context);
+        }
+        let active = scoped.as_ref().unwrap_or(active);
+        let mut result = Map::new();
+        for (key, value) in element {
+            if key == "@context" {
+                continue;
+            }
+            let expanded_property = match expand_iri(active, key, false, true) {
+                Some(ref p) if p.contains(':') || is_keyword(p) => p.clone(),
+                _ => continue,
+            };
+            if is_keyword(&expanded_property) {
+                self.expand_keyword(active, property, &expanded_property, value, &mut result)?;
+                continue;
+            }
+            let term = active.terms.get(key.as_str()).cloned().unwrap_or_default();
+            let expanded = if term.has_container("@language") && value.is_object() {
+                expand_language_map(value)?
+            } else if (term.has_container("@index") || term.has_container("@id") ||
+                       term.has_container("@type")) && value.is_object() {
+                self.expand_index_map(active, key, &term, value)?
+            } else {
+                self.expand(active, Some(key), value)?
+            };
+            if expanded.is_null() {
+                continue;
+            }
+            let expanded = if term.has_container("@list") && !is_list(&expanded) {
+                let mut map = Map::new();
+                map.insert(String::from("@list"), Value::Array(as_array(expanded)));
+                Value::Object(map)
+            } else {
+                expanded
+            };
+            if term.reverse {
+                let reverse = result.entry(String::from("@reverse"))
+                    .or_insert_with(|| Value::Object(Map::new()));
+                for item in as_array(expanded) {
+                    if is_list(&item) || item.get("@value").is_some() {
+                        return Err(invalid("invalid reverse property value"));
+                    }
+                    if let Value::Object(ref mut reverse) = *reverse {
+                        append(reverse, &expanded_property, item);
+                    }
+                }
+            } else {
+                append(&mut result, &expanded_property, expanded);
+            }
+        }
+        if result.contains_key("@value") {
+            let keys = ["@value", "@language", "@type", "@index"];
+            if result.keys().any(|k| !keys.contains(&k.as_str())) {
+                return Err(invalid("invalid value object"));
+            }
+            if result["@value"].is_null() {
+                return Ok(Value::Null);
+            }
+            if result.contains_key("@language") && !result["@value"].is_string() {
+                return Err(invalid("invalid language-tagged value"));
+            }
+        } else if result.contains_key("@set") {
+            return Ok(result.remove("@set").unwrap());
+        } else if result.contains_key("@list") {
+            if result.keys().any(|k| k != "@list" && k != "@index") {
+                return Err(invalid("invalid set or list object"));
+            }
+        } else if result.len() == 1 && result.contains_key("@language") {
+            return Ok(Value::Null);
+        }
+        if let None | Some("@graph") = property {
+            // free floating values and lists are dropped
+            if result.contains_key("@value") || result.contains_key("@list") {
+                return Ok(Value::Null);
+            }
+        }
+        Ok(Value::Object(result))
+    }
+    fn expand_keyword(&self,
+                      active: &Context,
+                      property: Option<&str>,
+                      keyword: &str,
+                      value: &Value,
+                      result: &mut Map<String, Value>)
+                      -> Result<()> {
+        let expanded = match keyword {
+            "@id" => {
+                let id = value.as_str().ok_or_else(|| invalid("invalid @id value"))?;
+                match expand_iri(active, id, true, false) {
+                    Some(id) => Value::String(id),
+                    None => return Ok(()),
+                }
+            }
+            "@type" => {
+                let mut types = Vec::new();
+                for t in as_array(value.clone()) {
+                    let t = t.as_str().ok_or_else(|| invalid("invalid type value"))?;
+                    if let Some(t) = expand_iri(active, t, true, true) {
+                        types.push(Value::String(t));
+                    }
+                }
+                if value.is_array() {
+                    Value::Array(types)
+                } else {
+                    types.pop().unwrap_or(Value::Null)
+                }
+            }
+            "@value" => {
+                if value.is_array() || value.is_object() {
+                    return Err(invalid("invalid value object value"));
+                }
+                value.clone()
+            }
+            "@language" => {
+                let language = value.as_str()
+                    .ok_or_else(|| invalid("invalid language-tagged string"))?;
+                Value::String(String::from(language))
+            }
+            "@index" => {
+                let index = value.as_str().ok_or_else(|| invalid("invalid @index value"))?;
+                Value::String(String::from(index))
+            }
+            "@graph" => Value::Array(as_array(self.expand(active, Some("@graph"), value)?)),
+            "@list" => {
+                if let None | Some("@graph") = property {
+                    return Ok(());
+                }
+                Value::Array(as_array(self.expand(active, property, value)?))
+            }
+            "@set" => self.expand(active, property, value)?,
+            "@reverse" => {
+                if !value.is_object() {
+                    return Err(invalid("invalid @reverse value"));
+                }
+                let expanded = self.expand(active, Some("@reverse"), value)?;
+                if let Value::Object(mut map) = expanded {
+                    // reverse properties of a reverse map are forward properties
+                    if let Some(Value::Object(forward)) = map.remove("@reverse") {
+                        for (p, v) in forward {
+                            append(result, &p, v);
+                        }
+                    }
+                    if !map.is_empty() {
+                        let reverse = result.entry(String::from("@reverse"))
+                            .or_insert_with(|| Value::Object(Map::new()));
+                        if let Value::Object(ref mut reverse) = *reverse {
+                            for (p, v) in map {
+                                append(reverse, &p, v);
+                            }
+                        }
+                    }
+                }
+                return Ok(());
+            }
+            _ => return Ok(()),
+        };
+        result.insert(String::from(keyword), expanded);
+        Ok(())
+    }
+    /// Expand the values of an index map. With an `@id` or `@type`
+    /// container, the keys are the ids or types of the values.
+    fn expand_index_map(&self,
+                        active: &Context,
+                        key: &str,
+                        term: &Term,
+                        value: &Value)
+                        -> Result<Value> {
+        let mut result = Vec::new();
+        if let Value::Object(ref map) = *value {
+            for (index, v) in map {
+                for item in as_array(self.expand(active, Some(key), v)?) {
+                    let mut item = item;
+                    if let Value::Object(ref mut node) = item {
+                        if term.has_container("@id") && !node.contains_key("@id") {
+                            if let Some(id) = expand_iri(active, index, true, false) {
+                                node.insert(String::from("@id"), Value::String(id));
+                            }
+                        } else if term.has_container("@type") {
+                            if let Some(t) = expand_iri(active, index, true, true) {
+                                let mut types = vec![Value::String(t)];
+                                if let Some(old) = node.remove("@type") {
+                                    types.extend(as_array(old));
+                                }
+                                node.insert(String::from("@type"), Value::Array(types));
+                            }
+                        }
+                    }
+                    result.push(item);
+                }
+            }
+        }
+        Ok(Value::Array(result))
+    }
+}
+
+fn is_list(value: &Value) -> bool {
+    value.get("@list").is_some()
+}
+
+fn expand_language_map(value: &Value) -> Result<Value> {
+    let mut result = Vec::new();
+    if let Value::Object(ref map) = *value {
+        for (language, values) in map {
+            for item in as_array(values.clone()) {
+                let mut object = Map::new();
+                match item {
+                    Value::String(_) => object.insert(String::from("@value"), item),
+                    _ => return Err(invalid("invalid language map value")),
+                };
+                if language != "@none" {
+                    object.insert(String::from("@language"), Value::String(language.clone()));
+                }
+                result.push(Value::Object(object));
+            }
+        }
+    }
+    Ok(Value::Array(result))
+}
+
+/// Expand a scalar that is the value of `property`.
+fn expand_value(active: &Context, property: &str, value: &Value) -> Value {
+    let term = active.terms.get(property);
+    let type_mapping = term.and_then(|t| t.type_mapping.as_ref()).map(|t| t.as_str());
+    let mut result = Map::new();
+    if let Value::String(ref s) = *value {
+        if let Some(vocab @ "@id") | Some(vocab @ "@vocab") = type_mapping {
+            let id = expand_iri(active, s, true, vocab == "@vocab");
+            result.insert(String::from("@id"), id.map_or(Value::Null, Value::String));
+            return Value::Object(result);
+        }
+    }
+    result.insert(String::from("@value"), value.clone());
+    match type_mapping {
+        Some("@id") | Some("@vocab") | Some("@none") | Some("@json") => {}
+        Some(datatype) => {
+            result.insert(String::from("@type"), Value::String(String::from(datatype)));
+        }
+        None => {
+            if value.is_string() {
+                let language = match term.and_then(|t| t.language.as_ref()) {
+                    Some(language) => language.as_ref(),
+                    None => active.language.as_ref(),
+                };
+                if let Some(language) = language {
+                    result.insert(String::from("@language"), Value::String(language.clone()));
+                }
+            }
+        }
+    }
+    Value::Object(result)
+}
+
+/// The canonical lexical form of an `xsd:double`, e.g. `1.5E1`.
+fn canonical_double(value: f64) -> String {
+    let formatted = format!("{:E}", value);
+    match formatted.find('E') {
+        Some(pos) if !formatted[..pos].contains('.') => {
+            format!("{}.0{}", &formatted[..pos], &formatted[pos..])
+        }
+        _ => formatted,
+    }
+}
+
+/// A JSON-LD parser that writes the triples of a document to a `GraphWriter`.
+///
+/// The document is expanded and each node object at the top of the
+/// expanded document is one step of the iterator. Only the default graph is
+/// written; use `JSONLDDatasetParser` to read the named graphs too.
+pub struct JSONLDParser<'a, W: 'a>
+    where W: graph::GraphWriter<'a>
+{
+    document: Option<Value>,
+    base: String,
+    loader: Option<DocumentLoader<'a>>,
+    nodes: vec::IntoIter<Value>,
+    writer: &'a mut W,
+    blank_nodes: HashMap<String, W::BlankNode>,
+    rdf_type: Option<W::IRI>,
+    rdf_first: Option<W::IRI>,
+    rdf_rest: Option<W::IRI>,
+    rdf_nil: Option<W::IRI>,
+    /// the name of the graph of the node objects that are being written
+    graph_name: Option<WriterBlankNodeOrIRI<'a, W>>,
+    /// the quads of the last node object at the top of the document
+    quads: Vec<Quad<'a, W>>,
+    done: bool,
+}
+
+/// A JSON-LD parser that writes the quads of a document to a
+/// `DatasetWriter`.
+///
+/// This is `JSONLDParser` for documents with named graphs: the node objects
+/// in the `@graph` of a node are written to the graph that is named by the
+/// `@id` of that node. Blank node labels are shared by all graphs.
+pub struct JSONLDDatasetParser<'a, W: 'a>
+    where W: graph::DatasetWriter<'a>
+{
+    parser: JSONLDParser<'a, W>,
+}
+
+/// A triple with the name of its graph.
+struct Quad<'a, W: 'a>
+    where W: graph::GraphWriter<'a>
+{
+    graph_name: Option<WriterBlankNodeOrIRI<'a, W>>,
+    subject: WriterBlankNodeOrIRI<'a, W>,
+    predicate: W::IRI,
+    object: WriterResource<'a, W>,
+}
+
+impl<'a, W: 'a> JSONLDParser<'a, W>
+    where W: graph::GraphWriter<'a>
+{
+    pub fn new(src: &'a str, base: &str, writer: &'a mut W) -> Result<JSONLDParser<'a, W>> {
+        if !iri::is_absolute(base) {
+            return Err(Error::Custom("base url is not absolute"));
+        }
+        let document = serde_json::from_str(src).map_err(|e| json_error(src, &e))?;
+        Ok(JSONLDParser {
+            document: Some(document),
+            base: String::from(base),
+            loader: None,
+            nodes: Vec::new().into_iter(),
+            writer: writer,
+            blank_nodes: HashMap::new(),
+            rdf_type: None,
+            rdf_first: None,
+            rdf_rest: None,
+            rdf_nil: None,
+            graph_name: None,
+            quads: Vec::new(),
+            done: false,
+        })
+    }
+    /// Load remote contexts with `loader`.
+    ///
+    /// The loader gets the IRI of a context and returns the JSON text of the
+    /// document. Without a loader, a document with a remote context cannot be
+    /// read.
+    pub fn set_document_loader<F>(&mut self, loader: F)
+        where F: Fn(&str) -> Result<String> + 'a
+    {
+        self.loader = Some(Box::new(loader));
+    }
+    fn expand_document(&mut self, document: Value) -> Result<()> {
+        let expander = Expander {
+            base: self.base.clone(),
+            loader: self.loader.as_ref(),
+        };
+        let context = Context {
+            base: Some(self.base.clone()),
+            ..Context::default()
+        };
+        let mut expanded = expander.expand(&context, None, &document)?;
+        // a top level object with only @graph is not a node
+        if let Value::Object(ref mut map) = expanded {
+            if map.len() == 1 && map.contains_key("@graph") {
+                let graph = map.remove("@graph").unwrap();
+                self.nodes = as_array(graph).into_iter();
+                return Ok(());
+            }
+        }
+        self.nodes = as_array(expanded).into_iter();
+        Ok(())
+    }
+    /// Collect a triple in the current graph.
+    fn add(&mut self,
+           subject: &WriterBlankNodeOrIRI<'a, W>,
+           predicate: &W::IRI,
+           object: WriterResource<'a, W>) {
+        self.quads.push(Quad {
+            graph_name: self.graph_name.as_ref().map(copy),
+            subject: copy(subject),
+            predicate: predicate.clone(),
+            object: object,
+        });
+    }
+    /// return Ok(true) when done
+    fn parse_node(&mut self) -> Result<bool> {
+        if let Some(document) = self.document.take() {
+            self.expand_document(document)?;
+        }
+        match self.nodes.next() {
+            Some(Value::Object(node)) => {
+                self.write_node(&node)?;
+                Ok(false)
+            }
+            Some(_) => Ok(false),
+            None => Ok(true),
+        }
+    }
+    fn get_blank(&mut self, label: &str) -> W::BlankNode {
+        if let Some(n) = self.blank_nodes.get(label) {
+            return n.clone();
+        }
+        let n = self.writer.create_blank_node();
+        self.blank_nodes.insert(String::from(label), n.clone());
+        n
+    }
+    /// The blank node or IRI for an expanded `@id` or `@type`, or `None` if
+    /// it is a relative IRI.
+    fn make_node(&mut self, id: &str) -> Option<WriterBlankNodeOrIRI<'a, W>> {
+        if id.starts_with("_:") {
+            Some(WriterBlankNodeOrIRI::BlankNode(self.get_blank(id), PhantomData))
+        } else if iri::is_absolute(id) {
+            Some(WriterBlankNodeOrIRI::IRI(self.writer.create_iri(&id)))
+        } else {
+            None
+        }
+    }
+    /// Write the triples of a node object and return its subject.
+    fn write_node(&mut self,
+                  node: &Map<String, Value>)
+                  -> Result<Option<WriterBlankNodeOrIRI<'a, W>>> {
+        let subject = match node.get("@id").and_then(|id| id.as_str()) {
+            Some(id) => self.make_node(id),
+            None => {
+                Some(WriterBlankNodeOrIRI::BlankNode(self.writer.create_blank_node(),
+                                                     PhantomData))
+            }
+        };
+        let subject = match subject {
+            Some(subject) => subject,
+            None => return Ok(None),
+        };
+        if let Some(graph) = node.get("@graph") {
+            let outer = mem::replace(&mut self.graph_name, Some(copy(&subject)));
+            for item in graph.as_array().into_iter().flat_map(|a| a.iter()) {
+                if let Value::Object(ref item) = *item {
+                    self.write_node(item)?;
+                }
+            }
+            self.graph_name = outer;
+        }
+        for (property, values) in node {
+            match property.as_str() {
+                "@type" => {
+                    let rdf_type = get_cached_iri(&mut self.rdf_type, self.writer, RDF_TYPE);
+                    for t in as_array(values.clone()) {
+                        if let Some(t) = t.as_str().and_then(|t| self.make_node(t)) {
+                            self.add(&subject, &rdf_type, to_object(t));
+                        }
+                    }
+                }
+                "@reverse" => {
+                    for (property, values) in values.as_object().into_iter().flatten() {
+                        let predicate = match self.make_predicate(property) {
+                            Some(predicate) => predicate,
+                            None => continue,
+                        };
+                        for value in as_array(values.clone()) {
+                            if let Value::Object(ref value) = value {
+                                if let Some(object) = self.write_node(value)? {
+                                    self.add(&object, &predicate, to_object(copy(&subject)));
+                                }
+                            }
+                        }
+                    }
+                }
+                p if p.starts_with('@') => {}
+                _ => {
+                    let predicate = match self.make_predicate(property) {
+                        Some(predicate) => predicate,
+                        None => continue,
+                    };
+                    for value in as_array(values.clone()) {
+                        if let Some(object) = self.make_object(&value)? {
+                            self.add(&subject, &predicate, object);
+                        }
+                    }
+                }
+            }
+        }
+        Ok(Some(subject))
+    }
+    fn make_predicate(&mut self, property: &str) -> Option<W::IRI> {
+        // blank node predicates are not RDF
+        if iri::is_absolute(property) && !property.starts_with("_:") {
+            Some(self.writer.create_iri(&property))
+        } else {
+            None
+        }
+    }
+    /// The object for an expanded value, list or node object.
+    fn make_object(&mut self, value: &Value) -> Result<Option<WriterResource<'a, W>>> {
+        let map = match *value {
+            Value::Object(ref map) => map,
+            _ => return Ok(None),
+        };
+        if let Some(v) = map.get("@value") {
+            return Ok(self.make_literal(v, map));
+        }
+        if let Some(list) = map.get("@list") {
+            let items = list.as_array().cloned().unwrap_or_default();
+            return Ok(Some(to_object(self.make_list(&items)?)));
+        }
+        Ok(self.write_node(map)?.map(to_object))
+    }
+    fn make_literal(&mut self,
+                    value: &Value,
+                    map: &Map<String, Value>)
+                    -> Option<WriterResource<'a, W>> {
+        let datatype = map.get("@type").and_then(|t| t.as_str());
+        if let Some(datatype) = datatype {
+            if !iri::is_absolute(datatype) || datatype.starts_with("_:") {
+                return None;
+            }
+        }
+        let (lexical, native_type) = match *value {
+            Value::String(ref s) => (s.clone(), XSD_STRING),
+            Value::Bool(b) => (b.to_string(), XSD_BOOLEAN),
+            Value::Number(ref n) => {
+                if n.is_f64() || datatype == Some(XSD_DOUBLE) {
+                    let lexical = canonical_double(n.as_f64().unwrap_or(0.));
+                    (lexical, XSD_DOUBLE)
+                } else {
+                    (n.to_string(), XSD_INTEGER)
+                }
+            }
+            _ => return None,
+        };
+        if let Some(language) = map.get("@language").and_then(|l| l.as_str()) {
+            let language = self.writer.create_language(language);
+            return Some(WriterResource::Literal(self.writer
+                .create_literal_language(&lexical, &language)));
+        }
+        let datatype = self.writer.create_datatype(datatype.unwrap_or(native_type));
+        Some(WriterResource::Literal(self.writer.create_literal_datatype(&lexical, &datatype)))
+    }
+    fn make_list(&mut self, items: &[Value]) -> Result<WriterBlankNodeOrIRI<'a, W>> {
+        let mut objects = Vec::new();
+        for item in items {
+            if let Some(object) = self.make_object(item)? {
+                objects.push(object);
+            }
+        }
+        let mut head =
+            WriterBlankNodeOrIRI::IRI(get_cached_iri(&mut self.rdf_nil, self.writer, RDF_NIL));
+        for object in objects.into_iter().rev() {
+            let this = WriterBlankNodeOrIRI::BlankNode(self.writer.create_blank_node(),
+                                                       PhantomData);
+            let rdf_first = get_cached_iri(&mut self.rdf_first, self.writer, RDF_FIRST);
+            let rdf_rest = get_cached_iri(&mut self.rdf_rest, self.writer, RDF_REST);
+            self.add(&this, &rdf_first, object);
+            self.add(&this, &rdf_rest, to_object(head));
+            head = this;
+        }
+        Ok(head)
+    }
+}
+
+impl<'a, W: 'a> Iterator for JSONLDParser<'a, W>
+    where W: graph::GraphWriter<'a>
+{
+    type Item = Result<()>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        match self.parse_node() {
+            Ok(true) => {
+                self.done = true;
+                None
+            }
+            Ok(false) => {
+                for quad in self.quads.drain(..) {
+                    if quad.graph_name.is_none() {
+                        self.writer.add(&quad.subject, &quad.predicate, &quad.object);
+                    }
+                }
+                Some(Ok(()))
+            }
+            Err(e) => {
+                self.done = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+impl<'a, W: 'a> JSONLDDatasetParser<'a, W>
+    where W: graph::DatasetWriter<'a>
+{
+    pub fn new(src: &'a str,
+               base: &str,
+               writer: &'a mut W)
+               -> Result<JSONLDDatasetParser<'a, W>> {
+        Ok(JSONLDDatasetParser { parser: JSONLDParser::new(src, base, writer)? })
+    }
+    /// Load remote contexts with `loader`. See
+    /// `JSONLDParser::set_document_loader`.
+    pub fn set_document_loader<F>(&mut self, loader: F)
+        where F: Fn(&str) -> Result<String> + 'a
+    {
+        self.parser.set_document_loader(loader);
+    }
+}
+
+impl<'a, W: 'a> Iterator for JSONLDDatasetParser<'a, W>
+    where W: graph::DatasetWriter<'a>
+{
+    type Item = Result<()>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let parser = &mut self.parser;
+        if parser.done {
+            return None;
+        }
+        match parser.parse_node() {
+            Ok(true) => {
+                parser.done = true;
+                None
+            }
+            Ok(false) => {
+                for quad in parser.quads.drain(..) {
+                    if let Err(e) = parser.writer.add_quad(quad.graph_name.as_ref(),
+                                                           &quad.subject,
+                                                           &quad.predicate,
+                                                           &quad.object) {
+                        parser.done = true;
+                        return Some(Err(e));
+                    }
+                }
+                Some(Ok(()))
+            }
+            Err(e) => {
+                parser.done = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+/// Create a syntax error for invalid JSON.
+fn json_error(src: &str, error: &serde_json::Error) -> Error {
+    let line = error.line();
+    let column = error.column();
+    let line_offset: usize =
+        src.split('\n').take(line.saturating_sub(1)).map(|l| l.len() + 1).sum();
+    let text = src[line_offset..].lines().next().unwrap_or("");
+    let offset = line_offset +
+                 text.char_indices().nth(column.saturating_sub(1)).map_or(text.len(), |(i, _)| i);
+    Error::Syntax(SyntaxError {
+        line: line,
+        column: column,
+        offset: offset,
+        statement: StatementKind::Value,
+        excerpt: src[offset..].lines().next().unwrap_or("").chars().take(40).collect(),
+        message: error.to_string(),
+    })
+}
+
+fn get_cached_iri<'a, W: 'a>(cache: &mut Option<W::IRI>, writer: &mut W, iri: &str) -> W::IRI
+    where W: graph::GraphWriter<'a>
+{
+    if cache.is_none() {
+        *cache = Some(writer.create_iri(&iri));
+    }
+    cache.clone().unwrap()
+}
+
+fn copy<'a, W>(node: &WriterBlankNodeOrIRI<'a, W>) -> WriterBlankNodeOrIRI<'a, W>
+    where W: graph::GraphWriter<'a>
+{
+    match *node {
+        WriterBlankNodeOrIRI::BlankNode(ref n, _) => {
+            WriterBlankNodeOrIRI::BlankNode(n.clone(), PhantomData)
+        }
+        WriterBlankNodeOrIRI::IRI(ref iri) => WriterBlankNodeOrIRI::IRI(iri.clone()),
+        WriterBlankNodeOrIRI::QuotedTriple(ref t) => WriterBlankNodeOrIRI::QuotedTriple(t.clone()),
+    }
+}
+
+fn to_object<'a, W>(node: WriterBlankNodeOrIRI<'a, W>) -> WriterResource<'a, W>
+    where W: graph::GraphWriter<'a>
+{
+    match node {
+        WriterBlankNodeOrIRI::BlankNode(n, p) => WriterResource::BlankNode(n, p),
+        WriterBlankNodeOrIRI::IRI(iri) => WriterResource::IRI(iri),
+        WriterBlankNodeOrIRI::QuotedTriple(t) => WriterResource::QuotedTriple(t),
+    }
+}
+
+#[cfg(test)]
+fn jsonld_to_ntriples(s: &str, context: Option<&'static str>) -> Result<String> {
+    use graph::{Graph, GraphWriter};
+    use graphs::tel;
+    use io::write_ntriples;
+    let mut writer = tel::GraphCreator::with_capacity(0);
+    {
+        let mut parser = JSONLDParser::new(s, "http://a.example/doc", &mut writer)?;
+        if let Some(context) = context {
+            parser.set_document_loader(move |iri| if iri == "http://a.example/context" {
+                Ok(String::from(context))
+            } else {
+                Err(Error::Custom("not found"))
+            });
+        }
+        for step in parser {
+            step?;
+        }
+    }
+    let graph: tel::Graph64 = writer.collect();
+    let mut out = Vec::new();
+    write_ntriples(graph.iter(), &graph, &mut out)?;
+    Ok(String::from_utf8(out)?)
+}
+
+#[test]
+fn test_jsonld() {
+    let jsonld = r##"{
+    "@context": {
+        "@vocab": "http://a.example/v#",
+        "ex": "http://a.example/",
+        "name": {"@language": "en"},
+        "knows": {"@id": "ex:knows", "@type": "@id"},
+        "age": {"@type": "http://www.w3.org/2001/XMLSchema#integer"},
+        "items": {"@id": "ex:items", "@container": "@list"},
+        "label": {"@container": "@language"},
+        "parent": {"@reverse": "ex:child"}
+    },
+    "@id": "ex:s",
+    "@type": "Thing",
+    "name": "Sam",
+    "knows": "t",
+    "age": "7",
+    "items": [1, true, 1.5],
+    "label": {"nl": "ding"},
+    "parent": {"@id": "ex:p"},
+    "@reverse": {"ex:owns": {"@id": "ex:o"}},
+    "ex:q": {"ex:r": {"@value": "x", "@type": "ex:T"}},
+    "ignored": null
+}"##;
+    assert_eq!(jsonld_to_ntriples(jsonld, None).unwrap(),
+               r#"_:1 <http://www.w3.org/1999/02/22-rdf-syntax-ns#first> "1.5E0"^^<http://www.w3.org/2001/XMLSchema#double> .
+_:1 <http://www.w3.org/1999/02/22-rdf-syntax-ns#rest> <http://www.w3.org/1999/02/22-rdf-syntax-ns#nil> .
+_:2 <http://www.w3.org/1999/02/22-rdf-syntax-ns#first> "true"^^<http://www.w3.org/2001/XMLSchema#boolean> .
+_:2 <http://www.w3.org/1999/02/22-rdf-syntax-ns#rest> _:1 .
+_:3 <http://www.w3.org/1999/02/22-rdf-syntax-ns#first> "1"^^<http://www.w3.org/2001/XMLSchema#integer> .
+_:3 <http://www.w3.org/1999/02/22-rdf-syntax-ns#rest> _:2 .
+_:4 <http://a.example/r> "x"^^<http://a.example/T> .
+<http://a.example/o> <http://a.example/owns> <http://a.example/s> .
+<http://a.example/p> <http://a.example/child> <http://a.example/s> .
+<http://a.example/s> <http://a.example/items> _:3 .
+<http://a.example/s> <http://a.example/knows> <http://a.example/t> .
+<http://a.example/s> <http://a.example/q> _:4 .
+<http://a.example/s> <http://a.example/v#age> "7"^^<http://www.w3.org/2001/XMLSchema#integer> .
+<http://a.example/s> <http://a.example/v#label> "ding"@nl .
+<http://a.example/s> <http://a.example/v#name> "Sam"@en .
+<http://a.example/s> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <http://a.example/v#Thing> .
+"#);
+}
+
+#[test]
+fn test_jsonld_graph_and_remote_context() {
+    let jsonld = r#"{
+    "@context": "context",
+    "@graph": [
+        {"@id": "_:a", "p": {"@id": "_:a"}},
+        {"@id": "g", "@graph": {"@id": "s", "p": "in graph"}}
+    ]
+}"#;
+    let context = r#"{"@context": {"@base": "http://a.example/base/",
+                                   "p": {"@id": "http://a.example/p"}}}"#;
+    assert_eq!(jsonld_to_ntriples(jsonld, Some(context)).unwrap(),
+               "_:1 <http://a.example/p> _:1 .\n");
+    assert!(jsonld_to_ntriples(jsonld, None).is_err());
+}
+
+#[test]
+fn test_jsonld_dataset() {
+    use graph::{Dataset, DatasetWriter};
+    use graphs::tel;
+    use io::write_nquads;
+    let jsonld = r#"{
+    "@context": {"@base": "http://a.example/base/", "p": {"@id": "http://a.example/p"}},
+    "@graph": [
+        {"@id": "_:a", "p": {"@id": "_:a"}},
+        {"@id": "g", "p": "named", "@graph": {"@id": "s", "p": "in graph"}},
+        {"@id": "_:b", "@graph": {"@id": "_:a", "p": "in blank graph"}}
+    ]
+}"#;
+    let mut writer = tel::DatasetCreator::with_capacity(0);
+    for step in JSONLDDatasetParser::new(jsonld, "http://a.example/doc", &mut writer).unwrap() {
+        step.unwrap();
+    }
+    let dataset: tel::Dataset64 = writer.collect_dataset();
+    let mut out = Vec::new();
+    write_nquads(dataset.graphs(), &mut out).unwrap();
+    assert_eq!(String::from_utf8(out).unwrap(),
+               r#"_:1 <http://a.example/p> _:1 .
+<http://a.example/base/g> <http://a.example/p> "named" .
+_:1 <http://a.example/p> "in blank graph" _:2 .
+<http://a.example/base/s> <http://a.example/p> "in graph" <http://a.example/base/g> .
+"#);
+}
+
+#[test]
+fn test_jsonld_errors() {
+    let errors = ["{\"@context\": {\"a\": \"b:c\", \"b\": \"a:d\"}, \"a\": 1}",
+                  "{\"@context\": {\"@id\": \"http://a.example/\"}}",
+                  "{\"http://a.example/p\": {\"@value\": 1, \"@language\": \"en\"}}",
+                  "{\"@context\": 1}"];
+    for error in errors.iter() {
+        assert!(jsonld_to_ntriples(error, None).is_err(), "{}", error);
+    }
+    match jsonld_to_ntriples("{\n  \"a\": [1,\n  ]}", None) {
+        Err(Error::Syntax(e)) => {
+            assert_eq!((e.line, e.column, e.offset), (3, 3, 15));
+            assert_eq!(e.statement, StatementKind::Value);
+        }
+        _ => panic!("expected a syntax error"),
+    }
+}
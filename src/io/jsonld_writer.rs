@@ -0,0 +1,558 @@
+use constants;
+use error::{Error, Result};
+use graph::*;
+use namespaces::*;
+use std::collections::HashSet;
+use std::fmt::Display;
+use std::io::Write;
+use super::nesting::{object_count, NestingWriter};
+
+/// The form of a JSON-LD document.
+#[derive (Debug,Clone,Copy,PartialEq,Eq)]
+pub enum JSONLDForm {
+    /// Full IRIs and an array for each value, without a context.
+    Expanded,
+    /// A `@context` with the namespaces, prefixed names and single values
+    /// without an array.
+    Compacted,
+}
+
+struct JSONLDWriter<'a, 'g, W: 'a, G: 'g>
+    where W: Write,
+          G: Graph<'g>
+{
+    buffer: Vec<u8>,
+    writer: &'a mut W,
+    namespaces: &'a Namespaces,
+    form: JSONLDForm,
+    xsd_string: Option<<G::LiteralPtr as LiteralPtr<'g>>::DatatypePtr>,
+    rdf_type: Option<G::IRIPtr>,
+    /// the labels of the blank nodes that were written as node objects
+    written: HashSet<String>,
+    /// the depth of the top-level node objects
+    depth: usize,
+    /// no top-level node object was written yet
+    first: bool,
+    graph: &'g G,
+}
+
+/// Write out a graph as JSON-LD.
+///
+/// Each subject is a node object with its properties. A blank node that is
+/// the object of only one triple is embedded in that triple's node object;
+/// other blank nodes are referenced by `@id`. Literals with a language or a
+/// datatype other than `xsd:string` are written as value objects.
+///
+/// In compacted form, `namespaces` is written as the `@context` and is used
+/// to shorten IRIs. The empty prefix becomes `@vocab`.
+///
+/// JSON-LD has no syntax for quoted triples, so a graph with quoted triples
+/// gives an error.
+pub fn write_jsonld<'g, G: 'g, W>(namespaces: &Namespaces,
+                                  graph: &'g G,
+                                  form: JSONLDForm,
+                                  writer: &mut W)
+                                  -> Result<()>
+    where G: Graph<'g>,
+          <G as Graph<'g>>::BlankNodePtr: Display,
+          W: Write
+{
+    let mut writer = JSONLDWriter {
+        buffer: Vec::new(),
+        writer: writer,
+        namespaces: namespaces,
+        form: form,
+        xsd_string: graph.find_datatype(constants::XSD_STRING),
+        rdf_type: graph.find_iri(constants::RDF_TYPE),
+        written: HashSet::new(),
+        depth: 1,
+        first: true,
+        graph: graph,
+    };
+    match form {
+        JSONLDForm::Expanded => {
+            writer.writer.write_all(b"[")?;
+            writer.write_statements()?;
+            writer.writer.write_all(b"\n]\n")?;
+        }
+        JSONLDForm::Compacted => {
+            writer.writer.write_all(b"{\n\t\"@context\": {")?;
+            writer.write_context()?;
+            writer.writer.write_all(b"\n\t},\n\t\"@graph\": [")?;
+            writer.depth = 2;
+            writer.write_statements()?;
+            writer.writer.write_all(b"\n\t]\n}\n")?;
+        }
+    }
+    Ok(())
+}
+
+/// A namespace can be used as a prefix if it ends with a character that
+/// separates the parts of an IRI.
+fn is_prefix_namespace(namespace: &str) -> bool {
+    namespace.ends_with(|c| ":/?#[]@".contains(c))
+}
+
+/// Escape a string so that it can be written between double quotes in JSON.
+pub fn escape_string(value: &str, to: &mut Vec<u8>) {
+    for c in value.chars() {
+        match c {
+            '"' => to.extend_from_slice(b"\\\""),
+            '\\' => to.extend_from_slice(b"\\\\"),
+            '\n' => to.extend_from_slice(b"\\n"),
+            '\r' => to.extend_from_slice(b"\\r"),
+            '\t' => to.extend_from_slice(b"\\t"),
+            c if (c as u32) < 0x20 => write!(to, "\\u{:04x}", c as u32).unwrap(),
+            c => {
+                let mut bytes = [0; 4];
+                to.extend_from_slice(c.encode_utf8(&mut bytes).as_bytes());
+            }
+        }
+    }
+}
+
+impl<'a, 'g, W: 'a, G: 'g> JSONLDWriter<'a, 'g, W, G>
+    where W: Write,
+          G: Graph<'g>,
+          <G as Graph<'g>>::BlankNodePtr: Display
+{
+    fn new_line(&mut self, depth: usize) -> Result<()> {
+        self.writer.write_all(b"\n")?;
+        for _ in 0..depth {
+            self.writer.write_all(b"\t")?;
+        }
+        Ok(())
+    }
+    fn write_string(&mut self, value: &str) -> Result<()> {
+        self.buffer.clear();
+        self.buffer.push(b'"');
+        escape_string(value, &mut self.buffer);
+        self.buffer.push(b'"');
+        self.writer.write_all(&self.buffer)?;
+        Ok(())
+    }
+    /// Write the key of a member of an object on a new line.
+    fn write_key(&mut self, key: &str, first: &mut bool, depth: usize) -> Result<()> {
+        if !*first {
+            self.writer.write_all(b",")?;
+        }
+        *first = false;
+        self.new_line(depth)?;
+        self.write_string(key)?;
+        self.writer.write_all(b": ")?;
+        Ok(())
+    }
+    fn write_context(&mut self) -> Result<()> {
+        let namespaces = self.namespaces;
+        let mut first = true;
+        for ns in namespaces.iter() {
+            if ns.prefix().is_empty() {
+                self.write_key("@vocab", &mut first, 2)?;
+            } else {
+                let prefix = String::from_utf8_lossy(ns.prefix());
+                self.write_key(&prefix, &mut first, 2)?;
+            }
+            self.write_string(ns.namespace())?;
+        }
+        Ok(())
+    }
+    /// Write `iri` in the form of the document. Property names and types
+    /// (`vocab`) may use `@vocab`, ids may only use prefixes.
+    fn write_iri(&mut self, iri: &str, vocab: bool) -> Result<()> {
+        if self.form == JSONLDForm::Expanded {
+            return self.write_string(iri);
+        }
+        let namespaces = self.namespaces;
+        for ns in namespaces.iter() {
+            let namespace = ns.namespace().as_str();
+            if !iri.starts_with(namespace) || iri.len() == namespace.len() {
+                continue;
+            }
+            let local = &iri[namespace.len()..];
+            if ns.prefix().is_empty() {
+                if vocab && !local.contains(':') && !local.starts_with('@') {
+                    return self.write_string(local);
+                }
+            } else if is_prefix_namespace(namespace) && !local.starts_with("//") {
+                let prefixed = format!("{}:{}", String::from_utf8_lossy(ns.prefix()), local);
+                return self.write_string(&prefixed);
+            }
+        }
+        self.write_string(iri)
+    }
+    fn write_blank_node_id(&mut self, blank_node: &G::BlankNodePtr) -> Result<()> {
+        self.write_string(&format!("_:b{}", blank_node))
+    }
+    /// Write a node object with the properties of `subject`.
+    fn write_node(&mut self,
+                  subject: &BlankNodeOrIRI<'g, G::BlankNodePtr, G::IRIPtr, G::QuotedTriplePtr>,
+                  with_id: bool,
+                  depth: usize)
+                  -> Result<()> {
+        self.writer.write_all(b"{")?;
+        let mut first = true;
+        if let BlankNodeOrIRI::BlankNode(ref blank_node, _) = *subject {
+            self.written.insert(blank_node.to_string());
+        }
+        if with_id {
+            self.write_key("@id", &mut first, depth + 1)?;
+            match *subject {
+                BlankNodeOrIRI::BlankNode(ref blank_node, _) => {
+                    self.write_blank_node_id(blank_node)?
+                }
+                BlankNodeOrIRI::IRI(ref iri) => self.write_iri(iri.as_str(), false)?,
+                BlankNodeOrIRI::QuotedTriple(_) => return Err(Self::quoted_triple_error()),
+            }
+        }
+        let mut types = Vec::new();
+        let mut properties: Vec<(G::IRIPtr, Vec<_>)> = Vec::new();
+        for triple in self.graph.iter_s(subject) {
+            let predicate = triple.predicate();
+            let object = triple.object();
+            if Some(&predicate) == self.rdf_type.as_ref() && !object.is_literal() {
+                types.push(object);
+                continue;
+            }
+            if properties.last().map(|p| &p.0) != Some(&predicate) {
+                properties.push((predicate, Vec::new()));
+            }
+            properties.last_mut().unwrap().1.push(object);
+        }
+        if !types.is_empty() {
+            self.write_key("@type", &mut first, depth + 1)?;
+            let array = self.form == JSONLDForm::Expanded || types.len() > 1;
+            if array {
+                self.writer.write_all(b"[")?;
+            }
+            for (i, t) in types.iter().enumerate() {
+                if i > 0 {
+                    self.writer.write_all(b", ")?;
+                }
+                match *t {
+                    Resource::BlankNode(ref blank_node, _) => {
+                        self.write_blank_node_id(blank_node)?
+                    }
+                    Resource::IRI(ref iri) => self.write_iri(iri.as_str(), true)?,
+                    Resource::Literal(_) => {}
+                    Resource::QuotedTriple(_) => return Err(Self::quoted_triple_error()),
+                }
+            }
+            if array {
+                self.writer.write_all(b"]")?;
+            }
+        }
+        for (predicate, objects) in properties {
+            if !first {
+                self.writer.write_all(b",")?;
+            }
+            first = false;
+            self.new_line(depth + 1)?;
+            self.write_iri(predicate.as_str(), true)?;
+            self.writer.write_all(b": ")?;
+            if self.form == JSONLDForm::Compacted && objects.len() == 1 {
+                let object = objects.into_iter().next().unwrap();
+                self.write_object(object, depth + 1)?;
+                continue;
+            }
+            self.writer.write_all(b"[")?;
+            for (i, object) in objects.into_iter().enumerate() {
+                if i > 0 {
+                    self.writer.write_all(b",")?;
+                }
+                self.new_line(depth + 2)?;
+                self.write_object(object, depth + 2)?;
+            }
+            self.new_line(depth + 1)?;
+            self.writer.write_all(b"]")?;
+        }
+        self.new_line(depth)?;
+        self.writer.write_all(b"}")?;
+        Ok(())
+    }
+    fn write_object(&mut self,
+                    object: Resource<'g,
+                                     G::BlankNodePtr,
+                                     G::IRIPtr,
+                                     G::LiteralPtr,
+                                     G::QuotedTriplePtr>,
+                    depth: usize)
+                    -> Result<()> {
+        match object {
+            Resource::IRI(iri) => {
+                self.writer.write_all(b"{\"@id\": ")?;
+                self.write_iri(iri.as_str(), false)?;
+                self.writer.write_all(b"}")?;
+            }
+            Resource::BlankNode(blank_node, _) => {
+                if self.written.contains(&blank_node.to_string()) ||
+                   object_count(self.graph, &blank_node) > 1 {
+                    self.writer.write_all(b"{\"@id\": ")?;
+                    self.write_blank_node_id(&blank_node)?;
+                    self.writer.write_all(b"}")?;
+                } else {
+                    // the blank node is only used here, embed it
+                    self.write_node(&blank_node.to_blank_node_or_iri(), false, depth)?;
+                }
+            }
+            Resource::Literal(literal) => {
+                let datatype = Some(literal.datatype());
+                if literal.language().is_none() && datatype == self.xsd_string &&
+                   self.form == JSONLDForm::Compacted {
+                    return self.write_string(literal.as_str());
+                }
+                self.writer.write_all(b"{\"@value\": ")?;
+                self.write_string(literal.as_str())?;
+                if let Some(language) = literal.language() {
+                    self.writer.write_all(b", \"@language\": ")?;
+                    self.write_string(language)?;
+                } else if datatype != self.xsd_string {
+                    self.writer.write_all(b", \"@type\": ")?;
+                    self.write_iri(literal.datatype_str(), true)?;
+                }
+                self.writer.write_all(b"}")?;
+            }
+            Resource::QuotedTriple(_) => return Err(Self::quoted_triple_error()),
+        }
+        Ok(())
+    }
+}
+
+impl<'a, 'g, W: 'a, G: 'g> NestingWriter<'g, G> for JSONLDWriter<'a, 'g, W, G>
+    where W: Write,
+          G: Graph<'g>,
+          <G as Graph<'g>>::BlankNodePtr: Display
+{
+    fn graph(&self) -> &'g G {
+        self.graph
+    }
+    fn is_written(&self, blank_node: &G::BlankNodePtr) -> bool {
+        self.written.contains(&blank_node.to_string())
+    }
+    fn write_subject(&mut self,
+                     subject: &BlankNodeOrIRI<'g,
+                                              G::BlankNodePtr,
+                                              G::IRIPtr,
+                                              G::QuotedTriplePtr>)
+                     -> Result<()> {
+        if !self.first {
+            self.writer.write_all(b",")?;
+        }
+        self.first = false;
+        let depth = self.depth;
+        self.new_line(depth)?;
+        let with_id = match *subject {
+            BlankNodeOrIRI::BlankNode(ref blank_node, _) => {
+                object_count(self.graph, blank_node) > 0
+            }
+            _ => true,
+        };
+        self.write_node(subject, with_id, depth)
+    }
+    fn quoted_triple_error() -> Error {
+        Error::Custom("Quoted triples cannot be written as JSON-LD.")
+    }
+}
+
+#[cfg(test)]
+fn turtle_to_jsonld(turtle: &str, form: JSONLDForm) -> String {
+    use graphs::tel;
+    use io::TurtleParser;
+    let mut writer = tel::GraphCreator::with_capacity(0);
+    for step in TurtleParser::new(turtle, "http://a.example/", &mut writer).unwrap() {
+        step.unwrap();
+    }
+    let graph: tel::Graph64 = writer.collect();
+    let mut ns = Namespaces::new();
+    ns.set(b"ex", "http://a.example/");
+    ns.set(b"xsd", "http://www.w3.org/2001/XMLSchema#");
+    let mut out = Vec::new();
+    write_jsonld(&ns, &graph, form, &mut out).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+#[test]
+fn test_write_jsonld() {
+    let turtle = "@prefix ex: <http://a.example/> .
+ex:s a ex:C ;
+  ex:p \"a \\\"b\\\"\"@en , \"1\"^^<http://www.w3.org/2001/XMLSchema#integer> , \"plain\" ;
+  ex:q [ ex:r ex:o ; ex:t _:shared ] ;
+  ex:u _:shared .
+_:shared ex:p \"x\" .
+";
+    assert_eq!(turtle_to_jsonld(turtle, JSONLDForm::Expanded),
+               r#"[
+	{
+		"@id": "_:b2",
+		"http://a.example/p": [
+			{"@value": "x"}
+		]
+	},
+	{
+		"@id": "http://a.example/s",
+		"@type": ["http://a.example/C"],
+		"http://a.example/p": [
+			{"@value": "a \"b\"", "@language": "en"},
+			{"@value": "1", "@type": "http://www.w3.org/2001/XMLSchema#integer"},
+			{"@value": "plain"}
+		],
+		"http://a.example/q": [
+			{
+				"http://a.example/r": [
+					{"@id": "http://a.example/o"}
+				],
+				"http://a.example/t": [
+					{"@id": "_:b2"}
+				]
+			}
+		],
+		"http://a.example/u": [
+			{"@id": "_:b2"}
+		]
+	}
+]
+"#);
+    assert_eq!(turtle_to_jsonld(turtle, JSONLDForm::Compacted),
+               r#"{
+	"@context": {
+		"ex": "http://a.example/",
+		"xsd": "http://www.w3.org/2001/XMLSchema#"
+	},
+	"@graph": [
+		{
+			"@id": "_:b2",
+			"ex:p": "x"
+		},
+		{
+			"@id": "ex:s",
+			"@type": "ex:C",
+			"ex:p": [
+				{"@value": "a \"b\"", "@language": "en"},
+				{"@value": "1", "@type": "xsd:integer"},
+				"plain"
+			],
+			"ex:q": {
+				"ex:r": {"@id": "ex:o"},
+				"ex:t": {"@id": "_:b2"}
+			},
+			"ex:u": {"@id": "_:b2"}
+		}
+	]
+}
+"#);
+}
+
+/// Write `turtle` in both forms, read each back and compare the triples.
+#[cfg(test)]
+fn assert_round_trip(turtle: &str) {
+    use graphs::tel;
+    use io::JSONLDParser;
+    use test_support::{ntriples, turtle_graph};
+    let graph: tel::Graph64 = turtle_graph(turtle);
+    for form in [JSONLDForm::Expanded, JSONLDForm::Compacted].iter() {
+        let jsonld = turtle_to_jsonld(turtle, *form);
+        let mut writer = tel::GraphCreator::with_capacity(0);
+        for step in JSONLDParser::new(&jsonld, "http://a.example/", &mut writer).unwrap() {
+            step.unwrap();
+        }
+        let graph2: tel::Graph64 = writer.collect();
+        assert_eq!(ntriples(&graph2.sort_blank_nodes()),
+                   ntriples(&graph.sort_blank_nodes()),
+                   "{}",
+                   jsonld);
+    }
+}
+
+#[test]
+fn test_write_jsonld_cycle() {
+    let turtle = "@prefix ex: <http://a.example/> .
+_:c1 ex:next _:c2 .
+_:c2 ex:next _:c1 .
+_:self ex:p _:self .
+";
+    // the first blank node of a cycle has an id and the rest is embedded
+    assert_eq!(turtle_to_jsonld(turtle, JSONLDForm::Compacted),
+               r#"{
+	"@context": {
+		"ex": "http://a.example/",
+		"xsd": "http://www.w3.org/2001/XMLSchema#"
+	},
+	"@graph": [
+		{
+			"@id": "_:b1",
+			"ex:next": {
+				"ex:next": {"@id": "_:b1"}
+			}
+		},
+		{
+			"@id": "_:b3",
+			"ex:p": {"@id": "_:b3"}
+		}
+	]
+}
+"#);
+    assert_round_trip(turtle);
+}
+
+#[test]
+fn test_write_jsonld_list() {
+    use test_support::turtle_graph;
+    let turtle = "@prefix ex: <http://a.example/> .
+ex:s ex:l ( 1 \"a\"@en ex:o ) ; ex:e () .
+";
+    // a list is written as embedded rdf:first and rdf:rest nodes
+    let mut ns = Namespaces::new();
+    ns.set(b"ex", "http://a.example/");
+    ns.set(b"rdf", "http://www.w3.org/1999/02/22-rdf-syntax-ns#");
+    let graph: ::graphs::tel::Graph64 = turtle_graph(turtle);
+    let mut out = Vec::new();
+    write_jsonld(&ns, &graph, JSONLDForm::Compacted, &mut out).unwrap();
+    assert_eq!(String::from_utf8(out).unwrap(),
+               r#"{
+	"@context": {
+		"ex": "http://a.example/",
+		"rdf": "http://www.w3.org/1999/02/22-rdf-syntax-ns#"
+	},
+	"@graph": [
+		{
+			"@id": "ex:s",
+			"ex:e": {"@id": "rdf:nil"},
+			"ex:l": {
+				"rdf:first": {"@value": "1", "@type": "http://www.w3.org/2001/XMLSchema#integer"},
+				"rdf:rest": {
+					"rdf:first": {"@value": "a", "@language": "en"},
+					"rdf:rest": {
+						"rdf:first": {"@id": "ex:o"},
+						"rdf:rest": {"@id": "rdf:nil"}
+					}
+				}
+			}
+		}
+	]
+}
+"#);
+    assert_round_trip(turtle);
+}
+
+#[test]
+fn test_write_jsonld_literals() {
+    let turtle = "@prefix ex: <http://a.example/> .
+@prefix xsd: <http://www.w3.org/2001/XMLSchema#> .
+ex:s ex:p \"x\"^^ex:dt , \"y\"@en-GB , \"z\"^^xsd:string , true , 1.5 , \"\\n\\\"\"@fr .
+";
+    assert_eq!(turtle_to_jsonld(turtle, JSONLDForm::Expanded),
+               r#"[
+	{
+		"@id": "http://a.example/s",
+		"http://a.example/p": [
+			{"@value": "\n\"", "@language": "fr"},
+			{"@value": "y", "@language": "en-GB"},
+			{"@value": "1.5", "@type": "http://www.w3.org/2001/XMLSchema#decimal"},
+			{"@value": "true", "@type": "http://www.w3.org/2001/XMLSchema#boolean"},
+			{"@value": "x", "@type": "http://a.example/dt"},
+			{"@value": "z"}
+		]
+	}
+]
+"#);
+    assert_round_trip(turtle);
+}
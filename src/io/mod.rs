@@ -1,11 +1,42 @@
 //! Functions for reading and writing RDF files.
 mod turtle;
+mod hdt;
+mod jsonld_parser;
+mod jsonld_writer;
+mod nesting;
+mod ntriples_parser;
 mod ntriples_writer;
+mod rdfxml_parser;
+mod rdfxml_writer;
+mod sparql_results_writer;
 
 /// A parser for Turtle files.
 pub type TurtleParser<'a, B> = turtle::parser::TurtleParser<'a, B>;
-/// A parser for `NTriples` files.
-pub type NTriplesParser<'a, B> = turtle::parser::TurtleParser<'a, B>;
-pub use self::ntriples_writer::write_ntriples;
-pub use self::turtle::pretty_turtle_writer::write_pretty_turtle;
+/// A parser for Turtle files that reads from a `std::io::BufRead`.
+pub type TurtleReader<'a, R, B> = turtle::parser::TurtleReader<'a, R, B>;
+/// A parser for TriG files.
+pub type TriGParser<'a, B> = turtle::parser::TriGParser<'a, B>;
+/// A strict parser for `NTriples` files.
+pub type NTriplesParser<'a, B> = ntriples_parser::NTriplesParser<'a, B>;
+/// A strict parser for `NTriples` files that reads from a `std::io::BufRead`.
+pub type NTriplesReader<'a, R, B> = ntriples_parser::NTriplesReader<'a, R, B>;
+/// A strict parser for `NQuads` files.
+pub type NQuadsParser<'a, B> = ntriples_parser::NQuadsParser<'a, B>;
+/// A parser for RDF/XML files.
+pub type RDFXMLParser<'a, B> = rdfxml_parser::RDFXMLParser<'a, B>;
+/// A parser for HDT files.
+pub type HDTParser<'a, B> = hdt::HDTParser<'a, B>;
+/// A parser for JSON-LD files.
+pub type JSONLDParser<'a, B> = jsonld_parser::JSONLDParser<'a, B>;
+/// A JSON-LD parser that writes the default and the named graphs of a document.
+pub type JSONLDDatasetParser<'a, B> = jsonld_parser::JSONLDDatasetParser<'a, B>;
+pub use self::hdt::write_hdt;
+pub use self::jsonld_parser::DocumentLoader;
+pub use self::jsonld_writer::{write_jsonld, JSONLDForm};
+pub use self::ntriples_writer::{write_ntriples, write_nquads};
+pub use self::rdfxml_writer::write_rdfxml;
+pub use self::sparql_results_writer::{write_sparql_csv, write_sparql_json, write_sparql_json_boolean,
+                                      write_sparql_tsv, write_sparql_xml, write_sparql_xml_boolean,
+                                      ResultTerm};
+pub use self::turtle::pretty_turtle_writer::{write_pretty_turtle, write_pretty_trig};
 pub use self::turtle::turtle_writer::write_turtle;
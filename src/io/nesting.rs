@@ -0,0 +1,69 @@
+use error::{Error, Result};
+use graph::*;
+
+/// The number of triples with `blank_node` as object, up to 2.
+pub fn object_count<'g, G: 'g>(graph: &'g G, blank_node: &G::BlankNodePtr) -> usize
+    where G: Graph<'g>
+{
+    graph.iter_o(&blank_node.to_resource()).take(2).count()
+}
+
+/// A writer that writes each subject of a graph with its properties and
+/// nests a blank node that is the object of only one triple in that triple.
+///
+/// This is shared by the RDF/XML and the JSON-LD writer.
+pub trait NestingWriter<'g, G: 'g>
+    where G: Graph<'g>
+{
+    /// The graph that is written.
+    fn graph(&self) -> &'g G;
+    /// Was `blank_node` written, as a subject or nested in another subject?
+    fn is_written(&self, blank_node: &G::BlankNodePtr) -> bool;
+    /// Write `subject` and its properties at the top level of the document.
+    fn write_subject(&mut self,
+                     subject: &BlankNodeOrIRI<'g,
+                                              G::BlankNodePtr,
+                                              G::IRIPtr,
+                                              G::QuotedTriplePtr>)
+                     -> Result<()>;
+    /// The error for a graph with quoted triples.
+    fn quoted_triple_error() -> Error;
+    /// Write all subjects. The blank nodes that are nested are skipped,
+    /// unless they are in a cycle of nested blank nodes that is not reached
+    /// from any other subject. Such blank nodes are written last.
+    fn write_statements(&mut self) -> Result<()> {
+        let mut subjects = Vec::new();
+        for triple in self.graph().iter() {
+            let subject = triple.subject();
+            if subjects.last() != Some(&subject) {
+                subjects.push(subject);
+            }
+        }
+        let mut deferred = Vec::new();
+        for subject in subjects {
+            let nested = match subject {
+                BlankNodeOrIRI::BlankNode(ref blank_node, _) => {
+                    object_count(self.graph(), blank_node) == 1
+                }
+                BlankNodeOrIRI::IRI(_) => false,
+                BlankNodeOrIRI::QuotedTriple(_) => return Err(Self::quoted_triple_error()),
+            };
+            if nested {
+                deferred.push(subject);
+            } else {
+                self.write_subject(&subject)?;
+            }
+        }
+        // blank nodes in a cycle were not written yet
+        for subject in deferred {
+            let written = match subject {
+                BlankNodeOrIRI::BlankNode(ref blank_node, _) => self.is_written(blank_node),
+                _ => true,
+            };
+            if !written {
+                self.write_subject(&subject)?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,783 @@
+use constants::*;
+use error::{Error, Result, StatementKind, SyntaxError};
+use graph;
+use iri::is_absolute;
+use std::collections::HashMap;
+use std::io::BufRead;
+use std::marker::PhantomData;
+use std::str;
+use super::turtle::grammar::{is_pn_chars, is_pn_chars_u};
+use super::turtle::grammar_helper::{unescape, unescape_iri};
+
+/// A strict, line-oriented parser for RDF 1.1 N-Triples.
+///
+/// Each line holds at most one triple. Turtle-only syntax such as prefixes,
+/// `@base`, relative IRIs, `a`, collections and unquoted literals is rejected.
+/// Errors are reported as `Error::Syntax` with the position of the term
+/// that could not be parsed.
+///
+/// Quoted triples from N-Triples-star, `<< s p o >>`, are accepted as subject
+/// and object.
+pub struct NTriplesParser<'a, W: 'a>
+    where W: graph::GraphWriter<'a>
+{
+    src: &'a str,
+    len: usize,
+    line: usize,
+    /// the byte offset of the current line
+    offset: usize,
+    done: bool,
+    state: State<'a, W>,
+}
+
+/// A strict, line-oriented parser for RDF 1.1 N-Triples that reads from a
+/// `BufRead`.
+///
+/// This is `NTriplesParser` for input that does not fit in memory: only the
+/// line that is being parsed is kept.
+pub struct NTriplesReader<'a, R, W: 'a>
+    where R: BufRead,
+          W: graph::GraphWriter<'a>
+{
+    reader: R,
+    /// the last chunk that was read, up to and including a `\n`
+    chunk: Vec<u8>,
+    /// everything before `pos` in `chunk` has been parsed
+    pos: usize,
+    line: usize,
+    /// the byte offset of `chunk`
+    chunk_offset: usize,
+    done: bool,
+    state: State<'a, W>,
+}
+
+/// A strict, line-oriented parser for RDF 1.1 N-Quads.
+///
+/// This is `NTriplesParser` with an optional graph name after the object.
+/// Triples without a graph name are written to the default graph.
+pub struct NQuadsParser<'a, W: 'a>
+    where W: graph::DatasetWriter<'a>
+{
+    parser: NTriplesParser<'a, W>,
+}
+
+/// The part of the parser that does not depend on where the lines come from.
+struct State<'a, W: 'a>
+    where W: graph::GraphWriter<'a>
+{
+    writer: &'a mut W,
+    blank_nodes: HashMap<String, W::BlankNode>,
+    buffer: String,
+    xsd_string: Option<W::Datatype>,
+    /// accept a graph name after the object
+    quads: bool,
+}
+
+/// The terms on one line.
+struct Quad<'a, W: 'a>
+    where W: graph::GraphWriter<'a>
+{
+    subject: graph::WriterBlankNodeOrIRI<'a, W>,
+    predicate: W::IRI,
+    object: graph::WriterResource<'a, W>,
+    graph_name: Option<graph::WriterBlankNodeOrIRI<'a, W>>,
+}
+
+/// A cursor into a single line of input.
+struct Line<'l> {
+    s: &'l str,
+    pos: usize,
+    /// the start of the term that is being parsed
+    mark: usize,
+}
+
+enum Term<'l> {
+    IRI(&'l str),
+    BlankNode(&'l str),
+    Literal(&'l str, LiteralSuffix<'l>),
+}
+
+enum LiteralSuffix<'l> {
+    None,
+    Language(&'l str),
+    Datatype(&'l str),
+}
+
+impl<'a, W: 'a> NTriplesParser<'a, W>
+    where W: graph::GraphWriter<'a>
+{
+    /// Create a parser that reads N-Triples from `src` and writes the triples
+    /// to `writer`.
+    pub fn new(src: &'a str, writer: &'a mut W) -> Result<NTriplesParser<'a, W>> {
+        Ok(NTriplesParser {
+            src: src,
+            len: src.len(),
+            line: 0,
+            offset: 0,
+            done: false,
+            state: State::new(writer),
+        })
+    }
+    /// The number of the line that was parsed last. The first line is 1.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+    /// Split off the next line. Line ends are `\n`, `\r` or `\r\n`.
+    fn next_line(&mut self) -> Option<&'a str> {
+        if self.src.is_empty() {
+            return None;
+        }
+        self.line += 1;
+        self.offset = self.len - self.src.len();
+        let src = self.src;
+        let (line, end) = split_line(src.as_bytes());
+        self.src = &src[end..];
+        Some(&src[..line])
+    }
+    /// Parse lines until a triple is found.
+    fn next_quad(&mut self) -> Option<Result<Quad<'a, W>>> {
+        if self.done {
+            return None;
+        }
+        while let Some(line) = self.next_line() {
+            match self.state.parse(line, self.line, self.offset) {
+                Ok(Some(quad)) => return Some(Ok(quad)),
+                Ok(None) => {}
+                Err(e) => {
+                    self.done = true;
+                    return Some(Err(e));
+                }
+            }
+        }
+        self.done = true;
+        None
+    }
+}
+
+impl<'a, W: 'a> Iterator for NTriplesParser<'a, W>
+    where W: graph::GraphWriter<'a>
+{
+    type Item = Result<()>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.next_quad() {
+            Some(Ok(quad)) => {
+                self.state.writer.add(&quad.subject, &quad.predicate, &quad.object);
+                Some(Ok(()))
+            }
+            Some(Err(e)) => Some(Err(e)),
+            None => None,
+        }
+    }
+}
+
+impl<'a, R, W: 'a> NTriplesReader<'a, R, W>
+    where R: BufRead,
+          W: graph::GraphWriter<'a>
+{
+    /// Create a parser that reads N-Triples from `reader` and writes the
+    /// triples to `writer`.
+    pub fn new(reader: R, writer: &'a mut W) -> Result<NTriplesReader<'a, R, W>> {
+        Ok(NTriplesReader {
+            reader: reader,
+            chunk: Vec::new(),
+            pos: 0,
+            line: 0,
+            chunk_offset: 0,
+            done: false,
+            state: State::new(writer),
+        })
+    }
+    /// The number of the line that was parsed last. The first line is 1.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+    /// Parse lines until a triple is found. Returns `Ok(None)` at the end of
+    /// the input.
+    fn next_quad(&mut self) -> Result<Option<Quad<'a, W>>> {
+        loop {
+            if self.pos == self.chunk.len() {
+                self.chunk_offset += self.chunk.len();
+                self.chunk.clear();
+                self.pos = 0;
+                // a `\r` on its own also ends a line, so a chunk can hold
+                // more than one line
+                if self.reader.read_until(b'\n', &mut self.chunk)? == 0 {
+                    return Ok(None);
+                }
+            }
+            self.line += 1;
+            let offset = self.chunk_offset + self.pos;
+            let (line, end) = split_line(&self.chunk[self.pos..]);
+            let line = str::from_utf8(&self.chunk[self.pos..self.pos + line])
+                .map_err(|_| Error::Custom("Error decoding invalid UTF-8"))?;
+            self.pos += end;
+            if let Some(quad) = self.state.parse(line, self.line, offset)? {
+                return Ok(Some(quad));
+            }
+        }
+    }
+}
+
+impl<'a, R, W: 'a> Iterator for NTriplesReader<'a, R, W>
+    where R: BufRead,
+          W: graph::GraphWriter<'a>
+{
+    type Item = Result<()>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        match self.next_quad() {
+            Ok(Some(quad)) => {
+                self.state.writer.add(&quad.subject, &quad.predicate, &quad.object);
+                Some(Ok(()))
+            }
+            Ok(None) => {
+                self.done = true;
+                None
+            }
+            Err(e) => {
+                self.done = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+impl<'a, W: 'a> NQuadsParser<'a, W>
+    where W: graph::DatasetWriter<'a>
+{
+    /// Create a parser that reads N-Quads from `src` and writes the quads
+    /// to `writer`.
+    pub fn new(src: &'a str, writer: &'a mut W) -> Result<NQuadsParser<'a, W>> {
+        let mut parser = NTriplesParser::new(src, writer)?;
+        parser.state.quads = true;
+        Ok(NQuadsParser { parser: parser })
+    }
+    /// The number of the line that was parsed last. The first line is 1.
+    pub fn line(&self) -> usize {
+        self.parser.line
+    }
+}
+
+impl<'a, W: 'a> Iterator for NQuadsParser<'a, W>
+    where W: graph::DatasetWriter<'a>
+{
+    type Item = Result<()>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.parser.next_quad() {
+            Some(Ok(quad)) => {
+                Some(self.parser.state.writer.add_quad(quad.graph_name.as_ref(),
+                                                       &quad.subject,
+                                                       &quad.predicate,
+                                                       &quad.object))
+            }
+            Some(Err(e)) => Some(Err(e)),
+            None => None,
+        }
+    }
+}
+
+/// The length of the first line in `src` and the offset of the next line.
+/// Line ends are `\n`, `\r` or `\r\n`.
+fn split_line(src: &[u8]) -> (usize, usize) {
+    match src.iter().position(|&b| b == b'\n' || b == b'\r') {
+        Some(pos) => {
+            let mut end = pos + 1;
+            if src[pos] == b'\r' && src.get(end) == Some(&b'\n') {
+                end += 1;
+            }
+            (pos, end)
+        }
+        None => (src.len(), src.len()),
+    }
+}
+
+impl<'a, W: 'a> State<'a, W>
+    where W: graph::GraphWriter<'a>
+{
+    fn new(writer: &'a mut W) -> State<'a, W> {
+        State {
+            writer: writer,
+            blank_nodes: HashMap::new(),
+            buffer: String::new(),
+            xsd_string: None,
+            quads: false,
+        }
+    }
+    /// Parse the line with the given number that starts at byte `offset`.
+    fn parse(&mut self, s: &str, line: usize, offset: usize) -> Result<Option<Quad<'a, W>>> {
+        let mut cursor = Line {
+            s: s,
+            pos: 0,
+            mark: 0,
+        };
+        self.parse_line(&mut cursor).map_err(|e| {
+            Error::Syntax(SyntaxError {
+                line: line,
+                column: s[..cursor.mark].chars().count() + 1,
+                offset: offset + cursor.mark,
+                statement: StatementKind::Triples,
+                excerpt: s[cursor.mark..].chars().take(40).collect(),
+                message: e.to_string(),
+            })
+        })
+    }
+    /// Parse one line. Returns `Ok(None)` if the line contains no triple.
+    fn parse_line(&mut self, line: &mut Line) -> Result<Option<Quad<'a, W>>> {
+        line.skip_ws();
+        if line.at_end() || line.peek() == Some(b'#') {
+            return Ok(None);
+        }
+        let subject = self.subject(line)?;
+        line.skip_ws();
+        let predicate = self.predicate(line)?;
+        line.skip_ws();
+        let object = self.object(line)?;
+        line.skip_ws();
+        line.mark = line.pos;
+        let mut graph_name = None;
+        if self.quads && line.peek() != Some(b'.') {
+            graph_name = Some(match line.term()? {
+                Term::IRI(iri) => graph::WriterBlankNodeOrIRI::IRI(self.create_iri(iri)?),
+                Term::BlankNode(label) => {
+                    graph::WriterBlankNodeOrIRI::BlankNode(self.get_blank(label), PhantomData)
+                }
+                Term::Literal(_, _) => {
+                    return Err(Error::Custom("The graph name must be an IRI or a blank node."))
+                }
+            });
+            line.skip_ws();
+            line.mark = line.pos;
+        }
+        if line.peek() != Some(b'.') {
+            return Err(Error::Custom("Expected '.' at the end of the triple."));
+        }
+        line.pos += 1;
+        line.skip_ws();
+        line.mark = line.pos;
+        if !line.at_end() && line.peek() != Some(b'#') {
+            return Err(Error::Custom("Unexpected content after the triple."));
+        }
+        Ok(Some(Quad {
+            subject: subject,
+            predicate: predicate,
+            object: object,
+            graph_name: graph_name,
+        }))
+    }
+    fn subject(&mut self, line: &mut Line) -> Result<graph::WriterBlankNodeOrIRI<'a, W>> {
+        line.mark = line.pos;
+        if line.rest().starts_with("<<") {
+            return Ok(graph::WriterBlankNodeOrIRI::QuotedTriple(self.quoted_triple(line)?));
+        }
+        Ok(match line.term()? {
+            Term::IRI(iri) => graph::WriterBlankNodeOrIRI::IRI(self.create_iri(iri)?),
+            Term::BlankNode(label) => {
+                graph::WriterBlankNodeOrIRI::BlankNode(self.get_blank(label), PhantomData)
+            }
+            Term::Literal(_, _) => return Err(Error::Custom("A literal cannot be a subject.")),
+        })
+    }
+    fn predicate(&mut self, line: &mut Line) -> Result<W::IRI> {
+        line.mark = line.pos;
+        match line.term()? {
+            Term::IRI(iri) => self.create_iri(iri),
+            _ => Err(Error::Custom("The predicate must be an IRI.")),
+        }
+    }
+    fn object(&mut self, line: &mut Line) -> Result<graph::WriterResource<'a, W>> {
+        line.mark = line.pos;
+        if line.rest().starts_with("<<") {
+            return Ok(graph::WriterResource::QuotedTriple(self.quoted_triple(line)?));
+        }
+        Ok(match line.term()? {
+            Term::IRI(iri) => graph::WriterResource::IRI(self.create_iri(iri)?),
+            Term::BlankNode(label) => {
+                graph::WriterResource::BlankNode(self.get_blank(label), PhantomData)
+            }
+            Term::Literal(lexical, suffix) => {
+                graph::WriterResource::Literal(self.create_literal(lexical, suffix)?)
+            }
+        })
+    }
+    /// N-Triples-star `quotedTriple ::= '<<' subject predicate object '>>'`
+    fn quoted_triple(&mut self, line: &mut Line) -> Result<W::QuotedTriple> {
+        line.pos += 2;
+        line.skip_ws();
+        let subject = self.subject(line)?;
+        line.skip_ws();
+        let predicate = self.predicate(line)?;
+        line.skip_ws();
+        let object = self.object(line)?;
+        line.skip_ws();
+        line.mark = line.pos;
+        if !line.rest().starts_with(">>") {
+            return Err(Error::Custom("Expected '>>' at the end of the quoted triple."));
+        }
+        line.pos += 2;
+        Ok(self.writer.create_quoted_triple(&subject, &predicate, &object))
+    }
+    fn get_blank(&mut self, label: &str) -> W::BlankNode {
+        if let Some(n) = self.blank_nodes.get(label) {
+            return n.clone();
+        }
+        let n = self.writer.create_blank_node();
+        self.blank_nodes.insert(String::from(label), n.clone());
+        n
+    }
+    fn create_iri(&mut self, iri: &str) -> Result<W::IRI> {
+        if iri.contains('\\') {
+            self.buffer.clear();
+            unescape_iri(iri, &mut self.buffer)?;
+            if !is_absolute(&self.buffer) {
+                return Err(Error::Custom("IRI is not absolute."));
+            }
+            Ok(self.writer.create_iri(&self.buffer.as_str()))
+        } else {
+            if !is_absolute(iri) {
+                return Err(Error::Custom("IRI is not absolute."));
+            }
+            Ok(self.writer.create_iri(&iri))
+        }
+    }
+    fn create_literal(&mut self, lexical: &str, suffix: LiteralSuffix) -> Result<W::Literal> {
+        let datatype = match suffix {
+            LiteralSuffix::None => {
+                if self.xsd_string.is_none() {
+                    self.xsd_string = Some(self.writer.create_datatype(XSD_STRING));
+                }
+                self.xsd_string.clone().unwrap()
+            }
+            LiteralSuffix::Datatype(iri) => {
+                self.buffer.clear();
+                unescape_iri(iri, &mut self.buffer)?;
+                if !is_absolute(&self.buffer) {
+                    return Err(Error::Custom("IRI is not absolute."));
+                }
+                self.writer.create_datatype(&self.buffer)
+            }
+            LiteralSuffix::Language(lang) => {
+                let language = self.writer.create_language(lang);
+                self.buffer.clear();
+                unescape(lexical, &mut self.buffer)?;
+                return Ok(self.writer.create_literal_language(&self.buffer, &language));
+            }
+        };
+        if lexical.contains('\\') {
+            self.buffer.clear();
+            unescape(lexical, &mut self.buffer)?;
+            Ok(self.writer.create_literal_datatype(&self.buffer, &datatype))
+        } else {
+            Ok(self.writer.create_literal_datatype(lexical, &datatype))
+        }
+    }
+}
+
+impl<'l> Line<'l> {
+    fn at_end(&self) -> bool {
+        self.pos >= self.s.len()
+    }
+    fn peek(&self) -> Option<u8> {
+        self.s.as_bytes().get(self.pos).cloned()
+    }
+    fn rest(&self) -> &'l str {
+        &self.s[self.pos..]
+    }
+    /// [WS] in N-Triples is only space and tab within a line.
+    fn skip_ws(&mut self) {
+        while let Some(b' ') | Some(b'\t') = self.peek() {
+            self.pos += 1;
+        }
+    }
+    fn term(&mut self) -> Result<Term<'l>> {
+        match self.peek() {
+            Some(b'<') => self.iri_ref().map(Term::IRI),
+            Some(b'_') => self.blank_node_label().map(Term::BlankNode),
+            Some(b'"') => self.literal(),
+            _ => Err(Error::Custom("Expected an IRI, a blank node or a literal.")),
+        }
+    }
+    /// [8] `IRIREF ::= '<' ([^#x00-#x20<>"{}|^`\] | UCHAR)* '>'`
+    fn iri_ref(&mut self) -> Result<&'l str> {
+        self.pos += 1;
+        let start = self.pos;
+        let bytes = self.s.as_bytes();
+        loop {
+            match bytes.get(self.pos) {
+                None => return Err(Error::Custom("Unterminated IRI.")),
+                Some(&b'>') => break,
+                Some(&b'\\') => {
+                    self.pos += 1;
+                    let n = match bytes.get(self.pos) {
+                        Some(&b'u') => 4,
+                        Some(&b'U') => 8,
+                        _ => return Err(Error::Custom("Invalid escape sequence in IRI.")),
+                    };
+                    self.pos += 1;
+                    self.hex(n)?;
+                }
+                Some(&b) if b <= b' ' || b"<\"{}|^`".contains(&b) => {
+                    return Err(Error::Custom("Invalid character in IRI."));
+                }
+                _ => self.pos += 1,
+            }
+        }
+        let iri = &self.s[start..self.pos];
+        self.pos += 1;
+        Ok(iri)
+    }
+    fn hex(&mut self, n: usize) -> Result<()> {
+        let end = self.pos + n;
+        if end > self.s.len() || !self.s.as_bytes()[self.pos..end].iter().all(u8::is_ascii_hexdigit) {
+            return Err(Error::Custom("Invalid escape sequence."));
+        }
+        self.pos = end;
+        Ok(())
+    }
+    /// [141s] `BLANK_NODE_LABEL ::= '_:' (PN_CHARS_U | [0-9]) ((PN_CHARS | '.')* PN_CHARS)?`
+    fn blank_node_label(&mut self) -> Result<&'l str> {
+        if !self.rest().starts_with("_:") {
+            return Err(Error::Custom("Invalid blank node label."));
+        }
+        self.pos += 2;
+        let start = self.pos;
+        let mut chars = self.rest().char_indices();
+        match chars.next() {
+            Some((_, c)) if is_pn_chars_u(c) || c.is_ascii_digit() => {}
+            _ => return Err(Error::Custom("Invalid blank node label.")),
+        }
+        let mut end = self.rest().len();
+        for (i, c) in chars {
+            if c != '.' && !is_pn_chars(c) {
+                end = i;
+                break;
+            }
+        }
+        let mut label = &self.rest()[..end];
+        // a trailing '.' terminates the triple
+        while label.ends_with('.') {
+            label = &label[..label.len() - 1];
+        }
+        self.pos = start + label.len();
+        Ok(label)
+    }
+    /// [6] `literal ::= STRING_LITERAL_QUOTE ('^^' IRIREF | LANGTAG)?`
+    fn literal(&mut self) -> Result<Term<'l>> {
+        self.pos += 1;
+        let start = self.pos;
+        let bytes = self.s.as_bytes();
+        loop {
+            match bytes.get(self.pos) {
+                None => return Err(Error::Custom("Unterminated string literal.")),
+                Some(&b'"') => break,
+                Some(&b'\\') => {
+                    self.pos += 1;
+                    match bytes.get(self.pos) {
+                        Some(&b'u') => {
+                            self.pos += 1;
+                            self.hex(4)?;
+                        }
+                        Some(&b'U') => {
+                            self.pos += 1;
+                            self.hex(8)?;
+                        }
+                        Some(b) if b"tbnrf\"'\\".contains(b) => self.pos += 1,
+                        _ => return Err(Error::Custom("Invalid escape sequence in literal.")),
+                    }
+                }
+                _ => self.pos += 1,
+            }
+        }
+        let lexical = &self.s[start..self.pos];
+        self.pos += 1;
+        let suffix = if self.rest().starts_with("^^") {
+            self.pos += 2;
+            if self.peek() != Some(b'<') {
+                return Err(Error::Custom("Expected an IRI as datatype."));
+            }
+            LiteralSuffix::Datatype(self.iri_ref()?)
+        } else if self.peek() == Some(b'@') {
+            self.pos += 1;
+            LiteralSuffix::Language(self.langtag()?)
+        } else {
+            LiteralSuffix::None
+        };
+        Ok(Term::Literal(lexical, suffix))
+    }
+    /// [144s] `LANGTAG ::= '@' [a-zA-Z]+ ('-' [a-zA-Z0-9]+)*`
+    fn langtag(&mut self) -> Result<&'l str> {
+        let start = self.pos;
+        let bytes = self.s.as_bytes();
+        let mut first = true;
+        loop {
+            let part = self.pos;
+            while let Some(&b) = bytes.get(self.pos) {
+                if b.is_ascii_alphabetic() || (!first && b.is_ascii_digit()) {
+                    self.pos += 1;
+                } else {
+                    break;
+                }
+            }
+            if self.pos == part {
+                return Err(Error::Custom("Invalid language tag."));
+            }
+            first = false;
+            if bytes.get(self.pos) == Some(&b'-') {
+                self.pos += 1;
+            } else {
+                break;
+            }
+        }
+        Ok(&self.s[start..self.pos])
+    }
+}
+
+#[cfg(test)]
+fn parse(src: &str) -> Result<usize> {
+    use graph::{Graph, GraphWriter};
+    use graphs::tel;
+    let mut writer = tel::GraphCreator::with_capacity(0);
+    {
+        let parser = NTriplesParser::new(src, &mut writer)?;
+        for step in parser {
+            step?;
+        }
+    }
+    let graph: tel::Graph64 = writer.collect();
+    Ok(graph.iter().count())
+}
+
+#[test]
+fn test_ntriples() {
+    let s = "# comment\n<http://a.example/s> <http://a.example/p> _:b1 .\r\n\
+             _:b1 <http://a.example/p> \"x\\n\"@en-GB . # comment\n\
+             \n\
+             _:b1 <http://a.example/p> \"\\u0041\"^^<http://a.example/dt>.";
+    assert_eq!(parse(s).unwrap(), 3);
+}
+
+#[test]
+fn test_ntriples_rejects_turtle() {
+    assert!(parse("@prefix : <http://a.example/> .\n").is_err());
+    assert!(parse("<s> <http://a.example/p> <http://a.example/o> .\n").is_err());
+    assert!(parse("<http://a.example/s> a <http://a.example/o> .\n").is_err());
+    assert!(parse("<http://a.example/s> <http://a.example/p> 1 .\n").is_err());
+    assert!(parse("<http://a.example/s> <http://a.example/p> ( ) .\n").is_err());
+    assert!(parse("<http://a.example/s> <http://a.example/p> <http://a.example/o>\n.\n").is_err());
+}
+
+#[test]
+fn test_ntriples_line_number() {
+    let s = "<http://a.example/s> <http://a.example/p> <http://a.example/o> .\n\
+             \n\
+             <http://a.example/s> <http://a.example/p> 'o' .\n";
+    match parse(s).unwrap_err() {
+        Error::Syntax(e) => {
+            assert_eq!(e.line, 3);
+            assert_eq!(e.column, 43);
+            assert_eq!(e.offset, 108);
+            assert_eq!(e.excerpt, "'o' .");
+        }
+        e => panic!("unexpected error {:?}", e),
+    }
+}
+
+#[test]
+fn test_ntriples_reader() {
+    use graph::{Graph, GraphWriter};
+    use graphs::tel;
+    use std::io::BufReader;
+    let s = "# comment\n<http://a.example/s> <http://a.example/p> _:b1 .\r\n\
+             _:b1 <http://a.example/p> \"x\\n\"@en-GB .\r\
+             _:b1 <http://a.example/p> \"\\u0041\"^^<http://a.example/dt>.";
+    let mut writer = tel::GraphCreator::with_capacity(0);
+    for step in NTriplesReader::new(BufReader::with_capacity(1, s.as_bytes()), &mut writer)
+        .unwrap() {
+        step.unwrap();
+    }
+    let graph: tel::Graph64 = writer.collect();
+    assert_eq!(graph.iter().count(), 3);
+    let s = "<http://a.example/s> <http://a.example/p> <http://a.example/o> .\r\
+             \r\
+             <http://a.example/s> <http://a.example/p> 'o' .\n";
+    let mut writer = tel::GraphCreator::with_capacity(0);
+    {
+        let mut reader = NTriplesReader::new(s.as_bytes(), &mut writer).unwrap();
+        assert!(reader.next().unwrap().is_ok());
+        match reader.next().unwrap().unwrap_err() {
+            Error::Syntax(e) => {
+                assert_eq!((e.line, e.column, e.offset), (3, 43, 108));
+                assert_eq!(e.excerpt, "'o' .");
+            }
+            e => panic!("unexpected error {:?}", e),
+        }
+        assert!(reader.next().is_none());
+    }
+    let _: tel::Graph64 = writer.collect();
+}
+
+#[test]
+fn test_nquads() {
+    use graph::{Dataset, DatasetWriter};
+    use graphs::tel;
+    use io::write_nquads;
+    let s = "<http://a.example/s> <http://a.example/p> \"o\" .\n\
+             _:g <http://a.example/p> _:g _:g .\n\
+             <http://a.example/s> <http://a.example/p> \"x\"@en <http://a.example/g> .\n";
+    let mut writer = tel::DatasetCreator::with_capacity(0);
+    for step in NQuadsParser::new(s, &mut writer).unwrap() {
+        step.unwrap();
+    }
+    let dataset: tel::Dataset64 = writer.collect_dataset();
+    let mut out = Vec::new();
+    write_nquads(dataset.graphs(), &mut out).unwrap();
+    assert_eq!(String::from_utf8(out).unwrap(),
+               "<http://a.example/s> <http://a.example/p> \"o\" .\n\
+                _:1 <http://a.example/p> _:1 _:1 .\n\
+                <http://a.example/s> <http://a.example/p> \"x\"@en <http://a.example/g> .\n");
+}
+
+#[test]
+fn test_nquads_graph_name() {
+    let quad = "<http://a.example/s> <http://a.example/p> <http://a.example/o> \
+                <http://a.example/g> .\n";
+    assert!(parse(quad).is_err());
+    use graph::DatasetWriter;
+    use graphs::tel;
+    let mut writer = tel::DatasetCreator::with_capacity(0);
+    let s = "<http://a.example/s> <http://a.example/p> <http://a.example/o> \"g\" .\n";
+    assert!(NQuadsParser::new(s, &mut writer).unwrap().next().unwrap().is_err());
+    let _: tel::Dataset64 = writer.collect_dataset();
+}
+
+#[test]
+fn test_ntriples_star() {
+    use graph::{Graph, GraphWriter};
+    use graphs::tel;
+    use io::write_ntriples;
+    let s = "<< <http://a.example/s> <http://a.example/p> _:o >> <http://a.example/q> _:o .\n\
+             <http://a.example/s> <http://a.example/r> <<<<_:o <http://a.example/p> \"1\">>\
+             <http://a.example/p><http://a.example/o>>> .\n";
+    let mut writer = tel::GraphCreator::with_capacity(0);
+    for step in NTriplesParser::new(s, &mut writer).unwrap() {
+        step.unwrap();
+    }
+    let graph: tel::Graph64 = writer.collect();
+    let mut out = Vec::new();
+    write_ntriples(graph.iter(), &graph, &mut out).unwrap();
+    assert_eq!(String::from_utf8(out).unwrap(),
+               "<< <http://a.example/s> <http://a.example/p> _:1 >> <http://a.example/q> _:1 .\n\
+                <http://a.example/s> <http://a.example/r> \
+                << << _:1 <http://a.example/p> \"1\" >> <http://a.example/p> \
+                <http://a.example/o> >> .\n");
+    let missing_end = "<< <http://a.example/s> <http://a.example/p> <http://a.example/o> \
+                       <http://a.example/q> <http://a.example/o> .\n";
+    match parse(missing_end).unwrap_err() {
+        Error::Syntax(e) => assert_eq!(e.column, 67),
+        e => panic!("unexpected error {:?}", e),
+    }
+}
@@ -11,15 +11,19 @@ struct NTriplesWriter<'a, 'g, W: 'a, G: 'g>
     buffer: Vec<u8>,
     writer: &'a mut W,
     xsd_string: Option<<<G as Graph<'g>>::LiteralPtr as LiteralPtr<'g>>::DatatypePtr>,
+    /// the graph name that is written after each triple
+    graph_name: Option<GraphName<'g, G::BlankNodePtr>>,
     phantom: PhantomData<&'g u8>,
 }
 
 /// write an RDF 1.1 N-Triples file in canonical form
+///
+/// Quoted triples are written as `<< s p o >>` like in N-Triples-star.
 pub fn write_ntriples<'g, G: 'g, T: 'g, I, W>(triples: I,
                                               graph: &'g G,
                                               writer: &mut W)
                                               -> Result<()>
-    where T: Triple<'g, G::BlankNodePtr, G::IRIPtr, G::LiteralPtr>,
+    where T: Triple<'g, G::BlankNodePtr, G::IRIPtr, G::LiteralPtr, G::QuotedTriplePtr>,
           G: Graph<'g>,
           <G as Graph<'g>>::BlankNodePtr: Display,
           I: Iterator<Item = T>,
@@ -29,6 +33,7 @@ pub fn write_ntriples<'g, G: 'g, T: 'g, I, W>(triples: I,
         buffer: Vec::new(),
         writer: writer,
         xsd_string: graph.find_datatype(constants::XSD_STRING),
+        graph_name: None,
         phantom: PhantomData,
     };
     for triple in triples {
@@ -37,6 +42,56 @@ pub fn write_ntriples<'g, G: 'g, T: 'g, I, W>(triples: I,
     Ok(())
 }
 
+/// write an RDF 1.1 N-Quads file in canonical form
+///
+/// `graphs` yields each graph with its name. The default graph has no name.
+pub fn write_nquads<'g, G: 'g, I, W>(graphs: I, writer: &mut W) -> Result<()>
+    where G: Graph<'g>,
+          <G as Graph<'g>>::BlankNodePtr: Display,
+          I: Iterator<Item = (Option<GraphName<'g, G::BlankNodePtr>>, &'g G)>,
+          W: Write
+{
+    for (name, graph) in graphs {
+        let mut writer = NTriplesWriter::<_, G> {
+            buffer: Vec::new(),
+            writer: writer,
+            xsd_string: graph.find_datatype(constants::XSD_STRING),
+            graph_name: name,
+            phantom: PhantomData,
+        };
+        for triple in graph.iter() {
+            writer.write_ntriple(&triple)?;
+        }
+    }
+    Ok(())
+}
+
+/// Escape an IRI so that it can be written between `<` and `>` in
+/// N-Triples and Turtle.
+pub fn escape_iri(iri: &str, to: &mut Vec<u8>) {
+    for b in iri.as_bytes() {
+        if *b <= b' ' || b"<>\"{}|^`\\".contains(b) {
+            write!(to, "\\u{:04X}", *b).unwrap();
+        } else {
+            to.push(*b);
+        }
+    }
+}
+
+/// Escape the value of a literal so that it can be written between double
+/// quotes in N-Triples and Turtle.
+pub fn escape_literal_value(value: &str, to: &mut Vec<u8>) {
+    for b in value.as_bytes() {
+        match *b {
+            b'"' => to.extend_from_slice(b"\\\""),
+            b'\\' => to.extend_from_slice(b"\\\\"),
+            b'\n' => to.extend_from_slice(b"\\n"),
+            b'\r' => to.extend_from_slice(b"\\r"),
+            b => to.push(b),
+        }
+    }
+}
+
 impl<'a, 'g, W: 'a, G: 'g> NTriplesWriter<'a, 'g, W, G>
     where W: Write,
           G: Graph<'g>,
@@ -45,13 +100,7 @@ impl<'a, 'g, W: 'a, G: 'g> NTriplesWriter<'a, 'g, W, G>
     fn write_iri(&mut self, iri: &str) -> Result<()> {
         self.writer.write_all(b"<")?;
         self.buffer.clear();
-        for b in iri.as_bytes() {
-            if *b < 20 || b"<>\"{}|^`\\".contains(b) {
-                write!(&mut self.buffer, "\\u00{:X} ", *b).unwrap();
-            } else {
-                self.buffer.push(*b);
-            }
-        }
+        escape_iri(iri, &mut self.buffer);
         self.writer.write_all(&self.buffer[..])?;
         self.writer.write_all(b">")
     }
@@ -62,12 +111,7 @@ impl<'a, 'g, W: 'a, G: 'g> NTriplesWriter<'a, 'g, W, G>
     }
     fn write_literal_value(&mut self, value: &str) -> Result<()> {
         self.buffer.clear();
-        for b in value.as_bytes() {
-            if *b == 0x22 || *b == 0x5C || *b == 0x0A || *b == 0x0D {
-                self.buffer.push(b'\\');
-            }
-            self.buffer.push(*b);
-        }
+        escape_literal_value(value, &mut self.buffer);
         self.writer.write_all(&self.buffer[..])
     }
     fn write_literal(&mut self, literal: G::LiteralPtr) -> Result<()> {
@@ -83,34 +127,61 @@ impl<'a, 'g, W: 'a, G: 'g> NTriplesWriter<'a, 'g, W, G>
         }
         Ok(())
     }
+    fn write_quoted_triple(&mut self, triple: G::QuotedTriplePtr) -> Result<()> {
+        self.writer.write_all(b"<< ")?;
+        self.write_triple(&triple)?;
+        self.writer.write_all(b" >>")
+    }
     fn write_subject(&mut self,
-                     subject: BlankNodeOrIRI<'g, G::BlankNodePtr, G::IRIPtr>)
+                     subject: BlankNodeOrIRI<'g, G::BlankNodePtr, G::IRIPtr, G::QuotedTriplePtr>)
                      -> Result<()> {
         match subject {
             BlankNodeOrIRI::BlankNode(blank_node, _) => self.write_blank_node(blank_node),
             BlankNodeOrIRI::IRI(iri) => self.write_iri(iri.as_str()),
+            BlankNodeOrIRI::QuotedTriple(triple) => self.write_quoted_triple(triple),
         }
     }
     fn write_predicate(&mut self, predicate: &str) -> Result<()> {
         self.write_iri(predicate)
     }
     fn write_object(&mut self,
-                    object: Resource<'g, G::BlankNodePtr, G::IRIPtr, G::LiteralPtr>)
+                    object: Resource<'g,
+                                     G::BlankNodePtr,
+                                     G::IRIPtr,
+                                     G::LiteralPtr,
+                                     G::QuotedTriplePtr>)
                     -> Result<()> {
         match object {
             Resource::BlankNode(blank_node, _) => self.write_blank_node(blank_node),
             Resource::IRI(iri) => self.write_iri(iri.as_str()),
             Resource::Literal(literal) => self.write_literal(literal),
+            Resource::QuotedTriple(triple) => self.write_quoted_triple(triple),
         }
     }
-    fn write_ntriple<T: 'g>(&mut self, triple: &T) -> Result<()>
-        where T: Triple<'g, G::BlankNodePtr, G::IRIPtr, G::LiteralPtr>
+    fn write_triple<T>(&mut self, triple: &T) -> Result<()>
+        where T: Triple<'g, G::BlankNodePtr, G::IRIPtr, G::LiteralPtr, G::QuotedTriplePtr>
     {
         self.write_subject(triple.subject())?;
         self.writer.write_all(b" ")?;
         self.write_predicate(triple.predicate().as_str())?;
         self.writer.write_all(b" ")?;
-        self.write_object(triple.object())?;
+        self.write_object(triple.object())
+    }
+    fn write_ntriple<T: 'g>(&mut self, triple: &T) -> Result<()>
+        where T: Triple<'g, G::BlankNodePtr, G::IRIPtr, G::LiteralPtr, G::QuotedTriplePtr>
+    {
+        self.write_triple(triple)?;
+        match self.graph_name.clone() {
+            Some(GraphName::IRI(iri)) => {
+                self.writer.write_all(b" ")?;
+                self.write_iri(iri)?;
+            }
+            Some(GraphName::BlankNode(blank_node)) => {
+                self.writer.write_all(b" ")?;
+                self.write_blank_node(blank_node)?;
+            }
+            None => {}
+        }
         self.writer.write_all(b" .\n")
     }
 }
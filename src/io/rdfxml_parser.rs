@@ -0,0 +1,892 @@
+use constants::*;
+use error::{Error, Result, StatementKind, SyntaxError};
+use graph;
+use graph::{WriterBlankNodeOrIRI, WriterResource};
+use iri;
+use std::collections::{HashMap, HashSet};
+use std::marker::PhantomData;
+use xml::attribute::OwnedAttribute;
+use xml::common::{Position, TextPosition};
+use xml::name::OwnedName;
+use xml::reader::{EventReader, ParserConfig, XmlEvent};
+
+const RDF_NS: &'static str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#";
+const XML_NS: &'static str = "http://www.w3.org/XML/1998/namespace";
+
+/// Names in the rdf: namespace that are not allowed as node elements,
+/// property elements or property attributes.
+const CORE_SYNTAX_TERMS: &'static [&'static str] = &["RDF", "ID", "about", "parseType",
+                                                     "resource", "nodeID", "datatype"];
+const OLD_TERMS: &'static [&'static str] = &["aboutEach", "aboutEachPrefix", "bagID"];
+
+/// Attributes without a namespace that are read as rdf: attributes.
+const UNQUALIFIED_RDF_ATTRIBUTES: &'static [&'static str] = &["ID", "about", "resource",
+                                                              "parseType", "type"];
+
+type Subject<'a, W> = WriterBlankNodeOrIRI<'a, W>;
+
+/// The subject and predicate of a property element and the IRI from its
+/// `rdf:ID`, which reifies the triple.
+struct Statement<'a, W: 'a>
+    where W: graph::GraphWriter<'a>
+{
+    subject: Subject<'a, W>,
+    predicate: W::IRI,
+    reification: Option<String>,
+}
+
+/// What is expected in an open element.
+enum FrameKind<'a, W: 'a>
+    where W: graph::GraphWriter<'a>
+{
+    /// The document: the root element is `rdf:RDF` or a node element.
+    Document,
+    /// `rdf:RDF`: node elements.
+    Nodes,
+    /// A node element or a property element with `rdf:parseType="Resource"`:
+    /// property elements. `li` is the number of `rdf:li` elements so far.
+    Node { subject: Subject<'a, W>, li: u32 },
+    /// A property element: a node element or text.
+    Property {
+        statement: Statement<'a, W>,
+        datatype: Option<String>,
+        text: String,
+        object: Option<Subject<'a, W>>,
+    },
+    /// A property element with `rdf:parseType="Collection"`: node elements.
+    Collection {
+        statement: Statement<'a, W>,
+        items: Vec<Subject<'a, W>>,
+    },
+    /// A property element with `rdf:parseType="Literal"`: any XML.
+    /// `namespaces` are the namespace declarations that were written with
+    /// the depth of their element.
+    Literal {
+        statement: Statement<'a, W>,
+        xml: String,
+        depth: usize,
+        namespaces: Vec<(usize, String, String)>,
+    },
+    /// A property element whose object was given by its attributes: nothing.
+    Empty,
+}
+
+struct Frame<'a, W: 'a>
+    where W: graph::GraphWriter<'a>
+{
+    base: String,
+    lang: Option<String>,
+    kind: FrameKind<'a, W>,
+}
+
+/// A parser for RDF/XML documents that writes the triples to a `GraphWriter`.
+///
+/// Each step of the iterator reads one node element at the top of the
+/// document. XML errors and RDF/XML syntax errors end the parsing.
+pub struct RDFXMLParser<'a, W: 'a>
+    where W: graph::GraphWriter<'a>
+{
+    source: &'a str,
+    reader: EventReader<&'a [u8]>,
+    writer: &'a mut W,
+    frames: Vec<Frame<'a, W>>,
+    blank_nodes: HashMap<String, W::BlankNode>,
+    /// the IRIs that were made from `rdf:ID` and may not be used again
+    ids: HashSet<String>,
+    rdf_type: Option<W::IRI>,
+    rdf_first: Option<W::IRI>,
+    rdf_rest: Option<W::IRI>,
+    rdf_nil: Option<W::IRI>,
+    done: bool,
+}
+
+impl<'a, W: 'a> RDFXMLParser<'a, W>
+    where W: graph::GraphWriter<'a>
+{
+    pub fn new(src: &'a str, base: &str, writer: &'a mut W) -> Result<RDFXMLParser<'a, W>> {
+        if !iri::is_absolute(base) {
+            return Err(Error::Custom("base url is not absolute"));
+        }
+        let reader = ParserConfig::new()
+            .trim_whitespace(false)
+            .whitespace_to_characters(true)
+            .cdata_to_characters(true)
+            .coalesce_characters(true)
+            .ignore_comments(true)
+            .create_reader(src.as_bytes());
+        Ok(RDFXMLParser {
+            source: src,
+            reader: reader,
+            writer: writer,
+            frames: vec![Frame {
+                             base: String::from(base),
+                             lang: None,
+                             kind: FrameKind::Document,
+                         }],
+            blank_nodes: HashMap::new(),
+            ids: HashSet::new(),
+            rdf_type: None,
+            rdf_first: None,
+            rdf_rest: None,
+            rdf_nil: None,
+            done: false,
+        })
+    }
+    /// return Ok(true) when done
+    fn parse_node_element(&mut self) -> Result<bool> {
+        loop {
+            let event = match self.reader.next() {
+                Ok(event) => event,
+                Err(e) => {
+                    let message = String::from(e.msg());
+                    return Err(syntax_error(self.source, e.position(), message));
+                }
+            };
+            let result = match event {
+                XmlEvent::StartElement { name, attributes, .. } => {
+                    self.start_element(name, attributes).map(|_| false)
+                }
+                XmlEvent::EndElement { name } => self.end_element(name),
+                XmlEvent::Characters(text) |
+                XmlEvent::Whitespace(text) |
+                XmlEvent::CData(text) => self.characters(&text).map(|_| false),
+                XmlEvent::EndDocument => return Ok(true),
+                _ => Ok(false),
+            };
+            match result {
+                Ok(true) => return Ok(false),
+                Ok(false) => {}
+                Err(e) => {
+                    let position = self.reader.position();
+                    return Err(syntax_error(self.source, position, e.to_string()));
+                }
+            }
+        }
+    }
+    fn start_element(&mut self, name: OwnedName, attributes: Vec<OwnedAttribute>) -> Result<()> {
+        let mut parent = self.frames.pop().expect("the document frame is never removed");
+        if let FrameKind::Literal { ref mut xml, ref mut depth, ref mut namespaces, .. } =
+            parent.kind {
+            *depth += 1;
+            write_start_tag(&name, &attributes, *depth, namespaces, xml);
+            self.frames.push(parent);
+            return Ok(());
+        }
+        let mut base = parent.base.clone();
+        let mut lang = parent.lang.clone();
+        let mut attrs = Vec::with_capacity(attributes.len());
+        for mut attr in attributes {
+            if attr.name.namespace.as_deref() == Some(XML_NS) {
+                match attr.name.local_name.as_str() {
+                    "base" => base = iri::join(&base, &attr.value),
+                    "lang" if attr.value.is_empty() => lang = None,
+                    "lang" => lang = Some(attr.value),
+                    _ => {}
+                }
+            } else if attr.name.namespace.is_some() {
+                attrs.push(attr);
+            } else if UNQUALIFIED_RDF_ATTRIBUTES.contains(&attr.name.local_name.as_str()) {
+                attr.name.namespace = Some(String::from(RDF_NS));
+                attrs.push(attr);
+            }
+        }
+        let iri = element_iri(&name)?;
+        let kind = match parent.kind {
+            FrameKind::Document if iri == format!("{}RDF", RDF_NS) => FrameKind::Nodes,
+            FrameKind::Document | FrameKind::Nodes => {
+                self.node_element(&iri, attrs, &base, &lang)?.1
+            }
+            FrameKind::Node { ref subject, ref mut li } => {
+                self.property_element(subject, li, iri, attrs, &base, &lang)?
+            }
+            FrameKind::Property { ref statement, ref datatype, ref text, ref mut object } => {
+                if object.is_some() {
+                    return Err(Error::Custom("A property element can have only one node \
+                                              element."));
+                }
+                if datatype.is_some() || !is_whitespace(text) {
+                    return Err(Error::Custom("A property element cannot contain text and a \
+                                              node element."));
+                }
+                let (subject, kind) = self.node_element(&iri, attrs, &base, &lang)?;
+                self.add(&statement.subject,
+                         &statement.predicate,
+                         &to_object(&subject));
+                *object = Some(subject);
+                kind
+            }
+            FrameKind::Collection { ref mut items, .. } => {
+                let (subject, kind) = self.node_element(&iri, attrs, &base, &lang)?;
+                items.push(subject);
+                kind
+            }
+            FrameKind::Empty => {
+                return Err(Error::Custom("This property element must be empty."));
+            }
+            FrameKind::Literal { .. } => unreachable!(),
+        };
+        self.frames.push(parent);
+        self.frames.push(Frame {
+            base: base,
+            lang: lang,
+            kind: kind,
+        });
+        Ok(())
+    }
+    /// Write the triples of the attributes of a node element and return its
+    /// subject and frame.
+    fn node_element(&mut self,
+                    iri: &str,
+                    attributes: Vec<OwnedAttribute>,
+                    base: &str,
+                    lang: &Option<String>)
+                    -> Result<(Subject<'a, W>, FrameKind<'a, W>)> {
+        if let Some(name) = rdf_local_name(iri) {
+            if name == "li" || CORE_SYNTAX_TERMS.contains(&name) || OLD_TERMS.contains(&name) {
+                return Err(Error::String(format!("rdf:{} is not allowed as a node element.",
+                                                 name)));
+            }
+        }
+        let mut subject = None;
+        let mut properties = Vec::new();
+        for attr in attributes {
+            let node = match rdf_local_name_of(&attr.name) {
+                Some("about") => {
+                    let iri = iri::join(base, &attr.value);
+                    WriterBlankNodeOrIRI::IRI(self.writer.create_iri(&iri))
+                }
+                Some("ID") => {
+                    let iri = self.id(base, &attr.value)?;
+                    WriterBlankNodeOrIRI::IRI(self.writer.create_iri(&iri))
+                }
+                Some("nodeID") => {
+                    WriterBlankNodeOrIRI::BlankNode(self.node_id(&attr.value)?, PhantomData)
+                }
+                _ => {
+                    properties.push(attr);
+                    continue;
+                }
+            };
+            if subject.is_some() {
+                return Err(Error::Custom("A node element can have only one of rdf:about, \
+                                          rdf:ID and rdf:nodeID."));
+            }
+            subject = Some(node);
+        }
+        let subject = match subject {
+            Some(subject) => subject,
+            None => WriterBlankNodeOrIRI::BlankNode(self.writer.create_blank_node(), PhantomData),
+        };
+        if iri != format!("{}Description", RDF_NS) {
+            let rdf_type = get_cached_iri(&mut self.rdf_type, self.writer, RDF_TYPE);
+            let class = WriterResource::IRI(self.writer.create_iri(&iri));
+            self.add(&subject, &rdf_type, &class);
+        }
+        self.add_property_attributes(&subject, properties, base, lang)?;
+        Ok((copy(&subject),
+            FrameKind::Node {
+            subject: subject,
+            li: 0,
+        }))
+    }
+    /// Handle the start of a property element and return its frame.
+    fn property_element(&mut self,
+                        subject: &Subject<'a, W>,
+                        li: &mut u32,
+                        mut iri: String,
+                        attributes: Vec<OwnedAttribute>,
+                        base: &str,
+                        lang: &Option<String>)
+                        -> Result<FrameKind<'a, W>> {
+        if let Some(name) = rdf_local_name(&iri).map(String::from) {
+            if name == "Description" || CORE_SYNTAX_TERMS.contains(&name.as_str()) ||
+               OLD_TERMS.contains(&name.as_str()) {
+                return Err(Error::String(format!("rdf:{} is not allowed as a property \
+                                                  element.",
+                                                 name)));
+            }
+            if name == "li" {
+                *li += 1;
+                iri = format!("{}_{}", RDF_NS, li);
+            }
+        }
+        let mut id = None;
+        let mut parse_type = None;
+        let mut resource = None;
+        let mut node_id = None;
+        let mut datatype = None;
+        let mut properties = Vec::new();
+        for attr in attributes {
+            match rdf_local_name_of(&attr.name) {
+                Some("ID") => id = Some(self.id(base, &attr.value)?),
+                Some("parseType") => parse_type = Some(attr.value),
+                Some("resource") => resource = Some(iri::join(base, &attr.value)),
+                Some("nodeID") => node_id = Some(self.node_id(&attr.value)?),
+                Some("datatype") => datatype = Some(iri::join(base, &attr.value)),
+                _ => properties.push(attr),
+            }
+        }
+        let statement = Statement {
+            subject: copy(subject),
+            predicate: self.writer.create_iri(&iri),
+            reification: id,
+        };
+        if let Some(parse_type) = parse_type {
+            if resource.is_some() || node_id.is_some() || datatype.is_some() ||
+               !properties.is_empty() {
+                return Err(Error::Custom("rdf:parseType can only be combined with rdf:ID."));
+            }
+            return Ok(match parse_type.as_str() {
+                "Resource" => {
+                    let node = self.writer.create_blank_node();
+                    let object = WriterResource::BlankNode(node.clone(), PhantomData);
+                    self.add_statement(&statement, &object);
+                    FrameKind::Node {
+                        subject: WriterBlankNodeOrIRI::BlankNode(node, PhantomData),
+                        li: 0,
+                    }
+                }
+                "Collection" => {
+                    FrameKind::Collection {
+                        statement: statement,
+                        items: Vec::new(),
+                    }
+                }
+                _ => {
+                    FrameKind::Literal {
+                        statement: statement,
+                        xml: String::new(),
+                        depth: 0,
+                        namespaces: Vec::new(),
+                    }
+                }
+            });
+        }
+        if resource.is_none() && node_id.is_none() && properties.is_empty() {
+            return Ok(FrameKind::Property {
+                statement: statement,
+                datatype: datatype,
+                text: String::new(),
+                object: None,
+            });
+        }
+        if datatype.is_some() {
+            return Err(Error::Custom("rdf:datatype cannot be combined with rdf:resource, \
+                                      rdf:nodeID or property attributes."));
+        }
+        let object = match (resource, node_id) {
+            (Some(_), Some(_)) => {
+                return Err(Error::Custom("rdf:resource and rdf:nodeID cannot be combined."));
+            }
+            (Some(iri), None) => WriterBlankNodeOrIRI::IRI(self.writer.create_iri(&iri)),
+            (None, Some(node)) => WriterBlankNodeOrIRI::BlankNode(node, PhantomData),
+            (None, None) => {
+                WriterBlankNodeOrIRI::BlankNode(self.writer.create_blank_node(), PhantomData)
+            }
+        };
+        self.add_statement(&statement, &to_object(&object));
+        self.add_property_attributes(&object, properties, base, lang)?;
+        Ok(FrameKind::Empty)
+    }
+    /// Return true if a top level node element was finished.
+    fn end_element(&mut self, name: OwnedName) -> Result<bool> {
+        let frame = self.frames.pop().expect("an element was open");
+        match frame.kind {
+            FrameKind::Literal { statement, mut xml, depth, mut namespaces } => {
+                if depth > 0 {
+                    xml.push_str("</");
+                    qualified_name(&name, &mut xml);
+                    xml.push('>');
+                    namespaces.retain(|n| n.0 != depth);
+                    self.frames.push(Frame {
+                        base: frame.base,
+                        lang: frame.lang,
+                        kind: FrameKind::Literal {
+                            statement: statement,
+                            xml: xml,
+                            depth: depth - 1,
+                            namespaces: namespaces,
+                        },
+                    });
+                    return Ok(false);
+                }
+                let datatype = self.writer.create_datatype(RDF_XML_LITERAL);
+                let literal = self.writer.create_literal_datatype(&xml, &datatype);
+                self.add_statement(&statement, &WriterResource::Literal(literal));
+            }
+            FrameKind::Property { statement, datatype, text, object } => {
+                let object = match object {
+                    Some(object) => to_object(&object),
+                    None => {
+                        let literal = self.create_literal(&text, datatype, &frame.lang);
+                        self.add(&statement.subject, &statement.predicate, &literal);
+                        literal
+                    }
+                };
+                self.reify(&statement, &object);
+            }
+            FrameKind::Collection { statement, items } => {
+                let list = self.make_collection(items);
+                self.add_statement(&statement, &to_object(&list));
+            }
+            FrameKind::Node { .. } => {
+                return Ok(matches!(self.frames.last().map(|frame| &frame.kind),
+                                   Some(&FrameKind::Document) | Some(&FrameKind::Nodes)));
+            }
+            FrameKind::Nodes | FrameKind::Empty | FrameKind::Document => {}
+        }
+        Ok(false)
+    }
+    fn characters(&mut self, text: &str) -> Result<()> {
+        match self.frames.last_mut() {
+            Some(&mut Frame { kind: FrameKind::Literal { ref mut xml, .. }, .. }) => {
+                escape_text(text, xml);
+            }
+            Some(&mut Frame { kind: FrameKind::Property { ref object, text: ref mut t, .. },
+                              .. }) => {
+                if object.is_some() && !is_whitespace(text) {
+                    return Err(Error::Custom("A property element cannot contain text and a \
+                                              node element."));
+                }
+                t.push_str(text);
+            }
+            _ => {
+                if !is_whitespace(text) {
+                    return Err(Error::Custom("Text is not allowed here."));
+                }
+            }
+        }
+        Ok(())
+    }
+    fn add(&mut self,
+           subject: &Subject<'a, W>,
+           predicate: &W::IRI,
+           object: &WriterResource<'a, W>) {
+        self.writer.add(subject, predicate, object);
+    }
+    /// Add the triple of a property element and its reification.
+    fn add_statement(&mut self, statement: &Statement<'a, W>, object: &WriterResource<'a, W>) {
+        self.add(&statement.subject, &statement.predicate, object);
+        self.reify(statement, object);
+    }
+    fn reify(&mut self, statement: &Statement<'a, W>, object: &WriterResource<'a, W>) {
+        let reification = match statement.reification {
+            Some(ref iri) => WriterBlankNodeOrIRI::IRI(self.writer.create_iri(iri)),
+            None => return,
+        };
+        let rdf_type = get_cached_iri(&mut self.rdf_type, self.writer, RDF_TYPE);
+        let rdf_statement = WriterResource::IRI(self.writer.create_iri(&RDF_STATEMENT));
+        self.add(&reification, &rdf_type, &rdf_statement);
+        let rdf_subject = self.writer.create_iri(&RDF_SUBJECT);
+        self.add(&reification, &rdf_subject, &to_object(&statement.subject));
+        let rdf_predicate = self.writer.create_iri(&RDF_PREDICATE);
+        let predicate = WriterResource::IRI(statement.predicate.clone());
+        self.add(&reification, &rdf_predicate, &predicate);
+        let rdf_object = self.writer.create_iri(&RDF_OBJECT);
+        self.add(&reification, &rdf_object, object);
+    }
+    fn add_property_attributes(&mut self,
+                               subject: &Subject<'a, W>,
+                               attributes: Vec<OwnedAttribute>,
+                               base: &str,
+                               lang: &Option<String>)
+                               -> Result<()> {
+        for attr in attributes {
+            let iri = element_iri(&attr.name)?;
+            let object = match rdf_local_name(&iri) {
+                Some("type") => {
+                    WriterResource::IRI(self.writer.create_iri(&iri::join(base, &attr.value)))
+                }
+                Some(name) if name == "li" || name == "Description" ||
+                              CORE_SYNTAX_TERMS.contains(&name) ||
+                              OLD_TERMS.contains(&name) => {
+                    return Err(Error::String(format!("rdf:{} is not allowed as a property \
+                                                      attribute.",
+                                                     name)));
+                }
+                _ => self.create_literal(&attr.value, None, lang),
+            };
+            let predicate = self.writer.create_iri(&iri);
+            self.add(subject, &predicate, &object);
+        }
+        Ok(())
+    }
+    fn create_literal(&mut self,
+                      value: &str,
+                      datatype: Option<String>,
+                      lang: &Option<String>)
+                      -> WriterResource<'a, W> {
+        WriterResource::Literal(match (datatype, lang.as_ref()) {
+            (Some(datatype), _) => {
+                let datatype = self.writer.create_datatype(&datatype);
+                self.writer.create_literal_datatype(value, &datatype)
+            }
+            (None, Some(lang)) => {
+                let language = self.writer.create_language(lang);
+                self.writer.create_literal_language(value, &language)
+            }
+            (None, None) => {
+                let datatype = self.writer.create_datatype(XSD_STRING);
+                self.writer.create_literal_datatype(value, &datatype)
+            }
+        })
+    }
+    fn make_collection(&mut self, items: Vec<Subject<'a, W>>) -> Subject<'a, W> {
+        let mut head =
+            WriterBlankNodeOrIRI::IRI(get_cached_iri(&mut self.rdf_nil, self.writer, RDF_NIL));
+        for item in items.into_iter().rev() {
+            let this = WriterBlankNodeOrIRI::BlankNode(self.writer.create_blank_node(),
+                                                       PhantomData);
+            let rdf_first = get_cached_iri(&mut self.rdf_first, self.writer, RDF_FIRST);
+            let rdf_rest = get_cached_iri(&mut self.rdf_rest, self.writer, RDF_REST);
+            self.add(&this, &rdf_first, &to_object(&item));
+            self.add(&this, &rdf_rest, &to_object(&head));
+            head = this;
+        }
+        head
+    }
+    /// The IRI for `rdf:ID`; each IRI can be used only once.
+    fn id(&mut self, base: &str, id: &str) -> Result<String> {
+        if !is_nc_name(id) {
+            return Err(Error::String(format!("'{}' is not a valid rdf:ID.", id)));
+        }
+        let iri = iri::join(base, &format!("#{}", id));
+        if !self.ids.insert(iri.clone()) {
+            return Err(Error::String(format!("rdf:ID '{}' is used more than once.", id)));
+        }
+        Ok(iri)
+    }
+    fn node_id(&mut self, label: &str) -> Result<W::BlankNode> {
+        if !is_nc_name(label) {
+            return Err(Error::String(format!("'{}' is not a valid rdf:nodeID.", label)));
+        }
+        if let Some(n) = self.blank_nodes.get(label) {
+            return Ok(n.clone());
+        }
+        let n = self.writer.create_blank_node();
+        self.blank_nodes.insert(String::from(label), n.clone());
+        Ok(n)
+    }
+}
+
+impl<'a, W: 'a> Iterator for RDFXMLParser<'a, W>
+    where W: graph::GraphWriter<'a>
+{
+    type Item = Result<()>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        match self.parse_node_element() {
+            Ok(true) => {
+                self.done = true;
+                None
+            }
+            Ok(false) => Some(Ok(())),
+            Err(e) => {
+                self.done = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+/// Create a syntax error for a zero-based `position` in `src`.
+fn syntax_error(src: &str, position: TextPosition, message: String) -> Error {
+    let mut offset = 0;
+    for _ in 0..position.row {
+        match src[offset..].find('\n') {
+            Some(pos) => offset += pos + 1,
+            None => break,
+        }
+    }
+    let line = src[offset..].lines().next().unwrap_or("");
+    offset += line.char_indices().nth(position.column as usize).map_or(line.len(), |(i, _)| i);
+    Error::Syntax(SyntaxError {
+        line: position.row as usize + 1,
+        column: position.column as usize + 1,
+        offset: offset,
+        statement: StatementKind::Element,
+        excerpt: src[offset..].lines().next().unwrap_or("").chars().take(40).collect(),
+        message: message,
+    })
+}
+
+fn get_cached_iri<'a, W: 'a>(cache: &mut Option<W::IRI>, writer: &mut W, iri: &str) -> W::IRI
+    where W: graph::GraphWriter<'a>
+{
+    if cache.is_none() {
+        *cache = Some(writer.create_iri(&iri));
+    }
+    cache.clone().unwrap()
+}
+
+fn copy<'a, W>(node: &Subject<'a, W>) -> Subject<'a, W>
+    where W: graph::GraphWriter<'a>
+{
+    match *node {
+        WriterBlankNodeOrIRI::BlankNode(ref n, _) => {
+            WriterBlankNodeOrIRI::BlankNode(n.clone(), PhantomData)
+        }
+        WriterBlankNodeOrIRI::IRI(ref iri) => WriterBlankNodeOrIRI::IRI(iri.clone()),
+        WriterBlankNodeOrIRI::QuotedTriple(ref t) => WriterBlankNodeOrIRI::QuotedTriple(t.clone()),
+    }
+}
+
+fn to_object<'a, W>(node: &Subject<'a, W>) -> WriterResource<'a, W>
+    where W: graph::GraphWriter<'a>
+{
+    match *node {
+        WriterBlankNodeOrIRI::BlankNode(ref n, _) => {
+            WriterResource::BlankNode(n.clone(), PhantomData)
+        }
+        WriterBlankNodeOrIRI::IRI(ref iri) => WriterResource::IRI(iri.clone()),
+        WriterBlankNodeOrIRI::QuotedTriple(ref t) => WriterResource::QuotedTriple(t.clone()),
+    }
+}
+
+/// The IRI of an element or attribute name.
+fn element_iri(name: &OwnedName) -> Result<String> {
+    match name.namespace {
+        Some(ref ns) => Ok(format!("{}{}", ns, name.local_name)),
+        None => {
+            Err(Error::String(format!("'{}' is not in a namespace.", name.local_name)))
+        }
+    }
+}
+
+fn rdf_local_name(iri: &str) -> Option<&str> {
+    iri.strip_prefix(RDF_NS)
+}
+
+fn rdf_local_name_of(name: &OwnedName) -> Option<&str> {
+    match name.namespace {
+        Some(ref ns) if ns == RDF_NS => Some(name.local_name.as_str()),
+        _ => None,
+    }
+}
+
+fn is_whitespace(text: &str) -> bool {
+    text.chars().all(|c| c == ' ' || c == '\t' || c == '\r' || c == '\n')
+}
+
+/// Check if `name` is a valid XML name without a colon.
+pub fn is_nc_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(c) if c.is_alphabetic() || c == '_' => {}
+        _ => return false,
+    }
+    chars.all(|c| c.is_alphanumeric() || c == '_' || c == '-' || c == '.' || c == '\u{B7}')
+}
+
+fn qualified_name(name: &OwnedName, to: &mut String) {
+    if let Some(ref prefix) = name.prefix {
+        to.push_str(prefix);
+        to.push(':');
+    }
+    to.push_str(&name.local_name);
+}
+
+/// Write a start tag for an XML literal in exclusive canonical form:
+/// namespaces are declared where they are first used and attributes are
+/// sorted.
+fn write_start_tag(name: &OwnedName,
+                   attributes: &[OwnedAttribute],
+                   depth: usize,
+                   namespaces: &mut Vec<(usize, String, String)>,
+                   xml: &mut String) {
+    xml.push('<');
+    qualified_name(name, xml);
+    let mut used = vec![(name.prefix.clone().unwrap_or_default(),
+                         name.namespace.clone().unwrap_or_default())];
+    for attr in attributes {
+        if let Some(ref prefix) = attr.name.prefix {
+            if prefix != "xml" {
+                used.push((prefix.clone(), attr.name.namespace.clone().unwrap_or_default()));
+            }
+        }
+    }
+    let mut declarations: Vec<(String, String)> = Vec::new();
+    for (prefix, ns) in used {
+        let written = namespaces.iter()
+            .rev()
+            .find(|n| n.1 == prefix)
+            .map_or("", |n| n.2.as_str());
+        if written != ns && !declarations.iter().any(|d| d.0 == prefix) {
+            declarations.push((prefix, ns));
+        }
+    }
+    declarations.sort();
+    for (prefix, ns) in declarations {
+        if prefix.is_empty() {
+            xml.push_str(" xmlns=\"");
+        } else {
+            xml.push_str(" xmlns:");
+            xml.push_str(&prefix);
+            xml.push_str("=\"");
+        }
+        escape_attribute(&ns, xml);
+        xml.push('"');
+        namespaces.push((depth, prefix, ns));
+    }
+    let mut attributes: Vec<_> = attributes.iter().collect();
+    attributes.sort_by(|a, b| {
+        (&a.name.namespace, &a.name.local_name).cmp(&(&b.name.namespace, &b.name.local_name))
+    });
+    for attr in attributes {
+        xml.push(' ');
+        qualified_name(&attr.name, xml);
+        xml.push_str("=\"");
+        escape_attribute(&attr.value, xml);
+        xml.push('"');
+    }
+    xml.push('>');
+}
+
+fn escape_text(text: &str, xml: &mut String) {
+    for c in text.chars() {
+        match c {
+            '&' => xml.push_str("&amp;"),
+            '<' => xml.push_str("&lt;"),
+            '>' => xml.push_str("&gt;"),
+            '\r' => xml.push_str("&#xD;"),
+            c => xml.push(c),
+        }
+    }
+}
+
+fn escape_attribute(text: &str, xml: &mut String) {
+    for c in text.chars() {
+        match c {
+            '&' => xml.push_str("&amp;"),
+            '<' => xml.push_str("&lt;"),
+            '"' => xml.push_str("&quot;"),
+            '\t' => xml.push_str("&#x9;"),
+            '\n' => xml.push_str("&#xA;"),
+            '\r' => xml.push_str("&#xD;"),
+            c => xml.push(c),
+        }
+    }
+}
+
+#[cfg(test)]
+fn rdfxml_to_ntriples(s: &str) -> Result<String> {
+    use graph::{Graph, GraphWriter};
+    use graphs::tel;
+    use io::write_ntriples;
+    let mut writer = tel::GraphCreator::with_capacity(0);
+    for step in RDFXMLParser::new(s, "http://a.example/doc", &mut writer)? {
+        step?;
+    }
+    let graph: tel::Graph64 = writer.collect();
+    let mut out = Vec::new();
+    write_ntriples(graph.iter(), &graph, &mut out)?;
+    Ok(String::from_utf8(out)?)
+}
+
+#[test]
+fn test_rdfxml() {
+    let s = r##"<?xml version="1.0"?>
+<rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#"
+         xmlns:ex="http://a.example/" xml:base="http://a.example/base/">
+  <ex:Thing rdf:about="s" ex:name="n" xml:lang="en">
+    <ex:p rdf:resource="#o"/>
+    <ex:q rdf:nodeID="b"/>
+    <ex:r rdf:datatype="http://a.example/dt">1</ex:r>
+    <ex:l rdf:parseType="Literal"><b xmlns="http://www.w3.org/1999/xhtml" class="c">x &amp; y</b></ex:l>
+    <ex:c rdf:parseType="Collection"><rdf:Description rdf:about="a"/><rdf:Description rdf:nodeID="b"/></ex:c>
+    <ex:res rdf:parseType="Resource" xml:lang=""><ex:v>w</ex:v></ex:res>
+    <rdf:li>one</rdf:li>
+    <ex:z rdf:ID="r"><ex:O/></ex:z>
+  </ex:Thing>
+</rdf:RDF>"##;
+    let rdf = "http://www.w3.org/1999/02/22-rdf-syntax-ns#";
+    let expected = "_:2 <RDF#first> _:1 .
+_:2 <RDF#rest> <RDF#nil> .
+_:3 <RDF#first> <http://a.example/base/a> .
+_:3 <RDF#rest> _:2 .
+_:4 <http://a.example/v> \"w\" .
+_:5 <RDF#type> <http://a.example/O> .
+<http://a.example/base/#r> <RDF#object> _:5 .
+<http://a.example/base/#r> <RDF#predicate> <http://a.example/z> .
+<http://a.example/base/#r> <RDF#subject> <http://a.example/base/s> .
+<http://a.example/base/#r> <RDF#type> <RDF#Statement> .
+<http://a.example/base/s> <http://a.example/c> _:3 .
+<http://a.example/base/s> <http://a.example/l> \"<b xmlns=\\\"http://www.w3.org/1999/xhtml\\\" \
+class=\\\"c\\\">x &amp; y</b>\"^^<RDF#XMLLiteral> .
+<http://a.example/base/s> <http://a.example/name> \"n\"@en .
+<http://a.example/base/s> <http://a.example/p> <http://a.example/base/#o> .
+<http://a.example/base/s> <http://a.example/q> _:1 .
+<http://a.example/base/s> <http://a.example/r> \"1\"^^<http://a.example/dt> .
+<http://a.example/base/s> <http://a.example/res> _:4 .
+<http://a.example/base/s> <http://a.example/z> _:5 .
+<http://a.example/base/s> <RDF#_1> \"one\"@en .
+<http://a.example/base/s> <RDF#type> <http://a.example/Thing> .
+";
+    assert_eq!(rdfxml_to_ntriples(s).unwrap(), expected.replace("RDF#", rdf));
+}
+
+#[test]
+fn test_rdfxml_literal_namespaces() {
+    let s = "<ex:s xmlns:ex=\"http://a.example/\" \
+             xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\">\
+             <ex:p rdf:parseType=\"Literal\"><ex:a ex:z=\"1\" a=\"2\"><ex:b/></ex:a> \
+             <c/></ex:p></ex:s>";
+    let nt = rdfxml_to_ntriples(s).unwrap();
+    assert!(nt.contains("\"<ex:a xmlns:ex=\\\"http://a.example/\\\" a=\\\"2\\\" \
+                         ex:z=\\\"1\\\"><ex:b></ex:b></ex:a> <c></c>\"^^"),
+            "{}",
+            nt);
+}
+
+#[test]
+fn test_rdfxml_steps() {
+    use graph::GraphWriter;
+    use graphs::tel;
+    let s = "<rdf:RDF xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\">\
+             <rdf:Description/><rdf:Description/></rdf:RDF>";
+    let mut writer = tel::GraphCreator::with_capacity(0);
+    {
+        let parser = RDFXMLParser::new(s, "http://a.example/", &mut writer).unwrap();
+        assert_eq!(parser.map(|step| step.unwrap()).count(), 2);
+    }
+    let graph: tel::Graph64 = writer.collect();
+    assert_eq!(graph.len(), 0);
+}
+
+#[test]
+fn test_rdfxml_errors() {
+    let rdf = "xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\"";
+    let errors = ["<rdf:RDF $><rdf:li/></rdf:RDF>",
+                  "<rdf:Description $ rdf:about=\"a\" rdf:nodeID=\"b\"/>",
+                  "<rdf:Description $><rdf:Description/></rdf:Description>",
+                  "<rdf:Description $ rdf:resource=\"a\"/>",
+                  "<rdf:Description $ rdf:nodeID=\"1\"/>",
+                  "<rdf:RDF $><rdf:Description rdf:ID=\"a\"/><rdf:Description \
+                   rdf:ID=\"a\"/></rdf:RDF>",
+                  "<rdf:Description $><rdf:type rdf:resource=\"a\">b</rdf:type></rdf:Description>",
+                  "<rdf:Description $><rdf:type>a<rdf:Description/></rdf:type></rdf:Description>",
+                  "<rdf:Description $>text</rdf:Description>",
+                  "<Description/>"];
+    for e in errors.iter() {
+        assert!(rdfxml_to_ntriples(&e.replace("$", rdf)).is_err(), "{}", e);
+    }
+}
+
+#[test]
+fn test_rdfxml_error_position() {
+    let s = "<rdf:RDF xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\">\n  \
+             <rdf:Description rdf:about=\"a\"/>\n  <rdf:li/>\n</rdf:RDF>";
+    match rdfxml_to_ntriples(s).unwrap_err() {
+        Error::Syntax(e) => {
+            assert_eq!(e.line, 3);
+            assert_eq!(e.column, 3);
+            assert_eq!(e.offset, 103);
+            assert_eq!(e.statement, StatementKind::Element);
+            assert_eq!(e.excerpt, "<rdf:li/>");
+        }
+        e => panic!("unexpected error {:?}", e),
+    }
+}
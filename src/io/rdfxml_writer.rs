@@ -0,0 +1,390 @@
+use constants;
+use error::{Error, Result};
+use graph::*;
+use namespaces::*;
+use std::collections::HashSet;
+use std::fmt::Display;
+use std::io::Write;
+use super::nesting::{object_count, NestingWriter};
+use super::rdfxml_parser::is_nc_name;
+
+const RDF_NS: &'static str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#";
+
+struct RDFXMLWriter<'a, 'g, W: 'a, G: 'g>
+    where W: Write,
+          G: Graph<'g>
+{
+    writer: &'a mut W,
+    namespaces: Namespaces,
+    xsd_string: Option<<G::LiteralPtr as LiteralPtr<'g>>::DatatypePtr>,
+    rdf_xml_literal: Option<<G::LiteralPtr as LiteralPtr<'g>>::DatatypePtr>,
+    /// the labels of the blank nodes that were written as subject
+    written: HashSet<String>,
+    graph: &'g G,
+}
+
+/// Write out a graph as RDF/XML.
+///
+/// The prefixes in `namespaces` are declared on the `rdf:RDF` element.
+/// Predicates that cannot be written with one of these prefixes get a
+/// generated prefix. A blank node that is the object of only one triple is
+/// nested in that triple's property element; other blank nodes are written
+/// with `rdf:nodeID`.
+///
+/// RDF/XML has no syntax for quoted triples, so a graph with quoted triples
+/// gives an error.
+pub fn write_rdfxml<'g, G: 'g, W>(namespaces: &Namespaces,
+                                  graph: &'g G,
+                                  writer: &mut W)
+                                  -> Result<()>
+    where G: Graph<'g>,
+          <G as Graph<'g>>::BlankNodePtr: Display,
+          W: Write
+{
+    let mut writer = RDFXMLWriter {
+        writer: writer,
+        namespaces: predicate_namespaces(namespaces, graph)?,
+        xsd_string: graph.find_datatype(constants::XSD_STRING),
+        rdf_xml_literal: graph.find_datatype(constants::RDF_XML_LITERAL),
+        written: HashSet::new(),
+        graph: graph,
+    };
+    writer.write_start()?;
+    writer.write_statements()?;
+    writer.writer.write_all(b"</rdf:RDF>\n")?;
+    Ok(())
+}
+
+/// Add a prefix for `rdf:` and for each namespace of a predicate that cannot
+/// be written with a prefix from `namespaces`.
+fn predicate_namespaces<'g, G: 'g>(namespaces: &Namespaces, graph: &'g G) -> Result<Namespaces>
+    where G: Graph<'g>
+{
+    let mut namespaces = namespaces.clone();
+    if namespaces.find_namespace(b"rdf") != Some(RDF_NS) {
+        if namespaces.find_namespace(b"rdf").is_some() {
+            return Err(Error::Custom("The prefix rdf: is used for another namespace."));
+        }
+        namespaces.set(b"rdf", RDF_NS);
+    }
+    let mut n = 0;
+    for triple in graph.iter() {
+        let predicate = triple.predicate();
+        let iri = predicate.as_str();
+        if find_qname(&namespaces, iri).is_some() {
+            continue;
+        }
+        let start = local_name_start(iri).ok_or_else(|| {
+                Error::String(format!("<{}> cannot be written as an XML name.", iri))
+            })?;
+        let mut prefix;
+        loop {
+            n += 1;
+            prefix = format!("ns{}", n);
+            if namespaces.find_namespace(prefix.as_bytes()).is_none() {
+                break;
+            }
+        }
+        namespaces.set(prefix.as_bytes(), &iri[..start]);
+    }
+    Ok(namespaces)
+}
+
+/// Find a prefix for `iri` such that the rest of `iri` is a valid XML name.
+fn find_qname<'a, 'b>(namespaces: &'a Namespaces, iri: &'b str) -> Option<(&'a [u8], &'b str)> {
+    namespaces.iter()
+        .find(|ns| iri.starts_with(ns.namespace().as_str()) &&
+                   is_nc_name(&iri[ns.namespace().len()..]))
+        .map(|ns| (ns.prefix(), &iri[ns.namespace().len()..]))
+}
+
+/// The start of the longest suffix of `iri` that is a valid XML name.
+fn local_name_start(iri: &str) -> Option<usize> {
+    let mut start = iri.len();
+    for (i, c) in iri.char_indices().rev() {
+        if !is_nc_name(&format!("a{}", c)) {
+            break;
+        }
+        start = i;
+    }
+    iri[start..]
+        .char_indices()
+        .find(|&(_, c)| c.is_alphabetic() || c == '_')
+        .map(|(i, _)| start + i)
+}
+
+/// Escape text for XML. In attribute values, quotes and white space are
+/// escaped too.
+pub fn escape(text: &str, attribute: bool, to: &mut Vec<u8>) {
+    for c in text.chars() {
+        match c {
+            '&' => to.extend_from_slice(b"&amp;"),
+            '<' => to.extend_from_slice(b"&lt;"),
+            '>' => to.extend_from_slice(b"&gt;"),
+            '\r' => to.extend_from_slice(b"&#xD;"),
+            '"' if attribute => to.extend_from_slice(b"&quot;"),
+            '\n' if attribute => to.extend_from_slice(b"&#xA;"),
+            '\t' if attribute => to.extend_from_slice(b"&#x9;"),
+            c => {
+                let mut bytes = [0; 4];
+                to.extend_from_slice(c.encode_utf8(&mut bytes).as_bytes());
+            }
+        }
+    }
+}
+
+impl<'a, 'g, W: 'a, G: 'g> RDFXMLWriter<'a, 'g, W, G>
+    where W: Write,
+          G: Graph<'g>,
+          <G as Graph<'g>>::BlankNodePtr: Display
+{
+    fn write_start(&mut self) -> Result<()> {
+        self.writer.write_all(b"<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<rdf:RDF")?;
+        let mut buffer = Vec::new();
+        for ns in self.namespaces.iter() {
+            buffer.extend_from_slice(b"\n\txmlns");
+            if !ns.prefix().is_empty() {
+                buffer.push(b':');
+                buffer.extend_from_slice(ns.prefix());
+            }
+            buffer.extend_from_slice(b"=\"");
+            escape(ns.namespace(), true, &mut buffer);
+            buffer.push(b'"');
+        }
+        self.writer.write_all(&buffer)?;
+        self.writer.write_all(b">\n")?;
+        Ok(())
+    }
+    fn write_description(&mut self,
+                         subject: &BlankNodeOrIRI<'g,
+                                                  G::BlankNodePtr,
+                                                  G::IRIPtr,
+                                                  G::QuotedTriplePtr>)
+                         -> Result<()> {
+        let mut buffer = Vec::new();
+        buffer.extend_from_slice(b"\t<rdf:Description");
+        match *subject {
+            BlankNodeOrIRI::BlankNode(ref blank_node, _) => {
+                self.written.insert(blank_node.to_string());
+                if object_count(self.graph, blank_node) > 0 {
+                    write!(&mut buffer, " rdf:nodeID=\"b{}\"", blank_node)?;
+                }
+            }
+            BlankNodeOrIRI::IRI(ref iri) => {
+                buffer.extend_from_slice(b" rdf:about=\"");
+                escape(iri.as_str(), true, &mut buffer);
+                buffer.push(b'"');
+            }
+            BlankNodeOrIRI::QuotedTriple(_) => return Err(Self::quoted_triple_error()),
+        }
+        buffer.extend_from_slice(b">\n");
+        self.writer.write_all(&buffer)?;
+        self.write_properties(subject, 2)?;
+        self.writer.write_all(b"\t</rdf:Description>\n")?;
+        Ok(())
+    }
+    fn write_properties(&mut self,
+                        subject: &BlankNodeOrIRI<'g,
+                                                 G::BlankNodePtr,
+                                                 G::IRIPtr,
+                                                 G::QuotedTriplePtr>,
+                        depth: usize)
+                        -> Result<()> {
+        for triple in self.graph.iter_s(subject) {
+            self.write_property(&triple.predicate(), triple.object(), depth)?;
+        }
+        Ok(())
+    }
+    fn write_property(&mut self,
+                      predicate: &G::IRIPtr,
+                      object: Resource<'g,
+                                       G::BlankNodePtr,
+                                       G::IRIPtr,
+                                       G::LiteralPtr,
+                                       G::QuotedTriplePtr>,
+                      depth: usize)
+                      -> Result<()> {
+        let mut name = Vec::new();
+        {
+            let (prefix, local) = find_qname(&self.namespaces, predicate.as_str())
+                .expect("Each predicate has a namespace.");
+            if !prefix.is_empty() {
+                name.extend_from_slice(prefix);
+                name.push(b':');
+            }
+            name.extend_from_slice(local.as_bytes());
+        }
+        let indent = vec![b'\t'; depth];
+        let mut buffer = indent.clone();
+        buffer.push(b'<');
+        buffer.extend_from_slice(&name);
+        let mut end_tag = true;
+        match object {
+            Resource::IRI(iri) => {
+                buffer.extend_from_slice(b" rdf:resource=\"");
+                escape(iri.as_str(), true, &mut buffer);
+                buffer.extend_from_slice(b"\"/>");
+                end_tag = false;
+            }
+            Resource::Literal(literal) => {
+                let datatype = Some(literal.datatype());
+                if let Some(lang) = literal.language() {
+                    buffer.extend_from_slice(b" xml:lang=\"");
+                    escape(lang, true, &mut buffer);
+                    buffer.extend_from_slice(b"\">");
+                    escape(literal.as_str(), false, &mut buffer);
+                } else if datatype == self.rdf_xml_literal {
+                    buffer.extend_from_slice(b" rdf:parseType=\"Literal\">");
+                    buffer.extend_from_slice(literal.as_str().as_bytes());
+                } else {
+                    if datatype != self.xsd_string {
+                        buffer.extend_from_slice(b" rdf:datatype=\"");
+                        escape(literal.datatype_str(), true, &mut buffer);
+                        buffer.push(b'"');
+                    }
+                    buffer.push(b'>');
+                    escape(literal.as_str(), false, &mut buffer);
+                }
+            }
+            Resource::BlankNode(blank_node, _) => {
+                let label = blank_node.to_string();
+                if self.written.contains(&label) || object_count(self.graph, &blank_node) > 1 {
+                    write!(&mut buffer, " rdf:nodeID=\"b{}\"/>", label)?;
+                    end_tag = false;
+                } else {
+                    // the blank node is only used here, nest its properties
+                    self.written.insert(label);
+                    let subject = blank_node.to_blank_node_or_iri();
+                    if self.graph.iter_s(&subject).next().is_none() {
+                        buffer.extend_from_slice(b" rdf:parseType=\"Resource\"/>");
+                        end_tag = false;
+                    } else {
+                        buffer.extend_from_slice(b" rdf:parseType=\"Resource\">\n");
+                        self.writer.write_all(&buffer)?;
+                        self.write_properties(&subject, depth + 1)?;
+                        buffer.clear();
+                        buffer.extend_from_slice(&indent);
+                    }
+                }
+            }
+            Resource::QuotedTriple(_) => return Err(Self::quoted_triple_error()),
+        }
+        if end_tag {
+            buffer.extend_from_slice(b"</");
+            buffer.extend_from_slice(&name);
+            buffer.push(b'>');
+        }
+        buffer.push(b'\n');
+        self.writer.write_all(&buffer)?;
+        Ok(())
+    }
+}
+
+impl<'a, 'g, W: 'a, G: 'g> NestingWriter<'g, G> for RDFXMLWriter<'a, 'g, W, G>
+    where W: Write,
+          G: Graph<'g>,
+          <G as Graph<'g>>::BlankNodePtr: Display
+{
+    fn graph(&self) -> &'g G {
+        self.graph
+    }
+    fn is_written(&self, blank_node: &G::BlankNodePtr) -> bool {
+        self.written.contains(&blank_node.to_string())
+    }
+    fn write_subject(&mut self,
+                     subject: &BlankNodeOrIRI<'g,
+                                              G::BlankNodePtr,
+                                              G::IRIPtr,
+                                              G::QuotedTriplePtr>)
+                     -> Result<()> {
+        self.write_description(subject)
+    }
+    fn quoted_triple_error() -> Error {
+        Error::Custom("Quoted triples cannot be written as RDF/XML.")
+    }
+}
+
+#[cfg(test)]
+const EXPECTED: &'static str = r##"<?xml version="1.0" encoding="utf-8"?>
+<rdf:RDF
+	xmlns:ex="http://a.example/"
+	xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#"
+	xmlns:ns1="http://a.example/other#">
+	<rdf:Description rdf:nodeID="b2">
+		<ex:p>&amp;</ex:p>
+	</rdf:Description>
+	<rdf:Description rdf:about="http://a.example/s">
+		<ex:e rdf:parseType="Resource"/>
+		<ns1:x rdf:nodeID="b2"/>
+		<ex:p xml:lang="en">a &lt; b</ex:p>
+		<ex:p rdf:datatype="http://www.w3.org/2001/XMLSchema#integer">1</ex:p>
+		<ex:p>plain</ex:p>
+		<ex:q rdf:parseType="Resource">
+			<ex:r rdf:resource="http://a.example/o"/>
+			<ex:t rdf:nodeID="b2"/>
+		</ex:q>
+		<rdf:type rdf:resource="http://a.example/C"/>
+	</rdf:Description>
+	<rdf:Description rdf:nodeID="b4">
+		<ex:next rdf:parseType="Resource">
+			<ex:next rdf:nodeID="b4"/>
+		</ex:next>
+	</rdf:Description>
+</rdf:RDF>
+"##;
+
+#[test]
+fn test_write_rdfxml() {
+    use graphs::tel;
+    use io::RDFXMLParser;
+    use test_support::{ntriples, turtle_graph};
+    let turtle = "@prefix ex: <http://a.example/> .
+ex:s a ex:C ;
+  ex:p \"a < b\"@en , \"1\"^^<http://www.w3.org/2001/XMLSchema#integer> , \"plain\" ;
+  ex:q [ ex:r ex:o ; ex:t _:shared ] ;
+  <http://a.example/other#x> _:shared ;
+  ex:e [] .
+_:shared ex:p \"&\" .
+_:c1 ex:next _:c2 .
+_:c2 ex:next _:c1 .
+";
+    let graph: tel::Graph64 = turtle_graph(turtle);
+    let mut ns = Namespaces::new();
+    ns.set(b"ex", "http://a.example/");
+    let mut out = Vec::new();
+    write_rdfxml(&ns, &graph, &mut out).unwrap();
+    let xml = String::from_utf8(out).unwrap();
+    assert_eq!(xml, EXPECTED);
+    let mut writer = tel::GraphCreator::with_capacity(0);
+    for step in RDFXMLParser::new(&xml, "http://a.example/", &mut writer).unwrap() {
+        step.unwrap();
+    }
+    let graph2: tel::Graph64 = writer.collect();
+    assert_eq!(ntriples(&graph2.sort_blank_nodes()),
+               ntriples(&graph.sort_blank_nodes()));
+}
+
+#[test]
+fn test_write_rdfxml_bad_predicate() {
+    use graphs::tel;
+    let mut writer = tel::GraphCreator::with_capacity(0);
+    let s = writer.create_iri(&"http://a.example/s");
+    let p = writer.create_iri(&"http://a.example/p/");
+    writer.add_iri_iri(&s, &p, &s);
+    let graph: tel::Graph64 = writer.collect();
+    assert!(write_rdfxml(&Namespaces::new(), &graph, &mut Vec::new()).is_err());
+}
+
+#[test]
+fn test_write_rdfxml_quoted_triple() {
+    use graphs::tel;
+    let mut writer = tel::GraphCreator::with_capacity(0);
+    let s = writer.create_iri(&"http://a.example/s");
+    let p = writer.create_iri(&"http://a.example/p");
+    let t = writer.create_quoted_triple(&WriterBlankNodeOrIRI::IRI(s.clone()),
+                                        &p,
+                                        &WriterResource::IRI(s.clone()));
+    writer.add(&WriterBlankNodeOrIRI::IRI(s), &p, &WriterResource::QuotedTriple(t));
+    let graph: tel::Graph64 = writer.collect();
+    assert!(write_rdfxml(&Namespaces::new(), &graph, &mut Vec::new()).is_err());
+}
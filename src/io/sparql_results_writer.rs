@@ -0,0 +1,570 @@
+use constants;
+use graph::*;
+use sparql::{LiteralRef, Value};
+use std::fmt::Display;
+use std::io::{Result, Write};
+use super::jsonld_writer::escape_string as escape_json;
+use super::ntriples_writer::{escape_iri, escape_literal_value};
+use super::rdfxml_writer::escape as escape_xml;
+
+const SPARQL_RESULTS_NS: &'static str = "http://www.w3.org/2005/sparql-results#";
+
+/// A value that can be written in query results.
+///
+/// This is implemented for `graph::Resource`, the values in the solutions of
+/// `query::BasicGraphPattern`, and for `sparql::Value`.
+pub trait ResultTerm: Sized {
+    /// The IRI, if this term is an IRI.
+    fn iri(&self) -> Option<&str>;
+    /// The literal, if this term is a literal.
+    fn literal(&self) -> Option<LiteralRef<'_>>;
+    /// The label, if this term is a blank node.
+    fn blank_node_label(&self) -> Option<String>;
+    /// The subject, predicate and object, if this term is a quoted triple.
+    fn quoted_triple(&self) -> Option<[Self; 3]>;
+}
+
+impl<'g, B, I, L, T> ResultTerm for Resource<'g, B, I, L, T>
+    where B: BlankNodePtr<'g> + Clone + Display,
+          I: IRIPtr<'g> + Clone,
+          L: LiteralPtr<'g>,
+          T: QuotedTriplePtr<'g, B, I, L> + Clone
+{
+    fn iri(&self) -> Option<&str> {
+        self.as_iri().map(|iri| iri.as_str())
+    }
+    fn literal(&self) -> Option<LiteralRef<'_>> {
+        self.as_literal().map(|literal| {
+            LiteralRef {
+                lexical: literal.as_str(),
+                datatype: literal.datatype_str(),
+                language: literal.language(),
+            }
+        })
+    }
+    fn blank_node_label(&self) -> Option<String> {
+        self.as_blank_node().map(|blank_node| blank_node.to_string())
+    }
+    fn quoted_triple(&self) -> Option<[Self; 3]> {
+        self.as_quoted_triple().map(|triple| {
+            [triple.subject().to_resource(), Resource::IRI(triple.predicate()), triple.object()]
+        })
+    }
+}
+
+impl<'g, G> ResultTerm for Value<'g, G>
+    where G: Graph<'g>,
+          <G as Graph<'g>>::BlankNodePtr: Display
+{
+    fn iri(&self) -> Option<&str> {
+        Value::iri(self)
+    }
+    fn literal(&self) -> Option<LiteralRef<'_>> {
+        Value::literal(self)
+    }
+    fn blank_node_label(&self) -> Option<String> {
+        match *self {
+            Value::Term(ref term) => term.blank_node_label(),
+            // blank nodes that were created by the query get a prefix so
+            // that they differ from the blank nodes in the graph
+            Value::BlankNode(n) => Some(format!("c{}", n)),
+            _ => None,
+        }
+    }
+    fn quoted_triple(&self) -> Option<[Self; 3]> {
+        match *self {
+            Value::Term(ref term) => {
+                term.quoted_triple()
+                    .map(|[s, p, o]| [Value::Term(s), Value::Term(p), Value::Term(o)])
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Write solutions in the SPARQL 1.1 Query Results JSON Format.
+///
+/// `variables` are the names of the variables without `?`. `solutions`
+/// yields a row for each solution with a value for each variable. Unbound
+/// variables are `None`. Quoted triples are written as in SPARQL-star.
+///
+/// ```
+/// use rome::graph::GraphWriter;
+/// use rome::graphs::tel;
+/// use rome::io::write_sparql_json;
+/// use rome::sparql::{Query, QueryResult};
+///
+/// let graph: tel::Graph64 = tel::GraphCreator::with_capacity(0).collect();
+/// let query = Query::parse("SELECT (1 + 1 AS ?two) WHERE {}").unwrap();
+/// if let QueryResult::Solutions(solutions) = query.evaluate(&graph) {
+///     let mut json = Vec::new();
+///     write_sparql_json(solutions.variables(), solutions.rows().iter(), &mut json).unwrap();
+///     assert!(String::from_utf8(json).unwrap().contains("\"value\": \"2\""));
+/// }
+/// ```
+pub fn write_sparql_json<S, I, R, T, W>(variables: &[S],
+                                        solutions: I,
+                                        writer: &mut W)
+                                        -> Result<()>
+    where S: AsRef<str>,
+          I: Iterator<Item = R>,
+          R: AsRef<[Option<T>]>,
+          T: ResultTerm,
+          W: Write
+{
+    let mut buffer = Vec::new();
+    buffer.extend_from_slice(b"{\n\t\"head\": {\"vars\": [");
+    for (i, variable) in variables.iter().enumerate() {
+        if i > 0 {
+            buffer.extend_from_slice(b", ");
+        }
+        json_string(variable.as_ref(), &mut buffer);
+    }
+    buffer.extend_from_slice(b"]},\n\t\"results\": {\"bindings\": [");
+    writer.write_all(&buffer)?;
+    let mut empty = true;
+    for solution in solutions {
+        buffer.clear();
+        buffer.extend_from_slice(if empty { b"\n\t\t{" } else { b",\n\t\t{" });
+        let mut first = true;
+        for (variable, value) in variables.iter().zip(solution.as_ref()) {
+            if let Some(ref value) = *value {
+                if !first {
+                    buffer.extend_from_slice(b", ");
+                }
+                json_string(variable.as_ref(), &mut buffer);
+                buffer.extend_from_slice(b": ");
+                json_term(value, &mut buffer);
+                first = false;
+            }
+        }
+        buffer.push(b'}');
+        writer.write_all(&buffer)?;
+        empty = false;
+    }
+    if !empty {
+        writer.write_all(b"\n\t")?;
+    }
+    writer.write_all(b"]}\n}\n")
+}
+
+/// Write the result of an `ASK` query in the SPARQL 1.1 Query Results JSON
+/// Format.
+pub fn write_sparql_json_boolean<W>(value: bool, writer: &mut W) -> Result<()>
+    where W: Write
+{
+    write!(writer, "{{\n\t\"head\": {{}},\n\t\"boolean\": {}\n}}\n", value)
+}
+
+/// Write solutions in the SPARQL Query Results XML Format.
+///
+/// The arguments are the same as for `write_sparql_json`.
+pub fn write_sparql_xml<S, I, R, T, W>(variables: &[S],
+                                       solutions: I,
+                                       writer: &mut W)
+                                       -> Result<()>
+    where S: AsRef<str>,
+          I: Iterator<Item = R>,
+          R: AsRef<[Option<T>]>,
+          T: ResultTerm,
+          W: Write
+{
+    let mut buffer = Vec::new();
+    write_xml_start(&mut buffer);
+    buffer.extend_from_slice(b"\t<head>\n");
+    for variable in variables {
+        buffer.extend_from_slice(b"\t\t<variable name=\"");
+        escape_xml(variable.as_ref(), true, &mut buffer);
+        buffer.extend_from_slice(b"\"/>\n");
+    }
+    buffer.extend_from_slice(b"\t</head>\n\t<results>\n");
+    writer.write_all(&buffer)?;
+    for solution in solutions {
+        buffer.clear();
+        buffer.extend_from_slice(b"\t\t<result>\n");
+        for (variable, value) in variables.iter().zip(solution.as_ref()) {
+            if let Some(ref value) = *value {
+                buffer.extend_from_slice(b"\t\t\t<binding name=\"");
+                escape_xml(variable.as_ref(), true, &mut buffer);
+                buffer.extend_from_slice(b"\">");
+                xml_term(value, &mut buffer);
+                buffer.extend_from_slice(b"</binding>\n");
+            }
+        }
+        buffer.extend_from_slice(b"\t\t</result>\n");
+        writer.write_all(&buffer)?;
+    }
+    writer.write_all(b"\t</results>\n</sparql>\n")
+}
+
+/// Write the result of an `ASK` query in the SPARQL Query Results XML
+/// Format.
+pub fn write_sparql_xml_boolean<W>(value: bool, writer: &mut W) -> Result<()>
+    where W: Write
+{
+    let mut buffer = Vec::new();
+    write_xml_start(&mut buffer);
+    write!(&mut buffer, "\t<head/>\n\t<boolean>{}</boolean>\n</sparql>\n", value)?;
+    writer.write_all(&buffer)
+}
+
+/// Write solutions in the SPARQL 1.1 Query Results CSV Format.
+///
+/// CSV only has the lexical forms of values: IRIs are written without `<>`
+/// and literals without datatype or language. The arguments are the same as
+/// for `write_sparql_json`.
+pub fn write_sparql_csv<S, I, R, T, W>(variables: &[S],
+                                       solutions: I,
+                                       writer: &mut W)
+                                       -> Result<()>
+    where S: AsRef<str>,
+          I: Iterator<Item = R>,
+          R: AsRef<[Option<T>]>,
+          T: ResultTerm,
+          W: Write
+{
+    let mut buffer = Vec::new();
+    let mut field = Vec::new();
+    for (i, variable) in variables.iter().enumerate() {
+        if i > 0 {
+            buffer.push(b',');
+        }
+        csv_field(variable.as_ref().as_bytes(), &mut buffer);
+    }
+    buffer.extend_from_slice(b"\r\n");
+    writer.write_all(&buffer)?;
+    for solution in solutions {
+        buffer.clear();
+        for (i, value) in solution.as_ref().iter().take(variables.len()).enumerate() {
+            if i > 0 {
+                buffer.push(b',');
+            }
+            if let Some(ref value) = *value {
+                field.clear();
+                if let Some(iri) = value.iri() {
+                    field.extend_from_slice(iri.as_bytes());
+                } else if let Some(literal) = value.literal() {
+                    field.extend_from_slice(literal.lexical.as_bytes());
+                } else {
+                    ntriples_term(value, &mut field);
+                }
+                csv_field(&field, &mut buffer);
+            }
+        }
+        buffer.extend_from_slice(b"\r\n");
+        writer.write_all(&buffer)?;
+    }
+    Ok(())
+}
+
+/// Write solutions in the SPARQL 1.1 Query Results TSV Format.
+///
+/// Values are written in N-Triples syntax. The arguments are the same as for
+/// `write_sparql_json`.
+pub fn write_sparql_tsv<S, I, R, T, W>(variables: &[S],
+                                       solutions: I,
+                                       writer: &mut W)
+                                       -> Result<()>
+    where S: AsRef<str>,
+          I: Iterator<Item = R>,
+          R: AsRef<[Option<T>]>,
+          T: ResultTerm,
+          W: Write
+{
+    let mut buffer = Vec::new();
+    for (i, variable) in variables.iter().enumerate() {
+        if i > 0 {
+            buffer.push(b'\t');
+        }
+        buffer.push(b'?');
+        buffer.extend_from_slice(variable.as_ref().as_bytes());
+    }
+    buffer.push(b'\n');
+    writer.write_all(&buffer)?;
+    let mut field = Vec::new();
+    for solution in solutions {
+        buffer.clear();
+        for (i, value) in solution.as_ref().iter().take(variables.len()).enumerate() {
+            if i > 0 {
+                buffer.push(b'\t');
+            }
+            if let Some(ref value) = *value {
+                field.clear();
+                ntriples_term(value, &mut field);
+                // N-Triples does not escape tabs in literals
+                for b in &field {
+                    if *b == b'\t' {
+                        buffer.extend_from_slice(b"\\t");
+                    } else {
+                        buffer.push(*b);
+                    }
+                }
+            }
+        }
+        buffer.push(b'\n');
+        writer.write_all(&buffer)?;
+    }
+    Ok(())
+}
+
+fn is_simple_literal(literal: &LiteralRef) -> bool {
+    literal.language.is_none() && literal.datatype == constants::XSD_STRING
+}
+
+fn json_string(value: &str, to: &mut Vec<u8>) {
+    to.push(b'"');
+    escape_json(value, to);
+    to.push(b'"');
+}
+
+fn json_term<T: ResultTerm>(term: &T, to: &mut Vec<u8>) {
+    if let Some(iri) = term.iri() {
+        to.extend_from_slice(b"{\"type\": \"uri\", \"value\": ");
+        json_string(iri, to);
+    } else if let Some(literal) = term.literal() {
+        to.extend_from_slice(b"{\"type\": \"literal\", \"value\": ");
+        json_string(literal.lexical, to);
+        if let Some(language) = literal.language {
+            to.extend_from_slice(b", \"xml:lang\": ");
+            json_string(language, to);
+        } else if !is_simple_literal(&literal) {
+            to.extend_from_slice(b", \"datatype\": ");
+            json_string(literal.datatype, to);
+        }
+    } else if let Some(label) = term.blank_node_label() {
+        to.extend_from_slice(b"{\"type\": \"bnode\", \"value\": ");
+        json_string(&label, to);
+    } else if let Some([s, p, o]) = term.quoted_triple() {
+        to.extend_from_slice(b"{\"type\": \"triple\", \"value\": {\"subject\": ");
+        json_term(&s, to);
+        to.extend_from_slice(b", \"predicate\": ");
+        json_term(&p, to);
+        to.extend_from_slice(b", \"object\": ");
+        json_term(&o, to);
+        to.push(b'}');
+    }
+    to.push(b'}');
+}
+
+fn write_xml_start(to: &mut Vec<u8>) {
+    to.extend_from_slice(b"<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<sparql xmlns=\"");
+    to.extend_from_slice(SPARQL_RESULTS_NS.as_bytes());
+    to.extend_from_slice(b"\">\n");
+}
+
+fn xml_term<T: ResultTerm>(term: &T, to: &mut Vec<u8>) {
+    if let Some(iri) = term.iri() {
+        to.extend_from_slice(b"<uri>");
+        escape_xml(iri, false, to);
+        to.extend_from_slice(b"</uri>");
+    } else if let Some(literal) = term.literal() {
+        to.extend_from_slice(b"<literal");
+        if let Some(language) = literal.language {
+            to.extend_from_slice(b" xml:lang=\"");
+            escape_xml(language, true, to);
+            to.push(b'"');
+        } else if !is_simple_literal(&literal) {
+            to.extend_from_slice(b" datatype=\"");
+            escape_xml(literal.datatype, true, to);
+            to.push(b'"');
+        }
+        to.push(b'>');
+        escape_xml(literal.lexical, false, to);
+        to.extend_from_slice(b"</literal>");
+    } else if let Some(label) = term.blank_node_label() {
+        to.extend_from_slice(b"<bnode>");
+        escape_xml(&label, false, to);
+        to.extend_from_slice(b"</bnode>");
+    } else if let Some([s, p, o]) = term.quoted_triple() {
+        to.extend_from_slice(b"<triple><subject>");
+        xml_term(&s, to);
+        to.extend_from_slice(b"</subject><predicate>");
+        xml_term(&p, to);
+        to.extend_from_slice(b"</predicate><object>");
+        xml_term(&o, to);
+        to.extend_from_slice(b"</object></triple>");
+    }
+}
+
+fn ntriples_term<T: ResultTerm>(term: &T, to: &mut Vec<u8>) {
+    if let Some(iri) = term.iri() {
+        to.push(b'<');
+        escape_iri(iri, to);
+        to.push(b'>');
+    } else if let Some(literal) = term.literal() {
+        to.push(b'"');
+        escape_literal_value(literal.lexical, to);
+        to.push(b'"');
+        if let Some(language) = literal.language {
+            to.push(b'@');
+            to.extend_from_slice(language.as_bytes());
+        } else if !is_simple_literal(&literal) {
+            to.extend_from_slice(b"^^<");
+            escape_iri(literal.datatype, to);
+            to.push(b'>');
+        }
+    } else if let Some(label) = term.blank_node_label() {
+        to.extend_from_slice(b"_:");
+        to.extend_from_slice(label.as_bytes());
+    } else if let Some([s, p, o]) = term.quoted_triple() {
+        to.extend_from_slice(b"<< ");
+        ntriples_term(&s, to);
+        to.push(b' ');
+        ntriples_term(&p, to);
+        to.push(b' ');
+        ntriples_term(&o, to);
+        to.extend_from_slice(b" >>");
+    }
+}
+
+/// Quote a field if it contains a quote, a comma or a line break.
+fn csv_field(field: &[u8], to: &mut Vec<u8>) {
+    if field.iter().any(|b| b"\",\r\n".contains(b)) {
+        to.push(b'"');
+        for b in field {
+            if *b == b'"' {
+                to.push(b'"');
+            }
+            to.push(*b);
+        }
+        to.push(b'"');
+    } else {
+        to.extend_from_slice(field);
+    }
+}
+
+#[cfg(test)]
+fn write_results<'g, G>(graph: &'g G, query: &str) -> [String; 4]
+    where G: Graph<'g>,
+          <G as Graph<'g>>::BlankNodePtr: Display
+{
+    use sparql::{Query, QueryResult};
+    let solutions = match Query::parse(query).unwrap().evaluate(graph) {
+        QueryResult::Solutions(solutions) => solutions,
+        _ => panic!("expected solutions"),
+    };
+    let variables = solutions.variables();
+    let mut out = [Vec::new(), Vec::new(), Vec::new(), Vec::new()];
+    write_sparql_json(variables, solutions.rows().iter(), &mut out[0]).unwrap();
+    write_sparql_xml(variables, solutions.rows().iter(), &mut out[1]).unwrap();
+    write_sparql_csv(variables, solutions.rows().iter(), &mut out[2]).unwrap();
+    write_sparql_tsv(variables, solutions.rows().iter(), &mut out[3]).unwrap();
+    let mut strings = [String::new(), String::new(), String::new(), String::new()];
+    for (s, o) in strings.iter_mut().zip(out.iter()) {
+        *s = String::from_utf8(o.clone()).unwrap();
+    }
+    strings
+}
+
+#[test]
+fn test_write_sparql_results() {
+    use graphs::tel::Graph64;
+    use test_support::turtle_graph;
+    let graph: Graph64 = turtle_graph("@prefix : <http://a.example/> .
+:a :name \"x, \\\"y\\\"\\tz\" ; :age 30 ; :knows _:b .
+_:b :name \"B & <b>\"@en .
+");
+    let [json, xml, csv, tsv] = write_results(&graph,
+                                              "PREFIX : <http://a.example/>
+        SELECT ?p ?n ?age WHERE { ?p :name ?n OPTIONAL { ?p :age ?age } } ORDER BY ?age");
+    let label = match json.find("\"bnode\", \"value\": \"") {
+        Some(start) => {
+            let start = start + 19;
+            json[start..start + json[start..].find('"').unwrap()].to_string()
+        }
+        None => panic!("no blank node in {}", json),
+    };
+    assert_eq!(json,
+               format!(r#"{{
+	"head": {{"vars": ["p", "n", "age"]}},
+	"results": {{"bindings": [
+		{{"p": {{"type": "bnode", "value": "{}"}}, "n": {{"type": "literal", "value": "B & <b>", "xml:lang": "en"}}}},
+		{{"p": {{"type": "uri", "value": "http://a.example/a"}}, "n": {{"type": "literal", "value": "x, \"y\"\tz"}}, "age": {{"type": "literal", "value": "30", "datatype": "http://www.w3.org/2001/XMLSchema#integer"}}}}
+	]}}
+}}
+"#,
+                       label));
+    assert_eq!(xml,
+               format!(r#"<?xml version="1.0" encoding="utf-8"?>
+<sparql xmlns="http://www.w3.org/2005/sparql-results#">
+	<head>
+		<variable name="p"/>
+		<variable name="n"/>
+		<variable name="age"/>
+	</head>
+	<results>
+		<result>
+			<binding name="p"><bnode>{}</bnode></binding>
+			<binding name="n"><literal xml:lang="en">B &amp; &lt;b&gt;</literal></binding>
+		</result>
+		<result>
+			<binding name="p"><uri>http://a.example/a</uri></binding>
+			<binding name="n"><literal>x, "y"	z</literal></binding>
+			<binding name="age"><literal datatype="http://www.w3.org/2001/XMLSchema#integer">30</literal></binding>
+		</result>
+	</results>
+</sparql>
+"#,
+                       label));
+    assert_eq!(csv,
+               format!("p,n,age\r\n_:{},B & <b>,\r\nhttp://a.example/a,\"x, \"\"y\"\"\tz\",30\r\n",
+                       label));
+    assert_eq!(tsv,
+               format!("?p\t?n\t?age\n_:{}\t\"B & <b>\"@en\t\n<http://a.example/a>\t\
+                        \"x, \\\"y\\\"\\tz\"\t\
+                        \"30\"^^<http://www.w3.org/2001/XMLSchema#integer>\n",
+                       label));
+    ::serde_json::from_str::<::serde_json::Value>(&json).unwrap();
+}
+
+#[test]
+fn test_write_sparql_results_empty() {
+    use graphs::tel::Graph64;
+    use test_support::turtle_graph;
+    let graph: Graph64 = turtle_graph("");
+    let [json, _, csv, tsv] = write_results(&graph, "SELECT ?x WHERE { ?x ?p ?o }");
+    assert_eq!(json,
+               "{\n\t\"head\": {\"vars\": [\"x\"]},\n\t\"results\": {\"bindings\": []}\n}\n");
+    assert_eq!(csv, "x\r\n");
+    assert_eq!(tsv, "?x\n");
+}
+
+#[test]
+fn test_write_sparql_boolean() {
+    let mut json = Vec::new();
+    write_sparql_json_boolean(true, &mut json).unwrap();
+    assert_eq!(String::from_utf8(json).unwrap(),
+               "{\n\t\"head\": {},\n\t\"boolean\": true\n}\n");
+    let mut xml = Vec::new();
+    write_sparql_xml_boolean(false, &mut xml).unwrap();
+    assert_eq!(String::from_utf8(xml).unwrap(),
+               "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n\
+                <sparql xmlns=\"http://www.w3.org/2005/sparql-results#\">\n\
+                \t<head/>\n\t<boolean>false</boolean>\n</sparql>\n");
+}
+
+#[test]
+fn test_write_basic_graph_pattern_solutions() {
+    use graphs::tel::Graph64;
+    use test_support::turtle_graph;
+    use query::{BasicGraphPattern, PatternTerm, TriplePattern};
+    let graph: Graph64 = turtle_graph("<http://a.example/s> <http://a.example/p> \
+                                       <http://a.example/o> .");
+    let mut bgp = BasicGraphPattern::new();
+    let s = bgp.variable("s");
+    let o = bgp.variable("o");
+    let p = graph.find_iri("http://a.example/p").unwrap();
+    bgp.add(TriplePattern {
+        subject: PatternTerm::Variable(s),
+        predicate: PatternTerm::Constant(p),
+        object: PatternTerm::Variable(o),
+    });
+    let solutions = bgp.evaluate(&graph);
+    let mut tsv = Vec::new();
+    write_sparql_tsv(bgp.variables(),
+                     solutions.iter().map(|solution| solution.values()),
+                     &mut tsv)
+        .unwrap();
+    assert_eq!(String::from_utf8(tsv).unwrap(),
+               "?s\t?o\n<http://a.example/s>\t<http://a.example/o>\n");
+}
@@ -97,6 +97,90 @@ named!(sparql_prefix<&str,Statement>, do_parse!(
     (Statement::Prefix(pname_ns, iri_ref))
 ));
 
+/// TriG [2g] `block ::= triplesOrGraph | wrappedGraph | triples2 | "GRAPH" labelOrSubject wrappedGraph`
+///
+/// Directives and default graph triples are parsed as Turtle statements.
+named!(pub trig_statement<&str,TrigStatement>, alt!(trig_graph
+        | map!(statement, TrigStatement::Statement)));
+
+/// `"GRAPH"? labelOrSubject? wrappedGraph`; the label is required after "GRAPH"
+fn trig_graph(str: &str) -> IResult<&str, TrigStatement> {
+    let mut left = str;
+    let mut label = None;
+    let keyword = match tag_no_case_s!(left, "GRAPH") {
+        // "GRAPH:a" and "GRAPHa:b" are prefixed names
+        Done(l, _) => !l.starts_with(|c| is_pn_chars(c) || c == '.' || c == ':'),
+        _ => false,
+    };
+    if keyword {
+        if let Done(l, _) = tws(&left[5..]) {
+            left = l;
+        }
+        match label_or_subject(left) {
+            Done(l, g) => {
+                left = l;
+                label = Some(g);
+            }
+            IResult::Error(e) => return IResult::Error(e),
+            IResult::Incomplete(i) => return IResult::Incomplete(i),
+        }
+    } else if let Done(l, g) = label_or_subject(left) {
+        left = l;
+        label = Some(g);
+    }
+    if let Done(l, _) = tws(left) {
+        left = l;
+    }
+    match wrapped_graph(left) {
+        Done(l, triples) => Done(l, TrigStatement::Graph(label, triples)),
+        IResult::Error(e) => IResult::Error(e),
+        IResult::Incomplete(i) => IResult::Incomplete(i),
+    }
+}
+
+/// TriG [7g] `labelOrSubject ::= iri | BlankNode`
+named!(label_or_subject<&str,GraphLabel>, alt!(
+    map!(iri, GraphLabel::IRI) | map!(blank_node, GraphLabel::BlankNode)));
+
+/// TriG [5g] `wrappedGraph ::= '{' triplesBlock? '}'`
+/// TriG [6g] `triplesBlock ::= triples ('.' triplesBlock?)?`
+fn wrapped_graph(str: &str) -> IResult<&str, Vec<Triples>> {
+    if str.is_empty() {
+        return IResult::Incomplete(Needed::Size(1));
+    }
+    let mut left = match str.strip_prefix('{') {
+        Some(left) => left,
+        None => return IResult::Error(ErrorKind::Custom(0)),
+    };
+    let mut triples_block = Vec::new();
+    loop {
+        if let Done(l, _) = tws(left) {
+            left = l;
+        }
+        if let Some(l) = left.strip_prefix('}') {
+            return Done(l, triples_block);
+        }
+        match triples(left) {
+            Done(l, t) => {
+                triples_block.push(t);
+                left = l;
+            }
+            IResult::Error(e) => return IResult::Error(e),
+            IResult::Incomplete(i) => return IResult::Incomplete(i),
+        }
+        if let Done(l, _) = tws(left) {
+            left = l;
+        }
+        if let Some(l) = left.strip_prefix('.') {
+            left = l;
+        } else if left.is_empty() {
+            return IResult::Incomplete(Needed::Size(1));
+        } else if !left.starts_with('}') {
+            return IResult::Error(ErrorKind::Custom(0));
+        }
+    }
+}
+
 /// [6] `triples ::= subject predicateObjectList | blankNodePropertyList predicateObjectList?`
 named!(triples<&str,Triples>, alt!(triples_subject | triples_blank));
 
@@ -192,8 +276,9 @@ named!(a<&str,IRI>, value!(
     tag_s!("a")
 ));
 
-/// [10] `subject ::= iri | BlankNode | collection`
+/// [10] `subject ::= iri | BlankNode | collection | quotedTriple`
 named!(subject<&str,Subject>, alt!(
+    map!(quoted_triple, Subject::QuotedTriple) |
     map!(iri, Subject::IRI) |
     map!(blank_node, Subject::BlankNode) |
     map!(collection, Subject::Collection)
@@ -201,8 +286,10 @@ named!(subject<&str,Subject>, alt!(
 
 /// [11] `predicate ::= iri`
 
-/// [12] `object ::= iri | BlankNode | collection | blankNodePropertyList | literal`
+/// [12] `object ::= iri | BlankNode | collection | blankNodePropertyList | literal |
+/// quotedTriple`
 named!(object<&str,Object>, alt!(
+    map!(quoted_triple, Object::QuotedTriple) |
     map!(literal, Object::Literal) |
     map!(iri, Object::IRI) |
     map!(blank_node, Object::BlankNode) |
@@ -229,6 +316,35 @@ named!(collection<&str,Vec<Object> >, do_parse!(
     tag_s!(")") >> (objects)
 ));
 
+/// Turtle-star [27t] `quotedTriple ::= '<<' qtSubject verb qtObject '>>'`
+named!(quoted_triple<&str,Box<QuotedTriple> >, do_parse!(
+    tag_s!("<<") >> tws >>
+    subject: qt_subject >> tws >>
+    predicate: verb >> tws >>
+    object: qt_object >> tws >>
+    tag_s!(">>") >>
+    (Box::new(QuotedTriple{
+        subject: subject,
+        predicate: predicate,
+        object: object
+    }))
+));
+
+/// Turtle-star [28t] `qtSubject ::= iri | BlankNode | quotedTriple`
+named!(qt_subject<&str,Subject>, alt!(
+    map!(quoted_triple, Subject::QuotedTriple) |
+    map!(iri, Subject::IRI) |
+    map!(blank_node, Subject::BlankNode)
+));
+
+/// Turtle-star [29t] `qtObject ::= iri | BlankNode | literal | quotedTriple`
+named!(qt_object<&str,Object>, alt!(
+    map!(quoted_triple, Object::QuotedTriple) |
+    map!(literal, Object::Literal) |
+    map!(iri, Object::IRI) |
+    map!(blank_node, Object::BlankNode)
+));
+
 /// [16] `NumericLiteral ::= INTEGER | DECIMAL | DOUBLE`
 
 /// [128s]  `RDFLiteral ::= String (LANGTAG | '^^' iri)?`
@@ -482,12 +598,12 @@ fn is_pn_chars_base(c: char) -> bool {
 }
 
 /// [164s] `PN_CHARS_U ::= PN_CHARS_BASE | '_'`
-fn is_pn_chars_u(c: char) -> bool {
+pub fn is_pn_chars_u(c: char) -> bool {
     c == '_' || is_pn_chars_base(c)
 }
 
 /// [166s] `PN_CHARS ::= PN_CHARS_U | '-' | [0-9] | #x00B7 | [#x0300-#x036F] | [#x203F-#x2040]`
-fn is_pn_chars(c: char) -> bool {
+pub fn is_pn_chars(c: char) -> bool {
     is_pn_chars_u(c) || c == '-' || is_digit(c) || c == 0xB7 as char ||
     in_range(c, 0x0300, 0x036F) || in_range(c, 0x203F, 0x2040)
 }
@@ -575,6 +691,202 @@ fn in_range(c: char, lower: u32, upper: u32) -> bool {
     c as u32 >= lower && c as u32 <= upper
 }
 
+/// The offset in `str` of the part of a Turtle statement that cannot be
+/// parsed.
+///
+/// The errors from nom do not say where the failing parser stopped and the
+/// list parsers stop at the first item that fails. So when a statement does
+/// not parse, its parts are parsed again one by one to find the first part
+/// that fails.
+pub fn statement_error_offset(str: &str) -> usize {
+    error_offset(str, locate_statement(str))
+}
+
+/// The offset in `str` of the part of a TriG statement that cannot be
+/// parsed. See `statement_error_offset`.
+pub fn trig_statement_error_offset(str: &str) -> usize {
+    error_offset(str, locate_trig_statement(str))
+}
+
+/// The rest of the input after a part that parses or the input at the part
+/// that fails.
+type Located<'a> = Result<&'a str, &'a str>;
+
+fn error_offset(str: &str, located: Located<'_>) -> usize {
+    match located {
+        Err(at) => str.len() - at.len(),
+        Ok(_) => 0,
+    }
+}
+
+fn part<'a, O>(str: &'a str, parser: fn(&'a str) -> IResult<&'a str, O>) -> Located<'a> {
+    match parser(str) {
+        Done(left, _) => Ok(left),
+        _ => Err(str),
+    }
+}
+
+fn token<'a>(str: &'a str, token: &str) -> Located<'a> {
+    str.strip_prefix(token).ok_or(str)
+}
+
+fn skip_ws(str: &str) -> &str {
+    match tws(str) {
+        Done(left, _) => left,
+        _ => str,
+    }
+}
+
+fn locate_statement(str: &str) -> Located<'_> {
+    if let Ok(left) = token(str, "@prefix") {
+        let left = part(skip_ws(left), pname_ns)?;
+        let left = part(skip_ws(left), iri_ref)?;
+        return token(skip_ws(left), ".");
+    }
+    if let Ok(left) = token(str, "@base") {
+        let left = part(skip_ws(left), iri_ref)?;
+        return token(skip_ws(left), ".");
+    }
+    if let Done(left, _) = tag_no_case_s!(str, "PREFIX") {
+        let left = part(skip_ws(left), pname_ns)?;
+        return part(skip_ws(left), iri_ref);
+    }
+    if let Done(left, _) = tag_no_case_s!(str, "BASE") {
+        return part(skip_ws(left), iri_ref);
+    }
+    let left = locate_triples(str)?;
+    token(skip_ws(left), ".")
+}
+
+fn locate_trig_statement(str: &str) -> Located<'_> {
+    let graph = match tag_no_case_s!(str, "GRAPH") {
+        Done(l, _) if !l.starts_with(|c| is_pn_chars(c) || c == '.' || c == ':') => {
+            Some(part(skip_ws(l), label_or_subject)?)
+        }
+        _ => {
+            match label_or_subject(str) {
+                Done(l, _) if skip_ws(l).starts_with('{') => Some(l),
+                _ if str.starts_with('{') => Some(str),
+                _ => None,
+            }
+        }
+    };
+    match graph {
+        Some(left) => locate_wrapped_graph(skip_ws(left)),
+        None => locate_statement(str),
+    }
+}
+
+fn locate_wrapped_graph(str: &str) -> Located<'_> {
+    let mut left = token(str, "{")?;
+    loop {
+        left = skip_ws(left);
+        if let Ok(left) = token(left, "}") {
+            return Ok(left);
+        }
+        left = skip_ws(locate_triples(left)?);
+        if let Ok(l) = token(left, ".") {
+            left = l;
+        } else if !left.starts_with('}') {
+            return Err(left);
+        }
+    }
+}
+
+fn locate_triples(str: &str) -> Located<'_> {
+    if str.starts_with('[') && part(str, anon).is_err() {
+        let left = locate_blank_node_property_list(str)?;
+        let l = skip_ws(left);
+        if part(l, verb).is_ok() {
+            return locate_predicate_object_list(l);
+        }
+        return Ok(left);
+    }
+    let left = match part(str, subject) {
+        Ok(left) => left,
+        Err(_) if str.starts_with("<<") => locate_quoted_triple(str)?,
+        Err(_) if str.starts_with('(') => locate_collection(str)?,
+        Err(at) => return Err(at),
+    };
+    locate_predicate_object_list(skip_ws(left))
+}
+
+fn locate_predicate_object_list(str: &str) -> Located<'_> {
+    let mut left = str;
+    loop {
+        left = part(left, verb)?;
+        left = locate_object_list(skip_ws(left))?;
+        let mut l = skip_ws(left);
+        if !l.starts_with(';') {
+            return Ok(left);
+        }
+        while let Ok(after) = token(l, ";") {
+            left = after;
+            l = skip_ws(after);
+        }
+        if part(l, verb).is_err() {
+            return Ok(left);
+        }
+        left = l;
+    }
+}
+
+fn locate_object_list(str: &str) -> Located<'_> {
+    let mut left = locate_object(str)?;
+    loop {
+        match token(skip_ws(left), ",") {
+            Ok(l) => left = locate_object(skip_ws(l))?,
+            Err(_) => return Ok(left),
+        }
+    }
+}
+
+fn locate_object(str: &str) -> Located<'_> {
+    match part(str, object) {
+        Ok(left) => Ok(left),
+        Err(_) if str.starts_with("<<") => locate_quoted_triple(str),
+        Err(_) if str.starts_with('(') => locate_collection(str),
+        Err(_) if str.starts_with('[') => locate_blank_node_property_list(str),
+        Err(at) => Err(at),
+    }
+}
+
+fn locate_blank_node_property_list(str: &str) -> Located<'_> {
+    let left = token(str, "[")?;
+    let left = locate_predicate_object_list(skip_ws(left))?;
+    token(skip_ws(left), "]")
+}
+
+fn locate_collection(str: &str) -> Located<'_> {
+    let mut left = skip_ws(token(str, "(")?);
+    loop {
+        if let Ok(left) = token(left, ")") {
+            return Ok(left);
+        }
+        if left.is_empty() {
+            return Err(left);
+        }
+        left = skip_ws(locate_object(left)?);
+    }
+}
+
+fn locate_quoted_triple(str: &str) -> Located<'_> {
+    let left = skip_ws(token(str, "<<")?);
+    let left = if left.starts_with("<<") {
+        locate_quoted_triple(left)?
+    } else {
+        part(left, qt_subject)?
+    };
+    let left = part(skip_ws(left), verb)?;
+    let left = skip_ws(left);
+    let left = if left.starts_with("<<") {
+        locate_quoted_triple(left)?
+    } else {
+        part(left, qt_object)?
+    };
+    token(skip_ws(left), ">>")
+}
+
 #[test]
 fn test_comment() {
     assert_eq!(comment("#\r\na"), Done(&"\na"[..],()));
@@ -820,3 +1132,29 @@ fn test_pn_local() {
     // dot does not belong in the pn_local
     assert_eq!(pn_local("c. "), Done(&". "[..], "c"));
 }
+
+#[test]
+fn test_trig_statement() {
+    let r = trig_statement("GRAPH <urn:g> { <urn:s> <urn:p> 1 . _:b <urn:p> 2 } .");
+    match r {
+        Done(left, TrigStatement::Graph(Some(GraphLabel::IRI(IRI::IRI("urn:g"))), triples)) => {
+            assert_eq!(left, " .");
+            assert_eq!(triples.len(), 2);
+        }
+        r => panic!("unexpected {:?}", r),
+    }
+    match trig_statement("_:g{}") {
+        Done("", TrigStatement::Graph(Some(GraphLabel::BlankNode(_)), triples)) => {
+            assert!(triples.is_empty());
+        }
+        r => panic!("unexpected {:?}", r),
+    }
+    match trig_statement("{ :s :p :o. } ") {
+        Done(" ", TrigStatement::Graph(None, ref triples)) => assert_eq!(triples.len(), 1),
+        r => panic!("unexpected {:?}", r),
+    }
+    match trig_statement("GRAPH:s :p :o . ") {
+        Done(" ", TrigStatement::Statement(Statement::Triples(_))) => {}
+        r => panic!("unexpected {:?}", r),
+    }
+}
@@ -38,6 +38,7 @@ pub enum Subject<'a> {
     IRI(IRI<'a>),
     BlankNode(BlankNode<'a>),
     Collection(Vec<Object<'a>>),
+    QuotedTriple(Box<QuotedTriple<'a>>),
 }
 
 #[derive(Debug,PartialEq,Eq)]
@@ -47,6 +48,14 @@ pub enum Object<'a> {
     Collection(Vec<Object<'a>>),
     BlankNodePropertyList(Vec<PredicatedObjects<'a>>),
     Literal(Literal<'a>),
+    QuotedTriple(Box<QuotedTriple<'a>>),
+}
+
+#[derive(Debug,PartialEq,Eq)]
+pub struct QuotedTriple<'a> {
+    pub subject: Subject<'a>,
+    pub predicate: IRI<'a>,
+    pub object: Object<'a>,
 }
 
 #[derive(Debug,PartialEq,Eq)]
@@ -67,3 +76,15 @@ pub enum Statement<'a> {
     Base(&'a str),
     Triples(Triples<'a>),
 }
+
+#[derive(Debug,PartialEq,Eq)]
+pub enum GraphLabel<'a> {
+    IRI(IRI<'a>),
+    BlankNode(BlankNode<'a>),
+}
+
+#[derive(Debug,PartialEq,Eq)]
+pub enum TrigStatement<'a> {
+    Statement(Statement<'a>),
+    Graph(Option<GraphLabel<'a>>, Vec<Triples<'a>>),
+}
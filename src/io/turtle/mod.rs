@@ -1,6 +1,6 @@
 pub mod parser;
-mod grammar_helper;
+pub mod grammar_helper;
 mod grammar_structs;
-mod grammar;
+pub mod grammar;
 pub mod turtle_writer;
 pub mod pretty_turtle_writer;
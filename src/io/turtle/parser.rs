@@ -1,55 +1,233 @@
 use constants::*;
-use error::{Error, Result};
+use error::{Error, Result, StatementKind, SyntaxError};
 use graph;
+use iri;
 use namespaces::*;
 use nom::IResult;
-use regex::Regex;
+use std::cmp;
 use std::collections::HashMap;
+use std::io::BufRead;
 use std::marker::PhantomData;
-use super::grammar::{statement, tws};
+use std::str;
+use super::grammar::{statement, statement_error_offset, trig_statement,
+                     trig_statement_error_offset, tws};
 use super::grammar_helper::*;
 use super::grammar_structs::*;
 
-struct StatementIterator<'a> {
-    src: &'a str,
-    done: bool,
+/// A position in the source text.
+#[derive(Clone,Copy)]
+struct Location {
+    line: usize,
+    column: usize,
+    offset: usize,
 }
 
-impl<'a> StatementIterator<'a> {
-    pub fn new(src: &str) -> Result<StatementIterator> {
-        match tws(src) {
-            IResult::Done(left, _) => {
-                Ok(StatementIterator {
-                    src: left,
-                    done: false,
-                })
+impl Location {
+    fn start() -> Location {
+        Location {
+            line: 1,
+            column: 1,
+            offset: 0,
+        }
+    }
+    /// The location after `text` if `text` starts at this location.
+    fn advance(&self, text: &str) -> Location {
+        let mut location = *self;
+        location.offset += text.len();
+        match text.rfind('\n') {
+            Some(pos) => {
+                location.line += text.bytes().filter(|b| *b == b'\n').count();
+                location.column = text[pos + 1..].chars().count() + 1;
             }
-            IResult::Error(_) => Err(Error::Custom("cannot start parsing")),
-            IResult::Incomplete(_) => {
-                Ok(StatementIterator {
-                    src: src,
-                    done: false,
-                })
+            None => location.column += text.chars().count(),
+        }
+        location
+    }
+}
+
+fn statement_kind(src: &str) -> StatementKind {
+    fn starts_with_keyword(src: &str, keyword: &str) -> bool {
+        src.len() >= keyword.len() && src.is_char_boundary(keyword.len()) &&
+        src[..keyword.len()].eq_ignore_ascii_case(keyword)
+    }
+    if src.starts_with("@prefix") || starts_with_keyword(src, "prefix") {
+        StatementKind::Prefix
+    } else if src.starts_with("@base") || starts_with_keyword(src, "base") {
+        StatementKind::Base
+    } else if src.starts_with('{') || starts_with_keyword(src, "graph") {
+        StatementKind::Graph
+    } else {
+        StatementKind::Triples
+    }
+}
+
+/// Create a syntax error for position `offset` in `src`.
+/// The position is relative to the start of `src`; use `relocate` to make it
+/// relative to the start of the document.
+fn syntax_error(src: &str, offset: usize, statement: StatementKind, error: &Error) -> Error {
+    let location = Location::start().advance(&src[..offset]);
+    Error::Syntax(SyntaxError {
+        line: location.line,
+        column: location.column,
+        offset: location.offset,
+        statement: statement,
+        excerpt: src[offset..].lines().next().unwrap_or("").chars().take(40).collect(),
+        message: error.to_string(),
+    })
+}
+
+/// Move the position of a syntax error by `location`.
+fn relocate(error: Error, location: Location) -> Error {
+    match error {
+        Error::Syntax(mut e) => {
+            if e.line == 1 {
+                e.column += location.column - 1;
             }
+            e.line += location.line - 1;
+            e.offset += location.offset;
+            Error::Syntax(e)
         }
+        e => e,
     }
 }
 
-impl<'a> Iterator for StatementIterator<'a> {
-    type Item = Result<Statement<'a>>;
+/// Find the end of a statement that could not be parsed.
+///
+/// The end is the first '.' outside of IRIs, strings and comments that is
+/// followed by whitespace, a comment or the end of the input. The returned
+/// position is just after the '.'. A short string cannot span lines, so if
+/// one is not terminated, the statement ends at the end of that line.
+fn find_statement_end(src: &str) -> Option<usize> {
+    let bytes = src.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'.' => {
+                match bytes.get(i + 1) {
+                    None | Some(&b' ') | Some(&b'\t') | Some(&b'\r') | Some(&b'\n') |
+                    Some(&b'#') => return Some(i + 1),
+                    _ => {}
+                }
+            }
+            b'#' => {
+                while i < bytes.len() && bytes[i] != b'\n' {
+                    i += 1;
+                }
+            }
+            b'<' => {
+                while i + 1 < bytes.len() && !b"> \t\r\n".contains(&bytes[i + 1]) {
+                    i += 1;
+                }
+            }
+            q @ b'"' | q @ b'\'' => {
+                let long = bytes.get(i + 1) == Some(&q) && bytes.get(i + 2) == Some(&q);
+                if long {
+                    i += 2;
+                }
+                i += 1;
+                while i < bytes.len() {
+                    match bytes[i] {
+                        b'\\' => i += 1,
+                        b'\r' | b'\n' if !long => return Some(i),
+                        b if b == q && !long => break,
+                        b if b == q && bytes.get(i + 1) == Some(&q) &&
+                             bytes.get(i + 2) == Some(&q) => {
+                            i += 2;
+                            break;
+                        }
+                        _ => {}
+                    }
+                    i += 1;
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+/// Iterates over the statements of a document.
+/// `S` is a Turtle `Statement` or a `TrigStatement`.
+struct StatementIterator<'a, S> {
+    /// the statement parser
+    parse: fn(&'a str) -> IResult<&'a str, S>,
+    /// finds the offset of a syntax error in a statement
+    error_offset: fn(&str) -> usize,
+    /// the complete document
+    source: &'a str,
+    /// the text that has not been parsed yet
+    src: &'a str,
+    /// the text of the last statement
+    statement: &'a str,
+    /// the last location that was computed; it is only computed for errors
+    location: Location,
+    done: bool,
+}
+
+impl<'a, S> StatementIterator<'a, S> {
+    pub fn new(src: &'a str,
+               parse: fn(&'a str) -> IResult<&'a str, S>,
+               error_offset: fn(&str) -> usize)
+               -> Result<StatementIterator<'a, S>> {
+        let left = match tws(src) {
+            IResult::Done(left, _) => left,
+            IResult::Error(_) => return Err(Error::Custom("cannot start parsing")),
+            IResult::Incomplete(_) => src,
+        };
+        Ok(StatementIterator {
+            parse: parse,
+            error_offset: error_offset,
+            source: src,
+            src: left,
+            statement: left,
+            location: Location::start(),
+            done: false,
+        })
+    }
+    /// The location of `at`, which must be a slice of the document.
+    fn location(&mut self, at: &str) -> Location {
+        let offset = at.as_ptr() as usize - self.source.as_ptr() as usize;
+        if offset < self.location.offset {
+            self.location = Location::start();
+        }
+        self.location = self.location.advance(&self.source[self.location.offset..offset]);
+        self.location
+    }
+    /// Continue after the statement that failed to parse.
+    fn skip_statement(&mut self) {
+        let end = find_statement_end(self.src).unwrap_or(self.src.len());
+        self.src = &self.src[end..];
+        if let IResult::Done(left, _) = tws(self.src) {
+            self.src = left;
+        }
+        self.done = false;
+    }
+    fn error(&mut self, offset: usize, error: &Error) -> Error {
+        let src = self.src;
+        let e = syntax_error(src, offset, statement_kind(src), error);
+        relocate(e, self.location(src))
+    }
+}
+
+impl<'a, S> Iterator for StatementIterator<'a, S> {
+    type Item = Result<S>;
 
     fn next(&mut self) -> Option<Self::Item> {
         if self.done {
             return None;
         }
         let mut r;
-        match statement(self.src) {
+        self.statement = self.src;
+        match (self.parse)(self.src) {
             IResult::Done(left, s) => {
                 r = Some(Ok(s));
+                self.statement = &self.src[..self.src.len() - left.len()];
                 self.src = left;
             }
             IResult::Error(e) => {
-                r = Some(Err(Error::from(e)));
+                let offset = (self.error_offset)(self.src);
+                r = Some(Err(self.error(offset, &Error::from(e))));
                 self.done = true;
             }
             IResult::Incomplete(_) => {
@@ -62,7 +240,7 @@ impl<'a> Iterator for StatementIterator<'a> {
                 self.src = left;
             }
             IResult::Error(_) => {
-                r = Some(Err(Error::Custom("error parsing whitespace")));
+                r = Some(Err(self.error(0, &Error::Custom("error parsing whitespace"))));
                 self.done = true;
             }
             IResult::Incomplete(_) => {
@@ -70,7 +248,7 @@ impl<'a> Iterator for StatementIterator<'a> {
             }
         }
         if r.is_none() && !self.src.is_empty() {
-            r = Some(Err(Error::Custom("trailing bytes")));
+            r = Some(Err(self.error(0, &Error::Custom("trailing bytes"))));
         }
         r
     }
@@ -81,7 +259,7 @@ struct ParserState<'a, W: 'a>
 {
     base: String,
     prefixes: Namespaces,
-    blank_nodes: HashMap<&'a str, W::BlankNode>,
+    blank_nodes: HashMap<String, W::BlankNode>,
     writer: &'a mut W,
     buffer: String,
     iri: String,
@@ -95,109 +273,184 @@ struct ParserState<'a, W: 'a>
     rdf_first: Option<W::IRI>,
     rdf_rest: Option<W::IRI>,
     rdf_nil: Option<W::IRI>,
+    /// the address and length of the text of the current statement
+    statement_address: usize,
+    statement_len: usize,
+    /// the position of the last error in the current statement
+    error_offset: usize,
 }
 
 pub struct TurtleParser<'a, W: 'a>
     where W: graph::GraphWriter<'a>
 {
-    statement_iterator: StatementIterator<'a>,
+    statement_iterator: StatementIterator<'a, Statement<'a>>,
     state: ParserState<'a, W>,
+    lenient: bool,
+    errors: Vec<SyntaxError>,
     done: bool,
 }
 
-fn is_absolute(url: &str) -> bool {
-    lazy_static! {
-        static ref RE: Regex = Regex::new("^[a-z][a-z0-9+.-]*:").unwrap();
+impl<'a, W: 'a> TurtleParser<'a, W>
+    where W: graph::GraphWriter<'a>
+{
+    pub fn new(src: &'a str, base: &str, writer: &'a mut W) -> Result<TurtleParser<'a, W>> {
+        Ok(TurtleParser {
+            statement_iterator: StatementIterator::new(src, statement, statement_error_offset)?,
+            state: ParserState::new(base, writer)?,
+            lenient: false,
+            errors: Vec::new(),
+            done: false,
+        })
     }
-    RE.is_match(url)
-}
-
-fn join_iri(base: &str, p: &str, to: &mut String) -> Result<()> {
-    to.clear();
-    if !is_absolute(p) {
-        let mut end = base.len();
-        if !p.starts_with('#') {
-            if let Some(pos) = base.rfind('/') {
-                end = pos + 1;
+    pub fn prefixes(&self) -> &Namespaces {
+        &self.state.prefixes
+    }
+    /// Keep parsing after syntax errors.
+    ///
+    /// In lenient mode a statement with a syntax error is skipped up to the
+    /// next '.' and the error is kept in `errors()` instead of being returned
+    /// by the iterator. Triples from a statement that failed after it was
+    /// parsed, e.g. because of an unknown prefix, may be partially written.
+    pub fn set_lenient(&mut self, lenient: bool) {
+        self.lenient = lenient;
+    }
+    /// The syntax errors that were skipped in lenient mode.
+    pub fn errors(&self) -> &[SyntaxError] {
+        &self.errors
+    }
+    /// Consume the parser and return the syntax errors that were skipped in
+    /// lenient mode.
+    pub fn into_errors(self) -> Vec<SyntaxError> {
+        self.errors
+    }
+    /// return Ok(true) when done
+    fn parse_statement(&mut self) -> Result<bool> {
+        loop {
+            let result = match self.statement_iterator.next() {
+                None => return Ok(true),
+                Some(Ok(statement)) => {
+                    let src = self.statement_iterator.statement;
+                    self.state
+                        .add_statement(statement, src)
+                        .map_err(|e| relocate(e, self.statement_iterator.location(src)))
+                }
+                Some(Err(e)) => {
+                    if self.lenient {
+                        self.statement_iterator.skip_statement();
+                    }
+                    Err(e)
+                }
+            };
+            match result {
+                Ok(()) => return Ok(false),
+                Err(Error::Syntax(e)) if self.lenient => self.errors.push(e),
+                Err(e) => return Err(e),
             }
         }
-        to.push_str(&base[..end]);
     }
-    to.push_str(p);
-    Ok(())
 }
 
-impl<'a, W: 'a> TurtleParser<'a, W>
+impl<'a, W: 'a> Iterator for TurtleParser<'a, W>
     where W: graph::GraphWriter<'a>
 {
-    pub fn new(src: &'a str, base: &str, writer: &'a mut W) -> Result<TurtleParser<'a, W>> {
-        if !is_absolute(base) {
-            return Err(Error::Custom("base url is not absolute"));
+    type Item = Result<()>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
         }
-        Ok(TurtleParser {
-            statement_iterator: StatementIterator::new(src)?,
-            state: ParserState {
-                base: String::from(base),
-                prefixes: Namespaces::new(),
-                blank_nodes: HashMap::new(),
-                writer: writer,
-                buffer: String::new(),
-                iri: String::new(),
-                literal: String::new(),
-                rdf_lang_string: None,
-                xsd_boolean: None,
-                xsd_decimal: None,
-                xsd_double: None,
-                xsd_integer: None,
-                xsd_string: None,
-                rdf_first: None,
-                rdf_rest: None,
-                rdf_nil: None,
-            },
+        match self.parse_statement() {
+            Ok(true) => {
+                self.done = true;
+                None
+            }
+            Ok(_) => Some(Ok(())),
+            Err(e) => Some(Err(e)),
+        }
+    }
+}
+
+/// A TriG parser that writes the quads of a document to a `DatasetWriter`.
+///
+/// Triples outside of a graph block and in a block without a label go to
+/// the default graph. Blank node labels are shared by all graphs.
+pub struct TriGParser<'a, W: 'a>
+    where W: graph::DatasetWriter<'a>
+{
+    statement_iterator: StatementIterator<'a, TrigStatement<'a>>,
+    state: ParserState<'a, W>,
+    lenient: bool,
+    errors: Vec<SyntaxError>,
+    done: bool,
+}
+
+impl<'a, W: 'a> TriGParser<'a, W>
+    where W: graph::DatasetWriter<'a>
+{
+    pub fn new(src: &'a str, base: &str, writer: &'a mut W) -> Result<TriGParser<'a, W>> {
+        Ok(TriGParser {
+            statement_iterator: StatementIterator::new(src,
+                                                       trig_statement,
+                                                       trig_statement_error_offset)?,
+            state: ParserState::new(base, writer)?,
+            lenient: false,
+            errors: Vec::new(),
             done: false,
         })
     }
     pub fn prefixes(&self) -> &Namespaces {
         &self.state.prefixes
     }
-    fn set_prefix(&mut self, prefix: &'a str, value: String) {
-        self.state.prefixes.insert(prefix.as_bytes(), value);
+    /// Keep parsing after syntax errors. See `TurtleParser::set_lenient`.
+    pub fn set_lenient(&mut self, lenient: bool) {
+        self.lenient = lenient;
+    }
+    /// The syntax errors that were skipped in lenient mode.
+    pub fn errors(&self) -> &[SyntaxError] {
+        &self.errors
+    }
+    /// Consume the parser and return the syntax errors that were skipped in
+    /// lenient mode.
+    pub fn into_errors(self) -> Vec<SyntaxError> {
+        self.errors
     }
     /// return Ok(true) when done
     fn parse_statement(&mut self) -> Result<bool> {
-        if let Some(statement) = self.statement_iterator.next() {
-            match statement {
-                Ok(Statement::Prefix(prefix, iri)) => {
-                    let mut result = String::with_capacity(iri.len());
-                    self.state.buffer.clear();
-                    unescape_iri(iri, &mut self.state.buffer)?;
-                    join_iri(self.state.base.as_str(),
-                             self.state.buffer.as_str(),
-                             &mut result)?;
-                    self.set_prefix(prefix, result);
+        loop {
+            let result = match self.statement_iterator.next() {
+                None => return Ok(true),
+                Some(Ok(statement)) => {
+                    let src = self.statement_iterator.statement;
+                    let result = match statement {
+                        TrigStatement::Statement(statement) => {
+                            self.state.add_statement(statement, src)
+                        }
+                        TrigStatement::Graph(label, triples) => {
+                            self.state.locate(src, StatementKind::Graph, |state| {
+                                add_graph(label, triples, state)
+                            })
+                        }
+                    };
+                    result.map_err(|e| relocate(e, self.statement_iterator.location(src)))
                 }
-                Ok(Statement::Base(new_base)) => {
-                    self.state.buffer.clear();
-                    unescape_iri(new_base, &mut self.state.buffer)?;
-                    let old_base = self.state.base.clone();
-                    join_iri(old_base.as_str(),
-                             self.state.buffer.as_str(),
-                             &mut self.state.base)?;
-                }
-                Ok(Statement::Triples(new_triples)) => {
-                    add_triples(new_triples, &mut self.state)?;
+                Some(Err(e)) => {
+                    if self.lenient {
+                        self.statement_iterator.skip_statement();
+                    }
+                    Err(e)
                 }
+            };
+            match result {
+                Ok(()) => return Ok(false),
+                Err(Error::Syntax(e)) if self.lenient => self.errors.push(e),
                 Err(e) => return Err(e),
             }
-            Ok(false)
-        } else {
-            Ok(true)
         }
     }
 }
 
-impl<'a, W: 'a> Iterator for TurtleParser<'a, W>
-    where W: graph::GraphWriter<'a>
+impl<'a, W: 'a> Iterator for TriGParser<'a, W>
+    where W: graph::DatasetWriter<'a>
 {
     type Item = Result<()>;
 
@@ -216,6 +469,210 @@ impl<'a, W: 'a> Iterator for TurtleParser<'a, W>
     }
 }
 
+/// The default for the largest statement that `TurtleReader` will buffer.
+const DEFAULT_MAX_STATEMENT_SIZE: usize = 64 * 1024 * 1024;
+
+/// A Turtle parser that pulls its input from a `BufRead`.
+///
+/// Only the statement that is being parsed is kept in memory. The text of a
+/// statement is discarded as soon as its triples have been passed to the
+/// `GraphWriter`.
+pub struct TurtleReader<'a, R, W: 'a>
+    where R: BufRead,
+          W: graph::GraphWriter<'a>
+{
+    reader: R,
+    /// decoded text; everything before `pos` has been parsed
+    buffer: String,
+    pos: usize,
+    /// the location of `pos` in the document
+    location: Location,
+    /// bytes at the end of the last read that are not a complete character yet
+    pending: Vec<u8>,
+    eof: bool,
+    max_statement_size: usize,
+    state: ParserState<'a, W>,
+    lenient: bool,
+    errors: Vec<SyntaxError>,
+    done: bool,
+}
+
+impl<'a, R, W: 'a> TurtleReader<'a, R, W>
+    where R: BufRead,
+          W: graph::GraphWriter<'a>
+{
+    pub fn new(reader: R, base: &str, writer: &'a mut W) -> Result<TurtleReader<'a, R, W>> {
+        Ok(TurtleReader {
+            reader: reader,
+            buffer: String::new(),
+            pos: 0,
+            location: Location::start(),
+            pending: Vec::new(),
+            eof: false,
+            max_statement_size: DEFAULT_MAX_STATEMENT_SIZE,
+            state: ParserState::new(base, writer)?,
+            lenient: false,
+            errors: Vec::new(),
+            done: false,
+        })
+    }
+    /// Set the size in bytes of the largest statement that will be read.
+    ///
+    /// A syntax error is reported once the end of the statement with the
+    /// error has been read.
+    pub fn set_max_statement_size(&mut self, max_statement_size: usize) {
+        self.max_statement_size = max_statement_size;
+    }
+    pub fn prefixes(&self) -> &Namespaces {
+        &self.state.prefixes
+    }
+    /// Keep parsing after syntax errors. See `TurtleParser::set_lenient`.
+    pub fn set_lenient(&mut self, lenient: bool) {
+        self.lenient = lenient;
+    }
+    /// The syntax errors that were skipped in lenient mode.
+    pub fn errors(&self) -> &[SyntaxError] {
+        &self.errors
+    }
+    /// Consume the reader and return the syntax errors that were skipped in
+    /// lenient mode.
+    pub fn into_errors(self) -> Vec<SyntaxError> {
+        self.errors
+    }
+    /// Keep `error` if it is a syntax error in lenient mode.
+    fn recover(&mut self, error: Error) -> Result<()> {
+        match error {
+            Error::Syntax(e) if self.lenient => {
+                self.errors.push(e);
+                Ok(())
+            }
+            e => Err(e),
+        }
+    }
+    /// Read at least as many bytes as are currently unparsed, so that
+    /// parsing a long statement takes linear time.
+    fn fill(&mut self) -> Result<()> {
+        if self.pos > 0 {
+            self.buffer.drain(..self.pos);
+            self.pos = 0;
+        }
+        let wanted = cmp::max(self.buffer.len(), 1);
+        let mut read = 0;
+        while read < wanted && !self.eof {
+            let len = {
+                let data = self.reader.fill_buf()?;
+                self.pending.extend_from_slice(data);
+                data.len()
+            };
+            self.reader.consume(len);
+            if len == 0 {
+                self.eof = true;
+            }
+            read += len;
+            let valid = match str::from_utf8(&self.pending) {
+                Ok(s) => {
+                    self.buffer.push_str(s);
+                    s.len()
+                }
+                Err(e) => {
+                    if e.error_len().is_some() || self.eof {
+                        return Err(Error::Custom("Error decoding invalid UTF-8"));
+                    }
+                    let valid = e.valid_up_to();
+                    self.buffer.push_str(str::from_utf8(&self.pending[..valid]).unwrap());
+                    valid
+                }
+            };
+            self.pending.drain(..valid);
+        }
+        Ok(())
+    }
+    /// Parse the next statement. Returns `Ok(true)` when done.
+    fn parse_statement(&mut self) -> Result<bool> {
+        loop {
+            let src = &self.buffer[self.pos..];
+            let start = match tws(src) {
+                IResult::Done(left, _) => left,
+                _ => src,
+            };
+            let skipped = src.len() - start.len();
+            let location = self.location.advance(&src[..skipped]);
+            // a comment that tws did not skip is not terminated yet
+            if start.is_empty() || start.starts_with('#') {
+                if self.eof {
+                    return Ok(true);
+                }
+            } else {
+                // a statement that ends at the end of the buffer may continue
+                // in the next read, e.g. `:a :b 1.` followed by `5 .`
+                let (error, offset) = match statement(start) {
+                    IResult::Done(left, s) if !left.is_empty() || self.eof => {
+                        let text = &start[..start.len() - left.len()];
+                        self.pos += skipped + text.len();
+                        self.location = location.advance(text);
+                        match self.state.add_statement(s, text) {
+                            Ok(()) => return Ok(false),
+                            Err(e) => {
+                                self.recover(relocate(e, location))?;
+                                continue;
+                            }
+                        }
+                    }
+                    IResult::Incomplete(_) if self.eof => (Error::Custom("trailing bytes"), 0),
+                    // do not wait for more input when the end of the broken
+                    // statement is known
+                    IResult::Error(e) if self.eof ||
+                                         find_statement_end(start)
+                        .is_some_and(|end| end < start.len()) => {
+                        (Error::from(e), statement_error_offset(start))
+                    }
+                    _ => {
+                        if start.len() > self.max_statement_size {
+                            return Err(Error::Custom("Statement is too long."));
+                        }
+                        self.location = location;
+                        self.pos += skipped;
+                        self.fill()?;
+                        continue;
+                    }
+                };
+                let e = syntax_error(start, offset, statement_kind(start), &error);
+                let end = find_statement_end(start).unwrap_or(start.len());
+                self.location = location.advance(&start[..end]);
+                self.pos += skipped + end;
+                self.recover(relocate(e, location))?;
+                continue;
+            }
+            self.location = location;
+            self.pos += skipped;
+            self.fill()?;
+        }
+    }
+}
+
+impl<'a, R, W: 'a> Iterator for TurtleReader<'a, R, W>
+    where R: BufRead,
+          W: graph::GraphWriter<'a>
+{
+    type Item = Result<()>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        match self.parse_statement() {
+            Ok(true) => {
+                self.done = true;
+                None
+            }
+            Ok(_) => Some(Ok(())),
+            Err(e) => {
+                self.done = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
 
 fn unescape_literal(string: &str, to: &mut String) -> Result<()> {
     to.clear();
@@ -226,15 +683,93 @@ fn unescape_literal(string: &str, to: &mut String) -> Result<()> {
 impl<'a, W> ParserState<'a, W>
     where W: graph::GraphWriter<'a>
 {
+    fn new(base: &str, writer: &'a mut W) -> Result<ParserState<'a, W>> {
+        if !iri::is_absolute(base) {
+            return Err(Error::Custom("base url is not absolute"));
+        }
+        Ok(ParserState {
+            base: String::from(base),
+            prefixes: Namespaces::new(),
+            blank_nodes: HashMap::new(),
+            writer: writer,
+            buffer: String::new(),
+            iri: String::new(),
+            literal: String::new(),
+            rdf_lang_string: None,
+            xsd_boolean: None,
+            xsd_decimal: None,
+            xsd_double: None,
+            xsd_integer: None,
+            xsd_string: None,
+            rdf_first: None,
+            rdf_rest: None,
+            rdf_nil: None,
+            statement_address: 0,
+            statement_len: 0,
+            error_offset: 0,
+        })
+    }
+    /// Add the triples or directive in `statement` whose text is `src`.
+    /// Errors are reported as syntax errors relative to the start of `src`.
+    fn add_statement(&mut self, statement: Statement, src: &str) -> Result<()> {
+        let kind = match statement {
+            Statement::Prefix(_, _) => StatementKind::Prefix,
+            Statement::Base(_) => StatementKind::Base,
+            Statement::Triples(_) => StatementKind::Triples,
+        };
+        self.locate(src, kind, |state| state.add_statement_content(statement))
+    }
+    /// Run `add` for the statement whose text is `src` and turn its errors
+    /// into syntax errors relative to the start of `src`.
+    fn locate<F>(&mut self, src: &str, kind: StatementKind, add: F) -> Result<()>
+        where F: FnOnce(&mut Self) -> Result<()>
+    {
+        self.statement_address = src.as_ptr() as usize;
+        self.statement_len = src.len();
+        self.error_offset = 0;
+        add(self).map_err(|e| syntax_error(src, self.error_offset, kind, &e))
+    }
+    /// The offset of `part` in the current statement or 0 if `part` is not
+    /// a slice of it.
+    fn offset_in_statement(&self, part: &str) -> usize {
+        let address = part.as_ptr() as usize;
+        if address >= self.statement_address &&
+           address < self.statement_address + self.statement_len {
+            address - self.statement_address
+        } else {
+            0
+        }
+    }
+    fn add_statement_content(&mut self, statement: Statement) -> Result<()> {
+        match statement {
+            Statement::Prefix(prefix, iri) => {
+                let mut result = String::with_capacity(iri.len());
+                self.buffer.clear();
+                unescape_iri(iri, &mut self.buffer)?;
+                iri::resolve(self.base.as_str(), self.buffer.as_str(), &mut result);
+                self.prefixes.insert(prefix.as_bytes(), result);
+            }
+            Statement::Base(new_base) => {
+                self.buffer.clear();
+                unescape_iri(new_base, &mut self.buffer)?;
+                let old_base = self.base.clone();
+                iri::resolve(old_base.as_str(), self.buffer.as_str(), &mut self.base);
+            }
+            Statement::Triples(new_triples) => {
+                add_triples(new_triples, self)?;
+            }
+        }
+        Ok(())
+    }
     fn new_blank(&mut self) -> W::BlankNode {
         self.writer.create_blank_node()
     }
-    fn get_blank(&mut self, label: &'a str) -> W::BlankNode {
+    fn get_blank(&mut self, label: &str) -> W::BlankNode {
         if let Some(n) = self.blank_nodes.get(label) {
             return n.clone();
         }
         let n = self.new_blank();
-        self.blank_nodes.insert(label, n.clone());
+        self.blank_nodes.insert(String::from(label), n.clone());
         n
     }
     fn resolve_iri(&mut self, iri: IRI) -> Result<()> {
@@ -242,8 +777,12 @@ impl<'a, W> ParserState<'a, W>
         match iri {
             IRI::IRI(iri) => {
                 self.buffer.clear();
-                unescape_iri(iri, &mut self.buffer)?;
-                join_iri(&self.base, self.buffer.as_str(), &mut self.iri)?;
+                if let Err(e) = unescape_iri(iri, &mut self.buffer) {
+                    // the iri starts after '<'
+                    self.error_offset = self.offset_in_statement(iri).saturating_sub(1);
+                    return Err(e);
+                }
+                iri::resolve(&self.base, self.buffer.as_str(), &mut self.iri);
             }
             IRI::PrefixedName(prefix, local) => {
                 match self.prefixes.find_namespace(prefix.as_bytes()) {
@@ -251,7 +790,17 @@ impl<'a, W> ParserState<'a, W>
                         self.iri.push_str(ns);
                         pn_local_unescape(local, &mut self.iri)?;
                     }
-                    None => return Err(Error::Custom("Cannot find prefix.")),
+                    None => {
+                        self.error_offset = if !prefix.is_empty() {
+                            self.offset_in_statement(prefix)
+                        } else if !local.is_empty() {
+                            // the name starts with the ':' before `local`
+                            self.offset_in_statement(local).saturating_sub(1)
+                        } else {
+                            0
+                        };
+                        return Err(Error::Custom("Cannot find prefix."));
+                    }
                 }
             }
         }
@@ -307,7 +856,7 @@ fn get_cached_iri<'a, W: 'a>(cache: &mut Option<W::IRI>, writer: &mut W, iri: &s
     cache.clone().unwrap()
 }
 
-fn make_blank<'a, W: 'a>(blank_node: BlankNode<'a>, state: &mut ParserState<'a, W>) -> W::BlankNode
+fn make_blank<'a, W: 'a>(blank_node: BlankNode, state: &mut ParserState<'a, W>) -> W::BlankNode
     where W: graph::GraphWriter<'a>
 {
     match blank_node {
@@ -322,10 +871,11 @@ fn s2o<'a, W>(s: graph::WriterBlankNodeOrIRI<'a, W>) -> graph::WriterResource<'a
     match s {
         graph::WriterBlankNodeOrIRI::IRI(iri) => graph::WriterResource::IRI(iri),
         graph::WriterBlankNodeOrIRI::BlankNode(n, p) => graph::WriterResource::BlankNode(n, p),
+        graph::WriterBlankNodeOrIRI::QuotedTriple(t) => graph::WriterResource::QuotedTriple(t),
     }
 }
 
-fn make_collection<'a, W>(collection: Vec<Object<'a>>,
+fn make_collection<'a, W>(collection: Vec<Object>,
                           state: &mut ParserState<'a, W>)
                           -> Result<graph::WriterBlankNodeOrIRI<'a, W>>
     where W: graph::GraphWriter<'a>
@@ -347,6 +897,10 @@ fn make_collection<'a, W>(collection: Vec<Object<'a>>,
             graph::WriterResource::Literal(o) => {
                 state.writer.add_blank_literal(&this, &rdf_first, &o);
             }
+            o @ graph::WriterResource::QuotedTriple(_) => {
+                let subject = graph::WriterBlankNodeOrIRI::BlankNode(this.clone(), PhantomData);
+                state.writer.add_quoted(&subject, &rdf_first, &o);
+            }
         }
         match head {
             graph::WriterBlankNodeOrIRI::BlankNode(head, _) => {
@@ -355,13 +909,16 @@ fn make_collection<'a, W>(collection: Vec<Object<'a>>,
             graph::WriterBlankNodeOrIRI::IRI(head) => {
                 state.writer.add_blank_iri(&this, &rdf_rest, &head);
             }
+            graph::WriterBlankNodeOrIRI::QuotedTriple(_) => {
+                unreachable!("The rest of a collection is a blank node or rdf:nil.")
+            }
         }
         head = graph::WriterBlankNodeOrIRI::BlankNode(this, PhantomData);
     }
     Ok(head)
 }
 
-fn make_subject<'a, W>(subject: Subject<'a>,
+fn make_subject<'a, W>(subject: Subject,
                        state: &mut ParserState<'a, W>)
                        -> Result<graph::WriterBlankNodeOrIRI<'a, W>>
     where W: graph::GraphWriter<'a>
@@ -375,10 +932,25 @@ fn make_subject<'a, W>(subject: Subject<'a>,
             graph::WriterBlankNodeOrIRI::IRI(state.writer.create_iri(&state.iri))
         }
         Subject::Collection(collection) => make_collection(collection, state)?,
+        Subject::QuotedTriple(triple) => {
+            graph::WriterBlankNodeOrIRI::QuotedTriple(make_quoted_triple(*triple, state)?)
+        }
     })
 }
 
-fn make_object<'a, W>(object: Object<'a>,
+fn make_quoted_triple<'a, W>(triple: QuotedTriple,
+                             state: &mut ParserState<'a, W>)
+                             -> Result<W::QuotedTriple>
+    where W: graph::GraphWriter<'a>
+{
+    let subject = make_subject(triple.subject, state)?;
+    state.resolve_iri(triple.predicate)?;
+    let predicate = state.writer.create_iri(&state.iri);
+    let object = make_object(triple.object, state)?;
+    Ok(state.writer.create_quoted_triple(&subject, &predicate, &object))
+}
+
+fn make_object<'a, W>(object: Object,
                       state: &mut ParserState<'a, W>)
                       -> Result<graph::WriterResource<'a, W>>
     where W: graph::GraphWriter<'a>
@@ -408,11 +980,14 @@ fn make_object<'a, W>(object: Object<'a>,
             add_predicated_objects(subject, predicated_objects_list, state)?;
             graph::WriterResource::BlankNode(blank, PhantomData)
         }
+        Object::QuotedTriple(triple) => {
+            graph::WriterResource::QuotedTriple(make_quoted_triple(*triple, state)?)
+        }
     })
 }
 
 fn add_predicated_objects<'a, W>(subject: graph::WriterBlankNodeOrIRI<'a, W>,
-                                 predicated_objects_list: Vec<PredicatedObjects<'a>>,
+                                 predicated_objects_list: Vec<PredicatedObjects>,
                                  state: &mut ParserState<'a, W>)
                                  -> Result<()>
     where W: graph::GraphWriter<'a>
@@ -428,17 +1003,45 @@ fn add_predicated_objects<'a, W>(subject: graph::WriterBlankNodeOrIRI<'a, W>,
     Ok(())
 }
 
-fn add_triples<'a, W>(new_triples: Triples<'a>, state: &mut ParserState<'a, W>) -> Result<()>
+fn add_triples<'a, W>(new_triples: Triples, state: &mut ParserState<'a, W>) -> Result<()>
     where W: graph::GraphWriter<'a>
 {
     let subject = make_subject(new_triples.subject, state)?;
     add_predicated_objects(subject, new_triples.predicated_objects_list, state)
 }
 
+fn add_graph<'a, W>(label: Option<GraphLabel>,
+                    triples: Vec<Triples>,
+                    state: &mut ParserState<'a, W>)
+                    -> Result<()>
+    where W: graph::DatasetWriter<'a>
+{
+    let graph_name = match label {
+        None => None,
+        Some(GraphLabel::IRI(iri)) => {
+            state.resolve_iri(iri)?;
+            Some(graph::WriterBlankNodeOrIRI::IRI(state.writer.create_iri(&state.iri)))
+        }
+        Some(GraphLabel::BlankNode(blank)) => {
+            Some(graph::WriterBlankNodeOrIRI::BlankNode(make_blank(blank, state), PhantomData))
+        }
+    };
+    state.writer.set_graph(graph_name.as_ref())?;
+    let mut result = Ok(());
+    for t in triples {
+        result = add_triples(t, state);
+        if result.is_err() {
+            break;
+        }
+    }
+    state.writer.set_graph(None)?;
+    result
+}
+
 #[test]
 fn blank_node() {
     let s = "<http://a.example/s> <http://a.example/p> _:b1 .\n";
-    let mut i = StatementIterator::new(s).unwrap();
+    let mut i = StatementIterator::new(s, statement, statement_error_offset).unwrap();
     let n = i.next();
     assert!(n.is_some());
     assert!(n.unwrap().is_ok());
@@ -447,7 +1050,7 @@ fn blank_node() {
 #[test]
 fn test_string_literal_long_quote() {
     let s = "<http://a.example/s> <http://a.example/p> \"\"\"first long literal\"\"\" .\n";
-    let mut i = StatementIterator::new(s).unwrap();
+    let mut i = StatementIterator::new(s, statement, statement_error_offset).unwrap();
     let n = i.next();
     assert!(n.is_some());
     assert!(n.unwrap().is_ok());
@@ -456,9 +1059,340 @@ fn test_string_literal_long_quote() {
 #[test]
 fn test_no_space_before_dot() {
     let s = "@prefix : <urn:> .\n:s..2 :p..2 :o.\n";
-    let mut i = StatementIterator::new(s).unwrap();
+    let mut i = StatementIterator::new(s, statement, statement_error_offset).unwrap();
     i.next();
     let n = i.next();
     assert!(n.is_some());
     assert!(n.unwrap().is_ok());
 }
+
+#[cfg(test)]
+fn count_triples<F>(parse: F) -> usize
+    where F: FnOnce(&mut ::graphs::tel::GraphCreator<::graphs::tel::Triple64SPO,
+                                                     ::graphs::tel::Triple64OPS>)
+{
+    use graph::{Graph, GraphWriter};
+    let mut writer = ::graphs::tel::GraphCreator::with_capacity(0);
+    parse(&mut writer);
+    let graph: ::graphs::tel::Graph64 = writer.collect();
+    graph.iter().count()
+}
+
+#[test]
+fn test_reader_small_reads() {
+    use std::io::BufReader;
+    let s = "@prefix : <urn:> .\n:s :p 1.5, \"\"\"a\nb\"\"\", 'é' ; :q _:b1 . # end\n\
+             _:b1 :p ( 1 2 ) .\n:s :p :o.x .";
+    let expected = count_triples(|w| {
+        for step in TurtleParser::new(s, "urn:", w).unwrap() {
+            step.unwrap();
+        }
+    });
+    assert_eq!(expected, 10);
+    let read = count_triples(|w| {
+        let reader = BufReader::with_capacity(1, s.as_bytes());
+        for step in TurtleReader::new(reader, "urn:", w).unwrap() {
+            step.unwrap();
+        }
+    });
+    assert_eq!(read, expected);
+}
+
+#[test]
+fn test_reader_error() {
+    let s = "<urn:s> <urn:p> <urn:o> .\n<urn:s> <urn:p> .\n<urn:s> <urn:p> <urn:o> .\n";
+    count_triples(|w| {
+        let mut reader = TurtleReader::new(s.as_bytes(), "urn:", w).unwrap();
+        assert!(reader.next().unwrap().is_ok());
+        assert!(reader.next().unwrap().is_err());
+        assert!(reader.next().is_none());
+    });
+}
+
+#[cfg(test)]
+fn first_error<I>(steps: I) -> SyntaxError
+    where I: Iterator<Item = Result<()>>
+{
+    for step in steps {
+        match step {
+            Err(Error::Syntax(e)) => return e,
+            Err(e) => panic!("unexpected error {:?}", e),
+            Ok(_) => {}
+        }
+    }
+    panic!("no error");
+}
+
+#[test]
+fn test_syntax_error_position() {
+    let s = "@prefix : <urn:> .\n:s :p :o .\n  :s :p ! :o .\n";
+    count_triples(|w| {
+        let e = first_error(TurtleParser::new(s, "urn:", w).unwrap());
+        assert_eq!((e.line, e.column, e.offset), (3, 9, 38));
+        assert_eq!(e.statement, StatementKind::Triples);
+        assert_eq!(e.excerpt, "! :o .");
+    });
+    count_triples(|w| {
+        let reader = TurtleReader::new(::std::io::BufReader::with_capacity(1, s.as_bytes()),
+                                       "urn:",
+                                       w)
+            .unwrap();
+        let e = first_error(reader);
+        assert_eq!((e.line, e.column, e.offset), (3, 9, 38));
+    });
+    // the error is at the token that does not fit, not at the statement
+    let errors = [("<urn:s> <urn:p> <urn:o> <urn:x> .", "<urn:x> ."),
+                  ("<urn:s> <urn:p> <urn:o y> .", "<urn:o y> ."),
+                  ("<urn:s> <urn:p> \"é\", 'x'@1 .", "@1 ."),
+                  ("<urn:s> <urn:p> [ <urn:q> (1 <urn:o y>) ] .", "<urn:o y>) ] ."),
+                  ("<urn:s> <urn:p> <urn:o> ; ; <urn:q> 1, .", "."),
+                  ("@prefix : <urn:> ; .", "; .")];
+    for &(s, excerpt) in &errors {
+        count_triples(|w| {
+            let e = first_error(TurtleParser::new(s, "urn:", w).unwrap());
+            assert_eq!(e.excerpt, excerpt);
+            assert_eq!(e.column, s.chars().count() - excerpt.chars().count() + 1);
+        });
+    }
+}
+
+#[test]
+fn test_reader_error_position() {
+    // the error is reported before the rest of the input is read
+    let mut s = String::from("@prefix : <urn:> .\n:s :p ! :o .\n");
+    for _ in 0..100 {
+        s.push_str(":s :p :o .\n");
+    }
+    count_triples(|w| {
+        let mut reader = TurtleReader::new(s.as_bytes(), "urn:", w).unwrap();
+        reader.set_max_statement_size(100);
+        let e = first_error(reader);
+        assert_eq!((e.line, e.column, e.offset), (2, 7, 25));
+        assert_eq!(e.excerpt, "! :o .");
+    });
+}
+
+#[test]
+fn test_unknown_prefix_position() {
+    let s = "@prefix : <urn:> .\n:s :p 'é', x:o .\n";
+    count_triples(|w| {
+        let e = first_error(TurtleParser::new(s, "urn:", w).unwrap());
+        assert_eq!((e.line, e.column, e.offset), (2, 12, 31));
+        assert_eq!(e.excerpt, "x:o .");
+        assert_eq!(e.message, "Cannot find prefix.");
+    });
+    count_triples(|w| {
+        let e = first_error(TurtleReader::new(s.as_bytes(), "urn:", w).unwrap());
+        assert_eq!((e.line, e.column, e.offset), (2, 12, 31));
+    });
+    let s = "PREFIX : <urn:>\n@prefix a: <\\u00>.";
+    count_triples(|w| {
+        let e = first_error(TurtleParser::new(s, "urn:", w).unwrap());
+        assert_eq!((e.line, e.column), (2, 1));
+        assert_eq!(e.statement, StatementKind::Prefix);
+    });
+}
+
+#[test]
+fn test_lenient() {
+    let s = "@prefix : <urn:> .\n:a :b :c .\n:a :b \"x. y\" ! .\n:a x:b :c .\n:a :b :d .\n\
+             :a :b 'broken .\n:a :b :e .\n:a :b :f";
+    let mut lines = Vec::new();
+    let count = count_triples(|w| {
+        let mut parser = TurtleParser::new(s, "urn:", w).unwrap();
+        parser.set_lenient(true);
+        for step in &mut parser {
+            step.unwrap();
+        }
+        lines.extend(parser.into_errors().iter().map(|e| e.line));
+    });
+    assert_eq!(count, 3);
+    assert_eq!(lines, vec![3, 4, 6, 8]);
+    let mut lines = Vec::new();
+    let count = count_triples(|w| {
+        let input = ::std::io::BufReader::with_capacity(1, s.as_bytes());
+        let mut reader = TurtleReader::new(input, "urn:", w).unwrap();
+        reader.set_lenient(true);
+        for step in &mut reader {
+            step.unwrap();
+        }
+        lines.extend(reader.into_errors().iter().map(|e| e.line));
+    });
+    assert_eq!(count, 3);
+    assert_eq!(lines, vec![3, 4, 6, 8]);
+}
+
+#[test]
+fn test_lenient_unterminated_string() {
+    use graph::{Graph, GraphWriter, IRIPtr, Triple};
+    use graphs::tel;
+    let s = "@prefix : <urn:> .\n:a :b \"broken .\n:a :b :kept .\n";
+    let mut writer = tel::GraphCreator::with_capacity(0);
+    let errors = {
+        let mut parser = TurtleParser::new(s, "urn:", &mut writer).unwrap();
+        parser.set_lenient(true);
+        for step in &mut parser {
+            step.unwrap();
+        }
+        parser.into_errors()
+    };
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].line, 2);
+    let graph: tel::Graph64 = writer.collect();
+    let objects: Vec<_> = graph.iter()
+        .map(|t| String::from(t.object().as_iri().unwrap().as_str()))
+        .collect();
+    assert_eq!(objects, ["urn:kept"]);
+    let count = count_triples(|w| {
+        let mut reader = TurtleReader::new(s.as_bytes(), "urn:", w).unwrap();
+        reader.set_lenient(true);
+        for step in &mut reader {
+            step.unwrap();
+        }
+    });
+    assert_eq!(count, 1);
+}
+
+#[test]
+fn test_relative_iris() {
+    use graph::{Graph, GraphWriter, IRIPtr, Triple};
+    let s = "@base <http://a/b/c/d> .\n@prefix p: <../e/f#> .\n@base <//g/h/> .\n\
+             <../i> p:j <?k> .";
+    let mut writer = ::graphs::tel::GraphCreator::with_capacity(0);
+    for step in TurtleParser::new(s, "http://x/", &mut writer).unwrap() {
+        step.unwrap();
+    }
+    let graph: ::graphs::tel::Graph64 = writer.collect();
+    let triple = graph.iter().next().unwrap();
+    assert_eq!(triple.subject().as_iri().unwrap().as_str(), "http://g/i");
+    assert_eq!(triple.predicate().as_str(), "http://a/b/e/f#j");
+    assert_eq!(triple.object().as_iri().unwrap().as_str(), "http://g/h/?k");
+}
+
+#[cfg(test)]
+fn trig_to_nquads(s: &str) -> (String, String) {
+    use graph::{Dataset, DatasetWriter};
+    use graphs::tel;
+    use io::{write_nquads, write_pretty_trig};
+    let mut writer = tel::DatasetCreator::with_capacity(0);
+    let prefixes = {
+        let mut parser = TriGParser::new(s, "http://a.example/", &mut writer).unwrap();
+        for step in &mut parser {
+            step.unwrap();
+        }
+        parser.prefixes().clone()
+    };
+    let dataset: tel::Dataset64 = writer.collect_dataset();
+    let mut nquads = Vec::new();
+    write_nquads(dataset.graphs(), &mut nquads).unwrap();
+    let mut trig = Vec::new();
+    write_pretty_trig(&prefixes, dataset.graphs(), &mut trig).unwrap();
+    (String::from_utf8(nquads).unwrap(), String::from_utf8(trig).unwrap())
+}
+
+#[test]
+fn test_trig() {
+    let s = "@prefix : <http://a.example/> .\n:s :p :o .\n\
+             GRAPH :g { :s :p 1, 2 ; :q [ :r :o ] . _:b :p :o }\n\
+             _:b { :s :p _:b . }\n{ :s :p :x }\n<g> { }";
+    let (nquads, trig) = trig_to_nquads(s);
+    assert_eq!(nquads,
+               "<http://a.example/s> <http://a.example/p> <http://a.example/o> .\n\
+                <http://a.example/s> <http://a.example/p> <http://a.example/x> .\n\
+                <http://a.example/s> <http://a.example/p> _:2 _:2 .\n\
+                _:1 <http://a.example/r> <http://a.example/o> <http://a.example/g> .\n\
+                _:2 <http://a.example/p> <http://a.example/o> <http://a.example/g> .\n\
+                <http://a.example/s> <http://a.example/p> \"1\"^^<http://www.w3.org/2001/XMLSchema#integer> <http://a.example/g> .\n\
+                <http://a.example/s> <http://a.example/p> \"2\"^^<http://www.w3.org/2001/XMLSchema#integer> <http://a.example/g> .\n\
+                <http://a.example/s> <http://a.example/q> _:1 <http://a.example/g> .\n");
+    assert_eq!(trig,
+               "@prefix :\t<http://a.example/> .\n\n\
+                :s\t:p\t:o ,\n\t\t:x .\n\n\
+                _:2 {\n\t:s\t:p\t_:2 .\n}\n\n\
+                :g {\n\t_:2\t:p\t:o .\n\t:s\t:p\t1 ,\n\t\t\t2 ;\n\t\t:q\t[:r\t:o] .\n}\n");
+    // the blank nodes are numbered in the order in which they are read
+    let swapped = nquads.replace("_:1", "_:x").replace("_:2", "_:1").replace("_:x", "_:2");
+    let mut expected: Vec<_> = swapped.lines().collect();
+    expected.sort();
+    let reparsed = trig_to_nquads(&trig).0;
+    let mut lines: Vec<_> = reparsed.lines().collect();
+    lines.sort();
+    assert_eq!(lines, expected);
+}
+
+#[test]
+fn test_trig_error() {
+    use graphs::tel;
+    let s = "@prefix : <urn:> .\n:s :p :o .\nGRAPH :g {\n  :s :p :o .\n  :s :p ! }\n";
+    let mut writer = tel::DatasetCreator::<tel::Triple64SPO, tel::Triple64OPS>::with_capacity(0);
+    let e = first_error(TriGParser::new(s, "urn:", &mut writer).unwrap());
+    assert_eq!((e.line, e.column), (5, 9));
+    assert_eq!(e.excerpt, "! }");
+    assert_eq!(e.statement, StatementKind::Graph);
+}
+
+#[test]
+fn test_trig_error_position() {
+    use graphs::tel;
+    let errors = [("<urn:g> { <urn:s> <urn:p> <urn:o> <urn:x> }", "<urn:x> }"),
+                  ("GRAPH \"g\" { <urn:s> <urn:p> <urn:o> }", "\"g\" { <urn:s> <urn:p> <urn:o> }"),
+                  ("{ <urn:s> <urn:p> [ <urn:q> 'x'@1 ] }", "@1 ] }"),
+                  ("_:g { <urn:s> <urn:p> <urn:o> . <urn:s> <urn:p> }", "}")];
+    for &(s, excerpt) in &errors {
+        let mut writer =
+            tel::DatasetCreator::<tel::Triple64SPO, tel::Triple64OPS>::with_capacity(0);
+        let e = first_error(TriGParser::new(s, "urn:", &mut writer).unwrap());
+        assert_eq!(e.excerpt, excerpt);
+        assert_eq!(e.column, s.chars().count() - excerpt.chars().count() + 1);
+    }
+}
+
+#[cfg(test)]
+fn turtle_star_to_ntriples(s: &str) -> (String, String) {
+    use graph::{Graph, GraphWriter};
+    use graphs::tel;
+    use io::{write_ntriples, write_pretty_turtle};
+    let mut writer = tel::GraphCreator::with_capacity(0);
+    let prefixes = {
+        let mut parser = TurtleParser::new(s, "http://a.example/", &mut writer).unwrap();
+        for step in &mut parser {
+            step.unwrap();
+        }
+        parser.prefixes().clone()
+    };
+    let graph: tel::Graph64 = writer.collect();
+    let mut ntriples = Vec::new();
+    write_ntriples(graph.iter(), &graph, &mut ntriples).unwrap();
+    let mut turtle = Vec::new();
+    write_pretty_turtle(&prefixes, &graph, &mut turtle).unwrap();
+    (String::from_utf8(ntriples).unwrap(), String::from_utf8(turtle).unwrap())
+}
+
+#[test]
+fn test_turtle_star() {
+    let s = "@prefix : <http://a.example/> .\n\
+             << :s :p [] >> :q << << :a :b :c >> :d \"x\" >> .\n\
+             << :s :p _:o >> :r _:o .\n\
+             << :s :p _:o >> :r _:o .\n\
+             :x :y ( << :a :b :c >> ) .";
+    let (ntriples, turtle) = turtle_star_to_ntriples(s);
+    // quoted triples share the numbering of the blank nodes
+    assert_eq!(ntriples,
+               "<< <http://a.example/s> <http://a.example/p> _:1 >> <http://a.example/q> \
+                << << <http://a.example/a> <http://a.example/b> <http://a.example/c> >> \
+                <http://a.example/d> \"x\" >> .\n\
+                << <http://a.example/s> <http://a.example/p> _:5 >> <http://a.example/r> _:5 .\n\
+                _:8 <http://www.w3.org/1999/02/22-rdf-syntax-ns#first> \
+                << <http://a.example/a> <http://a.example/b> <http://a.example/c> >> .\n\
+                _:8 <http://www.w3.org/1999/02/22-rdf-syntax-ns#rest> \
+                <http://www.w3.org/1999/02/22-rdf-syntax-ns#nil> .\n\
+                <http://a.example/x> <http://a.example/y> _:8 .\n");
+    // the blank node in the quoted triple keeps its label
+    assert_eq!(turtle,
+               "@prefix :\t<http://a.example/> .\n\n\
+                << :s :p _:1 >>\t:q\t<< << :a :b :c >> :d \"x\" >> .\n\
+                << :s :p _:5 >>\t:r\t_:5 .\n\
+                :x\t:y\t(<< :a :b :c >>) .\n");
+    // the duplicate triple is not read again, so one id less is used
+    assert_eq!(turtle_star_to_ntriples(&turtle).0, ntriples.replace("_:8", "_:7"));
+}
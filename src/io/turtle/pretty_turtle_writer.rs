@@ -3,11 +3,13 @@ use error::{Error, Result};
 use graph::*;
 use namespaces::*;
 use nom::IResult;
+use std::collections::{HashMap, HashSet};
 use std::fmt::Display;
 use std::io::Write;
 use std::iter::Peekable;
 use super::grammar::{boolean, decimal, integer, double, pn_local};
 use super::grammar_structs::Literal;
+use io::ntriples_writer::{escape_iri, escape_literal_value};
 
 struct TurtleWriter<'a, 'g, W: 'a, G: 'g>
     where W: Write,
@@ -25,10 +27,19 @@ struct TurtleWriter<'a, 'g, W: 'a, G: 'g>
     rdf_nil: Option<G::IRIPtr>,
     rdf_rest: Option<G::IRIPtr>,
     rdf_type: Option<G::IRIPtr>,
+    /// written at the start of each line, e.g. inside a TriG graph block
+    indent: &'static [u8],
+    /// blank nodes that are always written with their label
+    labelled: Option<&'a HashSet<String>>,
+    /// blank nodes that occur in a quoted triple and so need their label
+    quoted: HashSet<String>,
     graph: &'g G
 }
 
 /// Write out triples as pretty turtle.
+///
+/// Quoted triples are written as `<< s p o >>` like in Turtle-star. Blank
+/// nodes that occur in a quoted triple are always written with their label.
 pub fn write_pretty_turtle<'g, G: 'g, W>(namespaces: &Namespaces,
                                          graph: &'g G,
                                          writer: &mut W)
@@ -37,33 +48,162 @@ pub fn write_pretty_turtle<'g, G: 'g, W>(namespaces: &Namespaces,
           <G as Graph<'g>>::BlankNodePtr: Display,
           W: Write
 {
-    let mut writer = TurtleWriter::<_, G> {
-        buffer: Vec::new(),
-        base: String::new(),
-        writer: writer,
-        xsd_string: graph.find_datatype(constants::XSD_STRING),
-        xsd_boolean: graph.find_datatype(constants::XSD_BOOLEAN),
-        xsd_integer: graph.find_datatype(constants::XSD_INTEGER),
-        xsd_decimal: graph.find_datatype(constants::XSD_DECIMAL),
-        xsd_double: graph.find_datatype(constants::XSD_DOUBLE),
-        rdf_first: graph.find_iri(constants::RDF_FIRST),
-        rdf_nil: graph.find_iri(constants::RDF_NIL),
-        rdf_rest: graph.find_iri(constants::RDF_REST),
-        rdf_type: graph.find_iri(constants::RDF_TYPE),
-        graph: graph,
-    };
-    for ns in namespaces.iter() {
-        writer.write_prefix(ns)?;
-    }
-    writer.writer.write_all(b"\n")?;
+    let mut writer = TurtleWriter::new(graph, writer);
+    writer.write_prefixes(namespaces)?;
     writer.write_statements(namespaces)
 }
 
+/// Write out graphs as pretty TriG.
+///
+/// `graphs` yields each graph with its name. The default graph has no name
+/// and its triples are written outside of a graph block. The triples of each
+/// graph are grouped the same way as in `write_pretty_turtle`. Blank nodes
+/// that name a graph or occur in more than one graph keep their label.
+pub fn write_pretty_trig<'g, G: 'g, I, W>(namespaces: &Namespaces,
+                                          graphs: I,
+                                          writer: &mut W)
+                                          -> Result<()>
+    where G: Graph<'g>,
+          <G as Graph<'g>>::BlankNodePtr: Display,
+          I: Iterator<Item = (Option<GraphName<'g, G::BlankNodePtr>>, &'g G)>,
+          W: Write
+{
+    let graphs: Vec<_> = graphs.collect();
+    let labelled = shared_blank_nodes(&graphs);
+    let mut first = true;
+    for (name, graph) in graphs {
+        let mut writer = TurtleWriter::new(graph, writer);
+        writer.labelled = Some(&labelled);
+        if first {
+            writer.write_prefixes(namespaces)?;
+            first = false;
+        }
+        match name {
+            None => writer.write_statements(namespaces)?,
+            Some(name) => {
+                writer.writer.write_all(b"\n")?;
+                match name {
+                    GraphName::IRI(iri) => writer.write_iri_str(iri, namespaces)?,
+                    GraphName::BlankNode(blank_node) => {
+                        write!(writer.writer, "_:{}", blank_node)?
+                    }
+                }
+                writer.writer.write_all(b" {\n")?;
+                writer.indent = b"\t";
+                writer.write_statements(namespaces)?;
+                writer.writer.write_all(b"}\n")?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The labels of the blank nodes that name a graph or occur in more than one
+/// graph.
+fn shared_blank_nodes<'g, G: 'g>(graphs: &[(Option<GraphName<'g, G::BlankNodePtr>>, &'g G)])
+                                 -> HashSet<String>
+    where G: Graph<'g>,
+          <G as Graph<'g>>::BlankNodePtr: Display
+{
+    // the first graph in which each blank node occurs
+    let mut seen = HashMap::new();
+    let mut shared = HashSet::new();
+    for (i, &(ref name, graph)) in graphs.iter().enumerate() {
+        if let Some(GraphName::BlankNode(ref blank_node)) = *name {
+            shared.insert(blank_node.to_string());
+        }
+        for triple in graph.iter() {
+            let subject = match triple.subject() {
+                BlankNodeOrIRI::BlankNode(blank_node, _) => Some(blank_node.to_string()),
+                _ => None,
+            };
+            let object = match triple.object() {
+                Resource::BlankNode(blank_node, _) => Some(blank_node.to_string()),
+                _ => None,
+            };
+            for label in subject.into_iter().chain(object) {
+                if *seen.entry(label.clone()).or_insert(i) != i {
+                    shared.insert(label);
+                }
+            }
+        }
+    }
+    shared
+}
+
+/// The labels of the blank nodes that occur in the quoted triples of a graph.
+fn quoted_blank_nodes<'g, G: 'g>(graph: &'g G) -> HashSet<String>
+    where G: Graph<'g>,
+          <G as Graph<'g>>::BlankNodePtr: Display
+{
+    let mut quoted = HashSet::new();
+    let mut todo = Vec::new();
+    for triple in graph.iter() {
+        if let BlankNodeOrIRI::QuotedTriple(t) = triple.subject() {
+            todo.push(t);
+        }
+        if let Resource::QuotedTriple(t) = triple.object() {
+            todo.push(t);
+        }
+    }
+    while let Some(triple) = todo.pop() {
+        match triple.subject() {
+            BlankNodeOrIRI::BlankNode(blank_node, _) => {
+                quoted.insert(blank_node.to_string());
+            }
+            BlankNodeOrIRI::QuotedTriple(t) => todo.push(t),
+            BlankNodeOrIRI::IRI(_) => {}
+        }
+        match triple.object() {
+            Resource::BlankNode(blank_node, _) => {
+                quoted.insert(blank_node.to_string());
+            }
+            Resource::QuotedTriple(t) => todo.push(t),
+            _ => {}
+        }
+    }
+    quoted
+}
+
 impl<'a, 'g, W: 'a, G: 'g> TurtleWriter<'a, 'g, W, G>
     where W: Write,
           G: Graph<'g>,
           <G as Graph<'g>>::BlankNodePtr: Display
 {
+    fn new(graph: &'g G, writer: &'a mut W) -> TurtleWriter<'a, 'g, W, G> {
+        TurtleWriter {
+            buffer: Vec::new(),
+            base: String::new(),
+            writer: writer,
+            xsd_string: graph.find_datatype(constants::XSD_STRING),
+            xsd_boolean: graph.find_datatype(constants::XSD_BOOLEAN),
+            xsd_integer: graph.find_datatype(constants::XSD_INTEGER),
+            xsd_decimal: graph.find_datatype(constants::XSD_DECIMAL),
+            xsd_double: graph.find_datatype(constants::XSD_DOUBLE),
+            rdf_first: graph.find_iri(constants::RDF_FIRST),
+            rdf_nil: graph.find_iri(constants::RDF_NIL),
+            rdf_rest: graph.find_iri(constants::RDF_REST),
+            rdf_type: graph.find_iri(constants::RDF_TYPE),
+            indent: b"",
+            labelled: None,
+            quoted: quoted_blank_nodes(graph),
+            graph: graph,
+        }
+    }
+    fn write_prefixes(&mut self, namespaces: &Namespaces) -> Result<()> {
+        for ns in namespaces.iter() {
+            self.write_prefix(ns)?;
+        }
+        self.writer.write_all(b"\n")?;
+        Ok(())
+    }
+    /// Start a new line that is indented by `tabs`.
+    fn new_line(&mut self, tabs: &[u8]) -> Result<()> {
+        self.writer.write_all(b"\n")?;
+        self.writer.write_all(self.indent)?;
+        self.writer.write_all(tabs)?;
+        Ok(())
+    }
     fn write_prefix(&mut self, ns: &Namespace) -> Result<()> {
         self.writer.write_all(b"@prefix ")?;
         self.writer.write_all(ns.prefix())?;
@@ -104,17 +244,15 @@ impl<'a, 'g, W: 'a, G: 'g> TurtleWriter<'a, 'g, W, G>
         }
         self.writer.write_all(b"<")?;
         self.buffer.clear();
-        for b in iri.as_bytes() {
-            if *b < 20 || b"<>\"{}|^`\\".contains(b) {
-                write!(&mut self.buffer, "\\u00{:X}", *b).unwrap();
-            } else {
-                self.buffer.push(*b);
-            }
-        }
+        escape_iri(iri, &mut self.buffer);
         self.writer.write_all(&self.buffer[..])?;
         self.writer.write_all(b">")?;
         Ok(())
     }
+    fn is_labelled(&self, blank_node: &G::BlankNodePtr) -> bool {
+        let label = blank_node.to_string();
+        self.quoted.contains(&label) || self.labelled.is_some_and(|l| l.contains(&label))
+    }
     fn write_blank_node(&mut self, blank_node: G::BlankNodePtr) -> Result<()> {
         self.writer.write_all(b"_:")?;
         write!(self.writer, "{}", blank_node)?;
@@ -122,12 +260,7 @@ impl<'a, 'g, W: 'a, G: 'g> TurtleWriter<'a, 'g, W, G>
     }
     fn write_literal_value(&mut self, value: &str) -> Result<()> {
         self.buffer.clear();
-        for b in value.as_bytes() {
-            if *b == 0x22 || *b == 0x5C || *b == 0x0A || *b == 0x0D {
-                self.buffer.push(b'\\');
-            }
-            self.buffer.push(*b);
-        }
+        escape_literal_value(value, &mut self.buffer);
         self.writer.write_all(&self.buffer[..])?;
         Ok(())
     }
@@ -198,7 +331,7 @@ impl<'a, 'g, W: 'a, G: 'g> TurtleWriter<'a, 'g, W, G>
             if iter.next().is_some() {
                 return Err(Error::Custom("No more triples were expected for the list node."));
             }
-            self.writer.write_all(b"\n\t\t\t")?;
+            self.new_line(b"\t\t\t")?;
             iter = self.graph.iter_s(&rest.to_blank_node_or_iri());
             triple = iter.next().expect("The list node should have properties.");
             if Some(triple.predicate()) != self.rdf_first {
@@ -206,12 +339,41 @@ impl<'a, 'g, W: 'a, G: 'g> TurtleWriter<'a, 'g, W, G>
             }
         }
     }
+    fn write_quoted_triple(&mut self,
+                           triple: G::QuotedTriplePtr,
+                           namespaces: &Namespaces)
+                           -> Result<()> {
+        self.writer.write_all(b"<< ")?;
+        match triple.subject() {
+            BlankNodeOrIRI::BlankNode(blank_node, _) => self.write_blank_node(blank_node)?,
+            BlankNodeOrIRI::IRI(ref iri) => self.write_iri(iri, namespaces)?,
+            BlankNodeOrIRI::QuotedTriple(t) => self.write_quoted_triple(t, namespaces)?,
+        }
+        self.writer.write_all(b" ")?;
+        self.write_predicate(&triple.predicate(), namespaces)?;
+        self.writer.write_all(b" ")?;
+        match triple.object() {
+            Resource::BlankNode(blank_node, _) => self.write_blank_node(blank_node)?,
+            Resource::IRI(ref iri) => self.write_iri(iri, namespaces)?,
+            Resource::Literal(literal) => self.write_literal(literal, namespaces)?,
+            Resource::QuotedTriple(t) => self.write_quoted_triple(t, namespaces)?,
+        }
+        self.writer.write_all(b" >>")?;
+        Ok(())
+    }
     fn write_object(&mut self,
-                    object: Resource<'g, G::BlankNodePtr, G::IRIPtr, G::LiteralPtr>,
+                    object: Resource<'g,
+                                     G::BlankNodePtr,
+                                     G::IRIPtr,
+                                     G::LiteralPtr,
+                                     G::QuotedTriplePtr>,
                     namespaces: &Namespaces)
                     -> Result<()> {
         match object {
             Resource::BlankNode(blank_node, _) => {
+                if self.is_labelled(&blank_node) {
+                    return self.write_blank_node(blank_node);
+                }
                 // check how often the node is used as an object
                 {
                     let mut object_iter = self.graph.iter_o(&blank_node.to_resource());
@@ -245,6 +407,7 @@ impl<'a, 'g, W: 'a, G: 'g> TurtleWriter<'a, 'g, W, G>
             }
             Resource::IRI(iri) => self.write_iri(&iri, namespaces)?,
             Resource::Literal(literal) => self.write_literal(literal, namespaces)?,
+            Resource::QuotedTriple(triple) => self.write_quoted_triple(triple, namespaces)?,
         }
         Ok(())
     }
@@ -262,11 +425,12 @@ impl<'a, 'g, W: 'a, G: 'g> TurtleWriter<'a, 'g, W, G>
             let predicate = triple.predicate();
             self.write_predicate(&predicate, namespaces)?;
             self.writer.write_all(b"\t")?;
-            let mut same_subject = false;
+            let mut same_subject;
             loop {
                 self.write_object(triple.object(), namespaces)?;
                 match iter.peek() {
                     None => {
+                        same_subject = false;
                         break;
                     }
                     Some(next) => {
@@ -276,13 +440,15 @@ impl<'a, 'g, W: 'a, G: 'g> TurtleWriter<'a, 'g, W, G>
                         }
                     }
                 }
-                self.writer.write_all(b" ,\n\t\t")?;
+                self.writer.write_all(b" ,")?;
+                self.new_line(b"\t\t")?;
                 triple = iter.next().unwrap();
             }
             if !same_subject {
                 break;
             }
-            self.writer.write_all(b" ;\n\t")?;
+            self.writer.write_all(b" ;")?;
+            self.new_line(b"\t")?;
             triple = iter.next().unwrap();
         }
         Ok(())
@@ -293,18 +459,30 @@ impl<'a, 'g, W: 'a, G: 'g> TurtleWriter<'a, 'g, W, G>
             match triple.subject() {
                 BlankNodeOrIRI::BlankNode(blank_node, _) => {
                     let mut object_iter = self.graph.iter_o(&blank_node.to_resource());
-                    if object_iter.next().is_none() {
+                    if self.is_labelled(&blank_node) {
+                        self.writer.write_all(self.indent)?;
+                        self.write_blank_node(blank_node)?;
+                    } else if object_iter.next().is_none() {
                         // node is never used as object, write anonymous node []
+                        self.writer.write_all(self.indent)?;
                         self.writer.write_all(b"[]")?;
                     } else if object_iter.next().is_none() {
                         // node is only used once, skip it now
                         // it will be written when it is an object
                         continue;
                     } else {
+                        self.writer.write_all(self.indent)?;
                         self.write_blank_node(blank_node)?;
                     }
                 }
-                BlankNodeOrIRI::IRI(ref iri) => self.write_iri(iri, namespaces)?,
+                BlankNodeOrIRI::IRI(ref iri) => {
+                    self.writer.write_all(self.indent)?;
+                    self.write_iri(iri, namespaces)?
+                }
+                BlankNodeOrIRI::QuotedTriple(quoted) => {
+                    self.writer.write_all(self.indent)?;
+                    self.write_quoted_triple(quoted, namespaces)?
+                }
             }
             self.writer.write_all(b"\t")?;
             self.write_predicate_object_list(&triple, &mut iter, namespaces)?;
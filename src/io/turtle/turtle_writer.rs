@@ -6,6 +6,7 @@ use std::fmt::Display;
 use std::io::{Result, Write};
 use super::grammar::{boolean, decimal, integer, double, pn_local};
 use super::grammar_structs::Literal;
+use io::ntriples_writer::{escape_iri, escape_literal_value};
 
 struct TurtleWriter<'a, 'g, W: 'a, G: 'g>
     where W: Write,
@@ -19,17 +20,22 @@ struct TurtleWriter<'a, 'g, W: 'a, G: 'g>
     xsd_integer: Option<<G::LiteralPtr as LiteralPtr<'g>>::DatatypePtr>,
     xsd_decimal: Option<<G::LiteralPtr as LiteralPtr<'g>>::DatatypePtr>,
     xsd_double: Option<<G::LiteralPtr as LiteralPtr<'g>>::DatatypePtr>,
-    last_subject: Option<BlankNodeOrIRI<'g, <G as Graph<'g>>::BlankNodePtr, <G as Graph<'g>>::IRIPtr>>,
+    last_subject: Option<BlankNodeOrIRI<'g,
+                                        <G as Graph<'g>>::BlankNodePtr,
+                                        <G as Graph<'g>>::IRIPtr,
+                                        <G as Graph<'g>>::QuotedTriplePtr>>,
     open_statement: bool,
 }
 
 /// Write out triples as turtle.
+///
+/// Quoted triples are written as `<< s p o >>` like in Turtle-star.
 pub fn write_turtle<'g, G: 'g, T: 'g, I, W>(namespaces: &Namespaces,
                                             triples: I,
                                             graph: &'g G,
                                             writer: &mut W)
                                             -> Result<()>
-    where T: Triple<'g, G::BlankNodePtr, G::IRIPtr, G::LiteralPtr>,
+    where T: Triple<'g, G::BlankNodePtr, G::IRIPtr, G::LiteralPtr, G::QuotedTriplePtr>,
           G: Graph<'g>,
           <G as Graph<'g>>::BlankNodePtr: Display,
           I: Iterator<Item = T>,
@@ -96,13 +102,7 @@ impl<'a, 'g, W: 'a, G: 'g> TurtleWriter<'a, 'g, W, G>
         }
         self.writer.write_all(b"<")?;
         self.buffer.clear();
-        for b in iri.as_bytes() {
-            if *b < 20 || b"<>\"{}|^`\\".contains(b) {
-                write!(&mut self.buffer, "\\u00{:X}", *b).unwrap();
-            } else {
-                self.buffer.push(*b);
-            }
-        }
+        escape_iri(iri, &mut self.buffer);
         self.writer.write_all(&self.buffer[..])?;
         self.writer.write_all(b">")
     }
@@ -113,12 +113,7 @@ impl<'a, 'g, W: 'a, G: 'g> TurtleWriter<'a, 'g, W, G>
     }
     fn write_literal_value(&mut self, value: &str) -> Result<()> {
         self.buffer.clear();
-        for b in value.as_bytes() {
-            if *b == 0x22 || *b == 0x5C || *b == 0x0A || *b == 0x0D {
-                self.buffer.push(b'\\');
-            }
-            self.buffer.push(*b);
-        }
+        escape_literal_value(value, &mut self.buffer);
         self.writer.write_all(&self.buffer[..])
     }
     fn write_literal(&mut self, literal: G::LiteralPtr, namespaces: &Namespaces) -> Result<()> {
@@ -154,30 +149,48 @@ impl<'a, 'g, W: 'a, G: 'g> TurtleWriter<'a, 'g, W, G>
         }
         Ok(())
     }
+    fn write_quoted_triple(&mut self,
+                           triple: G::QuotedTriplePtr,
+                           namespaces: &Namespaces)
+                           -> Result<()> {
+        self.writer.write_all(b"<< ")?;
+        self.write_subject(triple.subject(), namespaces)?;
+        self.writer.write_all(b" ")?;
+        self.write_predicate(triple.predicate().as_str(), namespaces)?;
+        self.writer.write_all(b" ")?;
+        self.write_object(triple.object(), namespaces)?;
+        self.writer.write_all(b" >>")
+    }
     fn write_subject(&mut self,
-                     subject: BlankNodeOrIRI<'g, G::BlankNodePtr, G::IRIPtr>,
+                     subject: BlankNodeOrIRI<'g, G::BlankNodePtr, G::IRIPtr, G::QuotedTriplePtr>,
                      namespaces: &Namespaces)
                      -> Result<()> {
         match subject {
             BlankNodeOrIRI::BlankNode(blank_node, _) => self.write_blank_node(blank_node),
             BlankNodeOrIRI::IRI(ref iri) => self.write_iri(iri.as_str(), namespaces),
+            BlankNodeOrIRI::QuotedTriple(triple) => self.write_quoted_triple(triple, namespaces),
         }
     }
     fn write_predicate(&mut self, predicate: &str, namespaces: &Namespaces) -> Result<()> {
         self.write_iri(predicate, namespaces)
     }
     fn write_object(&mut self,
-                    object: Resource<'g, G::BlankNodePtr, G::IRIPtr, G::LiteralPtr>,
+                    object: Resource<'g,
+                                     G::BlankNodePtr,
+                                     G::IRIPtr,
+                                     G::LiteralPtr,
+                                     G::QuotedTriplePtr>,
                     namespaces: &Namespaces)
                     -> Result<()> {
         match object {
             Resource::BlankNode(blank_node, _) => self.write_blank_node(blank_node),
             Resource::IRI(iri) => self.write_iri(iri.as_str(), namespaces),
             Resource::Literal(literal) => self.write_literal(literal, namespaces),
+            Resource::QuotedTriple(triple) => self.write_quoted_triple(triple, namespaces),
         }
     }
     fn write_triple<T>(&mut self, triple: &T, namespaces: &Namespaces) -> Result<()>
-        where T: Triple<'g, G::BlankNodePtr, G::IRIPtr, G::LiteralPtr>
+        where T: Triple<'g, G::BlankNodePtr, G::IRIPtr, G::LiteralPtr, G::QuotedTriplePtr>
     {
         let subject = triple.subject();
         if self.last_subject.as_ref() == Some(&subject) {
@@ -0,0 +1,284 @@
+//! Resolution of relative IRI references as described in
+//! [RFC 3986](https://tools.ietf.org/html/rfc3986#section-5).
+//!
+//! ```
+//! use rome::iri;
+//! assert_eq!(iri::join("http://a/b/c/d;p?q", "../g"), "http://a/b/g");
+//! ```
+
+/// The five components of an IRI reference.
+///
+/// A component that is `None` is not present in the reference. This is
+/// different from a component that is present but empty, e.g. `?` has an
+/// empty query.
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub struct Components<'a> {
+    /// The scheme, without the ':'.
+    pub scheme: Option<&'a str>,
+    /// The authority, without the leading '//'.
+    pub authority: Option<&'a str>,
+    /// The path. It is always present but may be empty.
+    pub path: &'a str,
+    /// The query, without the '?'.
+    pub query: Option<&'a str>,
+    /// The fragment, without the '#'.
+    pub fragment: Option<&'a str>,
+}
+
+impl<'a> Components<'a> {
+    /// Split an IRI reference into its components.
+    pub fn parse(iri: &'a str) -> Components<'a> {
+        let mut rest = iri;
+        let scheme = scheme_len(rest).map(|len| {
+            let scheme = &rest[..len];
+            rest = &rest[len + 1..];
+            scheme
+        });
+        let authority = if rest.starts_with("//") {
+            let end = rest[2..].find(['/', '?', '#']).map_or(rest.len(), |p| p + 2);
+            let authority = &rest[2..end];
+            rest = &rest[end..];
+            Some(authority)
+        } else {
+            None
+        };
+        let fragment = rest.find('#').map(|p| {
+            let fragment = &rest[p + 1..];
+            rest = &rest[..p];
+            fragment
+        });
+        let query = rest.find('?').map(|p| {
+            let query = &rest[p + 1..];
+            rest = &rest[..p];
+            query
+        });
+        Components {
+            scheme: scheme,
+            authority: authority,
+            path: rest,
+            query: query,
+            fragment: fragment,
+        }
+    }
+    /// Write the components as an IRI reference.
+    pub fn write(&self, to: &mut String) {
+        if let Some(scheme) = self.scheme {
+            to.push_str(scheme);
+            to.push(':');
+        }
+        if let Some(authority) = self.authority {
+            to.push_str("//");
+            to.push_str(authority);
+        }
+        to.push_str(self.path);
+        if let Some(query) = self.query {
+            to.push('?');
+            to.push_str(query);
+        }
+        if let Some(fragment) = self.fragment {
+            to.push('#');
+            to.push_str(fragment);
+        }
+    }
+}
+
+/// The length of the scheme at the start of `iri`, if there is one.
+///
+/// `scheme = ALPHA *( ALPHA / DIGIT / "+" / "-" / "." )`
+fn scheme_len(iri: &str) -> Option<usize> {
+    let mut bytes = iri.bytes().enumerate();
+    match bytes.next() {
+        Some((_, b)) if b.is_ascii_alphabetic() => {}
+        _ => return None,
+    }
+    for (i, b) in bytes {
+        if b == b':' {
+            return Some(i);
+        }
+        if !b.is_ascii_alphanumeric() && b != b'+' && b != b'-' && b != b'.' {
+            return None;
+        }
+    }
+    None
+}
+
+/// An IRI is absolute if it starts with a scheme.
+pub fn is_absolute(iri: &str) -> bool {
+    scheme_len(iri).is_some()
+}
+
+/// Remove the segments `.` and `..` from `path` and append the result to
+/// `to`. (RFC 3986, section 5.2.4)
+pub fn remove_dot_segments(path: &str, to: &mut String) {
+    let start = to.len();
+    let mut input = path;
+    while !input.is_empty() {
+        if input.starts_with("../") {
+            input = &input[3..];
+        } else if input.starts_with("./") || input.starts_with("/./") {
+            input = &input[2..];
+        } else if input == "/." {
+            input = "/";
+        } else if input.starts_with("/../") || input == "/.." {
+            input = if input == "/.." { "/" } else { &input[3..] };
+            let end = to[start..].rfind('/').map_or(start, |p| start + p);
+            to.truncate(end);
+        } else if input == "." || input == ".." {
+            input = "";
+        } else {
+            let skip = if input.starts_with('/') { 1 } else { 0 };
+            let end = input[skip..].find('/').map_or(input.len(), |p| p + skip);
+            to.push_str(&input[..end]);
+            input = &input[end..];
+        }
+    }
+}
+
+/// Resolve `reference` against the absolute IRI `base` and write the result
+/// to `to`. (RFC 3986, section 5.2.2)
+pub fn resolve(base: &str, reference: &str, to: &mut String) {
+    to.clear();
+    let r = Components::parse(reference);
+    if r.scheme.is_some() {
+        push_scheme_and_authority(&r, to);
+        remove_dot_segments(r.path, to);
+        push_query_and_fragment(r.query, r.fragment, to);
+        return;
+    }
+    let b = Components::parse(base);
+    if r.authority.is_some() {
+        push_scheme_and_authority(&Components { scheme: b.scheme, ..r }, to);
+        remove_dot_segments(r.path, to);
+        push_query_and_fragment(r.query, r.fragment, to);
+        return;
+    }
+    push_scheme_and_authority(&b, to);
+    if r.path.is_empty() {
+        to.push_str(b.path);
+        push_query_and_fragment(r.query.or(b.query), r.fragment, to);
+        return;
+    }
+    if r.path.starts_with('/') {
+        remove_dot_segments(r.path, to);
+    } else {
+        // merge the paths (section 5.2.3)
+        let mut merged = String::with_capacity(b.path.len() + r.path.len() + 1);
+        if b.authority.is_some() && b.path.is_empty() {
+            merged.push('/');
+        } else if let Some(p) = b.path.rfind('/') {
+            merged.push_str(&b.path[..p + 1]);
+        }
+        merged.push_str(r.path);
+        remove_dot_segments(&merged, to);
+    }
+    push_query_and_fragment(r.query, r.fragment, to);
+}
+
+/// Resolve `reference` against the absolute IRI `base`.
+pub fn join(base: &str, reference: &str) -> String {
+    let mut iri = String::with_capacity(base.len() + reference.len());
+    resolve(base, reference, &mut iri);
+    iri
+}
+
+fn push_scheme_and_authority(c: &Components, to: &mut String) {
+    Components {
+        scheme: c.scheme,
+        authority: c.authority,
+        path: "",
+        query: None,
+        fragment: None,
+    }
+        .write(to);
+}
+
+fn push_query_and_fragment(query: Option<&str>, fragment: Option<&str>, to: &mut String) {
+    Components {
+        scheme: None,
+        authority: None,
+        path: "",
+        query: query,
+        fragment: fragment,
+    }
+        .write(to);
+}
+
+#[test]
+fn test_components() {
+    let c = Components::parse("http://a/b/c/d;p?q#f");
+    assert_eq!(c.scheme, Some("http"));
+    assert_eq!(c.authority, Some("a"));
+    assert_eq!(c.path, "/b/c/d;p");
+    assert_eq!(c.query, Some("q"));
+    assert_eq!(c.fragment, Some("f"));
+    let c = Components::parse("g:h?#");
+    assert_eq!(c.authority, None);
+    assert_eq!(c.path, "h");
+    assert_eq!(c.query, Some(""));
+    assert_eq!(c.fragment, Some(""));
+    assert_eq!(Components::parse("./g:h").scheme, None);
+    let mut s = String::new();
+    Components::parse("http://a/b/c/d;p?q#f").write(&mut s);
+    assert_eq!(s, "http://a/b/c/d;p?q#f");
+}
+
+#[cfg(test)]
+const RFC3986_BASE: &'static str = "http://a/b/c/d;p?q";
+
+/// RFC 3986, section 5.4.1
+#[test]
+fn test_normal_examples() {
+    let examples = [("g:h", "g:h"),
+                    ("g", "http://a/b/c/g"),
+                    ("./g", "http://a/b/c/g"),
+                    ("g/", "http://a/b/c/g/"),
+                    ("/g", "http://a/g"),
+                    ("//g", "http://g"),
+                    ("?y", "http://a/b/c/d;p?y"),
+                    ("g?y", "http://a/b/c/g?y"),
+                    ("#s", "http://a/b/c/d;p?q#s"),
+                    ("g#s", "http://a/b/c/g#s"),
+                    ("g?y#s", "http://a/b/c/g?y#s"),
+                    (";x", "http://a/b/c/;x"),
+                    ("g;x", "http://a/b/c/g;x"),
+                    ("g;x?y#s", "http://a/b/c/g;x?y#s"),
+                    ("", "http://a/b/c/d;p?q"),
+                    (".", "http://a/b/c/"),
+                    ("./", "http://a/b/c/"),
+                    ("..", "http://a/b/"),
+                    ("../", "http://a/b/"),
+                    ("../g", "http://a/b/g"),
+                    ("../..", "http://a/"),
+                    ("../../", "http://a/"),
+                    ("../../g", "http://a/g")];
+    for &(reference, expected) in examples.iter() {
+        assert_eq!(join(RFC3986_BASE, reference), expected, "reference {}", reference);
+    }
+}
+
+/// RFC 3986, section 5.4.2
+#[test]
+fn test_abnormal_examples() {
+    let examples = [("../../../g", "http://a/g"),
+                    ("../../../../g", "http://a/g"),
+                    ("/./g", "http://a/g"),
+                    ("/../g", "http://a/g"),
+                    ("g.", "http://a/b/c/g."),
+                    (".g", "http://a/b/c/.g"),
+                    ("g..", "http://a/b/c/g.."),
+                    ("..g", "http://a/b/c/..g"),
+                    ("./../g", "http://a/b/g"),
+                    ("./g/.", "http://a/b/c/g/"),
+                    ("g/./h", "http://a/b/c/g/h"),
+                    ("g/../h", "http://a/b/c/h"),
+                    ("g;x=1/./y", "http://a/b/c/g;x=1/y"),
+                    ("g;x=1/../y", "http://a/b/c/y"),
+                    ("g?y/./x", "http://a/b/c/g?y/./x"),
+                    ("g?y/../x", "http://a/b/c/g?y/../x"),
+                    ("g#s/./x", "http://a/b/c/g#s/./x"),
+                    ("g#s/../x", "http://a/b/c/g#s/../x"),
+                    ("http:g", "http:g")];
+    for &(reference, expected) in examples.iter() {
+        assert_eq!(join(RFC3986_BASE, reference), expected, "reference {}", reference);
+    }
+}
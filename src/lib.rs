@@ -7,20 +7,27 @@
 //!
 //! This crate is in early development.
 #[macro_use]
-extern crate lazy_static;
-#[macro_use]
 extern crate nom;
+extern crate memmap2;
 extern crate rand;
 extern crate regex;
+extern crate serde_json;
+extern crate xml;
 
 mod constants;
+mod crc;
 pub mod error;
 pub mod graph;
+#[macro_use]
 pub mod graphs;
 pub mod io;
+pub mod iri;
 pub mod iter;
 pub mod ontology_adapter;
 pub mod namespaces;
+pub mod query;
+pub mod reasoning;
+pub mod sparql;
 #[macro_use]
 pub mod resource;
 /// Ontology mapping for rdf: and rdfs:
@@ -32,3 +39,6 @@ pub use error::Result;
 
 #[cfg(unstable)]
 mod unstable;
+
+#[cfg(test)]
+mod test_support;
@@ -26,14 +26,18 @@ impl<'g, G> OntologyAdapter<'g, G>
     }
     #[doc(hidden)]
     pub fn iter_s_p(&self,
-                    subject: BlankNodeOrIRI<'g, G::BlankNodePtr, G::IRIPtr>,
+                    subject: BlankNodeOrIRI<'g, G::BlankNodePtr, G::IRIPtr, G::QuotedTriplePtr>,
                     predicate: G::IRIPtr)
                     -> G::SPORangeIter {
         self.graph.iter_s_p(&subject, &predicate)
     }
     #[doc(hidden)]
     pub fn iter_o_p(&self,
-                    object: Resource<'g, G::BlankNodePtr, G::IRIPtr, G::LiteralPtr>,
+                    object: Resource<'g,
+                                     G::BlankNodePtr,
+                                     G::IRIPtr,
+                                     G::LiteralPtr,
+                                     G::QuotedTriplePtr>,
                     predicate: G::IRIPtr)
                     -> G::OPSRangeIter {
         self.graph.iter_o_p(&object, &predicate)
@@ -0,0 +1,635 @@
+use graph::{Graph, IRIPtr, LiteralPtr, Resource, Triple};
+use std::cmp::{max, Ordering};
+use super::{resolve, PatternTerm, Solution, SubjectTerm, Term, TriplePattern};
+
+/// A lookup in an index costs about as much as reading this many triples
+/// from a range in an index.
+const LOOKUP_COST: usize = 8;
+/// The most matches that are counted for an estimate when the graph does not
+/// know the size of a range.
+const COUNT_LIMIT: usize = 1 << 12;
+
+/// A set of triple patterns that share variables.
+///
+/// The patterns are evaluated one after the other. The pattern with the
+/// smallest range in the indexes of the graph goes first. After that,
+/// patterns that share variables with the patterns before them go first,
+/// so that the number of solutions stays small.
+///
+/// A pattern is joined with the solutions so far in one of two ways. Either
+/// the pattern is looked up in the graph for each solution, or the matches of
+/// the pattern are read from the graph once and merged with the solutions.
+/// The second way is used when there are many solutions compared to the
+/// number of matches. It is cheapest when the index that the matches come
+/// from is sorted by the shared variable, because then they do not need to
+/// be sorted.
+pub struct BasicGraphPattern<'g, G>
+    where G: Graph<'g>
+{
+    variables: Vec<String>,
+    patterns: Vec<TriplePattern<'g, G>>,
+}
+
+impl<'g, G> BasicGraphPattern<'g, G>
+    where G: Graph<'g>
+{
+    /// Create an empty basic graph pattern.
+    pub fn new() -> BasicGraphPattern<'g, G> {
+        BasicGraphPattern {
+            variables: Vec::new(),
+            patterns: Vec::new(),
+        }
+    }
+    /// Get the number of the variable with the given name.
+    ///
+    /// The variable is created if it does not exist yet.
+    pub fn variable(&mut self, name: &str) -> usize {
+        match self.find_variable(name) {
+            Some(variable) => variable,
+            None => {
+                self.variables.push(String::from(name));
+                self.variables.len() - 1
+            }
+        }
+    }
+    /// Find the number of the variable with the given name.
+    pub fn find_variable(&self, name: &str) -> Option<usize> {
+        self.variables.iter().position(|v| v == name)
+    }
+    /// The names of the variables, in the order of their numbers.
+    pub fn variables(&self) -> &[String] {
+        &self.variables
+    }
+    /// Add a triple pattern.
+    ///
+    /// Panics if the pattern uses a variable that was not created with
+    /// `variable`.
+    pub fn add(&mut self, pattern: TriplePattern<'g, G>) {
+        for variable in pattern.variables() {
+            assert!(variable < self.variables.len(),
+                    "unknown variable {}",
+                    variable);
+        }
+        self.patterns.push(pattern);
+    }
+    /// The triple patterns in the order in which they were added.
+    pub fn patterns(&self) -> &[TriplePattern<'g, G>] {
+        &self.patterns
+    }
+    /// Find all solutions for the pattern in the graph.
+    ///
+    /// An empty basic graph pattern has one solution in which no variable
+    /// is bound.
+    pub fn evaluate(&self, graph: &'g G) -> Vec<Solution<'g, G>> {
+        let len = self.variables.len();
+        let mut solutions = vec![Solution::new(len)];
+        let mut bound = vec![false; len];
+        // the variable by which the solutions are sorted
+        let mut sorted = None;
+        let estimates = self.estimates(graph);
+        for i in self.plan(&estimates) {
+            if solutions.is_empty() {
+                break;
+            }
+            let pattern = &self.patterns[i];
+            let shared = shared_variables(pattern, &bound);
+            solutions = if use_merge_join(pattern, &shared, solutions.len(), estimates[i]) {
+                if shared.is_empty() {
+                    // the matches are added to each solution in turn, so
+                    // one solution gets the order of the matches
+                    if solutions.len() == 1 {
+                        sorted = sorted_variable(pattern);
+                    }
+                } else {
+                    let solutions_sorted = sorted == Some(shared[0]) && shared.len() == 1;
+                    sorted = Some(shared[0]);
+                    solutions = sort_by_shared(solutions, &shared, solutions_sorted);
+                }
+                merge_join(graph, pattern, &shared, solutions, len)
+            } else {
+                nested_loop_join(graph, pattern, &solutions)
+            };
+            for variable in pattern.variables() {
+                bound[variable] = true;
+            }
+        }
+        solutions
+    }
+    /// The estimated number of matches for each pattern.
+    fn estimates(&self, graph: &'g G) -> Vec<usize> {
+        self.patterns.iter().map(|p| estimate(graph, p)).collect()
+    }
+    /// Decide the order in which the patterns are evaluated.
+    fn plan(&self, estimates: &[usize]) -> Vec<usize> {
+        let mut bound = vec![false; self.variables.len()];
+        let mut remaining: Vec<usize> = (0..self.patterns.len()).collect();
+        let mut order = Vec::with_capacity(self.patterns.len());
+        while !remaining.is_empty() {
+            let (pos, _) = remaining.iter()
+                .enumerate()
+                .min_by_key(|&(_, &i)| {
+                    let variables = self.patterns[i].variables();
+                    let connected = order.is_empty() || variables.is_empty() ||
+                                    variables.iter().any(|&v| bound[v]);
+                    let unbound = if order.is_empty() {
+                        0
+                    } else {
+                        variables.iter().filter(|&&v| !bound[v]).count()
+                    };
+                    (!connected, unbound, estimates[i])
+                })
+                .unwrap();
+            let i = remaining.remove(pos);
+            for variable in self.patterns[i].variables() {
+                bound[variable] = true;
+            }
+            order.push(i);
+        }
+        order
+    }
+}
+
+impl<'g, G> Default for BasicGraphPattern<'g, G>
+    where G: Graph<'g>
+{
+    fn default() -> Self {
+        BasicGraphPattern::new()
+    }
+}
+
+/// The subject, predicate and object of a pattern, if they are constants.
+type Constants<'a, 'g, G> = (Option<&'a SubjectTerm<'g, G>>,
+                             Option<&'a <G as Graph<'g>>::IRIPtr>,
+                             Option<&'a Term<'g, G>>);
+
+/// The constant parts of the pattern.
+fn constants<'a, 'g, G>(pattern: &'a TriplePattern<'g, G>) -> Constants<'a, 'g, G>
+    where G: Graph<'g>
+{
+    (pattern.subject.as_constant(), pattern.predicate.as_constant(), pattern.object.as_constant())
+}
+
+/// The number of triples in the range of the index that is used for the
+/// constant parts of the pattern.
+///
+/// If the graph does not know the size of the range, the matches are counted
+/// up to `COUNT_LIMIT`.
+fn estimate<'g, G>(graph: &'g G, pattern: &TriplePattern<'g, G>) -> usize
+    where G: Graph<'g>
+{
+    let (subject, predicate, object) = constants(pattern);
+    let matches = graph.match_pattern(subject, predicate, object);
+    match matches.size_hint() {
+        (_, Some(upper)) => upper,
+        (lower, None) => max(lower, matches.take(COUNT_LIMIT).count()),
+    }
+}
+
+/// The variable by which the matches for the constant parts of the pattern
+/// are sorted.
+///
+/// This is the first part that is not fixed by the index that
+/// `Graph::match_pattern` uses. The matches are sorted in the order of
+/// `compare_terms`.
+fn sorted_variable<'g, G>(pattern: &TriplePattern<'g, G>) -> Option<usize>
+    where G: Graph<'g>
+{
+    let (subject, predicate, object) = constants(pattern);
+    match (subject.is_some(), predicate.is_some(), object.is_some()) {
+        (true, true, _) => pattern.object.as_variable(),
+        (true, false, _) |
+        (false, false, true) => pattern.predicate.as_variable(),
+        (false, _, _) => pattern.subject.as_variable(),
+    }
+}
+
+/// The variables of the pattern that are already bound.
+fn shared_variables<'g, G>(pattern: &TriplePattern<'g, G>, bound: &[bool]) -> Vec<usize>
+    where G: Graph<'g>
+{
+    let mut shared: Vec<usize> = pattern.variables().into_iter().filter(|&v| bound[v]).collect();
+    shared.sort();
+    shared.dedup();
+    shared
+}
+
+fn use_merge_join<'g, G>(pattern: &TriplePattern<'g, G>,
+                         shared: &[usize],
+                         solutions: usize,
+                         estimate: usize)
+                         -> bool
+    where G: Graph<'g>
+{
+    if shared.is_empty() {
+        // the matches are the same for each solution, so read them once
+        return true;
+    }
+    if matches_sorted(pattern, shared) {
+        estimate <= solutions.saturating_mul(LOOKUP_COST)
+    } else {
+        estimate <= solutions
+    }
+}
+
+/// Bind the variables in the pattern to the parts of the triple.
+///
+/// Returns `false` if a variable is already bound to a different term.
+fn bind_triple<'g, G, T>(graph: &'g G,
+                         pattern: &TriplePattern<'g, G>,
+                         triple: &T,
+                         solution: &mut Solution<'g, G>)
+                         -> bool
+    where G: Graph<'g>,
+          T: Triple<'g, G::BlankNodePtr, G::IRIPtr, G::LiteralPtr, G::QuotedTriplePtr>
+{
+    if let PatternTerm::Variable(v) = pattern.subject {
+        if !solution.bind(v, resolve(graph, triple.subject().to_resource())) {
+            return false;
+        }
+    }
+    if let PatternTerm::Variable(v) = pattern.predicate {
+        if !solution.bind(v, resolve(graph, Resource::IRI(triple.predicate()))) {
+            return false;
+        }
+    }
+    if let PatternTerm::Variable(v) = pattern.object {
+        if !solution.bind(v, resolve(graph, triple.object())) {
+            return false;
+        }
+    }
+    true
+}
+
+/// Look up the pattern for each solution.
+fn nested_loop_join<'g, G>(graph: &'g G,
+                           pattern: &TriplePattern<'g, G>,
+                           solutions: &[Solution<'g, G>])
+                           -> Vec<Solution<'g, G>>
+    where G: Graph<'g>
+{
+    let mut result = Vec::new();
+    for solution in solutions {
+        let subject = match pattern.subject {
+            PatternTerm::Constant(ref subject) => Some(subject.clone()),
+            PatternTerm::Variable(v) => {
+                match solution.get(v).map(|s| s.to_blank_node_or_iri()) {
+                    // a literal cannot be a subject
+                    Some(None) => continue,
+                    Some(subject) => subject,
+                    None => None,
+                }
+            }
+        };
+        let predicate = match pattern.predicate {
+            PatternTerm::Constant(ref predicate) => Some(predicate.clone()),
+            PatternTerm::Variable(v) => {
+                match solution.get(v).map(|p| p.as_iri()) {
+                    // only an IRI can be a predicate
+                    Some(None) => continue,
+                    Some(predicate) => predicate.cloned(),
+                    None => None,
+                }
+            }
+        };
+        let object = match pattern.object {
+            PatternTerm::Constant(ref object) => Some(object.clone()),
+            PatternTerm::Variable(v) => solution.get(v).cloned(),
+        };
+        for triple in graph.match_pattern(subject.as_ref(), predicate.as_ref(), object.as_ref()) {
+            let mut solution = solution.clone();
+            if bind_triple(graph, pattern, &triple, &mut solution) {
+                result.push(solution);
+            }
+        }
+    }
+    result
+}
+
+/// Are the matches for the pattern sorted by the shared variables, because
+/// the index that they come from is sorted by them?
+fn matches_sorted<'g, G>(pattern: &TriplePattern<'g, G>, shared: &[usize]) -> bool
+    where G: Graph<'g>
+{
+    shared.len() == 1 && sorted_variable(pattern) == Some(shared[0])
+}
+
+/// The rank of a kind of term in the indexes of the graphs: blank nodes come
+/// first, then quoted triples, IRIs and literals.
+fn rank<'g, G>(term: &Term<'g, G>) -> u8
+    where G: Graph<'g>
+{
+    match *term {
+        Resource::BlankNode(_, _) => 0,
+        Resource::QuotedTriple(_) => 1,
+        Resource::IRI(_) => 2,
+        Resource::Literal(_) => 3,
+    }
+}
+
+/// Compare two terms in the order of the indexes of the graphs.
+///
+/// IRIs and literals are compared by value, because a `GraphCollection` can
+/// have different pointers for the same IRI or literal.
+fn compare_terms<'g, G>(a: &Term<'g, G>, b: &Term<'g, G>) -> Ordering
+    where G: Graph<'g>
+{
+    if let (Some(a), Some(b)) = (a.as_iri(), b.as_iri()) {
+        return a.as_str().cmp(b.as_str());
+    }
+    if let (Some(a), Some(b)) = (a.as_literal(), b.as_literal()) {
+        return (a.as_str(), a.datatype_str(), a.language())
+            .cmp(&(b.as_str(), b.datatype_str(), b.language()));
+    }
+    rank::<G>(a).cmp(&rank::<G>(b)).then_with(|| a.cmp(b))
+}
+
+fn compare_shared<'g, G>(a: &Solution<'g, G>, b: &Solution<'g, G>, shared: &[usize]) -> Ordering
+    where G: Graph<'g>
+{
+    for &v in shared {
+        let cmp = match (a.get(v), b.get(v)) {
+            (Some(a), Some(b)) => compare_terms::<G>(a, b),
+            (a, b) => a.is_some().cmp(&b.is_some()),
+        };
+        if cmp != Ordering::Equal {
+            return cmp;
+        }
+    }
+    Ordering::Equal
+}
+
+/// Sort the solutions by the shared variables, unless they are known to be
+/// sorted already because they come from an index or from an earlier merge
+/// join on the same variable.
+fn sort_by_shared<'g, G>(mut solutions: Vec<Solution<'g, G>>,
+                         shared: &[usize],
+                         sorted: bool)
+                         -> Vec<Solution<'g, G>>
+    where G: Graph<'g>
+{
+    if sorted {
+        debug_assert!(solutions.is_sorted_by(|a, b| compare_shared(a, b, shared).is_le()));
+    } else {
+        solutions.sort_by(|a, b| compare_shared(a, b, shared));
+    }
+    solutions
+}
+
+/// The end of the run of solutions with the same shared values as the
+/// solution at `start`.
+fn run_end<'g, G>(solutions: &[Solution<'g, G>], start: usize, shared: &[usize]) -> usize
+    where G: Graph<'g>
+{
+    let first = &solutions[start];
+    start + 1 +
+    solutions[start + 1..]
+        .iter()
+        .take_while(|s| compare_shared(first, s, shared) == Ordering::Equal)
+        .count()
+}
+
+/// Combine two solutions.
+///
+/// Returns `None` if the solutions bind a variable to different terms.
+fn combine<'g, G>(a: &Solution<'g, G>, b: &Solution<'g, G>) -> Option<Solution<'g, G>>
+    where G: Graph<'g>
+{
+    let mut solution = a.clone();
+    for (v, value) in b.values().iter().enumerate() {
+        if let Some(ref value) = *value {
+            if !solution.bind(v, value.clone()) {
+                return None;
+            }
+        }
+    }
+    Some(solution)
+}
+
+/// Read the matches for the pattern once and merge them with the solutions,
+/// which must be sorted by the shared variables.
+fn merge_join<'g, G>(graph: &'g G,
+                     pattern: &TriplePattern<'g, G>,
+                     shared: &[usize],
+                     solutions: Vec<Solution<'g, G>>,
+                     len: usize)
+                     -> Vec<Solution<'g, G>>
+    where G: Graph<'g>
+{
+    let (subject, predicate, object) = constants(pattern);
+    let mut matches = Vec::new();
+    for triple in graph.match_pattern(subject, predicate, object) {
+        let mut solution = Solution::new(len);
+        if bind_triple(graph, pattern, &triple, &mut solution) {
+            matches.push(solution);
+        }
+    }
+    let sorted = shared.is_empty() || matches_sorted(pattern, shared);
+    let matches = sort_by_shared(matches, shared, sorted);
+    let mut result = Vec::new();
+    let mut i = 0;
+    let mut j = 0;
+    while i < solutions.len() && j < matches.len() {
+        match compare_shared(&solutions[i], &matches[j], shared) {
+            Ordering::Less => i += 1,
+            Ordering::Greater => j += 1,
+            Ordering::Equal => {
+                let i_end = run_end(&solutions, i, shared);
+                let j_end = run_end(&matches, j, shared);
+                // terms that compare equal are not always the same term,
+                // so combine checks each pair
+                for a in &solutions[i..i_end] {
+                    result.extend(matches[j..j_end].iter().filter_map(|b| combine(a, b)));
+                }
+                i = i_end;
+                j = j_end;
+            }
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+const TURTLE: &'static str = "@prefix : <http://a.example/> .
+:alice :knows :bob, :carol ; :name \"Alice\" ; :age \"1\" .
+:bob :knows :carol, :bob ; :name \"Bob\"@en ; :age \"1\"@en .
+:carol :knows :alice ; :name \"Carol\" ; :age \"2\" .
+_:dave :knows :alice ; :name \"1\" .
+<< :carol :knows :alice >> :name \"2\" .
+";
+
+#[cfg(test)]
+fn brute_force<'g, G>(graph: &'g G, bgp: &BasicGraphPattern<'g, G>) -> Vec<Solution<'g, G>>
+    where G: Graph<'g>
+{
+    // the terms of a collection are compared after resolving them
+    let same = |a: Term<'g, G>, b: Term<'g, G>| resolve(graph, a) == resolve(graph, b);
+    let mut solutions = vec![Solution::new(bgp.variables().len())];
+    for pattern in bgp.patterns() {
+        let mut next = Vec::new();
+        for solution in &solutions {
+            for triple in graph.iter() {
+                let (subject, predicate, object) = constants(pattern);
+                if subject.is_none_or(|s| same(s.to_resource(), triple.subject().to_resource())) &&
+                   predicate.is_none_or(|p| {
+                       same(Resource::IRI(p.clone()), Resource::IRI(triple.predicate()))
+                   }) &&
+                   object.is_none_or(|o| same(o.clone(), triple.object())) {
+                    let mut solution = solution.clone();
+                    if bind_triple(graph, pattern, &triple, &mut solution) {
+                        next.push(solution);
+                    }
+                }
+            }
+        }
+        solutions = next;
+    }
+    solutions
+}
+
+#[cfg(test)]
+fn check_queries<'g, G>(graph: &'g G)
+    where G: Graph<'g>
+{
+    use graph::IRIPtr;
+    let iri = |i: &str| graph.find_iri(&format!("http://a.example/{}", i)).unwrap();
+    let var = PatternTerm::<G::IRIPtr>::Variable;
+    let queries = vec![// who knows someone who knows alice?
+                       vec![(var(0), PatternTerm::Constant(iri("knows")), var(1)),
+                            (var(1),
+                             PatternTerm::Constant(iri("knows")),
+                             PatternTerm::Constant(iri("alice")))],
+                       // who knows themselves?
+                       vec![(var(0), PatternTerm::Constant(iri("knows")), var(0))],
+                       // which subjects share a value for any two predicates?
+                       vec![(var(0), var(1), var(2)), (var(3), var(4), var(2))],
+                       // people with the same age and name
+                       vec![(var(0), PatternTerm::Constant(iri("age")), var(1)),
+                            (var(2), PatternTerm::Constant(iri("name")), var(1))],
+                       // names of people that know each other
+                       vec![(var(0), PatternTerm::Constant(iri("name")), var(1)),
+                            (var(2), PatternTerm::Constant(iri("name")), var(3)),
+                            (var(0), PatternTerm::Constant(iri("knows")), var(2)),
+                            (var(2), PatternTerm::Constant(iri("knows")), var(0))],
+                       // everything, twice
+                       vec![(var(0), var(1), var(2)), (var(3), var(4), var(5))],
+                       vec![]];
+    for query in queries {
+        let mut bgp = BasicGraphPattern::new();
+        for (s, p, o) in query {
+            for v in [&s, &p, &o].iter().filter_map(|t| t.as_variable()) {
+                while bgp.variables().len() <= v {
+                    let name = format!("v{}", bgp.variables().len());
+                    bgp.variable(&name);
+                }
+            }
+            let subject = match s {
+                PatternTerm::Variable(v) => PatternTerm::Variable(v),
+                PatternTerm::Constant(i) => PatternTerm::Constant(i.to_blank_node_or_iri()),
+            };
+            let object = match o {
+                PatternTerm::Variable(v) => PatternTerm::Variable(v),
+                PatternTerm::Constant(i) => PatternTerm::Constant(i.to_resource()),
+            };
+            bgp.add(TriplePattern {
+                subject: subject,
+                predicate: p,
+                object: object,
+            });
+        }
+        let compare = |a: &Solution<'g, G>, b: &Solution<'g, G>| a.values().cmp(b.values());
+        let mut expected = brute_force(graph, &bgp);
+        expected.sort_by(&compare);
+        let mut found = bgp.evaluate(graph);
+        found.sort_by(&compare);
+        assert!(found == expected);
+    }
+}
+
+#[test]
+fn test_evaluate() {
+    use graphs::mem::MemGraph;
+    use graphs::tel::{Graph128, Graph64};
+    use sparql::test_collection::GraphCollection;
+    use test_support::turtle_graph;
+    use io::TurtleParser;
+    let graph: Graph64 = turtle_graph(TURTLE);
+    check_queries(&graph);
+    let mut graph = MemGraph::new();
+    for step in TurtleParser::new(TURTLE, "http://a.example/", &mut graph).unwrap() {
+        step.unwrap();
+    }
+    check_queries(&graph);
+    // the matches of a collection come from two indexes, with blank nodes in
+    // both graphs and :alice :knows :bob in both
+    let (first, second) = TURTLE.split_at(TURTLE.find("\n:carol").unwrap() + 1);
+    let first: Graph64 = turtle_graph(&format!("{}_:eve :knows :bob ; :name \"2\" .\n", first));
+    let second: Graph128 = turtle_graph(&format!("@prefix : <http://a.example/> .
+:alice :knows :bob .
+{}",
+                                                 second));
+    check_queries(&GraphCollection::new((&first, &second)));
+}
+
+#[test]
+fn test_plan() {
+    use graphs::tel::Graph64;
+    use test_support::turtle_graph;
+    let graph: Graph64 = turtle_graph(TURTLE);
+    let iri = |i: &str| graph.find_iri(&format!("http://a.example/{}", i)).unwrap();
+    let mut bgp = BasicGraphPattern::new();
+    let x = bgp.variable("x");
+    let y = bgp.variable("y");
+    let z = bgp.variable("z");
+    // unrelated to the others, so it goes last
+    bgp.add(TriplePattern {
+        subject: PatternTerm::Variable(z),
+        predicate: PatternTerm::Variable(z),
+        object: PatternTerm::Variable(z),
+    });
+    bgp.add(TriplePattern {
+        subject: PatternTerm::Variable(x),
+        predicate: PatternTerm::Constant(iri("knows")),
+        object: PatternTerm::Variable(y),
+    });
+    // the smallest range, so it goes first
+    let carol = graph.find_literal("Carol", "http://www.w3.org/2001/XMLSchema#string", None);
+    bgp.add(TriplePattern {
+        subject: PatternTerm::Variable(y),
+        predicate: PatternTerm::Constant(iri("name")),
+        object: PatternTerm::Constant(Resource::Literal(carol.unwrap())),
+    });
+    assert_eq!(bgp.plan(&bgp.estimates(&graph)), vec![2, 1, 0]);
+    assert_eq!(bgp.evaluate(&graph).len(), 0);
+}
+
+#[test]
+fn test_estimate() {
+    use graphs::mem::MemGraph;
+    use graphs::tel::{Graph128, Graph64};
+    use sparql::test_collection::GraphCollection;
+    use io::TurtleParser;
+    use test_support::turtle_graph;
+    fn knows<'g, G>(graph: &'g G) -> TriplePattern<'g, G>
+        where G: Graph<'g>
+    {
+        TriplePattern {
+            subject: PatternTerm::Variable(0),
+            predicate: PatternTerm::Constant(graph.find_iri("http://a.example/knows").unwrap()),
+            object: PatternTerm::Variable(1),
+        }
+    }
+    // a MemGraph does not know the size of a range, so the matches are
+    // counted
+    let mut graph = MemGraph::new();
+    for step in TurtleParser::new(TURTLE, "http://a.example/", &mut graph).unwrap() {
+        step.unwrap();
+    }
+    assert_eq!(estimate(&graph, &knows(&graph)), 6);
+    // a collection adds up the sizes of the ranges in its graphs
+    let (first, second) = TURTLE.split_at(TURTLE.find("\n:carol").unwrap() + 1);
+    let first: Graph64 = turtle_graph(first);
+    let second: Graph128 = turtle_graph(&format!("@prefix : <http://a.example/> .\n{}", second));
+    let collection = GraphCollection::new((&first, &second));
+    assert_eq!(estimate(&collection, &knows(&collection)),
+               estimate(&first, &knows(&first)) + estimate(&second, &knows(&second)));
+}
@@ -0,0 +1,212 @@
+//! Queries on RDF graphs.
+//!
+//! A basic graph pattern is a set of triple patterns. A triple pattern is
+//! like a triple, but its subject, predicate and object can be variables.
+//! Evaluating a basic graph pattern on a graph gives all the ways in which
+//! the variables can be bound to terms of the graph so that every triple
+//! pattern becomes a triple in the graph. Each of those is a `Solution`.
+//!
+//! ```
+//! use rome::graph::{Graph, GraphWriter, IRIPtr};
+//! use rome::graphs::tel;
+//! use rome::query::{BasicGraphPattern, PatternTerm, TriplePattern};
+//!
+//! let mut creator = tel::GraphCreator::with_capacity(0);
+//! let alice = creator.create_iri(&"http://example.org/alice");
+//! let bob = creator.create_iri(&"http://example.org/bob");
+//! let carol = creator.create_iri(&"http://example.org/carol");
+//! let knows = creator.create_iri(&"http://xmlns.com/foaf/0.1/knows");
+//! creator.add_iri_iri(&alice, &knows, &bob);
+//! creator.add_iri_iri(&bob, &knows, &carol);
+//! let graph: tel::Graph64 = creator.collect();
+//!
+//! // who knows someone who knows carol?
+//! let knows = graph.find_iri("http://xmlns.com/foaf/0.1/knows").unwrap();
+//! let carol = graph.find_iri("http://example.org/carol").unwrap();
+//! let mut bgp = BasicGraphPattern::new();
+//! let x = bgp.variable("x");
+//! let y = bgp.variable("y");
+//! bgp.add(TriplePattern {
+//!     subject: PatternTerm::Variable(x),
+//!     predicate: PatternTerm::Constant(knows.clone()),
+//!     object: PatternTerm::Variable(y),
+//! });
+//! bgp.add(TriplePattern {
+//!     subject: PatternTerm::Variable(y),
+//!     predicate: PatternTerm::Constant(knows),
+//!     object: PatternTerm::Constant(carol.to_resource()),
+//! });
+//! let solutions = bgp.evaluate(&graph);
+//! assert_eq!(solutions.len(), 1);
+//! let x = solutions[0].get(x).and_then(|x| x.as_iri()).unwrap();
+//! assert_eq!(x.as_str(), "http://example.org/alice");
+//! ```
+
+mod bgp;
+mod path;
+
+pub use self::bgp::BasicGraphPattern;
+pub use self::path::PropertyPath;
+
+use graph::{BlankNodeOrIRI, Graph, IRIPtr, LiteralPtr, Resource};
+
+/// A term of graph `G` that can be bound to a variable.
+///
+/// Any term can be the object of a triple, so terms have the type of objects.
+pub type Term<'g, G> = Resource<'g,
+                                <G as Graph<'g>>::BlankNodePtr,
+                                <G as Graph<'g>>::IRIPtr,
+                                <G as Graph<'g>>::LiteralPtr,
+                                <G as Graph<'g>>::QuotedTriplePtr>;
+
+/// A term of graph `G` that can be the subject of a triple.
+pub type SubjectTerm<'g, G> = BlankNodeOrIRI<'g,
+                                             <G as Graph<'g>>::BlankNodePtr,
+                                             <G as Graph<'g>>::IRIPtr,
+                                             <G as Graph<'g>>::QuotedTriplePtr>;
+
+/// The term of `graph` that has the same value as `term`.
+///
+/// A `GraphCollection` has different pointers for an IRI or a literal,
+/// depending on the graphs that the triple it came from is in. Lookups with
+/// such a pointer only see those graphs. The pointer from `find_iri` or
+/// `find_literal` refers to every graph, so the terms that are bound to
+/// variables or reached along a path are looked up again.
+pub(crate) fn resolve<'g, G>(graph: &'g G, term: Term<'g, G>) -> Term<'g, G>
+    where G: Graph<'g>
+{
+    let found = match term {
+        Resource::IRI(ref iri) => graph.find_iri(iri.as_str()).map(Resource::IRI),
+        Resource::Literal(ref literal) => {
+            graph.find_literal(literal.as_str(), literal.datatype_str(), literal.language())
+                .map(Resource::Literal)
+        }
+        _ => None,
+    };
+    found.unwrap_or(term)
+}
+
+/// A part of a triple pattern: a variable or a constant term.
+///
+/// Variables are numbered. The numbers are handed out by
+/// `BasicGraphPattern::variable`.
+#[derive(PartialEq,Eq,Clone,Debug)]
+pub enum PatternTerm<T> {
+    /// This is a variable.
+    Variable(usize),
+    /// This is a constant.
+    Constant(T),
+}
+
+impl<T> PatternTerm<T> {
+    /// Get the variable, if this is a variable.
+    pub fn as_variable(&self) -> Option<usize> {
+        match *self {
+            PatternTerm::Variable(v) => Some(v),
+            PatternTerm::Constant(_) => None,
+        }
+    }
+    /// Get the constant, if this is a constant.
+    pub fn as_constant(&self) -> Option<&T> {
+        match *self {
+            PatternTerm::Variable(_) => None,
+            PatternTerm::Constant(ref c) => Some(c),
+        }
+    }
+}
+
+/// A triple in which the subject, predicate and object can be variables.
+pub struct TriplePattern<'g, G>
+    where G: Graph<'g>
+{
+    /// The subject of the pattern.
+    pub subject: PatternTerm<SubjectTerm<'g, G>>,
+    /// The predicate of the pattern.
+    pub predicate: PatternTerm<G::IRIPtr>,
+    /// The object of the pattern.
+    pub object: PatternTerm<Term<'g, G>>,
+}
+
+impl<'g, G> Clone for TriplePattern<'g, G>
+    where G: Graph<'g>
+{
+    fn clone(&self) -> Self {
+        TriplePattern {
+            subject: self.subject.clone(),
+            predicate: self.predicate.clone(),
+            object: self.object.clone(),
+        }
+    }
+}
+
+impl<'g, G> TriplePattern<'g, G>
+    where G: Graph<'g>
+{
+    /// The variables in the pattern in the order subject, predicate, object.
+    ///
+    /// A variable that occurs more than once in the pattern is returned more
+    /// than once.
+    pub fn variables(&self) -> Vec<usize> {
+        self.subject
+            .as_variable()
+            .into_iter()
+            .chain(self.predicate.as_variable())
+            .chain(self.object.as_variable())
+            .collect()
+    }
+}
+
+/// A binding of the variables of a query to terms in a graph.
+///
+/// Variables that are not bound have no value.
+pub struct Solution<'g, G>
+    where G: Graph<'g>
+{
+    values: Vec<Option<Term<'g, G>>>,
+}
+
+impl<'g, G> Solution<'g, G>
+    where G: Graph<'g>
+{
+    /// Create a solution for `len` variables in which no variable is bound.
+    pub fn new(len: usize) -> Solution<'g, G> {
+        Solution { values: vec![None; len] }
+    }
+    /// Get the value of a variable.
+    pub fn get(&self, variable: usize) -> Option<&Term<'g, G>> {
+        self.values.get(variable).and_then(|v| v.as_ref())
+    }
+    /// Bind a variable to a value.
+    ///
+    /// If the variable already has another value, the solution is not
+    /// changed and `false` is returned.
+    pub fn bind(&mut self, variable: usize, value: Term<'g, G>) -> bool {
+        match self.values[variable] {
+            Some(ref v) => *v == value,
+            None => {
+                self.values[variable] = Some(value);
+                true
+            }
+        }
+    }
+    /// The values of all variables, in the order of the variable numbers.
+    pub fn values(&self) -> &[Option<Term<'g, G>>] {
+        &self.values
+    }
+}
+
+impl<'g, G> Clone for Solution<'g, G>
+    where G: Graph<'g>
+{
+    fn clone(&self) -> Self {
+        Solution { values: self.values.clone() }
+    }
+}
+
+impl<'g, G> PartialEq for Solution<'g, G>
+    where G: Graph<'g>
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.values == other.values
+    }
+}
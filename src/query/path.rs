@@ -0,0 +1,326 @@
+use graph::{Graph, IRIPtr, Triple};
+use std::collections::BTreeSet;
+use super::{resolve, Term};
+
+/// A property path: a route through a graph along the predicates of triples.
+///
+/// Property paths are written as in SPARQL 1.1. For example,
+/// `rdfs:subClassOf*` connects a class to itself and to all its
+/// superclasses, and `^foaf:knows/foaf:name` connects a person to the names
+/// of the people that know that person. `PropertyPath::parse` reads a path
+/// in this syntax.
+///
+/// ```
+/// use rome::graph::{Graph, GraphWriter, IRIPtr};
+/// use rome::graphs::tel;
+/// use rome::namespaces::Namespaces;
+/// use rome::query::PropertyPath;
+///
+/// let mut creator = tel::GraphCreator::with_capacity(0);
+/// let a = creator.create_iri(&"http://example.org/a");
+/// let b = creator.create_iri(&"http://example.org/b");
+/// let c = creator.create_iri(&"http://example.org/c");
+/// let next = creator.create_iri(&"http://example.org/next");
+/// creator.add_iri_iri(&a, &next, &b);
+/// creator.add_iri_iri(&b, &next, &c);
+/// let graph: tel::Graph64 = creator.collect();
+///
+/// let mut namespaces = Namespaces::new();
+/// namespaces.set(b"ex", "http://example.org/");
+/// let path = PropertyPath::parse("ex:next+", &namespaces).unwrap();
+/// let a = graph.find_iri("http://example.org/a").unwrap().to_resource();
+/// let reached: Vec<_> = path.objects(&graph, &a)
+///     .into_iter()
+///     .map(|t| t.as_iri().unwrap().as_str().to_string())
+///     .collect();
+/// assert_eq!(reached, ["http://example.org/b", "http://example.org/c"]);
+/// ```
+#[derive(Clone,Debug,PartialEq,Eq)]
+pub enum PropertyPath {
+    /// A predicate: `iri`.
+    Predicate(String),
+    /// The path in the other direction: `^path`.
+    Inverse(Box<PropertyPath>),
+    /// The first path followed by the second: `path1/path2`.
+    Sequence(Box<PropertyPath>, Box<PropertyPath>),
+    /// Either of the two paths: `path1|path2`.
+    Alternative(Box<PropertyPath>, Box<PropertyPath>),
+    /// The path zero or more times: `path*`.
+    ZeroOrMore(Box<PropertyPath>),
+    /// The path one or more times: `path+`.
+    OneOrMore(Box<PropertyPath>),
+    /// The path zero times or once: `path?`.
+    ZeroOrOne(Box<PropertyPath>),
+    /// Any predicate that is not in the first list, or any predicate in the
+    /// other direction that is not in the second list: `!(iri|^iri)`.
+    /// Only the directions with a non-empty list are used, except that `!()`
+    /// matches any predicate.
+    NegatedPropertySet(Vec<String>, Vec<String>),
+}
+
+// `PropertyPath::parse` is implemented by the SPARQL parser.
+
+impl PropertyPath {
+    /// Can the path connect a term to itself without following a triple?
+    pub fn is_nullable(&self) -> bool {
+        match *self {
+            PropertyPath::Predicate(_) |
+            PropertyPath::OneOrMore(_) |
+            PropertyPath::NegatedPropertySet(_, _) => false,
+            PropertyPath::ZeroOrMore(_) |
+            PropertyPath::ZeroOrOne(_) => true,
+            PropertyPath::Inverse(ref path) => path.is_nullable(),
+            PropertyPath::Sequence(ref a, ref b) => a.is_nullable() && b.is_nullable(),
+            PropertyPath::Alternative(ref a, ref b) => a.is_nullable() || b.is_nullable(),
+        }
+    }
+    /// Find the terms that can be reached from `start` along the path.
+    ///
+    /// The terms are sorted and each term occurs once.
+    pub fn objects<'g, G>(&self, graph: &'g G, start: &Term<'g, G>) -> Vec<Term<'g, G>>
+        where G: Graph<'g>
+    {
+        self.step(graph, start, true).into_iter().collect()
+    }
+    /// Find the terms from which `end` can be reached along the path.
+    ///
+    /// The terms are sorted and each term occurs once.
+    pub fn subjects<'g, G>(&self, graph: &'g G, end: &Term<'g, G>) -> Vec<Term<'g, G>>
+        where G: Graph<'g>
+    {
+        self.step(graph, end, false).into_iter().collect()
+    }
+    /// Find the pairs of terms that are connected by the path.
+    ///
+    /// If `subject` or `object` is given, only the pairs that start or end
+    /// with that term are returned. If neither is given, a nullable path
+    /// connects each subject and object in the graph to itself.
+    pub fn pairs<'g, G>(&self,
+                        graph: &'g G,
+                        subject: Option<&Term<'g, G>>,
+                        object: Option<&Term<'g, G>>)
+                        -> Vec<(Term<'g, G>, Term<'g, G>)>
+        where G: Graph<'g>
+    {
+        match (subject, object) {
+            (Some(s), _) => {
+                self.step(graph, s, true)
+                    .into_iter()
+                    .filter(|o| object.is_none_or(|object| object == o))
+                    .map(|o| (s.clone(), o))
+                    .collect()
+            }
+            (None, Some(o)) => {
+                self.step(graph, o, false).into_iter().map(|s| (s, o.clone())).collect()
+            }
+            (None, None) => {
+                if let PropertyPath::Predicate(ref iri) = *self {
+                    return match graph.find_iri(iri) {
+                        Some(p) => {
+                            graph.iter_p(&p)
+                                .map(|t| {
+                                    (resolve(graph, t.subject().to_resource()),
+                                     resolve(graph, t.object()))
+                                })
+                                .collect()
+                        }
+                        None => Vec::new(),
+                    };
+                }
+                let mut nodes = BTreeSet::new();
+                for triple in graph.iter() {
+                    nodes.insert(resolve(graph, triple.subject().to_resource()));
+                    nodes.insert(resolve(graph, triple.object()));
+                }
+                nodes.into_iter()
+                    .flat_map(|s| {
+                        self.step(graph, &s, true).into_iter().map(move |o| (s.clone(), o))
+                    })
+                    .collect()
+            }
+        }
+    }
+    fn step<'g, G>(&self,
+                   graph: &'g G,
+                   start: &Term<'g, G>,
+                   forward: bool)
+                   -> BTreeSet<Term<'g, G>>
+        where G: Graph<'g>
+    {
+        let mut terms = BTreeSet::new();
+        self.add_steps(graph, start, forward, &mut terms);
+        terms
+    }
+    /// Add the terms that can be reached from `start`. If `forward` is
+    /// false, the path is followed in the other direction.
+    fn add_steps<'g, G>(&self,
+                        graph: &'g G,
+                        start: &Term<'g, G>,
+                        forward: bool,
+                        terms: &mut BTreeSet<Term<'g, G>>)
+        where G: Graph<'g>
+    {
+        match *self {
+            PropertyPath::Predicate(ref iri) => {
+                let predicate = match graph.find_iri(iri) {
+                    Some(predicate) => predicate,
+                    None => return,
+                };
+                if !forward {
+                    terms.extend(graph.iter_o_p(start, &predicate)
+                        .map(|t| resolve(graph, t.subject().to_resource())));
+                } else if let Some(subject) = start.to_blank_node_or_iri() {
+                    terms.extend(graph.iter_s_p(&subject, &predicate)
+                        .map(|t| resolve(graph, t.object())));
+                }
+            }
+            PropertyPath::Inverse(ref path) => path.add_steps(graph, start, !forward, terms),
+            PropertyPath::Sequence(ref a, ref b) => {
+                let (first, second) = if forward { (a, b) } else { (b, a) };
+                for term in first.step(graph, start, forward) {
+                    second.add_steps(graph, &term, forward, terms);
+                }
+            }
+            PropertyPath::Alternative(ref a, ref b) => {
+                a.add_steps(graph, start, forward, terms);
+                b.add_steps(graph, start, forward, terms);
+            }
+            PropertyPath::ZeroOrMore(ref path) => {
+                terms.insert(start.clone());
+                path.add_closure(graph, start, forward, terms);
+            }
+            PropertyPath::OneOrMore(ref path) => path.add_closure(graph, start, forward, terms),
+            PropertyPath::ZeroOrOne(ref path) => {
+                terms.insert(start.clone());
+                path.add_steps(graph, start, forward, terms);
+            }
+            PropertyPath::NegatedPropertySet(ref iris, ref inverse_iris) => {
+                if !iris.is_empty() || inverse_iris.is_empty() {
+                    add_neighbours(graph, start, forward, terms, |p: &G::IRIPtr| {
+                        !iris.iter().any(|iri| iri == p.as_str())
+                    });
+                }
+                if !inverse_iris.is_empty() {
+                    add_neighbours(graph, start, !forward, terms, |p: &G::IRIPtr| {
+                        !inverse_iris.iter().any(|iri| iri == p.as_str())
+                    });
+                }
+            }
+        }
+    }
+    /// Add the terms that can be reached from `start` by following the path
+    /// one or more times.
+    fn add_closure<'g, G>(&self,
+                          graph: &'g G,
+                          start: &Term<'g, G>,
+                          forward: bool,
+                          terms: &mut BTreeSet<Term<'g, G>>)
+        where G: Graph<'g>
+    {
+        let mut reached = BTreeSet::new();
+        let mut todo = vec![start.clone()];
+        while let Some(term) = todo.pop() {
+            for next in self.step(graph, &term, forward) {
+                if !reached.contains(&next) {
+                    reached.insert(next.clone());
+                    todo.push(next);
+                }
+            }
+        }
+        terms.extend(reached);
+    }
+}
+
+/// Add the objects of the triples with subject `start` or, if `forward` is
+/// false, the subjects of the triples with object `start`. Only the triples
+/// with a predicate that passes `accept` are used.
+fn add_neighbours<'g, G, F>(graph: &'g G,
+                            start: &Term<'g, G>,
+                            forward: bool,
+                            terms: &mut BTreeSet<Term<'g, G>>,
+                            accept: F)
+    where G: Graph<'g>,
+          F: Fn(&G::IRIPtr) -> bool
+{
+    if forward {
+        if let Some(subject) = start.to_blank_node_or_iri() {
+            terms.extend(graph.iter_s(&subject)
+                .filter(|t| accept(&t.predicate()))
+                .map(|t| resolve(graph, t.object())));
+        }
+    } else {
+        terms.extend(graph.iter_o(start)
+            .filter(|t| accept(&t.predicate()))
+            .map(|t| resolve(graph, t.subject().to_resource())));
+    }
+}
+
+#[cfg(test)]
+fn reached(path: &str, start: &str, forward: bool) -> Vec<String> {
+    use graphs::tel::Graph64;
+    use test_support::turtle_graph;
+    use namespaces::Namespaces;
+    let graph: Graph64 = turtle_graph("@prefix : <http://a.example/> .
+:a :next :b . :b :next :c . :c :next :a . :c :label \"c\" .
+:d :parent :e . :e :parent :f .
+");
+    let mut namespaces = Namespaces::new();
+    namespaces.set(b"", "http://a.example/");
+    let path = PropertyPath::parse(path, &namespaces).unwrap();
+    let start = graph.find_iri(&format!("http://a.example/{}", start)).unwrap().to_resource();
+    let terms = if forward {
+        path.objects(&graph, &start)
+    } else {
+        path.subjects(&graph, &start)
+    };
+    terms.into_iter()
+        .map(|t| match t.as_iri() {
+            Some(iri) => iri.as_str().replace("http://a.example/", ":"),
+            None => String::from("literal"),
+        })
+        .collect()
+}
+
+#[test]
+fn test_cycle() {
+    assert_eq!(reached(":next+", "a", true), [":a", ":b", ":c"]);
+    assert_eq!(reached(":next*", "d", true), [":d"]);
+}
+
+#[test]
+fn test_inverse() {
+    assert_eq!(reached("^:parent", "e", true), [":d"]);
+    assert_eq!(reached("^:parent+", "f", true), [":d", ":e"]);
+    assert_eq!(reached(":parent+", "f", false), [":d", ":e"]);
+}
+
+#[test]
+fn test_sequence_and_alternative() {
+    assert_eq!(reached(":next/:next", "a", true), [":c"]);
+    assert_eq!(reached(":next/:label", "b", true), ["literal"]);
+    assert_eq!(reached(":next|:parent", "d", true), [":e"]);
+    assert_eq!(reached(":parent?", "d", true), [":d", ":e"]);
+}
+
+#[test]
+fn test_negated_property_set() {
+    assert_eq!(reached("!:next", "c", true), ["literal"]);
+    assert_eq!(reached("!(:next|^:parent)", "e", true), [":f"]);
+    assert_eq!(reached("!(^:label)", "a", true), [":c"]);
+    assert!(reached("!(^:next)", "a", true).is_empty());
+}
+
+#[test]
+fn test_pairs() {
+    use graphs::tel::Graph64;
+    use test_support::turtle_graph;
+    let graph: Graph64 = turtle_graph("@prefix : <http://a.example/> .
+:d :parent :e . :e :parent :f .
+");
+    let parent = PropertyPath::Predicate(String::from("http://a.example/parent"));
+    let path = PropertyPath::OneOrMore(Box::new(parent));
+    assert_eq!(path.pairs(&graph, None, None).len(), 3);
+    let f = graph.find_iri("http://a.example/f").unwrap().to_resource();
+    assert_eq!(path.pairs(&graph, None, Some(&f)).len(), 2);
+    assert!(!path.is_nullable());
+}
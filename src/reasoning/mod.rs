@@ -0,0 +1,3 @@
+//! Inference of the triples that follow from the triples in a graph.
+
+pub mod rdfs;
@@ -0,0 +1,478 @@
+//! RDFS entailment.
+//!
+//! The RDFS entailment rules from
+//! [RDF 1.1 Semantics](https://www.w3.org/TR/rdf11-mt/#patterns-of-rdfs-entailment-informative)
+//! are applied to the triples of a graph until no new triples follow:
+//!
+//! - rdfs2 and rdfs3: the subjects of a property with an `rdfs:domain` and
+//!   the objects of a property with an `rdfs:range` get the class as type.
+//! - rdfs5 and rdfs11: `rdfs:subPropertyOf` and `rdfs:subClassOf` are
+//!   transitive.
+//! - rdfs6 and rdfs10: each `rdf:Property` is a sub-property of itself and
+//!   each `rdfs:Class` is a sub-class of itself.
+//! - rdfs7: a triple with a property is also a triple with each of its
+//!   super-properties.
+//! - rdfs8, rdfs12 and rdfs13: each `rdfs:Class` is a sub-class of
+//!   `rdfs:Resource`, each `rdfs:ContainerMembershipProperty` is a
+//!   sub-property of `rdfs:member` and each `rdfs:Datatype` is a sub-class
+//!   of `rdfs:Literal`.
+//! - rdfs9: the instances of a class are instances of its super-classes.
+//!
+//! The `rdfs:domain` and `rdfs:range` triples of the RDF and RDFS vocabulary
+//! from the axiomatic triples are used by rdfs2 and rdfs3, so that, for
+//! example, the subject and object of `rdfs:subClassOf` are an
+//! `rdfs:Class`. These triples are not added to the graph themselves. The
+//! rules rdfs1 and rdfs4, which give every datatype and every resource a
+//! type, are not applied, so the axiomatic triples with `rdfs:Resource` as
+//! domain or range are left out too.
+//!
+//! `infer` creates a graph with the new triples and `entail` creates a graph
+//! with the triples of the original graph and the new triples. Use `entail`
+//! rather than a `GraphCollection` of the original and the inferred graph:
+//! a collection treats the blank nodes of its graphs as different blank
+//! nodes.
+//!
+//! ```
+//! use rome::graph::{Graph, GraphWriter};
+//! use rome::graphs::tel;
+//! use rome::reasoning::rdfs;
+//!
+//! let mut creator = tel::GraphCreator::with_capacity(0);
+//! let sub_class_of = creator.create_iri(&"http://www.w3.org/2000/01/rdf-schema#subClassOf");
+//! let rdf_type = creator.create_iri(&"http://www.w3.org/1999/02/22-rdf-syntax-ns#type");
+//! let cat = creator.create_iri(&"http://example.org/Cat");
+//! let mammal = creator.create_iri(&"http://example.org/Mammal");
+//! let animal = creator.create_iri(&"http://example.org/Animal");
+//! let tom = creator.create_iri(&"http://example.org/tom");
+//! creator.add_iri_iri(&cat, &sub_class_of, &mammal);
+//! creator.add_iri_iri(&mammal, &sub_class_of, &animal);
+//! creator.add_iri_iri(&tom, &rdf_type, &cat);
+//! let graph: tel::Graph64 = creator.collect();
+//!
+//! // Cat subClassOf Animal, tom type Mammal, tom type Animal and 20 triples
+//! // that follow from the axiomatic triples, such as Cat type rdfs:Class
+//! let inferred: tel::Graph64 = rdfs::infer(&graph, tel::GraphCreator::with_capacity(0));
+//! assert_eq!(inferred.iter().count(), 23);
+//!
+//! // the original graph and the inferred triples as one graph
+//! let entailed: tel::Graph64 = rdfs::entail(&graph, tel::GraphCreator::with_capacity(0));
+//! assert_eq!(entailed.iter().count(), 26);
+//! ```
+
+use constants;
+use graph::{Graph, GraphWriter};
+use graphs::mem::{write_triples, IRIPtr, MemGraph, Object, Subject};
+use std::sync::Arc;
+
+/// Write the triples that follow from `graph` and that are not in `graph`
+/// to `writer`.
+pub fn infer<'g, G, W>(graph: &'g G, mut writer: W) -> W::Graph
+    where G: Graph<'g> + 'g,
+          W: GraphWriter<'g>
+{
+    let mut reasoner = Reasoner::new(graph);
+    reasoner.run();
+    write_triples(&reasoner.inferred, &mut writer);
+    writer.collect()
+}
+
+/// Write the triples of `graph` and the triples that follow from them to
+/// `writer`.
+pub fn entail<'g, G, W>(graph: &'g G, mut writer: W) -> W::Graph
+    where G: Graph<'g> + 'g,
+          W: GraphWriter<'g>
+{
+    let mut reasoner = Reasoner::new(graph);
+    reasoner.run();
+    reasoner.graph.write_to(&mut writer);
+    writer.collect()
+}
+
+/// The IRIs that are used by the rules.
+struct Vocabulary {
+    rdf_type: IRIPtr,
+    property: IRIPtr,
+    class: IRIPtr,
+    container_membership_property: IRIPtr,
+    datatype: IRIPtr,
+    domain: IRIPtr,
+    literal: IRIPtr,
+    member: IRIPtr,
+    range: IRIPtr,
+    resource: IRIPtr,
+    sub_class_of: IRIPtr,
+    sub_property_of: IRIPtr,
+    /// The axiomatic `rdfs:domain` triples.
+    domains: Vec<(IRIPtr, IRIPtr)>,
+    /// The axiomatic `rdfs:range` triples.
+    ranges: Vec<(IRIPtr, IRIPtr)>,
+}
+
+/// The axiomatic `rdfs:domain` triples without the ones with `rdfs:Resource`.
+const AXIOMATIC_DOMAINS: &'static [(&'static str, &'static str)] =
+    &[(constants::RDFS_DOMAIN, constants::RDF_PROPERTY),
+      (constants::RDFS_RANGE, constants::RDF_PROPERTY),
+      (constants::RDFS_SUB_PROPERTY_OF, constants::RDF_PROPERTY),
+      (constants::RDFS_SUB_CLASS_OF, constants::RDFS_CLASS),
+      (constants::RDF_SUBJECT, constants::RDF_STATEMENT),
+      (constants::RDF_PREDICATE, constants::RDF_STATEMENT),
+      (constants::RDF_OBJECT, constants::RDF_STATEMENT),
+      (constants::RDF_FIRST, constants::RDF_LIST),
+      (constants::RDF_REST, constants::RDF_LIST)];
+
+/// The axiomatic `rdfs:range` triples without the ones with `rdfs:Resource`.
+/// The range `rdfs:Literal` of `rdfs:label` and `rdfs:comment` is left out
+/// too: the rules give no types to literals.
+const AXIOMATIC_RANGES: &'static [(&'static str, &'static str)] =
+    &[(constants::RDF_TYPE, constants::RDFS_CLASS),
+      (constants::RDFS_DOMAIN, constants::RDFS_CLASS),
+      (constants::RDFS_RANGE, constants::RDFS_CLASS),
+      (constants::RDFS_SUB_PROPERTY_OF, constants::RDF_PROPERTY),
+      (constants::RDFS_SUB_CLASS_OF, constants::RDFS_CLASS),
+      (constants::RDF_REST, constants::RDF_LIST)];
+
+fn axioms(axioms: &[(&str, &str)]) -> Vec<(IRIPtr, IRIPtr)> {
+    axioms.iter().map(|&(property, class)| (iri(property), iri(class))).collect()
+}
+
+fn iri(iri: &str) -> IRIPtr {
+    IRIPtr { iri: Arc::from(iri) }
+}
+
+/// Applies the rules to a copy of a graph.
+///
+/// Each triple is matched against each premise of the rules once, so each
+/// pair of triples that matches the premises of a rule is found when the
+/// second of the two is matched.
+struct Reasoner {
+    graph: MemGraph,
+    /// The triples that were added to the graph.
+    inferred: Vec<(Subject, IRIPtr, Object)>,
+    /// The triples that have not been matched against the rules yet.
+    todo: Vec<(Subject, IRIPtr, Object)>,
+    vocabulary: Vocabulary,
+}
+
+impl Reasoner {
+    fn new<'g, G>(graph: &'g G) -> Reasoner
+        where G: Graph<'g> + 'g
+    {
+        let mut copy = MemGraph::new();
+        copy.add_graph(graph);
+        let todo = copy.iter().map(|t| t.to_owned()).collect();
+        Reasoner {
+            graph: copy,
+            inferred: Vec::new(),
+            todo: todo,
+            vocabulary: Vocabulary {
+                rdf_type: iri(constants::RDF_TYPE),
+                property: iri(constants::RDF_PROPERTY),
+                class: iri(constants::RDFS_CLASS),
+                container_membership_property: iri(constants::RDFS_CONTAINER_MEMBERSHIP_PROPERTY),
+                datatype: iri(constants::RDFS_DATATYPE),
+                domain: iri(constants::RDFS_DOMAIN),
+                literal: iri(constants::RDFS_LITERAL),
+                member: iri(constants::RDFS_MEMBER),
+                range: iri(constants::RDFS_RANGE),
+                resource: iri(constants::RDFS_RESOURCE),
+                sub_class_of: iri(constants::RDFS_SUB_CLASS_OF),
+                sub_property_of: iri(constants::RDFS_SUB_PROPERTY_OF),
+                domains: axioms(AXIOMATIC_DOMAINS),
+                ranges: axioms(AXIOMATIC_RANGES),
+            },
+        }
+    }
+    fn run(&mut self) {
+        while let Some((subject, predicate, object)) = self.todo.pop() {
+            self.apply(&subject, &predicate, &object);
+        }
+    }
+    fn add(&mut self, subject: Subject, predicate: &IRIPtr, object: Object) {
+        if self.graph.insert(subject.clone(), predicate.clone(), object.clone()) {
+            self.inferred.push((subject.clone(), predicate.clone(), object.clone()));
+            self.todo.push((subject, predicate.clone(), object));
+        }
+    }
+    /// The objects of the triples with the given subject and predicate.
+    fn objects(&self, subject: &Subject, predicate: &IRIPtr) -> Vec<Object> {
+        self.graph
+            .iter_s_p(&subject.to_blank_node_or_iri(), predicate)
+            .map(|t| t.to_owned().2)
+            .collect()
+    }
+    /// The classes in the axiomatic triples with the given property.
+    fn axiomatic(axioms: &[(IRIPtr, IRIPtr)], property: &IRIPtr) -> Vec<Object> {
+        axioms.iter()
+            .filter(|&&(ref p, _)| p == property)
+            .map(|&(_, ref class)| Object::IRI(class.clone()))
+            .collect()
+    }
+    /// The subjects of the triples with the given predicate and object.
+    fn subjects(&self, predicate: &IRIPtr, object: &Object) -> Vec<Subject> {
+        self.graph
+            .iter_o_p(&object.to_resource(), predicate)
+            .map(|t| t.to_owned().0)
+            .collect()
+    }
+    /// The subjects and objects of the triples with the given predicate.
+    fn pairs(&self, predicate: &IRIPtr) -> Vec<(Subject, Object)> {
+        self.graph
+            .iter_p(predicate)
+            .map(|t| {
+                let (subject, _, object) = t.to_owned();
+                (subject, object)
+            })
+            .collect()
+    }
+    /// Add the triples that follow from the triple and the triples in the
+    /// graph.
+    fn apply(&mut self, s: &Subject, p: &IRIPtr, o: &Object) {
+        let rdf_type = self.vocabulary.rdf_type.clone();
+        let sub_class_of = self.vocabulary.sub_class_of.clone();
+        let sub_property_of = self.vocabulary.sub_property_of.clone();
+        // rdfs2, rdfs3 and rdfs7 with the triple as the instance of the
+        // property
+        let property = Subject::IRI(p.clone());
+        let mut domains = self.objects(&property, &self.vocabulary.domain);
+        domains.extend(Reasoner::axiomatic(&self.vocabulary.domains, p));
+        for class in domains {
+            self.add(s.clone(), &rdf_type, class);
+        }
+        if let Some(object) = o.to_subject() {
+            let mut ranges = self.objects(&property, &self.vocabulary.range);
+            ranges.extend(Reasoner::axiomatic(&self.vocabulary.ranges, p));
+            for class in ranges {
+                self.add(object.clone(), &rdf_type, class);
+            }
+        }
+        for super_property in self.objects(&property, &sub_property_of) {
+            if let Object::IRI(super_property) = super_property {
+                self.add(s.clone(), &super_property, o.clone());
+            }
+        }
+        // the rules with the triple as a statement about a property or a
+        // class
+        if *p == self.vocabulary.domain || *p == self.vocabulary.range {
+            if let Subject::IRI(ref property) = *s {
+                let domain = *p == self.vocabulary.domain;
+                for (subject, object) in self.pairs(property) {
+                    let instance = if domain { Some(subject) } else { object.to_subject() };
+                    if let Some(instance) = instance {
+                        self.add(instance, &rdf_type, o.clone());
+                    }
+                }
+            }
+        } else if *p == sub_property_of {
+            self.add_transitive(s, &sub_property_of, o);
+            if let (Subject::IRI(property), Object::IRI(super_property)) = (s, o) {
+                for (subject, object) in self.pairs(property) {
+                    self.add(subject, super_property, object);
+                }
+            }
+        } else if *p == sub_class_of {
+            self.add_transitive(s, &sub_class_of, o);
+            for instance in self.subjects(&rdf_type, &s.to_object()) {
+                self.add(instance, &rdf_type, o.clone());
+            }
+        } else if *p == rdf_type {
+            if let Some(class) = o.to_subject() {
+                for super_class in self.objects(&class, &sub_class_of) {
+                    self.add(s.clone(), &rdf_type, super_class);
+                }
+            }
+            let class = match *o {
+                Object::IRI(ref class) => class,
+                _ => return,
+            };
+            if *class == self.vocabulary.property {
+                self.add(s.clone(), &sub_property_of, s.to_object());
+            } else if *class == self.vocabulary.class {
+                let resource = Object::IRI(self.vocabulary.resource.clone());
+                self.add(s.clone(), &sub_class_of, resource);
+                self.add(s.clone(), &sub_class_of, s.to_object());
+            } else if *class == self.vocabulary.container_membership_property {
+                let member = Object::IRI(self.vocabulary.member.clone());
+                self.add(s.clone(), &sub_property_of, member);
+            } else if *class == self.vocabulary.datatype {
+                let literal = Object::IRI(self.vocabulary.literal.clone());
+                self.add(s.clone(), &sub_class_of, literal);
+            }
+        }
+    }
+    /// rdfs5 and rdfs11: combine the triple with the triples before and
+    /// after it in a chain of triples with a transitive predicate.
+    fn add_transitive(&mut self, s: &Subject, p: &IRIPtr, o: &Object) {
+        if let Some(object) = o.to_subject() {
+            for next in self.objects(&object, p) {
+                self.add(s.clone(), p, next);
+            }
+        }
+        for previous in self.subjects(p, &s.to_object()) {
+            self.add(previous, p, o.clone());
+        }
+    }
+}
+
+/// The inferred triples in short form, without the triples about the RDF and
+/// RDFS vocabulary and without the `rdfs:Resource` types, which follow for
+/// most graphs.
+#[cfg(test)]
+fn inferred(turtle: &str) -> Vec<String> {
+    let mut triples = inferred_with_vocabulary(turtle);
+    triples.retain(|t| {
+        !t.starts_with("rdf:") && !t.starts_with("rdfs:") &&
+        !t.ends_with(" rdf:type rdfs:Resource")
+    });
+    triples
+}
+
+#[cfg(test)]
+fn inferred_with_vocabulary(turtle: &str) -> Vec<String> {
+    use graphs::tel::{Graph64, GraphCreator};
+    use test_support::turtle_graph;
+    use graph::{IRIPtr, Triple};
+    let graph: Graph64 = turtle_graph(turtle);
+    let inferred: Graph64 = infer(&graph, GraphCreator::with_capacity(0));
+    let short = |iri: &str| {
+        iri.replace("http://a.example/", ":")
+            .replace(constants::RDFS_NS, "rdfs:")
+            .replace(constants::RDF_NS, "rdf:")
+    };
+    let mut triples: Vec<String> = inferred.iter()
+        .map(|t| {
+            let subject = t.subject().as_iri().map(|i| short(i.as_str()));
+            let object = t.object().as_iri().map(|i| short(i.as_str()));
+            format!("{} {} {}",
+                    subject.unwrap_or_else(|| String::from("_:b")),
+                    short(t.predicate().as_str()),
+                    object.unwrap_or_else(|| String::from("_:b")))
+        })
+        .collect();
+    triples.sort();
+    triples
+}
+
+#[test]
+fn test_domain_and_range() {
+    assert_eq!(inferred("@prefix : <http://a.example/> .
+@prefix rdfs: <http://www.w3.org/2000/01/rdf-schema#> .
+:knows rdfs:domain :Person ; rdfs:range :Agent .
+:alice :knows :bob . :bob :knows \"carol\" .
+"),
+               [":Agent rdf:type rdfs:Class",
+                ":Agent rdfs:subClassOf :Agent",
+                ":Agent rdfs:subClassOf rdfs:Resource",
+                ":Person rdf:type rdfs:Class",
+                ":Person rdfs:subClassOf :Person",
+                ":Person rdfs:subClassOf rdfs:Resource",
+                ":alice rdf:type :Person",
+                ":bob rdf:type :Agent",
+                ":bob rdf:type :Person",
+                ":knows rdf:type rdf:Property",
+                ":knows rdfs:subPropertyOf :knows"]);
+}
+
+#[test]
+fn test_sub_property_of() {
+    assert_eq!(inferred("@prefix : <http://a.example/> .
+@prefix rdfs: <http://www.w3.org/2000/01/rdf-schema#> .
+:hasMother rdfs:subPropertyOf :hasParent .
+:hasParent rdfs:subPropertyOf :hasAncestor ; rdfs:domain :Person .
+:alice :hasMother :carol .
+"),
+               [":Person rdf:type rdfs:Class",
+                ":Person rdfs:subClassOf :Person",
+                ":Person rdfs:subClassOf rdfs:Resource",
+                ":alice :hasAncestor :carol",
+                ":alice :hasParent :carol",
+                ":alice rdf:type :Person",
+                ":hasAncestor rdf:type rdf:Property",
+                ":hasAncestor rdfs:subPropertyOf :hasAncestor",
+                ":hasMother rdf:type rdf:Property",
+                ":hasMother rdfs:subPropertyOf :hasAncestor",
+                ":hasMother rdfs:subPropertyOf :hasMother",
+                ":hasParent rdf:type rdf:Property",
+                ":hasParent rdfs:subPropertyOf :hasParent"]);
+}
+
+#[test]
+fn test_sub_class_of() {
+    // the rules must reach a fixpoint on cycles
+    assert_eq!(inferred("@prefix : <http://a.example/> .
+@prefix rdfs: <http://www.w3.org/2000/01/rdf-schema#> .
+:A rdfs:subClassOf :B . :B rdfs:subClassOf :A .
+_:x a :A .
+"),
+               [":A rdf:type rdfs:Class",
+                ":A rdfs:subClassOf :A",
+                ":A rdfs:subClassOf rdfs:Resource",
+                ":B rdf:type rdfs:Class",
+                ":B rdfs:subClassOf :B",
+                ":B rdfs:subClassOf rdfs:Resource",
+                "_:b rdf:type :B"]);
+}
+
+#[test]
+fn test_types() {
+    assert_eq!(inferred("@prefix : <http://a.example/> .
+@prefix rdf: <http://www.w3.org/1999/02/22-rdf-syntax-ns#> .
+@prefix rdfs: <http://www.w3.org/2000/01/rdf-schema#> .
+:p a rdf:Property . :C a rdfs:Class . :m a rdfs:ContainerMembershipProperty .
+:d a rdfs:Datatype .
+"),
+               [":C rdfs:subClassOf :C",
+                ":C rdfs:subClassOf rdfs:Resource",
+                ":d rdf:type rdfs:Class",
+                ":d rdfs:subClassOf :d",
+                ":d rdfs:subClassOf rdfs:Literal",
+                ":d rdfs:subClassOf rdfs:Resource",
+                ":m rdf:type rdf:Property",
+                ":m rdfs:subPropertyOf :m",
+                ":m rdfs:subPropertyOf rdfs:member",
+                ":p rdfs:subPropertyOf :p"]);
+}
+
+#[test]
+fn test_axiomatic_triples() {
+    // the subject and object of rdfs:subClassOf are classes and the
+    // vocabulary that is used gets its types too
+    assert_eq!(inferred_with_vocabulary("@prefix : <http://a.example/> .
+@prefix rdfs: <http://www.w3.org/2000/01/rdf-schema#> .
+:Cat rdfs:subClassOf :Animal .
+"),
+               [":Animal rdf:type rdfs:Class",
+                ":Animal rdf:type rdfs:Resource",
+                ":Animal rdfs:subClassOf :Animal",
+                ":Animal rdfs:subClassOf rdfs:Resource",
+                ":Cat rdf:type rdfs:Class",
+                ":Cat rdf:type rdfs:Resource",
+                ":Cat rdfs:subClassOf :Cat",
+                ":Cat rdfs:subClassOf rdfs:Resource",
+                "rdfs:Class rdf:type rdfs:Class",
+                "rdfs:Class rdf:type rdfs:Resource",
+                "rdfs:Class rdfs:subClassOf rdfs:Class",
+                "rdfs:Class rdfs:subClassOf rdfs:Resource",
+                "rdfs:Resource rdf:type rdfs:Class",
+                "rdfs:Resource rdf:type rdfs:Resource",
+                "rdfs:Resource rdfs:subClassOf rdfs:Resource"]);
+}
+
+#[test]
+fn test_entail() {
+    use graph::{IRIPtr, Triple};
+    use graphs::tel::{Graph64, GraphCreator};
+    use test_support::turtle_graph;
+    let graph: Graph64 = turtle_graph("@prefix : <http://a.example/> .
+@prefix rdfs: <http://www.w3.org/2000/01/rdf-schema#> .
+:Cat rdfs:subClassOf :Animal . _:tom a :Cat .
+");
+    let entailed: Graph64 = entail(&graph, GraphCreator::with_capacity(0));
+    // the two triples, _:tom a :Animal and the 16 triples that follow from
+    // the axiomatic triples
+    assert_eq!(entailed.iter().count(), 19);
+    let animal = entailed.find_iri("http://a.example/Animal").unwrap().to_resource();
+    let rdf_type = entailed.find_iri(constants::RDF_TYPE).unwrap();
+    let tom = entailed.iter_o_p(&animal, &rdf_type).next().unwrap().subject();
+    let cat = entailed.find_iri("http://a.example/Cat").unwrap().to_resource();
+    assert_eq!(entailed.iter_o_p(&cat, &rdf_type).next().unwrap().subject(), tom);
+}
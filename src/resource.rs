@@ -13,7 +13,11 @@ rb{($p:ident) =>
 }
 macro_rules!
 resource{() =>
-    (graph::Resource<'g, rb!(BlankNodePtr), rb!(IRIPtr), rb!(LiteralPtr)>)
+    (graph::Resource<'g,
+                     rb!(BlankNodePtr),
+                     rb!(IRIPtr),
+                     rb!(LiteralPtr),
+                     rb!(QuotedTriplePtr)>)
 }
 macro_rules!
 adapter{() =>
@@ -25,7 +29,7 @@ g{($p:ident) =>
 }
 macro_rules!
 g_resource{() =>
-    (graph::Resource<'g, g!(BlankNodePtr), g!(IRIPtr), g!(LiteralPtr)>)
+    (graph::Resource<'g, g!(BlankNodePtr), g!(IRIPtr), g!(LiteralPtr), g!(QuotedTriplePtr)>)
 }
 
 /// Base trait for all ontology traits.
@@ -0,0 +1,273 @@
+use query::PropertyPath;
+use super::value::Literal;
+
+/// An IRI or a literal in a query.
+#[derive(Clone,Debug,PartialEq)]
+pub enum Constant {
+    IRI(String),
+    Literal(Literal),
+}
+
+/// A part of a triple pattern.
+#[derive(Clone,Debug,PartialEq)]
+pub enum TermPattern {
+    Variable(usize),
+    Constant(Constant),
+    /// A blank node in a `CONSTRUCT` template. In other patterns, blank
+    /// nodes are variables.
+    BlankNode(usize),
+}
+
+#[derive(Clone,Debug,PartialEq)]
+pub struct TriplePattern {
+    pub subject: TermPattern,
+    pub predicate: TermPattern,
+    pub object: TermPattern,
+}
+
+/// The SPARQL algebra.
+///
+/// The variables are numbers that index `Query::variables`.
+#[derive(Clone,Debug,PartialEq)]
+pub enum GraphPattern {
+    BGP(Vec<TriplePattern>),
+    /// A property path between two terms. Paths that are predicates,
+    /// inverses or sequences are translated to triple patterns instead.
+    Path(TermPattern, PropertyPath, TermPattern),
+    Join(Box<GraphPattern>, Box<GraphPattern>),
+    LeftJoin(Box<GraphPattern>, Box<GraphPattern>, Option<Expression>),
+    Filter(Expression, Box<GraphPattern>),
+    Union(Box<GraphPattern>, Box<GraphPattern>),
+    Minus(Box<GraphPattern>, Box<GraphPattern>),
+    Extend(Box<GraphPattern>, usize, Expression),
+    Values(Vec<usize>, Vec<Vec<Option<Constant>>>),
+    /// Group by the expressions and compute the aggregates for each group.
+    /// A grouping expression can be bound to a variable.
+    Group(Box<GraphPattern>, Vec<(Expression, Option<usize>)>, Vec<(usize, Aggregate)>),
+    OrderBy(Box<GraphPattern>, Vec<OrderCondition>),
+    Project(Box<GraphPattern>, Vec<usize>),
+    Distinct(Box<GraphPattern>),
+    Reduced(Box<GraphPattern>),
+    /// Skip a number of solutions and limit the number of solutions.
+    Slice(Box<GraphPattern>, usize, Option<usize>),
+}
+
+impl GraphPattern {
+    /// Join two patterns. Adjacent basic graph patterns are merged.
+    pub fn join(a: GraphPattern, b: GraphPattern) -> GraphPattern {
+        match (a, b) {
+            (GraphPattern::BGP(mut a), GraphPattern::BGP(b)) => {
+                a.extend(b);
+                GraphPattern::BGP(a)
+            }
+            (GraphPattern::BGP(ref a), b) if a.is_empty() => b,
+            (a, GraphPattern::BGP(ref b)) if b.is_empty() => a,
+            (a, b) => GraphPattern::Join(Box::new(a), Box::new(b)),
+        }
+    }
+    /// Add the variables that can be bound by solutions of this pattern.
+    pub fn visible_variables(&self, variables: &mut Vec<usize>) {
+        fn add(variables: &mut Vec<usize>, v: usize) {
+            if !variables.contains(&v) {
+                variables.push(v);
+            }
+        }
+        match *self {
+            GraphPattern::BGP(ref triples) => {
+                for t in triples {
+                    for p in &[&t.subject, &t.predicate, &t.object] {
+                        if let TermPattern::Variable(v) = **p {
+                            add(variables, v);
+                        }
+                    }
+                }
+            }
+            GraphPattern::Path(ref subject, _, ref object) => {
+                for p in &[subject, object] {
+                    if let TermPattern::Variable(v) = **p {
+                        add(variables, v);
+                    }
+                }
+            }
+            GraphPattern::Join(ref a, ref b) |
+            GraphPattern::LeftJoin(ref a, ref b, _) |
+            GraphPattern::Union(ref a, ref b) => {
+                a.visible_variables(variables);
+                b.visible_variables(variables);
+            }
+            GraphPattern::Filter(_, ref a) |
+            GraphPattern::Minus(ref a, _) |
+            GraphPattern::OrderBy(ref a, _) |
+            GraphPattern::Distinct(ref a) |
+            GraphPattern::Reduced(ref a) |
+            GraphPattern::Slice(ref a, _, _) => a.visible_variables(variables),
+            GraphPattern::Extend(ref a, v, _) => {
+                a.visible_variables(variables);
+                add(variables, v);
+            }
+            GraphPattern::Values(ref vars, _) |
+            GraphPattern::Project(_, ref vars) => {
+                for &v in vars {
+                    add(variables, v);
+                }
+            }
+            GraphPattern::Group(_, ref keys, ref aggregates) => {
+                for &(_, v) in keys {
+                    if let Some(v) = v {
+                        add(variables, v);
+                    }
+                }
+                for &(v, _) in aggregates {
+                    add(variables, v);
+                }
+            }
+        }
+    }
+}
+
+#[derive(Clone,Copy,Debug,PartialEq,Eq)]
+pub enum Operator {
+    Or,
+    And,
+    Equal,
+    NotEqual,
+    Less,
+    Greater,
+    LessOrEqual,
+    GreaterOrEqual,
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+}
+
+#[derive(Clone,Debug,PartialEq)]
+pub enum Expression {
+    Variable(usize),
+    Constant(Constant),
+    Binary(Operator, Box<Expression>, Box<Expression>),
+    Not(Box<Expression>),
+    UnaryPlus(Box<Expression>),
+    UnaryMinus(Box<Expression>),
+    In(Box<Expression>, Vec<Expression>),
+    Bound(usize),
+    If(Box<Expression>, Box<Expression>, Box<Expression>),
+    Coalesce(Vec<Expression>),
+    Exists(Box<GraphPattern>),
+    Function(Function, Vec<Expression>),
+}
+
+#[derive(Clone,Debug,PartialEq)]
+pub enum Function {
+    Str,
+    Lang,
+    LangMatches,
+    Datatype,
+    /// `IRI` or `URI`, with the base IRI of the query.
+    IRI(Option<String>),
+    BNode,
+    Rand,
+    Abs,
+    Ceil,
+    Floor,
+    Round,
+    Concat,
+    StrLen,
+    UCase,
+    LCase,
+    EncodeForURI,
+    Contains,
+    StrStarts,
+    StrEnds,
+    StrBefore,
+    StrAfter,
+    SubStr,
+    Replace,
+    Regex,
+    Year,
+    Month,
+    Day,
+    Hours,
+    Minutes,
+    Seconds,
+    Timezone,
+    Tz,
+    Now,
+    UUID,
+    StrUUID,
+    StrLang,
+    StrDT,
+    SameTerm,
+    IsIRI,
+    IsBlank,
+    IsLiteral,
+    IsNumeric,
+    /// A cast to an XSD datatype, e.g. `xsd:integer(?x)`.
+    Cast(String),
+}
+
+#[derive(Clone,Debug,PartialEq)]
+pub enum AggregateFunction {
+    Count,
+    Sum,
+    Min,
+    Max,
+    Avg,
+    Sample,
+    /// `GROUP_CONCAT` with a separator.
+    GroupConcat(String),
+}
+
+#[derive(Clone,Debug,PartialEq)]
+pub struct Aggregate {
+    pub function: AggregateFunction,
+    pub distinct: bool,
+    /// The expression to aggregate. `COUNT(*)` has no expression.
+    pub expression: Option<Expression>,
+}
+
+#[derive(Clone,Debug,PartialEq)]
+pub struct OrderCondition {
+    pub expression: Expression,
+    pub descending: bool,
+}
+
+#[derive(Clone,Debug,PartialEq)]
+pub enum QueryForm {
+    /// `SELECT` with the projected variables.
+    Select(Vec<usize>),
+    Ask,
+    /// `CONSTRUCT` with the template.
+    Construct(Vec<TriplePattern>),
+    /// `DESCRIBE` with the resources to describe.
+    Describe(Vec<TermPattern>),
+}
+
+#[derive(Clone,Debug,PartialEq)]
+pub struct Query {
+    pub form: QueryForm,
+    pub pattern: GraphPattern,
+    /// The names of the variables. Variables for blank nodes and aggregates
+    /// have names that start with a space.
+    pub variables: Vec<String>,
+}
+
+/// An operation of a SPARQL update.
+#[derive(Clone,Debug,PartialEq)]
+pub enum UpdateOperation {
+    /// Delete and insert the triples of the templates for each solution of
+    /// the pattern. `INSERT DATA`, `DELETE DATA` and `DELETE WHERE` are
+    /// translated to this operation too.
+    Modify {
+        delete: Vec<TriplePattern>,
+        insert: Vec<TriplePattern>,
+        pattern: GraphPattern,
+        /// The names of the variables of the templates and the pattern.
+        variables: Vec<String>,
+    },
+    /// Remove all triples: `CLEAR DEFAULT` or `CLEAR ALL`.
+    Clear,
+    /// Add the triples in a local file. If the flag is set, the operation
+    /// is `SILENT` and errors are ignored.
+    Load(String, bool),
+}
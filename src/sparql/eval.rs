@@ -0,0 +1,758 @@
+use constants;
+use graph::{BlankNodeOrIRI, Graph, IRIPtr, LiteralPtr, Resource, Triple};
+use query::{BasicGraphPattern, PatternTerm, PropertyPath, SubjectTerm, Term,
+            TriplePattern as Pattern};
+use std::cmp::Ordering;
+use super::ast::*;
+use super::expression::{self, Functions};
+use super::value::{compare_optional_terms, compare_terms, Literal, Numeric, Value};
+
+/// The values of all variables of a query. Unbound variables are `None`.
+pub type Row<'g, G> = Vec<Option<Value<'g, G>>>;
+
+/// The result of a query.
+pub enum QueryResult<'g, G>
+    where G: Graph<'g>
+{
+    /// The solutions of a `SELECT` query.
+    Solutions(Solutions<'g, G>),
+    /// The result of an `ASK` query.
+    Boolean(bool),
+    /// The triples of a `CONSTRUCT` or `DESCRIBE` query.
+    Triples(Vec<[Value<'g, G>; 3]>),
+}
+
+/// The solutions of a `SELECT` query: a table with a column for each
+/// projected variable.
+pub struct Solutions<'g, G>
+    where G: Graph<'g>
+{
+    variables: Vec<String>,
+    rows: Vec<Row<'g, G>>,
+}
+
+impl<'g, G> Solutions<'g, G>
+    where G: Graph<'g>
+{
+    /// The names of the projected variables, without `?`.
+    pub fn variables(&self) -> &[String] {
+        &self.variables
+    }
+    /// The solutions. Each row has a value for each of the variables, or
+    /// `None` if the variable is not bound.
+    pub fn rows(&self) -> &[Vec<Option<Value<'g, G>>>] {
+        &self.rows
+    }
+    /// The number of solutions.
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+    /// Are there no solutions?
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+    /// Get the value of the variable `name` in solution `row`.
+    pub fn get(&self, row: usize, name: &str) -> Option<&Value<'g, G>> {
+        let column = self.variables.iter().position(|v| v == name)?;
+        self.rows.get(row).and_then(|r| r[column].as_ref())
+    }
+}
+
+/// Convert a constant from the query to a value.
+pub fn constant<'g, G>(constant: &Constant) -> Value<'g, G>
+    where G: Graph<'g>
+{
+    match *constant {
+        Constant::IRI(ref iri) => Value::IRI(iri.clone()),
+        Constant::Literal(ref literal) => Value::Literal(literal.clone()),
+    }
+}
+
+/// Merge two rows if they agree on the variables that are bound in both.
+pub fn merge<'g, G>(a: &Row<'g, G>, b: &Row<'g, G>) -> Option<Row<'g, G>>
+    where G: Graph<'g>
+{
+    a.iter()
+        .zip(b.iter())
+        .map(|(a, b)| match (a.as_ref(), b.as_ref()) {
+            (Some(a), Some(b)) if a != b => None,
+            (Some(a), _) | (None, Some(a)) => Some(Some(a.clone())),
+            (None, None) => Some(None),
+        })
+        .collect()
+}
+
+fn compare_keys<'g, G>(a: &Row<'g, G>, b: &Row<'g, G>, keys: &[usize]) -> Ordering
+    where G: Graph<'g>
+{
+    for &k in keys {
+        let cmp = compare_optional_terms(a[k].as_ref(), b[k].as_ref());
+        if cmp != Ordering::Equal {
+            return cmp;
+        }
+    }
+    Ordering::Equal
+}
+
+fn compare_rows<'g, G>(a: &Row<'g, G>, b: &Row<'g, G>) -> Ordering
+    where G: Graph<'g>
+{
+    for (a, b) in a.iter().zip(b.iter()) {
+        let cmp = compare_optional_terms(a.as_ref(), b.as_ref());
+        if cmp != Ordering::Equal {
+            return cmp;
+        }
+    }
+    Ordering::Equal
+}
+
+/// Remove duplicate rows. The first of each set of equal rows is kept.
+fn distinct<'g, G>(rows: Vec<Row<'g, G>>) -> Vec<Row<'g, G>>
+    where G: Graph<'g>
+{
+    let mut order: Vec<usize> = (0..rows.len()).collect();
+    order.sort_by(|&i, &j| compare_rows(&rows[i], &rows[j]).then(i.cmp(&j)));
+    let mut keep = vec![false; rows.len()];
+    for (k, &i) in order.iter().enumerate() {
+        keep[i] = k == 0 || compare_rows(&rows[order[k - 1]], &rows[i]) != Ordering::Equal;
+    }
+    rows.into_iter().zip(keep).filter(|&(_, keep)| keep).map(|(row, _)| row).collect()
+}
+
+/// The rows of `right` that can be merged with a row of `left`, sorted on
+/// the variables that are bound in all rows of both.
+struct JoinIndex<'a, 'g: 'a, G>
+    where G: Graph<'g> + 'a
+{
+    right: &'a [Row<'g, G>],
+    keys: Vec<usize>,
+    order: Vec<usize>,
+}
+
+impl<'a, 'g, G> JoinIndex<'a, 'g, G>
+    where G: Graph<'g>
+{
+    fn new(left: &[Row<'g, G>], right: &'a [Row<'g, G>], len: usize) -> JoinIndex<'a, 'g, G> {
+        let keys: Vec<usize> = (0..len)
+            .filter(|&v| {
+                left.iter().all(|r| r[v].is_some()) && right.iter().all(|r| r[v].is_some())
+            })
+            .collect();
+        let mut order: Vec<usize> = (0..right.len()).collect();
+        order.sort_by(|&i, &j| compare_keys(&right[i], &right[j], &keys));
+        JoinIndex {
+            right: right,
+            keys: keys,
+            order: order,
+        }
+    }
+    /// The merged rows of `row` and the compatible rows of `right`.
+    fn matches<'b>(&'b self, row: &'b Row<'g, G>) -> impl Iterator<Item = Row<'g, G>> + 'b {
+        let start = self.order
+            .partition_point(|&i| compare_keys(&self.right[i], row, &self.keys) == Ordering::Less);
+        self.order[start..]
+            .iter()
+            .take_while(move |&&i| compare_keys(&self.right[i], row, &self.keys) == Ordering::Equal)
+            .filter_map(move |&i| merge(row, &self.right[i]))
+    }
+}
+
+/// Evaluates the algebra of a query on a graph.
+pub struct Evaluator<'q, 'g, G>
+    where G: Graph<'g> + 'g
+{
+    graph: &'g G,
+    /// The names of the variables of the query.
+    variables: &'q [String],
+    functions: Functions,
+}
+
+impl<'q, 'g, G> Evaluator<'q, 'g, G>
+    where G: Graph<'g>
+{
+    pub fn new(graph: &'g G, variables: &'q [String]) -> Evaluator<'q, 'g, G> {
+        Evaluator {
+            graph: graph,
+            variables: variables,
+            functions: Functions::new(),
+        }
+    }
+    pub fn functions(&self) -> &Functions {
+        &self.functions
+    }
+    /// Is there a solution for `pattern` that is compatible with `row`?
+    pub fn exists(&self, pattern: &GraphPattern, row: &Row<'g, G>) -> bool {
+        !self.evaluate(pattern, row).is_empty()
+    }
+    /// Find the solutions of `pattern`. The variables that are bound in
+    /// `seed` are treated as constants.
+    pub fn evaluate(&self, pattern: &GraphPattern, seed: &Row<'g, G>) -> Vec<Row<'g, G>> {
+        match *pattern {
+            GraphPattern::BGP(ref triples) => self.bgp(triples, seed),
+            GraphPattern::Path(ref subject, ref path, ref object) => {
+                self.path(subject, path, object, seed)
+            }
+            GraphPattern::Join(ref a, ref b) => {
+                let left = self.evaluate(a, seed);
+                if left.is_empty() {
+                    return left;
+                }
+                let right = self.evaluate(b, seed);
+                let index = JoinIndex::new(&left, &right, seed.len());
+                left.iter().flat_map(|row| index.matches(row)).collect()
+            }
+            GraphPattern::LeftJoin(ref a, ref b, ref expression) => {
+                let left = self.evaluate(a, seed);
+                if left.is_empty() {
+                    return left;
+                }
+                let right = self.evaluate(b, seed);
+                let index = JoinIndex::new(&left, &right, seed.len());
+                let mut rows = Vec::new();
+                for row in &left {
+                    let len = rows.len();
+                    rows.extend(index.matches(row).filter(|merged| match *expression {
+                        Some(ref e) => self.filter(e, merged),
+                        None => true,
+                    }));
+                    if rows.len() == len {
+                        rows.push(row.clone());
+                    }
+                }
+                rows
+            }
+            GraphPattern::Filter(ref expression, ref a) => {
+                let mut rows = self.evaluate(a, seed);
+                rows.retain(|row| self.filter(expression, row));
+                rows
+            }
+            GraphPattern::Union(ref a, ref b) => {
+                let mut rows = self.evaluate(a, seed);
+                rows.extend(self.evaluate(b, seed));
+                rows
+            }
+            GraphPattern::Minus(ref a, ref b) => {
+                let mut rows = self.evaluate(a, seed);
+                let right = self.evaluate(b, seed);
+                rows.retain(|row| {
+                    !right.iter().any(|r| {
+                        let shared = row.iter().zip(r.iter()).any(|(a, b)| {
+                            a.is_some() && b.is_some()
+                        });
+                        shared && merge(row, r).is_some()
+                    })
+                });
+                rows
+            }
+            GraphPattern::Extend(ref a, v, ref expression) => {
+                let mut rows = self.evaluate(a, seed);
+                for row in &mut rows {
+                    if row[v].is_none() {
+                        row[v] = expression::evaluate(self, expression, row);
+                    }
+                }
+                rows
+            }
+            GraphPattern::Values(ref variables, ref values) => {
+                values.iter()
+                    .filter_map(|values| {
+                        let mut row = vec![None; seed.len()];
+                        for (&v, value) in variables.iter().zip(values) {
+                            row[v] = value.as_ref().map(constant);
+                        }
+                        merge(seed, &row)
+                    })
+                    .collect()
+            }
+            GraphPattern::Group(ref a, ref keys, ref aggregates) => {
+                let rows = self.evaluate(a, seed);
+                self.group(rows, keys, aggregates)
+            }
+            GraphPattern::OrderBy(ref a, ref conditions) => {
+                let rows = self.evaluate(a, seed);
+                let mut keyed: Vec<(Row<'g, G>, Row<'g, G>)> = rows.into_iter()
+                    .map(|row| {
+                        let key = conditions.iter()
+                            .map(|c| expression::evaluate(self, &c.expression, &row))
+                            .collect();
+                        (key, row)
+                    })
+                    .collect();
+                keyed.sort_by(|a, b| {
+                    for (c, (a, b)) in conditions.iter().zip(a.0.iter().zip(b.0.iter())) {
+                        let cmp = expression::compare_for_order(a.as_ref(), b.as_ref());
+                        if cmp != Ordering::Equal {
+                            return if c.descending { cmp.reverse() } else { cmp };
+                        }
+                    }
+                    Ordering::Equal
+                });
+                keyed.into_iter().map(|(_, row)| row).collect()
+            }
+            GraphPattern::Project(ref a, ref variables) => {
+                let mut rows = self.evaluate(a, seed);
+                for row in &mut rows {
+                    for (v, value) in row.iter_mut().enumerate() {
+                        if !variables.contains(&v) {
+                            *value = None;
+                        }
+                    }
+                }
+                rows
+            }
+            GraphPattern::Distinct(ref a) |
+            GraphPattern::Reduced(ref a) => distinct(self.evaluate(a, seed)),
+            GraphPattern::Slice(ref a, offset, limit) => {
+                let rows = self.evaluate(a, seed).into_iter().skip(offset);
+                match limit {
+                    Some(limit) => rows.take(limit).collect(),
+                    None => rows.collect(),
+                }
+            }
+        }
+    }
+    /// Is the effective boolean value of the expression true?
+    fn filter(&self, expression: &Expression, row: &Row<'g, G>) -> bool {
+        expression::evaluate(self, expression, row)
+            .and_then(|v| v.effective_boolean_value())
+            .unwrap_or(false)
+    }
+    /// Find the term in the graph that is equal to `value`.
+    pub fn find_term(&self, value: &Value<'g, G>) -> Option<Term<'g, G>> {
+        match *value {
+            Value::Term(ref t) => Some(t.clone()),
+            Value::IRI(ref iri) => self.graph.find_iri(iri).map(|i| i.to_resource()),
+            Value::Literal(ref l) => {
+                self.graph
+                    .find_literal(&l.lexical, &l.datatype, l.language.as_deref())
+                    .map(|l| l.to_resource())
+            }
+            Value::BlankNode(_) => None,
+        }
+    }
+    fn bgp(&self, triples: &[TriplePattern], seed: &Row<'g, G>) -> Vec<Row<'g, G>> {
+        let mut bgp = BasicGraphPattern::new();
+        // the query variable for each variable in bgp
+        let mut variables = Vec::new();
+        for triple in triples {
+            let mut term = |pattern: &TermPattern| -> Option<PatternTerm<Term<'g, G>>> {
+                match *pattern {
+                    TermPattern::Variable(v) => {
+                        match seed[v] {
+                            Some(ref value) => self.find_term(value).map(PatternTerm::Constant),
+                            None => {
+                                let local = bgp.variable(&self.variables[v]);
+                                if local == variables.len() {
+                                    variables.push(v);
+                                }
+                                Some(PatternTerm::Variable(local))
+                            }
+                        }
+                    }
+                    TermPattern::Constant(ref c) => {
+                        self.find_term(&constant(c)).map(PatternTerm::Constant)
+                    }
+                    TermPattern::BlankNode(_) => None,
+                }
+            };
+            let subject = match term(&triple.subject) {
+                Some(PatternTerm::Variable(v)) => PatternTerm::Variable(v),
+                Some(PatternTerm::Constant(t)) => {
+                    match to_subject::<G>(t) {
+                        Some(s) => PatternTerm::Constant(s),
+                        None => return Vec::new(),
+                    }
+                }
+                None => return Vec::new(),
+            };
+            let predicate = match term(&triple.predicate) {
+                Some(PatternTerm::Variable(v)) => PatternTerm::Variable(v),
+                Some(PatternTerm::Constant(Resource::IRI(i))) => PatternTerm::Constant(i),
+                _ => return Vec::new(),
+            };
+            let object = match term(&triple.object) {
+                Some(o) => o,
+                None => return Vec::new(),
+            };
+            bgp.add(Pattern {
+                subject: subject,
+                predicate: predicate,
+                object: object,
+            });
+        }
+        bgp.evaluate(self.graph)
+            .into_iter()
+            .map(|solution| {
+                let mut row = seed.clone();
+                for (local, &v) in variables.iter().enumerate() {
+                    if let Some(term) = solution.get(local) {
+                        row[v] = Some(Value::Term(term.clone()));
+                    }
+                }
+                row
+            })
+            .collect()
+    }
+    fn path(&self,
+            subject: &TermPattern,
+            path: &PropertyPath,
+            object: &TermPattern,
+            seed: &Row<'g, G>)
+            -> Vec<Row<'g, G>> {
+        let value = |pattern: &TermPattern| match *pattern {
+            TermPattern::Variable(v) => seed[v].clone(),
+            TermPattern::Constant(ref c) => Some(constant(c)),
+            TermPattern::BlankNode(_) => None,
+        };
+        let (s, o) = (value(subject), value(object));
+        let find = |value: &Option<Value<'g, G>>| value.as_ref().map(|v| self.find_term(v));
+        let pairs: Vec<(Value<'g, G>, Value<'g, G>)> = match (find(&s), find(&o)) {
+            (Some(None), _) | (_, Some(None)) => {
+                // a term that is not in the graph is only connected to
+                // itself, by a path of length zero
+                if !path.is_nullable() {
+                    return Vec::new();
+                }
+                match (s, o) {
+                    (Some(s), Some(o)) if s == o => vec![(s, o)],
+                    (Some(s), None) => vec![(s.clone(), s)],
+                    (None, Some(o)) => vec![(o.clone(), o)],
+                    _ => Vec::new(),
+                }
+            }
+            (s, o) => {
+                let (s, o) = (s.flatten(), o.flatten());
+                path.pairs(self.graph, s.as_ref(), o.as_ref())
+                    .into_iter()
+                    .map(|(s, o)| (Value::Term(s), Value::Term(o)))
+                    .collect()
+            }
+        };
+        pairs.into_iter()
+            .filter_map(|(s, o)| {
+                let mut row = seed.clone();
+                for &(pattern, ref value) in &[(subject, s), (object, o)] {
+                    if let TermPattern::Variable(v) = *pattern {
+                        match row[v] {
+                            Some(ref bound) if bound != value => return None,
+                            Some(_) => {}
+                            None => row[v] = Some(value.clone()),
+                        }
+                    }
+                }
+                Some(row)
+            })
+            .collect()
+    }
+    fn group(&self,
+             rows: Vec<Row<'g, G>>,
+             keys: &[(Expression, Option<usize>)],
+             aggregates: &[(usize, Aggregate)])
+             -> Vec<Row<'g, G>> {
+        let mut keyed: Vec<(Row<'g, G>, Row<'g, G>)> = rows.into_iter()
+            .map(|row| {
+                let key = keys.iter().map(|k| expression::evaluate(self, &k.0, &row)).collect();
+                (key, row)
+            })
+            .collect();
+        keyed.sort_by(|a, b| compare_rows(&a.0, &b.0));
+        let mut groups: Vec<(&Row<'g, G>, Vec<&Row<'g, G>>)> = Vec::new();
+        for (key, row) in &keyed {
+            match groups.last_mut() {
+                Some(&mut (k, ref mut rows)) if compare_rows(k, key) == Ordering::Equal => {
+                    rows.push(row);
+                    continue;
+                }
+                _ => {}
+            }
+            groups.push((key, vec![row]));
+        }
+        let empty = Vec::new();
+        if groups.is_empty() && keys.is_empty() {
+            groups.push((&empty, Vec::new()));
+        }
+        let len = self.variables.len();
+        groups.into_iter()
+            .map(|(key, rows)| {
+                let mut row = vec![None; len];
+                for (&(ref expression, v), value) in keys.iter().zip(key.iter()) {
+                    match (expression, v) {
+                        (_, Some(v)) |
+                        (&Expression::Variable(v), None) => row[v] = value.clone(),
+                        _ => {}
+                    }
+                }
+                for &(v, ref aggregate) in aggregates {
+                    row[v] = self.aggregate(aggregate, &rows);
+                }
+                row
+            })
+            .collect()
+    }
+    fn aggregate(&self, aggregate: &Aggregate, rows: &[&Row<'g, G>]) -> Option<Value<'g, G>> {
+        let mut values: Vec<Value<'g, G>> = match aggregate.expression {
+            Some(ref e) => {
+                rows.iter().filter_map(|row| expression::evaluate(self, e, row)).collect()
+            }
+            None => {
+                let count = if aggregate.distinct {
+                    distinct(rows.iter().map(|&r| r.clone()).collect()).len()
+                } else {
+                    rows.len()
+                };
+                return Some(integer(count as i64));
+            }
+        };
+        if aggregate.distinct {
+            values.sort_by(compare_terms);
+            values.dedup();
+        }
+        match aggregate.function {
+            AggregateFunction::Count => Some(integer(values.len() as i64)),
+            AggregateFunction::Sum => sum(&values).map(|n| Value::Literal(n.to_literal())),
+            AggregateFunction::Avg => {
+                if values.is_empty() {
+                    return Some(integer(0));
+                }
+                let sum = sum(&values)?;
+                expression::arithmetic(Operator::Divide,
+                                       sum,
+                                       Numeric::Integer(values.len() as i64))
+                    .map(|n| Value::Literal(n.to_literal()))
+            }
+            AggregateFunction::Min => {
+                values.into_iter()
+                    .min_by(|a, b| expression::compare_for_order(Some(a), Some(b)))
+            }
+            AggregateFunction::Max => {
+                values.into_iter()
+                    .max_by(|a, b| expression::compare_for_order(Some(a), Some(b)))
+            }
+            AggregateFunction::Sample => values.into_iter().next(),
+            AggregateFunction::GroupConcat(ref separator) => {
+                let strings: Option<Vec<String>> = values.iter()
+                    .map(|v| v.literal().filter(|l| l.is_string()).map(|l| l.lexical.to_string()))
+                    .collect();
+                Some(Value::Literal(Literal::string(strings?.join(separator))))
+            }
+        }
+    }
+}
+
+fn integer<'g, G>(i: i64) -> Value<'g, G>
+    where G: Graph<'g>
+{
+    Value::Literal(Literal::new(i.to_string(), constants::XSD_INTEGER))
+}
+
+fn sum<'g, G>(values: &[Value<'g, G>]) -> Option<Numeric>
+    where G: Graph<'g>
+{
+    let mut sum = Numeric::Integer(0);
+    for value in values {
+        let n = Numeric::from_literal(value.literal()?)?;
+        sum = expression::arithmetic(Operator::Add, sum, n)?;
+    }
+    Some(sum)
+}
+
+fn to_subject<'g, G>(term: Term<'g, G>) -> Option<SubjectTerm<'g, G>>
+    where G: Graph<'g>
+{
+    match term {
+        Resource::BlankNode(b, p) => Some(BlankNodeOrIRI::BlankNode(b, p)),
+        Resource::IRI(i) => Some(BlankNodeOrIRI::IRI(i)),
+        Resource::QuotedTriple(t) => Some(BlankNodeOrIRI::QuotedTriple(t)),
+        Resource::Literal(_) => None,
+    }
+}
+
+fn subject_to_term<'g, G>(subject: SubjectTerm<'g, G>) -> Term<'g, G>
+    where G: Graph<'g>
+{
+    match subject {
+        BlankNodeOrIRI::BlankNode(b, p) => Resource::BlankNode(b, p),
+        BlankNodeOrIRI::IRI(i) => Resource::IRI(i),
+        BlankNodeOrIRI::QuotedTriple(t) => Resource::QuotedTriple(t),
+    }
+}
+
+/// Create a term of a template triple for a solution.
+///
+/// The blank nodes of the template are numbered from `blank_nodes`.
+fn instantiate<'g, G>(pattern: &TermPattern,
+                      row: &Row<'g, G>,
+                      blank_nodes: usize)
+                      -> Option<Value<'g, G>>
+    where G: Graph<'g>
+{
+    match *pattern {
+        TermPattern::Variable(v) => row[v].clone(),
+        TermPattern::Constant(ref c) => Some(constant(c)),
+        TermPattern::BlankNode(b) => Some(Value::BlankNode(blank_nodes + b)),
+    }
+}
+
+/// Create the triples of a template for each solution.
+///
+/// Each solution gets new blank nodes. Triples with an unbound variable, a
+/// literal as subject or a predicate that is not an IRI are left out.
+pub fn construct<'q, 'g, G>(evaluator: &Evaluator<'q, 'g, G>,
+                            template: &[TriplePattern],
+                            rows: &[Row<'g, G>])
+                            -> Vec<[Value<'g, G>; 3]>
+    where G: Graph<'g>
+{
+    let blank_nodes = template.iter()
+        .flat_map(|t| vec![&t.subject, &t.predicate, &t.object])
+        .filter_map(|p| match *p {
+            TermPattern::BlankNode(b) => Some(b + 1),
+            _ => None,
+        })
+        .max()
+        .unwrap_or(0);
+    let mut triples = Vec::new();
+    for row in rows {
+        let first = evaluator.functions().new_blank_nodes(blank_nodes);
+        for t in template {
+            let triple = (instantiate(&t.subject, row, first),
+                          instantiate(&t.predicate, row, first),
+                          instantiate(&t.object, row, first));
+            if let (Some(s), Some(p), Some(o)) = triple {
+                if s.literal().is_none() && p.iri().is_some() {
+                    triples.push([s, p, o]);
+                }
+            }
+        }
+    }
+    triples
+}
+
+fn compare_triples<'g, G>(a: &[Value<'g, G>; 3], b: &[Value<'g, G>; 3]) -> Ordering
+    where G: Graph<'g>
+{
+    compare_terms(&a[0], &b[0])
+        .then_with(|| compare_terms(&a[1], &b[1]))
+        .then_with(|| compare_terms(&a[2], &b[2]))
+}
+
+/// Evaluate a query on a graph.
+pub fn evaluate<'g, G>(query: &Query, graph: &'g G) -> QueryResult<'g, G>
+    where G: Graph<'g>
+{
+    let evaluator = Evaluator::new(graph, &query.variables);
+    let seed = vec![None; query.variables.len()];
+    let rows = evaluator.evaluate(&query.pattern, &seed);
+    match query.form {
+        QueryForm::Select(ref variables) => {
+            QueryResult::Solutions(Solutions {
+                variables: variables.iter().map(|&v| query.variables[v].clone()).collect(),
+                rows: rows.into_iter()
+                    .map(|mut row| variables.iter().map(|&v| row[v].take()).collect())
+                    .collect(),
+            })
+        }
+        QueryForm::Ask => QueryResult::Boolean(!rows.is_empty()),
+        QueryForm::Construct(ref template) => {
+            let mut triples = construct(&evaluator, template, &rows);
+            triples.sort_by(compare_triples);
+            triples.dedup_by(|a, b| compare_triples(a, b) == Ordering::Equal);
+            QueryResult::Triples(triples)
+        }
+        QueryForm::Describe(ref resources) => {
+            let mut values = Vec::new();
+            for resource in resources {
+                match *resource {
+                    TermPattern::Variable(v) => {
+                        values.extend(rows.iter().filter_map(|row| row[v].clone()))
+                    }
+                    TermPattern::Constant(ref c) => values.push(constant(c)),
+                    TermPattern::BlankNode(_) => {}
+                }
+            }
+            values.sort_by(compare_terms);
+            values.dedup();
+            let mut triples = Vec::new();
+            for value in &values {
+                let subject = match evaluator.find_term(value).and_then(to_subject::<G>) {
+                    Some(subject) => subject,
+                    None => continue,
+                };
+                for triple in graph.iter_s(&subject) {
+                    triples.push([Value::Term(subject_to_term::<G>(triple.subject())),
+                                  Value::Term(triple.predicate().to_resource()),
+                                  Value::Term(triple.object())]);
+                }
+            }
+            QueryResult::Triples(triples)
+        }
+    }
+}
+
+#[test]
+fn test_errors_leave_variables_unbound() {
+    use graphs::tel::Graph64;
+    use sparql::{run, TURTLE};
+    use test_support::turtle_graph;
+    let graph: Graph64 = turtle_graph(TURTLE);
+    assert_eq!(run(&graph, "SELECT ?p ?x WHERE { ?p :age ?a BIND(?a / 0 AS ?x) }"),
+               vec![":alice -", ":bob -", ":carol -"]);
+    assert_eq!(run(&graph,
+                   "SELECT ?n (?n + 1 AS ?x) (COALESCE(?n + 1, 0) AS ?y)
+                    WHERE { :alice :name ?n }"),
+               vec!["\"Alice\" - \"0\"^^xsd:integer"]);
+    // an error in a filter removes the solution, also when it is negated
+    assert_eq!(run(&graph, "SELECT ?p WHERE { ?p :name ?n FILTER(!(?n > 1)) }"),
+               Vec::<String>::new());
+    assert_eq!(run(&graph, "SELECT ?p WHERE { ?p :age ?a FILTER(?n > 1 || ?a > 30) }"),
+               vec![":carol"]);
+}
+
+#[test]
+fn test_filter_in_optional() {
+    use graphs::tel::Graph64;
+    use sparql::{run, TURTLE};
+    use test_support::turtle_graph;
+    let graph: Graph64 = turtle_graph(TURTLE);
+    // the filter of an optional group can use the variables of the group it
+    // extends
+    assert_eq!(run(&graph,
+                   "SELECT ?n ?a WHERE {
+                      ?p :name ?n OPTIONAL { ?p :age ?a FILTER(?n != \"Alice\") }
+                    }"),
+               vec!["\"Alice\" -",
+                    "\"Bob\"@en \"25\"^^xsd:integer",
+                    "\"Carol\" \"35\"^^xsd:integer",
+                    "\"Dave\" -"]);
+    // in a nested group they are not in scope
+    assert_eq!(run(&graph,
+                   "SELECT ?n ?a WHERE {
+                      ?p :name ?n OPTIONAL { { ?p :age ?a FILTER(?n != \"Alice\") } }
+                    }"),
+               vec!["\"Alice\" -", "\"Bob\"@en -", "\"Carol\" -", "\"Dave\" -"]);
+    // a filter after the optional group applies to all solutions
+    assert_eq!(run(&graph,
+                   "SELECT ?n ?a WHERE { ?p :name ?n OPTIONAL { ?p :age ?a } FILTER(!BOUND(?a)) }"),
+               vec!["\"Dave\" -"]);
+}
+
+#[test]
+fn test_aggregates_of_empty_groups() {
+    use graphs::tel::Graph64;
+    use sparql::{run, TURTLE};
+    use test_support::turtle_graph;
+    let graph: Graph64 = turtle_graph(TURTLE);
+    // without GROUP BY, there is one group, also if it is empty
+    assert_eq!(run(&graph,
+                   "SELECT (COUNT(*) AS ?c) (SUM(?a) AS ?s) (AVG(?a) AS ?v) (MIN(?a) AS ?min)
+                           (SAMPLE(?a) AS ?x) (GROUP_CONCAT(?a) AS ?g)
+                    WHERE { ?p :missing ?a }"),
+               vec!["\"0\"^^xsd:integer \"0\"^^xsd:integer \"0\"^^xsd:integer - - \"\""]);
+    assert_eq!(run(&graph, "SELECT ?p (COUNT(*) AS ?c) WHERE { ?p :missing ?a } GROUP BY ?p"),
+               Vec::<String>::new());
+    // the values for which the expression fails are left out
+    assert_eq!(run(&graph,
+                   "SELECT (COUNT(?a) AS ?c) (SUM(?a) AS ?s) (AVG(?a) AS ?v)
+                    WHERE { ?p :name ?n OPTIONAL { ?p :age ?a } FILTER(!BOUND(?a)) }"),
+               vec!["\"0\"^^xsd:integer \"0\"^^xsd:integer \"0\"^^xsd:integer"]);
+}
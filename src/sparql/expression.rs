@@ -0,0 +1,762 @@
+use constants;
+use graph::Graph;
+use iri;
+use rand;
+use regex::Regex;
+use std::cell::{Cell, RefCell};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+use super::ast::{Expression, Function, Operator};
+use super::eval::{constant, Evaluator, Row};
+use super::value::{compare_terms, is_numeric_datatype, Decimal, Literal, LiteralRef, Numeric,
+                   Value};
+
+/// The state of the functions that are not deterministic.
+pub struct Functions {
+    blank_nodes: Cell<usize>,
+    regexes: RefCell<HashMap<(String, String), Option<Regex>>>,
+    now: Literal,
+}
+
+impl Functions {
+    pub fn new() -> Functions {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+        Functions {
+            blank_nodes: Cell::new(0),
+            regexes: RefCell::new(HashMap::new()),
+            now: Literal::new(DateTime::from_unix(now.as_secs() as i64, now.subsec_millis())
+                                  .to_string(),
+                              constants::XSD_DATE_TIME),
+        }
+    }
+    /// Reserve `count` new blank nodes and return the number of the first.
+    pub fn new_blank_nodes(&self, count: usize) -> usize {
+        let first = self.blank_nodes.get();
+        self.blank_nodes.set(first + count);
+        first
+    }
+    /// Compile a regular expression with the flags of `REGEX` and `REPLACE`.
+    fn regex(&self, pattern: &str, flags: &str) -> Option<Regex> {
+        let key = (String::from(pattern), String::from(flags));
+        self.regexes
+            .borrow_mut()
+            .entry(key)
+            .or_insert_with(|| {
+                let mut inline = String::new();
+                for flag in flags.chars() {
+                    match flag {
+                        'i' | 's' | 'm' | 'x' => inline.push(flag),
+                        'q' => {}
+                        _ => return None,
+                    }
+                }
+                let pattern = if flags.contains('q') {
+                    ::regex::escape(pattern)
+                } else {
+                    String::from(pattern)
+                };
+                if inline.is_empty() {
+                    Regex::new(&pattern).ok()
+                } else {
+                    Regex::new(&format!("(?{}){}", inline, pattern)).ok()
+                }
+            })
+            .clone()
+    }
+}
+
+/// A date and time in the lexical space of `xsd:dateTime`.
+#[derive(Clone,Copy,Debug,PartialEq)]
+struct DateTime {
+    year: i64,
+    month: u32,
+    day: u32,
+    hour: u32,
+    minute: u32,
+    second: f64,
+    /// The offset from UTC in minutes.
+    timezone: Option<i32>,
+}
+
+// The number of days since 1970-01-01 for a date in the proleptic Gregorian
+// calendar.
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month = i64::from(month);
+    let day_of_year = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 +
+                      i64::from(day) - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+impl DateTime {
+    fn parse(s: &str) -> Option<DateTime> {
+        let (negative, s) = match s.strip_prefix('-') {
+            Some(s) => (true, s),
+            None => (false, s),
+        };
+        let t = s.find('T')?;
+        let mut date = s[..t].split('-');
+        let year: i64 = date.next()?.parse().ok()?;
+        let month: u32 = date.next()?.parse().ok()?;
+        let day: u32 = date.next()?.parse().ok()?;
+        let time = &s[t + 1..];
+        let (time, timezone) = if let Some(time) = time.strip_suffix('Z') {
+            (time, Some(0))
+        } else if time.len() > 6 &&
+                  (time.as_bytes()[time.len() - 6] == b'+' ||
+                   time.as_bytes()[time.len() - 6] == b'-') {
+            let (time, tz) = time.split_at(time.len() - 6);
+            let hours: i32 = tz[1..3].parse().ok()?;
+            let minutes: i32 = tz[4..].parse().ok()?;
+            let offset = hours * 60 + minutes;
+            (time, Some(if tz.starts_with('-') { -offset } else { offset }))
+        } else {
+            (time, None)
+        };
+        let mut parts = time.split(':');
+        let hour: u32 = parts.next()?.parse().ok()?;
+        let minute: u32 = parts.next()?.parse().ok()?;
+        let second_str = parts.next()?;
+        let second: f64 = second_str.parse().ok()?;
+        if date.next().is_some() || parts.next().is_some() || month == 0 || month > 12 ||
+           day == 0 || day > 31 || hour > 24 || minute > 59 || !(0. ..60.).contains(&second) ||
+           second_str.len() < 2 {
+            return None;
+        }
+        Some(DateTime {
+            year: if negative { -year } else { year },
+            month: month,
+            day: day,
+            hour: hour,
+            minute: minute,
+            second: second,
+            timezone: timezone,
+        })
+    }
+    fn from_unix(seconds: i64, millis: u32) -> DateTime {
+        let days = seconds.div_euclid(86400);
+        let rest = seconds.rem_euclid(86400);
+        // the inverse of days_from_civil
+        let z = days + 719468;
+        let era = z.div_euclid(146097);
+        let day_of_era = z - era * 146097;
+        let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 -
+                           day_of_era / 146096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let mp = (5 * day_of_year + 2) / 153;
+        let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+        let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+        DateTime {
+            year: year,
+            month: month,
+            day: day,
+            hour: (rest / 3600) as u32,
+            minute: (rest % 3600 / 60) as u32,
+            second: (rest % 60) as f64 + f64::from(millis) / 1000.,
+            timezone: Some(0),
+        }
+    }
+    /// The number of seconds since 1970-01-01T00:00:00Z. A time without a
+    /// timezone is taken to be in UTC.
+    fn timestamp(&self) -> f64 {
+        let days = days_from_civil(self.year, self.month, self.day);
+        let minutes = i64::from(self.hour) * 60 + i64::from(self.minute) -
+                      i64::from(self.timezone.unwrap_or(0));
+        (days * 86400 + minutes * 60) as f64 + self.second
+    }
+    fn timezone_string(&self) -> String {
+        match self.timezone {
+            None => String::new(),
+            Some(0) => String::from("Z"),
+            Some(tz) => {
+                format!("{}{:02}:{:02}",
+                        if tz < 0 { '-' } else { '+' },
+                        tz.abs() / 60,
+                        tz.abs() % 60)
+            }
+        }
+    }
+}
+
+impl ::std::fmt::Display for DateTime {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        write!(f,
+               "{:04}-{:02}-{:02}T{:02}:{:02}:{:06.3}{}",
+               self.year,
+               self.month,
+               self.day,
+               self.hour,
+               self.minute,
+               self.second,
+               self.timezone_string())
+    }
+}
+
+/// A literal value that can be compared with the SPARQL operators.
+enum Comparable<'a> {
+    Numeric(Numeric),
+    String(&'a str),
+    LangString(&'a str, &'a str),
+    Boolean(bool),
+    DateTime(DateTime),
+}
+
+fn comparable(literal: LiteralRef) -> Option<Comparable> {
+    if let Some(language) = literal.language {
+        Some(Comparable::LangString(literal.lexical, language))
+    } else if literal.datatype == constants::XSD_STRING {
+        Some(Comparable::String(literal.lexical))
+    } else if literal.datatype == constants::XSD_BOOLEAN {
+        match literal.lexical {
+            "true" | "1" => Some(Comparable::Boolean(true)),
+            "false" | "0" => Some(Comparable::Boolean(false)),
+            _ => None,
+        }
+    } else if literal.datatype == constants::XSD_DATE_TIME {
+        DateTime::parse(literal.lexical).map(Comparable::DateTime)
+    } else {
+        Numeric::from_literal(literal).map(Comparable::Numeric)
+    }
+}
+
+/// Compare two values with `<`. Only values of the same kind can be
+/// compared.
+fn compare<'g, G>(a: &Value<'g, G>, b: &Value<'g, G>) -> Option<Ordering>
+    where G: Graph<'g>
+{
+    match (comparable(a.literal()?)?, comparable(b.literal()?)?) {
+        (Comparable::Numeric(a), Comparable::Numeric(b)) => a.compare(b),
+        (Comparable::String(a), Comparable::String(b)) => Some(a.cmp(b)),
+        (Comparable::Boolean(a), Comparable::Boolean(b)) => Some(a.cmp(&b)),
+        (Comparable::DateTime(a), Comparable::DateTime(b)) => {
+            a.timestamp().partial_cmp(&b.timestamp())
+        }
+        _ => None,
+    }
+}
+
+/// Compare two values with `=`.
+fn equal<'g, G>(a: &Value<'g, G>, b: &Value<'g, G>) -> Option<bool>
+    where G: Graph<'g>
+{
+    if compare_terms(a, b) == Ordering::Equal {
+        return Some(true);
+    }
+    let (a, b) = match (a.literal(), b.literal()) {
+        (Some(a), Some(b)) => (a, b),
+        (None, None) => return Some(false),
+        // a literal is never equal to an IRI or a blank node
+        _ => return Some(false),
+    };
+    match (comparable(a), comparable(b)) {
+        (Some(Comparable::LangString(a, la)), Some(Comparable::LangString(b, lb))) => {
+            Some(a == b && la.eq_ignore_ascii_case(lb))
+        }
+        (Some(x), Some(y)) => {
+            match (x, y) {
+                (Comparable::Numeric(x), Comparable::Numeric(y)) => {
+                    Some(x.compare(y) == Some(Ordering::Equal))
+                }
+                (Comparable::DateTime(x), Comparable::DateTime(y)) => {
+                    Some(x.timestamp() == y.timestamp())
+                }
+                (Comparable::String(x), Comparable::String(y)) => Some(x == y),
+                (Comparable::Boolean(x), Comparable::Boolean(y)) => Some(x == y),
+                _ => Some(false),
+            }
+        }
+        // values of unknown datatypes cannot be compared
+        _ => None,
+    }
+}
+
+/// Compare two values for `ORDER BY`, `MIN` and `MAX`.
+///
+/// Unbound values come first, then blank nodes, IRIs and literals. Values
+/// that cannot be compared with `<` are ordered as RDF terms.
+pub fn compare_for_order<'g, G>(a: Option<&Value<'g, G>>, b: Option<&Value<'g, G>>) -> Ordering
+    where G: Graph<'g>
+{
+    match (a, b) {
+        (Some(a), Some(b)) => {
+            compare(a, b).filter(|&o| o != Ordering::Equal).unwrap_or_else(|| compare_terms(a, b))
+        }
+        (a, b) => a.is_some().cmp(&b.is_some()),
+    }
+}
+
+/// Apply an arithmetic operator to two numbers.
+pub fn arithmetic(operator: Operator, a: Numeric, b: Numeric) -> Option<Numeric> {
+    match operator {
+        Operator::Add => a.apply(b, i64::checked_add, Decimal::checked_add, |a, b| a + b),
+        Operator::Subtract => a.apply(b, i64::checked_sub, Decimal::checked_sub, |a, b| a - b),
+        Operator::Multiply => a.apply(b, i64::checked_mul, Decimal::checked_mul, |a, b| a * b),
+        // dividing two integers gives a decimal
+        _ => a.apply(b, |_, _| None, Decimal::checked_div, |a, b| a / b),
+    }
+}
+
+fn boolean<'g, G>(b: bool) -> Option<Value<'g, G>>
+    where G: Graph<'g>
+{
+    Some(Value::Literal(Literal::boolean(b)))
+}
+
+fn numeric<'g, G>(n: Numeric) -> Option<Value<'g, G>>
+    where G: Graph<'g>
+{
+    Some(Value::Literal(n.to_literal()))
+}
+
+fn simple<'g, G>(s: String) -> Option<Value<'g, G>>
+    where G: Graph<'g>
+{
+    Some(Value::Literal(Literal::string(s)))
+}
+
+/// Create a string with the same language or datatype as `like`.
+fn string_like<'g, G>(like: LiteralRef, s: String) -> Option<Value<'g, G>>
+    where G: Graph<'g>
+{
+    Some(Value::Literal(match like.language {
+        Some(language) => Literal::language(s, language),
+        None => Literal::string(s),
+    }))
+}
+
+fn to_numeric<'g, G>(value: &Value<'g, G>) -> Option<Numeric>
+    where G: Graph<'g>
+{
+    Numeric::from_literal(value.literal()?)
+}
+
+/// The literal, if it is a simple literal, an `xsd:string` or a literal
+/// with a language.
+fn to_string<'g, 'a, G>(value: &'a Value<'g, G>) -> Option<LiteralRef<'a>>
+    where G: Graph<'g>
+{
+    value.literal().filter(|l| l.is_string())
+}
+
+/// Are the two arguments of a string function compatible?
+fn compatible(a: LiteralRef, b: LiteralRef) -> bool {
+    b.language.is_none() || a.language == b.language
+}
+
+fn encode_for_uri(s: &str) -> String {
+    let mut encoded = String::with_capacity(s.len());
+    for b in s.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(b as char)
+            }
+            b => encoded.push_str(&format!("%{:02X}", b)),
+        }
+    }
+    encoded
+}
+
+fn uuid() -> String {
+    let mut bytes: [u8; 16] = rand::random();
+    bytes[6] = bytes[6] & 0x0f | 0x40;
+    bytes[8] = bytes[8] & 0x3f | 0x80;
+    let hex: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+    format!("{}-{}-{}-{}-{}",
+            &hex[..8],
+            &hex[8..12],
+            &hex[12..16],
+            &hex[16..20],
+            &hex[20..])
+}
+
+/// Evaluate an expression for a row.
+///
+/// `None` is returned if the expression has an error, e.g. when an argument
+/// has the wrong type or a variable is not bound.
+pub fn evaluate<'q, 'g, G>(evaluator: &Evaluator<'q, 'g, G>,
+                           expression: &Expression,
+                           row: &Row<'g, G>)
+                           -> Option<Value<'g, G>>
+    where G: Graph<'g>
+{
+    let eval = |e: &Expression| evaluate(evaluator, e, row);
+    let ebv = |e: &Expression| eval(e).and_then(|v| v.effective_boolean_value());
+    match *expression {
+        Expression::Variable(v) => row[v].clone(),
+        Expression::Constant(ref c) => Some(constant(c)),
+        Expression::Binary(Operator::Or, ref a, ref b) => {
+            match (ebv(a), ebv(b)) {
+                (Some(true), _) | (_, Some(true)) => boolean(true),
+                (Some(false), Some(false)) => boolean(false),
+                _ => None,
+            }
+        }
+        Expression::Binary(Operator::And, ref a, ref b) => {
+            match (ebv(a), ebv(b)) {
+                (Some(false), _) | (_, Some(false)) => boolean(false),
+                (Some(true), Some(true)) => boolean(true),
+                _ => None,
+            }
+        }
+        Expression::Binary(operator, ref a, ref b) => {
+            let a = eval(a)?;
+            let b = eval(b)?;
+            match operator {
+                Operator::Equal => boolean(equal(&a, &b)?),
+                Operator::NotEqual => boolean(!equal(&a, &b)?),
+                Operator::Less => boolean(compare(&a, &b)? == Ordering::Less),
+                Operator::Greater => boolean(compare(&a, &b)? == Ordering::Greater),
+                Operator::LessOrEqual => boolean(compare(&a, &b)? != Ordering::Greater),
+                Operator::GreaterOrEqual => boolean(compare(&a, &b)? != Ordering::Less),
+                _ => numeric(arithmetic(operator, to_numeric(&a)?, to_numeric(&b)?)?),
+            }
+        }
+        Expression::Not(ref a) => boolean(!ebv(a)?),
+        Expression::UnaryPlus(ref a) => numeric(to_numeric(&eval(a)?)?),
+        Expression::UnaryMinus(ref a) => {
+            numeric(match to_numeric(&eval(a)?)? {
+                Numeric::Integer(i) => Numeric::Integer(i.checked_neg()?),
+                Numeric::Decimal(d) => Numeric::Decimal(d.checked_neg()?),
+                Numeric::Float(f) => Numeric::Float(-f),
+                Numeric::Double(f) => Numeric::Double(-f),
+            })
+        }
+        Expression::In(ref a, ref list) => {
+            let a = eval(a)?;
+            let mut error = false;
+            for e in list {
+                match eval(e).and_then(|b| equal(&a, &b)) {
+                    Some(true) => return boolean(true),
+                    Some(false) => {}
+                    None => error = true,
+                }
+            }
+            if error { None } else { boolean(false) }
+        }
+        Expression::Bound(v) => boolean(row[v].is_some()),
+        Expression::If(ref condition, ref a, ref b) => {
+            if ebv(condition)? { eval(a) } else { eval(b) }
+        }
+        Expression::Coalesce(ref list) => list.iter().filter_map(&eval).next(),
+        Expression::Exists(ref pattern) => boolean(evaluator.exists(pattern, row)),
+        Expression::Function(ref function, ref arguments) => {
+            let mut values = Vec::with_capacity(arguments.len());
+            for a in arguments {
+                values.push(eval(a)?);
+            }
+            call(evaluator, function, &values)
+        }
+    }
+}
+
+fn call<'q, 'g, G>(evaluator: &Evaluator<'q, 'g, G>,
+                   function: &Function,
+                   args: &[Value<'g, G>])
+                   -> Option<Value<'g, G>>
+    where G: Graph<'g>
+{
+    let string = |i: usize| to_string(&args[i]);
+    let date_time = || {
+        let literal = args[0].literal()?;
+        if literal.datatype != constants::XSD_DATE_TIME {
+            return None;
+        }
+        DateTime::parse(literal.lexical)
+    };
+    let integer = |i: i64| numeric(Numeric::Integer(i));
+    match *function {
+        Function::Str => {
+            match args[0].iri() {
+                Some(iri) => simple(String::from(iri)),
+                None => simple(String::from(args[0].literal()?.lexical)),
+            }
+        }
+        Function::Lang => simple(String::from(args[0].literal()?.language.unwrap_or(""))),
+        Function::LangMatches => {
+            let tag = string(0)?.lexical.to_lowercase();
+            let range = string(1)?.lexical.to_lowercase();
+            boolean(if range == "*" {
+                !tag.is_empty()
+            } else {
+                tag == range || tag.starts_with(&format!("{}-", range))
+            })
+        }
+        Function::Datatype => Some(Value::IRI(String::from(args[0].literal()?.datatype))),
+        Function::IRI(ref base) => {
+            if let Some(iri) = args[0].iri() {
+                return Some(Value::IRI(String::from(iri)));
+            }
+            let s = string(0).filter(|s| s.language.is_none())?.lexical;
+            Some(Value::IRI(match *base {
+                Some(ref base) => iri::join(base, s),
+                None => String::from(s),
+            }))
+        }
+        Function::BNode => Some(Value::BlankNode(evaluator.functions().new_blank_nodes(1))),
+        Function::Rand => numeric(Numeric::Double(rand::random::<f64>())),
+        Function::Abs | Function::Ceil | Function::Floor | Function::Round => {
+            let f = match *function {
+                Function::Abs => f64::abs,
+                Function::Ceil => f64::ceil,
+                Function::Floor => f64::floor,
+                _ => |f: f64| (f + 0.5).floor(),
+            };
+            numeric(match to_numeric(&args[0])? {
+                Numeric::Integer(i) if *function == Function::Abs => {
+                    Numeric::Integer(i.checked_abs()?)
+                }
+                Numeric::Integer(i) => Numeric::Integer(i),
+                Numeric::Decimal(d) => {
+                    Numeric::Decimal(match *function {
+                        Function::Abs => d.abs(),
+                        Function::Ceil => d.ceil(),
+                        Function::Floor => d.floor(),
+                        _ => d.round(),
+                    }?)
+                }
+                Numeric::Float(d) => Numeric::Float(f(d)),
+                Numeric::Double(d) => Numeric::Double(f(d)),
+            })
+        }
+        Function::Concat => {
+            let mut result = String::new();
+            let mut language = None;
+            for (i, arg) in args.iter().enumerate() {
+                let s = to_string(arg)?;
+                result.push_str(s.lexical);
+                if i == 0 {
+                    language = s.language;
+                } else if language != s.language {
+                    language = None;
+                }
+            }
+            Some(Value::Literal(match language {
+                Some(language) => Literal::language(result, language),
+                None => Literal::string(result),
+            }))
+        }
+        Function::StrLen => integer(string(0)?.lexical.chars().count() as i64),
+        Function::UCase => string_like(string(0)?, string(0)?.lexical.to_uppercase()),
+        Function::LCase => string_like(string(0)?, string(0)?.lexical.to_lowercase()),
+        Function::EncodeForURI => simple(encode_for_uri(string(0)?.lexical)),
+        Function::Contains | Function::StrStarts | Function::StrEnds | Function::StrBefore |
+        Function::StrAfter => {
+            let a = string(0)?;
+            let b = string(1)?;
+            if !compatible(a, b) {
+                return None;
+            }
+            match *function {
+                Function::Contains => boolean(a.lexical.contains(b.lexical)),
+                Function::StrStarts => boolean(a.lexical.starts_with(b.lexical)),
+                Function::StrEnds => boolean(a.lexical.ends_with(b.lexical)),
+                _ => {
+                    match a.lexical.find(b.lexical) {
+                        Some(p) if *function == Function::StrBefore => {
+                            string_like(a, String::from(&a.lexical[..p]))
+                        }
+                        Some(p) => string_like(a, String::from(&a.lexical[p + b.lexical.len()..])),
+                        None => simple(String::new()),
+                    }
+                }
+            }
+        }
+        Function::SubStr => {
+            let s = string(0)?;
+            let start = (to_numeric(&args[1])?.to_f64() + 0.5).floor();
+            let end = match args.get(2) {
+                Some(len) => start + (to_numeric(len)?.to_f64() + 0.5).floor(),
+                None => f64::INFINITY,
+            };
+            let result = s.lexical
+                .chars()
+                .enumerate()
+                .filter(|&(i, _)| (i + 1) as f64 >= start && ((i + 1) as f64) < end)
+                .map(|(_, c)| c)
+                .collect();
+            string_like(s, result)
+        }
+        Function::Regex => {
+            let s = string(0)?;
+            let pattern = string(1)?.lexical;
+            let flags = match args.get(2) {
+                Some(flags) => to_string(flags)?.lexical,
+                None => "",
+            };
+            boolean(evaluator.functions().regex(pattern, flags)?.is_match(s.lexical))
+        }
+        Function::Replace => {
+            let s = string(0)?;
+            let pattern = string(1)?.lexical;
+            let replacement = string(2)?.lexical;
+            let flags = match args.get(3) {
+                Some(flags) => to_string(flags)?.lexical,
+                None => "",
+            };
+            let regex = evaluator.functions().regex(pattern, flags)?;
+            if regex.is_match("") {
+                return None;
+            }
+            string_like(s, regex.replace_all(s.lexical, replacement).into_owned())
+        }
+        Function::Year => integer(date_time()?.year),
+        Function::Month => integer(i64::from(date_time()?.month)),
+        Function::Day => integer(i64::from(date_time()?.day)),
+        Function::Hours => integer(i64::from(date_time()?.hour)),
+        Function::Minutes => integer(i64::from(date_time()?.minute)),
+        Function::Seconds => numeric(Numeric::Decimal(Decimal::from_f64(date_time()?.second)?)),
+        Function::Timezone => {
+            let tz = date_time()?.timezone?;
+            let (hours, minutes) = (tz.abs() / 60, tz.abs() % 60);
+            let mut duration = String::from(if tz < 0 { "-PT" } else { "PT" });
+            if hours > 0 {
+                duration.push_str(&format!("{}H", hours));
+            }
+            if minutes > 0 {
+                duration.push_str(&format!("{}M", minutes));
+            }
+            if tz == 0 {
+                duration.push_str("0S");
+            }
+            Some(Value::Literal(Literal::new(duration, constants::XSD_DAY_TIME_DURATION)))
+        }
+        Function::Tz => simple(date_time()?.timezone_string()),
+        Function::Now => Some(Value::Literal(evaluator.functions().now.clone())),
+        Function::UUID => Some(Value::IRI(format!("urn:uuid:{}", uuid()))),
+        Function::StrUUID => simple(uuid()),
+        Function::StrLang => {
+            let s = string(0).filter(|s| s.language.is_none())?;
+            let language = string(1).filter(|s| s.language.is_none())?;
+            if language.lexical.is_empty() {
+                return None;
+            }
+            Some(Value::Literal(Literal::language(String::from(s.lexical), language.lexical)))
+        }
+        Function::StrDT => {
+            let s = string(0).filter(|s| s.language.is_none())?;
+            let datatype = args[1].iri()?;
+            Some(Value::Literal(Literal::new(String::from(s.lexical), datatype)))
+        }
+        Function::SameTerm => boolean(compare_terms(&args[0], &args[1]) == Ordering::Equal),
+        Function::IsIRI => boolean(args[0].iri().is_some()),
+        Function::IsBlank => boolean(args[0].is_blank_node()),
+        Function::IsLiteral => boolean(args[0].literal().is_some()),
+        Function::IsNumeric => {
+            boolean(args[0].literal().is_some_and(|l| {
+                is_numeric_datatype(l.datatype) && Numeric::from_literal(l).is_some()
+            }))
+        }
+        Function::Cast(ref datatype) => cast(&args[0], datatype),
+    }
+}
+
+fn cast<'g, G>(value: &Value<'g, G>, datatype: &str) -> Option<Value<'g, G>>
+    where G: Graph<'g>
+{
+    if datatype == constants::XSD_STRING {
+        return match value.iri() {
+            Some(iri) => simple(String::from(iri)),
+            None => simple(String::from(value.literal()?.lexical)),
+        };
+    }
+    let literal = value.literal()?;
+    if literal.language.is_some() {
+        return None;
+    }
+    let lexical = literal.lexical.trim();
+    let from_string = literal.datatype == constants::XSD_STRING;
+    let number = Numeric::from_literal(literal);
+    let flag = match comparable(literal) {
+        Some(Comparable::Boolean(b)) => Some(b),
+        _ => None,
+    };
+    let typed = |lexical: &str| {
+        Some(Value::Literal(Literal::new(String::from(lexical), datatype)))
+    };
+    if datatype == constants::XSD_BOOLEAN {
+        if from_string {
+            return match lexical {
+                "true" | "1" => boolean(true),
+                "false" | "0" => boolean(false),
+                _ => None,
+            };
+        }
+        return boolean(flag.or_else(|| number.map(|n| n.to_f64() != 0. && !n.to_f64().is_nan()))?);
+    }
+    if datatype == constants::XSD_DATE_TIME {
+        return if (from_string || literal.datatype == constants::XSD_DATE_TIME) &&
+                  DateTime::parse(lexical).is_some() {
+            typed(lexical)
+        } else {
+            None
+        };
+    }
+    let f = if from_string {
+        let string = LiteralRef {
+            lexical: lexical,
+            datatype: datatype,
+            language: None,
+        };
+        Numeric::from_literal(string)?.to_f64()
+    } else if let Some(b) = flag {
+        if b { 1. } else { 0. }
+    } else {
+        number?.to_f64()
+    };
+    numeric(if datatype == constants::XSD_INTEGER {
+        if !f.is_finite() {
+            return None;
+        }
+        match number {
+            Some(Numeric::Integer(i)) => Numeric::Integer(i),
+            Some(Numeric::Decimal(d)) => Numeric::Integer(d.to_integer()?),
+            _ if from_string => Numeric::from_literal(LiteralRef {
+                lexical: lexical,
+                datatype: datatype,
+                language: None,
+            })?,
+            _ => {
+                // i64::MAX as f64 is 2^63, which is out of range
+                let f = f.trunc();
+                if f < i64::MIN as f64 || f >= -(i64::MIN as f64) {
+                    return None;
+                }
+                Numeric::Integer(f as i64)
+            }
+        }
+    } else if datatype == constants::XSD_DECIMAL {
+        Numeric::Decimal(match number {
+            _ if from_string => Decimal::parse(lexical)?,
+            Some(n @ Numeric::Integer(_)) |
+            Some(n @ Numeric::Decimal(_)) => n.to_decimal()?,
+            _ => Decimal::from_f64(f)?,
+        })
+    } else if datatype == constants::XSD_FLOAT {
+        Numeric::Float(f64::from(f as f32))
+    } else {
+        Numeric::Double(f)
+    })
+}
+
+#[test]
+fn test_date_time() {
+    let d = DateTime::parse("2011-01-10T14:45:13.815-05:00").unwrap();
+    assert_eq!((d.year, d.month, d.day, d.hour, d.minute), (2011, 1, 10, 14, 45));
+    assert_eq!(d.timezone, Some(-300));
+    assert_eq!(d.timezone_string(), "-05:00");
+    assert_eq!(DateTime::from_unix(d.timestamp().floor() as i64, 0).to_string(),
+               "2011-01-10T19:45:13.000Z");
+    assert!(DateTime::parse("2011-13-10T14:45:13").is_none());
+    assert!(DateTime::parse("2011-01-10").is_none());
+}
+
+#[test]
+fn test_encode_for_uri() {
+    assert_eq!(encode_for_uri("Los Angeles"), "Los%20Angeles");
+    assert_eq!(encode_for_uri("a/é"), "a%2F%C3%A9");
+}
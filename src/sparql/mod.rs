@@ -0,0 +1,426 @@
+//! SPARQL 1.1 queries on graphs.
+//!
+//! `Query::parse` reads a `SELECT`, `ASK`, `CONSTRUCT` or `DESCRIBE` query
+//! and `Query::evaluate` runs it on any `Graph`. Graph patterns can use
+//! property paths, `FILTER`, `OPTIONAL`, `UNION`, `MINUS`, `BIND`, `VALUES`,
+//! `EXISTS` and sub-queries. Solutions can be grouped with `GROUP BY` and
+//! aggregates, and modified with `ORDER BY`, `DISTINCT`, `LIMIT` and
+//! `OFFSET`.
+//!
+//! `Update::parse` reads a SPARQL update, which `Update::execute` applies to
+//! a `MemGraph`. `Update::execute_into` creates a changed copy of any other
+//! graph.
+//!
+//! The query is evaluated on the default graph only: `FROM`, `GRAPH` and
+//! `SERVICE` are not supported. Neither are the hash functions such as
+//! `MD5`.
+//!
+//! The solutions and booleans can be written in the standard result formats
+//! with `io::write_sparql_json`, `io::write_sparql_xml`, `io::write_sparql_csv`
+//! and `io::write_sparql_tsv`.
+//!
+//! ```
+//! use rome::graph::{Graph, GraphWriter};
+//! use rome::graphs::tel;
+//! use rome::sparql::{Query, QueryResult};
+//!
+//! let mut creator = tel::GraphCreator::with_capacity(0);
+//! let alice = creator.create_iri(&"http://example.org/alice");
+//! let bob = creator.create_iri(&"http://example.org/bob");
+//! let carol = creator.create_iri(&"http://example.org/carol");
+//! let knows = creator.create_iri(&"http://xmlns.com/foaf/0.1/knows");
+//! creator.add_iri_iri(&alice, &knows, &bob);
+//! creator.add_iri_iri(&alice, &knows, &carol);
+//! creator.add_iri_iri(&bob, &knows, &carol);
+//! let graph: tel::Graph64 = creator.collect();
+//!
+//! let query = Query::parse("
+//!     PREFIX foaf: <http://xmlns.com/foaf/0.1/>
+//!     SELECT ?person (COUNT(?friend) AS ?friends)
+//!     WHERE { ?person foaf:knows ?friend }
+//!     GROUP BY ?person
+//!     ORDER BY DESC(?friends)").unwrap();
+//! match query.evaluate(&graph) {
+//!     QueryResult::Solutions(solutions) => {
+//!         assert_eq!(solutions.len(), 2);
+//!         let person = solutions.get(0, "person").and_then(|p| p.iri());
+//!         assert_eq!(person, Some("http://example.org/alice"));
+//!         let friends = solutions.get(0, "friends").and_then(|f| f.literal());
+//!         assert_eq!(friends.map(|f| f.lexical), Some("2"));
+//!     }
+//!     _ => panic!("expected solutions"),
+//! }
+//! ```
+
+mod ast;
+mod eval;
+mod expression;
+mod parser;
+mod update;
+mod value;
+
+pub use self::eval::{QueryResult, Solutions};
+pub use self::value::{Literal, LiteralRef, Value};
+
+use error::Result;
+use graph::{Graph, GraphWriter};
+use graphs::mem::MemGraph;
+
+/// A parsed SPARQL query.
+#[derive(Clone,Debug)]
+pub struct Query {
+    query: ast::Query,
+}
+
+impl Query {
+    /// Parse a query.
+    ///
+    /// Syntax errors are reported as `Error::Syntax`.
+    pub fn parse(query: &str) -> Result<Query> {
+        Ok(Query { query: parser::parse_query(query)? })
+    }
+    /// Evaluate the query on a graph.
+    pub fn evaluate<'g, G>(&self, graph: &'g G) -> QueryResult<'g, G>
+        where G: Graph<'g>
+    {
+        eval::evaluate(&self.query, graph)
+    }
+}
+
+/// A parsed SPARQL update.
+///
+/// An update is a sequence of operations separated by `;`. The operations
+/// `INSERT DATA`, `DELETE DATA`, `DELETE WHERE`, `DELETE`/`INSERT` with
+/// `WHERE`, `CLEAR DEFAULT`, `CLEAR ALL` and `LOAD` are supported. `LOAD`
+/// reads local files only.
+///
+/// ```
+/// use rome::graph::Graph;
+/// use rome::graphs::mem::MemGraph;
+/// use rome::sparql::Update;
+///
+/// let mut graph = MemGraph::new();
+/// Update::parse("
+///     PREFIX ex: <http://example.org/>
+///     INSERT DATA { ex:alice ex:age 30 ; ex:name \"Alice\" } ;
+///     DELETE { ?p ex:age ?a } INSERT { ?p ex:age ?b }
+///     WHERE { ?p ex:age ?a BIND(?a + 1 AS ?b) }").unwrap()
+///     .execute(&mut graph)
+///     .unwrap();
+/// assert_eq!(graph.len(), 2);
+/// let age = graph.find_literal("31", "http://www.w3.org/2001/XMLSchema#integer", None);
+/// assert!(age.is_some());
+/// ```
+#[derive(Clone,Debug)]
+pub struct Update {
+    operations: Vec<ast::UpdateOperation>,
+}
+
+impl Update {
+    /// Parse an update.
+    ///
+    /// Syntax errors are reported as `Error::Syntax`.
+    pub fn parse(update: &str) -> Result<Update> {
+        Ok(Update { operations: parser::parse_update(update)? })
+    }
+    /// Apply the update to a graph.
+    ///
+    /// The operations are applied in order. If an operation fails, the
+    /// changes of the operations before it are kept.
+    pub fn execute(&self, graph: &mut MemGraph) -> Result<()> {
+        update::execute(&self.operations, graph)
+    }
+    /// Apply the update to a copy of a graph and write the result to
+    /// `writer`.
+    ///
+    /// This is how an immutable graph such as a `graphs::tel::Graph` is
+    /// changed: the graph is copied into a `MemGraph`, the update is applied
+    /// to the copy and the new graph is created from the copy. The new graph
+    /// has the same IRIs, so the `Namespaces` that were used for the old
+    /// graph can be used to write the new graph.
+    pub fn execute_into<'g, G, W>(&self, graph: &'g G, writer: W) -> Result<W::Graph>
+        where G: Graph<'g> + 'g,
+              W: GraphWriter<'g>
+    {
+        update::execute_into(&self.operations, graph, writer)
+    }
+}
+
+#[cfg(test)]
+const TURTLE: &'static str = "@prefix : <http://a.example/> .
+:alice :knows :bob, :carol ; :name \"Alice\" ; :age 30 .
+:bob :knows :carol ; :name \"Bob\"@en ; :age 25 .
+:carol :knows :alice ; :name \"Carol\" ; :age 35 ; :email \"carol@example.org\" .
+_:dave :knows :alice ; :name \"Dave\" .
+:list :items (1 2 3) .
+";
+
+#[cfg(test)]
+fn format_value<'g, G>(value: Option<&Value<'g, G>>) -> String
+    where G: Graph<'g>
+{
+    use constants;
+    let short = |iri: &str| {
+        iri.replace("http://a.example/", ":")
+            .replace("http://www.w3.org/2001/XMLSchema#", "xsd:")
+    };
+    let value = match value {
+        Some(value) => value,
+        None => return String::from("-"),
+    };
+    if let Some(iri) = value.iri() {
+        return short(iri);
+    }
+    match value.literal() {
+        Some(LiteralRef { lexical, language: Some(language), .. }) => {
+            format!("\"{}\"@{}", lexical, language)
+        }
+        Some(LiteralRef { lexical, datatype, .. }) if datatype == constants::XSD_STRING => {
+            format!("\"{}\"", lexical)
+        }
+        Some(LiteralRef { lexical, datatype, .. }) => {
+            format!("\"{}\"^^{}", lexical, short(datatype))
+        }
+        None => String::from("_:b"),
+    }
+}
+
+/// Run a query and write the result as lines of values. The lines are
+/// sorted unless the query has `ORDER BY`.
+#[cfg(test)]
+fn run<'g, G>(graph: &'g G, query: &str) -> Vec<String>
+    where G: Graph<'g>
+{
+    let query = format!("PREFIX : <http://a.example/>
+                         PREFIX xsd: <http://www.w3.org/2001/XMLSchema#>
+                         {}",
+                        query);
+    let mut lines: Vec<String> = match Query::parse(&query).unwrap().evaluate(graph) {
+        QueryResult::Solutions(solutions) => {
+            solutions.rows()
+                .iter()
+                .map(|row| {
+                    row.iter().map(|v| format_value(v.as_ref())).collect::<Vec<_>>().join(" ")
+                })
+                .collect()
+        }
+        QueryResult::Boolean(b) => vec![b.to_string()],
+        QueryResult::Triples(triples) => {
+            triples.iter()
+                .map(|t| {
+                    t.iter().map(|v| format_value(Some(v))).collect::<Vec<_>>().join(" ")
+                })
+                .collect()
+        }
+    };
+    if !query.contains("ORDER BY") {
+        lines.sort();
+    }
+    lines
+}
+
+#[cfg(test)]
+fn check_queries<'g, G>(graph: &'g G)
+    where G: Graph<'g>
+{
+    let queries: Vec<(&str, Vec<&str>)> = vec![
+        ("SELECT ?n WHERE { ?p :name ?n FILTER(?n = \"Alice\" || ?n = \"Carol\") }",
+         vec!["\"Alice\"", "\"Carol\""]),
+        ("SELECT ?n ?e WHERE { ?p :name ?n OPTIONAL { ?p :email ?e } } ORDER BY ?n",
+         vec!["\"Alice\" -", "\"Bob\"@en -", "\"Carol\" \"carol@example.org\"", "\"Dave\" -"]),
+        ("SELECT ?x WHERE { { :alice :knows ?x } UNION { ?x :knows :alice } }",
+         vec![":bob", ":carol", ":carol", "_:b"]),
+        ("SELECT ?p WHERE { ?p :name ?n MINUS { ?p :email ?e } }",
+         vec![":alice", ":bob", "_:b"]),
+        ("SELECT ?p ?y WHERE { ?p :age ?a BIND(?a + 1 AS ?y) }",
+         vec![":alice \"31\"^^xsd:integer",
+              ":bob \"26\"^^xsd:integer",
+              ":carol \"36\"^^xsd:integer"]),
+        ("SELECT ?p ?n WHERE { VALUES ?p { :alice :bob :nobody } ?p :name ?n }",
+         vec![":alice \"Alice\"", ":bob \"Bob\"@en"]),
+        ("SELECT ?p (COUNT(?o) AS ?c) WHERE { ?p :knows ?o } GROUP BY ?p HAVING (COUNT(?o) > 1)",
+         vec![":alice \"2\"^^xsd:integer"]),
+        ("SELECT (SUM(?a) AS ?s) (AVG(?a) AS ?v) (MIN(?a) AS ?min) (MAX(?a) AS ?max)
+          WHERE { ?p :age ?a }",
+         vec!["\"90\"^^xsd:integer \"30.0\"^^xsd:decimal \"25\"^^xsd:integer \
+               \"35\"^^xsd:integer"]),
+        ("SELECT ?p WHERE { ?p :age ?a } ORDER BY DESC(?a) LIMIT 2 OFFSET 1",
+         vec![":alice", ":bob"]),
+        ("SELECT DISTINCT ?o WHERE { ?p :knows ?o }", vec![":alice", ":bob", ":carol"]),
+        ("SELECT (COUNT(DISTINCT ?o) AS ?c) WHERE { ?p :knows ?o }",
+         vec!["\"3\"^^xsd:integer"]),
+        ("SELECT (STRLEN(?n) AS ?l) (UCASE(?n) AS ?u) (LANG(?n) AS ?lang)
+          WHERE { :bob :name ?n }",
+         vec!["\"3\"^^xsd:integer \"BOB\"@en \"en\""]),
+        ("SELECT ?n WHERE {
+            ?p :name ?n
+            FILTER(REGEX(?n, \"^[ab]\", \"i\"))
+            FILTER EXISTS { ?p :knows :carol }
+          }",
+         vec!["\"Alice\"", "\"Bob\"@en"]),
+        ("SELECT ?n WHERE { ?p :name ?n FILTER NOT EXISTS { ?p :knows ?o FILTER(?o = :alice) } }",
+         vec!["\"Alice\"", "\"Bob\"@en"]),
+        ("SELECT ?x WHERE { :list :items (1 ?x 3) }", vec!["\"2\"^^xsd:integer"]),
+        ("SELECT ?p ?n WHERE {
+            ?p :name ?n
+            { SELECT ?p WHERE { ?p :age ?a } ORDER BY ?a LIMIT 1 }
+          }",
+         vec![":bob \"Bob\"@en"]),
+        ("SELECT ?p (IF(?a > 28, \"old\", \"young\") AS ?g)
+          WHERE { ?p :age ?a FILTER(?p IN (:alice, :bob)) }",
+         vec![":alice \"old\"", ":bob \"young\""]),
+        ("SELECT (COUNT(*) AS ?c) WHERE { ?p :missing ?o }", vec!["\"0\"^^xsd:integer"]),
+        ("SELECT ?p WHERE { ?p :name \"Zed\" }", vec![]),
+        ("SELECT (xsd:integer(\"42\") + 1 AS ?x) (DATATYPE(1.5) AS ?d) WHERE {}",
+         vec!["\"43\"^^xsd:integer xsd:decimal"]),
+        ("SELECT (xsd:integer(-2.5e0) AS ?a) (xsd:integer(1e300) AS ?b)
+                 (xsd:integer(xsd:double(\"NaN\")) AS ?c)
+                 (xsd:integer(xsd:double(\"INF\")) AS ?d) WHERE {}",
+         vec!["\"-2\"^^xsd:integer - - -"]),
+        ("SELECT (0.1 + 0.2 AS ?x) (1 / 3 AS ?y) (0.1 + 0.2 = 0.3 AS ?z) WHERE {}",
+         vec!["\"0.3\"^^xsd:decimal \"0.333333333333333333\"^^xsd:decimal \
+               \"true\"^^xsd:boolean"]),
+        ("SELECT * WHERE { ?p :email ?e }", vec![":carol \"carol@example.org\""]),
+        ("ASK { :alice :knows :bob }", vec!["true"]),
+        ("ASK { :bob :knows :alice }", vec!["false"]),
+        ("CONSTRUCT { :alice :knownBy ?s } WHERE { ?s :knows :alice }",
+         vec![":alice :knownBy :carol", ":alice :knownBy _:b"]),
+        ("CONSTRUCT { [] :about ?s } WHERE { ?s :age ?a }",
+         vec!["_:b :about :alice", "_:b :about :bob", "_:b :about :carol"]),
+        ("SELECT ?x WHERE { :alice :knows+ ?x }", vec![":alice", ":bob", ":carol"]),
+        ("SELECT ?x WHERE { :alice ^:knows ?x }", vec![":carol", "_:b"]),
+        ("SELECT ?n WHERE { :bob :knows/:name ?n }", vec!["\"Carol\""]),
+        ("SELECT ?p WHERE { ?p :knows|:email \"carol@example.org\" }", vec![":carol"]),
+        ("SELECT ?o WHERE { :carol !(:name|:age|:email) ?o }", vec![":alice"]),
+        ("PREFIX rdf: <http://www.w3.org/1999/02/22-rdf-syntax-ns#>
+          SELECT ?x WHERE { :list :items/rdf:rest*/rdf:first ?x }",
+         vec!["\"1\"^^xsd:integer", "\"2\"^^xsd:integer", "\"3\"^^xsd:integer"]),
+        ("SELECT ?p WHERE { ?p :knows+ ?p }", vec![":alice", ":bob", ":carol"]),
+        ("ASK { :nobody :knows* :nobody }", vec!["true"]),
+        ("ASK { :bob :knows? :alice }", vec!["false"]),
+        ("DESCRIBE :bob",
+         vec![":bob :age \"25\"^^xsd:integer", ":bob :knows :carol", ":bob :name \"Bob\"@en"]),
+    ];
+    for (query, expected) in queries {
+        assert_eq!(run(graph, query), expected, "{}", query);
+    }
+}
+
+#[cfg(test)]
+graph_collection!(test_collection(0: ::graphs::tel::Graph64, 1: ::graphs::tel::Graph128));
+
+#[test]
+fn test_queries() {
+    use graphs::mem::MemGraph;
+    use graphs::tel::{Graph128, Graph64};
+    use test_support::turtle_graph;
+    use io::TurtleParser;
+    let graph: Graph64 = turtle_graph(TURTLE);
+    check_queries(&graph);
+    let mut graph = MemGraph::new();
+    for step in TurtleParser::new(TURTLE, "http://a.example/", &mut graph).unwrap() {
+        step.unwrap();
+    }
+    check_queries(&graph);
+    // the same triples in two graphs: the paths and joins go from one graph
+    // to the other and :alice :knows :bob is in both
+    let (first, second) = TURTLE.split_at(TURTLE.find("\n:carol").unwrap() + 1);
+    let first: Graph64 = turtle_graph(first);
+    let second: Graph128 = turtle_graph(&format!("@prefix : <http://a.example/> .
+:alice :knows :bob .
+{}",
+                                                 second));
+    check_queries(&test_collection::GraphCollection::new((&first, &second)));
+}
+
+#[test]
+fn test_blank_nodes_in_construct() {
+    use graphs::tel::Graph64;
+    use test_support::turtle_graph;
+    let graph: Graph64 = turtle_graph(TURTLE);
+    let query = Query::parse("CONSTRUCT { _:a <http://a.example/about> ?s }
+                              WHERE { ?s <http://a.example/age> ?a }")
+        .unwrap();
+    match query.evaluate(&graph) {
+        QueryResult::Triples(triples) => {
+            assert_eq!(triples.len(), 3);
+            assert!(triples[0][0] != triples[1][0]);
+            assert!(triples[1][0] != triples[2][0]);
+        }
+        _ => panic!("expected triples"),
+    }
+}
+
+#[test]
+fn test_path_in_construct_template() {
+    assert!(Query::parse("CONSTRUCT { ?s <http://a.example/p>+ ?o } WHERE { ?s ?p ?o }").is_err());
+}
+
+#[cfg(test)]
+fn update(graph: &mut MemGraph, update: &str) {
+    let update = format!("PREFIX : <http://a.example/> {}", update);
+    Update::parse(&update).unwrap().execute(graph).unwrap();
+}
+
+#[test]
+fn test_update() {
+    let mut graph = MemGraph::new();
+    update(&mut graph,
+           "INSERT DATA { :alice :knows :bob ; :age 30 . :bob :knows [ :name \"C\" ] }");
+    assert_eq!(graph.len(), 4);
+    update(&mut graph, "DELETE DATA { :alice :age 30 . :alice :age 31 }");
+    assert_eq!(run(&graph, "SELECT ?p ?o WHERE { :alice ?p ?o }"), [":knows :bob"]);
+    update(&mut graph,
+           "DELETE { ?x :knows ?y } INSERT { ?y :knownBy ?x } WHERE { ?x :knows ?y }");
+    assert_eq!(run(&graph, "SELECT ?s ?o WHERE { ?s :knownBy ?o }"),
+               [":bob :alice", "_:b :bob"]);
+    assert_eq!(run(&graph, "ASK { ?s :knows ?o }"), ["false"]);
+    update(&mut graph, "DELETE WHERE { ?s :name ?n }");
+    assert_eq!(graph.len(), 2);
+    update(&mut graph, "INSERT { _:b :about ?s } WHERE { ?s :knownBy ?o }");
+    assert_eq!(run(&graph, "SELECT (COUNT(DISTINCT ?b) AS ?c) WHERE { ?b :about ?s }"),
+               ["\"2\"^^xsd:integer"]);
+    update(&mut graph, "CLEAR DEFAULT ; INSERT DATA { :a :b :c }");
+    assert_eq!(run(&graph, "SELECT * WHERE { ?s ?p ?o }"), [":a :b :c"]);
+}
+
+#[test]
+fn test_update_into() {
+    use graphs::tel::{Graph64, GraphCreator};
+    use test_support::turtle_graph;
+    let graph: Graph64 = turtle_graph(TURTLE);
+    let update = Update::parse("PREFIX : <http://a.example/>
+                                DELETE { ?p :age ?a } WHERE { ?p :age ?a FILTER(?a > 28) } ;
+                                INSERT DATA { :erin :name \"Erin\" }")
+        .unwrap();
+    let new: Graph64 = update.execute_into(&graph, GraphCreator::with_capacity(0)).unwrap();
+    assert_eq!(run(&new, "SELECT ?p ?a WHERE { ?p :age ?a }"),
+               [":bob \"25\"^^xsd:integer"]);
+    assert_eq!(run(&new, "SELECT ?n WHERE { _:p :knows :alice ; :name ?n }"),
+               ["\"Carol\"", "\"Dave\""]);
+    assert_eq!(run(&new, "SELECT ?p WHERE { ?p :name \"Erin\" }"), [":erin"]);
+    assert_eq!(run(&graph, "SELECT (COUNT(*) AS ?c) WHERE { ?p :age ?a }"),
+               ["\"3\"^^xsd:integer"]);
+}
+
+#[test]
+fn test_load() {
+    use std::fs;
+    let path = ::std::env::temp_dir().join(format!("rome-load-{}.ttl", ::rand::random::<u32>()));
+    fs::write(&path, "@prefix : <http://a.example/> . :a :b [ :c :d ] .").unwrap();
+    let mut graph = MemGraph::new();
+    update(&mut graph, &format!("LOAD <file://{}>", path.display()));
+    fs::remove_file(&path).unwrap();
+    assert_eq!(run(&graph, "SELECT ?o WHERE { :a :b/:c ?o }"), [":d"]);
+    update(&mut graph, &format!("LOAD SILENT <file://{}>", path.display()));
+    assert_eq!(graph.len(), 2);
+    let load = Update::parse(&format!("LOAD <file://{}>", path.display())).unwrap();
+    assert!(load.execute(&mut graph).is_err());
+    let load = Update::parse("LOAD <http://a.example/data.ttl>").unwrap();
+    assert!(load.execute(&mut graph).is_err());
+    // a file IRI without an authority
+    fs::write(&path, "@prefix : <http://a.example/> . :a :e :f .").unwrap();
+    update(&mut graph, &format!("LOAD <file:{}>", path.display()));
+    fs::remove_file(&path).unwrap();
+    assert_eq!(run(&graph, "SELECT ?o WHERE { :a :e ?o }"), [":f"]);
+}
@@ -0,0 +1,1943 @@
+use constants;
+use error::{Error, Result, StatementKind, SyntaxError};
+use iri;
+use namespaces::Namespaces;
+use query::PropertyPath;
+use std::mem;
+use std::str::CharIndices;
+use super::ast::*;
+use super::value::Literal;
+
+/// A token of the SPARQL grammar.
+#[derive(Clone,Debug,PartialEq)]
+enum Token<'a> {
+    /// An IRI in angle brackets, without the brackets and with the escapes
+    /// resolved.
+    IRIRef(String),
+    /// A prefix and a local name with the escapes resolved.
+    PrefixedName(&'a str, String),
+    BlankNode(&'a str),
+    Variable(&'a str),
+    String(String),
+    LangTag(&'a str),
+    Integer(&'a str),
+    Decimal(&'a str),
+    Double(&'a str),
+    /// A keyword, a function name, `true`, `false` or `a`.
+    Name(&'a str),
+    Punct(&'static str),
+    End,
+}
+
+const PUNCTUATION: [&'static str; 25] = ["&&", "||", "!=", "<=", ">=", "^^", "{", "}", "(", ")",
+                                         "[", "]", ".", ",", ";", "*", "/", "+", "-", "!", "=",
+                                         "<", ">", "|", "^"];
+
+const AGGREGATES: [&'static str; 7] = ["COUNT", "SUM", "MIN", "MAX", "AVG", "SAMPLE",
+                                       "GROUP_CONCAT"];
+
+fn syntax_error(src: &str, offset: usize, message: &str) -> Error {
+    let before = &src[..offset];
+    let line = before.matches('\n').count() + 1;
+    let line_start = before.rfind('\n').map(|p| p + 1).unwrap_or(0);
+    Error::Syntax(SyntaxError {
+        line: line,
+        column: before[line_start..].chars().count() + 1,
+        offset: offset,
+        statement: StatementKind::Query,
+        excerpt: src[offset..].lines().next().unwrap_or("").chars().take(40).collect(),
+        message: String::from(message),
+    })
+}
+
+// [164] PN_CHARS_BASE, approximated by the Unicode alphabetic characters
+fn is_pn_chars_base(c: char) -> bool {
+    c.is_alphabetic()
+}
+
+// [165] PN_CHARS_U
+fn is_pn_chars_u(c: char) -> bool {
+    is_pn_chars_base(c) || c == '_'
+}
+
+// [167] PN_CHARS
+fn is_pn_chars(c: char) -> bool {
+    is_pn_chars_u(c) || c == '-' || c.is_ascii_digit() || c == '\u{B7}' ||
+    ('\u{300}'..='\u{36F}').contains(&c) || ('\u{203F}'..='\u{2040}').contains(&c)
+}
+
+/// The length of a name that starts with a character for which `first` is
+/// true, continues with PN_CHARS or '.' and does not end with '.'.
+fn name_len<F>(s: &str, first: F) -> usize
+    where F: Fn(char) -> bool
+{
+    let mut len = 0;
+    for (i, c) in s.char_indices() {
+        if i == 0 {
+            if !first(c) {
+                return 0;
+            }
+        } else if c == '.' {
+            continue;
+        } else if !is_pn_chars(c) {
+            break;
+        }
+        len = i + c.len_utf8();
+    }
+    len
+}
+
+// [166] VARNAME
+fn varname_len(s: &str) -> usize {
+    let mut len = 0;
+    for (i, c) in s.char_indices() {
+        if !(is_pn_chars_u(c) || c.is_ascii_digit() ||
+             i > 0 && (c == '\u{B7}' || ('\u{300}'..='\u{36F}').contains(&c) ||
+                       ('\u{203F}'..='\u{2040}').contains(&c))) {
+            break;
+        }
+        len = i + c.len_utf8();
+    }
+    len
+}
+
+fn hex_escape(chars: &mut CharIndices, len: usize) -> Option<char> {
+    let mut code = 0;
+    for _ in 0..len {
+        code = code * 16 + chars.next()?.1.to_digit(16)?;
+    }
+    ::std::char::from_u32(code)
+}
+
+// [139] IRIREF
+fn iri_ref(s: &str) -> Option<(String, usize)> {
+    let mut iri = String::new();
+    let mut chars = s[1..].char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '>' => return Some((iri, i + 2)),
+            '\\' => {
+                let len = match chars.next()?.1 {
+                    'u' => 4,
+                    'U' => 8,
+                    _ => return None,
+                };
+                iri.push(hex_escape(&mut chars, len)?);
+            }
+            '<' | '"' | '{' | '}' | '|' | '^' | '`' => return None,
+            c if c <= ' ' => return None,
+            c => iri.push(c),
+        }
+    }
+    None
+}
+
+// [156] STRING_LITERAL1 to [159] STRING_LITERAL_LONG2
+fn string(s: &str) -> Option<(String, usize)> {
+    let quote = s.chars().next()?;
+    let long = s[quote.len_utf8()..].starts_with(quote) && s[2..].starts_with(quote);
+    let start = if long { 3 } else { 1 };
+    let mut value = String::new();
+    let mut chars = s[start..].char_indices();
+    while let Some((i, c)) = chars.next() {
+        if c == '\\' {
+            value.push(match chars.next()?.1 {
+                't' => '\t',
+                'n' => '\n',
+                'r' => '\r',
+                'b' => '\u{8}',
+                'f' => '\u{C}',
+                '"' => '"',
+                '\'' => '\'',
+                '\\' => '\\',
+                'u' => hex_escape(&mut chars, 4)?,
+                'U' => hex_escape(&mut chars, 8)?,
+                _ => return None,
+            });
+        } else if c == quote {
+            if !long {
+                return Some((value, start + i + 1));
+            }
+            let rest = &s[start + i..];
+            if rest[1..].starts_with(quote) && rest[2..].starts_with(quote) {
+                return Some((value, start + i + 3));
+            }
+            value.push(c);
+        } else if !long && (c == '\n' || c == '\r') {
+            return None;
+        } else {
+            value.push(c);
+        }
+    }
+    None
+}
+
+// [146] INTEGER to [148] DOUBLE
+fn number(s: &str) -> Option<(Token<'_>, usize)> {
+    let b = s.as_bytes();
+    let digits = |mut i: usize| {
+        while i < b.len() && b[i].is_ascii_digit() {
+            i += 1;
+        }
+        i
+    };
+    let mut end = digits(0);
+    let mut kind = 0;
+    if b.get(end) == Some(&b'.') && b.get(end + 1).is_some_and(|c| c.is_ascii_digit()) {
+        end = digits(end + 1);
+        kind = 1;
+    }
+    if end == 0 {
+        return None;
+    }
+    if b.get(end) == Some(&b'e') || b.get(end) == Some(&b'E') {
+        let mut start = end + 1;
+        if b.get(start) == Some(&b'+') || b.get(start) == Some(&b'-') {
+            start += 1;
+        }
+        let exponent_end = digits(start);
+        if exponent_end > start {
+            end = exponent_end;
+            kind = 2;
+        }
+    }
+    let token = match kind {
+        0 => Token::Integer(&s[..end]),
+        1 => Token::Decimal(&s[..end]),
+        _ => Token::Double(&s[..end]),
+    };
+    Some((token, end))
+}
+
+// [145] LANGTAG
+fn langtag_len(s: &str) -> usize {
+    let b = s.as_bytes();
+    let mut end = 1;
+    while end < b.len() && b[end].is_ascii_alphabetic() {
+        end += 1;
+    }
+    if end == 1 {
+        return 0;
+    }
+    while end + 1 < b.len() && b[end] == b'-' && b[end + 1].is_ascii_alphanumeric() {
+        end += 1;
+        while end < b.len() && b[end].is_ascii_alphanumeric() {
+            end += 1;
+        }
+    }
+    end
+}
+
+// [169] PN_LOCAL
+fn pn_local(s: &str) -> (String, usize) {
+    let mut local = String::new();
+    let mut local_len = 0;
+    let mut len = 0;
+    let mut chars = s.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        if c == '%' {
+            let hex: String = s[i + 1..].chars().take(2).collect();
+            if hex.len() != 2 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+                break;
+            }
+            local.push('%');
+            local.push_str(&hex);
+            chars.next();
+            chars.next();
+        } else if c == '\\' {
+            match chars.peek() {
+                Some(&(_, e)) if "_~.-!$&'()*+,;=/?#@%".contains(e) => {
+                    local.push(e);
+                    chars.next();
+                }
+                _ => break,
+            }
+        } else if c == '.' && i > 0 {
+            local.push(c);
+            continue;
+        } else if is_pn_chars_u(c) || c == ':' || c.is_ascii_digit() || i > 0 && is_pn_chars(c) {
+            local.push(c);
+        } else {
+            break;
+        }
+        local_len = local.len();
+        len = chars.peek().map(|&(j, _)| j).unwrap_or(s.len());
+    }
+    local.truncate(local_len);
+    (local, len)
+}
+
+fn skip_whitespace(src: &str, mut pos: usize) -> usize {
+    loop {
+        let rest = &src[pos..];
+        let trimmed = rest.trim_start();
+        pos += rest.len() - trimmed.len();
+        if !trimmed.starts_with('#') {
+            return pos;
+        }
+        pos += trimmed.find('\n').unwrap_or(trimmed.len());
+    }
+}
+
+fn tokenize(src: &str) -> Result<Vec<(Token<'_>, usize)>> {
+    let mut tokens = Vec::new();
+    let mut pos = 0;
+    loop {
+        pos = skip_whitespace(src, pos);
+        let rest = &src[pos..];
+        let c = match rest.chars().next() {
+            Some(c) => c,
+            None => break,
+        };
+        let error = |message| Err(syntax_error(src, pos, message));
+        let (token, len) = match c {
+            '<' => {
+                match iri_ref(rest) {
+                    Some((iri, len)) => (Token::IRIRef(iri), len),
+                    None if rest.starts_with("<=") => (Token::Punct("<="), 2),
+                    None => (Token::Punct("<"), 1),
+                }
+            }
+            '"' | '\'' => {
+                match string(rest) {
+                    Some((s, len)) => (Token::String(s), len),
+                    None => return error("invalid string"),
+                }
+            }
+            '?' | '$' => {
+                match varname_len(&rest[1..]) {
+                    0 if c == '?' => (Token::Punct("?"), 1),
+                    0 => return error("invalid variable name"),
+                    len => (Token::Variable(&rest[1..len + 1]), len + 1),
+                }
+            }
+            '@' => {
+                match langtag_len(rest) {
+                    0 => return error("invalid language tag"),
+                    len => (Token::LangTag(&rest[1..len]), len),
+                }
+            }
+            '_' if rest.starts_with("_:") => {
+                match name_len(&rest[2..], |c| is_pn_chars_u(c) || c.is_ascii_digit()) {
+                    0 => return error("invalid blank node label"),
+                    len => (Token::BlankNode(&rest[2..len + 2]), len + 2),
+                }
+            }
+            '0'..='9' | '.' if number(rest).is_some() => number(rest).unwrap(),
+            c if c == ':' || is_pn_chars_base(c) => {
+                let prefix_len = name_len(rest, is_pn_chars_base);
+                if rest[prefix_len..].starts_with(':') {
+                    let (local, len) = pn_local(&rest[prefix_len + 1..]);
+                    (Token::PrefixedName(&rest[..prefix_len], local), prefix_len + 1 + len)
+                } else {
+                    let len = rest[..prefix_len].find('.').unwrap_or(prefix_len);
+                    (Token::Name(&rest[..len]), len)
+                }
+            }
+            _ => {
+                match PUNCTUATION.iter().find(|p| rest.starts_with(*p)) {
+                    Some(p) => (Token::Punct(p), p.len()),
+                    None => return error("unexpected character"),
+                }
+            }
+        };
+        tokens.push((token, pos));
+        pos += len;
+    }
+    tokens.push((Token::End, src.len()));
+    Ok(tokens)
+}
+
+/// The function for a built-in call, with the minimal and maximal number of
+/// arguments.
+fn builtin(name: &str, base: &Option<String>) -> Option<(Function, usize, usize)> {
+    Some(match &name.to_ascii_uppercase()[..] {
+        "STR" => (Function::Str, 1, 1),
+        "LANG" => (Function::Lang, 1, 1),
+        "LANGMATCHES" => (Function::LangMatches, 2, 2),
+        "DATATYPE" => (Function::Datatype, 1, 1),
+        "IRI" | "URI" => (Function::IRI(base.clone()), 1, 1),
+        "BNODE" => (Function::BNode, 0, 1),
+        "RAND" => (Function::Rand, 0, 0),
+        "ABS" => (Function::Abs, 1, 1),
+        "CEIL" => (Function::Ceil, 1, 1),
+        "FLOOR" => (Function::Floor, 1, 1),
+        "ROUND" => (Function::Round, 1, 1),
+        "CONCAT" => (Function::Concat, 0, usize::MAX),
+        "STRLEN" => (Function::StrLen, 1, 1),
+        "UCASE" => (Function::UCase, 1, 1),
+        "LCASE" => (Function::LCase, 1, 1),
+        "ENCODE_FOR_URI" => (Function::EncodeForURI, 1, 1),
+        "CONTAINS" => (Function::Contains, 2, 2),
+        "STRSTARTS" => (Function::StrStarts, 2, 2),
+        "STRENDS" => (Function::StrEnds, 2, 2),
+        "STRBEFORE" => (Function::StrBefore, 2, 2),
+        "STRAFTER" => (Function::StrAfter, 2, 2),
+        "SUBSTR" => (Function::SubStr, 2, 3),
+        "REPLACE" => (Function::Replace, 3, 4),
+        "REGEX" => (Function::Regex, 2, 3),
+        "YEAR" => (Function::Year, 1, 1),
+        "MONTH" => (Function::Month, 1, 1),
+        "DAY" => (Function::Day, 1, 1),
+        "HOURS" => (Function::Hours, 1, 1),
+        "MINUTES" => (Function::Minutes, 1, 1),
+        "SECONDS" => (Function::Seconds, 1, 1),
+        "TIMEZONE" => (Function::Timezone, 1, 1),
+        "TZ" => (Function::Tz, 1, 1),
+        "NOW" => (Function::Now, 0, 0),
+        "UUID" => (Function::UUID, 0, 0),
+        "STRUUID" => (Function::StrUUID, 0, 0),
+        "STRLANG" => (Function::StrLang, 2, 2),
+        "STRDT" => (Function::StrDT, 2, 2),
+        "SAMETERM" => (Function::SameTerm, 2, 2),
+        "ISIRI" | "ISURI" => (Function::IsIRI, 1, 1),
+        "ISBLANK" => (Function::IsBlank, 1, 1),
+        "ISLITERAL" => (Function::IsLiteral, 1, 1),
+        "ISNUMERIC" => (Function::IsNumeric, 1, 1),
+        _ => return None,
+    })
+}
+
+/// Is this the name of a built-in call?
+fn is_builtin_call(name: &str) -> bool {
+    let upper = name.to_ascii_uppercase();
+    builtin(&upper, &None).is_some() || AGGREGATES.contains(&&upper[..]) ||
+    ["BOUND", "IF", "COALESCE", "EXISTS", "NOT", "MD5", "SHA1", "SHA256", "SHA384", "SHA512"]
+        .contains(&&upper[..])
+}
+
+/// The datatypes that can be used as cast functions.
+const CASTS: [&'static str; 7] = [constants::XSD_STRING,
+                                  constants::XSD_BOOLEAN,
+                                  constants::XSD_INTEGER,
+                                  constants::XSD_DECIMAL,
+                                  constants::XSD_FLOAT,
+                                  constants::XSD_DOUBLE,
+                                  constants::XSD_DATE_TIME];
+
+/// The `SELECT` clause: the projected variables with their expressions, or
+/// `None` for `SELECT *`.
+struct SelectClause {
+    distinct: bool,
+    reduced: bool,
+    projection: Option<Vec<(usize, Option<Expression>)>>,
+}
+
+struct Parser<'a> {
+    src: &'a str,
+    tokens: Vec<(Token<'a>, usize)>,
+    pos: usize,
+    base: Option<String>,
+    namespaces: Namespaces,
+    variables: Vec<String>,
+    /// The labels of the blank nodes in a `CONSTRUCT` template. Anonymous
+    /// blank nodes have an empty label.
+    template_blank_nodes: Vec<String>,
+    in_template: bool,
+    /// The property paths in the triples that are being parsed, other than
+    /// the ones that are translated to triple patterns.
+    paths: Vec<(TermPattern, PropertyPath, TermPattern)>,
+    paths_allowed: bool,
+    /// The aggregates of the queries that are being parsed.
+    aggregates: Vec<Vec<(usize, Aggregate)>>,
+    aggregates_allowed: bool,
+}
+
+/// The predicate of triples: a variable or a property path.
+enum Verb {
+    Variable(usize),
+    Path(PropertyPath),
+}
+
+/// Parse a SPARQL query.
+pub fn parse_query(src: &str) -> Result<Query> {
+    Parser::new(src)?.query()
+}
+
+/// Parse a SPARQL update.
+pub fn parse_update(src: &str) -> Result<Vec<UpdateOperation>> {
+    Parser::new(src)?.update()
+}
+
+impl PropertyPath {
+    /// Parse a property path in SPARQL syntax, e.g. `rdfs:subClassOf*`.
+    ///
+    /// The prefixes of prefixed names are looked up in `namespaces`.
+    pub fn parse(path: &str, namespaces: &Namespaces) -> Result<PropertyPath> {
+        let mut parser = Parser::new(path)?;
+        parser.namespaces = namespaces.clone();
+        let path = parser.path()?;
+        if *parser.peek() != Token::End {
+            return parser.error("unexpected input after the path");
+        }
+        Ok(path)
+    }
+}
+
+impl<'a> Parser<'a> {
+    fn new(src: &'a str) -> Result<Parser<'a>> {
+        Ok(Parser {
+            src: src,
+            tokens: tokenize(src)?,
+            pos: 0,
+            base: None,
+            namespaces: Namespaces::new(),
+            variables: Vec::new(),
+            template_blank_nodes: Vec::new(),
+            in_template: false,
+            paths: Vec::new(),
+            paths_allowed: false,
+            aggregates: Vec::new(),
+            aggregates_allowed: false,
+        })
+    }
+    fn error<T>(&self, message: &str) -> Result<T> {
+        Err(syntax_error(self.src, self.tokens[self.pos].1, message))
+    }
+    fn peek(&self) -> &Token<'a> {
+        &self.tokens[self.pos].0
+    }
+    fn peek_second(&self) -> &Token<'a> {
+        &self.tokens[(self.pos + 1).min(self.tokens.len() - 1)].0
+    }
+    fn is_second_keyword(&self, keyword: &str) -> bool {
+        match *self.peek_second() {
+            Token::Name(name) => name.eq_ignore_ascii_case(keyword),
+            _ => false,
+        }
+    }
+    fn next(&mut self) -> Token<'a> {
+        let token = self.tokens[self.pos].0.clone();
+        if self.pos + 1 < self.tokens.len() {
+            self.pos += 1;
+        }
+        token
+    }
+    fn is_keyword(&self, keyword: &str) -> bool {
+        match *self.peek() {
+            Token::Name(name) => name.eq_ignore_ascii_case(keyword),
+            _ => false,
+        }
+    }
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        let found = self.is_keyword(keyword);
+        if found {
+            self.next();
+        }
+        found
+    }
+    fn expect_keyword(&mut self, keyword: &str) -> Result<()> {
+        if self.eat_keyword(keyword) {
+            Ok(())
+        } else {
+            self.error(&format!("expected {}", keyword))
+        }
+    }
+    fn is_punct(&self, punct: &str) -> bool {
+        match *self.peek() {
+            Token::Punct(p) => p == punct,
+            _ => false,
+        }
+    }
+    fn eat_punct(&mut self, punct: &str) -> bool {
+        let found = self.is_punct(punct);
+        if found {
+            self.next();
+        }
+        found
+    }
+    fn expect_punct(&mut self, punct: &str) -> Result<()> {
+        if self.eat_punct(punct) {
+            Ok(())
+        } else {
+            self.error(&format!("expected '{}'", punct))
+        }
+    }
+    fn variable(&mut self, name: &str) -> usize {
+        match self.variables.iter().position(|v| v == name) {
+            Some(v) => v,
+            None => {
+                self.variables.push(String::from(name));
+                self.variables.len() - 1
+            }
+        }
+    }
+    /// Create a variable that cannot be used in the query text.
+    fn hidden_variable(&mut self) -> usize {
+        let name = format!(" {}", self.variables.len());
+        self.variable(&name)
+    }
+    fn blank_node(&mut self, label: Option<&str>) -> TermPattern {
+        if self.in_template {
+            let label = String::from(label.unwrap_or(""));
+            let existing = self.template_blank_nodes.iter().position(|l| *l == label);
+            match existing {
+                Some(b) if !label.is_empty() => TermPattern::BlankNode(b),
+                _ => {
+                    self.template_blank_nodes.push(label);
+                    TermPattern::BlankNode(self.template_blank_nodes.len() - 1)
+                }
+            }
+        } else {
+            match label {
+                Some(label) => TermPattern::Variable(self.variable(&format!(" _:{}", label))),
+                None => TermPattern::Variable(self.hidden_variable()),
+            }
+        }
+    }
+    fn resolve(&self, iri: String) -> String {
+        match self.base {
+            Some(ref base) => iri::join(base, &iri),
+            None => iri,
+        }
+    }
+    // [136] iri
+    fn iri(&mut self) -> Result<Option<String>> {
+        match self.peek().clone() {
+            Token::IRIRef(iri) => {
+                self.next();
+                Ok(Some(self.resolve(iri)))
+            }
+            Token::PrefixedName(prefix, local) => {
+                match self.namespaces.find_namespace(prefix.as_bytes()) {
+                    Some(ns) => {
+                        let iri = format!("{}{}", ns, local);
+                        self.next();
+                        Ok(Some(iri))
+                    }
+                    None => self.error(&format!("unknown prefix '{}'", prefix)),
+                }
+            }
+            _ => Ok(None),
+        }
+    }
+    fn expect_iri(&mut self) -> Result<String> {
+        match self.iri()? {
+            Some(iri) => Ok(iri),
+            None => self.error("expected an IRI"),
+        }
+    }
+    fn expect_variable(&mut self) -> Result<usize> {
+        match *self.peek() {
+            Token::Variable(name) => {
+                self.next();
+                Ok(self.variable(name))
+            }
+            _ => self.error("expected a variable"),
+        }
+    }
+    // [1] QueryUnit
+    fn query(&mut self) -> Result<Query> {
+        self.prologue()?;
+        let query = if self.is_keyword("SELECT") {
+            self.select_query()?
+        } else if self.eat_keyword("ASK") {
+            self.ask_query()?
+        } else if self.eat_keyword("CONSTRUCT") {
+            self.construct_query()?
+        } else if self.eat_keyword("DESCRIBE") {
+            self.describe_query()?
+        } else {
+            return self.error("expected SELECT, ASK, CONSTRUCT or DESCRIBE");
+        };
+        if *self.peek() != Token::End {
+            return self.error("unexpected input after the query");
+        }
+        Ok(query)
+    }
+    // [3] UpdateUnit and [29] Update
+    fn update(&mut self) -> Result<Vec<UpdateOperation>> {
+        let mut operations = Vec::new();
+        loop {
+            self.prologue()?;
+            if *self.peek() == Token::End {
+                break;
+            }
+            self.variables.clear();
+            self.template_blank_nodes.clear();
+            if let Some(operation) = self.update_operation()? {
+                operations.push(operation);
+            }
+            if !self.eat_punct(";") {
+                break;
+            }
+        }
+        if *self.peek() != Token::End {
+            return self.error("unexpected input after the update");
+        }
+        Ok(operations)
+    }
+    // [30] Update1
+    //
+    // `CLEAR NAMED` and `CLEAR SILENT GRAPH` have nothing to clear, so they
+    // return `None`.
+    fn update_operation(&mut self) -> Result<Option<UpdateOperation>> {
+        if self.eat_keyword("LOAD") {
+            let silent = self.eat_keyword("SILENT");
+            let iri = self.expect_iri()?;
+            if self.is_keyword("INTO") {
+                return self.error("named graphs are not supported");
+            }
+            Ok(Some(UpdateOperation::Load(iri, silent)))
+        } else if self.eat_keyword("CLEAR") {
+            let silent = self.eat_keyword("SILENT");
+            if self.eat_keyword("DEFAULT") || self.eat_keyword("ALL") {
+                Ok(Some(UpdateOperation::Clear))
+            } else if self.eat_keyword("NAMED") {
+                Ok(None)
+            } else if self.is_keyword("GRAPH") && silent {
+                self.next();
+                self.expect_iri()?;
+                Ok(None)
+            } else if self.is_keyword("GRAPH") {
+                self.error("named graphs are not supported")
+            } else {
+                self.error("expected DEFAULT, NAMED, ALL or GRAPH")
+            }
+        } else if self.is_keyword("INSERT") && self.is_second_keyword("DATA") {
+            self.next();
+            self.next();
+            self.expect_punct("{")?;
+            let start = self.pos;
+            let insert = self.template()?;
+            self.check_template(&insert, start, "variables are not allowed in INSERT DATA", |p| {
+                match *p {
+                    TermPattern::Variable(_) => true,
+                    _ => false,
+                }
+            })?;
+            Ok(Some(self.modify(Vec::new(), insert, GraphPattern::BGP(Vec::new()))))
+        } else if self.is_keyword("DELETE") && self.is_second_keyword("DATA") {
+            self.next();
+            self.next();
+            self.expect_punct("{")?;
+            let start = self.pos;
+            let delete = self.template()?;
+            self.check_template(&delete,
+                                start,
+                                "variables and blank nodes are not allowed in DELETE DATA",
+                                |p| match *p {
+                                    TermPattern::Constant(_) => false,
+                                    _ => true,
+                                })?;
+            Ok(Some(self.modify(delete, Vec::new(), GraphPattern::BGP(Vec::new()))))
+        } else if self.is_keyword("DELETE") && self.is_second_keyword("WHERE") {
+            self.next();
+            self.next();
+            self.expect_punct("{")?;
+            let start = self.pos;
+            let delete = self.template()?;
+            self.check_template(&delete,
+                                start,
+                                "blank nodes are not allowed in DELETE WHERE",
+                                |p| match *p {
+                                    TermPattern::BlankNode(_) => true,
+                                    _ => false,
+                                })?;
+            let pattern = GraphPattern::BGP(delete.clone());
+            Ok(Some(self.modify(delete, Vec::new(), pattern)))
+        } else if self.is_keyword("WITH") {
+            self.error("WITH is not supported")
+        } else if self.is_keyword("DELETE") || self.is_keyword("INSERT") {
+            self.modify_operation().map(Some)
+        } else {
+            self.error("expected INSERT, DELETE, LOAD or CLEAR")
+        }
+    }
+    // [41] Modify, without `WITH`
+    fn modify_operation(&mut self) -> Result<UpdateOperation> {
+        let mut delete = Vec::new();
+        if self.eat_keyword("DELETE") {
+            self.expect_punct("{")?;
+            let start = self.pos;
+            delete = self.template()?;
+            self.check_template(&delete,
+                                start,
+                                "blank nodes are not allowed in DELETE templates",
+                                |p| match *p {
+                                    TermPattern::BlankNode(_) => true,
+                                    _ => false,
+                                })?;
+        }
+        let mut insert = Vec::new();
+        if self.eat_keyword("INSERT") {
+            self.expect_punct("{")?;
+            insert = self.template()?;
+        }
+        if self.is_keyword("USING") {
+            return self.error("USING is not supported");
+        }
+        self.expect_keyword("WHERE")?;
+        let pattern = self.group_graph_pattern()?;
+        Ok(self.modify(delete, insert, pattern))
+    }
+    /// Parse the triples of a template up to `}`. Blank nodes in the
+    /// template are new for each solution.
+    fn template(&mut self) -> Result<Vec<TriplePattern>> {
+        self.in_template = true;
+        let template = self.triples_template("}");
+        self.in_template = false;
+        template
+    }
+    /// Report an error at token `start` if a term in the template matches
+    /// `invalid`.
+    fn check_template<F>(&self,
+                         template: &[TriplePattern],
+                         start: usize,
+                         message: &str,
+                         invalid: F)
+                         -> Result<()>
+        where F: Fn(&TermPattern) -> bool
+    {
+        let terms = template.iter().flat_map(|t| vec![&t.subject, &t.predicate, &t.object]);
+        if terms.into_iter().any(invalid) {
+            return Err(syntax_error(self.src, self.tokens[start].1, message));
+        }
+        Ok(())
+    }
+    fn modify(&self,
+              delete: Vec<TriplePattern>,
+              insert: Vec<TriplePattern>,
+              pattern: GraphPattern)
+              -> UpdateOperation {
+        UpdateOperation::Modify {
+            delete: delete,
+            insert: insert,
+            pattern: pattern,
+            variables: self.variables.clone(),
+        }
+    }
+    // [4] Prologue
+    fn prologue(&mut self) -> Result<()> {
+        loop {
+            if self.eat_keyword("BASE") {
+                match self.next() {
+                    Token::IRIRef(iri) => self.base = Some(self.resolve(iri)),
+                    _ => return self.error("expected an IRI"),
+                }
+            } else if self.eat_keyword("PREFIX") {
+                let prefix = match self.next() {
+                    Token::PrefixedName(prefix, ref local) if local.is_empty() => prefix,
+                    _ => return self.error("expected a prefix"),
+                };
+                match self.next() {
+                    Token::IRIRef(iri) => {
+                        let iri = self.resolve(iri);
+                        self.namespaces.set(prefix.as_bytes(), &iri);
+                    }
+                    _ => return self.error("expected an IRI"),
+                }
+            } else {
+                return Ok(());
+            }
+        }
+    }
+    fn make_query(&self, form: QueryForm, pattern: GraphPattern) -> Query {
+        Query {
+            form: form,
+            pattern: pattern,
+            variables: self.variables.clone(),
+        }
+    }
+    // [7] SelectQuery
+    fn select_query(&mut self) -> Result<Query> {
+        let select = self.select_clause()?;
+        self.dataset_clause()?;
+        let pattern = self.where_clause()?;
+        let (pattern, variables) = self.solution_modifiers(pattern, Some(select))?;
+        Ok(self.make_query(QueryForm::Select(variables), pattern))
+    }
+    // [8] SubSelect
+    fn sub_select(&mut self) -> Result<GraphPattern> {
+        let select = self.select_clause()?;
+        let pattern = self.where_clause()?;
+        Ok(self.solution_modifiers(pattern, Some(select))?.0)
+    }
+    // [9] SelectClause
+    fn select_clause(&mut self) -> Result<SelectClause> {
+        self.expect_keyword("SELECT")?;
+        self.aggregates.push(Vec::new());
+        let distinct = self.eat_keyword("DISTINCT");
+        let reduced = !distinct && self.eat_keyword("REDUCED");
+        if self.eat_punct("*") {
+            return Ok(SelectClause {
+                distinct: distinct,
+                reduced: reduced,
+                projection: None,
+            });
+        }
+        self.aggregates_allowed = true;
+        let mut projection = Vec::new();
+        loop {
+            match *self.peek() {
+                Token::Variable(name) => {
+                    self.next();
+                    let v = self.variable(name);
+                    projection.push((v, None));
+                }
+                Token::Punct("(") => {
+                    self.next();
+                    let expression = self.expression()?;
+                    self.expect_keyword("AS")?;
+                    let v = self.expect_variable()?;
+                    if projection.iter().any(|&(p, _)| p == v) {
+                        return self.error("the variable is already in the projection");
+                    }
+                    self.expect_punct(")")?;
+                    projection.push((v, Some(expression)));
+                }
+                _ => break,
+            }
+        }
+        self.aggregates_allowed = false;
+        if projection.is_empty() {
+            return self.error("expected a variable or an expression");
+        }
+        Ok(SelectClause {
+            distinct: distinct,
+            reduced: reduced,
+            projection: Some(projection),
+        })
+    }
+    // [10] ConstructQuery
+    fn construct_query(&mut self) -> Result<Query> {
+        self.aggregates.push(Vec::new());
+        let template;
+        let pattern;
+        if self.eat_punct("{") {
+            self.in_template = true;
+            template = self.triples_template("}")?;
+            self.in_template = false;
+            self.dataset_clause()?;
+            pattern = self.where_clause()?;
+        } else {
+            self.dataset_clause()?;
+            self.expect_keyword("WHERE")?;
+            self.expect_punct("{")?;
+            template = self.triples_template("}")?;
+            pattern = GraphPattern::BGP(template.clone());
+        }
+        let (pattern, _) = self.solution_modifiers(pattern, None)?;
+        Ok(self.make_query(QueryForm::Construct(template), pattern))
+    }
+    // [11] DescribeQuery
+    fn describe_query(&mut self) -> Result<Query> {
+        self.aggregates.push(Vec::new());
+        let mut resources = Vec::new();
+        let all = self.eat_punct("*");
+        if !all {
+            loop {
+                if let Token::Variable(name) = *self.peek() {
+                    self.next();
+                    resources.push(TermPattern::Variable(self.variable(name)));
+                } else if let Some(iri) = self.iri()? {
+                    resources.push(TermPattern::Constant(Constant::IRI(iri)));
+                } else {
+                    break;
+                }
+            }
+        }
+        if !all && resources.is_empty() {
+            return self.error("expected a variable or an IRI");
+        }
+        self.dataset_clause()?;
+        let pattern = if self.is_keyword("WHERE") || self.is_punct("{") {
+            self.where_clause()?
+        } else {
+            GraphPattern::BGP(Vec::new())
+        };
+        if all {
+            let mut variables = Vec::new();
+            pattern.visible_variables(&mut variables);
+            resources = variables.into_iter()
+                .filter(|&v| !self.variables[v].starts_with(' '))
+                .map(TermPattern::Variable)
+                .collect();
+        }
+        let (pattern, _) = self.solution_modifiers(pattern, None)?;
+        Ok(self.make_query(QueryForm::Describe(resources), pattern))
+    }
+    // [12] AskQuery
+    fn ask_query(&mut self) -> Result<Query> {
+        self.aggregates.push(Vec::new());
+        self.dataset_clause()?;
+        let pattern = self.where_clause()?;
+        let (pattern, _) = self.solution_modifiers(pattern, None)?;
+        Ok(self.make_query(QueryForm::Ask, pattern))
+    }
+    // [13] DatasetClause
+    fn dataset_clause(&mut self) -> Result<()> {
+        if self.is_keyword("FROM") {
+            return self.error("FROM is not supported");
+        }
+        Ok(())
+    }
+    // [17] WhereClause
+    fn where_clause(&mut self) -> Result<GraphPattern> {
+        self.eat_keyword("WHERE");
+        self.group_graph_pattern()
+    }
+    // [18] SolutionModifier and [28] ValuesClause
+    //
+    // The modifiers are translated to the algebra as described in sections
+    // 18.2.4 and 18.2.5 of the specification. The returned variables are the
+    // projected variables.
+    fn solution_modifiers(&mut self,
+                          mut pattern: GraphPattern,
+                          select: Option<SelectClause>)
+                          -> Result<(GraphPattern, Vec<usize>)> {
+        let group = if self.eat_keyword("GROUP") {
+            self.expect_keyword("BY")?;
+            Some(self.group_conditions()?)
+        } else {
+            None
+        };
+        self.aggregates_allowed = true;
+        let mut having = Vec::new();
+        if self.eat_keyword("HAVING") {
+            having.push(self.constraint()?);
+            while self.is_constraint_start() {
+                having.push(self.constraint()?);
+            }
+        }
+        let mut order = Vec::new();
+        if self.eat_keyword("ORDER") {
+            self.expect_keyword("BY")?;
+            order.push(self.order_condition()?);
+            while self.is_constraint_start() || self.is_keyword("ASC") || self.is_keyword("DESC") ||
+                  match *self.peek() {
+                Token::Variable(_) => true,
+                _ => false,
+            } {
+                order.push(self.order_condition()?);
+            }
+        }
+        self.aggregates_allowed = false;
+        let (limit, offset) = self.limit_offset_clauses()?;
+        let values = if self.eat_keyword("VALUES") {
+            Some(self.data_block()?)
+        } else {
+            None
+        };
+        let aggregates = self.aggregates.pop().unwrap_or_default();
+        let mut visible = Vec::new();
+        pattern.visible_variables(&mut visible);
+        if let Some(ref values) = values {
+            values.visible_variables(&mut visible);
+        }
+        let grouped = group.is_some() || !aggregates.is_empty();
+        let mut group_variables = Vec::new();
+        if grouped {
+            let keys = group.unwrap_or_default();
+            for &(ref e, v) in &keys {
+                match (e, v) {
+                    (_, Some(v)) |
+                    (&Expression::Variable(v), None) => group_variables.push(v),
+                    _ => {}
+                }
+            }
+            group_variables.extend(aggregates.iter().map(|&(v, _)| v));
+            pattern = GraphPattern::Group(Box::new(pattern), keys, aggregates);
+        }
+        for h in having {
+            pattern = GraphPattern::Filter(h, Box::new(pattern));
+        }
+        if let Some(values) = values {
+            pattern = GraphPattern::join(pattern, values);
+        }
+        let mut projected = Vec::new();
+        let mut modifiers = None;
+        if let Some(select) = select {
+            match select.projection {
+                None if grouped => return self.error("SELECT * cannot be used with GROUP BY"),
+                None => {
+                    projected = visible.into_iter()
+                        .filter(|&v| !self.variables[v].starts_with(' '))
+                        .collect();
+                }
+                Some(projection) => {
+                    for (v, expression) in projection {
+                        match expression {
+                            Some(e) => {
+                                pattern = GraphPattern::Extend(Box::new(pattern), v, e);
+                                group_variables.push(v);
+                            }
+                            None if grouped && !group_variables.contains(&v) => {
+                                return self.error(&format!("variable ?{} is not grouped",
+                                                           self.variables[v]))
+                            }
+                            None => {}
+                        }
+                        projected.push(v);
+                    }
+                }
+            }
+            modifiers = Some((select.distinct, select.reduced));
+        }
+        if !order.is_empty() {
+            pattern = GraphPattern::OrderBy(Box::new(pattern), order);
+        }
+        if let Some((distinct, reduced)) = modifiers {
+            pattern = GraphPattern::Project(Box::new(pattern), projected.clone());
+            if distinct {
+                pattern = GraphPattern::Distinct(Box::new(pattern));
+            } else if reduced {
+                pattern = GraphPattern::Reduced(Box::new(pattern));
+            }
+        }
+        if offset > 0 || limit.is_some() {
+            pattern = GraphPattern::Slice(Box::new(pattern), offset, limit);
+        }
+        Ok((pattern, projected))
+    }
+    // [19] GroupClause
+    fn group_conditions(&mut self) -> Result<Vec<(Expression, Option<usize>)>> {
+        let mut conditions = Vec::new();
+        loop {
+            if let Token::Variable(name) = *self.peek() {
+                self.next();
+                conditions.push((Expression::Variable(self.variable(name)), None));
+            } else if self.eat_punct("(") {
+                let expression = self.expression()?;
+                let v = if self.eat_keyword("AS") {
+                    Some(self.expect_variable()?)
+                } else {
+                    None
+                };
+                self.expect_punct(")")?;
+                conditions.push((expression, v));
+            } else if self.is_constraint_start() {
+                conditions.push((self.constraint()?, None));
+            } else {
+                break;
+            }
+        }
+        if conditions.is_empty() {
+            return self.error("expected a grouping condition");
+        }
+        Ok(conditions)
+    }
+    fn is_constraint_start(&self) -> bool {
+        match *self.peek() {
+            Token::Punct("(") |
+            Token::IRIRef(_) |
+            Token::PrefixedName(_, _) => true,
+            Token::Name(name) => is_builtin_call(name),
+            _ => false,
+        }
+    }
+    // [69] Constraint
+    fn constraint(&mut self) -> Result<Expression> {
+        if self.is_constraint_start() {
+            self.primary_expression()
+        } else {
+            self.error("expected a constraint")
+        }
+    }
+    // [24] OrderCondition
+    fn order_condition(&mut self) -> Result<OrderCondition> {
+        let descending = self.is_keyword("DESC");
+        if descending || self.is_keyword("ASC") {
+            self.next();
+            self.expect_punct("(")?;
+            let expression = self.expression()?;
+            self.expect_punct(")")?;
+            return Ok(OrderCondition {
+                expression: expression,
+                descending: descending,
+            });
+        }
+        let expression = match *self.peek() {
+            Token::Variable(_) => self.primary_expression()?,
+            _ => self.constraint()?,
+        };
+        Ok(OrderCondition {
+            expression: expression,
+            descending: false,
+        })
+    }
+    // [25] LimitOffsetClauses
+    fn limit_offset_clauses(&mut self) -> Result<(Option<usize>, usize)> {
+        let mut limit = None;
+        let mut offset = None;
+        loop {
+            if limit.is_none() && self.eat_keyword("LIMIT") {
+                limit = Some(self.integer()?);
+            } else if offset.is_none() && self.eat_keyword("OFFSET") {
+                offset = Some(self.integer()?);
+            } else {
+                return Ok((limit, offset.unwrap_or(0)));
+            }
+        }
+    }
+    fn integer(&mut self) -> Result<usize> {
+        if let Token::Integer(i) = *self.peek() {
+            if let Ok(i) = i.parse() {
+                self.next();
+                return Ok(i);
+            }
+        }
+        self.error("expected an integer")
+    }
+    // [52] TriplesTemplate, followed by `end`
+    fn triples_template(&mut self, end: &str) -> Result<Vec<TriplePattern>> {
+        let mut triples = Vec::new();
+        while !self.eat_punct(end) {
+            self.triples_same_subject(&mut triples)?;
+            if !self.eat_punct(".") {
+                self.expect_punct(end)?;
+                break;
+            }
+        }
+        Ok(triples)
+    }
+    // [53] GroupGraphPattern
+    fn group_graph_pattern(&mut self) -> Result<GraphPattern> {
+        let (pattern, filter) = self.group_graph_pattern_and_filter()?;
+        Ok(match filter {
+            Some(expression) => GraphPattern::Filter(expression, Box::new(pattern)),
+            None => pattern,
+        })
+    }
+    /// Parse a group graph pattern and return the conjunction of the filters
+    /// of the group separately. The filters of a nested group stay in the
+    /// pattern.
+    fn group_graph_pattern_and_filter(&mut self)
+                                      -> Result<(GraphPattern, Option<Expression>)> {
+        self.expect_punct("{")?;
+        let parts = if self.is_keyword("SELECT") {
+            (self.sub_select()?, None)
+        } else {
+            self.group_graph_pattern_sub()?
+        };
+        self.expect_punct("}")?;
+        Ok(parts)
+    }
+    fn is_triples_start(&self) -> bool {
+        match *self.peek() {
+            Token::Variable(_) |
+            Token::IRIRef(_) |
+            Token::PrefixedName(_, _) |
+            Token::BlankNode(_) |
+            Token::String(_) |
+            Token::Integer(_) |
+            Token::Decimal(_) |
+            Token::Double(_) |
+            Token::Punct("[") |
+            Token::Punct("(") |
+            Token::Punct("+") |
+            Token::Punct("-") => true,
+            Token::Name(name) => {
+                name.eq_ignore_ascii_case("true") || name.eq_ignore_ascii_case("false")
+            }
+            _ => false,
+        }
+    }
+    // [54] GroupGraphPatternSub, translated as described in section 18.2.2.6
+    // of the specification
+    fn group_graph_pattern_sub(&mut self) -> Result<(GraphPattern, Option<Expression>)> {
+        let mut pattern = GraphPattern::BGP(Vec::new());
+        let mut filters = Vec::new();
+        loop {
+            if self.is_triples_start() {
+                let mut triples = Vec::new();
+                self.paths_allowed = true;
+                loop {
+                    self.triples_same_subject(&mut triples)?;
+                    if !self.eat_punct(".") || !self.is_triples_start() {
+                        break;
+                    }
+                }
+                self.paths_allowed = false;
+                pattern = GraphPattern::join(pattern, GraphPattern::BGP(triples));
+                for (subject, path, object) in mem::take(&mut self.paths) {
+                    let path = GraphPattern::Path(subject, path, object);
+                    pattern = GraphPattern::join(pattern, path);
+                }
+                continue;
+            }
+            if self.eat_keyword("OPTIONAL") {
+                let (right, expression) = self.group_graph_pattern_and_filter()?;
+                pattern = GraphPattern::LeftJoin(Box::new(pattern), Box::new(right), expression);
+            } else if self.eat_keyword("MINUS") {
+                let right = self.group_graph_pattern()?;
+                pattern = GraphPattern::Minus(Box::new(pattern), Box::new(right));
+            } else if self.is_punct("{") {
+                let mut right = self.group_graph_pattern()?;
+                while self.eat_keyword("UNION") {
+                    let next = self.group_graph_pattern()?;
+                    right = GraphPattern::Union(Box::new(right), Box::new(next));
+                }
+                pattern = GraphPattern::join(pattern, right);
+            } else if self.eat_keyword("FILTER") {
+                filters.push(self.constraint()?);
+            } else if self.eat_keyword("BIND") {
+                self.expect_punct("(")?;
+                let expression = self.expression()?;
+                self.expect_keyword("AS")?;
+                let v = self.expect_variable()?;
+                let mut visible = Vec::new();
+                pattern.visible_variables(&mut visible);
+                if visible.contains(&v) {
+                    return self.error("the variable of BIND is already in scope");
+                }
+                self.expect_punct(")")?;
+                pattern = GraphPattern::Extend(Box::new(pattern), v, expression);
+            } else if self.eat_keyword("VALUES") {
+                let values = self.data_block()?;
+                pattern = GraphPattern::join(pattern, values);
+            } else if self.is_keyword("GRAPH") {
+                return self.error("GRAPH is not supported");
+            } else if self.is_keyword("SERVICE") {
+                return self.error("SERVICE is not supported");
+            } else {
+                break;
+            }
+            self.eat_punct(".");
+        }
+        let filter = filters.pop()
+            .map(|first| filters.into_iter().rev().fold(first, |e, f| and(f, e)));
+        Ok((pattern, filter))
+    }
+    // [62] DataBlock
+    fn data_block(&mut self) -> Result<GraphPattern> {
+        let mut variables = Vec::new();
+        let mut rows = Vec::new();
+        if let Token::Variable(_) = *self.peek() {
+            variables.push(self.expect_variable()?);
+            self.expect_punct("{")?;
+            while !self.eat_punct("}") {
+                rows.push(vec![self.data_block_value()?]);
+            }
+        } else {
+            self.expect_punct("(")?;
+            while !self.eat_punct(")") {
+                variables.push(self.expect_variable()?);
+            }
+            self.expect_punct("{")?;
+            while !self.eat_punct("}") {
+                self.expect_punct("(")?;
+                let mut row = Vec::new();
+                while !self.eat_punct(")") {
+                    row.push(self.data_block_value()?);
+                }
+                if row.len() != variables.len() {
+                    return self.error("the number of values does not match the variables");
+                }
+                rows.push(row);
+            }
+        }
+        Ok(GraphPattern::Values(variables, rows))
+    }
+    // [65] DataBlockValue
+    fn data_block_value(&mut self) -> Result<Option<Constant>> {
+        if self.eat_keyword("UNDEF") {
+            return Ok(None);
+        }
+        match self.graph_term()? {
+            Some(c) => Ok(Some(c)),
+            None => self.error("expected a value or UNDEF"),
+        }
+    }
+    // [75] TriplesSameSubject
+    fn triples_same_subject(&mut self, out: &mut Vec<TriplePattern>) -> Result<()> {
+        if self.is_triples_node() {
+            let subject = self.triples_node(out)?;
+            if self.is_punct(".") || self.is_punct("}") || self.is_punct("]") {
+                return Ok(());
+            }
+            self.property_list_not_empty(&subject, out)
+        } else {
+            let subject = self.var_or_term()?;
+            self.property_list_not_empty(&subject, out)
+        }
+    }
+    // [77] PropertyListNotEmpty
+    fn property_list_not_empty(&mut self,
+                               subject: &TermPattern,
+                               out: &mut Vec<TriplePattern>)
+                               -> Result<()> {
+        loop {
+            let verb = self.verb()?;
+            loop {
+                let object = self.graph_node(out)?;
+                match verb {
+                    Verb::Variable(v) => {
+                        out.push(TriplePattern {
+                            subject: subject.clone(),
+                            predicate: TermPattern::Variable(v),
+                            object: object,
+                        })
+                    }
+                    Verb::Path(ref path) => self.add_path(subject.clone(), path, object, out),
+                }
+                if !self.eat_punct(",") {
+                    break;
+                }
+            }
+            if !self.eat_punct(";") {
+                return Ok(());
+            }
+            while self.eat_punct(";") {}
+            if self.is_punct(".") || self.is_punct("}") || self.is_punct("]") {
+                return Ok(());
+            }
+        }
+    }
+    // [78] Verb, [83] VerbPath and [84] VerbSimple
+    fn verb(&mut self) -> Result<Verb> {
+        if let Token::Variable(name) = *self.peek() {
+            self.next();
+            return Ok(Verb::Variable(self.variable(name)));
+        }
+        let start = self.tokens[self.pos].1;
+        let path = self.path()?;
+        match path {
+            PropertyPath::Predicate(_) => {}
+            _ if !self.paths_allowed => {
+                return Err(syntax_error(self.src, start, "property paths are not allowed here"));
+            }
+            _ => {}
+        }
+        Ok(Verb::Path(path))
+    }
+    /// Add the triples for a property path. Predicates, inverse paths and
+    /// sequences become triple patterns as described in section 18.2.2.4 of
+    /// the specification.
+    fn add_path(&mut self,
+                subject: TermPattern,
+                path: &PropertyPath,
+                object: TermPattern,
+                out: &mut Vec<TriplePattern>) {
+        match *path {
+            PropertyPath::Predicate(ref iri) => {
+                out.push(TriplePattern {
+                    subject: subject,
+                    predicate: TermPattern::Constant(Constant::IRI(iri.clone())),
+                    object: object,
+                })
+            }
+            PropertyPath::Inverse(ref path) => self.add_path(object, path, subject, out),
+            PropertyPath::Sequence(ref a, ref b) => {
+                let middle = TermPattern::Variable(self.hidden_variable());
+                self.add_path(subject, a, middle.clone(), out);
+                self.add_path(middle, b, object, out);
+            }
+            _ => self.paths.push((subject, path.clone(), object)),
+        }
+    }
+    // [88] Path and [89] PathAlternative
+    fn path(&mut self) -> Result<PropertyPath> {
+        let mut path = self.path_sequence()?;
+        while self.eat_punct("|") {
+            let next = self.path_sequence()?;
+            path = PropertyPath::Alternative(Box::new(path), Box::new(next));
+        }
+        Ok(path)
+    }
+    // [90] PathSequence
+    fn path_sequence(&mut self) -> Result<PropertyPath> {
+        let mut path = self.path_elt_or_inverse()?;
+        while self.eat_punct("/") {
+            let next = self.path_elt_or_inverse()?;
+            path = PropertyPath::Sequence(Box::new(path), Box::new(next));
+        }
+        Ok(path)
+    }
+    // [92] PathEltOrInverse
+    fn path_elt_or_inverse(&mut self) -> Result<PropertyPath> {
+        if self.eat_punct("^") {
+            let path = self.path_elt()?;
+            return Ok(PropertyPath::Inverse(Box::new(path)));
+        }
+        self.path_elt()
+    }
+    // [91] PathElt and [93] PathMod
+    fn path_elt(&mut self) -> Result<PropertyPath> {
+        let path = Box::new(self.path_primary()?);
+        Ok(if self.eat_punct("*") {
+            PropertyPath::ZeroOrMore(path)
+        } else if self.eat_punct("+") {
+            PropertyPath::OneOrMore(path)
+        } else if self.eat_punct("?") {
+            PropertyPath::ZeroOrOne(path)
+        } else {
+            *path
+        })
+    }
+    // [94] PathPrimary
+    fn path_primary(&mut self) -> Result<PropertyPath> {
+        if self.eat_punct("!") {
+            return self.path_negated_property_set();
+        }
+        if self.eat_punct("(") {
+            let path = self.path()?;
+            self.expect_punct(")")?;
+            return Ok(path);
+        }
+        Ok(PropertyPath::Predicate(self.path_iri()?))
+    }
+    fn path_iri(&mut self) -> Result<String> {
+        if let Token::Name("a") = *self.peek() {
+            self.next();
+            return Ok(String::from(constants::RDF_TYPE));
+        }
+        self.expect_iri()
+    }
+    // [95] PathNegatedPropertySet and [96] PathOneInPropertySet
+    fn path_negated_property_set(&mut self) -> Result<PropertyPath> {
+        let mut iris = Vec::new();
+        let mut inverse_iris = Vec::new();
+        let parenthesized = self.eat_punct("(");
+        if !(parenthesized && self.eat_punct(")")) {
+            loop {
+                if self.eat_punct("^") {
+                    inverse_iris.push(self.path_iri()?);
+                } else {
+                    iris.push(self.path_iri()?);
+                }
+                if !parenthesized || !self.eat_punct("|") {
+                    break;
+                }
+            }
+            if parenthesized {
+                self.expect_punct(")")?;
+            }
+        }
+        Ok(PropertyPath::NegatedPropertySet(iris, inverse_iris))
+    }
+    fn is_triples_node(&self) -> bool {
+        match (self.peek(), self.peek_second()) {
+            (&Token::Punct("["), &Token::Punct("]")) |
+            (&Token::Punct("("), &Token::Punct(")")) => false,
+            (&Token::Punct("["), _) |
+            (&Token::Punct("("), _) => true,
+            _ => false,
+        }
+    }
+    // [98] TriplesNode
+    fn triples_node(&mut self, out: &mut Vec<TriplePattern>) -> Result<TermPattern> {
+        if self.eat_punct("[") {
+            let node = self.blank_node(None);
+            self.property_list_not_empty(&node, out)?;
+            self.expect_punct("]")?;
+            return Ok(node);
+        }
+        self.expect_punct("(")?;
+        let mut items = Vec::new();
+        while !self.eat_punct(")") {
+            items.push(self.graph_node(out)?);
+        }
+        let iri = |iri: &str| TermPattern::Constant(Constant::IRI(String::from(iri)));
+        let mut list = iri(constants::RDF_NIL);
+        for item in items.into_iter().rev() {
+            let node = self.blank_node(None);
+            out.push(TriplePattern {
+                subject: node.clone(),
+                predicate: iri(constants::RDF_FIRST),
+                object: item,
+            });
+            out.push(TriplePattern {
+                subject: node.clone(),
+                predicate: iri(constants::RDF_REST),
+                object: list,
+            });
+            list = node;
+        }
+        Ok(list)
+    }
+    // [104] GraphNode
+    fn graph_node(&mut self, out: &mut Vec<TriplePattern>) -> Result<TermPattern> {
+        if self.is_triples_node() {
+            self.triples_node(out)
+        } else {
+            self.var_or_term()
+        }
+    }
+    // [106] VarOrTerm
+    fn var_or_term(&mut self) -> Result<TermPattern> {
+        match *self.peek() {
+            Token::Variable(name) => {
+                self.next();
+                Ok(TermPattern::Variable(self.variable(name)))
+            }
+            Token::BlankNode(label) => {
+                self.next();
+                Ok(self.blank_node(Some(label)))
+            }
+            Token::Punct("[") if *self.peek_second() == Token::Punct("]") => {
+                self.next();
+                self.next();
+                Ok(self.blank_node(None))
+            }
+            Token::Punct("(") if *self.peek_second() == Token::Punct(")") => {
+                self.next();
+                self.next();
+                Ok(TermPattern::Constant(Constant::IRI(String::from(constants::RDF_NIL))))
+            }
+            _ => {
+                match self.graph_term()? {
+                    Some(c) => Ok(TermPattern::Constant(c)),
+                    None => self.error("expected a variable or a term"),
+                }
+            }
+        }
+    }
+    // [109] GraphTerm without blank nodes and NIL
+    fn graph_term(&mut self) -> Result<Option<Constant>> {
+        if let Some(iri) = self.iri()? {
+            return Ok(Some(Constant::IRI(iri)));
+        }
+        Ok(self.literal()?.map(Constant::Literal))
+    }
+    // [129] RDFLiteral, [130] NumericLiteral and [134] BooleanLiteral
+    fn literal(&mut self) -> Result<Option<Literal>> {
+        let sign = match (self.peek(), self.peek_second()) {
+            (&Token::Punct(sign), &Token::Integer(_)) |
+            (&Token::Punct(sign), &Token::Decimal(_)) |
+            (&Token::Punct(sign), &Token::Double(_)) if sign == "+" || sign == "-" => {
+                self.next();
+                sign
+            }
+            _ => "",
+        };
+        let literal = match self.peek().clone() {
+            Token::Integer(i) => Literal::new(format!("{}{}", sign, i), constants::XSD_INTEGER),
+            Token::Decimal(d) => Literal::new(format!("{}{}", sign, d), constants::XSD_DECIMAL),
+            Token::Double(d) => Literal::new(format!("{}{}", sign, d), constants::XSD_DOUBLE),
+            Token::Name(name) if name.eq_ignore_ascii_case("true") => Literal::boolean(true),
+            Token::Name(name) if name.eq_ignore_ascii_case("false") => Literal::boolean(false),
+            Token::String(s) => {
+                self.next();
+                return Ok(Some(match *self.peek() {
+                    Token::LangTag(language) => {
+                        self.next();
+                        Literal::language(s, language)
+                    }
+                    Token::Punct("^^") => {
+                        self.next();
+                        let datatype = self.expect_iri()?;
+                        Literal::new(s, &datatype)
+                    }
+                    _ => Literal::string(s),
+                }));
+            }
+            _ => return Ok(None),
+        };
+        self.next();
+        Ok(Some(literal))
+    }
+    // [110] Expression
+    fn expression(&mut self) -> Result<Expression> {
+        let mut e = self.conditional_and_expression()?;
+        while self.eat_punct("||") {
+            let right = self.conditional_and_expression()?;
+            e = Expression::Binary(Operator::Or, Box::new(e), Box::new(right));
+        }
+        Ok(e)
+    }
+    // [112] ConditionalAndExpression
+    fn conditional_and_expression(&mut self) -> Result<Expression> {
+        let mut e = self.relational_expression()?;
+        while self.eat_punct("&&") {
+            let right = self.relational_expression()?;
+            e = and(e, right);
+        }
+        Ok(e)
+    }
+    // [114] RelationalExpression
+    fn relational_expression(&mut self) -> Result<Expression> {
+        let e = self.additive_expression()?;
+        let operator = match *self.peek() {
+            Token::Punct("=") => Operator::Equal,
+            Token::Punct("!=") => Operator::NotEqual,
+            Token::Punct("<") => Operator::Less,
+            Token::Punct(">") => Operator::Greater,
+            Token::Punct("<=") => Operator::LessOrEqual,
+            Token::Punct(">=") => Operator::GreaterOrEqual,
+            _ => {
+                if self.eat_keyword("IN") {
+                    let list = self.expression_list()?;
+                    return Ok(Expression::In(Box::new(e), list));
+                }
+                if self.is_keyword("NOT") {
+                    if let Token::Name(name) = *self.peek_second() {
+                        if name.eq_ignore_ascii_case("IN") {
+                            self.next();
+                            self.next();
+                            let list = self.expression_list()?;
+                            return Ok(Expression::Not(Box::new(Expression::In(Box::new(e),
+                                                                               list))));
+                        }
+                    }
+                }
+                return Ok(e);
+            }
+        };
+        self.next();
+        let right = self.additive_expression()?;
+        Ok(Expression::Binary(operator, Box::new(e), Box::new(right)))
+    }
+    // [116] AdditiveExpression
+    fn additive_expression(&mut self) -> Result<Expression> {
+        let mut e = self.multiplicative_expression()?;
+        loop {
+            let operator = match *self.peek() {
+                Token::Punct("+") => Operator::Add,
+                Token::Punct("-") => Operator::Subtract,
+                _ => return Ok(e),
+            };
+            self.next();
+            let right = self.multiplicative_expression()?;
+            e = Expression::Binary(operator, Box::new(e), Box::new(right));
+        }
+    }
+    // [117] MultiplicativeExpression
+    fn multiplicative_expression(&mut self) -> Result<Expression> {
+        let mut e = self.unary_expression()?;
+        loop {
+            let operator = match *self.peek() {
+                Token::Punct("*") => Operator::Multiply,
+                Token::Punct("/") => Operator::Divide,
+                _ => return Ok(e),
+            };
+            self.next();
+            let right = self.unary_expression()?;
+            e = Expression::Binary(operator, Box::new(e), Box::new(right));
+        }
+    }
+    // [118] UnaryExpression
+    fn unary_expression(&mut self) -> Result<Expression> {
+        if self.eat_punct("!") {
+            Ok(Expression::Not(Box::new(self.unary_expression()?)))
+        } else if self.eat_punct("+") {
+            Ok(Expression::UnaryPlus(Box::new(self.unary_expression()?)))
+        } else if self.eat_punct("-") {
+            Ok(Expression::UnaryMinus(Box::new(self.unary_expression()?)))
+        } else {
+            self.primary_expression()
+        }
+    }
+    // [119] PrimaryExpression
+    fn primary_expression(&mut self) -> Result<Expression> {
+        match *self.peek() {
+            Token::Punct("(") => {
+                self.next();
+                let e = self.expression()?;
+                self.expect_punct(")")?;
+                return Ok(e);
+            }
+            Token::Variable(name) => {
+                self.next();
+                return Ok(Expression::Variable(self.variable(name)));
+            }
+            Token::Name(name) if is_builtin_call(name) => return self.builtin_call(name),
+            _ => {}
+        }
+        if let Some(iri) = self.iri()? {
+            if !self.is_punct("(") {
+                return Ok(Expression::Constant(Constant::IRI(iri)));
+            }
+            if !CASTS.contains(&&iri[..]) {
+                return self.error(&format!("unknown function <{}>", iri));
+            }
+            let arguments = self.arguments()?;
+            if arguments.len() != 1 {
+                return self.error("a cast has one argument");
+            }
+            return Ok(Expression::Function(Function::Cast(iri), arguments));
+        }
+        match self.literal()? {
+            Some(literal) => Ok(Expression::Constant(Constant::Literal(literal))),
+            None => self.error("expected an expression"),
+        }
+    }
+    // [71] ArgList
+    fn arguments(&mut self) -> Result<Vec<Expression>> {
+        self.expect_punct("(")?;
+        let mut arguments = Vec::new();
+        if self.eat_punct(")") {
+            return Ok(arguments);
+        }
+        loop {
+            arguments.push(self.expression()?);
+            if !self.eat_punct(",") {
+                self.expect_punct(")")?;
+                return Ok(arguments);
+            }
+        }
+    }
+    // [72] ExpressionList
+    fn expression_list(&mut self) -> Result<Vec<Expression>> {
+        self.arguments()
+    }
+    // [121] BuiltInCall
+    fn builtin_call(&mut self, name: &str) -> Result<Expression> {
+        let upper = name.to_ascii_uppercase();
+        if AGGREGATES.contains(&&upper[..]) {
+            return self.aggregate(&upper);
+        }
+        match &upper[..] {
+            "BOUND" => {
+                self.next();
+                self.expect_punct("(")?;
+                let v = self.expect_variable()?;
+                self.expect_punct(")")?;
+                return Ok(Expression::Bound(v));
+            }
+            "EXISTS" => {
+                self.next();
+                return self.exists();
+            }
+            "NOT" => {
+                self.next();
+                self.expect_keyword("EXISTS")?;
+                return Ok(Expression::Not(Box::new(self.exists()?)));
+            }
+            "MD5" | "SHA1" | "SHA256" | "SHA384" | "SHA512" => {
+                return self.error(&format!("{} is not supported", upper));
+            }
+            _ => {}
+        }
+        self.next();
+        let mut arguments = self.arguments()?;
+        match &upper[..] {
+            "IF" if arguments.len() == 3 => {
+                let c = Box::new(arguments.pop().unwrap());
+                let b = Box::new(arguments.pop().unwrap());
+                let a = Box::new(arguments.pop().unwrap());
+                return Ok(Expression::If(a, b, c));
+            }
+            "IF" => return self.error("IF has three arguments"),
+            "COALESCE" => return Ok(Expression::Coalesce(arguments)),
+            _ => {}
+        }
+        let (function, min, max) = builtin(&upper, &self.base).unwrap();
+        if arguments.len() < min || arguments.len() > max {
+            return self.error(&format!("wrong number of arguments for {}", upper));
+        }
+        Ok(Expression::Function(function, arguments))
+    }
+    // [125] ExistsFunc
+    fn exists(&mut self) -> Result<Expression> {
+        let aggregates_allowed = self.aggregates_allowed;
+        self.aggregates_allowed = false;
+        let pattern = self.group_graph_pattern()?;
+        self.aggregates_allowed = aggregates_allowed;
+        Ok(Expression::Exists(Box::new(pattern)))
+    }
+    // [127] Aggregate
+    fn aggregate(&mut self, name: &str) -> Result<Expression> {
+        if !self.aggregates_allowed || self.aggregates.is_empty() {
+            return self.error("aggregates are only allowed in SELECT, HAVING and ORDER BY");
+        }
+        self.next();
+        self.expect_punct("(")?;
+        let distinct = self.eat_keyword("DISTINCT");
+        self.aggregates_allowed = false;
+        let expression = if name == "COUNT" && self.eat_punct("*") {
+            None
+        } else {
+            Some(self.expression()?)
+        };
+        self.aggregates_allowed = true;
+        let function = match name {
+            "COUNT" => AggregateFunction::Count,
+            "SUM" => AggregateFunction::Sum,
+            "MIN" => AggregateFunction::Min,
+            "MAX" => AggregateFunction::Max,
+            "AVG" => AggregateFunction::Avg,
+            "SAMPLE" => AggregateFunction::Sample,
+            _ => {
+                let mut separator = String::from(" ");
+                if self.eat_punct(";") {
+                    self.expect_keyword("SEPARATOR")?;
+                    self.expect_punct("=")?;
+                    match self.next() {
+                        Token::String(s) => separator = s,
+                        _ => return self.error("expected a string"),
+                    }
+                }
+                AggregateFunction::GroupConcat(separator)
+            }
+        };
+        self.expect_punct(")")?;
+        let v = self.hidden_variable();
+        let aggregate = Aggregate {
+            function: function,
+            distinct: distinct,
+            expression: expression,
+        };
+        self.aggregates.last_mut().unwrap().push((v, aggregate));
+        Ok(Expression::Variable(v))
+    }
+}
+
+/// Combine two expressions with `&&`.
+fn and(a: Expression, b: Expression) -> Expression {
+    Expression::Binary(Operator::And, Box::new(a), Box::new(b))
+}
+
+#[test]
+fn test_tokenize() {
+    let tokens: Vec<Token> = tokenize("PREFIX ex: <http://a.example/> # comment\n\
+                                       SELECT ?x WHERE { ?x ex:p\\.q \"a\\n\"@en, 1.5, -2 }")
+        .unwrap()
+        .into_iter()
+        .map(|(t, _)| t)
+        .collect();
+    assert_eq!(tokens,
+               vec![Token::Name("PREFIX"),
+                    Token::PrefixedName("ex", String::new()),
+                    Token::IRIRef(String::from("http://a.example/")),
+                    Token::Name("SELECT"),
+                    Token::Variable("x"),
+                    Token::Name("WHERE"),
+                    Token::Punct("{"),
+                    Token::Variable("x"),
+                    Token::PrefixedName("ex", String::from("p.q")),
+                    Token::String(String::from("a\n")),
+                    Token::LangTag("en"),
+                    Token::Punct(","),
+                    Token::Decimal("1.5"),
+                    Token::Punct(","),
+                    Token::Punct("-"),
+                    Token::Integer("2"),
+                    Token::Punct("}"),
+                    Token::End]);
+}
+
+#[test]
+fn test_parse_errors() {
+    let error = |query: &str| match parse_query(query) {
+        Err(Error::Syntax(e)) => (e.line, e.column),
+        _ => panic!("expected a syntax error for {}", query),
+    };
+    assert_eq!(error("SELECT * WHERE { ?x ex:p ?y }"), (1, 21));
+    assert_eq!(error("SELECT ?x\nWHERE { ?x <p> ?y"), (2, 18));
+    assert_eq!(error("SELECT (COUNT(*) AS ?n) WHERE { ?x <p> ?y } GROUP BY ?y HAVING ?x"),
+               (1, 64));
+    assert_eq!(error("SELECT ?x WHERE { FILTER(COUNT(?x) > 1) }"), (1, 26));
+    assert_eq!(error("SELECT ?x ?y WHERE { ?x <p> ?y } GROUP BY ?x"), (1, 45));
+    assert!(parse_query("SELECT ?x WHERE { ?x <p> ?y } GROUP BY ?x").is_ok());
+}
+
+#[test]
+fn test_update_parse_errors() {
+    let error = |update: &str| match parse_update(update) {
+        Err(Error::Syntax(e)) => e.column,
+        _ => panic!("expected a syntax error for {}", update),
+    };
+    assert_eq!(error("INSERT DATA { <a> <b> ?c }"), 15);
+    assert_eq!(error("DELETE DATA { <a> <b> [] }"), 15);
+    assert_eq!(error("DELETE { _:b <b> ?c } WHERE { ?a <b> ?c }"), 10);
+    assert_eq!(error("DELETE WHERE { _:b <b> ?c }"), 16);
+    assert_eq!(error("DELETE WHERE { ?a <b> [ <c> ?d ] }"), 16);
+    assert_eq!(error("INSERT DATA { <a> <b> <c> } INSERT DATA { <a> <b> <d> }"), 29);
+    assert_eq!(error("CLEAR GRAPH <g>"), 7);
+    assert_eq!(error("DROP DEFAULT"), 1);
+    assert_eq!(parse_update("CLEAR SILENT GRAPH <g> ; CLEAR NAMED").unwrap(), []);
+    assert_eq!(parse_update("").unwrap(), []);
+    assert_eq!(parse_update("CLEAR ALL ; LOAD SILENT <f>").unwrap(),
+               [UpdateOperation::Clear, UpdateOperation::Load(String::from("f"), true)]);
+}
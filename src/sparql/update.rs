@@ -0,0 +1,155 @@
+use error::{Error, Result};
+use graph::{Graph, GraphWriter};
+use graphs::mem::{IRIPtr, LiteralPtr, MemGraph, Object};
+use io::{NTriplesParser, RDFXMLParser, TurtleParser};
+use iri;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+use super::ast::*;
+use super::eval::{construct, Evaluator};
+use super::value::Value;
+
+/// A subject or object of a triple that is deleted or inserted by an update.
+enum Node {
+    Term(Object),
+    /// A blank node that is created by the update.
+    BlankNode(usize),
+}
+
+/// Apply the operations of an update to a graph, in order.
+pub fn execute(operations: &[UpdateOperation], graph: &mut MemGraph) -> Result<()> {
+    for operation in operations {
+        match *operation {
+            UpdateOperation::Modify { ref delete, ref insert, ref pattern, ref variables } => {
+                modify(graph, delete, insert, pattern, variables)
+            }
+            UpdateOperation::Clear => graph.clear(),
+            UpdateOperation::Load(ref iri, silent) => {
+                match load(graph, iri) {
+                    Err(_) if silent => {}
+                    result => result?,
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Apply the operations of an update to a copy of a graph and write the
+/// result to `writer`.
+pub fn execute_into<'g, G, W>(operations: &[UpdateOperation],
+                              graph: &'g G,
+                              mut writer: W)
+                              -> Result<W::Graph>
+    where G: Graph<'g> + 'g,
+          W: GraphWriter<'g>
+{
+    let mut copy = MemGraph::new();
+    copy.add_graph(graph);
+    execute(operations, &mut copy)?;
+    copy.write_to(&mut writer);
+    Ok(writer.collect())
+}
+
+fn modify(graph: &mut MemGraph,
+          delete: &[TriplePattern],
+          insert: &[TriplePattern],
+          pattern: &GraphPattern,
+          variables: &[String]) {
+    // all solutions are found before the graph is changed
+    let (deleted, inserted) = {
+        let evaluator = Evaluator::new(&*graph, variables);
+        let rows = evaluator.evaluate(pattern, &vec![None; variables.len()]);
+        let nodes = |triples: Vec<[Value<MemGraph>; 3]>| -> Vec<(Node, IRIPtr, Node)> {
+            triples.iter()
+                .filter_map(|t| {
+                    let predicate = IRIPtr { iri: Arc::from(t[1].iri()?) };
+                    Some((node(&t[0]), predicate, node(&t[2])))
+                })
+                .collect()
+        };
+        let deleted = nodes(construct(&evaluator, delete, &rows));
+        (deleted, nodes(construct(&evaluator, insert, &rows)))
+    };
+    for (subject, predicate, object) in deleted {
+        if let (Node::Term(subject), Node::Term(object)) = (subject, object) {
+            if let Some(subject) = subject.to_subject() {
+                graph.remove(&subject, &predicate, &object);
+            }
+        }
+    }
+    let mut blank_nodes = BTreeMap::new();
+    let mut resolve = |node: Node, graph: &mut MemGraph| match node {
+        Node::Term(term) => term,
+        Node::BlankNode(b) => {
+            Object::BlankNode(*blank_nodes.entry(b).or_insert_with(|| graph.create_blank_node()))
+        }
+    };
+    for (subject, predicate, object) in inserted {
+        let subject = resolve(subject, graph);
+        let object = resolve(object, graph);
+        if let Some(subject) = subject.to_subject() {
+            graph.insert(subject, predicate, object);
+        }
+    }
+}
+
+fn node(value: &Value<MemGraph>) -> Node {
+    match *value {
+        Value::Term(ref term) => Node::Term(Object::from(term)),
+        Value::IRI(ref iri) => Node::Term(Object::IRI(IRIPtr { iri: Arc::from(iri.as_str()) })),
+        Value::Literal(ref literal) => {
+            Node::Term(Object::Literal(LiteralPtr {
+                lexical: Arc::from(literal.lexical.as_str()),
+                datatype: Arc::from(literal.datatype.as_str()),
+                language: literal.language.as_ref().map(|l| Arc::from(l.as_str())),
+            }))
+        }
+        Value::BlankNode(b) => Node::BlankNode(b),
+    }
+}
+
+/// Add the triples in a local file to the graph.
+///
+/// The file is given as a `file:` IRI, such as `file:///tmp/data.ttl` or
+/// `file:/tmp/data.ttl`, or as a path. Files with the extension
+/// `nt` are read as N-Triples, files with the extension `rdf`, `owl` or
+/// `xml` as RDF/XML and all other files as Turtle. If the file cannot be
+/// read completely, the graph is not changed.
+fn load(graph: &mut MemGraph, iri: &str) -> Result<()> {
+    let path = if iri::is_absolute(iri) {
+        match iri.strip_prefix("file://").or_else(|| iri.strip_prefix("file:")) {
+            Some(path) => path,
+            None => return Err(Error::String(format!("{} is not a local file", iri))),
+        }
+    } else {
+        iri
+    };
+    let text = fs::read_to_string(path)?;
+    let base = if iri::is_absolute(iri) {
+        String::from(iri)
+    } else {
+        format!("file://{}", fs::canonicalize(path)?.display())
+    };
+    let mut loaded = MemGraph::new();
+    match Path::new(path).extension().and_then(|e| e.to_str()) {
+        Some("nt") => read(NTriplesParser::new(&text, &mut loaded)?)?,
+        Some("rdf") | Some("owl") | Some("xml") => {
+            read(RDFXMLParser::new(&text, &base, &mut loaded)?)?
+        }
+        _ => read(TurtleParser::new(&text, &base, &mut loaded)?)?,
+    }
+    loaded.write_to(graph);
+    Ok(())
+}
+
+fn read<I>(steps: I) -> Result<()>
+    where I: Iterator<Item = Result<()>>
+{
+    for step in steps {
+        step?;
+    }
+    Ok(())
+}
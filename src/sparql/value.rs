@@ -0,0 +1,562 @@
+use constants;
+use graph::{Graph, IRIPtr, LiteralPtr, Resource};
+use query::Term;
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::fmt;
+
+const XSD: &'static str = "http://www.w3.org/2001/XMLSchema#";
+
+/// A literal that was created by a query.
+#[derive(Clone,Debug,PartialEq,Eq,PartialOrd,Ord,Hash)]
+pub struct Literal {
+    /// The lexical value.
+    pub lexical: String,
+    /// The datatype IRI.
+    pub datatype: String,
+    /// The language, if the datatype is `rdf:langString`.
+    pub language: Option<String>,
+}
+
+impl Literal {
+    /// Create a literal with the given datatype.
+    pub fn new(lexical: String, datatype: &str) -> Literal {
+        Literal {
+            lexical: lexical,
+            datatype: String::from(datatype),
+            language: None,
+        }
+    }
+    /// Create an `xsd:string` literal.
+    pub fn string(lexical: String) -> Literal {
+        Literal::new(lexical, constants::XSD_STRING)
+    }
+    /// Create an `xsd:boolean` literal.
+    pub fn boolean(value: bool) -> Literal {
+        Literal::new(String::from(if value { "true" } else { "false" }),
+                     constants::XSD_BOOLEAN)
+    }
+    /// Create a literal with a language.
+    pub fn language(lexical: String, language: &str) -> Literal {
+        Literal {
+            lexical: lexical,
+            datatype: String::from(constants::RDF_LANG_STRING),
+            language: Some(String::from(language)),
+        }
+    }
+    /// Borrow the parts of the literal.
+    pub fn as_literal_ref(&self) -> LiteralRef<'_> {
+        LiteralRef {
+            lexical: &self.lexical,
+            datatype: &self.datatype,
+            language: self.language.as_deref(),
+        }
+    }
+}
+
+/// The parts of a literal in a query result.
+#[derive(Clone,Copy,Debug,PartialEq,Eq)]
+pub struct LiteralRef<'a> {
+    /// The lexical value.
+    pub lexical: &'a str,
+    /// The datatype IRI.
+    pub datatype: &'a str,
+    /// The language, if the datatype is `rdf:langString`.
+    pub language: Option<&'a str>,
+}
+
+impl<'a> LiteralRef<'a> {
+    /// Copy the literal.
+    pub fn to_literal(&self) -> Literal {
+        Literal {
+            lexical: String::from(self.lexical),
+            datatype: String::from(self.datatype),
+            language: self.language.map(String::from),
+        }
+    }
+    /// Is this an `xsd:string` or a literal with a language?
+    pub fn is_string(&self) -> bool {
+        self.datatype == constants::XSD_STRING || self.language.is_some()
+    }
+}
+
+/// A value that is bound to a variable in a query result.
+///
+/// Values that are read from the graph point into the graph. Values that
+/// are computed by the query, such as the result of `STR(?x)`, are not in the
+/// graph and own their data.
+pub enum Value<'g, G>
+    where G: Graph<'g>
+{
+    /// A term from the graph.
+    Term(Term<'g, G>),
+    /// An IRI that was computed by the query.
+    IRI(String),
+    /// A literal that was computed by the query.
+    Literal(Literal),
+    /// A blank node that was created by the query.
+    BlankNode(usize),
+}
+
+impl<'g, G> Value<'g, G>
+    where G: Graph<'g>
+{
+    /// The IRI, if this value is an IRI.
+    pub fn iri(&self) -> Option<&str> {
+        match *self {
+            Value::Term(Resource::IRI(ref i)) => Some(i.as_str()),
+            Value::IRI(ref i) => Some(i),
+            _ => None,
+        }
+    }
+    /// The literal, if this value is a literal.
+    pub fn literal(&self) -> Option<LiteralRef<'_>> {
+        match *self {
+            Value::Term(Resource::Literal(ref l)) => {
+                Some(LiteralRef {
+                    lexical: l.as_str(),
+                    datatype: l.datatype_str(),
+                    language: l.language(),
+                })
+            }
+            Value::Literal(ref l) => Some(l.as_literal_ref()),
+            _ => None,
+        }
+    }
+    /// Is this value a blank node?
+    pub fn is_blank_node(&self) -> bool {
+        match *self {
+            Value::Term(Resource::BlankNode(_, _)) |
+            Value::BlankNode(_) => true,
+            _ => false,
+        }
+    }
+    /// Is this value a quoted triple?
+    pub fn is_quoted_triple(&self) -> bool {
+        match *self {
+            Value::Term(Resource::QuotedTriple(_)) => true,
+            _ => false,
+        }
+    }
+    /// The effective boolean value, as used by `FILTER`.
+    ///
+    /// Values other than booleans, numbers and strings have no effective
+    /// boolean value.
+    pub fn effective_boolean_value(&self) -> Option<bool> {
+        let literal = self.literal()?;
+        if literal.datatype == constants::XSD_BOOLEAN {
+            Some(literal.lexical == "true" || literal.lexical == "1")
+        } else if literal.is_string() {
+            Some(!literal.lexical.is_empty())
+        } else if is_numeric_datatype(literal.datatype) {
+            Some(Numeric::from_literal(literal).is_some_and(|n| n.to_f64() != 0. &&
+                                                             !n.to_f64().is_nan()))
+        } else {
+            None
+        }
+    }
+    fn rank(&self) -> u8 {
+        match *self {
+            Value::Term(Resource::BlankNode(_, _)) => 0,
+            Value::BlankNode(_) => 1,
+            Value::Term(Resource::IRI(_)) |
+            Value::IRI(_) => 2,
+            Value::Term(Resource::Literal(_)) |
+            Value::Literal(_) => 3,
+            Value::Term(Resource::QuotedTriple(_)) => 4,
+        }
+    }
+}
+
+/// Compare two values as RDF terms.
+///
+/// The order is total and two values are equal only if they are the same RDF
+/// term. It is used to sort and group solutions.
+pub fn compare_terms<'g, G>(a: &Value<'g, G>, b: &Value<'g, G>) -> Ordering
+    where G: Graph<'g>
+{
+    let cmp = a.rank().cmp(&b.rank());
+    if cmp != Ordering::Equal {
+        return cmp;
+    }
+    match (a, b) {
+        (&Value::Term(Resource::BlankNode(ref a, _)),
+         &Value::Term(Resource::BlankNode(ref b, _))) => a.cmp(b),
+        (&Value::BlankNode(a), &Value::BlankNode(b)) => a.cmp(&b),
+        (&Value::Term(Resource::QuotedTriple(ref a)),
+         &Value::Term(Resource::QuotedTriple(ref b))) => a.cmp(b),
+        _ => {
+            match (a.literal(), b.literal()) {
+                (Some(a), Some(b)) => {
+                    (a.lexical, a.datatype, a.language).cmp(&(b.lexical, b.datatype, b.language))
+                }
+                _ => a.iri().cmp(&b.iri()),
+            }
+        }
+    }
+}
+
+/// Compare two optional values as RDF terms. Unbound values come first.
+pub fn compare_optional_terms<'g, G>(a: Option<&Value<'g, G>>,
+                                     b: Option<&Value<'g, G>>)
+                                     -> Ordering
+    where G: Graph<'g>
+{
+    match (a, b) {
+        (Some(a), Some(b)) => compare_terms(a, b),
+        (a, b) => a.is_some().cmp(&b.is_some()),
+    }
+}
+
+impl<'g, G> Clone for Value<'g, G>
+    where G: Graph<'g>
+{
+    fn clone(&self) -> Self {
+        match *self {
+            Value::Term(ref t) => Value::Term(t.clone()),
+            Value::IRI(ref i) => Value::IRI(i.clone()),
+            Value::Literal(ref l) => Value::Literal(l.clone()),
+            Value::BlankNode(b) => Value::BlankNode(b),
+        }
+    }
+}
+
+impl<'g, G> PartialEq for Value<'g, G>
+    where G: Graph<'g>
+{
+    fn eq(&self, other: &Self) -> bool {
+        compare_terms(self, other) == Ordering::Equal
+    }
+}
+
+impl<'g, G> Eq for Value<'g, G> where G: Graph<'g> {}
+
+/// Is the datatype one of the numeric XSD datatypes?
+pub fn is_numeric_datatype(datatype: &str) -> bool {
+    datatype == constants::XSD_DECIMAL || datatype == constants::XSD_FLOAT ||
+    datatype == constants::XSD_DOUBLE || is_integer_datatype(datatype)
+}
+
+fn is_integer_datatype(datatype: &str) -> bool {
+    datatype.starts_with(XSD) &&
+    match &datatype[XSD.len()..] {
+        "integer" | "nonPositiveInteger" | "negativeInteger" | "long" | "int" | "short" |
+        "byte" | "nonNegativeInteger" | "unsignedLong" | "unsignedInt" | "unsignedShort" |
+        "unsignedByte" | "positiveInteger" => true,
+        _ => false,
+    }
+}
+
+/// The number of fraction digits of a `Decimal`.
+const DECIMAL_DIGITS: usize = 18;
+const DECIMAL_SCALE: i128 = 1_000_000_000_000_000_000;
+
+/// An `xsd:decimal` with a fixed number of fraction digits.
+///
+/// The value is stored as an integer that is scaled by 10^18, so sums and
+/// products of decimals are exact. Digits after the 18th fraction digit are
+/// truncated.
+#[derive(Clone,Copy,Debug,PartialEq,Eq,PartialOrd,Ord)]
+pub struct Decimal(i128);
+
+impl Decimal {
+    /// Read a decimal from its lexical form, e.g. `-1.5`, `1.` or `.5`.
+    pub fn parse(lexical: &str) -> Option<Decimal> {
+        let (negative, digits) = match lexical.as_bytes().first() {
+            Some(&b'-') => (true, &lexical[1..]),
+            Some(&b'+') => (false, &lexical[1..]),
+            _ => (false, lexical),
+        };
+        let (integer, fraction) = match digits.find('.') {
+            Some(dot) => (&digits[..dot], &digits[dot + 1..]),
+            None => (digits, ""),
+        };
+        if integer.is_empty() && fraction.is_empty() ||
+           !integer.bytes().chain(fraction.bytes()).all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        let mut value: i128 = 0;
+        for b in integer.bytes() {
+            value = value.checked_mul(10)?.checked_add(i128::from(b - b'0'))?;
+        }
+        value = value.checked_mul(DECIMAL_SCALE)?;
+        let mut scale = DECIMAL_SCALE;
+        for b in fraction.bytes().take(DECIMAL_DIGITS) {
+            scale /= 10;
+            value = value.checked_add(i128::from(b - b'0') * scale)?;
+        }
+        Some(Decimal(if negative { -value } else { value }))
+    }
+    pub fn from_integer(i: i64) -> Decimal {
+        Decimal(i128::from(i) * DECIMAL_SCALE)
+    }
+    /// The decimal with the digits of the shortest representation of `f`.
+    pub fn from_f64(f: f64) -> Option<Decimal> {
+        if f.is_finite() {
+            Decimal::parse(&f.to_string())
+        } else {
+            None
+        }
+    }
+    /// The integer part of the decimal.
+    pub fn to_integer(self) -> Option<i64> {
+        i64::try_from(self.0 / DECIMAL_SCALE).ok()
+    }
+    pub fn to_f64(self) -> f64 {
+        self.to_string().parse().unwrap()
+    }
+    pub fn checked_add(self, other: Decimal) -> Option<Decimal> {
+        self.0.checked_add(other.0).map(Decimal)
+    }
+    pub fn checked_sub(self, other: Decimal) -> Option<Decimal> {
+        self.0.checked_sub(other.0).map(Decimal)
+    }
+    pub fn checked_mul(self, other: Decimal) -> Option<Decimal> {
+        // (a + b / s) * (c + d / s) * s = a * c * s + a * d + b * c + b * d / s
+        let (a, b) = (self.0 / DECIMAL_SCALE, self.0 % DECIMAL_SCALE);
+        let (c, d) = (other.0 / DECIMAL_SCALE, other.0 % DECIMAL_SCALE);
+        a.checked_mul(c)?
+            .checked_mul(DECIMAL_SCALE)?
+            .checked_add(a.checked_mul(d)?)?
+            .checked_add(b.checked_mul(c)?)?
+            .checked_add(b * d / DECIMAL_SCALE)
+            .map(Decimal)
+    }
+    /// Divide by `other`. The quotient is truncated. Dividing by zero fails.
+    pub fn checked_div(self, other: Decimal) -> Option<Decimal> {
+        if other.0 == 0 {
+            return None;
+        }
+        let (n, d) = (self.0.unsigned_abs(), other.0.unsigned_abs());
+        let mut quotient = n / d;
+        let mut remainder = n % d;
+        for _ in 0..DECIMAL_DIGITS {
+            // add the remainder ten times so that no value exceeds d
+            let (mut digit, mut next) = (0, 0);
+            for _ in 0..10 {
+                if next >= d - remainder {
+                    next -= d - remainder;
+                    digit += 1;
+                } else {
+                    next += remainder;
+                }
+            }
+            quotient = quotient.checked_mul(10)?.checked_add(digit)?;
+            remainder = next;
+        }
+        let value = i128::try_from(quotient).ok()?;
+        Some(Decimal(if (self.0 < 0) != (other.0 < 0) { -value } else { value }))
+    }
+    pub fn checked_neg(self) -> Option<Decimal> {
+        self.0.checked_neg().map(Decimal)
+    }
+    pub fn abs(self) -> Option<Decimal> {
+        self.0.checked_abs().map(Decimal)
+    }
+    pub fn floor(self) -> Option<Decimal> {
+        self.0.div_euclid(DECIMAL_SCALE).checked_mul(DECIMAL_SCALE).map(Decimal)
+    }
+    pub fn ceil(self) -> Option<Decimal> {
+        self.checked_neg()?.floor()?.checked_neg()
+    }
+    /// Round to the nearest integer. Halves are rounded up.
+    pub fn round(self) -> Option<Decimal> {
+        self.checked_add(Decimal(DECIMAL_SCALE / 2))?.floor()
+    }
+}
+
+impl fmt::Display for Decimal {
+    /// Write the decimal in canonical form, with at least one fraction digit.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let sign = if self.0 < 0 { "-" } else { "" };
+        let integer = (self.0 / DECIMAL_SCALE).unsigned_abs();
+        let fraction = format!("{:018}", (self.0 % DECIMAL_SCALE).unsigned_abs());
+        let fraction = fraction.trim_end_matches('0');
+        write!(f,
+               "{}{}.{}",
+               sign,
+               integer,
+               if fraction.is_empty() { "0" } else { fraction })
+    }
+}
+
+/// A number in one of the numeric XSD datatypes.
+#[derive(Clone,Copy,Debug,PartialEq,PartialOrd)]
+pub enum Numeric {
+    /// An `xsd:integer` or a datatype that is derived from it.
+    Integer(i64),
+    /// An `xsd:decimal`.
+    Decimal(Decimal),
+    /// An `xsd:float`.
+    Float(f64),
+    /// An `xsd:double`.
+    Double(f64),
+}
+
+impl Numeric {
+    /// Read a number from a literal.
+    pub fn from_literal(literal: LiteralRef) -> Option<Numeric> {
+        let lexical = literal.lexical.trim();
+        if is_integer_datatype(literal.datatype) {
+            lexical.trim_start_matches('+').parse().ok().map(Numeric::Integer)
+        } else if literal.datatype == constants::XSD_DECIMAL {
+            Decimal::parse(lexical).map(Numeric::Decimal)
+        } else if literal.datatype == constants::XSD_FLOAT {
+            parse_double(lexical).map(Numeric::Float)
+        } else if literal.datatype == constants::XSD_DOUBLE {
+            parse_double(lexical).map(Numeric::Double)
+        } else {
+            None
+        }
+    }
+    /// The number as a `f64`.
+    pub fn to_f64(self) -> f64 {
+        match self {
+            Numeric::Integer(i) => i as f64,
+            Numeric::Decimal(d) => d.to_f64(),
+            Numeric::Float(f) |
+            Numeric::Double(f) => f,
+        }
+    }
+    /// The number as a decimal, if it is an integer or a decimal.
+    pub fn to_decimal(self) -> Option<Decimal> {
+        match self {
+            Numeric::Integer(i) => Some(Decimal::from_integer(i)),
+            Numeric::Decimal(d) => Some(d),
+            _ => None,
+        }
+    }
+    fn rank(self) -> u8 {
+        match self {
+            Numeric::Integer(_) => 0,
+            Numeric::Decimal(_) => 1,
+            Numeric::Float(_) => 2,
+            Numeric::Double(_) => 3,
+        }
+    }
+    /// Create a float or a double, whichever is wider for `self` and
+    /// `other`.
+    fn promote(self, other: Numeric, value: f64) -> Numeric {
+        match self.rank().max(other.rank()) {
+            0..=2 => Numeric::Float(value),
+            _ => Numeric::Double(value),
+        }
+    }
+    /// Apply an operation to two numbers after promoting them to the same
+    /// type. Integers stay integers unless the integer operation has no
+    /// result, e.g. because it overflows. Then they become decimals.
+    pub fn apply<I, D, F>(self,
+                          other: Numeric,
+                          integer: I,
+                          decimal: D,
+                          float: F)
+                          -> Option<Numeric>
+        where I: Fn(i64, i64) -> Option<i64>,
+              D: Fn(Decimal, Decimal) -> Option<Decimal>,
+              F: Fn(f64, f64) -> f64
+    {
+        if let (Numeric::Integer(a), Numeric::Integer(b)) = (self, other) {
+            if let Some(i) = integer(a, b) {
+                return Some(Numeric::Integer(i));
+            }
+        }
+        match (self.to_decimal(), other.to_decimal()) {
+            (Some(a), Some(b)) => decimal(a, b).map(Numeric::Decimal),
+            _ => Some(self.promote(other, float(self.to_f64(), other.to_f64()))),
+        }
+    }
+    /// Compare two numbers. Integers and decimals are compared exactly.
+    pub fn compare(self, other: Numeric) -> Option<Ordering> {
+        match (self.to_decimal(), other.to_decimal()) {
+            (Some(a), Some(b)) => Some(a.cmp(&b)),
+            _ => self.to_f64().partial_cmp(&other.to_f64()),
+        }
+    }
+    /// Convert the number to a literal in canonical form.
+    pub fn to_literal(self) -> Literal {
+        match self {
+            Numeric::Integer(i) => Literal::new(i.to_string(), constants::XSD_INTEGER),
+            Numeric::Decimal(d) => Literal::new(d.to_string(), constants::XSD_DECIMAL),
+            Numeric::Float(f) => Literal::new(format_double(f), constants::XSD_FLOAT),
+            Numeric::Double(f) => Literal::new(format_double(f), constants::XSD_DOUBLE),
+        }
+    }
+}
+
+fn parse_double(lexical: &str) -> Option<f64> {
+    match lexical {
+        "INF" | "+INF" => Some(f64::INFINITY),
+        "-INF" => Some(f64::NEG_INFINITY),
+        "NaN" => Some(f64::NAN),
+        _ if lexical.contains(|c: char| c.is_ascii_alphabetic() && c != 'e' && c != 'E') => {
+            None
+        }
+        _ => lexical.parse().ok(),
+    }
+}
+
+fn format_double(f: f64) -> String {
+    if f.is_nan() {
+        String::from("NaN")
+    } else if f.is_infinite() {
+        String::from(if f > 0. { "INF" } else { "-INF" })
+    } else {
+        let s = format!("{:E}", f);
+        match s.find('E') {
+            Some(e) if !s[..e].contains('.') => format!("{}.0{}", &s[..e], &s[e..]),
+            _ => s,
+        }
+    }
+}
+
+#[test]
+fn test_numeric() {
+    let literal = |lexical, datatype| {
+        Numeric::from_literal(LiteralRef {
+            lexical: lexical,
+            datatype: datatype,
+            language: None,
+        })
+    };
+    assert_eq!(literal("+12", constants::XSD_INTEGER), Some(Numeric::Integer(12)));
+    let decimal = |lexical| Decimal::parse(lexical).unwrap();
+    assert_eq!(literal("1.5", constants::XSD_DECIMAL), Some(Numeric::Decimal(decimal("1.5"))));
+    assert_eq!(literal("1e3", constants::XSD_DECIMAL), None);
+    assert_eq!(literal("1e3", constants::XSD_DOUBLE), Some(Numeric::Double(1000.)));
+    assert_eq!(literal("x", constants::XSD_INTEGER), None);
+    assert_eq!(Numeric::Decimal(decimal("2")).to_literal().lexical, "2.0");
+    assert_eq!(Numeric::Double(1500.).to_literal().lexical, "1.5E3");
+    assert_eq!(Numeric::Double(2.).to_literal().lexical, "2.0E0");
+    let half = Numeric::Decimal(decimal("0.5"));
+    let sum = Numeric::Integer(1).apply(half, i64::checked_add, Decimal::checked_add, |a, b| a + b);
+    assert_eq!(sum, Some(Numeric::Decimal(decimal("1.5"))));
+    let product = Numeric::Integer(i64::MAX)
+        .apply(Numeric::Integer(2), i64::checked_mul, Decimal::checked_mul, |a, b| a * b);
+    assert_eq!(product.unwrap().to_literal().lexical, "18446744073709551614.0");
+}
+
+#[test]
+fn test_decimal() {
+    let decimal = |lexical| Decimal::parse(lexical).unwrap();
+    let format = |d: Option<Decimal>| d.unwrap().to_string();
+    assert_eq!(decimal("+01.50").to_string(), "1.5");
+    assert_eq!(decimal("-.5").to_string(), "-0.5");
+    assert_eq!(decimal("1.").to_string(), "1.0");
+    assert_eq!(Decimal::parse("."), None);
+    assert_eq!(Decimal::parse("1e3"), None);
+    assert_eq!(Decimal::parse("NaN"), None);
+    assert_eq!(format(decimal("0.1").checked_add(decimal("0.2"))), "0.3");
+    assert_eq!(format(decimal("0.3").checked_sub(decimal("0.1"))), "0.2");
+    assert_eq!(format(decimal("2.5").checked_mul(decimal("-0.4"))), "-1.0");
+    assert_eq!(format(decimal("10000000000").checked_mul(decimal("10000000000"))),
+               "100000000000000000000.0");
+    assert_eq!(format(decimal("1").checked_div(decimal("3"))), "0.333333333333333333");
+    assert_eq!(format(decimal("-7").checked_div(decimal("2"))), "-3.5");
+    assert_eq!(decimal("1").checked_div(decimal("0")), None);
+    assert_eq!(format(decimal("-1.5").floor()), "-2.0");
+    assert_eq!(format(decimal("-1.5").ceil()), "-1.0");
+    assert_eq!(format(decimal("-1.5").round()), "-1.0");
+    assert_eq!(format(decimal("2.5").round()), "3.0");
+    assert_eq!(Decimal::from_f64(0.1), Some(decimal("0.1")));
+    assert_eq!(decimal("0.1").to_f64(), 0.1);
+}
@@ -0,0 +1,66 @@
+//! Fixtures that are shared by the tests of several modules.
+
+use graph::{Graph, GraphWriter};
+use graphs::mem::MemGraph;
+use graphs::tel::{Graph128, Graph64, GraphCreator};
+use io::{write_ntriples, TurtleParser};
+use std::fmt::Display;
+
+/// A graph with literals, blank nodes, a list and a quoted triple.
+pub(crate) const TURTLE: &'static str = "@prefix : <http://a.example/> .
+:s :p \"é\"@fr, 1.5, [ :q _:b ] ; :r << _:b :p :o >> .
+_:b :p ( 1 2 ) .
+";
+
+/// A graph that the tests can read from Turtle.
+pub(crate) trait FromTurtle {
+    /// Read a graph from `turtle` with the base `http://a.example/`.
+    fn from_turtle(turtle: &str) -> Self;
+}
+
+fn parse<'a, W: 'a>(turtle: &'a str, writer: &'a mut W)
+    where W: GraphWriter<'a>
+{
+    for step in TurtleParser::new(turtle, "http://a.example/", writer).unwrap() {
+        step.unwrap();
+    }
+}
+
+impl FromTurtle for Graph64 {
+    fn from_turtle(turtle: &str) -> Graph64 {
+        let mut writer = GraphCreator::with_capacity(0);
+        parse(turtle, &mut writer);
+        writer.collect()
+    }
+}
+
+impl FromTurtle for Graph128 {
+    fn from_turtle(turtle: &str) -> Graph128 {
+        let mut writer = GraphCreator::with_capacity(0);
+        parse(turtle, &mut writer);
+        writer.collect()
+    }
+}
+
+impl FromTurtle for MemGraph {
+    fn from_turtle(turtle: &str) -> MemGraph {
+        let mut writer = MemGraph::new();
+        parse(turtle, &mut writer);
+        writer
+    }
+}
+
+/// Read a graph from `turtle` with the base `http://a.example/`.
+pub(crate) fn turtle_graph<G: FromTurtle>(turtle: &str) -> G {
+    G::from_turtle(turtle)
+}
+
+/// Write the triples of `graph` as N-Triples.
+pub(crate) fn ntriples<'g, G: 'g>(graph: &'g G) -> String
+    where G: Graph<'g>,
+          <G as Graph<'g>>::BlankNodePtr: Display
+{
+    let mut out = Vec::new();
+    write_ntriples(graph.iter(), graph, &mut out).unwrap();
+    String::from_utf8(out).unwrap()
+}